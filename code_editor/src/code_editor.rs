@@ -37,6 +37,8 @@ live_design! {
         delimiter_highlight: #f,
         error_decoration: #f00,
         warning_decoration: #0f0,
+        search_match_decoration: #ff0,
+        remote_selection_decoration: #4af,
     }
 
     DrawIndentGuide = {{DrawIndentGuide}} {
@@ -169,6 +171,20 @@ live_design! {
             }
         }
 
+        draw_popup_bg: {
+            draw_depth: 2.0,
+            color: #3a3a3a
+        }
+        draw_popup_selection: {
+            draw_depth: 2.5,
+            color: #08f8
+        }
+        draw_popup_text: {
+            draw_depth: 3.0,
+            text_style: <THEME_FONT_CODE> {},
+            color: #C0C0C0,
+        }
+
         animator: {
             blink = {
                 default: off
@@ -231,6 +247,9 @@ pub struct CodeEditor {
     #[live] draw_cursor: DrawColor,
     #[live] draw_cursor_bg: DrawColor,
     #[live] draw_bg: DrawColor,
+    #[live] draw_popup_bg: DrawColor,
+    #[live] draw_popup_selection: DrawColor,
+    #[live] draw_popup_text: DrawText,
     #[rust(KeepCursorInView::Off)] keep_cursor_in_view: KeepCursorInView,
     #[rust] last_cursor_screen_pos: Option<DVec2>,
 
@@ -248,6 +267,33 @@ pub struct CodeEditor {
     #[animator] animator: Animator,
 
     #[rust] blink_timer: Timer,
+
+    #[live(0.6)] hover_delay: f64,
+    #[rust] hover_timer: Timer,
+    #[rust] hover_abs: DVec2,
+    #[rust] popup: Option<Popup>,
+}
+
+// A popup that is shown on top of the text, such as the documentation of the symbol under the
+// mouse, or a list of completions for the word before the cursor.
+enum Popup {
+    Hover {
+        position: Position,
+        text: String,
+    },
+    Completions {
+        start: Position,
+        items: Vec<CompletionItem>,
+        selected: usize,
+    },
+}
+
+/// An item in the list of completions shown by the code editor.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CompletionItem {
+    pub label: String,
+    pub detail: Option<String>,
+    pub insert_text: String,
 }
 
 enum KeepCursorInView {
@@ -475,8 +521,12 @@ impl CodeEditor {
         self.draw_selection_layer(cx, session);
         self.draw_text_layer(cx, session);
         self.draw_indent_guide_layer(cx, session);
-        self.draw_decoration_layer(cx, session);
+        self.draw_decoration_layer(cx, session, &session.document().decorations());
+        self.draw_decoration_layer(cx, session, &session.search_matches());
+        self.draw_decoration_layer(cx, session, &session.document().remote_selections());
+        self.draw_decoration_layer(cx, session, &session.document().diagnostics());
         self.draw_selection_layer(cx, session);
+        self.draw_popup(cx, session);
 
         // Get the last added selection.
         // Get the normalized cursor position. To go from normalized to screen position, multiply by
@@ -510,6 +560,42 @@ impl CodeEditor {
         self.redraw(cx);
     }
 
+    /// Shows the documentation for the symbol at the given position, until the mouse moves away
+    /// or a key is pressed.
+    pub fn show_hover(&mut self, cx: &mut Cx, position: Position, text: String) {
+        if text.trim().is_empty() {
+            return;
+        }
+        self.popup = Some(Popup::Hover { position, text });
+        self.redraw(cx);
+    }
+
+    /// Shows a list of completions for the word that ends at the cursor. The list is narrowed
+    /// down as the word is typed, and closed when the cursor leaves the word.
+    pub fn show_completions(
+        &mut self,
+        cx: &mut Cx,
+        session: &Session,
+        items: Vec<CompletionItem>,
+    ) {
+        if items.is_empty() {
+            self.hide_popup(cx);
+            return;
+        }
+        self.popup = Some(Popup::Completions {
+            start: word_start(session, cursor_position(session)),
+            items,
+            selected: 0,
+        });
+        self.redraw(cx);
+    }
+
+    pub fn hide_popup(&mut self, cx: &mut Cx) {
+        if self.popup.take().is_some() {
+            self.redraw(cx);
+        }
+    }
+
     pub fn reset_font_size(&mut self) {
         self.draw_gutter.text_style.font_size = 9.0;
         self.draw_text.text_style.font_size = 9.0;
@@ -568,6 +654,20 @@ impl CodeEditor {
             }
             self.blink_timer = cx.start_timeout(self.blink_speed)
         }
+        if self.hover_timer.is_event(event).is_some() {
+            let ((position, _), is_in_gutter) = self.pick(session, self.hover_abs);
+            if !is_in_gutter {
+                actions.push(CodeEditorAction::HoverRequested(position));
+            }
+        }
+        if let Some(Popup::Hover { .. }) = self.popup {
+            if let Event::KeyDown(_) | Event::TextInput(_) | Event::Scroll(_) = event {
+                self.hide_popup(cx);
+            }
+        }
+        if self.handle_completion_keys(cx, event, session, &mut actions) {
+            return actions;
+        }
         let mut keyboard_moved_cursor = false;
         match event.hits(cx, self.scroll_bars.area()) {
             Hit::KeyFocusLost(_) => {
@@ -637,6 +737,19 @@ impl CodeEditor {
                     self.redraw(cx);
                 }
             }
+            Hit::KeyDown(KeyEvent {
+                key_code: KeyCode::Space,
+                modifiers: KeyModifiers { control: true, .. },
+                ..
+            }) => {
+                actions.push(CodeEditorAction::CompletionRequested(cursor_position(session)));
+            }
+            Hit::KeyDown(KeyEvent {
+                key_code: KeyCode::F12,
+                ..
+            }) => {
+                actions.push(CodeEditorAction::DefinitionRequested(cursor_position(session)));
+            }
             Hit::KeyDown(KeyEvent {
                 key_code: KeyCode::KeyA,
                 modifiers: KeyModifiers {control, logo, ..},
@@ -753,6 +866,14 @@ impl CodeEditor {
                 self.redraw(cx);
                 keyboard_moved_cursor = true;
                 actions.push(CodeEditorAction::TextDidChange);
+                // Member accesses and paths are where completions are most useful, so they are
+                // requested automatically there.
+                let position = cursor_position(session);
+                let text = session.document().as_text();
+                let before = &text.as_lines()[position.line_index][..position.byte_index];
+                if input == "." || input == ":" && before.ends_with("::") {
+                    actions.push(CodeEditorAction::CompletionRequested(position));
+                }
             }
             Hit::TextInput(TextInputEvent {
                 ref input,
@@ -860,10 +981,13 @@ impl CodeEditor {
                     KeyModifiers {
                         alt: false,
                         shift: false,
+                        control,
+                        logo,
                         ..
                     },
                 ..
             }) => {
+                self.popup = None;
                 self.animator_play(cx, id!(focus.on));
                 cx.set_key_focus(self.scroll_bars.area());
                 let ((cursor, affinity), is_in_gutter) = self.pick(session, abs);
@@ -881,6 +1005,9 @@ impl CodeEditor {
                         }
                     },
                 );
+                if (control || logo) && !is_in_gutter {
+                    actions.push(CodeEditorAction::DefinitionRequested(cursor));
+                }
                 self.reset_cursor_blinker(cx);
                 self.keep_cursor_in_view = KeepCursorInView::Always(abs, cx.new_next_frame());
                 self.redraw(cx);
@@ -921,8 +1048,20 @@ impl CodeEditor {
                 self.reset_cursor_blinker(cx);
                 self.keep_cursor_in_view = KeepCursorInView::Off;
             }
-            Hit::FingerHoverIn(_) | Hit::FingerHoverOver(_) => {
+            Hit::FingerHoverIn(FingerHoverEvent { abs, .. })
+            | Hit::FingerHoverOver(FingerHoverEvent { abs, .. }) => {
                 cx.set_cursor(MouseCursor::Text);
+                if (abs - self.hover_abs).length() > self.cell_size.x {
+                    if let Some(Popup::Hover { .. }) = self.popup {
+                        self.hide_popup(cx);
+                    }
+                    self.hover_abs = abs;
+                    cx.stop_timer(self.hover_timer);
+                    self.hover_timer = cx.start_timeout(self.hover_delay);
+                }
+            }
+            Hit::FingerHoverOut(_) => {
+                cx.stop_timer(self.hover_timer);
             }
             Hit::FingerDown(FingerDownEvent {
                 abs,
@@ -958,6 +1097,136 @@ impl CodeEditor {
         actions
     }
 
+    // Handles the keys that navigate the list of completions while it is shown. Returns `true` if
+    // the event was consumed.
+    fn handle_completion_keys(
+        &mut self,
+        cx: &mut Cx,
+        event: &Event,
+        session: &mut Session,
+        actions: &mut Vec<CodeEditorAction>,
+    ) -> bool {
+        let Some(Popup::Completions {
+            start,
+            items,
+            selected,
+        }) = &mut self.popup
+        else {
+            return false;
+        };
+        let Event::KeyDown(KeyEvent { key_code, .. }) = event else {
+            return false;
+        };
+        if !cx.has_key_focus(self.scroll_bars.area()) {
+            return false;
+        }
+        let matches = matching_completions(session, *start, items).unwrap_or_default();
+        match key_code {
+            KeyCode::ArrowUp => *selected = selected.saturating_sub(1),
+            KeyCode::ArrowDown => {
+                *selected = (*selected + 1).min(matches.len().saturating_sub(1))
+            }
+            KeyCode::ReturnKey | KeyCode::Tab => {
+                let Some(item) = matches.get(*selected) else {
+                    return false;
+                };
+                let text = item.insert_text.clone();
+                let start = *start;
+                let cursor = cursor_position(session);
+                session.set_selection(start, Affinity::Before, SelectionMode::Simple);
+                session.move_to(cursor, Affinity::After);
+                session.paste(text.into());
+                self.popup = None;
+                self.keep_cursor_in_view = KeepCursorInView::Once;
+                actions.push(CodeEditorAction::TextDidChange);
+            }
+            KeyCode::Escape => self.popup = None,
+            _ => return false,
+        }
+        self.redraw(cx);
+        true
+    }
+
+    fn draw_popup(&mut self, cx: &mut Cx2d, session: &Session) {
+        const MAX_ROW_COUNT: usize = 12;
+        const MAX_COLUMN_COUNT: usize = 100;
+
+        let (position, rows, selected_row) = match &mut self.popup {
+            None => return,
+            Some(Popup::Hover { position, text }) => (
+                *position,
+                text.lines()
+                    .take(MAX_ROW_COUNT)
+                    .map(|line| line.replace('\t', "    "))
+                    .collect::<Vec<_>>(),
+                None,
+            ),
+            Some(Popup::Completions {
+                start,
+                items,
+                selected,
+            }) => {
+                let matches = matching_completions(session, *start, items).unwrap_or_default();
+                if matches.is_empty() {
+                    self.popup = None;
+                    return;
+                }
+                *selected = (*selected).min(matches.len() - 1);
+                let first = selected.saturating_sub(MAX_ROW_COUNT - 1);
+                let rows = matches[first..]
+                    .iter()
+                    .take(MAX_ROW_COUNT)
+                    .map(|item| match &item.detail {
+                        Some(detail) => format!("{}  {}", item.label, detail),
+                        None => item.label.clone(),
+                    })
+                    .collect();
+                (*start, rows, Some(*selected - first))
+            }
+        };
+        let is_stale = session
+            .document()
+            .as_text()
+            .as_lines()
+            .get(position.line_index)
+            .map_or(true, |line| !line.is_char_boundary(position.byte_index));
+        if is_stale {
+            // The text changed underneath the popup.
+            self.popup = None;
+            return;
+        }
+        let rows: Vec<String> = rows
+            .into_iter()
+            .map(|row| row.chars().take(MAX_COLUMN_COUNT).collect())
+            .collect();
+        let column_count = rows.iter().map(|row| row.column_count()).max().unwrap_or(0);
+
+        let (x, y) = session
+            .layout()
+            .logical_to_normalized_position(position, Affinity::Before);
+        let padding = dvec2(4.0, 2.0);
+        let origin = dvec2(x, y + 1.0) * self.cell_size + self.viewport_rect.pos;
+        let size = dvec2(column_count as f64, rows.len() as f64) * self.cell_size + 2.0 * padding;
+        self.draw_popup_bg.draw_abs(cx, Rect { pos: origin, size });
+        if let Some(selected_row) = selected_row {
+            self.draw_popup_selection.draw_abs(
+                cx,
+                Rect {
+                    pos: origin + dvec2(0.0, padding.y + selected_row as f64 * self.cell_size.y),
+                    size: dvec2(size.x, self.cell_size.y),
+                },
+            );
+        }
+        self.draw_popup_text.text_style.font_size = self.draw_text.text_style.font_size;
+        for (index, row) in rows.iter().enumerate() {
+            self.draw_popup_text.draw_abs(
+                cx,
+                origin + padding + dvec2(0.0, index as f64 * self.cell_size.y),
+                row,
+            );
+        }
+    }
+
     fn draw_gutter(&mut self, cx: &mut Cx2d, session: &Session) {
         let mut line_index = self.line_start;
         let mut origin_y = session.layout().line(self.line_start).y();
@@ -1155,9 +1424,13 @@ impl CodeEditor {
         }
     }
 
-    fn draw_decoration_layer(&mut self, cx: &mut Cx2d<'_>, session: &Session) {
+    fn draw_decoration_layer(
+        &mut self,
+        cx: &mut Cx2d<'_>,
+        session: &Session,
+        decorations: &[Decoration],
+    ) {
         let mut active_decoration = None;
-        let decorations = session.document().decorations();
         let mut decorations = decorations.iter();
         while decorations.as_slice().first().map_or(false, |decoration| {
             decoration.end().line_index < self.line_start
@@ -1419,9 +1692,64 @@ impl CodeEditor {
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, DefaultNone)]
 pub enum CodeEditorAction {
     TextDidChange,
+    /// The mouse rested on the given position.
+    HoverRequested(Position),
+    /// Completions were asked for at the given position, either explicitly or by typing a `.` or
+    /// `::`.
+    CompletionRequested(Position),
+    /// The definition of the symbol at the given position was asked for, by pressing F12 or
+    /// clicking while holding control or command.
+    DefinitionRequested(Position),
     None
 }
 
+fn cursor_position(session: &Session) -> Position {
+    session.selections()[session.last_added_selection_index().unwrap()]
+        .cursor
+        .position
+}
+
+// Returns the start of the word that ends at the given position.
+fn word_start(session: &Session, position: Position) -> Position {
+    let text = session.document().as_text();
+    let line = &text.as_lines()[position.line_index];
+    let byte_index = line[..position.byte_index]
+        .char_indices()
+        .rev()
+        .take_while(|(_, char)| char.is_alphanumeric() || *char == '_')
+        .last()
+        .map_or(position.byte_index, |(byte_index, _)| byte_index);
+    Position {
+        line_index: position.line_index,
+        byte_index,
+    }
+}
+
+// Returns the completions that match the word between `start` and the cursor, with those that
+// start with the word first, or `None` if the cursor is no longer in that word.
+fn matching_completions<'a>(
+    session: &Session,
+    start: Position,
+    items: &'a [CompletionItem],
+) -> Option<Vec<&'a CompletionItem>> {
+    let cursor = cursor_position(session);
+    if cursor.line_index != start.line_index || cursor.byte_index < start.byte_index {
+        return None;
+    }
+    let text = session.document().as_text();
+    let word = text.as_lines()[cursor.line_index].get(start.byte_index..cursor.byte_index)?;
+    if !word.chars().all(|char| char.is_alphanumeric() || char == '_') {
+        return None;
+    }
+    let word = word.to_lowercase();
+    let mut matches: Vec<_> = items
+        .iter()
+        .filter(|item| item.label.to_lowercase().contains(&word))
+        .collect();
+    matches.sort_by_key(|item| !item.label.to_lowercase().starts_with(&word));
+    Some(matches)
+}
+
 struct DrawDecorationLayer<'a> {
     code_editor: &'a mut CodeEditor,
     active_decoration: Option<ActiveDecoration>,
@@ -1574,10 +1902,36 @@ impl<'a> DrawDecorationLayer<'a> {
                     decoration,
                     start_x,
                 });
+            } else if decoration.ty == DecorationType::RemoteSelection {
+                self.draw_remote_cursor(cx, line, origin_y, row_index, column_index);
             }
         }
     }
 
+    fn draw_remote_cursor(
+        &mut self,
+        cx: &mut Cx2d,
+        line: Line<'_>,
+        origin_y: f64,
+        row_index: usize,
+        column_index: usize,
+    ) {
+        let (x, y) = line.grid_to_normalized_position(row_index, column_index);
+        self.code_editor.draw_decoration.color =
+            self.code_editor.token_colors.remote_selection_decoration;
+        self.code_editor.draw_decoration.draw_abs(
+            cx,
+            Rect {
+                pos: DVec2 { x, y: origin_y + y } * self.code_editor.cell_size
+                    + self.code_editor.viewport_rect.pos,
+                size: DVec2 {
+                    x: 2.0,
+                    y: line.scale() * self.code_editor.cell_size.y,
+                },
+            },
+        );
+    }
+
     fn draw_decoration(
         &mut self,
         cx: &mut Cx2d,
@@ -1592,6 +1946,12 @@ impl<'a> DrawDecorationLayer<'a> {
             match self.active_decoration.as_mut().unwrap().decoration.ty {
                 DecorationType::Warning => self.code_editor.token_colors.warning_decoration,
                 DecorationType::Error => self.code_editor.token_colors.error_decoration,
+                DecorationType::SearchMatch => {
+                    self.code_editor.token_colors.search_match_decoration
+                }
+                DecorationType::RemoteSelection => {
+                    self.code_editor.token_colors.remote_selection_decoration
+                }
             };
 
         self.code_editor.draw_decoration.draw_abs(
@@ -1902,6 +2262,10 @@ struct TokenColors {
     error_decoration: Vec4,
     #[live]
     warning_decoration: Vec4,
+    #[live]
+    search_match_decoration: Vec4,
+    #[live]
+    remote_selection_decoration: Vec4,
 }
 
 #[derive(Live, LiveHook, LiveRegister)]
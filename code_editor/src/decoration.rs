@@ -7,6 +7,8 @@ use {
 pub enum DecorationType {
     Error,
     Warning,
+    SearchMatch,
+    RemoteSelection,
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
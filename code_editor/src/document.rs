@@ -1,11 +1,11 @@
 use {
     crate::{
         char::CharExt,
-        decoration::{Decoration, DecorationSet},
+        decoration::{Decoration, DecorationSet, DecorationType},
         history::{EditKind, History},
         inlays::{BlockInlay, InlineInlay},
         iter::IteratorExt,
-        selection::SelectionSet,
+        selection::{Selection, SelectionSet},
         session::SessionId,
         settings::Settings,
         str::StrExt,
@@ -17,7 +17,7 @@ use {
         cell::{Ref, RefCell},
         cmp::Ordering,
         collections::HashMap,
-        iter,
+        iter, mem,
         ops::Range,
         rc::Rc,
         sync::mpsc::Sender,
@@ -29,20 +29,44 @@ pub struct Document(Rc<DocumentInner>);
 
 impl Document {
     pub fn new(text: Text, decorations: DecorationSet) -> Self {
+        let line_count = text.as_lines().len();
+        Self::with_tokenizer(text, decorations, Tokenizer::new(line_count))
+    }
+
+    /// Creates a document that is highlighted based on the file extension of `path`.
+    pub fn for_path(path: &str, text: Text, decorations: DecorationSet) -> Self {
+        let line_count = text.as_lines().len();
+        Self::with_tokenizer(text, decorations, Tokenizer::for_path(path, line_count))
+    }
+
+    pub fn with_tokenizer(text: Text, decorations: DecorationSet, tokenizer: Tokenizer) -> Self {
+        Self::with_history(History::from(text), decorations, tokenizer)
+    }
+
+    /// Creates a document from an existing history, such as one restored from a journal.
+    pub fn with_history(
+        history: History,
+        decorations: DecorationSet,
+        tokenizer: Tokenizer,
+    ) -> Self {
+        let text = history.as_text();
         let line_count = text.as_lines().len();
         let tokens: Vec<_> = (0..line_count)
             .map(|line| tokenize(&text.as_lines()[line]).collect::<Vec<_>>())
             .collect();
         let inner = Self(Rc::new(DocumentInner {
-            history: RefCell::new(History::from(text)),
+            history: RefCell::new(history),
             layout: RefCell::new(DocumentLayout {
                 indent_state: (0..line_count).map(|_| None).collect(),
                 tokens,
                 inline_inlays: (0..line_count).map(|_| Vec::new()).collect(),
                 block_inlays: Vec::new(),
             }),
-            tokenizer: RefCell::new(Tokenizer::new(line_count)),
+            tokenizer: RefCell::new(tokenizer),
             decorations: RefCell::new(decorations),
+            remote_selections: RefCell::new(DecorationSet::new()),
+            diagnostics: RefCell::new(DecorationSet::new()),
+            recorded_changes: RefCell::new(None),
             edit_senders: RefCell::new(HashMap::new()),
         }));
         inner.update_indent_state();
@@ -57,6 +81,10 @@ impl Document {
         Ref::map(self.0.history.borrow(), |history| history.as_text())
     }
 
+    pub fn history(&self) -> Ref<'_, History> {
+        self.0.history.borrow()
+    }
+
     pub fn layout(&self) -> Ref<'_, DocumentLayout> {
         self.0.layout.borrow()
     }
@@ -73,6 +101,34 @@ impl Document {
         kind: EditKind,
         selections: &SelectionSet,
         settings: &Settings,
+        f: impl FnMut(Editor<'_>, Position, Length),
+    ) {
+        self.edit_selections_with_indent(
+            session_id,
+            kind,
+            selections,
+            Some(settings.tab_column_count),
+            f,
+        );
+    }
+
+    /// Like `edit_selections`, but inserts text exactly as given, without reindenting lines.
+    pub fn edit_selections_verbatim(
+        &self,
+        session_id: SessionId,
+        kind: EditKind,
+        selections: &SelectionSet,
+        f: impl FnMut(Editor<'_>, Position, Length),
+    ) {
+        self.edit_selections_with_indent(session_id, kind, selections, None, f);
+    }
+
+    fn edit_selections_with_indent(
+        &self,
+        session_id: SessionId,
+        kind: EditKind,
+        selections: &SelectionSet,
+        tab_column_count: Option<usize>,
         mut f: impl FnMut(Editor<'_>, Position, Length),
     ) {
         let mut history = self.0.history.borrow_mut();
@@ -134,8 +190,10 @@ impl Document {
             prev_edit_start = edit_start;
         }
         drop(history);
-        self.autoindent(&line_ranges, settings.tab_column_count, &mut edits);
-        self.update_after_edit(session_id, None, &edits);
+        if let Some(tab_column_count) = tab_column_count {
+            self.autoindent(&line_ranges, tab_column_count, &mut edits);
+        }
+        self.update_after_edit(Some(session_id), None, &edits);
     }
 
     pub fn edit_linewise(
@@ -171,7 +229,76 @@ impl Document {
             }
         }
         drop(history);
-        self.update_after_edit(origin_id, None, &edits);
+        self.update_after_edit(Some(origin_id), None, &edits);
+    }
+
+    pub fn remote_selections(&self) -> Ref<'_, [Decoration]> {
+        Ref::map(self.0.remote_selections.borrow(), |selections| {
+            selections.as_decorations()
+        })
+    }
+
+    /// Replaces the selections that are shown for the participant with the given id.
+    pub fn set_remote_selections(&self, participant_id: usize, selections: &[Selection]) {
+        let mut remote_selections = self.0.remote_selections.borrow_mut();
+        let mut new_selections = DecorationSet::new();
+        for decoration in remote_selections.iter() {
+            if decoration.id != participant_id {
+                new_selections.add_decoration(*decoration);
+            }
+        }
+        for selection in selections {
+            new_selections.add_decoration(Decoration::new(
+                participant_id,
+                selection.start(),
+                selection.end(),
+                DecorationType::RemoteSelection,
+            ));
+        }
+        *remote_selections = new_selections;
+    }
+
+    pub fn diagnostics(&self) -> Ref<'_, [Decoration]> {
+        Ref::map(self.0.diagnostics.borrow(), |diagnostics| {
+            diagnostics.as_decorations()
+        })
+    }
+
+    /// Replaces the diagnostics that are shown for this document, such as those reported by a
+    /// language server. These are kept apart from the other decorations, so that they can be
+    /// replaced as a whole without affecting those.
+    pub fn set_diagnostics(&self, diagnostics: DecorationSet) {
+        *self.0.diagnostics.borrow_mut() = diagnostics;
+    }
+
+    /// Starts recording the changes that are made by the sessions of this document, so they can
+    /// be sent to the other participants in a collaboration session.
+    pub fn start_recording_changes(&self) {
+        self.0.recorded_changes.borrow_mut().get_or_insert_with(Vec::new);
+    }
+
+    pub fn take_recorded_changes(&self) -> Vec<Change> {
+        self.0
+            .recorded_changes
+            .borrow_mut()
+            .as_mut()
+            .map_or_else(Vec::new, mem::take)
+    }
+
+    /// Applies changes that were made by another participant in a collaboration session. These
+    /// changes are not recorded, and cannot be undone.
+    pub fn apply_remote_changes(&self, changes: Vec<Change>) {
+        let mut edits = Vec::with_capacity(changes.len());
+        let mut history = self.0.history.borrow_mut();
+        for change in changes {
+            history.apply_remote_change(change.clone());
+            edits.push(Edit {
+                change,
+                drift: Drift::Before,
+            });
+        }
+        drop(history);
+        self.update_after_edit(None, None, &edits);
     }
 
     pub fn add_decoration(&mut self, decoration: Decoration) {
@@ -323,7 +450,7 @@ impl Document {
         let mut changes = Vec::new();
         let selections = self.0.history.borrow_mut().undo(selections, &mut changes);
         if let Some(selections) = selections {
-            self.update_after_edit(origin_id, Some(selections), &changes);
+            self.update_after_edit(Some(origin_id), Some(selections), &changes);
             true
         } else {
             false
@@ -334,16 +461,18 @@ impl Document {
         let mut changes = Vec::new();
         let selections = self.0.history.borrow_mut().redo(selections, &mut changes);
         if let Some(selections) = selections {
-            self.update_after_edit(origin_id, Some(selections), &changes);
+            self.update_after_edit(Some(origin_id), Some(selections), &changes);
             true
         } else {
             false
         }
     }
 
+    // A missing `origin_id` means the edits were made by another participant in a collaboration
+    // session, rather than by one of our own sessions.
     fn update_after_edit(
         &self,
-        origin_id: SessionId,
+        origin_id: Option<SessionId>,
         selections: Option<SelectionSet>,
         edits: &[Edit],
     ) {
@@ -382,12 +511,23 @@ impl Document {
             &mut self.0.layout.borrow_mut().tokens,
         );
         let mut decorations = self.0.decorations.borrow_mut();
+        let mut remote_selections = self.0.remote_selections.borrow_mut();
+        let mut diagnostics = self.0.diagnostics.borrow_mut();
         for edit in edits {
             decorations.apply_edit(edit);
+            remote_selections.apply_edit(edit);
+            diagnostics.apply_edit(edit);
         }
         drop(decorations);
+        drop(remote_selections);
+        drop(diagnostics);
+        if origin_id.is_some() {
+            if let Some(recorded_changes) = &mut *self.0.recorded_changes.borrow_mut() {
+                recorded_changes.extend(edits.iter().map(|edit| edit.change.clone()));
+            }
+        }
         for (&session_id, edit_sender) in &*self.0.edit_senders.borrow() {
-            if session_id == origin_id {
+            if Some(session_id) == origin_id {
                 edit_sender
                     .send((selections.clone(), edits.to_vec()))
                     .unwrap();
@@ -684,6 +824,9 @@ struct DocumentInner {
     layout: RefCell<DocumentLayout>,
     tokenizer: RefCell<Tokenizer>,
     decorations: RefCell<DecorationSet>,
+    remote_selections: RefCell<DecorationSet>,
+    diagnostics: RefCell<DecorationSet>,
+    recorded_changes: RefCell<Option<Vec<Change>>>,
     edit_senders: RefCell<HashMap<SessionId, Sender<(Option<SelectionSet>, Vec<Edit>)>>>,
 }
 
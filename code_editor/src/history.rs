@@ -1,7 +1,11 @@
-use crate::{
-    selection::SelectionSet,
-    session::SessionId,
-    text::{Edit, Text},
+use {
+    crate::{
+        collab,
+        selection::SelectionSet,
+        session::SessionId,
+        text::{Change, Drift, Edit, Text},
+    },
+    makepad_widgets::makepad_micro_serde::*,
 };
 
 #[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
@@ -89,11 +93,43 @@ impl History {
         }
     }
 
+    /// Applies a change that was made by another participant in a collaboration session.
+    ///
+    /// The change is not undoable. Instead, the edits on the undo and redo stacks are transformed
+    /// so that they still apply to the changed text.
+    pub fn apply_remote_change(&mut self, change: Change) {
+        self.undo_stack.transform(change.clone());
+        self.redo_stack.transform(change.clone());
+        self.text.apply_change(change);
+        self.current_desc = None;
+    }
+
     pub fn into_text(self) -> Text {
         self.text
     }
 }
 
+// The current group is not serialized, because the session that it belongs to does not outlive
+// the process. A deserialized history always starts a new group on the next edit.
+impl SerBin for History {
+    fn ser_bin(&self, s: &mut Vec<u8>) {
+        self.text.ser_bin(s);
+        self.undo_stack.ser_bin(s);
+        self.redo_stack.ser_bin(s);
+    }
+}
+
+impl DeBin for History {
+    fn de_bin(o: &mut usize, d: &[u8]) -> Result<Self, DeBinErr> {
+        Ok(Self {
+            text: DeBin::de_bin(o, d)?,
+            current_desc: None,
+            undo_stack: DeBin::de_bin(o, d)?,
+            redo_stack: DeBin::de_bin(o, d)?,
+        })
+    }
+}
+
 impl From<Text> for History {
     fn from(text: Text) -> Self {
         Self {
@@ -134,7 +170,7 @@ impl GroupDesc {
     }
 }
 
-#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, SerBin, DeBin)]
 struct Stack {
     groups: Vec<Group>,
     edits: Vec<Edit>,
@@ -166,9 +202,57 @@ impl Stack {
         self.groups.clear();
         self.edits.clear();
     }
+
+    // The edits on a stack are applied from last to first, starting from the current text. To
+    // transform them against a change to the current text, we walk the stack in that order, and
+    // move the change back in time past each edit as we go. If an edit cannot be transformed, the
+    // stack no longer applies to the text, and is cleared.
+    fn transform(&mut self, change: Change) {
+        let mut changes = vec![change];
+        let mut reversed_edits = Vec::with_capacity(self.edits.len());
+        let mut edit_end = self.edits.len();
+        let mut group_edit_ends = Vec::with_capacity(self.groups.len());
+        let mut is_invalid = false;
+        'groups: for group in self.groups.iter_mut().rev() {
+            for edit in self.edits[group.edit_start..edit_end].iter().rev() {
+                let Ok((transformed_changes, next_changes)) =
+                    collab::transform(vec![edit.change.clone()], changes)
+                else {
+                    is_invalid = true;
+                    break 'groups;
+                };
+                reversed_edits.extend(transformed_changes.into_iter().map(|change| Edit {
+                    change,
+                    drift: edit.drift,
+                }));
+                changes = next_changes;
+            }
+            for change in &changes {
+                group.selections.apply_edit(
+                    &Edit {
+                        change: change.clone(),
+                        drift: Drift::Before,
+                    },
+                    None,
+                );
+            }
+            group_edit_ends.push(reversed_edits.len());
+            edit_end = group.edit_start;
+        }
+        if is_invalid {
+            self.clear();
+            return;
+        }
+        let edit_count = reversed_edits.len();
+        for (group, reversed_edit_end) in self.groups.iter_mut().rev().zip(group_edit_ends) {
+            group.edit_start = edit_count - reversed_edit_end;
+        }
+        reversed_edits.reverse();
+        self.edits = reversed_edits;
+    }
 }
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, SerBin, DeBin)]
 struct Group {
     selections: SelectionSet,
     edit_start: usize,
@@ -0,0 +1,153 @@
+use {
+    crate::{history::History, selection::SelectionSet, text::Text},
+    makepad_widgets::makepad_micro_serde::*,
+};
+
+/// A snapshot of the history of a document, together with a hash of the text that was last saved
+/// to disk. Journals are written next to the workspace so that undo history survives restarts, and
+/// so that edits that were never saved can be recovered after a crash.
+#[derive(Clone, Debug, SerBin, DeBin)]
+pub struct Journal {
+    saved_hash: u64,
+    history: History,
+}
+
+impl Journal {
+    pub fn new(saved_hash: u64, history: History) -> Self {
+        Self {
+            saved_hash,
+            history,
+        }
+    }
+
+    pub fn saved_hash(&self) -> u64 {
+        self.saved_hash
+    }
+
+    pub fn as_text(&self) -> &Text {
+        self.history.as_text()
+    }
+
+    /// Returns `true` if the journaled text contains edits that were never saved.
+    pub fn is_dirty(&self) -> bool {
+        content_hash(self.history.as_text()) != self.saved_hash
+    }
+
+    /// Returns `true` if this journal was written for a file with the given contents on disk.
+    pub fn is_based_on(&self, text: &Text) -> bool {
+        content_hash(text) == self.saved_hash
+    }
+
+    /// Turns this journal into the history for a file that has `text` on disk.
+    ///
+    /// If the journal does not belong to `text`, because the file changed on disk since the
+    /// journal was written, it is discarded and a fresh history is returned instead. Otherwise, if
+    /// `recover` is `true`, any unsaved edits are kept. If not, the history is rewound to the saved
+    /// text, leaving the unsaved edits on the redo stack.
+    pub fn into_history(self, text: &Text, recover: bool) -> History {
+        if !self.is_based_on(text) {
+            return History::from(text.clone());
+        }
+        if recover {
+            return self.history;
+        }
+        let mut history = self.history;
+        let mut edits = Vec::new();
+        while history.as_text() != text {
+            edits.clear();
+            if history.undo(&SelectionSet::new(), &mut edits).is_none() {
+                return History::from(text.clone());
+            }
+        }
+        history
+    }
+}
+
+/// A 64-bit FNV-1a hash of `text`, used to recognize whether a file changed since it was journaled.
+pub fn content_hash(text: &Text) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let mut hash = OFFSET_BASIS;
+    for (index, line) in text.as_lines().iter().enumerate() {
+        if index > 0 {
+            hash = (hash ^ u64::from(b'\n')).wrapping_mul(PRIME);
+        }
+        for byte in line.bytes() {
+            hash = (hash ^ u64::from(byte)).wrapping_mul(PRIME);
+        }
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{decoration::DecorationSet, Document, Session},
+    };
+
+    /// Opens `saved`, types `typed` at the start, and returns the resulting journal.
+    fn journal(saved: &str, typed: &str) -> Journal {
+        let text = Text::from(saved);
+        let session = Session::new(Document::new(text.clone(), DecorationSet::new()));
+        session.insert(Text::from(typed));
+        let history = session.document().history().clone();
+        Journal::new(content_hash(&text), history)
+    }
+
+    #[test]
+    fn ser_bin_round_trip() {
+        let journal = journal("fn main() {}\n", "// edited\n");
+        let restored = Journal::deserialize_bin(&journal.serialize_bin()).unwrap();
+        assert_eq!(restored.saved_hash(), journal.saved_hash());
+        assert_eq!(restored.as_text(), journal.as_text());
+        assert!(restored.is_dirty());
+        // The undo stack survives, so the restored history can still be rewound.
+        let saved = Text::from("fn main() {}\n");
+        assert_eq!(restored.into_history(&saved, false).as_text(), &saved);
+    }
+
+    #[test]
+    fn dirty_only_with_unsaved_edits() {
+        let saved = Text::from("a\nb");
+        assert!(journal("a\nb", "x").is_dirty());
+        let clean = Journal::new(content_hash(&saved), History::from(saved.clone()));
+        assert!(!clean.is_dirty());
+        assert!(clean.is_based_on(&saved));
+        assert_eq!(content_hash(&Text::from("a\nb")), content_hash(&saved));
+        assert_ne!(content_hash(&Text::from("ab")), content_hash(&saved));
+    }
+
+    #[test]
+    fn restores_history_for_matching_text() {
+        let saved = Text::from("hello");
+        let mut history = journal("hello", "well, ").into_history(&saved, false);
+        // The unsaved edit is not applied, but can be redone.
+        assert_eq!(history.as_text(), &saved);
+        let mut edits = Vec::new();
+        assert!(history.redo(&SelectionSet::new(), &mut edits).is_some());
+        assert_eq!(history.as_text().to_string(), "well, hello");
+    }
+
+    #[test]
+    fn recovers_unsaved_edits_for_matching_text() {
+        let saved = Text::from("hello");
+        let history = journal("hello", "well, ").into_history(&saved, true);
+        assert_eq!(history.as_text().to_string(), "well, hello");
+    }
+
+    #[test]
+    fn discards_journal_for_changed_text() {
+        // The file changed on disk after the journal was written, so neither the unsaved edits
+        // nor the undo history may be applied to it.
+        let changed = Text::from("hello, changed on disk");
+        for recover in [false, true] {
+            let mut history = journal("hello", "well, ").into_history(&changed, recover);
+            assert_eq!(history.as_text(), &changed);
+            let mut edits = Vec::new();
+            assert!(history.undo(&SelectionSet::new(), &mut edits).is_none());
+            assert!(history.redo(&SelectionSet::new(), &mut edits).is_none());
+        }
+    }
+}
@@ -1,4 +1,5 @@
 pub use makepad_widgets;
+pub use makepad_collab::{self, collab, text};
 use makepad_widgets::*;
 
 pub mod char;
@@ -8,12 +9,14 @@ pub mod document;
 pub mod history;
 pub mod inlays;
 pub mod iter;
+pub mod journal;
 pub mod layout;
+pub mod regex;
+pub mod search;
 pub mod selection;
 pub mod session;
 pub mod settings;
 pub mod str;
-pub mod text;
 pub mod token;
 pub mod tokenizer;
 pub mod widgets;
@@ -26,4 +29,7 @@ pub use self::{
 
 pub fn live_design(cx: &mut Cx) {
     crate::code_editor::live_design(cx);
+    cx.set_global(makepad_widgets::markdown::CodeHighlighter(
+        crate::tokenizer::highlight_code,
+    ));
 }
@@ -0,0 +1,770 @@
+use std::{error, fmt, ops::Range};
+
+/// A small regular expression engine for searching within a single line of text.
+///
+/// Supports literals, `.`, character classes (`[a-z]`, `[^...]`, `\d`, `\w`, `\s` and their
+/// negations), the anchors `^`, `$`, `\b` and `\B`, capturing and non-capturing (`(?:...)`)
+/// groups, alternation, and greedy and lazy quantifiers (`*`, `+`, `?`, `{n}`, `{n,}`, `{n,m}`).
+///
+/// Patterns are compiled to a small program which is run by a backtracking matcher that never
+/// visits the same instruction at the same position twice, so matching takes time linear in the
+/// length of the line times the length of the program.
+#[derive(Clone, Debug)]
+pub struct Regex {
+    program: Vec<Inst>,
+    group_count: usize,
+    case_insensitive: bool,
+}
+
+impl Regex {
+    pub fn new(pattern: &str, case_insensitive: bool) -> Result<Self, RegexError> {
+        let mut parser = Parser {
+            chars: pattern.chars().collect(),
+            index: 0,
+            group_count: 0,
+        };
+        let node = parser.parse_alternation()?;
+        if parser.index < parser.chars.len() {
+            // The only way for the parser to stop early is at an unmatched `)`.
+            return Err(RegexError::UnmatchedParen(parser.index));
+        }
+        Ok(Self::compile(&node, parser.group_count, case_insensitive))
+    }
+
+    /// Creates a regex that matches `string` literally.
+    pub fn literal(string: &str, case_insensitive: bool) -> Self {
+        let node = Node::Concat(string.chars().map(Node::Char).collect());
+        Self::compile(&node, 0, case_insensitive)
+    }
+
+    /// The number of capture groups, not counting the implicit group for the whole match.
+    pub fn group_count(&self) -> usize {
+        self.group_count
+    }
+
+    /// Returns the byte range of the leftmost match that starts at or after `start`.
+    pub fn find_at(&self, text: &str, start: usize) -> Option<Range<usize>> {
+        self.find_at_with(text, start, &mut Cache::default())
+    }
+
+    /// Like `find_at`, but reuses the scratch space in `cache`. Repeated searches in the same
+    /// line should share a cache so it is only allocated once.
+    pub fn find_at_with(&self, text: &str, start: usize, cache: &mut Cache) -> Option<Range<usize>> {
+        self.captures_at_with(text, start, cache)
+            .and_then(|captures| captures.into_iter().next().flatten())
+    }
+
+    /// Like `find_at`, but also returns the byte ranges of all capture groups. The first entry
+    /// is the whole match.
+    pub fn captures_at(&self, text: &str, start: usize) -> Option<Vec<Option<Range<usize>>>> {
+        self.captures_at_with(text, start, &mut Cache::default())
+    }
+
+    fn captures_at_with(
+        &self,
+        text: &str,
+        start: usize,
+        cache: &mut Cache,
+    ) -> Option<Vec<Option<Range<usize>>>> {
+        let slots = self.exec(text, start, cache)?;
+        Some(
+            slots
+                .chunks(2)
+                .map(|slots| match (slots[0], slots[1]) {
+                    (Some(start), Some(end)) => Some(start..end),
+                    _ => None,
+                })
+                .collect(),
+        )
+    }
+
+    /// Expands `$n` and `${n}` in `replacement` to the text of the corresponding capture group.
+    /// `$$` is a literal dollar sign.
+    pub fn expand(
+        &self,
+        text: &str,
+        captures: &[Option<Range<usize>>],
+        replacement: &str,
+    ) -> String {
+        let mut string = String::new();
+        let mut chars = replacement.chars().peekable();
+        while let Some(char) = chars.next() {
+            if char != '$' {
+                string.push(char);
+                continue;
+            }
+            if chars.next_if_eq(&'$').is_some() {
+                string.push('$');
+                continue;
+            }
+            let braced = chars.next_if_eq(&'{').is_some();
+            let mut digits = String::new();
+            while let Some(digit) = chars.next_if(|char| char.is_ascii_digit()) {
+                digits.push(digit);
+            }
+            let closed = !braced || chars.next_if_eq(&'}').is_some();
+            match digits.parse::<usize>() {
+                Ok(index) if closed => {
+                    if let Some(Some(range)) = captures.get(index) {
+                        string.push_str(&text[range.clone()]);
+                    }
+                }
+                _ => {
+                    // Not a group reference, so keep it as written.
+                    string.push('$');
+                    if braced {
+                        string.push('{');
+                    }
+                    string.push_str(&digits);
+                }
+            }
+        }
+        string
+    }
+
+    fn compile(node: &Node, group_count: usize, case_insensitive: bool) -> Self {
+        // An unanchored search is a lazy `.*` in front of the pattern.
+        let mut program = vec![Inst::Split(3, 1), Inst::Any, Inst::Jmp(0), Inst::Save(0)];
+        compile_node(node, &mut program);
+        program.push(Inst::Save(1));
+        program.push(Inst::Match);
+        Self {
+            program,
+            group_count,
+            case_insensitive,
+        }
+    }
+
+    fn exec(&self, text: &str, start: usize, cache: &mut Cache) -> Option<Vec<Option<usize>>> {
+        if start > text.len() {
+            return None;
+        }
+        // Only positions at or after `start` are ever visited, so only those need clearing.
+        let stride = self.program.len();
+        let end = stride * (text.len() + 1);
+        if cache.visited.len() < end {
+            cache.visited.resize(end, false);
+        }
+        let visited = &mut cache.visited[..end];
+        visited[stride * start..].fill(false);
+        let mut slots = vec![None; 2 * (self.group_count + 1)];
+        let mut stack = vec![Job::Thread { pc: 0, pos: start }];
+        while let Some(job) = stack.pop() {
+            let (mut pc, mut pos) = match job {
+                Job::Thread { pc, pos } => (pc, pos),
+                Job::Restore { slot, value } => {
+                    slots[slot] = value;
+                    continue;
+                }
+            };
+            loop {
+                let visited = &mut visited[pos * stride + pc];
+                if *visited {
+                    break;
+                }
+                *visited = true;
+                match self.program[pc] {
+                    Inst::Char(expected) => match text[pos..].chars().next() {
+                        Some(char) if self.chars_eq(char, expected) => {
+                            pc += 1;
+                            pos += char.len_utf8();
+                        }
+                        _ => break,
+                    },
+                    Inst::Any => match text[pos..].chars().next() {
+                        Some(char) => {
+                            pc += 1;
+                            pos += char.len_utf8();
+                        }
+                        None => break,
+                    },
+                    Inst::Class(ref class) => match text[pos..].chars().next() {
+                        Some(char) if class.matches(char, self.case_insensitive) => {
+                            pc += 1;
+                            pos += char.len_utf8();
+                        }
+                        _ => break,
+                    },
+                    Inst::Assert(assertion) => {
+                        if !assertion.holds(text, pos) {
+                            break;
+                        }
+                        pc += 1;
+                    }
+                    Inst::Split(preferred, other) => {
+                        stack.push(Job::Thread { pc: other, pos });
+                        pc = preferred;
+                    }
+                    Inst::Jmp(target) => pc = target,
+                    Inst::Save(slot) => {
+                        stack.push(Job::Restore {
+                            slot,
+                            value: slots[slot],
+                        });
+                        slots[slot] = Some(pos);
+                        pc += 1;
+                    }
+                    Inst::Match => return Some(slots),
+                }
+            }
+        }
+        None
+    }
+
+    fn chars_eq(&self, char: char, expected: char) -> bool {
+        char == expected || self.case_insensitive && char.to_lowercase().eq(expected.to_lowercase())
+    }
+}
+
+/// Scratch space for matching, see `Regex::find_at_with`.
+#[derive(Clone, Debug, Default)]
+pub struct Cache {
+    visited: Vec<bool>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum RegexError {
+    UnexpectedEnd,
+    UnmatchedParen(usize),
+    UnmatchedBracket(usize),
+    NothingToRepeat(usize),
+    InvalidRange(usize),
+    InvalidEscape(usize),
+    RepeatTooLarge(usize),
+}
+
+impl fmt::Display for RegexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd => write!(f, "unexpected end of pattern"),
+            Self::UnmatchedParen(index) => write!(f, "unmatched parenthesis at {}", index),
+            Self::UnmatchedBracket(index) => write!(f, "unmatched bracket at {}", index),
+            Self::NothingToRepeat(index) => write!(f, "nothing to repeat at {}", index),
+            Self::InvalidRange(index) => write!(f, "invalid character range at {}", index),
+            Self::InvalidEscape(index) => write!(f, "invalid escape sequence at {}", index),
+            Self::RepeatTooLarge(index) => write!(f, "repetition count too large at {}", index),
+        }
+    }
+}
+
+impl error::Error for RegexError {}
+
+const MAX_REPEAT: usize = 1000;
+
+#[derive(Clone, Debug)]
+enum Node {
+    Empty,
+    Char(char),
+    Any,
+    Class(Class),
+    Assert(Assertion),
+    Group(Box<Node>, Option<usize>),
+    Concat(Vec<Node>),
+    Alternate(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+    },
+}
+
+#[derive(Clone, Debug)]
+enum Inst {
+    Char(char),
+    Any,
+    Class(Class),
+    Assert(Assertion),
+    Split(usize, usize),
+    Jmp(usize),
+    Save(usize),
+    Match,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Job {
+    Thread { pc: usize, pos: usize },
+    Restore { slot: usize, value: Option<usize> },
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Assertion {
+    Start,
+    End,
+    WordBoundary,
+    NotWordBoundary,
+}
+
+impl Assertion {
+    fn holds(self, text: &str, pos: usize) -> bool {
+        match self {
+            Self::Start => pos == 0,
+            Self::End => pos == text.len(),
+            Self::WordBoundary => is_word_boundary(text, pos),
+            Self::NotWordBoundary => !is_word_boundary(text, pos),
+        }
+    }
+}
+
+/// Whether exactly one of the characters around the byte offset `pos` is a word character.
+pub fn is_word_boundary(text: &str, pos: usize) -> bool {
+    let before = text[..pos].chars().next_back().is_some_and(is_word_char);
+    let after = text[pos..].chars().next().is_some_and(is_word_char);
+    before != after
+}
+
+pub fn is_word_char(char: char) -> bool {
+    char.is_alphanumeric() || char == '_'
+}
+
+#[derive(Clone, Debug)]
+struct Class {
+    negated: bool,
+    items: Vec<ClassItem>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum ClassItem {
+    Range(char, char),
+    Digit(bool),
+    Word(bool),
+    Space(bool),
+}
+
+impl Class {
+    fn matches(&self, char: char, case_insensitive: bool) -> bool {
+        let matches = self.items.iter().any(|item| {
+            item.matches(char)
+                || case_insensitive
+                    && (char.to_lowercase().any(|char| item.matches(char))
+                        || char.to_uppercase().any(|char| item.matches(char)))
+        });
+        matches != self.negated
+    }
+}
+
+impl ClassItem {
+    fn matches(self, char: char) -> bool {
+        match self {
+            Self::Range(start, end) => (start..=end).contains(&char),
+            Self::Digit(negated) => char.is_ascii_digit() != negated,
+            Self::Word(negated) => is_word_char(char) != negated,
+            Self::Space(negated) => char.is_whitespace() != negated,
+        }
+    }
+}
+
+fn compile_node(node: &Node, program: &mut Vec<Inst>) {
+    match node {
+        Node::Empty => {}
+        &Node::Char(char) => program.push(Inst::Char(char)),
+        Node::Any => program.push(Inst::Any),
+        Node::Class(class) => program.push(Inst::Class(class.clone())),
+        &Node::Assert(assertion) => program.push(Inst::Assert(assertion)),
+        Node::Group(node, index) => match index {
+            Some(index) => {
+                program.push(Inst::Save(2 * index));
+                compile_node(node, program);
+                program.push(Inst::Save(2 * index + 1));
+            }
+            None => compile_node(node, program),
+        },
+        Node::Concat(nodes) => {
+            for node in nodes {
+                compile_node(node, program);
+            }
+        }
+        Node::Alternate(nodes) => {
+            let mut jumps = Vec::new();
+            for (index, node) in nodes.iter().enumerate() {
+                if index + 1 == nodes.len() {
+                    compile_node(node, program);
+                    break;
+                }
+                let split = program.len();
+                program.push(Inst::Split(split + 1, 0));
+                compile_node(node, program);
+                jumps.push(program.len());
+                program.push(Inst::Jmp(0));
+                let next = program.len();
+                program[split] = Inst::Split(split + 1, next);
+            }
+            let end = program.len();
+            for jump in jumps {
+                program[jump] = Inst::Jmp(end);
+            }
+        }
+        &Node::Repeat {
+            ref node,
+            min,
+            max,
+            greedy,
+        } => {
+            for _ in 0..min {
+                compile_node(node, program);
+            }
+            match max {
+                None => {
+                    let split = program.len();
+                    program.push(Inst::Split(0, 0));
+                    compile_node(node, program);
+                    program.push(Inst::Jmp(split));
+                    let end = program.len();
+                    program[split] = split_inst(split + 1, end, greedy);
+                }
+                Some(max) => {
+                    let mut splits = Vec::new();
+                    for _ in min..max {
+                        splits.push(program.len());
+                        program.push(Inst::Split(0, 0));
+                        compile_node(node, program);
+                    }
+                    let end = program.len();
+                    for split in splits {
+                        program[split] = split_inst(split + 1, end, greedy);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn split_inst(body: usize, exit: usize, greedy: bool) -> Inst {
+    if greedy {
+        Inst::Split(body, exit)
+    } else {
+        Inst::Split(exit, body)
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    index: usize,
+    group_count: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let char = self.peek()?;
+        self.index += 1;
+        Some(char)
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_alternation(&mut self) -> Result<Node, RegexError> {
+        let mut nodes = vec![self.parse_concat()?];
+        while self.eat('|') {
+            nodes.push(self.parse_concat()?);
+        }
+        Ok(if nodes.len() == 1 {
+            nodes.pop().unwrap()
+        } else {
+            Node::Alternate(nodes)
+        })
+    }
+
+    fn parse_concat(&mut self) -> Result<Node, RegexError> {
+        let mut nodes = Vec::new();
+        while let Some(char) = self.peek() {
+            if char == '|' || char == ')' {
+                break;
+            }
+            let node = self.parse_atom()?;
+            nodes.push(self.parse_quantifiers(node)?);
+        }
+        Ok(match nodes.len() {
+            0 => Node::Empty,
+            1 => nodes.pop().unwrap(),
+            _ => Node::Concat(nodes),
+        })
+    }
+
+    fn parse_quantifiers(&mut self, mut node: Node) -> Result<Node, RegexError> {
+        loop {
+            let start = self.index;
+            let (min, max) = match self.peek() {
+                Some('*') => (0, None),
+                Some('+') => (1, None),
+                Some('?') => (0, Some(1)),
+                Some('{') => match self.parse_counted()? {
+                    Some(counts) => counts,
+                    None => return Ok(node),
+                },
+                _ => return Ok(node),
+            };
+            if self.index == start {
+                self.index += 1;
+            }
+            if matches!(node, Node::Empty | Node::Assert(_)) {
+                return Err(RegexError::NothingToRepeat(start));
+            }
+            if min > MAX_REPEAT || max.is_some_and(|max| max > MAX_REPEAT) {
+                return Err(RegexError::RepeatTooLarge(start));
+            }
+            let greedy = !self.eat('?');
+            node = Node::Repeat {
+                node: Box::new(node),
+                min,
+                max,
+                greedy,
+            };
+        }
+    }
+
+    /// Parses `{n}`, `{n,}` or `{n,m}`. Anything else is not a quantifier, in which case nothing
+    /// is consumed and the `{` is taken literally instead.
+    fn parse_counted(&mut self) -> Result<Option<(usize, Option<usize>)>, RegexError> {
+        let start = self.index;
+        debug_assert!(self.peek() == Some('{'));
+        self.index += 1;
+        let Some(min) = self.parse_number() else {
+            self.index = start;
+            return Ok(None);
+        };
+        let max = if self.eat(',') {
+            self.parse_number()
+        } else {
+            Some(min)
+        };
+        if !self.eat('}') {
+            self.index = start;
+            return Ok(None);
+        }
+        if max.is_some_and(|max| max < min) {
+            return Err(RegexError::InvalidRange(start));
+        }
+        Ok(Some((min, max)))
+    }
+
+    fn parse_number(&mut self) -> Option<usize> {
+        let start = self.index;
+        while self.peek().is_some_and(|char| char.is_ascii_digit()) {
+            self.index += 1;
+        }
+        self.chars[start..self.index]
+            .iter()
+            .collect::<String>()
+            .parse()
+            .ok()
+    }
+
+    fn parse_atom(&mut self) -> Result<Node, RegexError> {
+        let start = self.index;
+        match self.next().ok_or(RegexError::UnexpectedEnd)? {
+            '(' => {
+                let index = if self.eat('?') {
+                    if !self.eat(':') {
+                        return Err(RegexError::InvalidEscape(start));
+                    }
+                    None
+                } else {
+                    self.group_count += 1;
+                    Some(self.group_count)
+                };
+                let node = self.parse_alternation()?;
+                if !self.eat(')') {
+                    return Err(RegexError::UnmatchedParen(start));
+                }
+                Ok(Node::Group(Box::new(node), index))
+            }
+            '[' => self.parse_class(start),
+            '.' => Ok(Node::Any),
+            '^' => Ok(Node::Assert(Assertion::Start)),
+            '$' => Ok(Node::Assert(Assertion::End)),
+            '*' | '+' | '?' => Err(RegexError::NothingToRepeat(start)),
+            '\\' => {
+                let char = self.next().ok_or(RegexError::UnexpectedEnd)?;
+                Ok(match char {
+                    'b' => Node::Assert(Assertion::WordBoundary),
+                    'B' => Node::Assert(Assertion::NotWordBoundary),
+                    _ => match escape_class_item(char) {
+                        Some(item) => Node::Class(Class {
+                            negated: false,
+                            items: vec![item],
+                        }),
+                        None => Node::Char(
+                            escape_char(char).ok_or(RegexError::InvalidEscape(start))?,
+                        ),
+                    },
+                })
+            }
+            char => Ok(Node::Char(char)),
+        }
+    }
+
+    fn parse_class(&mut self, start: usize) -> Result<Node, RegexError> {
+        let negated = self.eat('^');
+        let mut items = Vec::new();
+        let mut first = true;
+        loop {
+            let char = self.next().ok_or(RegexError::UnmatchedBracket(start))?;
+            if char == ']' && !first {
+                break;
+            }
+            first = false;
+            let item_start = self.index - 1;
+            let range_start = if char == '\\' {
+                let char = self.next().ok_or(RegexError::UnmatchedBracket(start))?;
+                if let Some(item) = escape_class_item(char) {
+                    items.push(item);
+                    continue;
+                }
+                escape_char(char).ok_or(RegexError::InvalidEscape(item_start))?
+            } else {
+                char
+            };
+            if self.peek() == Some('-')
+                && self.chars.get(self.index + 1).is_some_and(|&char| char != ']')
+            {
+                self.index += 1;
+                let char = self.next().ok_or(RegexError::UnmatchedBracket(start))?;
+                let range_end = if char == '\\' {
+                    let char = self.next().ok_or(RegexError::UnmatchedBracket(start))?;
+                    escape_char(char).ok_or(RegexError::InvalidEscape(item_start))?
+                } else {
+                    char
+                };
+                if range_end < range_start {
+                    return Err(RegexError::InvalidRange(item_start));
+                }
+                items.push(ClassItem::Range(range_start, range_end));
+            } else {
+                items.push(ClassItem::Range(range_start, range_start));
+            }
+        }
+        Ok(Node::Class(Class { negated, items }))
+    }
+}
+
+fn escape_class_item(char: char) -> Option<ClassItem> {
+    Some(match char {
+        'd' => ClassItem::Digit(false),
+        'D' => ClassItem::Digit(true),
+        'w' => ClassItem::Word(false),
+        'W' => ClassItem::Word(true),
+        's' => ClassItem::Space(false),
+        'S' => ClassItem::Space(true),
+        _ => return None,
+    })
+}
+
+fn escape_char(char: char) -> Option<char> {
+    match char {
+        'n' => Some('\n'),
+        't' => Some('\t'),
+        'r' => Some('\r'),
+        char if char.is_ascii_punctuation() || char == ' ' => Some(char),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find<'a>(pattern: &str, text: &'a str) -> Option<&'a str> {
+        let regex = Regex::new(pattern, false).unwrap();
+        regex.find_at(text, 0).map(|range| &text[range])
+    }
+
+    #[test]
+    fn literals() {
+        assert_eq!(find("abc", "xxabcxx"), Some("abc"));
+        assert_eq!(find("abd", "xxabcxx"), None);
+        assert_eq!(find(r"a\.b", "axb a.b"), Some("a.b"));
+        assert_eq!(find(r"\(x\)", "f(x)"), Some("(x)"));
+        assert_eq!(find("a{x", "a{x"), Some("a{x"));
+        let regex = Regex::literal("a.b*", false);
+        assert_eq!(regex.find_at("aab a.b*", 0), Some(4..8));
+    }
+
+    #[test]
+    fn classes() {
+        assert_eq!(find("[a-c]+", "xyzabcabd"), Some("abcab"));
+        assert_eq!(find("[^a-z ]+", "abc DEF"), Some("DEF"));
+        assert_eq!(find(r"\d+", "v12.5"), Some("12"));
+        assert_eq!(find(r"\w+", "  foo_bar1 "), Some("foo_bar1"));
+        assert_eq!(find(r"\s\S", "a  b"), Some(" b"));
+        assert_eq!(find(r"[\d.]+", "v12.5"), Some("12.5"));
+        assert_eq!(find("[]a]+", "x]a]"), Some("]a]"));
+        assert_eq!(find("[a-]+", "x-a-"), Some("-a-"));
+        assert_eq!(find("é.", "café!"), Some("é!"));
+    }
+
+    #[test]
+    fn anchors() {
+        assert_eq!(find("^ab", "ab ab"), Some("ab"));
+        assert_eq!(Regex::new("^ab", false).unwrap().find_at("ab ab", 1), None);
+        assert_eq!(Regex::new("ab$", false).unwrap().find_at("ab ab", 0), Some(3..5));
+        assert_eq!(find(r"\bcat\b", "concat cat"), Some("cat"));
+        assert_eq!(Regex::new(r"\bcat\b", false).unwrap().find_at("concat cat", 0), Some(7..10));
+        assert_eq!(Regex::new(r"\Bcat", false).unwrap().find_at("cat concat", 0), Some(7..10));
+    }
+
+    #[test]
+    fn alternation_and_quantifiers() {
+        assert_eq!(find("cat|dog", "hotdog"), Some("dog"));
+        assert_eq!(find("a(b|cd)e", "acde"), Some("acde"));
+        assert_eq!(find("(?:ab)+", "xababa"), Some("abab"));
+        assert_eq!(find("a.*b", "a1b2b"), Some("a1b2b"));
+        assert_eq!(find("a.*?b", "a1b2b"), Some("a1b"));
+        assert_eq!(find("colou?r", "color"), Some("color"));
+        assert_eq!(find("a{2,3}", "aaaa"), Some("aaa"));
+        assert_eq!(find("a{2}", "a aa"), Some("aa"));
+        assert_eq!(find("x|", "abc"), Some(""));
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let regex = Regex::new("hello [a-z]+", true).unwrap();
+        assert_eq!(regex.find_at("say HeLLo WORLD", 0), Some(4..15));
+        assert_eq!(Regex::literal("ÄB", true).find_at("xäb", 0), Some(1..4));
+        assert_eq!(Regex::new("hello", false).unwrap().find_at("HELLO", 0), None);
+    }
+
+    #[test]
+    fn captures_and_expand() {
+        let regex = Regex::new(r"(\w+)=(\d+)?", false).unwrap();
+        assert_eq!(regex.group_count(), 2);
+        let text = "key=42 other=";
+        let captures = regex.captures_at(text, 0).unwrap();
+        assert_eq!(captures, [Some(0..6), Some(0..3), Some(4..6)]);
+        assert_eq!(regex.expand(text, &captures, "$2:${1}$$ $9 $x"), "42:key$  $x");
+        let captures = regex.captures_at(text, 6).unwrap();
+        assert_eq!(captures, [Some(7..13), Some(7..12), None]);
+    }
+
+    #[test]
+    fn cache_is_reusable() {
+        let regex = Regex::new("b+", false).unwrap();
+        let mut cache = Cache::default();
+        assert_eq!(regex.find_at_with("abba abbb", 0, &mut cache), Some(1..3));
+        assert_eq!(regex.find_at_with("abba abbb", 3, &mut cache), Some(6..9));
+        assert_eq!(regex.find_at_with("b", 0, &mut cache), Some(0..1));
+        assert_eq!(regex.find_at_with("ab", 3, &mut cache), None);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Regex::new("(ab", false).unwrap_err(), RegexError::UnmatchedParen(0));
+        assert_eq!(Regex::new("ab)", false).unwrap_err(), RegexError::UnmatchedParen(2));
+        assert_eq!(Regex::new("[ab", false).unwrap_err(), RegexError::UnmatchedBracket(0));
+        assert_eq!(Regex::new("*a", false).unwrap_err(), RegexError::NothingToRepeat(0));
+        assert_eq!(Regex::new("[z-a]", false).unwrap_err(), RegexError::InvalidRange(1));
+        assert_eq!(Regex::new(r"\q", false).unwrap_err(), RegexError::InvalidEscape(0));
+        assert_eq!(Regex::new("a{2000}", false).unwrap_err(), RegexError::RepeatTooLarge(1));
+        assert_eq!(Regex::new("a\\", false).unwrap_err(), RegexError::UnexpectedEnd);
+    }
+}
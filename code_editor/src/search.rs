@@ -0,0 +1,256 @@
+use {
+    crate::{
+        regex::{self, Cache, Regex, RegexError},
+        text::Position,
+    },
+    std::ops::Range,
+};
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SearchOptions {
+    pub case_insensitive: bool,
+    pub whole_word: bool,
+    pub regex: bool,
+}
+
+/// A compiled search pattern. Matches never span multiple lines.
+#[derive(Clone, Debug)]
+pub struct SearchQuery {
+    pattern: String,
+    options: SearchOptions,
+    regex: Regex,
+}
+
+impl SearchQuery {
+    pub fn new(pattern: &str, options: SearchOptions) -> Result<Self, RegexError> {
+        let regex = if options.regex {
+            Regex::new(pattern, options.case_insensitive)?
+        } else {
+            Regex::literal(pattern, options.case_insensitive)
+        };
+        Ok(Self {
+            pattern: pattern.to_owned(),
+            options,
+            regex,
+        })
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn options(&self) -> SearchOptions {
+        self.options
+    }
+
+    /// Returns all non-empty, non-overlapping matches in `lines`, in order.
+    pub fn find_all(&self, lines: &[String]) -> Vec<SearchMatch> {
+        let mut matches = Vec::new();
+        let mut cache = Cache::default();
+        for (line_index, line) in lines.iter().enumerate() {
+            let mut byte_index = 0;
+            while let Some(range) = self.find_in_line(line, byte_index, &mut cache) {
+                byte_index = range.end;
+                matches.push(SearchMatch {
+                    start: Position {
+                        line_index,
+                        byte_index: range.start,
+                    },
+                    end: Position {
+                        line_index,
+                        byte_index: range.end,
+                    },
+                });
+            }
+        }
+        matches
+    }
+
+    /// Returns the replacement text for `search_match`, with `$n` and `${n}` expanded to the
+    /// corresponding capture group if this is a regex search.
+    pub fn replacement_for(
+        &self,
+        lines: &[String],
+        search_match: SearchMatch,
+        replacement: &str,
+    ) -> String {
+        if !self.options.regex {
+            return replacement.to_owned();
+        }
+        let line = &lines[search_match.start.line_index];
+        match self.regex.captures_at(line, search_match.start.byte_index) {
+            Some(captures) => self.regex.expand(line, &captures, replacement),
+            None => replacement.to_owned(),
+        }
+    }
+
+    fn find_in_line(&self, line: &str, start: usize, cache: &mut Cache) -> Option<Range<usize>> {
+        let mut start = start;
+        loop {
+            let range = self.regex.find_at_with(line, start, cache)?;
+            if !range.is_empty() && (!self.options.whole_word || self.is_whole_word(line, &range)) {
+                return Some(range);
+            }
+            // Skip past the start of the rejected match and try again.
+            start = range.start + line[range.start..].chars().next()?.len_utf8();
+        }
+    }
+
+    fn is_whole_word(&self, line: &str, range: &Range<usize>) -> bool {
+        !line[..range.start]
+            .chars()
+            .next_back()
+            .is_some_and(regex::is_word_char)
+            && !line[range.end..]
+                .chars()
+                .next()
+                .is_some_and(regex::is_word_char)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SearchMatch {
+    pub start: Position,
+    pub end: Position,
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{decoration::DecorationSet, text::Text, Document, Session},
+    };
+
+    fn lines(text: &str) -> Vec<String> {
+        text.split('\n').map(|line| line.to_owned()).collect()
+    }
+
+    fn find_all(pattern: &str, options: SearchOptions, text: &str) -> Vec<(usize, Range<usize>)> {
+        SearchQuery::new(pattern, options)
+            .unwrap()
+            .find_all(&lines(text))
+            .into_iter()
+            .map(|search_match| {
+                (
+                    search_match.start.line_index,
+                    search_match.start.byte_index..search_match.end.byte_index,
+                )
+            })
+            .collect()
+    }
+
+    fn session(text: &str) -> Session {
+        Session::new(Document::new(Text::from(text), DecorationSet::new()))
+    }
+
+    fn document_text(session: &Session) -> String {
+        session.document().as_text().to_string()
+    }
+
+    #[test]
+    fn plain_search() {
+        let options = SearchOptions::default();
+        assert_eq!(find_all("a.", options, "a.a.\nxa."), [(0, 0..2), (0, 2..4), (1, 1..3)]);
+        assert_eq!(find_all("aa", options, "aaaaa"), [(0, 0..2), (0, 2..4)]);
+        assert_eq!(find_all("Foo", options, "foo FOO"), []);
+        assert_eq!(find_all("", options, "abc"), []);
+    }
+
+    #[test]
+    fn case_insensitive_search() {
+        let options = SearchOptions {
+            case_insensitive: true,
+            ..SearchOptions::default()
+        };
+        assert_eq!(find_all("Foo", options, "foo FOO fOo"), [(0, 0..3), (0, 4..7), (0, 8..11)]);
+    }
+
+    #[test]
+    fn whole_word_search() {
+        let options = SearchOptions {
+            whole_word: true,
+            ..SearchOptions::default()
+        };
+        assert_eq!(find_all("id", options, "id ids _id id_ (id)"), [(0, 0..2), (0, 16..18)]);
+        let options = SearchOptions {
+            whole_word: true,
+            regex: true,
+            ..SearchOptions::default()
+        };
+        // A rejected match at one position doesn't hide a whole word match later in the line.
+        assert_eq!(find_all(r"a\w*", options, "xab ab"), [(0, 4..6)]);
+    }
+
+    #[test]
+    fn regex_search_and_replacement() {
+        let options = SearchOptions {
+            regex: true,
+            ..SearchOptions::default()
+        };
+        let text = lines("let x = 1;\nlet yy = 22;");
+        let query = SearchQuery::new(r"let (\w+) = (\d+)", options).unwrap();
+        let matches = query.find_all(&text);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(query.replacement_for(&text, matches[1], "const $1: u32 = $2"), "const yy: u32 = 22");
+        assert!(SearchQuery::new("(", options).is_err());
+        // Without the regex option `$1` is inserted as is.
+        let query = SearchQuery::new("x", SearchOptions::default()).unwrap();
+        assert_eq!(query.replacement_for(&text, query.find_all(&text)[0], "$1"), "$1");
+    }
+
+    #[test]
+    fn replace_all_is_one_undo_group() {
+        let session = session("foo bar foo\nfoo");
+        session.set_search_query(Some(SearchQuery::new("foo", SearchOptions::default()).unwrap()));
+        assert_eq!(session.search_matches().len(), 3);
+        assert_eq!(session.replace_all("quux"), 3);
+        assert_eq!(document_text(&session), "quux bar quux\nquux");
+        assert_eq!(session.search_matches().len(), 0);
+        assert!(session.undo());
+        assert_eq!(document_text(&session), "foo bar foo\nfoo");
+        assert!(!session.undo());
+    }
+
+    #[test]
+    fn replace_selects_next_match() {
+        let session = session("a1 a2 a3");
+        let options = SearchOptions {
+            regex: true,
+            ..SearchOptions::default()
+        };
+        session.set_search_query(Some(SearchQuery::new(r"a(\d)", options).unwrap()));
+        // The first call only selects a match, the following ones replace it.
+        assert!(!session.replace("b$1"));
+        assert!(session.replace("b$1"));
+        assert_eq!(document_text(&session), "b1 a2 a3");
+        let selection = session.selections()[0];
+        assert_eq!((selection.start().byte_index, selection.end().byte_index), (3, 5));
+    }
+
+    #[test]
+    fn select_all_occurrences() {
+        let session = session("one two one\ntwo one");
+        session.set_search_query(Some(SearchQuery::new("one", SearchOptions::default()).unwrap()));
+        assert!(session.select_all_matches());
+        let selections = session
+            .selections()
+            .iter()
+            .map(|selection| (selection.start(), selection.end()))
+            .collect::<Vec<_>>();
+        let position = |line_index, byte_index| Position { line_index, byte_index };
+        assert_eq!(
+            selections,
+            [
+                (position(0, 0), position(0, 3)),
+                (position(0, 8), position(0, 11)),
+                (position(1, 4), position(1, 7)),
+            ]
+        );
+        // Typing replaces every occurrence at once.
+        session.insert(Text::from("1"));
+        assert_eq!(document_text(&session), "1 two 1\ntwo 1");
+
+        session.set_search_query(Some(SearchQuery::new("three", SearchOptions::default()).unwrap()));
+        assert!(!session.select_all_matches());
+    }
+}
@@ -4,10 +4,11 @@ use {
         str::StrExt,
         text::{Edit, Length, Position},
     },
+    makepad_widgets::makepad_micro_serde::*,
     std::{ops, ops::Deref, slice::Iter},
 };
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, Hash, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Hash, Eq, SerBin, DeBin)]
 pub struct Selection {
     pub cursor: Cursor,
     pub anchor: Position,
@@ -116,7 +117,7 @@ impl From<Cursor> for Selection {
     }
 }
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, SerBin, DeBin)]
 pub struct SelectionSet {
     selections: Vec<Selection>,
 }
@@ -245,7 +246,7 @@ impl<'a> IntoIterator for &'a SelectionSet {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, SerBin, DeBin)]
 pub struct Cursor {
     pub position: Position,
     pub affinity: Affinity,
@@ -549,7 +550,7 @@ impl From<Position> for Cursor {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, SerBin, DeBin)]
 pub enum Affinity {
     Before,
     After,
@@ -1,9 +1,11 @@
 use {
     crate::{
         char::CharExt,
+        decoration::{Decoration, DecorationType},
         document::Document,
         history::EditKind,
         layout::{BlockElement, Layout, WrappedElement},
+        search::{SearchMatch, SearchQuery},
         selection::{Affinity, Cursor, SelectionSet},
         str::StrExt,
         text::{Change, Drift, Edit, Length, Position, Text},
@@ -30,6 +32,7 @@ pub struct Session {
     selection_state: RefCell<SelectionState>,
     wrap_column: Cell<Option<usize>>,
     fold_state: RefCell<FoldState>,
+    search_state: RefCell<SearchState>,
     edit_receiver: Receiver<(Option<SelectionSet>, Vec<Edit>)>,
 }
 
@@ -63,6 +66,7 @@ impl Session {
                 folded_lines: HashSet::new(),
                 unfolding_lines: HashSet::new(),
             }),
+            search_state: RefCell::new(SearchState::default()),
             edit_receiver,
         };
         for line in 0..line_count {
@@ -779,12 +783,227 @@ impl Session {
             .redo(self.id, &self.selection_state.borrow().selections)
     }
 
+    pub fn search_query(&self) -> Ref<'_, Option<SearchQuery>> {
+        Ref::map(self.search_state.borrow(), |search_state| &search_state.query)
+    }
+
+    /// The matches of the current search query, as decorations of type `SearchMatch`. These are
+    /// kept up to date as the document is edited.
+    pub fn search_matches(&self) -> Ref<'_, [Decoration]> {
+        Ref::map(self.search_state.borrow(), |search_state| {
+            search_state.matches.as_slice()
+        })
+    }
+
+    /// Sets the query to search for, or clears the search if `query` is `None`. Meant to be
+    /// called on every change to the search input.
+    pub fn set_search_query(&self, query: Option<SearchQuery>) {
+        self.search_state.borrow_mut().query = query;
+        self.update_search_matches();
+    }
+
+    /// Selects the first match after the last added selection, wrapping around at the end of the
+    /// document. Returns `false` if there are no matches.
+    pub fn find_next(&self) -> bool {
+        let position = self.last_added_selection().end();
+        self.select_next_match_from(position)
+    }
+
+    /// Selects the last match before the last added selection, wrapping around at the start of
+    /// the document. Returns `false` if there are no matches.
+    pub fn find_prev(&self) -> bool {
+        let position = self.last_added_selection().start();
+        let search_state = self.search_state.borrow();
+        let Some(search_match) = search_state
+            .matches
+            .iter()
+            .rev()
+            .find(|decoration| decoration.end() <= position)
+            .or(search_state.matches.last())
+            .copied()
+        else {
+            return false;
+        };
+        drop(search_state);
+        self.select_match(search_match);
+        true
+    }
+
+    /// Adds a selection for every match, with the match after the last added selection as the
+    /// primary one. Returns `false` if there are no matches.
+    pub fn select_all_matches(&self) -> bool {
+        let position = self.last_added_selection().end();
+        let search_state = self.search_state.borrow();
+        if search_state.matches.is_empty() {
+            return false;
+        }
+        let mut selections = SelectionSet::new();
+        for &decoration in &search_state.matches {
+            selections.add_selection(match_to_selection(decoration));
+        }
+        let primary_index = search_state
+            .matches
+            .iter()
+            .position(|decoration| decoration.start() >= position)
+            .unwrap_or(0);
+        drop(search_state);
+        let mut selection_state = self.selection_state.borrow_mut();
+        selection_state.mode = SelectionMode::Simple;
+        selection_state.selections = selections;
+        selection_state.last_added_selection_index = Some(primary_index);
+        selection_state.injected_char_stack.clear();
+        drop(selection_state);
+        self.update_highlighted_delimiter_positions();
+        self.document().force_new_group();
+        true
+    }
+
+    /// Replaces the last added selection if it is a match, and selects the next match. If it is
+    /// not a match, only selects the next match. Returns whether anything was replaced.
+    pub fn replace(&self, replacement: &str) -> bool {
+        let selection = self.last_added_selection();
+        let search_state = self.search_state.borrow();
+        let search_match = search_state.matches.iter().copied().find(|decoration| {
+            decoration.start() == selection.start() && decoration.end() == selection.end()
+        });
+        let replaced = match (search_state.query.as_ref(), search_match) {
+            (Some(query), Some(search_match)) => Some((
+                search_match,
+                Text::from(query.replacement_for(
+                    self.document.as_text().as_lines(),
+                    decoration_to_match(search_match),
+                    replacement,
+                )),
+            )),
+            _ => None,
+        };
+        drop(search_state);
+        let Some((search_match, text)) = replaced else {
+            self.find_next();
+            return false;
+        };
+        let mut selections = SelectionSet::new();
+        selections.set_selection(match_to_selection(search_match));
+        self.replace_selections(&selections, &[text.clone()]);
+        self.select_next_match_from(search_match.start() + text.length());
+        true
+    }
+
+    /// Replaces every match as a single undoable edit, and returns the number of replacements.
+    pub fn replace_all(&self, replacement: &str) -> usize {
+        let search_state = self.search_state.borrow();
+        let Some(query) = search_state.query.as_ref() else {
+            return 0;
+        };
+        let mut selections = SelectionSet::new();
+        let mut texts = Vec::new();
+        let text = self.document.as_text();
+        for &decoration in &search_state.matches {
+            selections.add_selection(match_to_selection(decoration));
+            texts.push(Text::from(query.replacement_for(
+                text.as_lines(),
+                decoration_to_match(decoration),
+                replacement,
+            )));
+        }
+        drop(text);
+        drop(search_state);
+        if texts.is_empty() {
+            return 0;
+        }
+        self.replace_selections(&selections, &texts);
+        texts.len()
+    }
+
     pub fn handle_changes(&mut self) {
+        self.receive_changes();
+    }
+
+    fn receive_changes(&self) {
         while let Ok((selections, edits)) = self.edit_receiver.try_recv() {
             self.update_after_edit(selections, &edits);
         }
     }
 
+    fn last_added_selection(&self) -> Selection {
+        let selection_state = self.selection_state.borrow();
+        let index = selection_state.last_added_selection_index.unwrap_or(0);
+        selection_state.selections.as_selections()[index]
+    }
+
+    fn select_next_match_from(&self, position: Position) -> bool {
+        let search_state = self.search_state.borrow();
+        let Some(search_match) = search_state
+            .matches
+            .iter()
+            .find(|decoration| decoration.start() >= position)
+            .or(search_state.matches.first())
+            .copied()
+        else {
+            return false;
+        };
+        drop(search_state);
+        self.select_match(search_match);
+        true
+    }
+
+    fn select_match(&self, search_match: Decoration) {
+        let mut selection_state = self.selection_state.borrow_mut();
+        selection_state.mode = SelectionMode::Simple;
+        selection_state
+            .selections
+            .set_selection(match_to_selection(search_match));
+        selection_state.last_added_selection_index = Some(0);
+        selection_state.injected_char_stack.clear();
+        drop(selection_state);
+        self.update_highlighted_delimiter_positions();
+        self.document().force_new_group();
+    }
+
+    /// Replaces each selection with the corresponding text, as a single group in the history.
+    fn replace_selections(&self, selections: &SelectionSet, texts: &[Text]) {
+        let mut texts = texts.iter();
+        self.document.edit_selections_verbatim(
+            self.id,
+            EditKind::Other,
+            selections,
+            |mut editor, position, length| {
+                editor.apply_edit(Edit {
+                    change: Change::Delete(position, length),
+                    drift: Drift::Before,
+                });
+                editor.apply_edit(Edit {
+                    change: Change::Insert(position, texts.next().unwrap().clone()),
+                    drift: Drift::Before,
+                });
+            },
+        );
+        self.document.force_new_group();
+        // Apply our own edits right away, so that selections and matches are up to date.
+        self.receive_changes();
+    }
+
+    fn update_search_matches(&self) {
+        let mut search_state = self.search_state.borrow_mut();
+        let matches = match &search_state.query {
+            Some(query) => query
+                .find_all(self.document.as_text().as_lines())
+                .into_iter()
+                .enumerate()
+                .map(|(index, search_match)| {
+                    Decoration::new(
+                        index,
+                        search_match.start,
+                        search_match.end,
+                        DecorationType::SearchMatch,
+                    )
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        search_state.matches = matches;
+    }
+
     fn modify_selections(
         &self,
         reset_anchor: bool,
@@ -886,6 +1105,9 @@ impl Session {
         }
         drop(selection_state);
         self.update_highlighted_delimiter_positions();
+        if self.search_state.borrow().query.is_some() {
+            self.update_search_matches();
+        }
     }
 
     fn update_y(&self) {
@@ -1014,6 +1236,12 @@ struct SelectionState {
     highlighted_delimiter_positions: HashSet<Position>,
 }
 
+#[derive(Debug, Default)]
+struct SearchState {
+    query: Option<SearchQuery>,
+    matches: Vec<Decoration>,
+}
+
 #[derive(Debug)]
 struct FoldState {
     folding_lines: HashSet<usize>,
@@ -1021,6 +1249,24 @@ struct FoldState {
     unfolding_lines: HashSet<usize>,
 }
 
+fn match_to_selection(decoration: Decoration) -> Selection {
+    Selection {
+        cursor: Cursor {
+            position: decoration.end(),
+            affinity: Affinity::Before,
+            preferred_column_index: None,
+        },
+        anchor: decoration.start(),
+    }
+}
+
+fn decoration_to_match(decoration: Decoration) -> SearchMatch {
+    SearchMatch {
+        start: decoration.start(),
+        end: decoration.end(),
+    }
+}
+
 pub fn reindent(string: &str, f: impl FnOnce(usize) -> usize) -> (usize, usize, String) {
     let indentation = string.indent().unwrap_or("");
     let indentation_column_count = indentation.column_count();
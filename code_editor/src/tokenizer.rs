@@ -1,22 +1,147 @@
-use crate::{
-    text::{Change, Text},
-    token::TokenKind,
-    Token,
+use {
+    crate::{
+        text::{Change, Text},
+        token::TokenKind,
+        Token,
+    },
+    makepad_widgets::markdown::CodeTokenKind,
+    std::fmt,
 };
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub mod json;
+pub mod markdown;
+pub mod plain;
+pub mod rust;
+pub mod toml;
+
+pub use self::{
+    json::Json, markdown::Markdown, plain::Plain, rust::Rust, toml::Toml,
+};
+
+/// A line based tokenizer for a single language.
+///
+/// Each line is tokenized starting from the state the previous line ended in, so `State` should
+/// only carry what needs to survive a line break (an unterminated block comment, a fenced code
+/// block, and so on). The `Tokenizer` caches the start and end state of every line, and only
+/// re-tokenizes lines whose start state changed.
+pub trait Language: fmt::Debug + 'static {
+    type State: Copy + Default + Eq + fmt::Debug;
+
+    /// Consumes a single non-empty token from `cursor`, which is never at the end of the line.
+    fn next_token(&self, state: Self::State, cursor: &mut Cursor<'_>) -> (Self::State, TokenKind);
+}
+
+#[derive(Debug)]
 pub struct Tokenizer {
-    state: Vec<Option<(State, State)>>,
+    lines: Box<dyn LineTokenizer>,
 }
 
 impl Tokenizer {
     pub fn new(line_count: usize) -> Self {
+        Self::with_language(Rust, line_count)
+    }
+
+    pub fn with_language<L: Language>(language: L, line_count: usize) -> Self {
         Self {
-            state: (0..line_count).map(|_| None).collect(),
+            lines: Box::new(LanguageTokenizer {
+                language,
+                state: (0..line_count).map(|_| None).collect(),
+            }),
+        }
+    }
+
+    /// Picks a built-in language based on the extension of `path`, falling back to plain text.
+    pub fn for_path(path: &str, line_count: usize) -> Self {
+        let extension = path
+            .rsplit(['/', '\\'])
+            .next()
+            .and_then(|name| name.rsplit_once('.'))
+            .map(|(_, extension)| extension.to_ascii_lowercase());
+        extension
+            .and_then(|extension| Self::with_extension(&extension, line_count))
+            .unwrap_or_else(|| Self::with_language(Plain, line_count))
+    }
+
+    /// Picks a built-in language for a lowercase file extension, if there is one.
+    pub fn with_extension(extension: &str, line_count: usize) -> Option<Self> {
+        match extension {
+            "rs" | "glsl" | "vert" | "frag" | "wgsl" | "c" | "h" | "cpp" | "js" | "ts" => {
+                Some(Self::with_language(Rust, line_count))
+            }
+            "toml" | "lock" => Some(Self::with_language(Toml, line_count)),
+            "json" => Some(Self::with_language(Json, line_count)),
+            "md" | "markdown" => Some(Self::with_language(Markdown, line_count)),
+            _ => None,
         }
     }
 
     pub fn apply_change(&mut self, change: &Change) {
+        self.lines.apply_change(change)
+    }
+
+    pub fn update(&mut self, text: &Text, tokens: &mut [Vec<Token>]) {
+        self.lines.update(text, tokens)
+    }
+}
+
+/// Highlights a code block in a `Markdown` widget, where `language` is the info string of its
+/// fence. Languages without a tokenizer are not highlighted.
+pub fn highlight_code(language: &str, code: &str) -> Option<Vec<Vec<(usize, CodeTokenKind)>>> {
+    let language = language.split_whitespace().next()?.to_ascii_lowercase();
+    let extension = match language.as_str() {
+        "rust" => "rs",
+        "javascript" => "js",
+        "typescript" => "ts",
+        "markdown" => "md",
+        language => language,
+    };
+    let text = Text::from(code);
+    let mut tokenizer = Tokenizer::with_extension(extension, text.as_lines().len())?;
+    let mut tokens = (0..text.as_lines().len()).map(|_| Vec::new()).collect::<Vec<_>>();
+    tokenizer.update(&text, &mut tokens);
+    Some(
+        tokens
+            .into_iter()
+            .map(|line| {
+                line.into_iter()
+                    .map(|token| {
+                        let kind = match token.kind {
+                            TokenKind::BranchKeyword => CodeTokenKind::BranchKeyword,
+                            TokenKind::LoopKeyword => CodeTokenKind::LoopKeyword,
+                            TokenKind::OtherKeyword => CodeTokenKind::OtherKeyword,
+                            TokenKind::Comment => CodeTokenKind::Comment,
+                            TokenKind::Constant => CodeTokenKind::Constant,
+                            TokenKind::Number => CodeTokenKind::Number,
+                            TokenKind::String => CodeTokenKind::String,
+                            TokenKind::Function => CodeTokenKind::Function,
+                            TokenKind::Typename => CodeTokenKind::Typename,
+                            TokenKind::Punctuator => CodeTokenKind::Punctuator,
+                            TokenKind::Unknown
+                            | TokenKind::Delimiter
+                            | TokenKind::Identifier
+                            | TokenKind::Whitespace => CodeTokenKind::Plain,
+                        };
+                        (token.len, kind)
+                    })
+                    .collect()
+            })
+            .collect(),
+    )
+}
+
+trait LineTokenizer: fmt::Debug {
+    fn apply_change(&mut self, change: &Change);
+    fn update(&mut self, text: &Text, tokens: &mut [Vec<Token>]);
+}
+
+#[derive(Debug)]
+struct LanguageTokenizer<L: Language> {
+    language: L,
+    state: Vec<Option<(L::State, L::State)>>,
+}
+
+impl<L: Language> LineTokenizer for LanguageTokenizer<L> {
+    fn apply_change(&mut self, change: &Change) {
         match *change {
             Change::Insert(point, ref text) => {
                 self.state[point.line_index] = None;
@@ -38,8 +163,8 @@ impl Tokenizer {
         }
     }
 
-    pub fn update(&mut self, text: &Text, tokens: &mut [Vec<Token>]) {
-        let mut state = State::default();
+    fn update(&mut self, text: &Text, tokens: &mut [Vec<Token>]) {
+        let mut state = L::State::default();
         for line in 0..text.as_lines().len() {
             match self.state[line] {
                 Some((start_state, end_state)) if state == start_state => {
@@ -49,13 +174,16 @@ impl Tokenizer {
                     let start_state = state;
                     let mut new_tokens = Vec::new();
                     let mut cursor = Cursor::new(&text.as_lines()[line]);
-                    loop {
-                        let (next_state, token) = state.next(&mut cursor);
+                    while !cursor.is_at_end() {
+                        let start = cursor.index;
+                        let (next_state, kind) = self.language.next_token(state, &mut cursor);
+                        let end = cursor.index;
+                        assert!(start < end);
                         state = next_state;
-                        match token {
-                            Some(token) => new_tokens.push(token),
-                            None => break,
-                        }
+                        new_tokens.push(Token {
+                            len: end - start,
+                            kind,
+                        });
                     }
                     self.state[line] = Some((start_state, state));
                     tokens[line] = new_tokens;
@@ -65,434 +193,52 @@ impl Tokenizer {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-pub enum State {
-    Initial(InitialState),
-    BlockCommentTail(BlockCommentTailState),
-    DoubleQuotedStringTail(DoubleQuotedStringTailState),
-    RawDoubleQuotedStringTail(RawDoubleQuotedStringTailState),
-}
-
-impl Default for State {
-    fn default() -> State {
-        State::Initial(InitialState)
-    }
-}
-
-impl State {
-    pub fn next(self, cursor: &mut Cursor) -> (State, Option<Token>) {
-        if cursor.peek(0) == '\0' {
-            return (self, None);
-        }
-        let start = cursor.index;
-        let (next_state, kind) = match self {
-            State::Initial(state) => state.next(cursor),
-            State::BlockCommentTail(state) => state.next(cursor),
-            State::DoubleQuotedStringTail(state) => state.next(cursor),
-            State::RawDoubleQuotedStringTail(state) => state.next(cursor),
-        };
-        let end = cursor.index;
-        assert!(start < end);
-        (
-            next_state,
-            Some(Token {
-                len: end - start,
-                kind,
-            }),
-        )
-    }
+#[derive(Debug)]
+pub struct Cursor<'a> {
+    string: &'a str,
+    index: usize,
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-pub struct InitialState;
-
-impl InitialState {
-    fn next(self, cursor: &mut Cursor<'_>) -> (State, TokenKind) {
-        match (cursor.peek(0), cursor.peek(1), cursor.peek(2)) {
-            ('r', '#', '"') | ('r', '#', '#') => self.raw_string(cursor),
-            ('b', 'r', '"') | ('b', 'r', '#') => self.raw_byte_string(cursor),
-            ('/', '/', _) => self.line_comment(cursor),
-            ('/', '*', _) => self.block_comment(cursor),
-            ('b', '\'', _) => self.byte(cursor),
-            ('b', '"', _) => self.byte_string(cursor),
-            ('!', '=', _)
-            | ('%', '=', _)
-            | ('&', '&', _)
-            | ('&', '=', _)
-            | ('*', '=', _)
-            | ('+', '=', _)
-            | ('-', '=', _)
-            | ('-', '>', _)
-            | ('.', '.', _)
-            | ('/', '=', _)
-            | (':', ':', _)
-            | ('<', '<', _)
-            | ('<', '=', _)
-            | ('=', '=', _)
-            | ('=', '>', _)
-            | ('>', '=', _)
-            | ('>', '>', _)
-            | ('^', '=', _)
-            | ('|', '=', _)
-            | ('|', '|', _) => {
-                cursor.skip(2);
-                (State::Initial(InitialState), TokenKind::Punctuator)
-            }
-            ('\'', _, _) => self.char_or_lifetime(cursor),
-            ('"', _, _) => self.string(cursor),
-            ('(', _, _) => {
-                cursor.skip(1);
-                (State::Initial(InitialState), TokenKind::Delimiter)
-            }
-            (')', _, _) => {
-                cursor.skip(1);
-                (State::Initial(InitialState), TokenKind::Delimiter)
-            }
-            ('[', _, _) => {
-                cursor.skip(1);
-                (State::Initial(InitialState), TokenKind::Delimiter)
-            }
-            (']', _, _) => {
-                cursor.skip(1);
-                (State::Initial(InitialState), TokenKind::Delimiter)
-            }
-            ('{', _, _) => {
-                cursor.skip(1);
-                (State::Initial(InitialState), TokenKind::Delimiter)
-            }
-            ('}', _, _) => {
-                cursor.skip(1);
-                (State::Initial(InitialState), TokenKind::Delimiter)
-            }
-            ('.', char, _) if char.is_digit(10) => self.number(cursor),
-            ('!', _, _)
-            | ('#', _, _)
-            | ('$', _, _)
-            | ('%', _, _)
-            | ('&', _, _)
-            | ('*', _, _)
-            | ('+', _, _)
-            | (',', _, _)
-            | ('-', _, _)
-            | ('.', _, _)
-            | ('/', _, _)
-            | (':', _, _)
-            | (';', _, _)
-            | ('<', _, _)
-            | ('=', _, _)
-            | ('>', _, _)
-            | ('?', _, _)
-            | ('@', _, _)
-            | ('^', _, _)
-            | ('_', _, _)
-            | ('|', _, _) => {
-                cursor.skip(1);
-                (State::Initial(InitialState), TokenKind::Punctuator)
-            }
-            (char, _, _) if char.is_identifier_start() => self.identifier_or_keyword(cursor),
-            (char, _, _) if char.is_digit(10) => self.number(cursor),
-            (char, _, _) if char.is_whitespace() => self.whitespace(cursor),
-            _ => {
-                cursor.skip(1);
-                (State::Initial(InitialState), TokenKind::Unknown)
-            }
-        }
-    }
-
-    fn line_comment(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        debug_assert!(cursor.peek(0) == '/' && cursor.peek(1) == '/');
-        cursor.skip(2);
-        while cursor.skip_if(|ch| ch != '\0') {}
-        (State::Initial(InitialState), TokenKind::Comment)
-    }
-
-    fn block_comment(self, cursor: &mut Cursor<'_>) -> (State, TokenKind) {
-        debug_assert!(cursor.peek(0) == '/' && cursor.peek(1) == '*');
-        cursor.skip(2);
-        BlockCommentTailState { depth: 0 }.next(cursor)
-    }
-
-    fn identifier_or_keyword(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        debug_assert!(cursor.peek(0).is_identifier_start());
-        let start = cursor.index;
-        cursor.skip(1);
-        while cursor.skip_if(|char| char.is_identifier_continue()) {}
-        let end = cursor.index;
-        let string = &cursor.string[start..end];
-        (
-            State::Initial(InitialState),
-            match string {
-                "else" | "if" | "match" | "return" => TokenKind::BranchKeyword,
-                "break" | "continue" | "for" | "loop" | "while" => TokenKind::LoopKeyword,
-                "Self" | "as" | "async" | "await" | "const" | "crate" | "dyn" | "enum"
-                | "extern" | "false" | "fn" | "impl" | "in" | "let" | "mod" | "move" | "mut"
-                | "pub" | "ref" | "self" | "static" | "struct" | "super" | "trait" | "true"
-                | "type" | "unsafe" | "use" | "where" | "usize" | "isize" | "u8" | "u16"
-                | "u32" | "u64" | "i8" | "i16" | "i32" | "i64" | "vec2" | "vec3" | "vec4"
-                | "bool" | "f32" | "f64" => TokenKind::OtherKeyword,
-                _ => {
-                    let mut chars = string.chars();
-                    if chars.next().unwrap().is_uppercase() {
-                        match chars.next() {
-                            Some(char) if char.is_uppercase() => TokenKind::Constant,
-                            _ => TokenKind::Typename,
-                        }
-                    } else if cursor.peek(0) == '(' {
-                        TokenKind::Function
-                    } else {
-                        TokenKind::Identifier
-                    }
-                }
-            },
-        )
-    }
-
-    fn number(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        match (cursor.peek(0), cursor.peek(1)) {
-            ('0', 'b') => {
-                cursor.skip(2);
-                if !cursor.skip_digits(2) {
-                    return (State::Initial(InitialState), TokenKind::Unknown);
-                }
-                return (State::Initial(InitialState), TokenKind::Number);
-            }
-            ('0', 'o') => {
-                cursor.skip(2);
-                if !cursor.skip_digits(8) {
-                    return (State::Initial(InitialState), TokenKind::Unknown);
-                }
-                return (State::Initial(InitialState), TokenKind::Number);
-            }
-            ('0', 'x') => {
-                cursor.skip(2);
-                if !cursor.skip_digits(16) {
-                    return (State::Initial(InitialState), TokenKind::Unknown);
-                }
-                return (State::Initial(InitialState), TokenKind::Number);
-            }
-            _ => {
-                cursor.skip_digits(10);
-                match cursor.peek(0) {
-                    '.' if cursor.peek(1) != '.' && !cursor.peek(0).is_identifier_start() => {
-                        cursor.skip(1);
-                        if cursor.skip_digits(10) {
-                            if cursor.peek(0) == 'E' || cursor.peek(0) == 'e' {
-                                if !cursor.skip_exponent() {
-                                    return (State::Initial(InitialState), TokenKind::Unknown);
-                                }
-                            }
-                        }
-                        cursor.skip_suffix();
-                        return (State::Initial(InitialState), TokenKind::Number);
-                    }
-                    'E' | 'e' => {
-                        if !cursor.skip_exponent() {
-                            return (State::Initial(InitialState), TokenKind::Unknown);
-                        }
-                        cursor.skip_suffix();
-                        return (State::Initial(InitialState), TokenKind::Number);
-                    }
-                    _ => {
-                        cursor.skip_suffix();
-                        return (State::Initial(InitialState), TokenKind::Number);
-                    }
-                }
-            }
-        };
-    }
-
-    fn char_or_lifetime(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        if cursor.peek(1).is_identifier_start() && cursor.peek(2) != '\'' {
-            debug_assert!(cursor.peek(0) == '\'');
-            cursor.skip(2);
-            while cursor.skip_if(|ch| ch.is_identifier_continue()) {}
-            if cursor.peek(0) == '\'' {
-                cursor.skip(1);
-                cursor.skip_suffix();
-                (State::Initial(InitialState), TokenKind::String)
-            } else {
-                (State::Initial(InitialState), TokenKind::String)
-            }
-        } else {
-            self.single_quoted_string(cursor)
-        }
-    }
-
-    fn byte(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        debug_assert!(cursor.peek(0) == 'b');
-        cursor.skip(1);
-        self.single_quoted_string(cursor)
-    }
-
-    fn string(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        self.double_quoted_string(cursor)
-    }
-
-    fn byte_string(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        debug_assert!(cursor.peek(0) == 'b');
-        cursor.skip(1);
-        self.double_quoted_string(cursor)
-    }
-
-    fn raw_string(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        debug_assert!(cursor.peek(0) == 'r');
-        cursor.skip(1);
-        self.raw_double_quoted_string(cursor)
-    }
-
-    fn raw_byte_string(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        debug_assert!(cursor.peek(0) == 'b' && cursor.peek(1) == 'r');
-        cursor.skip(2);
-        self.raw_double_quoted_string(cursor)
-    }
-
-    fn single_quoted_string(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        debug_assert!(cursor.peek(0) == '\'');
-        cursor.skip(1);
-        loop {
-            match (cursor.peek(0), cursor.peek(1)) {
-                ('\'', _) => {
-                    cursor.skip(1);
-                    cursor.skip_suffix();
-                    break;
-                }
-                ('\0', _) => return (State::Initial(InitialState), TokenKind::Unknown),
-                ('\\', '\'') | ('\\', '\\') => cursor.skip(2),
-                _ => cursor.skip(1),
-            }
-        }
-        (State::Initial(InitialState), TokenKind::String)
+impl<'a> Cursor<'a> {
+    pub fn new(string: &'a str) -> Self {
+        Cursor { string, index: 0 }
     }
 
-    fn double_quoted_string(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        debug_assert!(cursor.peek(0) == '"');
-        cursor.skip(1);
-        DoubleQuotedStringTailState.next(cursor)
+    /// The part of the line that has already been consumed.
+    pub fn consumed(&self) -> &'a str {
+        &self.string[..self.index]
     }
 
-    fn raw_double_quoted_string(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        let mut start_hash_count = 0;
-        while cursor.skip_if(|ch| ch == '#') {
-            start_hash_count += 1;
-        }
-        RawDoubleQuotedStringTailState { start_hash_count }.next(cursor)
+    /// The part of the line that has not been consumed yet.
+    pub fn rest(&self) -> &'a str {
+        &self.string[self.index..]
     }
 
-    fn whitespace(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        debug_assert!(cursor.peek(0).is_whitespace());
-        cursor.skip(1);
-        while cursor.skip_if(|char| char.is_whitespace()) {}
-        (State::Initial(InitialState), TokenKind::Whitespace)
+    /// Everything consumed since byte offset `start`.
+    pub fn slice_from(&self, start: usize) -> &'a str {
+        &self.string[start..self.index]
     }
-}
-
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-pub struct BlockCommentTailState {
-    depth: usize,
-}
 
-impl BlockCommentTailState {
-    fn next(self, cursor: &mut Cursor<'_>) -> (State, TokenKind) {
-        let mut state = self;
-        loop {
-            match (cursor.peek(0), cursor.peek(1)) {
-                ('/', '*') => {
-                    cursor.skip(2);
-                    state.depth += 1;
-                }
-                ('*', '/') => {
-                    cursor.skip(2);
-                    if state.depth == 0 {
-                        break (State::Initial(InitialState), TokenKind::Comment);
-                    }
-                    state.depth -= 1;
-                }
-                ('\0', _) => {
-                    break (State::BlockCommentTail(state), TokenKind::Comment);
-                }
-                _ => cursor.skip(1),
-            }
-        }
+    pub fn index(&self) -> usize {
+        self.index
     }
-}
-
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-pub struct DoubleQuotedStringTailState;
 
-impl DoubleQuotedStringTailState {
-    fn next(self, cursor: &mut Cursor<'_>) -> (State, TokenKind) {
-        loop {
-            match (cursor.peek(0), cursor.peek(1)) {
-                ('"', _) => {
-                    cursor.skip(1);
-                    cursor.skip_suffix();
-                    break (State::Initial(InitialState), TokenKind::String);
-                }
-                ('\0', _) => {
-                    break (
-                        State::DoubleQuotedStringTail(DoubleQuotedStringTailState),
-                        TokenKind::String,
-                    );
-                }
-                ('\\', '"') | ('\\', '\\') => cursor.skip(2),
-                _ => cursor.skip(1),
-            }
-        }
+    pub fn is_at_end(&self) -> bool {
+        self.index == self.string.len()
     }
-}
-
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-pub struct RawDoubleQuotedStringTailState {
-    start_hash_count: usize,
-}
 
-impl RawDoubleQuotedStringTailState {
-    fn next(self, cursor: &mut Cursor<'_>) -> (State, TokenKind) {
-        loop {
-            match cursor.peek(0) {
-                '"' => {
-                    cursor.skip(1);
-                    let mut end_hash_count = 0;
-                    while end_hash_count < self.start_hash_count && cursor.skip_if(|ch| ch == '#') {
-                        end_hash_count += 1;
-                    }
-                    if end_hash_count == self.start_hash_count {
-                        cursor.skip_suffix();
-                        break (State::Initial(InitialState), TokenKind::String);
-                    }
-                }
-                '\0' => {
-                    break (State::RawDoubleQuotedStringTail(self), TokenKind::String);
-                }
-                _ => cursor.skip(1),
-            }
-        }
-    }
-}
-
-#[derive(Debug)]
-pub struct Cursor<'a> {
-    string: &'a str,
-    index: usize,
-}
-
-impl<'a> Cursor<'a> {
-    pub fn new(string: &'a str) -> Self {
-        Cursor { string, index: 0 }
-    }
-
-    fn peek(&self, index: usize) -> char {
+    pub fn peek(&self, index: usize) -> char {
         self.string[self.index..].chars().nth(index).unwrap_or('\0')
     }
 
-    fn skip(&mut self, count: usize) {
+    pub fn skip(&mut self, count: usize) {
         self.index = self.string[self.index..]
             .char_indices()
             .nth(count)
             .map_or(self.string.len(), |(index, _)| self.index + index);
     }
 
-    fn skip_if<P>(&mut self, predicate: P) -> bool
+    pub fn skip_if<P>(&mut self, predicate: P) -> bool
     where
         P: FnOnce(char) -> bool,
     {
@@ -504,7 +250,22 @@ impl<'a> Cursor<'a> {
         }
     }
 
-    fn skip_exponent(&mut self) -> bool {
+    pub fn skip_while<P>(&mut self, mut predicate: P) -> usize
+    where
+        P: FnMut(char) -> bool,
+    {
+        let mut count = 0;
+        while self.skip_if(|char| char != '\0' && predicate(char)) {
+            count += 1;
+        }
+        count
+    }
+
+    pub fn skip_to_end(&mut self) {
+        self.index = self.string.len();
+    }
+
+    pub fn skip_exponent(&mut self) -> bool {
         debug_assert!(self.peek(0) == 'E' || self.peek(0) == 'e');
         self.skip(1);
         if self.peek(0) == '+' || self.peek(0) == '-' {
@@ -513,7 +274,7 @@ impl<'a> Cursor<'a> {
         self.skip_digits(10)
     }
 
-    fn skip_digits(&mut self, radix: u32) -> bool {
+    pub fn skip_digits(&mut self, radix: u32) -> bool {
         let mut has_skip_digits = false;
         loop {
             match self.peek(0) {
@@ -530,7 +291,7 @@ impl<'a> Cursor<'a> {
         has_skip_digits
     }
 
-    fn skip_suffix(&mut self) -> bool {
+    pub fn skip_suffix(&mut self) -> bool {
         if self.peek(0).is_identifier_start() {
             self.skip(1);
             while self.skip_if(|char| char.is_identifier_continue()) {}
@@ -538,6 +299,11 @@ impl<'a> Cursor<'a> {
         }
         false
     }
+
+    /// Skips whitespace, returning whether anything was skipped.
+    pub fn skip_whitespace(&mut self) -> bool {
+        self.skip_while(|char| char.is_whitespace()) > 0
+    }
 }
 
 pub trait CharExt {
@@ -560,3 +326,186 @@ impl CharExt for char {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::text::{Length, Position},
+    };
+
+    /// Keeps text, tokenizer and tokens in sync the way a `Document` does.
+    struct Buffer {
+        text: Text,
+        tokenizer: Tokenizer,
+        tokens: Vec<Vec<Token>>,
+    }
+
+    impl Buffer {
+        fn new(tokenizer: Tokenizer, code: &str) -> Self {
+            let text = Text::from(code);
+            let tokens = (0..text.as_lines().len()).map(|_| Vec::new()).collect();
+            Self { text, tokenizer, tokens }
+        }
+
+        fn apply_change(&mut self, change: Change) {
+            // Only the line structure matters here, the tokenizer re-tokenizes the changed lines.
+            match change {
+                Change::Insert(point, ref text) => {
+                    let line = point.line_index + 1;
+                    let line_count = text.length().line_count;
+                    self.tokens.splice(line..line, (0..line_count).map(|_| Vec::new()));
+                }
+                Change::Delete(start, length) => {
+                    let line = start.line_index + 1;
+                    self.tokens.drain(line..line + length.line_count);
+                }
+            }
+            self.tokenizer.apply_change(&change);
+            self.text.apply_change(change);
+        }
+
+        /// Tokenizes every line, leaving out whitespace, and pairs each token with its text.
+        fn tokens(&mut self) -> Vec<Vec<(String, TokenKind)>> {
+            self.tokenizer.update(&self.text, &mut self.tokens);
+            self.text
+                .as_lines()
+                .iter()
+                .zip(&self.tokens)
+                .map(|(line, tokens)| {
+                    let mut start = 0;
+                    tokens
+                        .iter()
+                        .filter_map(|token| {
+                            let string = line[start..start + token.len].to_string();
+                            start += token.len;
+                            (token.kind != TokenKind::Whitespace).then_some((string, token.kind))
+                        })
+                        .collect()
+                })
+                .collect()
+        }
+    }
+
+    fn tokenize<L: Language>(language: L, code: &str) -> Vec<Vec<(String, TokenKind)>> {
+        Buffer::new(Tokenizer::with_language(language, Text::from(code).as_lines().len()), code).tokens()
+    }
+
+    fn kinds(line: &[(String, TokenKind)]) -> Vec<TokenKind> {
+        line.iter().map(|(_, kind)| *kind).collect()
+    }
+
+    #[test]
+    fn toml_tokens() {
+        use TokenKind::*;
+        let lines = tokenize(
+            Toml,
+            "[package]\nname = \"demo\" # comment\na.b = true\n\"quoted\" = 1.5e3\nlist = [\n  [1, 2],\n]\ndate = 1979-05-27 07:32:00\ntext = '''\n[not a table]\n'''",
+        );
+        assert_eq!(kinds(&lines[0]), [Typename]);
+        assert_eq!(kinds(&lines[1]), [Identifier, Punctuator, String, Comment]);
+        assert_eq!(kinds(&lines[2]), [Identifier, Punctuator, Identifier, Punctuator, Constant]);
+        assert_eq!(kinds(&lines[3]), [Identifier, Punctuator, Number]);
+        assert_eq!(kinds(&lines[4]), [Identifier, Punctuator, Delimiter]);
+        // Inside an array a `[` at the start of a line is a nested array, not a table header.
+        assert_eq!(kinds(&lines[5]), [Delimiter, Number, Punctuator, Number, Delimiter, Punctuator]);
+        assert_eq!(kinds(&lines[6]), [Delimiter]);
+        assert_eq!(lines[7][2], ("1979-05-27 07:32:00".to_string(), Number));
+        assert_eq!(kinds(&lines[8]), [Identifier, Punctuator, String]);
+        assert_eq!(kinds(&lines[9]), [String]);
+        assert_eq!(kinds(&lines[10]), [String]);
+    }
+
+    #[test]
+    fn json_tokens() {
+        use TokenKind::*;
+        let lines = tokenize(
+            Json,
+            "{\"key\": \"value\", \"n\": -1.5e-3,\n\"flags\": [true, null, bad], // note\n/* open\nstill */ \"x\": 1}",
+        );
+        assert_eq!(
+            kinds(&lines[0]),
+            [Delimiter, Identifier, Punctuator, String, Punctuator, Identifier, Punctuator, Number, Punctuator]
+        );
+        assert_eq!(
+            kinds(&lines[1]),
+            [Identifier, Punctuator, Delimiter, Constant, Punctuator, Constant, Punctuator, Unknown, Delimiter, Punctuator, Comment]
+        );
+        assert_eq!(kinds(&lines[2]), [Comment]);
+        assert_eq!(kinds(&lines[3]), [Comment, Identifier, Punctuator, Number, Delimiter]);
+    }
+
+    #[test]
+    fn markdown_tokens() {
+        use TokenKind::*;
+        let lines = tokenize(
+            Markdown,
+            "# Title\n- item with *emphasis* and `code`\n1. see [link](http://example.com)\n> quote\n---\n```rust\nlet x = 1;\n```\nsnake_case_word",
+        );
+        assert_eq!(kinds(&lines[0]), [Typename]);
+        assert_eq!(kinds(&lines[1]), [Punctuator, Identifier, Identifier, OtherKeyword, Identifier, String]);
+        assert_eq!(kinds(&lines[2]), [Punctuator, Identifier, Function, String]);
+        assert_eq!(kinds(&lines[3]), [Comment]);
+        assert_eq!(kinds(&lines[4]), [Punctuator]);
+        assert_eq!(kinds(&lines[5]), [Punctuator]);
+        assert_eq!(kinds(&lines[6]), [String]);
+        assert_eq!(kinds(&lines[7]), [Punctuator]);
+        assert!(lines[8].iter().all(|(_, kind)| *kind == Identifier));
+    }
+
+    #[test]
+    fn for_path_picks_language() {
+        let kind = |path: &str| Buffer::new(Tokenizer::for_path(path, 1), "# heading").tokens()[0][0].1;
+        assert_eq!(kind("Cargo.toml"), TokenKind::Comment);
+        assert_eq!(kind("docs/README.MD"), TokenKind::Typename);
+        assert_eq!(kind("C:\\src\\main.rs"), TokenKind::Punctuator);
+        assert_eq!(kind("notes.txt"), kind("Makefile"));
+        assert!(Tokenizer::with_extension("json", 1).is_some());
+        assert!(Tokenizer::with_extension("txt", 1).is_none());
+    }
+
+    #[test]
+    fn block_comment_state_carries_after_change() {
+        let mut buffer = Buffer::new(Tokenizer::with_language(Rust, 3), "let a = 1;\nlet b = 2;\nlet c = 3;");
+        let before = buffer.tokens();
+        assert_ne!(kinds(&before[2]), [TokenKind::Comment]);
+
+        // Opening a block comment on the first line turns the following lines into comment.
+        buffer.apply_change(Change::Insert(Position::zero(), Text::from("/* ")));
+        let lines = buffer.tokens();
+        for line in &lines {
+            assert!(line.iter().all(|(_, kind)| *kind == TokenKind::Comment), "{:?}", line);
+        }
+
+        // Closing it at the end of the second line gives the third line its old tokens back.
+        let position = Position { line_index: 1, byte_index: 10 };
+        buffer.apply_change(Change::Insert(position, Text::from(" */")));
+        let lines = buffer.tokens();
+        assert_eq!(kinds(&lines[1]), [TokenKind::Comment]);
+        assert_eq!(lines[2], before[2]);
+
+        // Removing the opening restores the original tokens.
+        buffer.apply_change(Change::Delete(position, Length { line_count: 0, byte_count: 3 }));
+        buffer.apply_change(Change::Delete(Position::zero(), Length { line_count: 0, byte_count: 3 }));
+        assert_eq!(buffer.tokens(), before);
+    }
+
+    #[test]
+    fn fenced_block_state_carries_after_change() {
+        let mut buffer = Buffer::new(Tokenizer::with_language(Markdown, 4), "intro\n*emphasis*\n\n**strong**");
+        let before = buffer.tokens();
+
+        // Inserting an opening fence as a new line turns everything after it into code.
+        let fence = Position { line_index: 1, byte_index: 0 };
+        buffer.apply_change(Change::Insert(fence, Text::from("```\n")));
+        let lines = buffer.tokens();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(kinds(&lines[1]), [TokenKind::Punctuator]);
+        assert_eq!(kinds(&lines[2]), [TokenKind::String]);
+        assert_eq!(kinds(&lines[4]), [TokenKind::String]);
+
+        // Deleting the fence line again re-tokenizes the lines after it.
+        buffer.apply_change(Change::Delete(fence, Length { line_count: 1, byte_count: 0 }));
+        assert_eq!(buffer.tokens(), before);
+    }
+}
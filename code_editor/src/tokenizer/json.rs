@@ -0,0 +1,118 @@
+use crate::{
+    token::TokenKind,
+    tokenizer::{Cursor, Language},
+};
+
+/// JSON, including the `//` and `/* */` comments that JSONC config files allow.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Json;
+
+impl Language for Json {
+    type State = State;
+
+    fn next_token(&self, state: State, cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+        match state {
+            State::Initial => initial(cursor),
+            State::BlockCommentTail => block_comment_tail(cursor),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum State {
+    #[default]
+    Initial,
+    BlockCommentTail,
+}
+
+fn initial(cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    match (cursor.peek(0), cursor.peek(1)) {
+        ('/', '/') => {
+            cursor.skip_to_end();
+            (State::Initial, TokenKind::Comment)
+        }
+        ('/', '*') => {
+            cursor.skip(2);
+            block_comment_tail(cursor)
+        }
+        ('"', _) => (State::Initial, string(cursor)),
+        ('{' | '}' | '[' | ']', _) => {
+            cursor.skip(1);
+            (State::Initial, TokenKind::Delimiter)
+        }
+        (',' | ':', _) => {
+            cursor.skip(1);
+            (State::Initial, TokenKind::Punctuator)
+        }
+        ('-', char) | (char, _) if char.is_ascii_digit() => (State::Initial, number(cursor)),
+        (char, _) if char.is_whitespace() => {
+            cursor.skip_whitespace();
+            (State::Initial, TokenKind::Whitespace)
+        }
+        (char, _) if char.is_alphabetic() => {
+            let start = cursor.index();
+            cursor.skip_while(|char| char.is_alphanumeric());
+            let kind = match cursor.slice_from(start) {
+                "true" | "false" | "null" => TokenKind::Constant,
+                _ => TokenKind::Unknown,
+            };
+            (State::Initial, kind)
+        }
+        _ => {
+            cursor.skip(1);
+            (State::Initial, TokenKind::Unknown)
+        }
+    }
+}
+
+fn block_comment_tail(cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    loop {
+        match (cursor.peek(0), cursor.peek(1)) {
+            ('*', '/') => {
+                cursor.skip(2);
+                break (State::Initial, TokenKind::Comment);
+            }
+            ('\0', _) => break (State::BlockCommentTail, TokenKind::Comment),
+            _ => cursor.skip(1),
+        }
+    }
+}
+
+fn string(cursor: &mut Cursor<'_>) -> TokenKind {
+    debug_assert!(cursor.peek(0) == '"');
+    cursor.skip(1);
+    loop {
+        match cursor.peek(0) {
+            '"' => {
+                cursor.skip(1);
+                break;
+            }
+            '\0' => return TokenKind::Unknown,
+            '\\' => cursor.skip(2),
+            _ => cursor.skip(1),
+        }
+    }
+    // Object keys are strings followed by a colon.
+    if cursor.rest().trim_start().starts_with(':') {
+        TokenKind::Identifier
+    } else {
+        TokenKind::String
+    }
+}
+
+fn number(cursor: &mut Cursor<'_>) -> TokenKind {
+    cursor.skip_if(|char| char == '-');
+    if !cursor.skip_digits(10) {
+        return TokenKind::Unknown;
+    }
+    if cursor.peek(0) == '.' {
+        cursor.skip(1);
+        if !cursor.skip_digits(10) {
+            return TokenKind::Unknown;
+        }
+    }
+    if (cursor.peek(0) == 'e' || cursor.peek(0) == 'E') && !cursor.skip_exponent() {
+        return TokenKind::Unknown;
+    }
+    TokenKind::Number
+}
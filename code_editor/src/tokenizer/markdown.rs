@@ -0,0 +1,226 @@
+use crate::{
+    token::TokenKind,
+    tokenizer::{Cursor, Language},
+};
+
+/// CommonMark style Markdown. Block structure is recognized at the start of a line, and only
+/// fenced code blocks and HTML comments carry over to the next line.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Markdown;
+
+impl Language for Markdown {
+    type State = State;
+
+    fn next_token(&self, state: State, cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+        match state {
+            State::Initial => initial(cursor),
+            State::FencedCode(fence) => fenced_code(fence, cursor),
+            State::HtmlCommentTail => html_comment_tail(cursor),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum State {
+    #[default]
+    Initial,
+    FencedCode(Fence),
+    HtmlCommentTail,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Fence {
+    char: char,
+    len: usize,
+}
+
+fn initial(cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    if cursor.consumed().is_empty() {
+        if let Some(result) = block_start(cursor) {
+            return result;
+        }
+    }
+    match (cursor.peek(0), cursor.peek(1), cursor.peek(2)) {
+        ('<', '!', '-') if cursor.rest().starts_with("<!--") => {
+            cursor.skip(4);
+            html_comment_tail(cursor)
+        }
+        ('`', _, _) => (State::Initial, code_span(cursor)),
+        ('*' | '_', _, _) => (State::Initial, emphasis(cursor)),
+        ('!', '[', _) | ('[', _, _) => (State::Initial, link_text(cursor)),
+        ('(', _, _) if cursor.consumed().ends_with(']') => (State::Initial, link_destination(cursor)),
+        ('\\', char, _) if char.is_ascii_punctuation() => {
+            cursor.skip(2);
+            (State::Initial, TokenKind::Identifier)
+        }
+        (char, _, _) if char.is_whitespace() => {
+            cursor.skip_whitespace();
+            (State::Initial, TokenKind::Whitespace)
+        }
+        (char, _, _) if is_special(char) => {
+            cursor.skip(1);
+            (State::Initial, TokenKind::Punctuator)
+        }
+        _ => {
+            cursor.skip_while(|char| !char.is_whitespace() && !is_special(char));
+            (State::Initial, TokenKind::Identifier)
+        }
+    }
+}
+
+/// Recognizes headings, fences, thematic breaks, block quotes and list markers.
+fn block_start(cursor: &mut Cursor<'_>) -> Option<(State, TokenKind)> {
+    let line = cursor.rest();
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let trimmed = &line[indent..];
+    let first = trimmed.chars().next()?;
+    match first {
+        '#' => {
+            let level = trimmed.chars().take_while(|&char| char == '#').count();
+            let after = &trimmed[level..];
+            if level <= 6 && (after.is_empty() || after.starts_with(' ')) {
+                cursor.skip_to_end();
+                return Some((State::Initial, TokenKind::Typename));
+            }
+        }
+        '`' | '~' => {
+            let len = trimmed.chars().take_while(|&char| char == first).count();
+            if len >= 3 && !(first == '`' && trimmed[len..].contains('`')) {
+                cursor.skip_to_end();
+                return Some((State::FencedCode(Fence { char: first, len }), TokenKind::Punctuator));
+            }
+        }
+        '-' | '*' | '_' if is_thematic_break(trimmed, first) => {
+            cursor.skip_to_end();
+            return Some((State::Initial, TokenKind::Punctuator));
+        }
+        '>' => {
+            cursor.skip_to_end();
+            return Some((State::Initial, TokenKind::Comment));
+        }
+        _ => {}
+    }
+    let marker_len = list_marker_len(trimmed)?;
+    cursor.skip(indent + marker_len);
+    Some((State::Initial, TokenKind::Punctuator))
+}
+
+fn is_thematic_break(line: &str, marker: char) -> bool {
+    line.chars().all(|char| char == marker || char == ' ' || char == '\t')
+        && line.chars().filter(|&char| char == marker).count() >= 3
+}
+
+fn list_marker_len(line: &str) -> Option<usize> {
+    let digit_count = line.chars().take_while(|char| char.is_ascii_digit()).count();
+    let len = match line[digit_count..].chars().next()? {
+        '-' | '*' | '+' if digit_count == 0 => 1,
+        '.' | ')' if (1..=9).contains(&digit_count) => digit_count + 1,
+        _ => return None,
+    };
+    match line[len..].chars().next() {
+        None | Some(' ') | Some('\t') => Some(len),
+        _ => None,
+    }
+}
+
+fn fenced_code(fence: Fence, cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    let line = cursor.rest().trim_start_matches(' ');
+    let len = line.chars().take_while(|&char| char == fence.char).count();
+    let is_closing = cursor.consumed().is_empty()
+        && cursor.rest().len() - line.len() <= 3
+        && len >= fence.len
+        && line[len..].trim().is_empty();
+    cursor.skip_to_end();
+    if is_closing {
+        (State::Initial, TokenKind::Punctuator)
+    } else {
+        (State::FencedCode(fence), TokenKind::String)
+    }
+}
+
+fn html_comment_tail(cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    match cursor.rest().find("-->") {
+        Some(index) => {
+            cursor.skip(cursor.rest()[..index].chars().count() + 3);
+            (State::Initial, TokenKind::Comment)
+        }
+        None => {
+            cursor.skip_to_end();
+            (State::HtmlCommentTail, TokenKind::Comment)
+        }
+    }
+}
+
+fn code_span(cursor: &mut Cursor<'_>) -> TokenKind {
+    let len = cursor.skip_while(|char| char == '`');
+    let fence = "`".repeat(len);
+    let rest = cursor.rest();
+    match rest.find(&fence) {
+        Some(index) => {
+            cursor.skip(rest[..index].chars().count() + len);
+            TokenKind::String
+        }
+        None => TokenKind::Punctuator,
+    }
+}
+
+fn emphasis(cursor: &mut Cursor<'_>) -> TokenKind {
+    let marker = cursor.peek(0);
+    // An underscore inside a word, as in `snake_case`, doesn't open emphasis.
+    let is_intraword = cursor
+        .consumed()
+        .chars()
+        .next_back()
+        .is_some_and(|char| char.is_alphanumeric());
+    let len = cursor.skip_while(|char| char == marker).min(3);
+    if marker == '_' && is_intraword {
+        return TokenKind::Identifier;
+    }
+    let delimiter = marker.to_string().repeat(len);
+    let rest = cursor.rest();
+    match rest.find(&delimiter) {
+        Some(index) if index > 0 && !rest.starts_with(|char: char| char.is_whitespace()) => {
+            cursor.skip(rest[..index].chars().count() + len);
+            TokenKind::OtherKeyword
+        }
+        _ => TokenKind::Punctuator,
+    }
+}
+
+fn link_text(cursor: &mut Cursor<'_>) -> TokenKind {
+    if cursor.peek(0) == '!' {
+        cursor.skip(1);
+    }
+    let rest = cursor.rest();
+    match rest.find(']') {
+        Some(index) => {
+            cursor.skip(rest[..index].chars().count() + 1);
+            TokenKind::Function
+        }
+        None => {
+            cursor.skip(1);
+            TokenKind::Punctuator
+        }
+    }
+}
+
+fn link_destination(cursor: &mut Cursor<'_>) -> TokenKind {
+    let rest = cursor.rest();
+    match rest.find(')') {
+        Some(index) => {
+            cursor.skip(rest[..index].chars().count() + 1);
+            TokenKind::String
+        }
+        None => {
+            cursor.skip(1);
+            TokenKind::Punctuator
+        }
+    }
+}
+
+fn is_special(char: char) -> bool {
+    matches!(char, '`' | '*' | '_' | '[' | ']' | '(' | ')' | '!' | '<' | '\\')
+}
@@ -0,0 +1,34 @@
+use crate::{
+    token::TokenKind,
+    tokenizer::{Cursor, Language},
+};
+
+/// Fallback for files without a dedicated language. Only tells words, whitespace and brackets
+/// apart, so bracket matching still works.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Plain;
+
+impl Language for Plain {
+    type State = ();
+
+    fn next_token(&self, _state: (), cursor: &mut Cursor<'_>) -> ((), TokenKind) {
+        let kind = match cursor.peek(0) {
+            '(' | ')' | '[' | ']' | '{' | '}' => {
+                cursor.skip(1);
+                TokenKind::Delimiter
+            }
+            char if char.is_whitespace() => {
+                cursor.skip_whitespace();
+                TokenKind::Whitespace
+            }
+            _ => {
+                cursor.skip(1);
+                cursor.skip_while(|char| {
+                    !char.is_whitespace() && !matches!(char, '(' | ')' | '[' | ']' | '{' | '}')
+                });
+                TokenKind::Identifier
+            }
+        };
+        ((), kind)
+    }
+}
@@ -0,0 +1,176 @@
+use crate::{
+    token::TokenKind,
+    tokenizer::{Cursor, Language},
+};
+
+/// TOML, as used by `Cargo.toml` and friends.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Toml;
+
+impl Language for Toml {
+    type State = State;
+
+    fn next_token(&self, state: State, cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+        match state.string {
+            Some(string) => string_tail(state, string, cursor),
+            None => initial(state, cursor),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct State {
+    /// Arrays are the only values that may span multiple lines besides strings, and inside them
+    /// a `[` at the start of a line opens a nested array rather than a table header.
+    array_depth: usize,
+    string: Option<MultiLineString>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum MultiLineString {
+    Basic,
+    Literal,
+}
+
+fn initial(mut state: State, cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    let at_line_start = cursor.consumed().trim().is_empty();
+    match (cursor.peek(0), cursor.peek(1), cursor.peek(2)) {
+        ('#', _, _) => {
+            cursor.skip_to_end();
+            (state, TokenKind::Comment)
+        }
+        ('[', _, _) if at_line_start && state.array_depth == 0 => {
+            cursor.skip_while(|char| char != ']');
+            cursor.skip_while(|char| char == ']');
+            (state, TokenKind::Typename)
+        }
+        ('"', '"', '"') => {
+            cursor.skip(3);
+            string_tail(state, MultiLineString::Basic, cursor)
+        }
+        ('\'', '\'', '\'') => {
+            cursor.skip(3);
+            string_tail(state, MultiLineString::Literal, cursor)
+        }
+        ('"' | '\'', _, _) => (state, string(cursor)),
+        ('[', _, _) => {
+            cursor.skip(1);
+            state.array_depth += 1;
+            (state, TokenKind::Delimiter)
+        }
+        (']', _, _) => {
+            cursor.skip(1);
+            state.array_depth = state.array_depth.saturating_sub(1);
+            (state, TokenKind::Delimiter)
+        }
+        ('{' | '}', _, _) => {
+            cursor.skip(1);
+            (state, TokenKind::Delimiter)
+        }
+        ('=' | ',' | '.', _, _) => {
+            cursor.skip(1);
+            (state, TokenKind::Punctuator)
+        }
+        (char, _, _) if char.is_whitespace() => {
+            cursor.skip_whitespace();
+            (state, TokenKind::Whitespace)
+        }
+        (char, _, _) if is_bare_key_char(char) || char == '+' => (state, bare(cursor)),
+        _ => {
+            cursor.skip(1);
+            (state, TokenKind::Unknown)
+        }
+    }
+}
+
+fn string_tail(
+    mut state: State,
+    string: MultiLineString,
+    cursor: &mut Cursor<'_>,
+) -> (State, TokenKind) {
+    let quote = match string {
+        MultiLineString::Basic => '"',
+        MultiLineString::Literal => '\'',
+    };
+    loop {
+        match cursor.peek(0) {
+            '\\' if string == MultiLineString::Basic => cursor.skip(2),
+            char if char == quote && cursor.peek(1) == quote && cursor.peek(2) == quote => {
+                cursor.skip(3);
+                // Up to two more quotes are allowed to end up inside the string.
+                cursor.skip_if(|char| char == quote);
+                cursor.skip_if(|char| char == quote);
+                state.string = None;
+                break (state, TokenKind::String);
+            }
+            '\0' => {
+                state.string = Some(string);
+                break (state, TokenKind::String);
+            }
+            _ => cursor.skip(1),
+        }
+    }
+}
+
+fn string(cursor: &mut Cursor<'_>) -> TokenKind {
+    let quote = cursor.peek(0);
+    cursor.skip(1);
+    loop {
+        match cursor.peek(0) {
+            char if char == quote => {
+                cursor.skip(1);
+                break;
+            }
+            '\0' => return TokenKind::Unknown,
+            '\\' if quote == '"' => cursor.skip(2),
+            _ => cursor.skip(1),
+        }
+    }
+    if is_followed_by_key_separator(cursor.rest()) {
+        TokenKind::Identifier
+    } else {
+        TokenKind::String
+    }
+}
+
+/// Bare keys, and the unquoted values: booleans, numbers and dates.
+fn bare(cursor: &mut Cursor<'_>) -> TokenKind {
+    let start = cursor.index();
+    cursor.skip_while(is_bare_key_char);
+    if is_followed_by_key_separator(cursor.rest()) {
+        return TokenKind::Identifier;
+    }
+    // Numbers and dates may contain characters that can't occur in a bare key.
+    cursor.skip_while(|char| is_bare_key_char(char) || matches!(char, '+' | '.' | ':'));
+    if cursor.peek(0) == ' ' && cursor.peek(1).is_ascii_digit() && is_date(cursor.slice_from(start)) {
+        // A date and time may be separated by a space instead of a `T`.
+        cursor.skip(1);
+        cursor.skip_while(|char| is_bare_key_char(char) || matches!(char, '+' | '.' | ':'));
+    }
+    match cursor.slice_from(start) {
+        "true" | "false" => TokenKind::Constant,
+        "inf" | "+inf" | "-inf" | "nan" | "+nan" | "-nan" => TokenKind::Number,
+        string if string.starts_with(|char: char| char.is_ascii_digit() || char == '+' || char == '-') => {
+            TokenKind::Number
+        }
+        _ => TokenKind::Unknown,
+    }
+}
+
+fn is_bare_key_char(char: char) -> bool {
+    char.is_ascii_alphanumeric() || char == '_' || char == '-'
+}
+
+fn is_date(string: &str) -> bool {
+    let bytes = string.as_bytes();
+    bytes.len() == 10 && bytes[4] == b'-' && bytes[7] == b'-'
+}
+
+/// Whether a key ends here, i.e. the rest of the line continues with `=` or a dotted key.
+fn is_followed_by_key_separator(rest: &str) -> bool {
+    let rest = rest.trim_start();
+    rest.starts_with('=')
+        || rest
+            .strip_prefix('.')
+            .is_some_and(|rest| !rest.starts_with(|char: char| char.is_ascii_digit()))
+}
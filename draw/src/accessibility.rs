@@ -0,0 +1,351 @@
+use {
+    std::rc::Rc,
+    std::cell::RefCell,
+    crate::{
+        cx_2d::Cx2d,
+        makepad_platform::{
+            AccessAction,
+            Area,
+            DrawListId,
+            Rect,
+            Cx,
+        },
+    }
+};
+
+/// The semantic information widgets report while they draw, kept per draw list like the
+/// nav tree so that draw lists which are not redrawn keep their nodes.
+#[derive(Default)]
+pub struct CxAccessTree {
+    access_lists: Vec<CxAccessList>
+}
+
+#[derive(Clone)]
+pub struct CxAccessTreeRc(pub Rc<RefCell<CxAccessTree >>);
+
+#[derive(Debug, Default, Clone)]
+pub struct CxAccessList {
+    pub access_list: Vec<AccessItem>
+}
+
+impl std::ops::Index<DrawListId> for CxAccessTree {
+    type Output = CxAccessList;
+    fn index(&self, index: DrawListId) -> &Self::Output {
+        &self.access_lists[index.index()]
+    }
+}
+
+impl std::ops::IndexMut<DrawListId> for CxAccessTree {
+    fn index_mut(&mut self, index: DrawListId) -> &mut Self::Output {
+        &mut self.access_lists[index.index()]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AccessRole {
+    Window,
+    Group,
+    Label,
+    Button,
+    Link,
+    CheckBox,
+    RadioButton,
+    Slider,
+    TextInput,
+    DropDown,
+    List,
+    ListItem,
+    Table,
+    TableRow,
+    ColumnHeader,
+    Image,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum AccessValue {
+    #[default]
+    None,
+    Text(String),
+    Number {value: f64, min: f64, max: f64, step: f64},
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AccessStates {
+    pub disabled: bool,
+    /// `None` when the node can't be checked at all.
+    pub checked: Option<bool>,
+    pub selected: bool,
+    pub editable: bool,
+    pub multiline: bool,
+    pub password: bool,
+    pub focusable: bool,
+    /// Filled in from the key focus when the tree is built.
+    pub focused: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AccessActions {
+    pub click: bool,
+    pub focus: bool,
+    pub increment: bool,
+    pub decrement: bool,
+    pub set_value: bool,
+    pub scroll_into_view: bool,
+}
+
+impl AccessActions {
+    pub fn supports(&self, action: &AccessAction) -> bool {
+        match action {
+            AccessAction::Click => self.click,
+            AccessAction::Focus => self.focus,
+            AccessAction::Increment => self.increment,
+            AccessAction::Decrement => self.decrement,
+            AccessAction::SetValue(_) => self.set_value,
+            AccessAction::ScrollIntoView => self.scroll_into_view,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AccessNode {
+    /// Identifies the widget across redraws, usually its `WidgetUid`.
+    pub uid: u64,
+    pub role: AccessRole,
+    pub name: String,
+    pub value: AccessValue,
+    pub states: AccessStates,
+    pub actions: AccessActions,
+    pub area: Area,
+}
+
+impl AccessNode {
+    pub fn new(uid: u64, role: AccessRole, area: Area) -> Self {
+        Self {
+            uid,
+            role,
+            name: String::new(),
+            value: AccessValue::None,
+            states: AccessStates::default(),
+            actions: AccessActions::default(),
+            area
+        }
+    }
+
+    pub fn with_name(self, name: impl Into<String>) -> Self {Self {name: name.into(), ..self}}
+    pub fn with_value(self, value: AccessValue) -> Self {Self {value, ..self}}
+    pub fn with_states(self, states: AccessStates) -> Self {Self {states, ..self}}
+    pub fn with_actions(self, actions: AccessActions) -> Self {Self {actions, ..self}}
+}
+
+#[derive(Debug, Clone)]
+pub enum AccessItem {
+    Child(DrawListId),
+    Node(AccessNode),
+    BeginGroup(AccessNode),
+    EndGroup
+}
+
+pub struct AccessGroupIndex(usize);
+
+/// A snapshot of the access lists of a window, with the bounds and focus resolved.
+#[derive(Clone, Debug, Default)]
+pub struct AccessTree {
+    pub nodes: Vec<AccessTreeNode>,
+    pub focus: Option<usize>,
+}
+
+#[derive(Clone, Debug)]
+pub struct AccessTreeNode {
+    pub node: AccessNode,
+    pub rect: Rect,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+}
+
+impl AccessTree {
+    pub fn new(root: AccessNode, rect: Rect) -> Self {
+        Self {
+            nodes: vec![AccessTreeNode {node: root, rect, parent: None, children: Vec::new()}],
+            focus: None
+        }
+    }
+
+    pub fn root(&self) -> &AccessTreeNode {
+        &self.nodes[0]
+    }
+
+    pub fn find(&self, role: AccessRole, name: &str) -> Option<usize> {
+        self.nodes.iter().position( | n | n.node.role == role && n.node.name == name)
+    }
+
+    pub fn find_uid(&self, uid: u64) -> Option<usize> {
+        self.nodes.iter().position( | n | n.node.uid == uid)
+    }
+
+    pub fn children_of(&self, index: usize) -> impl Iterator<Item = &AccessTreeNode> {
+        self.nodes[index].children.iter().map(move | child | &self.nodes[*child])
+    }
+
+    /// Sends `action` to the widget behind the node, if the node supports it.
+    pub fn perform(&self, cx: &mut Cx, index: usize, action: AccessAction) -> bool {
+        let Some(tree_node) = self.nodes.get(index) else {return false};
+        if !tree_node.node.actions.supports(&action) || tree_node.node.area.is_empty() {
+            return false
+        }
+        cx.send_access_action(tree_node.node.area, action);
+        true
+    }
+
+    fn push<R>(&mut self, parent: usize, node: &AccessNode, resolve: &mut R) -> usize
+    where R: FnMut(&AccessNode) -> (Rect, bool) {
+        let (rect, focused) = resolve(node);
+        let index = self.nodes.len();
+        let mut node = node.clone();
+        node.states.focused = focused;
+        if focused {
+            self.focus = Some(index);
+        }
+        self.nodes.push(AccessTreeNode {node, rect, parent: Some(parent), children: Vec::new()});
+        self.nodes[parent].children.push(index);
+        index
+    }
+
+    fn append_list<'a, F, R>(&mut self, items: &'a [AccessItem], parents: &mut Vec<usize>, child_list: &F, resolve: &mut R)
+    where F: Fn(DrawListId) -> Option<&'a [AccessItem]>, R: FnMut(&AccessNode) -> (Rect, bool) {
+        for item in items {
+            match item {
+                AccessItem::Child(draw_list_id) => if let Some(items) = child_list(*draw_list_id) {
+                    self.append_list(items, parents, child_list, resolve);
+                }
+                AccessItem::Node(node) => {
+                    self.push(*parents.last().unwrap(), node, resolve);
+                }
+                AccessItem::BeginGroup(node) => {
+                    let index = self.push(*parents.last().unwrap(), node, resolve);
+                    parents.push(index);
+                }
+                AccessItem::EndGroup => if parents.len() > 1 {
+                    parents.pop();
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Cx2d<'a> {
+
+    pub fn lazy_construct_access_tree(cx: &mut Cx) {
+        if !cx.has_global::<CxAccessTreeRc>() {
+            cx.set_global(CxAccessTreeRc(Rc::new(RefCell::new(CxAccessTree::default()))));
+        }
+    }
+
+    /// Builds the accessibility tree of everything drawn below `root`, under a node for the
+    /// window itself.
+    pub fn access_tree(cx: &mut Cx, root: DrawListId, window: AccessNode, rect: Rect) -> AccessTree {
+        Self::lazy_construct_access_tree(cx);
+        let access_tree_rc = cx.get_global::<CxAccessTreeRc>().clone();
+        let access_tree = &*access_tree_rc.0.borrow();
+        let mut tree = AccessTree::new(window, rect);
+        let child_list = | draw_list_id: DrawListId | access_tree.access_lists
+            .get(draw_list_id.index())
+            .map( | list | list.access_list.as_slice());
+        if let Some(items) = child_list(root) {
+            tree.append_list(items, &mut vec![0], &child_list, &mut | node: &AccessNode | {
+                let rect = if node.area.is_valid(cx) {node.area.rect(cx)} else {Rect::default()};
+                (rect, !node.area.is_empty() && cx.has_key_focus(node.area))
+            });
+        }
+        tree
+    }
+
+    pub fn access_list_clear(&mut self, draw_list_id: DrawListId) {
+        let mut access_tree = self.access_tree_rc.0.borrow_mut();
+        if draw_list_id.index() >= access_tree.access_lists.len() {
+            access_tree.access_lists.resize(draw_list_id.index() + 1, Default::default());
+        }
+        access_tree[draw_list_id].access_list.clear();
+    }
+
+    pub fn access_list_item_push(&mut self, draw_list_id: DrawListId, item: AccessItem) {
+        let mut access_tree = self.access_tree_rc.0.borrow_mut();
+        access_tree[draw_list_id].access_list.push(item);
+    }
+
+    pub fn add_access_node(&mut self, node: AccessNode) {
+        let draw_list_id = *self.draw_list_stack.last().unwrap();
+        self.access_list_item_push(draw_list_id, AccessItem::Node(node));
+    }
+
+    /// Starts a node whose children are the nodes added until `end_access_group`. Its area
+    /// usually isn't known until then.
+    pub fn begin_access_group(&mut self, node: AccessNode) -> AccessGroupIndex {
+        let mut access_tree = self.access_tree_rc.0.borrow_mut();
+        let draw_list_id = *self.draw_list_stack.last().unwrap();
+        let index = AccessGroupIndex(access_tree[draw_list_id].access_list.len());
+        access_tree[draw_list_id].access_list.push(AccessItem::BeginGroup(node));
+        index
+    }
+
+    pub fn end_access_group(&mut self, index: AccessGroupIndex, area: Area) {
+        let mut access_tree = self.access_tree_rc.0.borrow_mut();
+        let draw_list_id = *self.draw_list_stack.last().unwrap();
+        let access_list = &mut access_tree[draw_list_id].access_list;
+        if let Some(AccessItem::BeginGroup(node)) = access_list.get_mut(index.0) {
+            node.area = area;
+        }
+        access_list.push(AccessItem::EndGroup);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::makepad_math::DVec2;
+
+    fn node(uid: u64, role: AccessRole, name: &str) -> AccessNode {
+        AccessNode::new(uid, role, Area::Empty).with_name(name)
+    }
+
+    fn build(items: &[AccessItem], focus_uid: u64) -> AccessTree {
+        let mut tree = AccessTree::new(node(0, AccessRole::Window, "app"), Rect::default());
+        tree.append_list(items, &mut vec![0], &| _ | None, &mut | node: &AccessNode | {
+            let rect = Rect {pos: DVec2 {x: node.uid as f64, y: 0.0}, size: DVec2 {x: 10.0, y: 10.0}};
+            (rect, node.uid == focus_uid)
+        });
+        tree
+    }
+
+    #[test]
+    fn groups_nest_their_nodes() {
+        let tree = build(&[
+            AccessItem::Node(node(1, AccessRole::Label, "Name")),
+            AccessItem::BeginGroup(node(2, AccessRole::List, "")),
+            AccessItem::Node(node(3, AccessRole::Button, "First")),
+            AccessItem::Node(node(4, AccessRole::Button, "Second")),
+            AccessItem::EndGroup,
+            AccessItem::Node(node(5, AccessRole::TextInput, "")),
+        ], 4);
+        let roles: Vec<_> = tree.children_of(0).map( | n | n.node.role).collect();
+        assert_eq!(roles, vec![AccessRole::Label, AccessRole::List, AccessRole::TextInput]);
+        let list = tree.find_uid(2).unwrap();
+        let names: Vec<_> = tree.children_of(list).map( | n | n.node.name.as_str()).collect();
+        assert_eq!(names, vec!["First", "Second"]);
+
+        let second = tree.find(AccessRole::Button, "Second").unwrap();
+        assert_eq!(tree.nodes[second].parent, Some(list));
+        assert_eq!(tree.nodes[second].rect.pos.x, 4.0);
+        assert_eq!(tree.focus, Some(second));
+        assert!(tree.nodes[second].node.states.focused);
+    }
+
+    #[test]
+    fn unbalanced_group_ends_stop_at_the_root() {
+        let tree = build(&[
+            AccessItem::EndGroup,
+            AccessItem::Node(node(1, AccessRole::Label, "Still here")),
+        ], 0);
+        assert_eq!(tree.root().children.len(), 1);
+        assert_eq!(tree.focus, None);
+    }
+}
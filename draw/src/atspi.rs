@@ -0,0 +1,277 @@
+// AT-SPI mapping: maps the accessibility tree onto the object model of AT-SPI, the accessibility
+// bus of the Linux desktop, with roles and states numbered as in atspi-constants.h. Nothing is
+// registered on the bus yet; serving these nodes over D-Bus is left to a bridge to be added later.
+use {
+    crate::{
+        accessibility::{AccessTree, AccessRole, AccessValue},
+        makepad_math::DVec2,
+        makepad_platform::{AccessAction, Cx},
+    }
+};
+
+pub const ATSPI_ROOT_PATH: &str = "/org/a11y/atspi/accessible/root";
+pub const ATSPI_PATH_PREFIX: &str = "/org/a11y/atspi/accessible/";
+
+pub const ATSPI_STATE_ACTIVE: u32 = 1;
+pub const ATSPI_STATE_CHECKED: u32 = 4;
+pub const ATSPI_STATE_EDITABLE: u32 = 7;
+pub const ATSPI_STATE_ENABLED: u32 = 8;
+pub const ATSPI_STATE_FOCUSABLE: u32 = 11;
+pub const ATSPI_STATE_FOCUSED: u32 = 12;
+pub const ATSPI_STATE_MULTI_LINE: u32 = 17;
+pub const ATSPI_STATE_SELECTABLE: u32 = 22;
+pub const ATSPI_STATE_SELECTED: u32 = 23;
+pub const ATSPI_STATE_SENSITIVE: u32 = 24;
+pub const ATSPI_STATE_SHOWING: u32 = 25;
+pub const ATSPI_STATE_SINGLE_LINE: u32 = 26;
+pub const ATSPI_STATE_VISIBLE: u32 = 30;
+pub const ATSPI_STATE_CHECKABLE: u32 = 41;
+
+impl AccessRole {
+    pub fn atspi_role(&self) -> u32 {
+        match self {
+            Self::Window => 69,
+            Self::Group => 39,
+            Self::Label => 29,
+            Self::Button => 43,
+            Self::Link => 88,
+            Self::CheckBox => 7,
+            Self::RadioButton => 44,
+            Self::Slider => 51,
+            Self::TextInput => 79,
+            Self::DropDown => 11,
+            Self::List => 31,
+            Self::ListItem => 32,
+            Self::Table => 55,
+            Self::TableRow => 90,
+            Self::ColumnHeader => 57,
+            Self::Image => 27,
+        }
+    }
+
+    pub fn atspi_role_name(&self) -> &'static str {
+        match self {
+            Self::Window => "window",
+            Self::Group => "panel",
+            Self::Label => "label",
+            Self::Button => "push button",
+            Self::Link => "link",
+            Self::CheckBox => "check box",
+            Self::RadioButton => "radio button",
+            Self::Slider => "slider",
+            Self::TextInput => "entry",
+            Self::DropDown => "combo box",
+            Self::List => "list",
+            Self::ListItem => "list item",
+            Self::Table => "table",
+            Self::TableRow => "table row",
+            Self::ColumnHeader => "table column header",
+            Self::Image => "image",
+        }
+    }
+}
+
+/// The state set as AT-SPI encodes it, two words of flags indexed by state number.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AtspiStateSet(pub [u32; 2]);
+
+impl AtspiStateSet {
+    pub fn insert(&mut self, state: u32) {
+        self.0[state as usize / 32] |= 1 << (state % 32);
+    }
+
+    pub fn contains(&self, state: u32) -> bool {
+        self.0[state as usize / 32] & (1 << (state % 32)) != 0
+    }
+}
+
+/// The `org.a11y.atspi.Value` interface of a node.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AtspiValue {
+    pub current: f64,
+    pub minimum: f64,
+    pub maximum: f64,
+    pub minimum_increment: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct AtspiNode {
+    pub path: String,
+    pub parent: Option<String>,
+    pub children: Vec<String>,
+    pub role: u32,
+    pub role_name: &'static str,
+    pub name: String,
+    pub states: AtspiStateSet,
+    /// x, y, width and height in screen pixels.
+    pub extents: [i32; 4],
+    /// The action names of the `org.a11y.atspi.Action` interface, in `DoAction` order.
+    pub actions: Vec<&'static str>,
+    pub text: Option<String>,
+    pub value: Option<AtspiValue>,
+}
+
+impl AccessTree {
+    /// Object paths use the widget uid so they stay the same across rebuilds of the tree.
+    pub fn atspi_path(&self, index: usize) -> String {
+        if index == 0 {
+            return ATSPI_ROOT_PATH.to_string()
+        }
+        format!("{}{}", ATSPI_PATH_PREFIX, self.nodes[index].node.uid)
+    }
+
+    pub fn atspi_find(&self, path: &str) -> Option<usize> {
+        if path == ATSPI_ROOT_PATH {
+            return Some(0)
+        }
+        let uid = path.strip_prefix(ATSPI_PATH_PREFIX)?.parse().ok()?;
+        self.find_uid(uid).filter( | index | *index != 0)
+    }
+
+    fn atspi_actions(&self, index: usize) -> Vec<(&'static str, AccessAction)> {
+        let actions = self.nodes[index].node.actions;
+        let mut out = Vec::new();
+        if actions.click {
+            out.push(("click", AccessAction::Click));
+        }
+        if actions.increment {
+            out.push(("increment", AccessAction::Increment));
+        }
+        if actions.decrement {
+            out.push(("decrement", AccessAction::Decrement));
+        }
+        out
+    }
+
+    /// Maps every node, with extents placed by the window position and dpi factor.
+    pub fn to_atspi(&self, window_pos: DVec2, dpi_factor: f64) -> Vec<AtspiNode> {
+        let mut out = Vec::with_capacity(self.nodes.len());
+        for (index, tree_node) in self.nodes.iter().enumerate() {
+            let node = &tree_node.node;
+            let mut states = AtspiStateSet::default();
+            states.insert(ATSPI_STATE_VISIBLE);
+            states.insert(ATSPI_STATE_SHOWING);
+            if !node.states.disabled {
+                states.insert(ATSPI_STATE_ENABLED);
+                states.insert(ATSPI_STATE_SENSITIVE);
+            }
+            if let Some(checked) = node.states.checked {
+                states.insert(ATSPI_STATE_CHECKABLE);
+                if checked {
+                    states.insert(ATSPI_STATE_CHECKED);
+                }
+            }
+            if node.states.selected {
+                states.insert(ATSPI_STATE_SELECTABLE);
+                states.insert(ATSPI_STATE_SELECTED);
+            }
+            if node.states.editable {
+                states.insert(ATSPI_STATE_EDITABLE);
+                states.insert(if node.states.multiline {ATSPI_STATE_MULTI_LINE} else {ATSPI_STATE_SINGLE_LINE});
+            }
+            if node.states.focusable || node.actions.focus {
+                states.insert(ATSPI_STATE_FOCUSABLE);
+            }
+            if node.states.focused {
+                states.insert(ATSPI_STATE_FOCUSED);
+            }
+            if index == 0 && self.focus.is_some() {
+                states.insert(ATSPI_STATE_ACTIVE);
+            }
+            let pos = (window_pos + tree_node.rect.pos) * dpi_factor;
+            let size = tree_node.rect.size * dpi_factor;
+            let (text, value) = match &node.value {
+                AccessValue::None => (None, None),
+                // passwords are not read out
+                AccessValue::Text(text) => (Some(if node.states.password {String::new()} else {text.clone()}), None),
+                AccessValue::Number {value, min, max, step} => (None, Some(AtspiValue {
+                    current: *value,
+                    minimum: *min,
+                    maximum: *max,
+                    minimum_increment: *step
+                })),
+            };
+            out.push(AtspiNode {
+                path: self.atspi_path(index),
+                parent: tree_node.parent.map( | parent | self.atspi_path(parent)),
+                children: tree_node.children.iter().map( | child | self.atspi_path(*child)).collect(),
+                role: node.role.atspi_role(),
+                role_name: node.role.atspi_role_name(),
+                name: node.name.clone(),
+                states,
+                extents: [pos.x as i32, pos.y as i32, size.x as i32, size.y as i32],
+                actions: self.atspi_actions(index).into_iter().map( | (name, _) | name).collect(),
+                text,
+                value,
+            });
+        }
+        out
+    }
+
+    /// What `org.a11y.atspi.Action.DoAction` does for the node with the given path.
+    pub fn atspi_do_action(&self, cx: &mut Cx, path: &str, action_index: usize) -> bool {
+        let Some(index) = self.atspi_find(path) else {return false};
+        match self.atspi_actions(index).into_iter().nth(action_index) {
+            Some((_, action)) => self.perform(cx, index, action),
+            None => false
+        }
+    }
+
+    /// What `org.a11y.atspi.Component.GrabFocus` does for the node with the given path.
+    pub fn atspi_grab_focus(&self, cx: &mut Cx, path: &str) -> bool {
+        let Some(index) = self.atspi_find(path) else {return false};
+        self.perform(cx, index, AccessAction::Focus)
+    }
+
+    /// What `org.a11y.atspi.Value.SetCurrentValue` does for the node with the given path.
+    pub fn atspi_set_current_value(&self, cx: &mut Cx, path: &str, value: f64) -> bool {
+        let Some(index) = self.atspi_find(path) else {return false};
+        self.perform(cx, index, AccessAction::SetValue(value.to_string()))
+    }
+
+    /// What `org.a11y.atspi.EditableText.SetTextContents` does for the node with the given path.
+    pub fn atspi_set_text_contents(&self, cx: &mut Cx, path: &str, text: &str) -> bool {
+        let Some(index) = self.atspi_find(path) else {return false};
+        self.perform(cx, index, AccessAction::SetValue(text.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accessibility::{AccessNode, AccessStates, AccessActions};
+    use crate::makepad_platform::{Area, Rect};
+
+    #[test]
+    fn nodes_map_role_states_and_extents() {
+        let mut tree = AccessTree::new(AccessNode::new(0, AccessRole::Window, Area::Empty).with_name("app"), Rect::default());
+        let check = AccessNode::new(7, AccessRole::CheckBox, Area::Empty)
+            .with_name("Dark mode")
+            .with_states(AccessStates {checked: Some(true), focused: true, ..Default::default()})
+            .with_actions(AccessActions {click: true, focus: true, ..Default::default()});
+        tree.nodes.push(crate::accessibility::AccessTreeNode {
+            node: check,
+            rect: Rect {pos: DVec2 {x: 10.0, y: 20.0}, size: DVec2 {x: 100.0, y: 16.0}},
+            parent: Some(0),
+            children: Vec::new(),
+        });
+        tree.nodes[0].children.push(1);
+        tree.focus = Some(1);
+
+        let nodes = tree.to_atspi(DVec2 {x: 5.0, y: 0.0}, 2.0);
+        assert_eq!(nodes[0].path, ATSPI_ROOT_PATH);
+        assert_eq!(nodes[0].children, vec![format!("{}7", ATSPI_PATH_PREFIX)]);
+        assert!(nodes[0].states.contains(ATSPI_STATE_ACTIVE));
+
+        let check = &nodes[1];
+        assert_eq!((check.role, check.role_name), (7, "check box"));
+        assert_eq!(check.parent.as_deref(), Some(ATSPI_ROOT_PATH));
+        assert_eq!(check.extents, [30, 40, 200, 32]);
+        assert_eq!(check.actions, vec!["click"]);
+        for state in [ATSPI_STATE_CHECKABLE, ATSPI_STATE_CHECKED, ATSPI_STATE_FOCUSABLE, ATSPI_STATE_FOCUSED, ATSPI_STATE_ENABLED] {
+            assert!(check.states.contains(state));
+        }
+        assert!(!check.states.contains(ATSPI_STATE_EDITABLE));
+        assert_eq!(tree.atspi_find(&check.path), Some(1));
+    }
+}
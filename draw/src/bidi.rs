@@ -0,0 +1,311 @@
+use {
+    std::ops::Range,
+    unicode_bidi::{bidi_class, BidiClass, BidiInfo, Level},
+};
+
+/// The direction a paragraph of text is laid out in.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum BaseDirection {
+    /// Picks the direction of the first strong character of each paragraph (rules P2 and P3).
+    #[default]
+    Auto,
+    LeftToRight,
+    RightToLeft,
+}
+
+/// The result of running the Unicode Bidirectional Algorithm over a text, by character.
+///
+/// Every line is a paragraph of its own, and is reordered on its own. A `\n` keeps its place, so
+/// visual indices of the characters of a line have the same range as their logical indices, and
+/// cursor positions can be mapped between the two without changing lines.
+#[derive(Clone, Debug, Default)]
+pub struct BidiText {
+    levels: Vec<Level>,
+    // the character ranges of the lines, without the `\n`
+    lines: Vec<Range<usize>>,
+    visual_to_logical: Vec<usize>,
+    logical_to_visual: Vec<usize>,
+    has_rtl: bool,
+}
+
+impl BidiText {
+    pub fn new(text: &str, direction: BaseDirection) -> Self {
+        let para_level = match direction {
+            BaseDirection::Auto => None,
+            BaseDirection::LeftToRight => Some(Level::ltr()),
+            BaseDirection::RightToLeft => Some(Level::rtl()),
+        };
+        let mut bidi = Self::default();
+        for line in text.split('\n') {
+            let start = bidi.levels.len();
+            if !bidi.lines.is_empty() {
+                // the `\n` before this line
+                bidi.levels.push(Level::ltr());
+                bidi.visual_to_logical.push(start);
+            }
+            let start = bidi.levels.len();
+            let info = BidiInfo::new(line, para_level);
+            let mut levels = info.levels.clone();
+            for para in &info.paragraphs {
+                let reordered = info.reordered_levels(para, para.range.clone());
+                levels[para.range.clone()].copy_from_slice(&reordered[para.range.clone()]);
+            }
+            for para in &info.paragraphs {
+                let para_start = bidi.levels.len();
+                let para_levels: Vec<Level> = line[para.range.clone()]
+                    .char_indices()
+                    .map(|(index, _)| levels[para.range.start + index])
+                    .collect();
+                bidi.visual_to_logical.extend(
+                    BidiInfo::reorder_visual(&para_levels)
+                        .into_iter()
+                        .map(|index| para_start + index),
+                );
+                bidi.levels.extend(para_levels);
+            }
+            bidi.lines.push(start..bidi.levels.len());
+        }
+        bidi.has_rtl = bidi.levels.iter().any(|level| level.is_rtl());
+        bidi.logical_to_visual = vec![0; bidi.visual_to_logical.len()];
+        for (visual, logical) in bidi.visual_to_logical.iter().enumerate() {
+            bidi.logical_to_visual[*logical] = visual;
+        }
+        bidi
+    }
+
+    pub fn char_count(&self) -> usize {
+        self.levels.len()
+    }
+
+    pub fn has_rtl(&self) -> bool {
+        self.has_rtl
+    }
+
+    /// The resolved embedding level of a character, after the line rules (L1).
+    pub fn level(&self, index: usize) -> Level {
+        self.levels[index]
+    }
+
+    pub fn is_rtl(&self, index: usize) -> bool {
+        self.levels[index].is_rtl()
+    }
+
+    /// Characters in visual order, as their logical indices.
+    pub fn visual_order(&self) -> &[usize] {
+        &self.visual_to_logical
+    }
+
+    pub fn visual_index(&self, logical: usize) -> usize {
+        self.logical_to_visual[logical]
+    }
+
+    pub fn logical_index(&self, visual: usize) -> usize {
+        self.visual_to_logical[visual]
+    }
+
+    fn line_of_cursor(&self, cursor: usize) -> Range<usize> {
+        let index = self.lines.partition_point(|line| line.end < cursor);
+        self.lines[index.min(self.lines.len() - 1)].clone()
+    }
+
+    /// Maps a cursor, which sits in front of the character with its logical index, to the
+    /// position between visual indices it is drawn at. The cursor sticks to the character after
+    /// it, or to the one before it at the end of a line.
+    pub fn visual_cursor(&self, cursor: usize) -> usize {
+        let line = self.line_of_cursor(cursor);
+        if cursor < line.end {
+            self.logical_to_visual[cursor] + self.is_rtl(cursor) as usize
+        }
+        else if line.end > line.start {
+            self.logical_to_visual[cursor - 1] + !self.is_rtl(cursor - 1) as usize
+        }
+        else {
+            cursor
+        }
+    }
+
+    /// The inverse of `visual_cursor`, used to find the cursor for a point between two glyphs.
+    pub fn logical_cursor(&self, visual: usize) -> usize {
+        let line = self.line_of_cursor(visual);
+        if visual < line.end {
+            let index = self.visual_to_logical[visual];
+            index + self.is_rtl(index) as usize
+        }
+        else if line.end > line.start {
+            let index = self.visual_to_logical[visual - 1];
+            index + !self.is_rtl(index) as usize
+        }
+        else {
+            visual
+        }
+    }
+
+    /// Moves a cursor one position to the left on screen, going to the end of the previous line
+    /// from the start of a line.
+    pub fn cursor_left(&self, cursor: usize) -> usize {
+        let line = self.line_of_cursor(cursor);
+        let mut visual = self.visual_cursor(cursor);
+        while visual > line.start {
+            visual -= 1;
+            let moved = self.logical_cursor(visual);
+            if moved != cursor {
+                return moved
+            }
+        }
+        if line.start > 0 {line.start - 1} else {cursor}
+    }
+
+    /// Moves a cursor one position to the right on screen, going to the start of the next line
+    /// from the end of a line.
+    pub fn cursor_right(&self, cursor: usize) -> usize {
+        let line = self.line_of_cursor(cursor);
+        let mut visual = self.visual_cursor(cursor);
+        while visual < line.end {
+            visual += 1;
+            let moved = self.logical_cursor(visual);
+            if moved != cursor {
+                return moved
+            }
+        }
+        if line.end < self.char_count() {line.end + 1} else {cursor}
+    }
+
+    /// The ranges of visual indices that a range of logical indices covers, from left to right.
+    /// A selection in mixed text can be split up into several of them.
+    pub fn visual_ranges(&self, range: Range<usize>) -> Vec<Range<usize>> {
+        let mut visual: Vec<usize> = range.map(|index| self.logical_to_visual[index]).collect();
+        visual.sort_unstable();
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        for index in visual {
+            match ranges.last_mut() {
+                Some(range) if range.end == index => range.end += 1,
+                _ => ranges.push(index..index + 1),
+            }
+        }
+        ranges
+    }
+}
+
+/// Whether a character is removed by rule X9 of the algorithm, so it has no level of its own and
+/// does not take part in reordering.
+pub fn is_removed_by_x9(c: char) -> bool {
+    matches!(
+        bidi_class(c),
+        BidiClass::RLE | BidiClass::LRE | BidiClass::RLO | BidiClass::LRO | BidiClass::PDF | BidiClass::BN
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Runs test cases in the format of BidiCharacterTest.txt from the Unicode Character
+    // Database: code points; paragraph direction (0 is LTR, 1 is RTL, 2 is auto); resolved
+    // paragraph level; resolved levels, with `x` for removed characters; visual order.
+    fn check_bidi_character_tests(data: &str) -> usize {
+        let mut count = 0;
+        for (line_index, line) in data.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let fields: Vec<&str> = line.split(';').collect();
+            let text: String = fields[0]
+                .split_whitespace()
+                .map(|hex| char::from_u32(u32::from_str_radix(hex, 16).unwrap()).unwrap())
+                .collect();
+            let direction = match fields[1] {
+                "0" => BaseDirection::LeftToRight,
+                "1" => BaseDirection::RightToLeft,
+                _ => BaseDirection::Auto,
+            };
+            let bidi = BidiText::new(&text, direction);
+            let chars: Vec<char> = text.chars().collect();
+            for (index, expected) in fields[3].split_whitespace().enumerate() {
+                if expected != "x" {
+                    assert_eq!(
+                        bidi.level(index).number().to_string(),
+                        expected,
+                        "level of character {} on line {}: {}",
+                        index,
+                        line_index + 1,
+                        line
+                    );
+                }
+            }
+            let order: Vec<String> = bidi
+                .visual_order()
+                .iter()
+                .filter(|index| !is_removed_by_x9(chars[**index]))
+                .map(|index| index.to_string())
+                .collect();
+            let expected: Vec<&str> = fields[4].split_whitespace().collect();
+            assert_eq!(order, expected, "visual order on line {}: {}", line_index + 1, line);
+            count += 1;
+        }
+        count
+    }
+
+    #[test]
+    fn bidi_character_tests() {
+        // Hebrew alef and bet are 05D0 and 05D1, Arabic digits 0661 and 0662.
+        let data = "\
+            0061 0020 05D0 05D1 0020 0062;0;0;0 0 1 1 0 0;0 1 3 2 4 5\n\
+            05D0 05D1 0020 0061 0062 0020 05D0;2;1;1 1 1 2 2 1 1;6 5 3 4 2 1 0\n\
+            0061 0062 0020 05D0;1;1;2 2 1 1;3 2 0 1\n\
+            05D0 0020 0031 0032 0020 05D1;2;1;1 1 2 2 1 1;5 4 2 3 1 0\n\
+            0061 0020 0661 0662;0;0;0 0 2 2;0 1 2 3\n\
+            0061 202B 0062 202C 0063;0;0;0 x 2 x 0;0 2 4\n\
+            05D0 0020 0028 0061 0029;2;1;1 1 1 2 1;4 3 2 1 0\n\
+            0061 0020 0020;1;1;2 1 1;2 1 0\n\
+        ";
+        assert_eq!(check_bidi_character_tests(data), 8);
+    }
+
+    // A subset of BidiCharacterTest.txt, see the comment at the top of the file for which part.
+    #[test]
+    fn bidi_character_test_file() {
+        assert_eq!(check_bidi_character_tests(include_str!("bidi_character_test.txt")), 3854);
+    }
+
+    #[test]
+    fn lines_are_reordered_on_their_own() {
+        let bidi = BidiText::new("ab\u{5D0}\u{5D1}\n\u{5D2}c", BaseDirection::Auto);
+        assert_eq!(bidi.visual_order(), &[0, 1, 3, 2, 4, 6, 5]);
+    }
+
+    #[test]
+    fn cursor_movement() {
+        // "ab" followed by two Hebrew letters, drawn as "ab" + reversed Hebrew
+        let bidi = BidiText::new("ab\u{5D0}\u{5D1}", BaseDirection::LeftToRight);
+        // visually: a b bet alef
+        let mut cursor = 0;
+        let mut visited = vec![cursor];
+        loop {
+            let next = bidi.cursor_right(cursor);
+            if next == cursor {
+                break
+            }
+            cursor = next;
+            visited.push(cursor);
+        }
+        // right of "b" is the logical end of the Hebrew, which is then walked backwards
+        assert_eq!(visited, vec![0, 1, 4, 3, 2]);
+        assert_eq!(bidi.visual_cursor(2), 4);
+        assert_eq!(bidi.visual_cursor(4), 2);
+        assert_eq!(bidi.cursor_left(4), 1);
+        assert_eq!(bidi.cursor_left(2), 3);
+        assert_eq!(bidi.cursor_left(0), 0);
+        assert_eq!(bidi.visual_ranges(1..3), vec![1..2, 3..4]);
+    }
+
+    #[test]
+    fn left_to_right_text_moves_logically() {
+        let bidi = BidiText::new("ab\ncd", BaseDirection::Auto);
+        for cursor in 0..5 {
+            assert_eq!(bidi.cursor_right(cursor), cursor + 1);
+            assert_eq!(bidi.cursor_left(cursor + 1), cursor);
+            assert_eq!(bidi.visual_cursor(cursor), cursor);
+        }
+    }
+}
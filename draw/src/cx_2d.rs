@@ -16,6 +16,7 @@ use {
             Cx
         },
         nav::CxNavTreeRc,
+        accessibility::CxAccessTreeRc,
         icon_atlas::CxIconAtlasRc,
         font_atlas::CxFontsAtlasRc,
         draw_list_2d::DrawList2d,
@@ -45,6 +46,7 @@ pub struct Cx2d<'a> {
     pub fonts_atlas_rc: CxFontsAtlasRc,
     pub icon_atlas_rc: CxIconAtlasRc,
     pub nav_tree_rc: CxNavTreeRc,
+    pub access_tree_rc: CxAccessTreeRc,
 }
 
 impl<'a> Deref for Cx2d<'a> {type Target = Cx; fn deref(&self) -> &Self::Target {self.cx}}
@@ -73,10 +75,12 @@ impl<'a> Cx2d<'a> {
     pub fn new(cx: &'a mut Cx, draw_event: &'a DrawEvent) -> Self {
         Self::lazy_construct_font_atlas(cx);
         Self::lazy_construct_nav_tree(cx);
+        Self::lazy_construct_access_tree(cx);
         Self::lazy_construct_icon_atlas(cx);
         cx.redraw_id += 1;
         let fonts_atlas_rc = cx.get_global::<CxFontsAtlasRc>().clone();
         let nav_tree_rc = cx.get_global::<CxNavTreeRc>().clone();
+        let access_tree_rc = cx.get_global::<CxAccessTreeRc>().clone();
         let icon_atlas_rc = cx.get_global::<CxIconAtlasRc>().clone();
         Self {
             overlay_id: None,
@@ -91,6 +95,7 @@ impl<'a> Cx2d<'a> {
             turtles: Vec::new(),
             align_list: Vec::new(),
             nav_tree_rc,
+            access_tree_rc,
             icon_atlas_rc
         }
     }
@@ -10,6 +10,7 @@ pub use {
         io::prelude::*,
         fs::File,
         collections::HashMap,
+        ops::Range,
     },
     crate::{
         makepad_platform::*,
@@ -190,6 +191,68 @@ impl CxFontsAtlas {
         font_id
     }
     
+    /// Picks the first font of the fallback chain `font_ids` that has a glyph for `c`. If none of
+    /// them do, the first font that is loaded is used, so the character shows up as its tofu.
+    pub fn font_for_char(&self, font_ids: &[usize], c: char) -> Option<usize> {
+        let mut first_loaded = None;
+        for &font_id in font_ids {
+            if let Some(Some(cxfont)) = self.fonts.get(font_id) {
+                if cxfont.has_glyph(c) {
+                    return Some(font_id)
+                }
+                first_loaded.get_or_insert(font_id);
+            }
+        }
+        first_loaded
+    }
+    
+    /// Splits `text` into runs that can each be shaped with a single font of the fallback chain
+    /// `font_ids`. Whitespace stays in the run before it when that font has it, and characters
+    /// that extend a cluster (joiners, variation selectors and so on), as well as characters
+    /// joined on with a zero width joiner, always do, so a cluster is never split across faces.
+    pub fn font_runs(&self, font_ids: &[usize], text: &str) -> Vec<(usize, Range<usize>)> {
+        let mut runs: Vec<(usize, Range<usize>)> = Vec::new();
+        if font_ids.len() == 1 {
+            if let Some(Some(_)) = self.fonts.get(font_ids[0]) {
+                runs.push((font_ids[0], 0..text.len()));
+            }
+            return runs
+        }
+        let mut prev = None;
+        for (index, c) in text.char_indices() {
+            let end = index + c.len_utf8();
+            let joined = prev == Some('\u{200D}');
+            prev = Some(c);
+            if let Some((font_id, range)) = runs.last_mut() {
+                let stays = extends_cluster(c) || joined
+                    || (c.is_whitespace() && self.fonts[*font_id].as_ref().unwrap().has_glyph(c));
+                if stays {
+                    range.end = end;
+                    continue;
+                }
+            }
+            let Some(font_id) = self.font_for_char(font_ids, c) else {
+                return runs
+            };
+            match runs.last_mut() {
+                Some((last_font_id, range)) if *last_font_id == font_id => range.end = end,
+                _ => runs.push((font_id, index..end)),
+            }
+        }
+        runs
+    }
+    
+    /// The advance of `c` in logical pixels at `font_size`, in the font of the fallback chain
+    /// `font_ids` that it is drawn with. Each font is scaled by its own units per em.
+    pub fn char_advance(&mut self, font_ids: &[usize], c: char, font_size: f64) -> Option<f64> {
+        let font_id = self.font_for_char(font_ids, c)?;
+        let cxfont = self.fonts[font_id].as_mut().unwrap();
+        let units_per_em = cxfont.ttf_font.units_per_em;
+        let glyph_id = cxfont.owned_font_face.with_ref(|face| face.glyph_index(c))?.0 as usize;
+        let glyph = cxfont.get_glyph_by_id(glyph_id).ok()?;
+        Some(glyph.horizontal_metrics.advance_width * font_size * 96.0 / (72.0 * units_per_em))
+    }
+    
     pub fn reset_fonts_atlas(&mut self) {
         for cxfont in &mut self.fonts {
             if let Some(cxfont) = cxfont {
@@ -209,6 +272,21 @@ impl CxFontsAtlas {
     }
 }
 
+// Characters that are part of the cluster of the character before them, and should be shaped with
+// the same font.
+fn extends_cluster(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0300}'..='\u{036F}' // combining diacritical marks
+        | '\u{200C}' | '\u{200D}' // zero width (non-)joiner
+        | '\u{20D0}'..='\u{20FF}' // combining marks for symbols, such as the keycap
+        | '\u{FE00}'..='\u{FE0F}' // variation selectors
+        | '\u{1F3FB}'..='\u{1F3FF}' // emoji skin tone modifiers
+        | '\u{E0020}'..='\u{E007F}' // tags, used in flag sequences
+        | '\u{E0100}'..='\u{E01EF}' // variation selectors supplement
+    )
+}
+
 impl<'a> Cx2d<'a> {
     pub fn lazy_construct_font_atlas(cx: &mut Cx){
         // ok lets fetch/instance our CxFontsAtlasRc
@@ -566,6 +644,10 @@ impl CxFont {
         self.atlas_pages.len() - 1
     }
 
+    pub fn has_glyph(&self, c: char) -> bool {
+        self.owned_font_face.with_ref(|face| face.glyph_index(c).is_some())
+    }
+
     pub fn get_glyph(&mut self, c:char)->Option<&Glyph>{
         if c < '\u{10000}' {
             Some(self.get_glyph_by_id(self.owned_font_face.with_ref(|face| face.glyph_index(c))?.0 as usize).unwrap())
@@ -578,3 +660,91 @@ impl CxFont {
         self.owned_font_face.with_ref(|face| self.ttf_font.get_glyph_by_id(face, id))
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // Liberation Mono lacks the combining acute accent and the check mark, which IBM Plex Sans has,
+    // and has the smiley, which IBM Plex Sans lacks. Neither has an em space.
+    const MONO: usize = 0;
+    const SANS: usize = 1;
+    
+    fn atlas() -> CxFontsAtlas {
+        let mut cx = Cx::new(Box::new(|_, _| {}));
+        let mut atlas = CxFontsAtlas::new(Texture::new(&mut cx));
+        for name in ["LiberationMono-Regular", "IBMPlexSans-Text"] {
+            let path = format!("{}/../widgets/resources/{}.ttf", env!("CARGO_MANIFEST_DIR"), name);
+            let bytes = std::fs::read(path).unwrap();
+            atlas.fonts.push(Some(CxFont::load_from_ttf_bytes(Rc::new(bytes)).unwrap()));
+        }
+        atlas
+    }
+    
+    fn runs<'a>(atlas: &CxFontsAtlas, font_ids: &[usize], text: &'a str) -> Vec<(usize, &'a str)> {
+        atlas.font_runs(font_ids, text).into_iter().map(|(font_id, range)| (font_id, &text[range])).collect()
+    }
+    
+    #[test]
+    fn picks_the_first_font_with_a_glyph() {
+        let atlas = atlas();
+        assert_eq!(atlas.font_for_char(&[MONO, SANS], 'a'), Some(MONO));
+        assert_eq!(atlas.font_for_char(&[MONO, SANS], '✓'), Some(SANS));
+        assert_eq!(atlas.font_for_char(&[SANS, MONO], '☺'), Some(MONO));
+        // without any font that has it, the character shows up as the tofu of the first one
+        assert_eq!(atlas.font_for_char(&[SANS, MONO], '\u{2003}'), Some(SANS));
+        // fonts that failed to load are skipped
+        assert_eq!(atlas.font_for_char(&[7, MONO], 'a'), Some(MONO));
+        assert_eq!(atlas.font_for_char(&[7], 'a'), None);
+    }
+    
+    #[test]
+    fn splits_runs_at_font_changes() {
+        let atlas = atlas();
+        assert_eq!(runs(&atlas, &[MONO, SANS], "ab✓cd"), [(MONO, "ab"), (SANS, "✓"), (MONO, "cd")]);
+        assert_eq!(runs(&atlas, &[MONO, SANS], "✓✓ab"), [(SANS, "✓✓"), (MONO, "ab")]);
+        assert_eq!(runs(&atlas, &[MONO, SANS], ""), []);
+    }
+    
+    #[test]
+    fn keeps_whitespace_in_the_current_run() {
+        let atlas = atlas();
+        assert_eq!(runs(&atlas, &[MONO, SANS], "ab ✓ cd"), [(MONO, "ab "), (SANS, "✓ "), (MONO, "cd")]);
+        assert_eq!(runs(&atlas, &[MONO, SANS], "✓  a"), [(SANS, "✓  "), (MONO, "a")]);
+        // unless the font of the run does not have it
+        assert_eq!(runs(&atlas, &[SANS, MONO], "☺\u{2003}a"), [(MONO, "☺"), (SANS, "\u{2003}a")]);
+    }
+    
+    #[test]
+    fn keeps_clusters_in_one_font() {
+        let atlas = atlas();
+        // the accent is only in Sans, but stays with the letter it belongs to
+        assert_eq!(runs(&atlas, &[MONO, SANS], "e\u{301}x"), [(MONO, "e\u{301}x")]);
+        assert_eq!(runs(&atlas, &[MONO, SANS], "✓\u{FE0F}a"), [(SANS, "✓\u{FE0F}"), (MONO, "a")]);
+        // as does whatever is joined on with a zero width joiner
+        assert_eq!(runs(&atlas, &[MONO, SANS], "✓\u{200D}☺ ☺"), [(SANS, "✓\u{200D}☺ "), (MONO, "☺")]);
+        assert_eq!(runs(&atlas, &[MONO, SANS], "☺\u{200D}✓a"), [(MONO, "☺\u{200D}✓a")]);
+    }
+    
+    #[test]
+    fn uses_one_run_for_a_single_font() {
+        let atlas = atlas();
+        // without fallbacks, nothing is looked up, not even glyphs the font lacks
+        assert_eq!(runs(&atlas, &[MONO], "a✓ b"), [(MONO, "a✓ b")]);
+        assert_eq!(runs(&atlas, &[7], "a✓ b"), []);
+    }
+    
+    #[test]
+    fn measures_chars_in_their_own_font() {
+        let mut atlas = atlas();
+        let mono_a = atlas.char_advance(&[MONO, SANS], 'a', 12.0).unwrap();
+        // Liberation Mono is monospaced, and advances scale with the font size
+        assert_eq!(atlas.char_advance(&[MONO, SANS], 'i', 12.0), Some(mono_a));
+        assert!((atlas.char_advance(&[MONO, SANS], 'a', 24.0).unwrap() - 2.0 * mono_a).abs() < 1e-9);
+        let check = atlas.char_advance(&[MONO, SANS], '✓', 12.0).unwrap();
+        assert_eq!(atlas.char_advance(&[SANS], '✓', 12.0), Some(check));
+        assert_ne!(atlas.char_advance(&[SANS], 'i', 12.0), Some(mono_a));
+        assert_eq!(atlas.char_advance(&[7], 'a', 12.0), None);
+    }
+}
@@ -11,7 +11,10 @@ pub mod turtle;
 pub mod font_atlas;
 pub mod geometry;
 pub mod nav;
+pub mod accessibility;
+pub mod atspi;
 pub mod icon_atlas;
+pub mod bidi;
 mod owned_font_face;
  
 pub use crate::{
@@ -24,6 +27,10 @@ pub use crate::{
         Padding,
         Flow,
         Size,
+        GridTrack,
+        GridTracks,
+        GridLayout,
+        GridCell,
         TurtleAlignRange,
         DeferWalk
     },
@@ -35,7 +42,21 @@ pub use crate::{
         NavOrder,
         NavStop,
         NavItem,
-        NavScrollIndex
+        NavScrollIndex,
+        NavScopeKind,
+        NavEntry,
+        NavStops,
+    },
+    accessibility::{
+        AccessRole,
+        AccessValue,
+        AccessStates,
+        AccessActions,
+        AccessNode,
+        AccessItem,
+        AccessGroupIndex,
+        AccessTree,
+        AccessTreeNode,
     },
     draw_list_2d::{
         DrawList2d,
@@ -53,6 +74,7 @@ pub use crate::{
         draw_line::DrawLine,
         draw_text::DrawText,
         draw_color::DrawColor,
+        compute_shader::ComputeShader,
     },
     geometry::{
         GeometryGen,
@@ -69,4 +91,5 @@ pub fn live_design(cx: &mut Cx) {
     crate::geometry::geometry_gen::live_design(cx);
     crate::shader::std::live_design(cx);
     crate::shader::draw_trapezoid::live_design(cx);
+    crate::shader::compute_shader::live_design(cx);
 }
\ No newline at end of file
@@ -4,10 +4,7 @@ use {
     std::cell::RefCell,
     crate::{
         cx_2d::Cx2d,
-        makepad_platform::Area,
-        makepad_platform::DrawListId,
-        makepad_platform::Margin,
-        makepad_platform::Cx,
+        makepad_platform::*,
     }
 };
 
@@ -37,7 +34,7 @@ impl std::ops::IndexMut<DrawListId> for CxNavTree {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum NavOrder {
     Default,
     Top(u64),
@@ -45,12 +42,51 @@ pub enum NavOrder {
     Bottom(u64),
 }
 
+impl NavOrder {
+    /// Maps a `tab_index` like html does: positive indices come first in ascending order,
+    /// zero follows the draw order and negative indices can't be reached with Tab at all.
+    pub fn from_tab_index(tab_index: i64) -> Option<Self> {
+        match tab_index {
+            0 => Some(Self::Default),
+            i if i > 0 => Some(Self::Top(i as u64)),
+            _ => None
+        }
+    }
+    
+    fn sort_key(&self) -> (u8, u64) {
+        match self {
+            Self::Top(i) => (0, *i),
+            Self::Default => (1, 0),
+            Self::Middle(i) => (1, *i),
+            Self::Bottom(i) => (2, *i),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct NavStop {
     pub role: NavRole,
     pub order: NavOrder,
     pub margin: Margin,
-    pub area: Area
+    pub area: Area,
+    /// Picked when Tab enters the arrow key scope the stop is in, like a checked radio button.
+    pub selected: bool,
+}
+
+impl NavStop {
+    pub fn new(area: Area, role: NavRole) -> Self {
+        Self {
+            role,
+            order: NavOrder::Default,
+            margin: Margin::default(),
+            area,
+            selected: false
+        }
+    }
+    
+    pub fn with_order(self, order: NavOrder) -> Self {Self {order, ..self}}
+    pub fn with_margin(self, margin: Margin) -> Self {Self {margin, ..self}}
+    pub fn with_selected(self, selected: bool) -> Self {Self {selected, ..self}}
 }
 
 #[derive(Debug, Clone)]
@@ -58,14 +94,130 @@ pub enum NavItem {
     Child(DrawListId),
     Stop(NavStop),
     BeginScroll(Area),
-    EndScroll(Area)
+    EndScroll(Area),
+    BeginScope(NavScopeKind),
+    EndScope,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum NavRole {
     TextInput,
     DropDown,
     Slider,
+    Button,
+    CheckBox,
+    RadioButton,
+    Table,
+}
+
+impl NavRole {
+    /// Stops that use the arrow keys themselves aren't left with them.
+    pub fn uses_arrow_keys(&self) -> bool {
+        matches!(self, Self::TextInput | Self::DropDown | Self::Slider | Self::Table)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Live, LiveHook)]
+#[live_ignore]
+pub enum NavScopeKind {
+    #[pick] None,
+    // Tab cycles through the stops of the scope while the focus is in it
+    Focus,
+    // like Focus, but while it is drawn no stop outside of it can be reached
+    Modal,
+    // the stops in the scope are a single Tab stop and the arrow keys move between them
+    Arrows,
+}
+
+/// A nav stop with the scroll areas and scopes it was drawn in.
+#[derive(Debug, Clone)]
+pub struct NavEntry {
+    pub stop: NavStop,
+    pub scroll_stack: Vec<Area>,
+    /// Outermost first, numbered in draw order.
+    pub scopes: Vec<(usize, NavScopeKind)>,
+}
+
+impl NavEntry {
+    fn arrow_scope(&self) -> Option<usize> {
+        self.scopes.iter().rev().find( | (_, kind) | *kind == NavScopeKind::Arrows).map( | (id, _) | *id)
+    }
+    
+    fn in_scope(&self, scope: usize) -> bool {
+        self.scopes.iter().any( | (id, _) | *id == scope)
+    }
+}
+
+/// All nav stops of a window in draw order, to find where Tab and the arrow keys go.
+#[derive(Debug, Clone, Default)]
+pub struct NavStops {
+    pub entries: Vec<NavEntry>,
+    /// The last modal scope that was drawn, it keeps the focus inside.
+    pub modal_scope: Option<usize>,
+}
+
+impl NavStops {
+    pub fn focused(&self, cx: &Cx) -> Option<usize> {
+        self.entries.iter().position( | entry | cx.has_key_focus(entry.stop.area))
+    }
+    
+    pub fn next_tab_stop(&self, focus: Option<usize>, backward: bool) -> Option<usize> {
+        let trap = self.modal_scope.or_else( || {
+            let focus = &self.entries[focus?];
+            focus.scopes.iter().rev()
+                .find( | (_, kind) | matches!(kind, NavScopeKind::Focus | NavScopeKind::Modal))
+                .map( | (id, _) | *id)
+        });
+        let focus_arrow_scope = focus.and_then( | focus | self.entries[focus].arrow_scope());
+        
+        // an arrow key scope takes part with one stop: the focused, selected or first one
+        let mut order = Vec::new();
+        for (index, entry) in self.entries.iter().enumerate() {
+            if trap.is_some_and( | trap | !entry.in_scope(trap)) {
+                continue;
+            }
+            if let Some(arrow_scope) = entry.arrow_scope() {
+                let pick = if focus_arrow_scope == Some(arrow_scope) {
+                    focus
+                }
+                else {
+                    let mut group = self.entries.iter().enumerate().filter( | (_, e) | e.arrow_scope() == Some(arrow_scope));
+                    let first = group.clone().next().map( | (i, _) | i);
+                    group.find( | (_, e) | e.stop.selected).map( | (i, _) | i).or(first)
+                };
+                if pick != Some(index) {
+                    continue;
+                }
+            }
+            order.push(index);
+        }
+        order.sort_by_key( | index | self.entries[*index].stop.order.sort_key());
+        
+        if order.is_empty() {
+            return None
+        }
+        let next = match focus.and_then( | focus | order.iter().position( | index | *index == focus)) {
+            Some(pos) if backward => (pos + order.len() - 1) % order.len(),
+            Some(pos) => (pos + 1) % order.len(),
+            None if backward => order.len() - 1,
+            None => 0
+        };
+        Some(order[next])
+    }
+    
+    pub fn next_arrow_stop(&self, focus: Option<usize>, backward: bool) -> Option<usize> {
+        let focus = focus?;
+        if self.entries[focus].stop.role.uses_arrow_keys() {
+            return None
+        }
+        let arrow_scope = self.entries[focus].arrow_scope()?;
+        let group: Vec<usize> = (0..self.entries.len())
+            .filter( | index | self.entries[*index].arrow_scope() == Some(arrow_scope))
+            .collect();
+        let pos = group.iter().position( | index | *index == focus)?;
+        let next = if backward {(pos + group.len() - 1) % group.len()} else {(pos + 1) % group.len()};
+        Some(group[next]).filter( | next | *next != focus)
+    }
 }
 
 impl<'a> Cx2d<'a> {
@@ -102,6 +254,7 @@ impl<'a> Cx2d<'a> {
                     NavItem::EndScroll(area)=>{
                         if *area != scroll_stack.pop().unwrap(){panic!()};
                     }
+                    NavItem::BeginScope(_) | NavItem::EndScope => ()
                 }
             }
             None
@@ -114,6 +267,48 @@ impl<'a> Cx2d<'a> {
         }
     }
     
+    /// Collects the nav stops below `root` with the scroll areas and scopes around them.
+    pub fn nav_stops(cx: &mut Cx, root: DrawListId) -> NavStops {
+        let nav_tree_rc = cx.get_global::<CxNavTreeRc>().clone();
+        let nav_tree = &*nav_tree_rc.0.borrow();
+        fn collect(nav_tree: &CxNavTree, draw_list_id: DrawListId, scroll_stack: &mut Vec<Area>, scopes: &mut Vec<(usize, NavScopeKind)>, scope_count: &mut usize, out: &mut NavStops) {
+            if draw_list_id.index() >= nav_tree.nav_lists.len() {
+                return
+            }
+            for nav_item in &nav_tree[draw_list_id].nav_list {
+                match nav_item {
+                    NavItem::Child(draw_list_id) => {
+                        collect(nav_tree, *draw_list_id, scroll_stack, scopes, scope_count, out);
+                    }
+                    NavItem::Stop(stop) => out.entries.push(NavEntry {
+                        stop: stop.clone(),
+                        scroll_stack: scroll_stack.clone(),
+                        scopes: scopes.clone()
+                    }),
+                    NavItem::BeginScroll(area) => {
+                        scroll_stack.push(*area);
+                    }
+                    NavItem::EndScroll(_) => {
+                        scroll_stack.pop();
+                    }
+                    NavItem::BeginScope(kind) => {
+                        if *kind == NavScopeKind::Modal {
+                            out.modal_scope = Some(*scope_count);
+                        }
+                        scopes.push((*scope_count, *kind));
+                        *scope_count += 1;
+                    }
+                    NavItem::EndScope => {
+                        scopes.pop();
+                    }
+                }
+            }
+        }
+        let mut out = NavStops::default();
+        collect(nav_tree, root, &mut Vec::new(), &mut Vec::new(), &mut 0, &mut out);
+        out
+    }
+    
     pub fn nav_list_clear(&mut self, draw_list_id: DrawListId) {
         let mut nav_tree = self.nav_tree_rc.0.borrow_mut();
         if draw_list_id.index() >= nav_tree.nav_lists.len() {
@@ -128,13 +323,22 @@ impl<'a> Cx2d<'a> {
     }
     
     pub fn add_nav_stop(&mut self, area: Area, role: NavRole, margin: Margin) {
+        self.push_nav_stop(NavStop::new(area, role).with_margin(margin));
+    }
+    
+    pub fn push_nav_stop(&mut self, stop: NavStop) {
         let draw_list_id = *self.draw_list_stack.last().unwrap();
-        self.nav_list_item_push(draw_list_id, NavItem::Stop(NavStop {
-            role,
-            area,
-            order: NavOrder::Default,
-            margin
-        }));
+        self.nav_list_item_push(draw_list_id, NavItem::Stop(stop));
+    }
+    
+    pub fn begin_nav_scope(&mut self, kind: NavScopeKind) {
+        let draw_list_id = *self.draw_list_stack.last().unwrap();
+        self.nav_list_item_push(draw_list_id, NavItem::BeginScope(kind));
+    }
+    
+    pub fn end_nav_scope(&mut self) {
+        let draw_list_id = *self.draw_list_stack.last().unwrap();
+        self.nav_list_item_push(draw_list_id, NavItem::EndScope);
     }
     
     pub fn add_begin_scroll(&mut self)->NavScrollIndex{
@@ -153,4 +357,81 @@ impl<'a> Cx2d<'a> {
     }
 }
 
-pub struct NavScrollIndex(usize);
\ No newline at end of file
+pub struct NavScrollIndex(usize);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn stops(spec: &[(NavOrder, &[(usize, NavScopeKind)], bool)]) -> NavStops {
+        NavStops {
+            entries: spec.iter().map( | (order, scopes, selected) | NavEntry {
+                stop: NavStop::new(Area::Empty, NavRole::Button).with_order(*order).with_selected(*selected),
+                scroll_stack: Vec::new(),
+                scopes: scopes.to_vec(),
+            }).collect(),
+            modal_scope: None
+        }
+    }
+    
+    fn tab_cycle(stops: &NavStops, start: Option<usize>, backward: bool) -> Vec<usize> {
+        let mut out = Vec::new();
+        let mut focus = start;
+        for _ in 0..stops.entries.len() {
+            focus = stops.next_tab_stop(focus, backward);
+            out.push(focus.unwrap());
+        }
+        out
+    }
+    
+    #[test]
+    fn tab_follows_tab_index_then_draw_order() {
+        let stops = stops(&[
+            (NavOrder::Default, &[], false),
+            (NavOrder::Top(2), &[], false),
+            (NavOrder::Default, &[], false),
+            (NavOrder::Top(1), &[], false),
+        ]);
+        assert_eq!(tab_cycle(&stops, None, false), vec![3, 1, 0, 2]);
+        assert_eq!(tab_cycle(&stops, None, true), vec![2, 0, 1, 3]);
+    }
+    
+    #[test]
+    fn scopes_keep_the_focus_inside() {
+        let mut stops = stops(&[
+            (NavOrder::Default, &[], false),
+            (NavOrder::Default, &[(0, NavScopeKind::Focus)], false),
+            (NavOrder::Default, &[(0, NavScopeKind::Focus)], false),
+            (NavOrder::Default, &[], false),
+        ]);
+        // from outside the popup its stops are part of the order
+        assert_eq!(stops.next_tab_stop(Some(0), false), Some(1));
+        assert_eq!(stops.next_tab_stop(Some(0), true), Some(3));
+        // once inside, tab cycles in it
+        assert_eq!(tab_cycle(&stops, Some(1), false), vec![2, 1, 2, 1]);
+        
+        stops.entries[1].scopes = vec![(0, NavScopeKind::Modal)];
+        stops.entries[2].scopes = vec![(0, NavScopeKind::Modal)];
+        stops.modal_scope = Some(0);
+        assert_eq!(stops.next_tab_stop(Some(3), false), Some(1));
+    }
+    
+    #[test]
+    fn arrow_scopes_are_one_tab_stop() {
+        let stops = stops(&[
+            (NavOrder::Default, &[], false),
+            (NavOrder::Default, &[(0, NavScopeKind::Arrows)], false),
+            (NavOrder::Default, &[(0, NavScopeKind::Arrows)], true),
+            (NavOrder::Default, &[(0, NavScopeKind::Arrows)], false),
+            (NavOrder::Default, &[], false),
+        ]);
+        // tab enters the group at the selected stop and leaves it from any of them
+        assert_eq!(stops.next_tab_stop(Some(0), false), Some(2));
+        assert_eq!(stops.next_tab_stop(Some(3), false), Some(4));
+        assert_eq!(stops.next_tab_stop(Some(4), true), Some(2));
+        // arrows move inside it and wrap around
+        assert_eq!(stops.next_arrow_stop(Some(2), false), Some(3));
+        assert_eq!(stops.next_arrow_stop(Some(3), false), Some(1));
+        assert_eq!(stops.next_arrow_stop(Some(1), true), Some(3));
+        assert_eq!(stops.next_arrow_stop(Some(0), false), None);
+    }
+}
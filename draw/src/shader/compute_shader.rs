@@ -0,0 +1,191 @@
+use {
+    crate::{
+        makepad_platform::*,
+    },
+};
+
+live_design!{
+    ComputeShader = {{ComputeShader}} {
+        fn compute(self, id: ivec3) {
+        }
+    }
+}
+
+/// Base class for compute shaders declared in the DSL. Inherit from it with `buffer` fields,
+/// `uniform`s, an optional `workgroup_size` and a `fn compute(self, id: ivec3)` method, bind
+/// the buffers in declaration order with `set_buffer` and queue the work with `dispatch`.
+#[derive(Live, LiveRegister)]
+#[repr(C)]
+pub struct ComputeShader {
+    #[deref] pub compute_vars: ComputeVars,
+}
+
+impl LiveHook for ComputeShader {
+    fn before_apply(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) {
+        self.compute_vars.before_apply_init_shader(cx, apply, index, nodes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::makepad_platform::{
+            makepad_shader_compiler::{generate_glsl, generate_hlsl, generate_metal, generate_wgsl, DrawShaderConstTable, DrawShaderDef, ShaderRegistry},
+            os::headless::HeadlessRenderer,
+        },
+        naga::{
+            front::glsl,
+            valid::{Capabilities, ValidationFlags, Validator},
+            Module, ShaderStage,
+        },
+    };
+
+    const TEST_SHADER: &str = r#"
+        import makepad_draw::shader::compute_shader::ComputeShader;
+
+        ComputeTest = <ComputeShader> {
+            workgroup_size: 4
+            uniform count: float
+            uniform gain: float
+            buffer input: vec2
+            buffer output: float
+
+            fn compute(self, id: ivec3) {
+                if float(id.x) < self.count {
+                    let v = self.input[id.x];
+                    self.output[id.x] = v.x * v.y * self.gain;
+                }
+            }
+        }
+    "#;
+
+    fn test_cx() -> (Cx, LivePtr) {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        crate::live_design(&mut cx);
+        cx.register_live_body(LiveBody {
+            file: "draw/src/shader/compute_shader_test.rs".to_string(),
+            cargo_manifest_path: env!("CARGO_MANIFEST_DIR").to_string(),
+            module_path: "makepad_draw::shader::compute_shader_test".to_string(),
+            line: 0,
+            column: 0,
+            code: TEST_SHADER.to_string(),
+            live_type_infos: Vec::new(),
+        });
+        cx.init_cx_os();
+        let test_ptr = cx.live_registry.borrow().module_id_and_name_to_ptr(
+            LiveModuleId::from_str("makepad_draw::shader::compute_shader_test").unwrap(),
+            live_id!(ComputeTest)
+        ).unwrap();
+        (cx, test_ptr)
+    }
+
+    type Generate = fn(&DrawShaderDef, &DrawShaderConstTable, &ShaderRegistry) -> String;
+
+    fn generate(cx: &mut Cx, test_ptr: LivePtr, generate: Generate) -> String {
+        let compute = ComputeShader::new_from_ptr(cx, Some(test_ptr));
+        let compute_shader = compute.compute_shader.unwrap();
+        assert_eq!(compute.workgroup_size(cx), Some([4, 1, 1]));
+
+        let compute_shader_def = cx.shader_registry.draw_shader_defs.get(&compute_shader.compute_shader_ptr).unwrap();
+        let const_table = cx.shader_registry.compute_const_table(compute_shader.compute_shader_ptr);
+        generate(compute_shader_def, &const_table, &cx.shader_registry)
+    }
+
+    fn validate(source: &str, module: &Module) {
+        if let Err(err) = Validator::new(ValidationFlags::all(), Capabilities::all()).validate(module) {
+            panic!("{}\n{}", source, err.emit_to_string(source));
+        }
+    }
+
+    #[test]
+    fn compute_wgsl_output_validates() {
+        let (mut cx, test_ptr) = test_cx();
+        let source = generate(&mut cx, test_ptr, generate_wgsl::generate_compute_shader);
+        let module = naga::front::wgsl::parse_str(&source).unwrap_or_else( | err | {
+            panic!("{}\n{}", source, err.emit_to_string(&source))
+        });
+        validate(&source, &module);
+    }
+
+    #[test]
+    fn compute_glsl_output_validates() {
+        let (mut cx, test_ptr) = test_cx();
+        let compute = generate(&mut cx, test_ptr, generate_glsl::generate_compute_shader);
+        // naga reads the Vulkan flavour of GLSL, which has no loose uniforms, so those are put in
+        // blocks of their own
+        let mut binding = 2;
+        let compute: Vec<String> = compute.lines().map( | line | match line.strip_prefix("uniform ") {
+            Some(decl) => {
+                binding += 1;
+                format!("layout(binding = {}) uniform Block{} {{ {} }};", binding, binding, decl)
+            }
+            None => line.to_string()
+        }).collect();
+        // the header the OpenGL backend puts in front, at the lowest version naga reads, and
+        // without the sampling helpers, which this shader does not use
+        let source = format!("#version 450\nprecision highp float;\nprecision highp int;\n{}", compute.join("\n"));
+        let module = glsl::Frontend::default().parse(&glsl::Options::from(ShaderStage::Compute), &source).unwrap_or_else( | err | {
+            panic!("{}\n{}", source, err.emit_to_string(&source))
+        });
+        validate(&source, &module);
+    }
+
+    fn assert_lines(source: &str, lines: &[&str]) {
+        for line in lines {
+            assert!(source.lines().any( | l | l == *line), "missing `{}` in\n{}", line, source);
+        }
+    }
+
+    // there is no HLSL or MSL validator to run here, so these check the entry point and the
+    // bindings the DirectX 11 and Metal backends rely on
+    #[test]
+    fn compute_hlsl_output() {
+        let (mut cx, test_ptr) = test_cx();
+        let source = generate(&mut cx, test_ptr, generate_hlsl::generate_shader);
+        assert_lines(&source, &[
+            "cbuffer LiveUniforms : register(b0) {",
+            "cbuffer Uniforms_user : register(b5) {",
+            "RWStructuredBuffer<float2> ds_input: register(u0);",
+            "RWStructuredBuffer<float> ds_output: register(u1);",
+            "[numthreads(4, 1, 1)]",
+            "void compute_main(uint3 id: SV_DispatchThreadID) {",
+        ]);
+        assert!(!source.contains("vertex_main") && !source.contains("pixel_main"), "{}", source);
+    }
+
+    #[test]
+    fn compute_metal_output() {
+        let (mut cx, test_ptr) = test_cx();
+        let source = generate(&mut cx, test_ptr, | d, c, r | generate_metal::generate_shader(d, c, r).mtlsl);
+        assert_lines(&source, &[
+            "kernel void compute_main(",
+            "uint3 id [[thread_position_in_grid]]",
+            ", constant LiveUniforms &live_uniforms [[buffer(2)]]",
+            ", constant Uniforms_user &uniforms_user [[buffer(4)]]",
+            ", device float2 *ds_input [[buffer(5)]]",
+            ", device float *ds_output [[buffer(6)]]",
+            "    buffers.ds_output = ds_output;",
+        ]);
+        assert!(!source.contains("vertex_main") && !source.contains("fragment_main"), "{}", source);
+    }
+
+    #[test]
+    fn headless_dispatch_writes_buffers() {
+        let (mut cx, test_ptr) = test_cx();
+        let mut compute = ComputeShader::new_from_ptr(&mut cx, Some(test_ptr));
+        let input = ComputeBuffer::new_with_data(&mut cx, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let output = ComputeBuffer::new(&mut cx, 4);
+        compute.set_buffer(0, &input);
+        compute.set_buffer(1, &output);
+        compute.set_uniform(&cx, id!(count), &[3.0]);
+        compute.set_uniform(&cx, id!(gain), &[0.5]);
+        // 3 invocations round up to one workgroup of 4, the last one is skipped by the shader
+        compute.dispatch_invocations(&mut cx, [3, 1, 1]);
+
+        let mut renderer = HeadlessRenderer::new(dvec2(8.0, 8.0), 1.0);
+        renderer.frame(&mut cx, 0.0);
+        assert_eq!(cx.compute_buffers[output.compute_buffer_id()].data, vec![1.0, 6.0, 15.0, 0.0]);
+        assert!(cx.compute_shaders.ops.is_empty());
+    }
+}
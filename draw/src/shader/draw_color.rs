@@ -19,3 +19,97 @@ pub struct DrawColor {
     #[deref] pub draw_super: DrawQuad,
     #[live] pub color: Vec4
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        std::{cell::RefCell, rc::Rc},
+        super::*,
+        crate::{Cx2d, DrawList2d, Layout},
+        crate::makepad_platform::os::headless::HeadlessRenderer,
+    };
+
+    struct Scene {
+        pass: Pass,
+        draw_list: DrawList2d,
+        draw_color: DrawColor,
+    }
+
+    type SharedScene = Rc<RefCell<Option<Scene >> >;
+
+    // an orange 3x2 quad at 2,1 in an 8x4 black window
+    fn quad_scene() -> (Cx, SharedScene, Rc<RefCell<Vec<ReadbackEvent >> >) {
+        let scene: SharedScene = Default::default();
+        let readbacks: Rc<RefCell<Vec<ReadbackEvent >> > = Default::default();
+        let draw_scene = scene.clone();
+        let event_readbacks = readbacks.clone();
+        let mut cx = Cx::new(Box::new(move | cx, event | {
+            if let Event::Draw(draw_event) = event {
+                let mut scene = draw_scene.borrow_mut();
+                let scene = scene.as_mut().unwrap();
+                let cx = &mut Cx2d::new(cx, draw_event);
+                cx.begin_pass(&scene.pass, None);
+                scene.draw_list.begin_always(cx);
+                cx.begin_pass_sized_turtle(Layout::flow_down());
+                scene.draw_color.draw_abs(cx, Rect {pos: dvec2(2.0, 1.0), size: dvec2(3.0, 2.0)});
+                cx.end_pass_sized_turtle();
+                scene.draw_list.end(cx);
+                cx.end_pass(&scene.pass);
+            }
+            if let Event::Readback(readback) = event {
+                event_readbacks.borrow_mut().push(readback.clone());
+            }
+        }));
+        crate::live_design(&mut cx);
+        cx.init_cx_os();
+
+        let window = WindowHandle::new(&mut cx);
+        let pass = Pass::new(&mut cx);
+        window.set_pass(&mut cx, &pass);
+        pass.set_window_clear_color(&mut cx, vec4(0.0, 0.0, 0.0, 1.0));
+        let mut draw_color = DrawColor::new_local(&mut cx);
+        draw_color.color = vec4(1.0, 0.5, 0.0, 1.0);
+        *scene.borrow_mut() = Some(Scene {pass, draw_list: DrawList2d::new(&mut cx), draw_color});
+        (cx, scene, readbacks)
+    }
+
+    #[test]
+    fn headless_renders_a_quad() {
+        let (mut cx, scene, _) = quad_scene();
+        let pass_id = scene.borrow().as_ref().unwrap().pass.pass_id();
+        let mut renderer = HeadlessRenderer::new(dvec2(8.0, 4.0), 1.0);
+        renderer.startup(&mut cx);
+        renderer.frame(&mut cx, 0.0);
+
+        let image = renderer.pass_image(&cx, pass_id).unwrap();
+        assert_eq!((image.width, image.height), (8, 4));
+        for y in 0..4 {
+            for x in 0..8 {
+                let inside = (2..5).contains(&x) && (1..3).contains(&y);
+                let expected = if inside {[255, 128, 0, 255]} else {[0, 0, 0, 255]};
+                assert_eq!(image.pixel(x, y), expected, "pixel {x},{y}");
+            }
+        }
+    }
+
+    #[test]
+    fn headless_pass_readback() {
+        let (mut cx, scene, readbacks) = quad_scene();
+        let mut renderer = HeadlessRenderer::new(dvec2(8.0, 4.0), 1.0);
+        renderer.startup(&mut cx);
+        renderer.frame(&mut cx, 0.0);
+        assert!(readbacks.borrow().is_empty());
+
+        scene.borrow().as_ref().unwrap().pass.read_back(&mut cx, live_id!(screenshot));
+        renderer.frame(&mut cx, 1.0);
+
+        let readbacks = readbacks.borrow();
+        assert_eq!(readbacks.len(), 1);
+        assert_eq!(readbacks[0].request_id, live_id!(screenshot));
+        let image = readbacks[0].image.as_ref().unwrap();
+        assert_eq!((image.width, image.height), (8, 4));
+        let pixels = image.to_bgra_u32();
+        assert_eq!(pixels[8 + 2], 0xffff8000);
+        assert_eq!(pixels[8 + 1], 0xff000000);
+    }
+}
@@ -2,11 +2,12 @@ use {
     crate::{
         makepad_platform::*,
         turtle::{Walk, Size, Align},
-        font_atlas::{CxFontsAtlasTodo, CxFont, CxFontsAtlas, Font},
+        font_atlas::{CxFontsAtlasTodo, CxFontsAtlas, Font},
         draw_list_2d::ManyInstances,
         geometry::GeometryQuad2D,
         cx_2d::Cx2d
     },
+    std::ops::Range,
 };
 
 
@@ -142,6 +143,8 @@ const _: () = assert!(crate::font_atlas::ATLAS_HEIGHT == 4096);
 #[live_ignore]
 pub struct TextStyle {
     #[live()] pub font: Font,
+    /// Fonts to draw the characters that `font` has no glyphs for, in order of preference.
+    #[live] pub fallback_fonts: Vec<Font>,
     #[live(9.0)] pub font_size: f64,
     #[live(1.0)] pub brightness: f32,
     #[live(0.5)] pub curve: f32,
@@ -150,6 +153,16 @@ pub struct TextStyle {
     #[live(1.3)] pub height_factor: f64,
 }
 
+impl TextStyle {
+    /// The ids of `font` followed by those of the fallback fonts that were found.
+    pub fn font_ids(&self) -> Vec<usize> {
+        std::iter::once(&self.font)
+            .chain(&self.fallback_fonts)
+            .filter_map(|font| font.font_id)
+            .collect()
+    }
+}
+
 #[derive(Clone, Live, LiveHook)]
 #[live_ignore]
 pub enum TextWrap {
@@ -190,7 +203,7 @@ impl<'a> WordIterator<'a> {
         }
     }
     
-    fn next_word(&mut self, font: &mut CxFont) -> Option<WordIteratorItem> {
+    fn next_word(&mut self, fonts_atlas: &mut CxFontsAtlas, font_ids: &[usize]) -> Option<WordIteratorItem> {
         if let Some(char_iter) = &mut self.char_iter {
             while let Some((i, c)) = char_iter.next() {
                 self.last_index = i;
@@ -202,9 +215,7 @@ impl<'a> WordIterator<'a> {
                     with_newline: false
                 };
                 
-                let adv = if let Some(glyph) = font.get_glyph(c) {
-                    glyph.horizontal_metrics.advance_width * self.font_size_total
-                }else {0.0};
+                let adv = fonts_atlas.char_advance(font_ids, c, self.font_size_total).unwrap_or(0.0);
                 
                 if c == '\r' {
                     continue;
@@ -364,9 +375,9 @@ impl DrawText {
         }
         //self.draw_clip = cx.turtle().draw_clip().into();
         //let in_many = self.many_instances.is_some();
-        let font_id = self.text_style.font.font_id.unwrap();
+        let font_ids = self.text_style.font_ids();
         
-        if fonts_atlas.fonts[font_id].is_none() {
+        if font_ids.iter().all(|font_id| fonts_atlas.fonts[*font_id].is_none()) {
             return
         }
         
@@ -380,19 +391,8 @@ impl DrawText {
             self.begin_many_instances_internal(cx, fonts_atlas);
         }
         
-        let cxfont = fonts_atlas.fonts[font_id].as_mut().unwrap();
         let dpi_factor = cx.current_dpi_factor();
         
-        let atlas_page_id = cxfont.get_atlas_page_id(dpi_factor, self.text_style.font_size);
-        
-        let font = &mut cxfont.ttf_font;
-        let owned_font_face = &cxfont.owned_font_face;
-        
-        let font_size_logical = self.text_style.font_size * 96.0 / (72.0 * font.units_per_em);
-        let font_size_pixels = font_size_logical * dpi_factor;
-        
-        let atlas_page = &mut cxfont.atlas_pages[atlas_page_id];
-        
         let mi = if let Some(mi) = &mut self.many_instances {mi} else {return};
         let zbias_step = 0.00001;
         let mut char_depth = self.draw_depth;
@@ -418,67 +418,87 @@ impl DrawText {
             for (run_level, run_range) in runs_with_level_and_range {
                 // FIXME(eddyb) UBA/`unicode_bidi` only offers a LTR/RTL distinction,
                 // even if `rustybuzz` has vertical `Direction`s as well.
-                let (glyph_ids, new_rustybuzz_buffer) = cxfont
-                    .shape_cache
-                    .get_or_compute_glyph_ids(
-                    (
-                            if run_level.is_rtl() {
-                                rustybuzz::Direction::RightToLeft
-                            } else {
-                                rustybuzz::Direction::LeftToRight
-                            },
-                            &bidi_info.text[run_range]
-                        ),
-                        rustybuzz_buffer,
-                        owned_font_face
-                    );
-                rustybuzz_buffer = new_rustybuzz_buffer;
-                for &glyph_id in glyph_ids {
-                    let glyph = owned_font_face.with_ref(|face| font.get_glyph_by_id(face, glyph_id).unwrap());
+                let direction = if run_level.is_rtl() {
+                    rustybuzz::Direction::RightToLeft
+                } else {
+                    rustybuzz::Direction::LeftToRight
+                };
+                let run = &bidi_info.text[run_range];
+                
+                // Each run is split up further by the font of the fallback chain that has its
+                // glyphs. Those are in logical order, so a right-to-left run draws them backwards.
+                let mut font_runs = fonts_atlas.font_runs(&font_ids, run);
+                if run_level.is_rtl() {
+                    font_runs.reverse();
+                }
+                
+                for (font_id, font_range) in font_runs {
+                    let cxfont = fonts_atlas.fonts[font_id].as_mut().unwrap();
+                    let atlas_page_id = cxfont.get_atlas_page_id(dpi_factor, self.text_style.font_size);
                     
-                    let advance = glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale;
+                    let font = &mut cxfont.ttf_font;
+                    let owned_font_face = &cxfont.owned_font_face;
                     
-                    // HACK(eddyb) this is a different padding from the SDF padding,
-                    // this allows the glyph rasterization to avoid touching the
-                    // edges of the raster area, while the SDF padding exists for
-                    // e.g. bilinear sampling to have excess texels to sample.
-                    let pad_dpx = 2.0;
-                    let w_dpx = ((glyph.bounds.p_max.x - glyph.bounds.p_min.x) * font_size_pixels).ceil() + pad_dpx * 2.0;
-                    let h_dpx = ((glyph.bounds.p_max.y - glyph.bounds.p_min.y) * font_size_pixels).ceil() + pad_dpx * 2.0;
-                    let (w_dpx, h_dpx) = if w_dpx <= pad_dpx * 2.0{(0.0,0.0)}else { (w_dpx, h_dpx) };
-                                        
-                    let tc = *atlas_page.atlas_glyphs.entry(glyph_id).or_insert_with(|| {
-                        // see if we can fit it
-                        // allocate slot
-                        fonts_atlas.alloc.alloc_atlas_glyph(w_dpx, h_dpx, CxFontsAtlasTodo {
-                            font_id,
-                            atlas_page_id,
-                            glyph_id,
-                        })
-                    });
-
-                    let pad = pad_dpx * self.font_scale / dpi_factor;
-                    let w = w_dpx * self.font_scale / dpi_factor;
-                    let h = h_dpx * self.font_scale / dpi_factor;
+                    let font_size_logical = self.text_style.font_size * 96.0 / (72.0 * font.units_per_em);
+                    let font_size_pixels = font_size_logical * dpi_factor;
                     
-                    let delta_x = font_size_logical * self.font_scale * glyph.bounds.p_min.x - pad;
-                    let delta_y = -(font_size_logical * self.font_scale * glyph.bounds.p_min.y - pad)
-                        + self.text_style.font_size * self.font_scale * self.text_style.top_drop;
-                    // give the callback a chance to do things
-                    //et scaled_min_pos_x = walk_x + delta_x;
-                    //let scaled_min_pos_y = pos.y - delta_y;
-                    self.font_t1 = tc.t1;
-                    self.font_t2 = tc.t2;
-                    self.rect_pos = dvec2(walk_x + delta_x, pos.y + delta_y).into();
-                    self.rect_size = dvec2(w, h).into();
-                    self.char_depth = char_depth;
-                    self.delta.x = delta_x as f32;
-                    self.delta.y = delta_y as f32;
-                    self.shader_font_size = self.text_style.font_size as f32;
-                    self.advance = advance as f32; //char_offset as f32;
-                    char_depth += zbias_step;
-                    mi.instances.extend_from_slice(self.draw_vars.as_slice());
-                    walk_x += advance;
+                    let atlas_page = &mut cxfont.atlas_pages[atlas_page_id];
+                    
+                    let (glyph_ids, new_rustybuzz_buffer) = cxfont
+                        .shape_cache
+                        .get_or_compute_glyph_ids(
+                            (direction, &run[font_range]),
+                            rustybuzz_buffer,
+                            owned_font_face
+                        );
+                    rustybuzz_buffer = new_rustybuzz_buffer;
+                    for &glyph_id in glyph_ids {
+                        let glyph = owned_font_face.with_ref(|face| font.get_glyph_by_id(face, glyph_id).unwrap());
+                        
+                        let advance = glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale;
+                        
+                        // HACK(eddyb) this is a different padding from the SDF padding,
+                        // this allows the glyph rasterization to avoid touching the
+                        // edges of the raster area, while the SDF padding exists for
+                        // e.g. bilinear sampling to have excess texels to sample.
+                        let pad_dpx = 2.0;
+                        let w_dpx = ((glyph.bounds.p_max.x - glyph.bounds.p_min.x) * font_size_pixels).ceil() + pad_dpx * 2.0;
+                        let h_dpx = ((glyph.bounds.p_max.y - glyph.bounds.p_min.y) * font_size_pixels).ceil() + pad_dpx * 2.0;
+                        let (w_dpx, h_dpx) = if w_dpx <= pad_dpx * 2.0{(0.0,0.0)}else { (w_dpx, h_dpx) };
+                        
+                        let tc = *atlas_page.atlas_glyphs.entry(glyph_id).or_insert_with(|| {
+                            // see if we can fit it
+                            // allocate slot
+                            fonts_atlas.alloc.alloc_atlas_glyph(w_dpx, h_dpx, CxFontsAtlasTodo {
+                                font_id,
+                                atlas_page_id,
+                                glyph_id,
+                            })
+                        });
+                        
+                        let pad = pad_dpx * self.font_scale / dpi_factor;
+                        let w = w_dpx * self.font_scale / dpi_factor;
+                        let h = h_dpx * self.font_scale / dpi_factor;
+                        
+                        let delta_x = font_size_logical * self.font_scale * glyph.bounds.p_min.x - pad;
+                        let delta_y = -(font_size_logical * self.font_scale * glyph.bounds.p_min.y - pad)
+                            + self.text_style.font_size * self.font_scale * self.text_style.top_drop;
+                        // give the callback a chance to do things
+                        //et scaled_min_pos_x = walk_x + delta_x;
+                        //let scaled_min_pos_y = pos.y - delta_y;
+                        self.font_t1 = tc.t1;
+                        self.font_t2 = tc.t2;
+                        self.rect_pos = dvec2(walk_x + delta_x, pos.y + delta_y).into();
+                        self.rect_size = dvec2(w, h).into();
+                        self.char_depth = char_depth;
+                        self.delta.x = delta_x as f32;
+                        self.delta.y = delta_y as f32;
+                        self.shader_font_size = self.text_style.font_size as f32;
+                        self.advance = advance as f32; //char_offset as f32;
+                        char_depth += zbias_step;
+                        mi.instances.extend_from_slice(self.draw_vars.as_slice());
+                        walk_x += advance;
+                    }
                 }
             }
         }
@@ -490,25 +510,19 @@ impl DrawText {
     
     fn compute_geom_inner(&self, cx: &Cx2d, walk: Walk, text: &str, fonts_atlas: &mut CxFontsAtlas) -> Option<TextGeom> {
         // we include the align factor and the width/height
-        let font_id = self.text_style.font.font_id.unwrap();
+        let font_ids = self.text_style.font_ids();
         
-        if fonts_atlas.fonts[font_id].is_none() {
+        if font_ids.iter().all(|font_id| fonts_atlas.fonts[*font_id].is_none()) {
             return None
         }
         
-        let font_size_logical = self.text_style.font_size * 96.0 / (72.0 * fonts_atlas.fonts[font_id].as_ref().unwrap().ttf_font.units_per_em);
+        let font_size = self.text_style.font_size * self.font_scale;
         let line_height = self.text_style.font_size * self.text_style.height_factor * self.font_scale;
-        let eval_width = cx.turtle().eval_width(walk.width, walk.margin, cx.turtle().layout().flow);
-        let eval_height = cx.turtle().eval_height(walk.height, walk.margin, cx.turtle().layout().flow);
+        let DVec2 {x: eval_width, y: eval_height} = cx.turtle().eval_walk(&walk);
         
         match if walk.width.is_fit() {&TextWrap::Line}else {&self.wrap} {
             TextWrap::Ellipsis => {
-                let ellip_width = if let Some(glyph) = fonts_atlas.fonts[font_id].as_mut().unwrap().get_glyph('.') {
-                    glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale
-                }
-                else {
-                    0.0
-                };
+                let ellip_width = fonts_atlas.char_advance(&font_ids, '.', font_size).unwrap_or(0.0);
                 
                 let mut measured_width = 0.0;
                 let mut ellip_pt = None;
@@ -517,8 +531,7 @@ impl DrawText {
                     if measured_width + ellip_width * 3.0 < eval_width {
                         ellip_pt = Some((i, measured_width, 3));
                     }
-                    if let Some(glyph) = fonts_atlas.fonts[font_id].as_mut().unwrap().get_glyph(c) {
-                        let adv = glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale;
+                    if let Some(adv) = fonts_atlas.char_advance(&font_ids, c, font_size) {
                         // ok so now what.
                         if measured_width + adv >= eval_width { // we have to drop back to ellip_pt
                             // if we don't have an ellip_pt, set it to 0
@@ -554,8 +567,8 @@ impl DrawText {
                 let mut measured_width = 0.0;
                 let mut measured_height = line_height;
                 
-                let mut iter = WordIterator::new(text.char_indices(), eval_width, font_size_logical * self.font_scale);
-                while let Some(word) = iter.next_word(fonts_atlas.fonts[font_id].as_mut().unwrap()) {
+                let mut iter = WordIterator::new(text.char_indices(), eval_width, font_size);
+                while let Some(word) = iter.next_word(fonts_atlas, &font_ids) {
                     if measured_width + word.width >= eval_width {
                         measured_height += line_height * self.text_style.line_spacing;
                         measured_width = word.width;
@@ -587,8 +600,7 @@ impl DrawText {
                     if c == '\n' {
                         measured_height += line_height * self.text_style.line_spacing;
                     }
-                    if let Some(glyph) = fonts_atlas.fonts[font_id].as_mut().unwrap().get_glyph(c) {
-                        let adv = glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale;
+                    if let Some(adv) = fonts_atlas.char_advance(&font_ids, c, font_size) {
                         measured_width += adv;
                     }
                     if measured_width > max_width {
@@ -612,15 +624,16 @@ impl DrawText {
         if text.len() == 0 {
             return
         }        
-        let font_id = if let Some(font_id) = self.text_style.font.font_id{font_id}else{
+        if self.text_style.font.font_id.is_none() {
             //log!("Draw text without font");
             return
-        };
+        }
+        let font_ids = self.text_style.font_ids();
         let fonts_atlas_rc = cx.fonts_atlas_rc.clone();
         let mut fonts_atlas = fonts_atlas_rc.0.borrow_mut();
         let fonts_atlas = &mut*fonts_atlas;
-                
-        let font_size_logical = self.text_style.font_size * 96.0 / (72.0 * fonts_atlas.fonts[font_id].as_ref().unwrap().ttf_font.units_per_em);
+        
+        let font_size = self.text_style.font_size * self.font_scale;
         let line_height = self.text_style.font_size * self.text_style.height_factor * self.font_scale;
         
         // lets get the width of the current turtle
@@ -630,18 +643,32 @@ impl DrawText {
         let mut iter = WordIterator::new(
             text.char_indices(),
             padded_rect.size.x,
-            font_size_logical * self.font_scale, 
+            font_size,
         );
         
-        while let Some(word) = iter.next_word(fonts_atlas.fonts[font_id].as_mut().unwrap()) {
+        // words that the turtle puts next to each other are drawn together, so the bidi runs of
+        // the line they form are reordered across words
+        let mut lines: Vec<(DVec2, f64, Range<usize>)> = Vec::new();
+        while let Some(word) = iter.next_word(fonts_atlas, &font_ids) {
             let walk_rect = cx.walk_turtle(Walk {
                 abs_pos: None,
                 margin: Margin::default(),
                 width: Size::Fixed(word.width),
-                height: Size::Fixed(line_height)
+                height: Size::Fixed(line_height),
+                ..Walk::default()
             });
-            // make sure our iterator uses the xpos from the turtle
-            self.draw_inner(cx, walk_rect.pos, &text[word.start..word.end], fonts_atlas);
+            match lines.last_mut() {
+                Some((pos, width, range)) if range.end == word.start
+                    && pos.y == walk_rect.pos.y
+                    && (pos.x + *width - walk_rect.pos.x).abs() < 0.001 => {
+                    *width += word.width;
+                    range.end = word.end;
+                }
+                _ => lines.push((walk_rect.pos, word.width, word.start..word.end))
+            }
+        }
+        for (pos, _, range) in lines {
+            self.draw_inner(cx, pos, text[range].trim_end_matches('\r'), fonts_atlas);
         }
         if self.many_instances.is_some() {
             self.end_many_instances(cx)
@@ -652,15 +679,16 @@ impl DrawText {
         if text.len() == 0 {
             return
         }        
-        let font_id = if let Some(font_id) = self.text_style.font.font_id{font_id}else{
+        if self.text_style.font.font_id.is_none() {
             //log!("Draw text without font");
             return
-        };
+        }
+        let font_ids = self.text_style.font_ids();
         let fonts_atlas_rc = cx.fonts_atlas_rc.clone();
         let mut fonts_atlas = fonts_atlas_rc.0.borrow_mut();
         let fonts_atlas = &mut*fonts_atlas;
         
-        let font_size_logical = self.text_style.font_size * 96.0 / (72.0 * fonts_atlas.fonts[font_id].as_ref().unwrap().ttf_font.units_per_em);
+        let font_size = self.text_style.font_size * self.font_scale;
         let line_height = self.text_style.font_size * self.text_style.height_factor * self.font_scale;
                 
         //let in_many = self.many_instances.is_some();
@@ -686,7 +714,8 @@ impl DrawText {
                             abs_pos: walk.abs_pos,
                             margin: walk.margin,
                             width: Size::Fixed(geom.eval_width),
-                            height: Size::Fixed(height)
+                            height: Size::Fixed(height),
+                            ..walk
                         });
                         
                         self.draw_inner(cx, rect.pos + dvec2(0.0, y_align), &text[0..ellip], fonts_atlas);
@@ -703,7 +732,8 @@ impl DrawText {
                                 } else {
                                     geom.eval_height
                                 }
-                            )
+                            ),
+                            ..walk
                         });
                         let x_align = (geom.eval_width - geom.measured_width) * align.x;
                         self.draw_inner(cx, rect.pos + dvec2(x_align, y_align), text, fonts_atlas);
@@ -714,24 +744,38 @@ impl DrawText {
                         abs_pos: walk.abs_pos,
                         margin: walk.margin,
                         width: Size::Fixed(geom.eval_width),
-                        height: Size::Fixed(geom.measured_height)
+                        height: Size::Fixed(geom.measured_height),
+                        ..walk
                     });
                     let mut pos = dvec2(0.0, 0.0);
                     
-                    let mut iter = WordIterator::new(text.char_indices(), geom.eval_width, font_size_logical * self.font_scale);
-                    while let Some(word) = iter.next_word(fonts_atlas.fonts[font_id].as_mut().unwrap()) {
+                    // the words are gathered into lines first, which are drawn as a whole so
+                    // their bidi runs are reordered across words
+                    let mut lines: Vec<(DVec2, Range<usize>)> = Vec::new();
+                    let mut at_line_start = true;
+                    let mut iter = WordIterator::new(text.char_indices(), geom.eval_width, font_size);
+                    while let Some(word) = iter.next_word(fonts_atlas, &font_ids) {
                         if pos.x + word.width >= geom.eval_width {
                             pos.y += line_height * self.text_style.line_spacing;
                             pos.x = 0.0;
+                            at_line_start = true;
                         }
-                        self.draw_inner(cx, rect.pos + pos, &text[word.start..word.end], fonts_atlas);
+                        match lines.last_mut() {
+                            Some((_, range)) if !at_line_start => range.end = word.end,
+                            _ => lines.push((rect.pos + pos, word.start..word.end))
+                        }
+                        at_line_start = false;
                         pos.x += word.width;
                         
                         if word.with_newline {
                             pos.y += line_height * self.text_style.line_spacing;
                             pos.x = 0.0;
+                            at_line_start = true;
                         }
                     }
+                    for (pos, range) in lines {
+                        self.draw_inner(cx, pos, text[range].trim_end_matches('\r'), fonts_atlas);
+                    }
                 }
                 TextWrap::Line => {
                     // lets just output it and walk it
@@ -739,7 +783,8 @@ impl DrawText {
                         abs_pos: walk.abs_pos,
                         margin: walk.margin,
                         width: Size::Fixed(geom.measured_width),
-                        height: Size::Fixed(height)
+                        height: Size::Fixed(height),
+                        ..walk
                     });
                     // lets do our y alignment
                     let mut ypos = 0.0;
@@ -387,3 +387,95 @@ live_design!{
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        crate::{
+            makepad_platform::*,
+            makepad_platform::makepad_shader_compiler::generate_wgsl,
+            shader::{draw_color::DrawColor, draw_icon::DrawIcon, draw_line::DrawLine, draw_text::DrawText},
+        },
+        naga::valid::{Capabilities, ValidationFlags, Validator},
+    };
+    
+    const TEST_SHADER: &str = r#"
+        import makepad_draw::shader::std::*;
+        import makepad_draw::shader::draw_color::DrawColor;
+        
+        WgslTest = <DrawColor> {
+            uniform gain: float
+            instance hover: float
+            varying warp: vec2
+            
+            fn shade(self, t: float, f: fn(x: float) -> float) -> float {
+                return f(t) * self.gain;
+            }
+            
+            fn vertex(self) -> vec4 {
+                self.warp = self.geom_pos * 2.0;
+                return self.clip_and_transform_vertex(self.rect_pos, self.rect_size);
+            }
+            
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                sdf.box(1., 1., self.rect_size.x - 2., self.rect_size.y - 2., 3.);
+                sdf.fill_keep(mix(self.color, #f00, self.hover));
+                sdf.stroke(#fff, 1.);
+                let acc = vec3(0.);
+                for i in 0..4 {
+                    acc.xy += vec2(float(i), mod(self.warp.x, 0.5));
+                }
+                let k = 2.0;
+                let s = self.shade(atan(self.pos.y, self.pos.x), |x| sin(x) * k);
+                let m = mat3(1.0) * acc;
+                if all(lessThan(m, vec3(1.0))) {
+                    acc.z = 1.0;
+                }
+                return sdf.result * mix(0.5, 1.0, step(0.5, s)) + vec4(clamp(acc, 0.0, 1.0), 0.0);
+            }
+        }
+    "#;
+    
+    #[test]
+    fn wgsl_output_validates() {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        crate::live_design(&mut cx);
+        cx.register_live_body(LiveBody {
+            file: "draw/src/shader/std_wgsl_test.rs".to_string(),
+            cargo_manifest_path: env!("CARGO_MANIFEST_DIR").to_string(),
+            module_path: "makepad_draw::shader::std_wgsl_test".to_string(),
+            line: 0,
+            column: 0,
+            code: TEST_SHADER.to_string(),
+            live_type_infos: Vec::new(),
+        });
+        cx.init_cx_os();
+        
+        let _ = DrawColor::new_local(&mut cx);
+        let _ = DrawIcon::new_local(&mut cx);
+        let _ = DrawLine::new_local(&mut cx);
+        let _ = DrawText::new_local(&mut cx);
+        let test_ptr = cx.live_registry.borrow().module_id_and_name_to_ptr(
+            LiveModuleId::from_str("makepad_draw::shader::std_wgsl_test").unwrap(),
+            live_id!(WgslTest)
+        ).unwrap();
+        let _ = DrawColor::new_from_ptr(&mut cx, Some(test_ptr));
+        
+        assert!(cx.shader_registry.draw_shader_defs.len() >= 5);
+        for (draw_shader_ptr, draw_shader_def) in &cx.shader_registry.draw_shader_defs {
+            let const_table = cx.shader_registry.compute_const_table(*draw_shader_ptr);
+            for source in [
+                generate_wgsl::generate_vertex_shader(draw_shader_def, &const_table, &cx.shader_registry),
+                generate_wgsl::generate_pixel_shader(draw_shader_def, &const_table, &cx.shader_registry),
+            ] {
+                let module = naga::front::wgsl::parse_str(&source).unwrap_or_else( | err | {
+                    panic!("{}\n{}", source, err.emit_to_string(&source))
+                });
+                if let Err(err) = Validator::new(ValidationFlags::all(), Capabilities::all()).validate(&module) {
+                    panic!("{}\n{}", source, err.emit_to_string(&source));
+                }
+            }
+        }
+    }
+}
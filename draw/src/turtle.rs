@@ -15,7 +15,8 @@ pub struct Layout {
     #[live] pub align: Align,
     #[live] pub flow: Flow,
     #[live] pub spacing: f64,
-    #[live] pub line_spacing: f64
+    #[live] pub line_spacing: f64,
+    #[live] pub grid: GridLayout
 }
 
 impl Default for Layout{
@@ -28,7 +29,8 @@ impl Default for Layout{
             align: Align{x:0.0,y:0.0},
             flow: Flow::Right,
             spacing: 0.0,
-            line_spacing: 0.0
+            line_spacing: 0.0,
+            grid: GridLayout::default()
         }
     }
 }
@@ -40,6 +42,10 @@ pub struct Walk {
     #[live] pub margin: Margin,
     #[live] pub width: Size,
     #[live] pub height: Size,
+    #[live] pub width_min: Option<f64>,
+    #[live] pub width_max: Option<f64>,
+    #[live] pub height_min: Option<f64>,
+    #[live] pub height_max: Option<f64>,
 }
 
 #[derive(Clone, Copy, Default, Debug, Live, LiveHook, LiveRegister)]
@@ -79,7 +85,51 @@ pub enum Flow {
     //Left,
     //Up,
     Overlay, 
-    RightWrap
+    RightWrap,
+    // places children in the cells of `Layout::grid`, row by row
+    Grid
+}
+
+/// The size of a column or row of a `Flow::Grid`. A plain number is a `Fixed` size.
+#[derive(Copy, Clone, Debug, Live)]
+#[live_ignore]
+pub enum GridTrack {
+    // as large as the largest child in it
+    #[pick] Fit,
+    #[live(100.0)] Fixed(f64),
+    // a share of the space the other tracks leave, by weight, or `Fit` when the grid has no
+    // size of its own on that axis
+    #[live(1.0)] Fr(f64)
+}
+
+pub const GRID_MAX_TRACKS: usize = 16;
+
+/// A column or row template, written as an array like `[Fit, Fr(1.0), 120]`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct GridTracks {
+    tracks: [GridTrack; GRID_MAX_TRACKS],
+    len: usize
+}
+
+#[derive(Copy, Clone, Debug, Default, Live, LiveHook, LiveRegister)]
+#[live_ignore]
+pub struct GridLayout {
+    /// Without columns the grid has a single `Fr(1.0)` column.
+    #[live] pub columns: GridTracks,
+    /// Rows past the template are `Fit`.
+    #[live] pub rows: GridTracks,
+    #[live] pub column_gap: f64,
+    #[live] pub row_gap: f64
+}
+
+/// Where the next child of a `Flow::Grid` goes, set with `Cx2d::set_grid_cell`.
+#[derive(Copy, Clone, Debug, Live, LiveHook, LiveRegister)]
+#[live_ignore]
+pub struct GridCell {
+    #[live(1usize)] pub column_span: usize,
+    #[live(1usize)] pub row_span: usize,
+    /// Aligns the child in its cell instead of `Layout::align`.
+    #[live] pub align: Option<Align>
 }
 
 #[derive(Copy, Clone, Debug, Live)]
@@ -88,17 +138,24 @@ pub enum Size {
     #[pick] Fill,
     #[live(200.0)] Fixed(f64),
     Fit,
-    All
+    All,
+    // a `Fill` that takes this many shares of the space its deferred siblings split, written
+    // as `Fill(2.0)`
+    #[live(1.0)] FillWeighted(f64)
 }
 
 #[derive(Clone, Debug)]
 pub enum DeferWalk{
     Unresolved{
         defer_index: usize,
-        margin: Margin,
-        other_axis: Size,
+        walk: Walk,
         pos: DVec2
     },
+    // a Fill child of a grid, walked once the tracks that decide its cell size are known
+    UnresolvedCell{
+        defer_index: usize,
+        walk: Walk
+    },
     Resolved(Walk)
 }
 
@@ -112,6 +169,14 @@ pub enum AlignEntry{
     EndTurtle
 }
 
+// the weight and size bounds of a deferred Fill child along the flow
+#[derive(Clone, Copy, Debug)]
+struct DeferFill {
+    weight: f64,
+    min: Option<f64>,
+    max: Option<f64>
+}
+
 #[derive(Clone, Default, Debug)]
 pub struct TurtleWalk {
     align_start: usize,
@@ -119,13 +184,47 @@ pub struct TurtleWalk {
     rect: Rect,
 }
 
+#[derive(Clone, Copy, Debug)]
+struct GridPlacement {
+    column: usize,
+    row: usize,
+    column_span: usize,
+    row_span: usize,
+    align: Option<Align>
+}
+
+#[derive(Clone, Debug)]
+struct GridItem {
+    walk_index: usize,
+    placement: GridPlacement,
+    // where the child was walked, and its size including margins
+    rect: Rect
+}
+
+#[derive(Clone, Default, Debug)]
+struct GridState {
+    columns: usize,
+    next_cell: Option<GridCell>,
+    // the cell of the child that is being drawn, reserved when it begins its turtle
+    pending: Option<GridPlacement>,
+    // the cells of deferred Fill children, by defer index
+    deferred: Vec<GridPlacement>,
+    cursor: (usize, usize),
+    // row major cell occupancy
+    occupied: Vec<bool>,
+    items: Vec<GridItem>,
+    // track sizes from the children walked so far, to walk the next ones with
+    column_sizes: Vec<f64>,
+    row_sizes: Vec<f64>
+}
+
 #[derive(Clone, Default, Debug)]
 pub struct Turtle {
     walk: Walk,
     layout: Layout,
     align_start: usize,
     turtle_walks_start: usize,
-    defer_count: usize,
+    defer_fills: Vec<DeferFill>,
     shift: DVec2,
     pos: DVec2,
     origin: DVec2,
@@ -133,7 +232,8 @@ pub struct Turtle {
     height: f64,
     width_used: f64,
     height_used: f64,
-    guard_area: Area
+    guard_area: Area,
+    grid: Option<GridState>
 }
 
 impl<'a> Cx2d<'a> {
@@ -154,12 +254,9 @@ impl<'a> Cx2d<'a> {
             return None
         }
         let turtle = self.turtles.last_mut().unwrap();
-        let defer_index = turtle.defer_count;
+        let defer_index = turtle.defer_fills.len();
         let pos = turtle.pos;
-        let size = dvec2(
-            turtle.eval_width(walk.width, walk.margin, turtle.layout.flow),
-            turtle.eval_height(walk.height, walk.margin, turtle.layout.flow)
-        );
+        let size = turtle.eval_walk(&walk);
         let margin_size = walk.margin.size();
         match turtle.layout.flow {
             Flow::Right if walk.width.is_fill() => {
@@ -167,11 +264,14 @@ impl<'a> Cx2d<'a> {
                 turtle.pos.x += margin_size.x + spacing.x;
                 turtle.update_width_max(turtle.pos.x, 0.0);
                 turtle.update_height_max(turtle.pos.y, size.y + margin_size.y);
-                turtle.defer_count += 1;
+                turtle.defer_fills.push(DeferFill {
+                    weight: walk.width.fill_weight(),
+                    min: walk.width_min,
+                    max: walk.width_max
+                });
                 Some(DeferWalk::Unresolved{
                     defer_index,
-                    margin: walk.margin,
-                    other_axis: walk.height,
+                    walk,
                     pos: pos + spacing
                 })
             },
@@ -180,11 +280,14 @@ impl<'a> Cx2d<'a> {
                 turtle.pos.y += margin_size.y + spacing.y;
                 turtle.update_width_max(turtle.pos.x, size.x + margin_size.x);
                 turtle.update_height_max(turtle.pos.y, 0.0);
-                turtle.defer_count += 1;
+                turtle.defer_fills.push(DeferFill {
+                    weight: walk.height.fill_weight(),
+                    min: walk.height_min,
+                    max: walk.height_max
+                });
                 Some(DeferWalk::Unresolved {
                     defer_index,
-                    margin: walk.margin,
-                    other_axis: walk.width,
+                    walk,
                     pos: pos + spacing
                 })
             },
@@ -192,6 +295,15 @@ impl<'a> Cx2d<'a> {
                 error!("flow RightWrap does not support fill childnodes");
                 None
             },
+            Flow::Grid if turtle.grid_fill_depends_on_fr(walk) => {
+                turtle.reserve_grid_cell();
+                let grid = turtle.grid.as_mut().unwrap();
+                grid.deferred.push(grid.pending.take().unwrap());
+                Some(DeferWalk::UnresolvedCell{
+                    defer_index: grid.deferred.len() - 1,
+                    walk
+                })
+            },
             _ => {
                 None
             }
@@ -201,12 +313,12 @@ impl<'a> Cx2d<'a> {
     pub fn begin_pass_sized_turtle(&mut self, layout: Layout) {
         let pass_size = self.current_pass_size();
         self.align_list.push(AlignEntry::BeginTurtle(dvec2(0.0,0.0),pass_size));
-        let turtle = Turtle {
+        let mut turtle = Turtle {
             walk: Walk::fill(),
             layout,
             align_start: self.align_list.len() - 1,
             turtle_walks_start: self.turtle_walks.len(),
-            defer_count: 0,
+            defer_fills: Vec::new(),
             pos: DVec2 {
                 x: layout.padding.left,
                 y: layout.padding.top
@@ -218,7 +330,9 @@ impl<'a> Cx2d<'a> {
             width_used: layout.padding.left,
             height_used: layout.padding.top,
             guard_area: Area::Empty,
+            grid: GridState::for_layout(&layout),
         };
+        turtle.update_grid_track_sizes();
         self.turtles.push(turtle);
     }
     
@@ -247,14 +361,18 @@ impl<'a> Cx2d<'a> {
     }
     
     pub fn begin_turtle_with_guard(&mut self, walk: Walk, layout: Layout, guard_area: Area) {
+        if let (Some(parent), None) = (self.turtles.last_mut(), walk.abs_pos) {
+            parent.reserve_grid_cell();
+        }
         let (origin, width, height, draw_clip) = if let Some(parent) = self.turtles.last() {
             
-            let o = walk.margin.left_top() + if let Some(pos) = walk.abs_pos {pos} else {
+            let o = walk.margin.left_top() + if let Some(pos) = walk.abs_pos {pos} else if let Some(cell) = parent.grid_cell_rect() {
+                cell.pos
+            } else {
                 parent.pos + parent.child_spacing(self.turtle_walks.len()) 
             };
             
-            let w = parent.eval_width(walk.width, walk.margin, parent.layout.flow);
-            let h = parent.eval_height(walk.height, walk.margin, parent.layout.flow);
+            let DVec2 {x: w, y: h} = parent.eval_walk(&walk);
             
             // figure out new clipping rect
             let (x0, x1) = if layout.clip_x {
@@ -280,18 +398,18 @@ impl<'a> Cx2d<'a> {
         }
         else {
             let o = DVec2 {x: walk.margin.left, y: walk.margin.top};
-            let w = walk.width.fixed_or_nan();
-            let h = walk.height.fixed_or_nan();
+            let w = walk.clamp_width(walk.width.fixed_or_nan());
+            let h = walk.clamp_height(walk.height.fixed_or_nan());
             
             (o, w, h, (dvec2(o.x, o.y), dvec2(o.x + w, o.y + h)))
         };
         self.align_list.push(AlignEntry::BeginTurtle(draw_clip.0,draw_clip.1));
-        let turtle = Turtle {
+        let mut turtle = Turtle {
             walk,
             layout,
             align_start: self.align_list.len()-1,
             turtle_walks_start: self.turtle_walks.len(),
-            defer_count: 0,
+            defer_fills: Vec::new(),
             pos: DVec2 {
                 x: origin.x + layout.padding.left,
                 y: origin.y + layout.padding.top
@@ -303,8 +421,9 @@ impl<'a> Cx2d<'a> {
             width_used: layout.padding.left,
             height_used: layout.padding.top,
             guard_area,
+            grid: GridState::for_layout(&layout),
         };
-        
+        turtle.update_grid_track_sizes();
         self.turtles.push(turtle);
     }
     
@@ -322,21 +441,28 @@ impl<'a> Cx2d<'a> {
     }
     
     pub fn end_turtle_with_guard(&mut self, guard_area: Area) -> Rect {
-        let turtle = self.turtles.pop().unwrap();
+        let mut turtle = self.turtles.pop().unwrap();
         if guard_area != turtle.guard_area {
             panic!("End turtle guard area misaligned!, begin/end pair not matched begin {:?} end {:?}", turtle.guard_area, guard_area)
         }
         
+        if let Some(grid) = &turtle.grid {
+            let width = track_span_size(&grid.column_sizes, 0, grid.column_sizes.len(), turtle.layout.grid.column_gap);
+            let height = track_span_size(&grid.row_sizes, 0, grid.row_sizes.len(), turtle.layout.grid.row_gap);
+            turtle.width_used = turtle.layout.padding.left + width;
+            turtle.height_used = turtle.layout.padding.top + height;
+        }
+        
         // computed width / height
         let w = if turtle.width.is_nan() {
-            Size::Fixed(turtle.width_used + turtle.layout.padding.right - turtle.layout.scroll.x)
+            Size::Fixed(turtle.walk.clamp_width(turtle.width_used + turtle.layout.padding.right - turtle.layout.scroll.x))
         }
         else {
             Size::Fixed(turtle.width)
         };
         
         let h = if turtle.height.is_nan() {
-            Size::Fixed(turtle.height_used + turtle.layout.padding.bottom - turtle.layout.scroll.y)
+            Size::Fixed(turtle.walk.clamp_height(turtle.height_used + turtle.layout.padding.bottom - turtle.layout.scroll.y))
         }
         else {
             Size::Fixed(turtle.height)
@@ -344,12 +470,11 @@ impl<'a> Cx2d<'a> {
         
         match turtle.layout.flow {
             Flow::Right => {
-                if turtle.defer_count > 0 {
-                    let left = turtle.width_left();
-                    let part = left / turtle.defer_count as f64;
+                if !turtle.defer_fills.is_empty() {
+                    let sizes = distribute_fill(turtle.width_left(), &turtle.defer_fills);
                     for i in turtle.turtle_walks_start..self.turtle_walks.len() {
                         let walk = &self.turtle_walks[i];
-                        let shift_x = walk.defer_index as f64 * part;
+                        let shift_x = sizes[0..walk.defer_index].iter().sum();
                         let shift_y = turtle.layout.align.y * (turtle.padded_height_or_used() - walk.rect.size.y);
                         let align_start = walk.align_start;
                         let align_end = self.get_turtle_walk_align_end(i);
@@ -368,17 +493,16 @@ impl<'a> Cx2d<'a> {
                 }
             },
             Flow::RightWrap=>{
-                if turtle.defer_count > 0{panic!()}
+                if !turtle.defer_fills.is_empty(){panic!()}
                 // for now we only support align:0,0
             }
             Flow::Down => {
-                if turtle.defer_count > 0 {
-                    let left = turtle.height_left();
-                    let part = left / turtle.defer_count as f64;
+                if !turtle.defer_fills.is_empty() {
+                    let sizes = distribute_fill(turtle.height_left(), &turtle.defer_fills);
                     for i in turtle.turtle_walks_start..self.turtle_walks.len() {
                         let walk = &self.turtle_walks[i];
                         let shift_x = turtle.layout.align.x * (turtle.padded_width_or_used() - walk.rect.size.x);
-                        let shift_y = walk.defer_index as f64 * part;
+                        let shift_y = sizes[0..walk.defer_index].iter().sum();
                         let align_start = walk.align_start;
                         let align_end = self.get_turtle_walk_align_end(i);
                         self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
@@ -405,6 +529,20 @@ impl<'a> Cx2d<'a> {
                     self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
                 }
             }
+            Flow::Grid => {
+                // children were walked into cells sized by the children before them, so move
+                // them all to their final cells
+                let mut items = turtle.grid.as_ref().unwrap().items.iter().peekable();
+                for i in turtle.turtle_walks_start..self.turtle_walks.len() {
+                    let shift = match items.next_if( | item | item.walk_index == i) {
+                        Some(item) => turtle.grid_item_shift(item),
+                        None => dvec2(0.0, 0.0)
+                    };
+                    let align_start = self.turtle_walks[i].align_start;
+                    let align_end = self.get_turtle_walk_align_end(i);
+                    self.move_align_list(shift.x, shift.y, align_start, align_end, false, turtle.shift);
+                }
+            }
         }
 
         self.turtle_walks.truncate(turtle.turtle_walks_start);
@@ -419,6 +557,18 @@ impl<'a> Cx2d<'a> {
         rect
     }
     
+    /// Moves a `Flow::RightWrap` turtle to the start of the next line, unless it is at the
+    /// start of a line already.
+    pub fn turtle_new_line(&mut self) {
+        let turtle = self.turtle_mut();
+        let line_start = turtle.origin.x + turtle.layout.padding.left;
+        if turtle.pos.x <= line_start {
+            return
+        }
+        turtle.pos.x = line_start;
+        turtle.pos.y = turtle.pos.y.max(turtle.origin.y + turtle.height_used + turtle.layout.line_spacing);
+    }
+    
     pub fn walk_turtle(&mut self, walk: Walk) -> Rect {
         self.walk_turtle_move(walk, self.align_list.len())
     }
@@ -455,10 +605,10 @@ impl<'a> Cx2d<'a> {
      fn walk_turtle_move(&mut self, walk: Walk, align_start: usize) -> Rect {
         
         let turtle = self.turtles.last_mut().unwrap();
-        let size = dvec2(
-            turtle.eval_width(walk.width, walk.margin, turtle.layout.flow),
-            turtle.eval_height(walk.height, walk.margin, turtle.layout.flow)
-        );
+        if walk.abs_pos.is_none() {
+            turtle.reserve_grid_cell();
+        }
+        let size = turtle.eval_walk(&walk);
         
         if let Some(pos) = walk.abs_pos {
             self.turtle_walks.push(TurtleWalk {
@@ -477,6 +627,11 @@ impl<'a> Cx2d<'a> {
                 Flow::RightWrap=>{
                     panic!("Cannot use abs_pos in a flow::Rightwrap");
                 }
+                Flow::Grid => { // not placed in a cell
+                    turtle.grid.as_mut().unwrap().next_cell = None;
+                    turtle.update_width_max(pos.x, size.x);
+                    turtle.update_height_max(pos.y,size.y);
+                }
             }
             Rect {pos: pos + walk.margin.left_top(), size}
         }
@@ -484,7 +639,7 @@ impl<'a> Cx2d<'a> {
             let spacing = turtle.child_spacing(self.turtle_walks.len());
             let mut pos = turtle.pos;
             let margin_size = walk.margin.size();
-            let defer_index = turtle.defer_count;
+            let defer_index = turtle.defer_fills.len();
             match turtle.layout.flow {
                 Flow::Right => {
                     turtle.pos.x = pos.x + size.x + margin_size.x + spacing.x;
@@ -498,7 +653,9 @@ impl<'a> Cx2d<'a> {
                     }
                 },
                 Flow::RightWrap => {
-                    if turtle.pos.x - turtle.origin.x + size.x > turtle.width - turtle.layout.padding.right - turtle.layout.padding.left{
+                    // items that are wider than a line still start on the current one if it is empty
+                    let at_line_start = turtle.pos.x <= turtle.origin.x + turtle.layout.padding.left;
+                    if !at_line_start && turtle.pos.x - turtle.origin.x + size.x > turtle.width - turtle.layout.padding.right{
                         // lets check if we have to move inner itemsthings
                         pos.x =  turtle.origin.x + turtle.layout.padding.left;
                         let dx = pos.x - turtle.pos.x;                        
@@ -540,6 +697,24 @@ impl<'a> Cx2d<'a> {
                     turtle.update_width_max(turtle.pos.x, size.x);
                     turtle.update_height_max(turtle.pos.y,size.y);
                 }
+                Flow::Grid => {
+                    pos = turtle.grid_cell_rect().unwrap().pos;
+                    let walk_index = self.turtle_walks.len();
+                    let grid = turtle.grid.as_mut().unwrap();
+                    let placement = grid.pending.take().unwrap();
+                    let item_size = size + margin_size;
+                    grid.items.push(GridItem {
+                        walk_index,
+                        placement,
+                        rect: Rect {pos, size: dvec2(
+                            if item_size.x.is_nan() {0.0} else {item_size.x},
+                            if item_size.y.is_nan() {0.0} else {item_size.y}
+                        )}
+                    });
+                    turtle.update_grid_track_sizes();
+                    turtle.update_width_max(pos.x, size.x + margin_size.x);
+                    turtle.update_height_max(pos.y, size.y + margin_size.y);
+                }
             };
             
             self.turtle_walks.push(TurtleWalk {
@@ -556,14 +731,14 @@ impl<'a> Cx2d<'a> {
             return Rect::default()
         }
         let turtle = self.turtles.last().unwrap();
-        let size = dvec2(
-            turtle.eval_width(walk.width, walk.margin, turtle.layout.flow),
-            turtle.eval_height(walk.height, walk.margin, turtle.layout.flow)
-        );
+        let size = turtle.eval_walk(&walk);
         
         if let Some(pos) = walk.abs_pos {
             Rect {pos: pos + walk.margin.left_top(), size}
         }
+        else if let Some(cell) = turtle.grid_cell_rect() {
+            Rect {pos: cell.pos + walk.margin.left_top(), size}
+        }
         else {
             let spacing = turtle.child_spacing(self.turtle_walks.len());
             let pos = turtle.pos;
@@ -696,6 +871,14 @@ impl<'a> Cx2d<'a> {
         }
     }
     
+    /// Sets the spans and alignment of the next child of a `Flow::Grid` turtle. Children
+    /// without one take a single cell. Does nothing in the other flows.
+    pub fn set_grid_cell(&mut self, cell: GridCell) {
+        if let Some(grid) = self.turtles.last_mut().and_then( | turtle | turtle.grid.as_mut()) {
+            grid.next_cell = Some(cell);
+        }
+    }
+    
     pub fn get_turtle_align_range(&self) -> TurtleAlignRange {
         TurtleAlignRange{
             start:  self.turtles.last().unwrap().align_start,
@@ -766,7 +949,7 @@ impl Turtle {
     }
     
     fn child_spacing(&self, walks_len: usize) -> DVec2 {
-        if self.turtle_walks_start < walks_len || self.defer_count > 0 {
+        if self.turtle_walks_start < walks_len || !self.defer_fills.is_empty() {
             match self.layout.flow {
                 Flow::Right => {
                     dvec2(self.layout.spacing, 0.0)
@@ -774,7 +957,7 @@ impl Turtle {
                 Flow::Down => {
                     dvec2(0.0, self.layout.spacing)
                 }
-                Flow::Overlay => {
+                Flow::Overlay | Flow::Grid => {
                     dvec2(0.0, 0.0)
                 }
                 Flow::RightWrap=>{
@@ -818,11 +1001,20 @@ impl Turtle {
         self.layout.scroll
     }
     
+    /// The size of a walk in this turtle, kept within its minimum and maximum sizes. A `NaN`
+    /// is a size that is fit to the content.
+    pub fn eval_walk(&self, walk: &Walk) -> DVec2 {
+        dvec2(
+            walk.clamp_width(self.eval_width(walk.width, walk.margin, self.layout.flow)),
+            walk.clamp_height(self.eval_height(walk.height, walk.margin, self.layout.flow))
+        )
+    }
+    
     pub fn eval_width(&self, width: Size, margin: Margin, flow: Flow) -> f64 {
         return match width {
             Size::Fit => std::f64::NAN,
             Size::Fixed(v) => max_zero_keep_nan(v),
-            Size::Fill => {
+            Size::Fill | Size::FillWeighted(_) => {
                 match flow {
                     Flow::Right | Flow::RightWrap=> {
                         max_zero_keep_nan(self.width_left() - margin.width())
@@ -834,6 +1026,9 @@ impl Turtle {
                         }
                         return r
                     }
+                    Flow::Grid => {
+                        max_zero_keep_nan(self.grid_fill_size().x - margin.width())
+                    }
                 }
             },
             Size::All=>self.width
@@ -844,7 +1039,7 @@ impl Turtle {
         return match height {
             Size::Fit => std::f64::NAN,
             Size::Fixed(v) => max_zero_keep_nan(v),
-            Size::Fill => {
+            Size::Fill | Size::FillWeighted(_) => {
                 match flow {
                     Flow::RightWrap | Flow::Right | Flow::Overlay => {
                         let r = max_zero_keep_nan(self.height - self.layout.padding.height() - margin.height());
@@ -856,6 +1051,9 @@ impl Turtle {
                     Flow::Down => {
                         max_zero_keep_nan(self.height_left() - margin.height())
                     }
+                    Flow::Grid => {
+                        max_zero_keep_nan(self.grid_fill_size().y - margin.height())
+                    }
                 }
             }
             Size::All=>self.height
@@ -929,38 +1127,319 @@ impl Turtle {
     }
 }
 
+impl Turtle {
+    // reserves a cell for the next child of a grid, until it is walked
+    fn reserve_grid_cell(&mut self) {
+        if let Some(grid) = &mut self.grid {
+            if grid.pending.is_none() {
+                let placement = grid.peek_placement();
+                grid.occupy(&placement);
+                grid.cursor = (placement.column + placement.column_span, placement.row);
+                grid.pending = Some(placement);
+            }
+            grid.next_cell = None;
+        }
+    }
+    
+    fn update_grid_track_sizes(&mut self) {
+        let available = dvec2(
+            max_zero_keep_nan(self.width - self.layout.padding.width()),
+            max_zero_keep_nan(self.height - self.layout.padding.height())
+        );
+        let layout = &self.layout.grid;
+        if let Some(grid) = &mut self.grid {
+            let column_spans: Vec<_> = grid.items.iter().map( | item | {
+                (item.placement.column, item.placement.column_span, item.rect.size.x)
+            }).collect();
+            let row_spans: Vec<_> = grid.items.iter().map( | item | {
+                (item.placement.row, item.placement.row_span, item.rect.size.y)
+            }).collect();
+            let rows = layout.rows.len().max(grid.occupied.len() / grid.columns);
+            grid.column_sizes = grid_track_sizes(layout.column_tracks(), grid.columns, &column_spans, layout.column_gap, available.x);
+            grid.row_sizes = grid_track_sizes(layout.rows.tracks(), rows, &row_spans, layout.row_gap, available.y);
+        }
+    }
+    
+    fn grid_placement_rect(&self, placement: &GridPlacement) -> Rect {
+        let grid = self.grid.as_ref().unwrap();
+        let gap = dvec2(self.layout.grid.column_gap, self.layout.grid.row_gap);
+        Rect {
+            pos: self.origin + self.layout.padding.left_top() + dvec2(
+                track_span_size(&grid.column_sizes, 0, placement.column, gap.x) + if placement.column > 0 {gap.x} else {0.0},
+                track_span_size(&grid.row_sizes, 0, placement.row, gap.y) + if placement.row > 0 {gap.y} else {0.0},
+            ),
+            size: dvec2(
+                track_span_size(&grid.column_sizes, placement.column, placement.column_span, gap.x),
+                track_span_size(&grid.row_sizes, placement.row, placement.row_span, gap.y),
+            )
+        }
+    }
+    
+    /// The cell the next child of a grid is walked into, sized by the children walked so far.
+    fn grid_cell_rect(&self) -> Option<Rect> {
+        let grid = self.grid.as_ref()?;
+        let placement = grid.pending.unwrap_or_else( || grid.peek_placement());
+        Some(self.grid_placement_rect(&placement))
+    }
+    
+    // the size a Fill child gets in its cell, which is only known when none of the tracks it
+    // spans depend on the size of their children
+    fn grid_fill_size(&self) -> DVec2 {
+        let Some(grid) = &self.grid else {
+            return dvec2(f64::NAN, f64::NAN)
+        };
+        let placement = grid.pending.unwrap_or_else( || grid.peek_placement());
+        let size = self.grid_placement_rect(&placement).size;
+        let columns = self.layout.grid.column_tracks();
+        let rows = self.layout.grid.rows.tracks();
+        let available_x = self.width - self.layout.padding.width();
+        let available_y = self.height - self.layout.padding.height();
+        dvec2(
+            if (placement.column..placement.column + placement.column_span).any( | i | track_is_fit(columns, i, available_x)) {
+                f64::NAN
+            } else {
+                size.x
+            },
+            if (placement.row..placement.row + placement.row_span).any( | i | track_is_fit(rows, i, available_y)) {
+                f64::NAN
+            } else {
+                size.y
+            }
+        )
+    }
+    
+    // whether a Fill child spans a fractional track, which the Fit tracks walked after it
+    // could still shrink
+    fn grid_fill_depends_on_fr(&self, walk: Walk) -> bool {
+        let Some(grid) = &self.grid else {
+            return false
+        };
+        let placement = grid.peek_placement();
+        let spans_fr = | tracks: &[GridTrack], start: usize, span: usize, available: f64 | {
+            !available.is_nan() && (start..start + span).any( | i | matches!(tracks.get(i), Some(GridTrack::Fr(_))))
+        };
+        walk.width.is_fill() && spans_fr(
+            self.layout.grid.column_tracks(),
+            placement.column,
+            placement.column_span,
+            self.width - self.layout.padding.width()
+        ) || walk.height.is_fill() && spans_fr(
+            self.layout.grid.rows.tracks(),
+            placement.row,
+            placement.row_span,
+            self.height - self.layout.padding.height()
+        )
+    }
+    
+    fn grid_item_shift(&self, item: &GridItem) -> DVec2 {
+        let cell = self.grid_placement_rect(&item.placement);
+        let align = item.placement.align.unwrap_or(self.layout.align);
+        cell.pos - item.rect.pos + dvec2(
+            align.x * (cell.size.x - item.rect.size.x),
+            align.y * (cell.size.y - item.rect.size.y)
+        )
+    }
+}
+
+impl GridState {
+    fn for_layout(layout: &Layout) -> Option<Self> {
+        if let Flow::Grid = layout.flow {
+            Some(Self {
+                columns: layout.grid.column_tracks().len(),
+                ..Self::default()
+            })
+        }
+        else {
+            None
+        }
+    }
+    
+    // auto placement goes row by row, and leaves the cells before the cursor empty
+    fn peek_placement(&self) -> GridPlacement {
+        let cell = self.next_cell.unwrap_or_default();
+        let column_span = cell.column_span.clamp(1, self.columns);
+        let row_span = cell.row_span.max(1);
+        let (mut column, mut row) = self.cursor;
+        loop {
+            if column + column_span > self.columns {
+                column = 0;
+                row += 1;
+            }
+            else if self.is_free(column, row, column_span, row_span) {
+                return GridPlacement {column, row, column_span, row_span, align: cell.align}
+            }
+            else {
+                column += 1;
+            }
+        }
+    }
+    
+    fn is_free(&self, column: usize, row: usize, column_span: usize, row_span: usize) -> bool {
+        (row..row + row_span).all( | r | (column..column + column_span).all( | c | {
+            !self.occupied.get(r * self.columns + c).copied().unwrap_or(false)
+        }))
+    }
+    
+    fn occupy(&mut self, placement: &GridPlacement) {
+        let end = (placement.row + placement.row_span) * self.columns;
+        if self.occupied.len() < end {
+            self.occupied.resize(end, false);
+        }
+        for r in placement.row..placement.row + placement.row_span {
+            for c in placement.column..placement.column + placement.column_span {
+                self.occupied[r * self.columns + c] = true;
+            }
+        }
+    }
+}
+
+fn track_is_fit(tracks: &[GridTrack], index: usize, available: f64) -> bool {
+    match tracks.get(index) {
+        Some(GridTrack::Fixed(_)) => false,
+        Some(GridTrack::Fr(_)) => available.is_nan(),
+        Some(GridTrack::Fit) | None => true,
+    }
+}
+
+fn track_span_size(sizes: &[f64], start: usize, span: usize, gap: f64) -> f64 {
+    if span == 0 {
+        return 0.0
+    }
+    (start..start + span).map( | i | sizes.get(i).copied().unwrap_or(0.0)).sum::<f64>() + gap * (span - 1) as f64
+}
+
+/// Sizes `count` tracks of a grid, from their template and the `(start, span, size)` of the
+/// children in them. Tracks past the template are `Fit`.
+fn grid_track_sizes(tracks: &[GridTrack], count: usize, children: &[(usize, usize, f64)], gap: f64, available: f64) -> Vec<f64> {
+    let mut sizes: Vec<f64> = (0..count).map( | i | match tracks.get(i) {
+        Some(GridTrack::Fixed(v)) => v.max(0.0),
+        _ => 0.0
+    }).collect();
+    // children in a single track size it, then children spanning more tracks grow the fitting
+    // ones they span evenly, smallest spans first
+    for (start, _, size) in children.iter().filter( | child | child.1 == 1) {
+        if *start < count && track_is_fit(tracks, *start, available) {
+            sizes[*start] = sizes[*start].max(*size);
+        }
+    }
+    let mut spanning: Vec<_> = children.iter().filter( | child | child.1 > 1).collect();
+    spanning.sort_by_key( | child | child.1);
+    for (start, span, size) in spanning {
+        let end = (start + span).min(count);
+        let fit: Vec<usize> = (*start..end).filter( | i | track_is_fit(tracks, *i, available)).collect();
+        let used = track_span_size(&sizes, *start, end - start, gap);
+        if !fit.is_empty() && *size > used {
+            let grow = (size - used) / fit.len() as f64;
+            for i in fit {
+                sizes[i] += grow;
+            }
+        }
+    }
+    if !available.is_nan() {
+        let weight = | i: usize | match tracks.get(i) {
+            Some(GridTrack::Fr(weight)) => Some(weight.max(0.0)),
+            _ => None
+        };
+        let total: f64 = (0..count).filter_map(weight).sum();
+        if total > 0.0 {
+            let used = (0..count).filter( | i | weight(*i).is_none()).map( | i | sizes[i]).sum::<f64>()
+                + gap * count.saturating_sub(1) as f64;
+            let left = (available - used).max(0.0);
+            for (i, size) in sizes.iter_mut().enumerate() {
+                if let Some(weight) = weight(i) {
+                    *size = left * weight / total;
+                }
+            }
+        }
+    }
+    sizes
+}
+
+/// Splits the space left in a turtle between its deferred Fill children by weight. Children
+/// that would go past their minimum or maximum size get that size instead, and the others
+/// split what remains.
+fn distribute_fill(left: f64, fills: &[DeferFill]) -> Vec<f64> {
+    let mut sizes = vec![f64::NAN; fills.len()];
+    if left.is_nan() {
+        return sizes
+    }
+    let mut frozen = vec![false; fills.len()];
+    loop {
+        let free = left - (0..fills.len()).filter( | i | frozen[*i]).map( | i | sizes[i]).sum::<f64>();
+        let weight: f64 = (0..fills.len()).filter( | i | !frozen[*i]).map( | i | fills[i].weight).sum();
+        let mut violation = 0.0;
+        let mut grown = Vec::new();
+        let mut shrunk = Vec::new();
+        for (i, fill) in fills.iter().enumerate().filter( | (i, _) | !frozen[*i]) {
+            let share = if weight > 0.0 {free.max(0.0) * fill.weight / weight} else {0.0};
+            sizes[i] = clamp_keep_nan(share, fill.min, fill.max);
+            violation += sizes[i] - share;
+            if sizes[i] > share {
+                grown.push(i);
+            }
+            else if sizes[i] < share {
+                shrunk.push(i);
+            }
+        }
+        // like flexbox, only the children on the side of the total violation keep their bound
+        let freeze = if violation > 0.0 {grown} else if violation < 0.0 {shrunk} else {break};
+        if freeze.is_empty() {
+            break
+        }
+        for i in freeze {
+            frozen[i] = true;
+        }
+    }
+    sizes
+}
+
 impl DeferWalk {
     
-    pub fn resolve(&mut self, cx: &Cx2d) -> Walk {
+    pub fn resolve(&mut self, cx: &mut Cx2d) -> Walk {
         match self{
             Self::Resolved(walk)=>{*walk},
-            Self::Unresolved{pos, defer_index, margin, other_axis}=>{
+            Self::UnresolvedCell{defer_index, walk}=>{
+                // the child is walked into the cell it was deferred with
+                let turtle = cx.turtles.last_mut().unwrap();
+                let grid = turtle.grid.as_mut().unwrap();
+                grid.pending = Some(grid.deferred[*defer_index]);
+                let size = turtle.grid_fill_size();
+                let resolve = | size: Size, fill: f64, margin: f64 | {
+                    if size.is_fill() && !fill.is_nan() {Size::Fixed(max_zero_keep_nan(fill - margin))} else {size}
+                };
+                let walk = Walk {
+                    width: resolve(walk.width, size.x, walk.margin.width()),
+                    height: resolve(walk.height, size.y, walk.margin.height()),
+                    ..*walk
+                };
+                *self = DeferWalk::Resolved(walk);
+                walk
+            }
+            Self::Unresolved{pos, defer_index, walk}=>{
                 let turtle = cx.turtles.last().unwrap();
                 let walk = match turtle.layout.flow {
                     Flow::Right => {
-                        let left = turtle.width_left();
-                        let part = left / turtle.defer_count as f64;
+                        let sizes = distribute_fill(turtle.width_left(), &turtle.defer_fills);
+                        let offset: f64 = sizes[0..*defer_index].iter().sum();
                         Walk {
-                            abs_pos: Some(*pos + dvec2(part * *defer_index as f64, 0.)),
-                            margin: *margin,
-                            width: Size::Fixed(part),
-                            height: *other_axis
+                            abs_pos: Some(*pos + dvec2(offset, 0.)),
+                            width: Size::Fixed(sizes[*defer_index]),
+                            ..*walk
                         }
                     },
                     Flow::RightWrap => {
                         panic!()
                     }
                     Flow::Down => { 
-                        let left = turtle.height_left();
-                        let part = left / turtle.defer_count as f64;
+                        let sizes = distribute_fill(turtle.height_left(), &turtle.defer_fills);
+                        let offset: f64 = sizes[0..*defer_index].iter().sum();
                         Walk {
-                            abs_pos: Some(*pos + dvec2(0., part * *defer_index as f64)),
-                            margin: *margin,
-                            height: Size::Fixed(part),
-                            width: *other_axis
+                            abs_pos: Some(*pos + dvec2(0., offset)),
+                            height: Size::Fixed(sizes[*defer_index]),
+                            ..*walk
                         }
                     }
-                    Flow::Overlay => panic!()
+                    Flow::Overlay | Flow::Grid => panic!()
                 };
                 *self = DeferWalk::Resolved(walk);
                 walk
@@ -991,6 +1470,19 @@ impl Layout {
             ..Self::default()
         }
     }
+    
+    pub fn grid(columns: &[GridTrack], column_gap: f64, row_gap: f64) -> Self {
+        Self {
+            flow: Flow::Grid,
+            grid: GridLayout {
+                columns: GridTracks::new(columns),
+                rows: GridTracks::default(),
+                column_gap,
+                row_gap
+            },
+            ..Self::default()
+        }
+    }
 
     pub fn with_scroll(mut self, v: DVec2) -> Self {
         self.scroll = v;
@@ -1045,12 +1537,41 @@ impl Layout {
 }
 
 impl Walk {
+    pub fn clamp_width(&self, width: f64) -> f64 {
+        clamp_keep_nan(width, self.width_min, self.width_max)
+    }
+    
+    pub fn clamp_height(&self, height: f64) -> f64 {
+        clamp_keep_nan(height, self.height_min, self.height_max)
+    }
+    
+    pub fn with_width_min(mut self, v: f64) -> Self {
+        self.width_min = Some(v);
+        self
+    }
+    
+    pub fn with_width_max(mut self, v: f64) -> Self {
+        self.width_max = Some(v);
+        self
+    }
+    
+    pub fn with_height_min(mut self, v: f64) -> Self {
+        self.height_min = Some(v);
+        self
+    }
+    
+    pub fn with_height_max(mut self, v: f64) -> Self {
+        self.height_max = Some(v);
+        self
+    }
+    
     pub fn empty() -> Self {
         Self {
             abs_pos: None,
             margin: Margin::default(),
             width: Size::Fixed(0.0),
             height: Size::Fixed(0.0),
+            ..Self::default()
         }
     }
     
@@ -1060,6 +1581,7 @@ impl Walk {
             margin: Margin::default(),
             width: w,
             height: h,
+            ..Self::default()
         }
     }
 
@@ -1069,6 +1591,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fixed(rect.size.x),
             height: Size::Fixed(rect.size.y),
+            ..Self::default()
         }
     }
     
@@ -1078,6 +1601,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fixed(w),
             height: Size::Fixed(h),
+            ..Self::default()
         }
     }
         
@@ -1087,6 +1611,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fixed(size.x),
             height: Size::Fixed(size.y),
+            ..Self::default()
         }
     }
     
@@ -1096,6 +1621,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fit,
             height: Size::Fit,
+            ..Self::default()
         }
     }
     
@@ -1105,6 +1631,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fill,
             height: Size::Fill,
+            ..Self::default()
         }
     }
     
@@ -1114,6 +1641,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fill,
             height: Size::Fit,
+            ..Self::default()
         }
     }
     
@@ -1194,6 +1722,109 @@ impl Default for Flow {
     fn default() -> Self {Self::Down}
 }
 
+impl Default for GridTrack {
+    fn default() -> Self {Self::Fit}
+}
+
+impl LiveHook for GridTrack {
+    fn skip_apply(&mut self, _cx: &mut Cx, _apply: &mut Apply, index: usize, nodes: &[LiveNode]) -> Option<usize> {
+        if let Some(v) = nodes[index].value.as_float(){
+            *self = Self::Fixed(v);
+            Some(index + 1)
+        }
+        else{
+            None
+        }
+    }
+}
+
+impl GridTracks {
+    pub fn new(tracks: &[GridTrack]) -> Self {
+        let mut ret = Self::default();
+        for track in tracks.iter().take(GRID_MAX_TRACKS) {
+            ret.tracks[ret.len] = *track;
+            ret.len += 1;
+        }
+        ret
+    }
+    
+    pub fn tracks(&self) -> &[GridTrack] {
+        &self.tracks[0..self.len]
+    }
+    
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl LiveHook for GridTracks {}
+impl LiveApply for GridTracks {
+    fn apply(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) -> usize {
+        // we can only apply from an Array
+        if nodes[index].is_array(){
+            self.len = 0;
+            let mut index = index + 1;
+            loop{
+                if nodes[index].is_close(){
+                    index += 1;
+                    break;
+                }
+                if self.len < GRID_MAX_TRACKS{
+                    let mut track = GridTrack::default();
+                    index = track.apply(cx, apply, index, nodes);
+                    self.tracks[self.len] = track;
+                    self.len += 1;
+                }
+                else{
+                    error!("a grid can have at most {} columns and {} template rows", GRID_MAX_TRACKS, GRID_MAX_TRACKS);
+                    index = nodes.skip_node(index)
+                }
+            }
+            index
+        }
+        else{
+            cx.apply_error_expected_array(live_error_origin!(), index, nodes);
+            nodes.skip_node(index)
+        }
+    }
+}
+
+impl LiveNew for GridTracks {
+    fn new(_cx: &mut Cx) -> Self {
+        Self::default()
+    }
+    
+    fn live_type_info(cx: &mut Cx) -> LiveTypeInfo {
+        GridTrack::live_type_info(cx)
+    }
+}
+
+impl GridLayout {
+    /// The column template, or a single column that fills the grid if there is none.
+    pub fn column_tracks(&self) -> &[GridTrack] {
+        if self.columns.is_empty() {
+            &[GridTrack::Fr(1.0)]
+        }
+        else {
+            self.columns.tracks()
+        }
+    }
+}
+
+impl Default for GridCell {
+    fn default() -> Self {
+        Self {
+            column_span: 1,
+            row_span: 1,
+            align: None
+        }
+    }
+}
+
 
 impl LiveHook for Size {
     fn skip_apply(&mut self, cx: &mut Cx, _apply: &mut Apply, index: usize, nodes: &[LiveNode]) -> Option<usize> {
@@ -1258,6 +1889,15 @@ impl LiveHook for Size {
                 *self = Self::Fixed(*v as f64);
                 Some(index + 1)
             }
+            LiveValue::TupleEnum(live_id!(Fill)) => {
+                if let Some(v) = nodes[index + 1].value.as_float() {
+                    *self = Self::FillWeighted(v);
+                }
+                else {
+                    cx.apply_error_wrong_value_type_for_primitive(live_error_origin!(), index, nodes, "Fill weight");
+                }
+                Some(nodes.skip_node(index))
+            }
             _ => None
         }
     }
@@ -1300,10 +1940,28 @@ impl Size {
     
     pub fn is_fill(&self) -> bool {
         match self {
-            Self::Fill => true,
+            Self::Fill | Self::FillWeighted(_) => true,
             _ => false
         }
     }
+    
+    /// The share of the space left that a Fill takes next to its deferred siblings.
+    pub fn fill_weight(&self) -> f64 {
+        match self {
+            Self::Fill => 1.0,
+            Self::FillWeighted(v) => v.max(0.0),
+            _ => 0.0
+        }
+    }
+}
+
+// the minimum wins over the maximum
+fn clamp_keep_nan(v: f64, min: Option<f64>, max: Option<f64>) -> f64 {
+    if v.is_nan() {
+        return v
+    }
+    let v = if let Some(max) = max {v.min(max)} else {v};
+    if let Some(min) = min {v.max(min)} else {v}
 }
 
 fn max_zero_keep_nan(v: f64) -> f64 {
@@ -1314,3 +1972,68 @@ fn max_zero_keep_nan(v: f64) -> f64 {
         f64::max(v, 0.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn grid_tracks_are_sized_by_template_and_children() {
+        let tracks = [GridTrack::Fit, GridTrack::Fr(1.0), GridTrack::Fixed(60.0)];
+        let children = [(0, 1, 40.0), (0, 1, 90.0), (1, 1, 500.0), (2, 1, 20.0)];
+        assert_eq!(grid_track_sizes(&tracks, 3, &children, 10.0, 400.0), vec![90.0, 230.0, 60.0]);
+        // without a size of their own, fractional tracks fit their children
+        assert_eq!(grid_track_sizes(&tracks, 3, &children, 10.0, f64::NAN), vec![90.0, 500.0, 60.0]);
+        // weights share what is left, and tracks past the template fit
+        let tracks = [GridTrack::Fr(1.0), GridTrack::Fr(3.0)];
+        assert_eq!(grid_track_sizes(&tracks, 3, &[(2, 1, 30.0)], 0.0, 230.0), vec![50.0, 150.0, 30.0]);
+    }
+    
+    #[test]
+    fn spanning_children_grow_fit_tracks() {
+        let tracks = [GridTrack::Fit, GridTrack::Fit, GridTrack::Fixed(10.0)];
+        let children = [(0, 1, 20.0), (0, 3, 100.0)];
+        assert_eq!(grid_track_sizes(&tracks, 3, &children, 5.0, f64::NAN), vec![50.0, 30.0, 10.0]);
+    }
+    
+    #[test]
+    fn fill_is_split_by_weight_within_bounds() {
+        let fill = | weight, min, max | DeferFill {weight, min, max};
+        assert_eq!(distribute_fill(300.0, &[fill(2.0, None, None), fill(1.0, None, None)]), vec![200.0, 100.0]);
+        // the capped child gives its space to the others
+        let sizes = distribute_fill(300.0, &[fill(1.0, None, Some(50.0)), fill(1.0, None, None), fill(1.0, None, None)]);
+        assert_eq!(sizes, vec![50.0, 125.0, 125.0]);
+        // and a minimum takes space from them
+        let sizes = distribute_fill(300.0, &[fill(1.0, Some(200.0), None), fill(1.0, None, None)]);
+        assert_eq!(sizes, vec![200.0, 100.0]);
+        assert!(distribute_fill(f64::NAN, &[fill(1.0, None, None)])[0].is_nan());
+    }
+    
+    #[test]
+    fn walk_bounds_keep_fit_sizes() {
+        let walk = Walk::fill().with_width_min(200.0).with_width_max(600.0);
+        assert_eq!(walk.clamp_width(100.0), 200.0);
+        assert_eq!(walk.clamp_width(1000.0), 600.0);
+        assert!(walk.clamp_width(f64::NAN).is_nan());
+        assert_eq!(walk.clamp_height(1000.0), 1000.0);
+    }
+    
+    #[test]
+    fn grid_cells_are_placed_row_by_row() {
+        let mut grid = GridState {columns: 3, ..GridState::default()};
+        let mut place = | column_span, row_span | {
+            grid.next_cell = Some(GridCell {column_span, row_span, align: None});
+            let placement = grid.peek_placement();
+            grid.occupy(&placement);
+            grid.cursor = (placement.column + placement.column_span, placement.row);
+            (placement.column, placement.row, placement.column_span)
+        };
+        assert_eq!(place(1, 1), (0, 0, 1));
+        assert_eq!(place(1, 2), (1, 0, 1));
+        // does not fit in the rest of the first row, nor next to the row span in the second
+        assert_eq!(place(2, 1), (0, 2, 2));
+        assert_eq!(place(1, 1), (2, 2, 1));
+        // spans are clamped to the columns
+        assert_eq!(place(5, 1), (0, 3, 3));
+    }
+}
@@ -28,13 +28,10 @@ live_design!{
                 }
             }
             
-            message_label = <Label> {
+            message_label = <Markdown> {
                 width: 300,
                 height: Fit
-                draw_text: {
-                    color: #f
-                },
-                text: "hi! how may I assist you today?",
+                body: "hi! how may I assist you today?",
             }
             
             message_input = <TextInput> {
@@ -101,7 +98,7 @@ impl MatchEvent for App {
        for event in responses{
            match &event.response {
                NetworkResponse::HttpResponse(response) => {
-                   let label = self.ui.label(id!(message_label));
+                   let label = self.ui.widget(id!(message_label));
                    match event.request_id {
                        live_id!(SendChatMessage) => {
                            if response.status_code == 200 {
@@ -116,7 +113,7 @@ impl MatchEvent for App {
                    }
                }
                NetworkResponse::HttpRequestError(error) => {
-                   let label = self.ui.label(id!(message_label));
+                   let label = self.ui.widget(id!(message_label));
                    label.set_text_and_redraw(cx, &format!("Failed to connect with OpenAI {:?}", error));
                }
                _ => ()
@@ -289,6 +289,29 @@ live_design!{
                      }
                 }   
 
+                <ZooHeader>{
+                    title = {text:"Grid"}
+                    <ZooDesc>{text:"A View with flow: Grid places its children in columns and rows. Tracks are Fit, a fixed size, or Fr(weight) for a share of the space that is left. Children can span cells and have their own alignment with grid_cell."}
+                    <View> {
+                        width: 400,
+                        height: Fit,
+                        flow: Grid,
+                        align: {y: 0.5},
+                        grid: {columns: [Fit, Fr(1.0), 60], column_gap: 10, row_gap: 5},
+                        <Label> {text: "Name"}
+                        <TextInput> {width: Fill, text: "Makepad"}
+                        <ZooBlock> {width: 20, height: 20, draw_bg: {color: #0f0}}
+                        <Label> {text: "Email address"}
+                        <TextInput> {width: Fill, text: "zoo@makepad.nl"}
+                        <ZooBlock> {width: 20, height: 20, draw_bg: {color: #f00}}
+                        <View> {
+                            grid_cell: {column_span: 3, align: {x: 1.0}},
+                            width: Fit, height: Fit,
+                            <Button> {text: "Submit"}
+                        }
+                    }
+                }
+
 
 
                 <ZooHeader>{
@@ -412,9 +435,7 @@ live_design!{
                     <ZooHeader>{
                         title = {text:"RadioButton"} <ZooDesc>{text:"RadioButton ?"}<ZooGroup>
                         {
-                            <View>{
-                                width: Fit,
-                                height: Fit,
+                            <RadioButtonGroup>{
                                 <RadioButton>{label:"Option 1: yey"}
                                 <RadioButton>{label:"Option 2: hah"}
                                 <RadioButton>{label:"Option 3: hmm"}
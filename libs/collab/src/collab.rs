@@ -0,0 +1,366 @@
+use {
+    crate::text::{Change, Drift, Edit, Length, Position},
+    std::{fmt, mem},
+};
+
+/// A change that does not fit the text it is transformed against, such as a range that runs past
+/// the largest position that can be represented.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct InvalidChange;
+
+impl fmt::Display for InvalidChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "change does not fit the text")
+    }
+}
+
+/// Transforms two sequences of changes that were made concurrently to the same text.
+///
+/// Returns `(a', b')` such that applying `a` followed by `b'` results in the same text as
+/// applying `b` followed by `a'`. When both sides insert at the same position, the text inserted
+/// by `b` ends up first, so `b` should be the sequence that was established first (for instance,
+/// the one the server already applied).
+pub fn transform(a: Vec<Change>, b: Vec<Change>) -> Result<(Vec<Change>, Vec<Change>), InvalidChange> {
+    if a.is_empty() || b.is_empty() {
+        return Ok((a, b));
+    }
+    if a.len() == 1 && b.len() == 1 {
+        let a = a.into_iter().next().unwrap();
+        let b = b.into_iter().next().unwrap();
+        return Ok((transform_change(&a, &b, false)?, transform_change(&b, &a, true)?));
+    }
+    if a.len() > 1 {
+        let mut a = a;
+        let a_rest = a.split_off(1);
+        let (mut a, b) = transform(a, b)?;
+        let (a_rest, b) = transform(a_rest, b)?;
+        a.extend(a_rest);
+        Ok((a, b))
+    } else {
+        let mut b = b;
+        let b_rest = b.split_off(1);
+        let (a, mut b) = transform(a, b)?;
+        let (a, b_rest) = transform(a, b_rest)?;
+        b.extend(b_rest);
+        Ok((a, b))
+    }
+}
+
+/// Transforms `change` so that it can be applied after `other`, where both were made to the same
+/// text. If `wins` is `true`, text inserted by `change` ends up before text inserted by `other`
+/// at the same position.
+fn transform_change(change: &Change, other: &Change, wins: bool) -> Result<Vec<Change>, InvalidChange> {
+    let other_edit = |drift| Edit {
+        change: other.clone(),
+        drift,
+    };
+    let apply_other = |position: Position, drift| {
+        position
+            .checked_apply_edit(&other_edit(drift))
+            .ok_or(InvalidChange)
+    };
+    match *change {
+        Change::Insert(position, ref text) => {
+            if text.is_empty() {
+                return Ok(Vec::new());
+            }
+            let drift = if wins { Drift::After } else { Drift::Before };
+            Ok(vec![Change::Insert(apply_other(position, drift)?, text.clone())])
+        }
+        Change::Delete(start, length) => {
+            if length == Length::zero() {
+                return Ok(Vec::new());
+            }
+            let end = start.checked_add(length).ok_or(InvalidChange)?;
+            match *other {
+                Change::Insert(position, ref text) if start < position && position < end => {
+                    // The other side inserted text inside the range that we delete. Keep that
+                    // text, and delete what is on either side of it instead.
+                    Ok(vec![
+                        Change::Delete(start, position.checked_sub(start).ok_or(InvalidChange)?),
+                        Change::Delete(
+                            start.checked_add(text.length()).ok_or(InvalidChange)?,
+                            end.checked_sub(position).ok_or(InvalidChange)?,
+                        ),
+                    ])
+                }
+                _ => {
+                    // Text inserted right at either end of the range is not deleted.
+                    let start = apply_other(start, Drift::Before)?;
+                    let end = apply_other(end, Drift::After)?;
+                    if start == end {
+                        Ok(Vec::new())
+                    } else {
+                        Ok(vec![Change::Delete(start, end.checked_sub(start).ok_or(InvalidChange)?)])
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The client side of a collaboration session for a single document.
+///
+/// At most one batch of local changes is in flight to the server at any time. Changes made while
+/// waiting for the server to acknowledge that batch are buffered, and sent as a single batch once
+/// the acknowledgement comes in.
+#[derive(Clone, Debug, Default)]
+pub struct CollabState {
+    revision: usize,
+    outstanding: Option<Vec<Change>>,
+    buffered: Vec<Change>,
+}
+
+impl CollabState {
+    pub fn new(revision: usize) -> Self {
+        Self {
+            revision,
+            outstanding: None,
+            buffered: Vec::new(),
+        }
+    }
+
+    /// The latest revision of the server that this client has seen.
+    pub fn revision(&self) -> usize {
+        self.revision
+    }
+
+    /// Returns `true` if every local change has been acknowledged by the server.
+    pub fn is_synchronized(&self) -> bool {
+        self.outstanding.is_none() && self.buffered.is_empty()
+    }
+
+    /// Records changes that were made locally. Returns the changes that should be sent to the
+    /// server, based on `self.revision()`, if nothing is in flight yet.
+    pub fn apply_local(&mut self, changes: Vec<Change>) -> Option<Vec<Change>> {
+        if changes.is_empty() {
+            return None;
+        }
+        if self.outstanding.is_some() {
+            self.buffered.extend(changes);
+            return None;
+        }
+        self.outstanding = Some(changes.clone());
+        Some(changes)
+    }
+
+    /// Handles the server acknowledging the changes in flight as `revision`. Returns the buffered
+    /// changes that should be sent to the server next, if any.
+    pub fn acknowledge(&mut self, revision: usize) -> Option<Vec<Change>> {
+        self.revision = revision;
+        self.outstanding = None;
+        if self.buffered.is_empty() {
+            return None;
+        }
+        let changes = mem::take(&mut self.buffered);
+        self.outstanding = Some(changes.clone());
+        Some(changes)
+    }
+
+    /// Handles changes that another participant made, as the next revision of the server. Returns
+    /// the changes transformed so they can be applied to the local text. The state is left
+    /// untouched if the changes cannot be transformed.
+    pub fn apply_remote(&mut self, changes: Vec<Change>) -> Result<Vec<Change>, InvalidChange> {
+        let (outstanding, changes) = match self.outstanding.clone() {
+            Some(outstanding) => {
+                let (outstanding, changes) = transform(outstanding, changes)?;
+                (Some(outstanding), changes)
+            }
+            None => (None, changes),
+        };
+        let (buffered, changes) = transform(self.buffered.clone(), changes)?;
+        self.revision += 1;
+        self.outstanding = outstanding;
+        self.buffered = buffered;
+        Ok(changes)
+    }
+
+    /// Maps a position in the latest revision of the server to the corresponding position in the
+    /// local text.
+    pub fn transform_position(&self, position: Position) -> Position {
+        self.outstanding
+            .iter()
+            .flatten()
+            .chain(&self.buffered)
+            .fold(position, |position, change| {
+                position.apply_edit(&Edit {
+                    change: change.clone(),
+                    drift: Drift::Before,
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::text::Text,
+    };
+
+    fn pos(line_index: usize, byte_index: usize) -> Position {
+        Position {
+            line_index,
+            byte_index,
+        }
+    }
+
+    fn insert(line_index: usize, byte_index: usize, text: &str) -> Change {
+        Change::Insert(pos(line_index, byte_index), Text::from(text))
+    }
+
+    fn delete(start: Position, end: Position) -> Change {
+        Change::Delete(start, end - start)
+    }
+
+    fn apply(text: &str, changes: &[Change]) -> String {
+        let mut text = Text::from(text);
+        for change in changes {
+            text.apply_change(change.clone());
+        }
+        text.to_string()
+    }
+
+    // Applies `a` then `b'`, and `b` then `a'`, checks that both give the same text and returns it.
+    fn converge(text: &str, a: Change, b: Change) -> String {
+        let (a_prime, b_prime) = transform(vec![a.clone()], vec![b.clone()]).unwrap();
+        let a_then_b = apply(&apply(text, &[a]), &b_prime);
+        let b_then_a = apply(&apply(text, &[b]), &a_prime);
+        assert_eq!(a_then_b, b_then_a);
+        a_then_b
+    }
+
+    #[test]
+    fn insert_insert_at_same_position() {
+        let a = insert(0, 1, "X");
+        let b = insert(0, 1, "Y");
+        assert_eq!(transform_change(&a, &b, false), Ok(vec![insert(0, 2, "X")]));
+        assert_eq!(transform_change(&a, &b, true), Ok(vec![insert(0, 1, "X")]));
+        // `b` was established first, so its text comes first
+        assert_eq!(converge("abc", a.clone(), b.clone()), "aYXbc");
+        assert_eq!(converge("abc", b, a), "aXYbc");
+    }
+
+    #[test]
+    fn insert_inside_delete() {
+        let cases = [
+            ("abcdef", insert(0, 3, "XY"), "aXYf"),
+            ("abcdef", insert(0, 2, "X\nY"), "aX\nYf"),
+            ("ab\ncd\nef", insert(1, 1, "X\nY\nZ"), "aX\nY\nZf"),
+        ];
+        for (text, b, expected) in cases {
+            let end = Text::from(text).length();
+            let a = delete(pos(0, 1), pos(end.line_count, end.byte_count - 1));
+            let (a_prime, _) = transform(vec![a.clone()], vec![b.clone()]).unwrap();
+            // the delete is split around the inserted text
+            assert_eq!(a_prime.len(), 2);
+            assert_eq!(converge(text, a, b), expected);
+        }
+    }
+
+    #[test]
+    fn delete_delete() {
+        let cases = [
+            // overlapping
+            (delete(pos(0, 1), pos(0, 4)), delete(pos(0, 2), pos(0, 5)), "af"),
+            (delete(pos(0, 2), pos(0, 5)), delete(pos(0, 1), pos(0, 4)), "af"),
+            // nested
+            (delete(pos(0, 1), pos(0, 5)), delete(pos(0, 2), pos(0, 3)), "af"),
+            (delete(pos(0, 2), pos(0, 3)), delete(pos(0, 1), pos(0, 5)), "af"),
+            // the same range
+            (delete(pos(0, 1), pos(0, 5)), delete(pos(0, 1), pos(0, 5)), "af"),
+        ];
+        for (a, b, expected) in cases {
+            assert_eq!(converge("abcdef", a, b), expected);
+        }
+        // across lines
+        let a = delete(pos(0, 1), pos(1, 1));
+        let b = delete(pos(0, 2), pos(2, 0));
+        assert_eq!(converge("abc\ndef\nghi", a.clone(), b.clone()), "aghi");
+        assert_eq!(converge("abc\ndef\nghi", b, a), "aghi");
+    }
+
+    #[test]
+    fn delete_touching_insert() {
+        let a = delete(pos(0, 1), pos(0, 3));
+        // text inserted at either end of the deleted range is kept
+        assert_eq!(converge("abcdef", a.clone(), insert(0, 1, "X")), "aXdef");
+        assert_eq!(converge("abcdef", a.clone(), insert(0, 3, "Y")), "aYdef");
+        assert_eq!(converge("abcdef", insert(0, 1, "X"), a.clone()), "aXdef");
+        assert_eq!(converge("abcdef", insert(0, 3, "Y\nZ"), a), "aY\nZdef");
+    }
+
+    #[test]
+    fn transform_rejects_overflow() {
+        let far = pos(0, usize::MAX - 1);
+        let cases = [
+            (insert(0, usize::MAX, "X"), insert(0, 0, "Y")),
+            (Change::Delete(far, Length { line_count: 0, byte_count: 2 }), insert(0, 0, "Y")),
+            (insert(0, 1, "Y"), Change::Delete(far, Length { line_count: 0, byte_count: 2 })),
+            (insert(usize::MAX, 0, "X"), insert(0, 0, "Y\n")),
+        ];
+        for (a, b) in cases {
+            assert_eq!(transform(vec![a], vec![b]), Err(InvalidChange));
+        }
+    }
+
+    #[test]
+    fn collab_state_round_trip() {
+        let mut state = CollabState::new(0);
+        let mut text = Text::from("abc");
+        // the first local change is sent right away, the second one waits for it
+        let first = insert(0, 3, "X");
+        text.apply_change(first.clone());
+        assert_eq!(state.apply_local(vec![first.clone()]), Some(vec![first]));
+        let second = insert(0, 0, "Y");
+        text.apply_change(second.clone());
+        assert_eq!(state.apply_local(vec![second]), None);
+        assert!(!state.is_synchronized());
+
+        // another participant's change lands as revision 1, before ours
+        for change in state.apply_remote(vec![delete(pos(0, 1), pos(0, 2))]).unwrap() {
+            text.apply_change(change);
+        }
+        assert_eq!(text.to_string(), "YacX");
+        assert_eq!(state.revision(), 1);
+        // a position in the server text maps past the local insert before it
+        assert_eq!(state.transform_position(pos(0, 1)), pos(0, 2));
+
+        // the buffered change goes out once the first one is acknowledged, transformed
+        assert_eq!(state.acknowledge(2), Some(vec![insert(0, 0, "Y")]));
+        assert_eq!(state.acknowledge(3), None);
+        assert!(state.is_synchronized());
+        assert_eq!(state.revision(), 3);
+    }
+
+    #[test]
+    fn collab_state_unchanged_on_invalid_remote() {
+        let mut state = CollabState::new(0);
+        state.apply_local(vec![insert(0, 0, "X")]);
+        let far = pos(0, usize::MAX);
+        assert_eq!(state.apply_remote(vec![insert(0, 0, "Y"), Change::Delete(far, Length { line_count: 0, byte_count: 1 })]), Err(InvalidChange));
+        assert_eq!(state.revision(), 0);
+        assert_eq!(state.transform_position(pos(0, 0)), pos(0, 1));
+    }
+
+    #[test]
+    fn every_pair_converges() {
+        let text = "ab\ncd";
+        let positions: Vec<Position> = (0..2)
+            .flat_map(|line_index| (0..=2).map(move |byte_index| pos(line_index, byte_index)))
+            .collect();
+        let mut changes = Vec::new();
+        for &position in &positions {
+            changes.push(Change::Insert(position, Text::from("X")));
+            changes.push(Change::Insert(position, Text::from("Y\nZ")));
+            for &end in positions.iter().filter(|end| **end > position) {
+                changes.push(delete(position, end));
+            }
+        }
+        for a in &changes {
+            for b in &changes {
+                converge(text, a.clone(), b.clone());
+            }
+        }
+    }
+}
@@ -0,0 +1,7 @@
+//! The text and delta types shared by the code editor and the file server, and the operational
+//! transform that lets several participants edit the same file.
+
+pub mod collab;
+pub mod text;
+
+pub use makepad_micro_serde;
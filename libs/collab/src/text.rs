@@ -1,12 +1,15 @@
-use std::{
-    cmp::Ordering,
-    fmt, io,
-    io::BufRead,
-    iter,
-    ops::{Add, AddAssign, Sub, SubAssign},
+use {
+    makepad_micro_serde::*,
+    std::{
+        cmp::Ordering,
+        fmt, io,
+        io::BufRead,
+        iter,
+        ops::{Add, AddAssign, Sub, SubAssign},
+    },
 };
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, SerBin, DeBin)]
 pub struct Text {
     lines: Vec<String>,
 }
@@ -174,7 +177,7 @@ impl FromIterator<char> for Text {
     }
 }
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, SerBin, DeBin)]
 pub struct Edit {
     pub change: Change,
     pub drift: Drift,
@@ -189,7 +192,7 @@ impl Edit {
     }
 }
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, SerBin, DeBin)]
 pub enum Change {
     Insert(Position, Text),
     Delete(Position, Length),
@@ -204,7 +207,7 @@ impl Change {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd, SerBin, DeBin)]
 pub struct Position {
     pub line_index: usize,
     pub byte_index: usize,
@@ -237,6 +240,59 @@ impl Position {
     }
 }
 
+impl Position {
+    /// Like `self + length`, but returns `None` instead of overflowing.
+    pub fn checked_add(self, length: Length) -> Option<Self> {
+        if length.line_count == 0 {
+            Some(Self {
+                line_index: self.line_index,
+                byte_index: self.byte_index.checked_add(length.byte_count)?,
+            })
+        } else {
+            Some(Self {
+                line_index: self.line_index.checked_add(length.line_count)?,
+                byte_index: length.byte_count,
+            })
+        }
+    }
+
+    /// Like `self - other`, but returns `None` if `other` comes after `self`.
+    pub fn checked_sub(self, other: Self) -> Option<Length> {
+        if self.line_index == other.line_index {
+            Some(Length {
+                line_count: 0,
+                byte_count: self.byte_index.checked_sub(other.byte_index)?,
+            })
+        } else {
+            Some(Length {
+                line_count: self.line_index.checked_sub(other.line_index)?,
+                byte_count: self.byte_index,
+            })
+        }
+    }
+
+    /// Like `apply_edit`, but returns `None` if the edit makes the position overflow.
+    pub fn checked_apply_edit(self, edit: &Edit) -> Option<Self> {
+        match edit.change {
+            Change::Insert(point, ref text) => match self.cmp(&point) {
+                Ordering::Less => Some(self),
+                Ordering::Equal if edit.drift == Drift::After => Some(self),
+                _ => point
+                    .checked_add(text.length())?
+                    .checked_add(self.checked_sub(point)?),
+            },
+            Change::Delete(start, length) => {
+                let end = start.checked_add(length)?;
+                if self < start {
+                    Some(self)
+                } else {
+                    start.checked_add(self.checked_sub(end.min(self))?)
+                }
+            }
+        }
+    }
+}
+
 impl Add<Length> for Position {
     type Output = Self;
 
@@ -279,7 +335,7 @@ impl Sub for Position {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd, SerBin, DeBin)]
 pub struct Length {
     pub line_count: usize,
     pub byte_count: usize,
@@ -339,7 +395,7 @@ impl SubAssign for Length {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, SerBin, DeBin)]
 pub enum Drift {
     Before,
     After,
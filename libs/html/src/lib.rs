@@ -212,7 +212,7 @@ pub struct HtmlDoc{
          CommentBody
      }
              
-     fn process_entity(c:char, body:&str, in_entity:&mut Option<usize>, i:usize, decoded:&mut String, last_was_ws:&mut bool, errors:&mut Option<Vec<HtmlError>>){
+     fn process_entity(c:char, body:&str, in_entity:&mut Option<usize>, i:usize, decoded:&mut String, last_was_ws:&mut bool, keep_ws:bool, errors:&mut Option<Vec<HtmlError>>){
          if c=='&'{
              if in_entity.is_some(){
                  if let Some(errors) = errors{errors.push(HtmlError{message:"Unexpected & inside entity".into(), position:i})};
@@ -221,20 +221,22 @@ pub struct HtmlDoc{
          }
          else if let Some(start) = in_entity{
              if c == ';'{
-                 match match_entity(&body[*start..i-1]){
+                 match match_entity(&body[*start..i]){
                      Err(e)=>{
                          if let Some(errors) = errors{errors.push(HtmlError{message:e, position:i})};
-                         decoded.push_str(&body[*start..i-1]);
+                         decoded.push_str(&body[*start..i]);
                      }
                      Ok(entity)=>{
                          decoded.push(std::char::from_u32(entity).unwrap());
                      }
                  }
+                 *in_entity = None;
+                 *last_was_ws = false;
              }
          }
          else{
              if c.is_whitespace(){
-                 if !*last_was_ws{
+                 if keep_ws || !*last_was_ws{
                      decoded.push(c);
                  }
                  *last_was_ws = true;
@@ -251,11 +253,14 @@ pub struct HtmlDoc{
      let mut decoded = String::new();
      let mut in_entity = None;
      let mut last_was_ws = false;
+     // whitespace inside pre tags is kept as is
+     let mut pre_depth = 0usize;
+     let mut pre_scanned = 0;
      for (i, c) in body.char_indices(){
          state = match state{
              State::Text(start)=>{ 
                  if c == '<'{
-                     if start != i{
+                     if start != decoded.len(){
                          if let Some(start) = in_entity{
                               if let Some(errors) = errors{errors.push(HtmlError{message:"Unterminated entity".into(), position:start})};
                          }
@@ -264,7 +269,15 @@ pub struct HtmlDoc{
                      State::ElementName(i+1)
                  }
                  else{
-                     process_entity(c, &body, &mut in_entity, i, &mut decoded, &mut last_was_ws, errors);
+                     for node in &nodes[pre_scanned..]{
+                         match node{
+                             HtmlNode::OpenTag{lc,..} if *lc == live_id!(pre)=>pre_depth += 1,
+                             HtmlNode::CloseTag{lc,..} if *lc == live_id!(pre)=>pre_depth = pre_depth.saturating_sub(1),
+                             _=>()
+                         }
+                     }
+                     pre_scanned = nodes.len();
+                     process_entity(c, &body, &mut in_entity, i, &mut decoded, &mut last_was_ws, pre_depth > 0, errors);
                      State::Text(start)
                  }
              }
@@ -278,7 +291,7 @@ pub struct HtmlDoc{
                  else if c.is_whitespace(){
                      if start == i{
                           if let Some(errors) = errors{errors.push(HtmlError{message:"Found whitespace at beginning of tag".into(), position:i})};
-                         State::Text(i+1)
+                         State::Text(decoded.len())
                      }
                      else{
                         nodes.push(HtmlNode::OpenTag{lc:LiveId::from_str_lc(&body[start..i]),nc:LiveId::from_str(&body[start..i])});
@@ -312,11 +325,11 @@ pub struct HtmlDoc{
              }
              State::ElementCloseScanSpaces=>{
                  if c == '>'{
-                     State::Text(i+1)
+                     State::Text(decoded.len())
                  }
                  else if !c.is_whitespace(){
                       if let Some(errors) = errors{errors.push(HtmlError{message:"Unexpected character after whitespace whilst looking for closing tag >".into(), position:i})};
-                     State::Text(i+1)
+                     State::Text(decoded.len())
                  }
                  else{
                      State::ElementCloseScanSpaces
@@ -337,7 +350,7 @@ pub struct HtmlDoc{
                      State::ElementSelfClose
                  }
                  else if c == '>'{
-                     State::Text(i+1)
+                     State::Text(decoded.len())
                  }
                  else if !c.is_whitespace(){
                      State::AttribName(i)
@@ -372,7 +385,7 @@ pub struct HtmlDoc{
                  }
                  else if c == '>'{
                      nodes.push(HtmlNode::Attribute{lc,nc,start:0,end:0});
-                     State::Text(i+1)
+                     State::Text(decoded.len())
                  }
                  else if c == '='{
                      State::AttribValueStart(lc,nc)
@@ -411,7 +424,7 @@ pub struct HtmlDoc{
                      State::ElementAttrs
                  }
                  else{
-                     process_entity(c, &body, &mut in_entity, i, &mut decoded, &mut last_was_ws, errors);
+                     process_entity(c, &body, &mut in_entity, i, &mut decoded, &mut last_was_ws, false, errors);
                      State::AttribValueSq(lc,nc, start)
                  }
              }
@@ -424,7 +437,7 @@ pub struct HtmlDoc{
                      State::ElementAttrs
                  }
                  else{
-                     process_entity(c, &body, &mut in_entity, i, &mut decoded, &mut last_was_ws, errors);
+                     process_entity(c, &body, &mut in_entity, i, &mut decoded, &mut last_was_ws, false, errors);
                      State::AttribValueDq(lc,nc, start)
                  }
              }
@@ -435,7 +448,7 @@ pub struct HtmlDoc{
                  }
                  else if c == '>'{
                      nodes.push(HtmlNode::Attribute{lc,nc, start, end:decoded.len()});
-                     State::Text(i+1)
+                     State::Text(decoded.len())
                  }
                  else if c.is_whitespace(){
                      nodes.push(HtmlNode::Attribute{lc,nc, start, end:decoded.len()});
@@ -476,7 +489,7 @@ pub struct HtmlDoc{
              },
              State::CommentEnd=>{
                  if c == '>'{
-                     State::Text(i+1)
+                     State::Text(decoded.len())
                  }
                  else{
                      State::CommentBody
@@ -488,7 +501,7 @@ pub struct HtmlDoc{
           if let Some(errors) = errors{errors.push(HtmlError{message:"Unterminated entity".into(), position:start})};
      }
      if let State::Text(start) = state{
-         if start != body.len(){
+         if start != decoded.len(){
              nodes.push(HtmlNode::Text{start, end:decoded.len()});
          }
      }
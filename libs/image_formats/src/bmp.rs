@@ -638,7 +638,6 @@ trait WriteTypes {
     fn push16(&mut self,d: u16);
     fn push16b(&mut self,d: u16);
     fn push32(&mut self,d: u32);
-    fn push32b(&mut self,d: u32);
 }
 
 impl WriteTypes for Vec<u8> {
@@ -656,12 +655,6 @@ impl WriteTypes for Vec<u8> {
         self.push(((d >> 16) & 255) as u8);
         self.push((d >> 24) as u8);
     }
-    fn push32b(&mut self,d: u32) {
-        self.push((d >> 24) as u8);
-        self.push(((d >> 16) & 255) as u8);
-        self.push(((d >> 8) & 255) as u8);
-        self.push((d & 255) as u8);
-    }
 }
 
 pub fn encode(image: &ImageBuffer) -> Result<Vec<u8>,String> {
@@ -0,0 +1,181 @@
+// image_formats::codec
+// format sniffing and a single entrypoint for decoding/encoding
+
+use {
+    crate::{bmp, jpeg, png, ImageBuffer, ImageError},
+    makepad_zune_jpeg::JpegDecoder,
+    makepad_zune_png::{
+        PngDecoder,
+        makepad_zune_core::result::DecodingResult,
+    },
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Bmp,
+}
+
+impl ImageFormat {
+    /// Detects the format from the leading magic bytes of an encoded image.
+    pub fn sniff(data: &[u8]) -> Option<ImageFormat> {
+        if data.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+            Some(ImageFormat::Png)
+        }
+        else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(ImageFormat::Jpeg)
+        }
+        else if data.starts_with(b"BM") && data.len() >= 26 {
+            Some(ImageFormat::Bmp)
+        }
+        else {
+            None
+        }
+    }
+
+    /// Guesses the format from a file name or path, case insensitive.
+    pub fn from_path(path: &str) -> Option<ImageFormat> {
+        let ext = path.rsplit('.').next()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "png" => Some(ImageFormat::Png),
+            "jpg" | "jpeg" => Some(ImageFormat::Jpeg),
+            "bmp" => Some(ImageFormat::Bmp),
+            _ => None
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::Bmp => "bmp",
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "image/png",
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::Bmp => "image/bmp",
+        }
+    }
+}
+
+/// Decodes an image, detecting its format from the data.
+pub fn decode(data: &[u8]) -> Result<ImageBuffer, ImageError> {
+    match ImageFormat::sniff(data) {
+        Some(format) => decode_as(data, format),
+        None => Err(ImageError::UnsupportedFormat)
+    }
+}
+
+/// Decodes an image of a known format.
+pub fn decode_as(data: &[u8], format: ImageFormat) -> Result<ImageBuffer, ImageError> {
+    if data.is_empty() {
+        return Err(ImageError::EmptyData);
+    }
+    match format {
+        ImageFormat::Png => ImageBuffer::from_png(data),
+        ImageFormat::Jpeg => ImageBuffer::from_jpg(data),
+        ImageFormat::Bmp => ImageBuffer::from_bmp(data),
+    }
+}
+
+/// Encodes an image into the given format with default settings.
+pub fn encode(image: &ImageBuffer, format: ImageFormat) -> Result<Vec<u8>, ImageError> {
+    if image.width == 0 || image.height == 0 || image.data.len() != image.width * image.height {
+        return Err(ImageError::EmptyData);
+    }
+    match format {
+        ImageFormat::Png => png::encode(image).map_err(ImageError::Encode),
+        ImageFormat::Jpeg => jpeg::encode(image).map_err(ImageError::Encode),
+        ImageFormat::Bmp => bmp::encode(image).map_err(ImageError::Encode),
+    }
+}
+
+impl ImageBuffer {
+    pub fn from_png(data: &[u8]) -> Result<Self, ImageError> {
+        let mut decoder = PngDecoder::new(data);
+        let image = decoder.decode().map_err(ImageError::PngDecode)?;
+        let (width, height) = decoder.get_dimensions().ok_or(ImageError::EmptyData)?;
+        match image {
+            DecodingResult::U8(data) => ImageBuffer::from_packed_u8(&data, width, height),
+            DecodingResult::U16(data) => {
+                let data: Vec<u8> = data.iter().map(|v| (v >> 8) as u8).collect();
+                ImageBuffer::from_packed_u8(&data, width, height)
+            }
+            _ => Err(ImageError::EmptyData)
+        }
+    }
+
+    pub fn from_jpg(data: &[u8]) -> Result<Self, ImageError> {
+        let mut decoder = JpegDecoder::new(data);
+        let data = decoder.decode().map_err(ImageError::JpgDecode)?;
+        let info = decoder.info().ok_or(ImageError::EmptyData)?;
+        ImageBuffer::from_packed_u8(&data, info.width as usize, info.height as usize)
+    }
+
+    pub fn from_bmp(data: &[u8]) -> Result<Self, ImageError> {
+        bmp::decode(data).map_err(ImageError::BmpDecode)
+    }
+
+    pub fn encode(&self, format: ImageFormat) -> Result<Vec<u8>, ImageError> {
+        encode(self, format)
+    }
+
+    /// Encodes as JPEG with explicit quality and chroma subsampling.
+    pub fn encode_jpeg(&self, options: &jpeg::JpegEncodeOptions) -> Result<Vec<u8>, ImageError> {
+        jpeg::encode_with_options(self, options).map_err(ImageError::Encode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_image() -> ImageBuffer {
+        let mut image = ImageBuffer::new(7, 5);
+        for (i, pixel) in image.data.iter_mut().enumerate() {
+            *pixel = 0x80000000 | (((i as u32 * 37) & 0xff) << 16) | (((i as u32 * 11) & 0xff) << 8) | ((i as u32 * 5) & 0xff);
+        }
+        image
+    }
+
+    #[test]
+    fn sniff_formats() {
+        let image = test_image();
+        assert_eq!(ImageFormat::sniff(&image.encode(ImageFormat::Png).unwrap()), Some(ImageFormat::Png));
+        assert_eq!(ImageFormat::sniff(&image.encode(ImageFormat::Bmp).unwrap()), Some(ImageFormat::Bmp));
+        assert_eq!(ImageFormat::sniff(&image.encode(ImageFormat::Jpeg).unwrap()), Some(ImageFormat::Jpeg));
+        assert_eq!(ImageFormat::sniff(b"GIF89a"), None);
+        assert_eq!(ImageFormat::from_path("a/b/photo.JPEG"), Some(ImageFormat::Jpeg));
+        assert_eq!(ImageFormat::from_path("noext"), None);
+    }
+
+    #[test]
+    fn png_round_trip() {
+        let image = test_image();
+        let decoded = decode(&encode(&image, ImageFormat::Png).unwrap()).unwrap();
+        assert_eq!((decoded.width, decoded.height), (7, 5));
+        assert_eq!(decoded.data, image.data);
+
+        let mut opaque = image.clone();
+        opaque.data.iter_mut().for_each(|p| *p |= 0xff000000);
+        let decoded = decode(&encode(&opaque, ImageFormat::Png).unwrap()).unwrap();
+        assert_eq!(decoded.data, opaque.data);
+    }
+
+    #[test]
+    fn bmp_round_trip() {
+        let image = test_image();
+        let decoded = decode(&encode(&image, ImageFormat::Bmp).unwrap()).unwrap();
+        assert_eq!(decoded.data, image.data);
+    }
+
+    #[test]
+    fn rejects_empty() {
+        assert!(matches!(decode(&[]), Err(ImageError::UnsupportedFormat)));
+        assert!(matches!(encode(&ImageBuffer::new(0, 0), ImageFormat::Png), Err(ImageError::EmptyData)));
+    }
+}
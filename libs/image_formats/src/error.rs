@@ -0,0 +1,36 @@
+// image_formats::error
+
+use std::fmt;
+pub use makepad_zune_png::error::PngDecodeErrors;
+pub use makepad_zune_jpeg::errors::DecodeErrors as JpgDecodeErrors;
+
+/// The possible errors that can occur when decoding or encoding an image.
+#[derive(Debug)]
+pub enum ImageError {
+    /// The image data buffer was empty.
+    EmptyData,
+    /// The image's pixel data was not aligned to 1, 2, 3 or 4-byte pixels.
+    /// The unsupported alignment value (in bytes) is included.
+    InvalidPixelAlignment(usize),
+    /// The image data could not be decoded as a JPEG.
+    JpgDecode(JpgDecodeErrors),
+    /// The image data could not be decoded as a PNG.
+    PngDecode(PngDecodeErrors),
+    /// The image data could not be decoded as a BMP.
+    BmpDecode(String),
+    /// The image could not be encoded into the requested format.
+    Encode(String),
+    /// The image file at the given resource path could not be found.
+    PathNotFound(String),
+    /// The image data was in an unsupported format.
+    /// Currently, only JPEG, PNG and BMP are supported.
+    UnsupportedFormat,
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for ImageError {}
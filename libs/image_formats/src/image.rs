@@ -1,7 +1,10 @@
 // image_formats::image
 // by Desmond Germans, 2019
 
-#[derive(Default)] 
+use crate::ImageError;
+
+/// Pixels are stored as 0xAARRGGBB, row by row from the top.
+#[derive(Default, Clone)] 
 pub struct ImageBuffer {
     pub width: usize,
     pub height: usize,
@@ -16,4 +19,68 @@ impl ImageBuffer {
             data: vec![0; width * height],
         }
     }
+    
+    /// Builds an image from tightly packed 8 bit Luma, LumaA, RGB or RGBA pixels,
+    /// the layout is derived from the number of bytes per pixel.
+    pub fn from_packed_u8(in_data: &[u8], width: usize, height: usize) -> Result<ImageBuffer, ImageError> {
+        let pixels = width * height;
+        if pixels == 0 || in_data.is_empty() {
+            return Err(ImageError::EmptyData);
+        }
+        let mut out = vec![0u32; pixels];
+        match in_data.len() / pixels {
+            4 => for i in 0..pixels {
+                let r = in_data[i*4];
+                let g = in_data[i*4+1];
+                let b = in_data[i*4+2];
+                let a = in_data[i*4+3];
+                out[i] = ((a as u32)<<24) | ((r as u32)<<16) | ((g as u32)<<8) | (b as u32);
+            }
+            3 => for i in 0..pixels {
+                let r = in_data[i*3];
+                let g = in_data[i*3+1];
+                let b = in_data[i*3+2];
+                out[i] = 0xff000000 | ((r as u32)<<16) | ((g as u32)<<8) | (b as u32);
+            }
+            2 => for i in 0..pixels {
+                let r = in_data[i*2];
+                let a = in_data[i*2+1];
+                out[i] = ((a as u32)<<24) | ((r as u32)<<16) | ((r as u32)<<8) | (r as u32);
+            }
+            1 => for i in 0..pixels {
+                let r = in_data[i];
+                out[i] = 0xff000000 | ((r as u32)<<16) | ((r as u32)<<8) | (r as u32);
+            }
+            unsupported => {
+                return Err(ImageError::InvalidPixelAlignment(unsupported));
+            }
+        }
+        Ok(ImageBuffer {
+            width,
+            height,
+            data: out
+        })
+    }
+    
+    /// Returns the pixels as packed RGBA bytes.
+    pub fn to_rgba_u8(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.data.len() * 4);
+        for c in &self.data {
+            out.extend_from_slice(&[(c >> 16) as u8, (c >> 8) as u8, *c as u8, (c >> 24) as u8]);
+        }
+        out
+    }
+    
+    /// Returns the pixels as packed RGB bytes, dropping alpha.
+    pub fn to_rgb_u8(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.data.len() * 3);
+        for c in &self.data {
+            out.extend_from_slice(&[(c >> 16) as u8, (c >> 8) as u8, *c as u8]);
+        }
+        out
+    }
+    
+    pub fn is_opaque(&self) -> bool {
+        self.data.iter().all(|c| c >> 24 == 0xff)
+    }
 }
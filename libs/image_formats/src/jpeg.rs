@@ -913,8 +913,8 @@ pub fn test(src: &[u8]) -> Option<(usize, usize)> {
         let length = from_be16(&src[sp + 2..sp + 4]) as usize;
         match marker {
             0xFFC0 | 0xFFC1 | 0xFFC2 => {
-                let width = from_be16(&src[sp + 5..sp + 7]) as usize;
-                let height = from_be16(&src[sp + 7..sp + 9]) as usize;
+                let height = from_be16(&src[sp + 5..sp + 7]) as usize;
+                let width = from_be16(&src[sp + 7..sp + 9]) as usize;
                 let components = src[sp + 9];
                 if (components == 1) || (components == 3) { // does not support RGBA or CMYK JPEGs
                     return Some((width, height));
@@ -1235,6 +1235,435 @@ pub fn decode(src: &[u8]) -> Result<ImageBuffer, String> {
     Err("Invalid JPEG 11".to_string())
 }
 
-pub fn encode(_image: &ImageBuffer) -> Result<Vec<u8>, String> {
-    Err("not implemented yet".to_string())
+// encoder: baseline sequential, Huffman coded with the standard Annex K tables
+
+/// Chroma resolution relative to luma.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChromaSubsampling {
+    /// Full resolution chroma, best for graphics and text.
+    Yuv444,
+    /// Chroma halved in both directions, best for photos.
+    Yuv420,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct JpegEncodeOptions {
+    /// 1 (smallest) ..= 100 (best)
+    pub quality: u8,
+    pub subsampling: ChromaSubsampling,
+}
+
+impl Default for JpegEncodeOptions {
+    fn default() -> Self {
+        JpegEncodeOptions {
+            quality: 90,
+            subsampling: ChromaSubsampling::Yuv420,
+        }
+    }
+}
+
+const ZIGZAG: [u8; 64] = [0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20, 13, 6, 7, 14, 21, 28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59, 52, 45, 38, 31, 39, 46, 53, 60, 61, 54, 47, 55, 62, 63,];
+
+const LUMA_QUANT: [u8; 64] = [16, 11, 10, 16, 24, 40, 51, 61, 12, 12, 14, 19, 26, 58, 60, 55, 14, 13, 16, 24, 40, 57, 69, 56, 14, 17, 22, 29, 51, 87, 80, 62, 18, 22, 37, 56, 68, 109, 103, 77, 24, 35, 55, 64, 81, 104, 113, 92, 49, 64, 78, 87, 103, 121, 120, 101, 72, 92, 95, 98, 112, 100, 103, 99,];
+const CHROMA_QUANT: [u8; 64] = [17, 18, 24, 47, 99, 99, 99, 99, 18, 21, 26, 66, 99, 99, 99, 99, 24, 26, 56, 99, 99, 99, 99, 99, 47, 66, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99,];
+
+const DC_LUMA_BITS: [u8; 16] = [0, 1, 5, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0];
+const DC_CHROMA_BITS: [u8; 16] = [0, 3, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0];
+const DC_VALS: [u8; 12] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+const AC_LUMA_BITS: [u8; 16] = [0, 2, 1, 3, 3, 2, 4, 3, 5, 5, 4, 4, 0, 0, 1, 0x7D];
+const AC_LUMA_VALS: [u8; 162] = [
+    0x01, 0x02, 0x03, 0x00, 0x04, 0x11, 0x05, 0x12, 0x21, 0x31, 0x41, 0x06, 0x13, 0x51, 0x61, 0x07, 0x22, 0x71, 0x14, 0x32, 0x81, 0x91, 0xA1, 0x08,
+    0x23, 0x42, 0xB1, 0xC1, 0x15, 0x52, 0xD1, 0xF0, 0x24, 0x33, 0x62, 0x72, 0x82, 0x09, 0x0A, 0x16, 0x17, 0x18, 0x19, 0x1A, 0x25, 0x26, 0x27, 0x28,
+    0x29, 0x2A, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3A, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x4A, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59,
+    0x5A, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69, 0x6A, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7A, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89,
+    0x8A, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9A, 0xA2, 0xA3, 0xA4, 0xA5, 0xA6, 0xA7, 0xA8, 0xA9, 0xAA, 0xB2, 0xB3, 0xB4, 0xB5, 0xB6,
+    0xB7, 0xB8, 0xB9, 0xBA, 0xC2, 0xC3, 0xC4, 0xC5, 0xC6, 0xC7, 0xC8, 0xC9, 0xCA, 0xD2, 0xD3, 0xD4, 0xD5, 0xD6, 0xD7, 0xD8, 0xD9, 0xDA, 0xE1, 0xE2,
+    0xE3, 0xE4, 0xE5, 0xE6, 0xE7, 0xE8, 0xE9, 0xEA, 0xF1, 0xF2, 0xF3, 0xF4, 0xF5, 0xF6, 0xF7, 0xF8, 0xF9, 0xFA,
+];
+const AC_CHROMA_BITS: [u8; 16] = [0, 2, 1, 2, 4, 4, 3, 4, 7, 5, 4, 4, 0, 1, 2, 0x77];
+const AC_CHROMA_VALS: [u8; 162] = [
+    0x00, 0x01, 0x02, 0x03, 0x11, 0x04, 0x05, 0x21, 0x31, 0x06, 0x12, 0x41, 0x51, 0x07, 0x61, 0x71, 0x13, 0x22, 0x32, 0x81, 0x08, 0x14, 0x42, 0x91,
+    0xA1, 0xB1, 0xC1, 0x09, 0x23, 0x33, 0x52, 0xF0, 0x15, 0x62, 0x72, 0xD1, 0x0A, 0x16, 0x24, 0x34, 0xE1, 0x25, 0xF1, 0x17, 0x18, 0x19, 0x1A, 0x26,
+    0x27, 0x28, 0x29, 0x2A, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3A, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x4A, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58,
+    0x59, 0x5A, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69, 0x6A, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7A, 0x82, 0x83, 0x84, 0x85, 0x86, 0x87,
+    0x88, 0x89, 0x8A, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9A, 0xA2, 0xA3, 0xA4, 0xA5, 0xA6, 0xA7, 0xA8, 0xA9, 0xAA, 0xB2, 0xB3, 0xB4,
+    0xB5, 0xB6, 0xB7, 0xB8, 0xB9, 0xBA, 0xC2, 0xC3, 0xC4, 0xC5, 0xC6, 0xC7, 0xC8, 0xC9, 0xCA, 0xD2, 0xD3, 0xD4, 0xD5, 0xD6, 0xD7, 0xD8, 0xD9, 0xDA,
+    0xE2, 0xE3, 0xE4, 0xE5, 0xE6, 0xE7, 0xE8, 0xE9, 0xEA, 0xF2, 0xF3, 0xF4, 0xF5, 0xF6, 0xF7, 0xF8, 0xF9, 0xFA,
+];
+
+// (code, length) per symbol, the encoding side of `Table`
+struct CodeTable {
+    codes: [(u16, u8); 256],
+}
+
+impl CodeTable {
+    fn new(bits: &[u8; 16], huffval: &[u8]) -> CodeTable {
+        let mut codes = [(0u16, 0u8); 256];
+        let mut code = 0u16;
+        let mut k = 0;
+        for (i, count) in bits.iter().enumerate() {
+            for _ in 0..*count {
+                codes[huffval[k] as usize] = (code, (i + 1) as u8);
+                code += 1;
+                k += 1;
+            }
+            code <<= 1;
+        }
+        CodeTable {
+            codes,
+        }
+    }
+}
+
+struct BitWriter {
+    out: Vec<u8>,
+    cache: u32,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn put(&mut self, code: u32, length: u32) {
+        self.cache = (self.cache << length) | (code & ((1 << length) - 1));
+        self.bits += length;
+        while self.bits >= 8 {
+            let b = (self.cache >> (self.bits - 8)) as u8;
+            self.out.push(b);
+            if b == 0xFF {
+                self.out.push(0x00);
+            }
+            self.bits -= 8;
+        }
+    }
+    
+    fn flush(&mut self) {
+        if self.bits > 0 {
+            self.put(0x7F, 8 - self.bits);
+        }
+    }
+}
+
+fn scale_quant(base: &[u8; 64], quality: u8) -> [u8; 64] {
+    let quality = clamp(quality, 1, 100) as u32;
+    let scale = if quality < 50 {5000 / quality} else {200 - quality * 2};
+    let mut table = [0u8; 64];
+    for i in 0..64 {
+        table[i] = clamp((base[i] as u32 * scale + 50) / 100, 1, 255) as u8;
+    }
+    table
+}
+
+fn forward_dct(block: &[f32; 64], out: &mut [f32; 64]) {
+    let mut cos = [[0f32; 8]; 8];
+    for (u, row) in cos.iter_mut().enumerate() {
+        let cu = if u == 0 {std::f32::consts::FRAC_1_SQRT_2} else {1.0};
+        for (x, c) in row.iter_mut().enumerate() {
+            *c = 0.5 * cu * (((2 * x + 1) as f32 * u as f32 * std::f32::consts::PI) / 16.0).cos();
+        }
+    }
+    let mut temp = [0f32; 64];
+    for y in 0..8 {
+        for u in 0..8 {
+            let mut sum = 0.0;
+            for x in 0..8 {
+                sum += block[y * 8 + x] * cos[u][x];
+            }
+            temp[y * 8 + u] = sum;
+        }
+    }
+    for u in 0..8 {
+        for v in 0..8 {
+            let mut sum = 0.0;
+            for y in 0..8 {
+                sum += temp[y * 8 + u] * cos[v][y];
+            }
+            out[v * 8 + u] = sum;
+        }
+    }
+}
+
+fn category(v: i32) -> u32 {
+    32 - v.unsigned_abs().leading_zeros()
+}
+
+fn write_block(writer: &mut BitWriter, block: &[f32; 64], qtable: &[u8; 64], dc: &mut i32, dcct: &CodeTable, acct: &CodeTable) {
+    let mut coeffs = [0f32; 64];
+    forward_dct(block, &mut coeffs);
+    let mut quant = [0i32; 64];
+    for (i, q) in quant.iter_mut().enumerate() {
+        let z = ZIGZAG[i] as usize;
+        *q = (coeffs[z] / qtable[z] as f32).round() as i32;
+    }
+    
+    let diff = quant[0] - *dc;
+    *dc = quant[0];
+    let cat = category(diff);
+    let (code, length) = dcct.codes[cat as usize];
+    writer.put(code as u32, length as u32);
+    if cat > 0 {
+        let bits = if diff < 0 {diff - 1} else {diff};
+        writer.put(bits as u32, cat);
+    }
+    
+    let mut run = 0;
+    for &coeff in &quant[1..] {
+        if coeff == 0 {
+            run += 1;
+            continue;
+        }
+        while run > 15 {
+            let (code, length) = acct.codes[0xF0];
+            writer.put(code as u32, length as u32);
+            run -= 16;
+        }
+        let cat = category(coeff);
+        let (code, length) = acct.codes[((run << 4) | cat) as usize];
+        writer.put(code as u32, length as u32);
+        let bits = if coeff < 0 {coeff - 1} else {coeff};
+        writer.put(bits as u32, cat);
+        run = 0;
+    }
+    if run > 0 {
+        let (code, length) = acct.codes[0x00];
+        writer.put(code as u32, length as u32);
+    }
+}
+
+fn push_marker(dst: &mut Vec<u8>, marker: u16, payload: &[u8]) {
+    dst.extend_from_slice(&marker.to_be_bytes());
+    dst.extend_from_slice(&((payload.len() + 2) as u16).to_be_bytes());
+    dst.extend_from_slice(payload);
+}
+
+fn push_huffman_table(payload: &mut Vec<u8>, class_id: u8, bits: &[u8; 16], vals: &[u8]) {
+    payload.push(class_id);
+    payload.extend_from_slice(bits);
+    payload.extend_from_slice(vals);
+}
+
+pub fn encode(image: &ImageBuffer) -> Result<Vec<u8>, String> {
+    encode_with_options(image, &JpegEncodeOptions::default())
+}
+
+pub fn encode_with_options(image: &ImageBuffer, options: &JpegEncodeOptions) -> Result<Vec<u8>, String> {
+    let width = image.width;
+    let height = image.height;
+    if width == 0 || height == 0 || width > 65535 || height > 65535 || image.data.len() != width * height {
+        return Err("Invalid image size for JPEG".to_string());
+    }
+    let luma_q = scale_quant(&LUMA_QUANT, options.quality);
+    let chroma_q = scale_quant(&CHROMA_QUANT, options.quality);
+    
+    let mut dst = Vec::new();
+    dst.extend_from_slice(&[0xFF, 0xD8]);
+    push_marker(&mut dst, 0xFFE0, &[b'J', b'F', b'I', b'F', 0, 1, 1, 0, 0, 1, 0, 1, 0, 0]);
+    
+    let mut payload = Vec::new();
+    // DQT tables are stored in zigzag order
+    payload.push(0x00);
+    payload.extend(ZIGZAG.iter().map(|&z| luma_q[z as usize]));
+    payload.push(0x01);
+    payload.extend(ZIGZAG.iter().map(|&z| chroma_q[z as usize]));
+    push_marker(&mut dst, 0xFFDB, &payload);
+    
+    let samp = match options.subsampling {
+        ChromaSubsampling::Yuv444 => 0x11,
+        ChromaSubsampling::Yuv420 => 0x22,
+    };
+    let mut payload = vec![8];
+    payload.extend_from_slice(&(height as u16).to_be_bytes());
+    payload.extend_from_slice(&(width as u16).to_be_bytes());
+    payload.extend_from_slice(&[3, 1, samp, 0, 2, 0x11, 1, 3, 0x11, 1]);
+    push_marker(&mut dst, 0xFFC0, &payload);
+    
+    let mut payload = Vec::new();
+    push_huffman_table(&mut payload, 0x00, &DC_LUMA_BITS, &DC_VALS);
+    push_huffman_table(&mut payload, 0x10, &AC_LUMA_BITS, &AC_LUMA_VALS);
+    push_huffman_table(&mut payload, 0x01, &DC_CHROMA_BITS, &DC_VALS);
+    push_huffman_table(&mut payload, 0x11, &AC_CHROMA_BITS, &AC_CHROMA_VALS);
+    push_marker(&mut dst, 0xFFC4, &payload);
+    
+    push_marker(&mut dst, 0xFFDA, &[3, 1, 0x00, 2, 0x11, 3, 0x11, 0, 63, 0]);
+    
+    let dc_luma = CodeTable::new(&DC_LUMA_BITS, &DC_VALS);
+    let ac_luma = CodeTable::new(&AC_LUMA_BITS, &AC_LUMA_VALS);
+    let dc_chroma = CodeTable::new(&DC_CHROMA_BITS, &DC_VALS);
+    let ac_chroma = CodeTable::new(&AC_CHROMA_BITS, &AC_CHROMA_VALS);
+    
+    // level shifted YCbCr, edge pixels are replicated into the padding
+    let ycc = |x: usize, y: usize| -> (f32, f32, f32) {
+        let c = image.data[y.min(height - 1) * width + x.min(width - 1)];
+        let r = ((c >> 16) & 255) as f32;
+        let g = ((c >> 8) & 255) as f32;
+        let b = (c & 255) as f32;
+        (
+            0.299 * r + 0.587 * g + 0.114 * b - 128.0,
+            -0.168736 * r - 0.331264 * g + 0.5 * b,
+            0.5 * r - 0.418688 * g - 0.081312 * b,
+        )
+    };
+    
+    let mbsize = if options.subsampling == ChromaSubsampling::Yuv420 {16} else {8};
+    let mbwidth = width.div_ceil(mbsize);
+    let mbheight = height.div_ceil(mbsize);
+    let mut writer = BitWriter {out: dst, cache: 0, bits: 0};
+    let mut dc = [0i32; 3];
+    let mut yb = [0f32; 64];
+    let mut cbb = [0f32; 64];
+    let mut crb = [0f32; 64];
+    for my in 0..mbheight {
+        for mx in 0..mbwidth {
+            let x0 = mx * mbsize;
+            let y0 = my * mbsize;
+            match options.subsampling {
+                ChromaSubsampling::Yuv444 => {
+                    for i in 0..8 {
+                        for k in 0..8 {
+                            let (y, cb, cr) = ycc(x0 + k, y0 + i);
+                            yb[i * 8 + k] = y;
+                            cbb[i * 8 + k] = cb;
+                            crb[i * 8 + k] = cr;
+                        }
+                    }
+                    write_block(&mut writer, &yb, &luma_q, &mut dc[0], &dc_luma, &ac_luma);
+                }
+                ChromaSubsampling::Yuv420 => {
+                    for by in 0..2 {
+                        for bx in 0..2 {
+                            for i in 0..8 {
+                                for k in 0..8 {
+                                    yb[i * 8 + k] = ycc(x0 + bx * 8 + k, y0 + by * 8 + i).0;
+                                }
+                            }
+                            write_block(&mut writer, &yb, &luma_q, &mut dc[0], &dc_luma, &ac_luma);
+                        }
+                    }
+                    for i in 0..8 {
+                        for k in 0..8 {
+                            let mut cb = 0.0;
+                            let mut cr = 0.0;
+                            for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                                let (_, u, v) = ycc(x0 + k * 2 + dx, y0 + i * 2 + dy);
+                                cb += u;
+                                cr += v;
+                            }
+                            cbb[i * 8 + k] = cb * 0.25;
+                            crb[i * 8 + k] = cr * 0.25;
+                        }
+                    }
+                }
+            }
+            write_block(&mut writer, &cbb, &chroma_q, &mut dc[1], &dc_chroma, &ac_chroma);
+            write_block(&mut writer, &crb, &chroma_q, &mut dc[2], &dc_chroma, &ac_chroma);
+        }
+    }
+    writer.flush();
+    let mut dst = writer.out;
+    dst.extend_from_slice(&[0xFF, 0xD9]);
+    Ok(dst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient(width: usize, height: usize) -> ImageBuffer {
+        let mut image = ImageBuffer::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let r = (x * 255 / width) as u32;
+                let g = (y * 255 / height) as u32;
+                let b = ((x + y) * 127 / (width + height)) as u32 + 64;
+                image.data[y * width + x] = 0xFF000000 | (r << 16) | (g << 8) | b;
+            }
+        }
+        image
+    }
+
+    fn mean_error(a: &ImageBuffer, b: &ImageBuffer) -> f64 {
+        let mut total = 0u64;
+        for (pa, pb) in a.data.iter().zip(b.data.iter()) {
+            for shift in [0, 8, 16] {
+                total += (((pa >> shift) & 255) as i64 - ((pb >> shift) & 255) as i64).unsigned_abs();
+            }
+        }
+        total as f64 / (a.data.len() * 3) as f64
+    }
+
+    #[test]
+    fn round_trip_subsampling() {
+        // odd sizes exercise the partial macroblocks on the right and bottom edge
+        let image = gradient(37, 29);
+        for subsampling in [ChromaSubsampling::Yuv444, ChromaSubsampling::Yuv420] {
+            let data = encode_with_options(&image, &JpegEncodeOptions {quality: 90, subsampling}).unwrap();
+            assert_eq!(test(&data), Some((37, 29)));
+            let decoded = decode(&data).unwrap();
+            assert_eq!((decoded.width, decoded.height), (37, 29));
+            let error = mean_error(&image, &decoded);
+            assert!(error < 4.0, "{:?} mean error {}", subsampling, error);
+        }
+    }
+
+    #[test]
+    fn quality_affects_size() {
+        let mut image = gradient(64, 64);
+        // add some high frequency detail so quantization matters
+        for (i, pixel) in image.data.iter_mut().enumerate() {
+            if (i / 3) % 2 == 0 {
+                *pixel ^= 0x00303030;
+            }
+        }
+        let low = encode_with_options(&image, &JpegEncodeOptions {quality: 10, subsampling: ChromaSubsampling::Yuv420}).unwrap();
+        let high = encode_with_options(&image, &JpegEncodeOptions {quality: 95, subsampling: ChromaSubsampling::Yuv420}).unwrap();
+        assert!(low.len() < high.len());
+        assert!(mean_error(&image, &decode(&high).unwrap()) < mean_error(&image, &decode(&low).unwrap()));
+    }
+
+    fn psnr(a: &ImageBuffer, b: &ImageBuffer) -> f64 {
+        let mut total = 0f64;
+        for (pa, pb) in a.data.iter().zip(b.data.iter()) {
+            for shift in [0, 8, 16] {
+                let d = ((pa >> shift) & 255) as f64 - ((pb >> shift) & 255) as f64;
+                total += d * d;
+            }
+        }
+        let mse = total / (a.data.len() * 3) as f64;
+        10.0 * (255.0 * 255.0 / mse).log10()
+    }
+
+    #[test]
+    fn quality_75_size_and_psnr() {
+        // a smooth gradient with a fine texture on top, so both low and high
+        // frequencies carry energy
+        let mut image = ImageBuffer::new(64, 64);
+        for y in 0..64 {
+            for x in 0..64 {
+                let (fx, fy) = (x as f32, y as f32);
+                let v = 128.0 + 60.0 * (fx * 0.1).sin() * (fy * 0.07).cos() + 30.0 * (fx * 0.9 + fy * 0.4).sin();
+                let v = v.clamp(0.0, 255.0) as u32;
+                image.data[y * 64 + x] = 0xFF000000 | (v << 16) | (((v + x as u32) & 255) << 8) | (255 - v);
+            }
+        }
+        let data = encode_with_options(&image, &JpegEncodeOptions {quality: 75, subsampling: ChromaSubsampling::Yuv444}).unwrap();
+        // decode with the zune decoder so the check doesn't rely on our own tables
+        let decoded = ImageBuffer::from_jpg(&data).unwrap();
+        let psnr = psnr(&image, &decoded);
+        assert!(psnr > 30.5, "psnr {}", psnr);
+        assert!(data.len() > 1500 && data.len() < 3000, "size {}", data.len());
+    }
+
+    #[test]
+    fn quant_tables_in_zigzag_order() {
+        // at quality 50 the tables are the unscaled Annex K tables, which libjpeg
+        // writes as 16 11 12 14 12 10 16 14 ... in the DQT segment
+        let data = encode_with_options(&gradient(8, 8), &JpegEncodeOptions {quality: 50, subsampling: ChromaSubsampling::Yuv444}).unwrap();
+        let dqt = data.windows(2).position(|w| w == [0xFF, 0xDB]).unwrap();
+        assert_eq!(&data[dqt + 5..dqt + 13], &[16, 11, 12, 14, 12, 10, 16, 14]);
+        assert_eq!(data[dqt + 4 + 64], 99);
+        assert_eq!(&data[dqt + 4 + 65..dqt + 4 + 70], &[0x01, 17, 18, 18, 24]);
+    }
+
+    #[test]
+    fn rejects_empty_image() {
+        assert!(encode(&ImageBuffer::new(0, 4)).is_err());
+    }
 }
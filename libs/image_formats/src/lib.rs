@@ -4,7 +4,13 @@
 
 mod image;
 pub use image::*;
-//pub mod bmp;
-//pub mod png;
+mod error;
+pub use error::*;
+mod codec;
+pub use codec::*;
+pub mod bmp;
+pub mod png;
 pub mod jpeg;
 
+pub use makepad_zune_png;
+pub use makepad_zune_jpeg;
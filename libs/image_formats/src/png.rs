@@ -4,6 +4,10 @@
 use std::cmp::Ordering;
 
 use crate::ImageBuffer;
+use makepad_zune_png::{
+    PngEncoder,
+    makepad_zune_core::{bit_depth::BitDepth, colorspace::ColorSpace, options::EncoderOptions},
+};
 
 // Inflate algorithm
 const LITLEN_LENGTH: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
@@ -1039,6 +1043,17 @@ pub fn decode(src: &[u8]) -> Result<ImageBuffer, String> {
     }
 }
 
-pub fn encode(_image: &ImageBuffer) -> Result<Vec<u8>, String> {
-    Err("not implemented".to_string())
+pub fn encode(image: &ImageBuffer) -> Result<Vec<u8>, String> {
+    if image.width == 0 || image.height == 0 || image.data.len() != image.width * image.height {
+        return Err("image is empty".to_string());
+    }
+    // opaque images are written without alpha to keep files small
+    let (pixels, colorspace) = if image.is_opaque() {
+        (image.to_rgb_u8(), ColorSpace::RGB)
+    }
+    else {
+        (image.to_rgba_u8(), ColorSpace::RGBA)
+    };
+    let options = EncoderOptions::new(image.width, image.height, colorspace, BitDepth::Eight);
+    Ok(PngEncoder::new(&pixels, options).encode())
 }
\ No newline at end of file
@@ -0,0 +1,805 @@
+/// The inline content of a block, as a flat list where emphasis and links are bracketed by
+/// begin and end items.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MdInline {
+    Text(String),
+    Code(String),
+    BeginEmphasis,
+    EndEmphasis,
+    BeginStrong,
+    EndStrong,
+    BeginLink(String),
+    EndLink,
+    LineBreak,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MdBlock {
+    Heading {level: usize, inlines: Vec<MdInline>},
+    Paragraph(Vec<MdInline>),
+    /// A fenced or indented code block, with the info string of the fence.
+    Code {info: String, text: String},
+    Quote(Vec<MdBlock>),
+    List {ordered: bool, start: u64, items: Vec<Vec<MdBlock>>},
+    Table {header: Vec<Vec<MdInline>>, rows: Vec<Vec<Vec<MdInline>>>},
+    Rule,
+}
+
+/// A parsed markdown document that text can be appended to, as it streams in.
+///
+/// Text that is appended can only change the last block of the document, so appending only
+/// parses that block again, and what comes after it.
+#[derive(Default)]
+pub struct MarkdownDoc {
+    source: String,
+    blocks: Vec<MdBlock>,
+    // the byte offset in the source at which each block starts
+    block_starts: Vec<usize>,
+}
+
+impl MarkdownDoc {
+    pub fn parse(source: &str) -> Self {
+        let mut doc = Self::default();
+        doc.append(source);
+        doc
+    }
+
+    pub fn append(&mut self, text: &str) {
+        self.source.push_str(text);
+        let last = self.blocks.len().saturating_sub(1);
+        let offset = self.block_starts.get(last).copied().unwrap_or(0);
+        self.blocks.truncate(last);
+        self.block_starts.truncate(last);
+
+        let mut lines = Vec::new();
+        let mut line_starts = Vec::new();
+        let mut pos = offset;
+        for line in self.source[offset..].split('\n') {
+            line_starts.push(pos);
+            pos += line.len() + 1;
+            lines.push(line.strip_suffix('\r').unwrap_or(line));
+        }
+        for (line, block) in parse_blocks(&lines) {
+            self.blocks.push(block);
+            self.block_starts.push(line_starts[line]);
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn blocks(&self) -> &[MdBlock] {
+        &self.blocks
+    }
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+struct Fence<'a> {
+    ch: char,
+    len: usize,
+    indent: usize,
+    info: &'a str,
+}
+
+fn fence_start(line: &str) -> Option<Fence<'_>> {
+    let indent = indent(line);
+    if indent > 3 {
+        return None
+    }
+    let rest = &line[indent..];
+    let ch = rest.chars().next()?;
+    if ch != '`' && ch != '~' {
+        return None
+    }
+    let len = rest.len() - rest.trim_start_matches(ch).len();
+    let info = rest[len..].trim();
+    // the info string of a backtick fence can not hold backticks
+    if len < 3 || (ch == '`' && info.contains('`')) {
+        return None
+    }
+    Some(Fence {ch, len, indent, info})
+}
+
+fn is_fence_end(line: &str, fence: &Fence) -> bool {
+    let indent = indent(line);
+    if indent > 3 {
+        return false
+    }
+    let rest = &line[indent..];
+    let len = rest.len() - rest.trim_start_matches(fence.ch).len();
+    len >= fence.len && rest[len..].trim().is_empty()
+}
+
+fn atx_heading(line: &str) -> Option<(usize, &str)> {
+    let indent = indent(line);
+    if indent > 3 {
+        return None
+    }
+    let rest = &line[indent..];
+    let level = rest.len() - rest.trim_start_matches('#').len();
+    if level == 0 || level > 6 {
+        return None
+    }
+    let text = &rest[level..];
+    if !text.is_empty() && !text.starts_with([' ', '\t']) {
+        return None
+    }
+    // a closing sequence of #s is not part of the heading
+    let text = text.trim();
+    let closed = text.trim_end_matches('#');
+    let text = if closed.is_empty() || closed.ends_with([' ', '\t']) {closed.trim_end()} else {text};
+    Some((level, text))
+}
+
+fn is_rule(line: &str) -> bool {
+    if indent(line) > 3 {
+        return false
+    }
+    let line = line.trim();
+    let Some(ch) = line.chars().next() else {
+        return false
+    };
+    (ch == '-' || ch == '*' || ch == '_')
+        && line.chars().all( | c | c == ch || c == ' ' || c == '\t')
+        && line.chars().filter( | c | *c == ch).count() >= 3
+}
+
+fn setext_level(line: &str) -> Option<usize> {
+    if indent(line) > 3 {
+        return None
+    }
+    let line = line.trim();
+    if !line.is_empty() && line.chars().all( | c | c == '=') {
+        Some(1)
+    }
+    else if !line.is_empty() && line.chars().all( | c | c == '-') {
+        Some(2)
+    }
+    else {
+        None
+    }
+}
+
+fn quote_content(line: &str) -> Option<&str> {
+    let indent = indent(line);
+    if indent > 3 {
+        return None
+    }
+    let rest = line[indent..].strip_prefix('>')?;
+    Some(rest.strip_prefix(' ').unwrap_or(rest))
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ListKind {
+    Bullet(char),
+    Ordered(char),
+}
+
+struct ListMarker<'a> {
+    kind: ListKind,
+    start: u64,
+    content_indent: usize,
+    content: &'a str,
+}
+
+fn list_marker(line: &str) -> Option<ListMarker<'_>> {
+    let marker_indent = indent(line);
+    if marker_indent > 3 {
+        return None
+    }
+    let rest = &line[marker_indent..];
+    let (kind, start, marker_len) = match rest.chars().next()? {
+        ch @ ('-' | '+' | '*') => (ListKind::Bullet(ch), 1, 1),
+        _ => {
+            let digits = rest.len() - rest.trim_start_matches( | c: char | c.is_ascii_digit()).len();
+            if digits == 0 || digits > 9 {
+                return None
+            }
+            let delim = rest[digits..].chars().next()?;
+            if delim != '.' && delim != ')' {
+                return None
+            }
+            (ListKind::Ordered(delim), rest[..digits].parse().ok()?, digits + 1)
+        }
+    };
+    let after = &rest[marker_len..];
+    if !after.is_empty() && !after.starts_with([' ', '\t']) {
+        return None
+    }
+    // content that is indented more than 4 spaces is indented code inside the item
+    let spaces = indent(after);
+    let spaces = if spaces == 0 || spaces > 4 || is_blank(after) {1} else {spaces};
+    let content_indent = marker_indent + marker_len + spaces;
+    Some(ListMarker {
+        kind,
+        start,
+        content_indent,
+        content: line.get(content_indent..).unwrap_or(""),
+    })
+}
+
+// whether a line starts a block that ends a paragraph that comes before it
+fn interrupts_paragraph(line: &str) -> bool {
+    fence_start(line).is_some()
+        || atx_heading(line).is_some()
+        || is_rule(line)
+        || quote_content(line).is_some()
+        || list_marker(line).is_some_and( | marker | {
+            !is_blank(marker.content) && (matches!(marker.kind, ListKind::Bullet(_)) || marker.start == 1)
+        })
+}
+
+fn is_table_delimiter(line: &str) -> bool {
+    let cells = split_row(line);
+    line.contains(['|', '-'])
+        && !cells.is_empty()
+        && cells.iter().all( | cell | {
+        let cell = cell.trim();
+        let cell = cell.strip_prefix(':').unwrap_or(cell);
+        let cell = cell.strip_suffix(':').unwrap_or(cell);
+        !cell.is_empty() && cell.chars().all( | c | c == '-')
+    })
+}
+
+fn split_row(line: &str) -> Vec<&str> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = if line.ends_with('|') && !line.ends_with("\\|") {&line[..line.len() - 1]} else {line};
+    let mut cells = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        if c == '|' && !escaped {
+            cells.push(line[start..i].trim());
+            start = i + 1;
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    cells.push(line[start..].trim());
+    cells
+}
+
+fn parse_cell(cell: &str) -> Vec<MdInline> {
+    parse_inlines(&cell.replace("\\|", "|"))
+}
+
+fn join_lines(lines: &[&str]) -> String {
+    lines.join("\n")
+}
+
+/// Parses the blocks in the given lines, together with the index of the line each starts at.
+fn parse_blocks(lines: &[&str]) -> Vec<(usize, MdBlock)> {
+    let mut blocks = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        if is_blank(line) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        let block = if indent(line) >= 4 {
+            let mut code = Vec::new();
+            while i < lines.len() && (is_blank(lines[i]) || indent(lines[i]) >= 4) {
+                code.push(lines[i].get(4..).unwrap_or(""));
+                i += 1;
+            }
+            while code.last().is_some_and( | line | is_blank(line)) {
+                code.pop();
+            }
+            MdBlock::Code {info: String::new(), text: join_lines(&code)}
+        }
+        else if let Some(fence) = fence_start(line) {
+            i += 1;
+            let mut code = Vec::new();
+            while i < lines.len() {
+                if is_fence_end(lines[i], &fence) {
+                    i += 1;
+                    break;
+                }
+                let line = lines[i];
+                code.push(&line[indent(line).min(fence.indent)..]);
+                i += 1;
+            }
+            MdBlock::Code {info: fence.info.to_string(), text: join_lines(&code)}
+        }
+        else if let Some((level, text)) = atx_heading(line) {
+            i += 1;
+            MdBlock::Heading {level, inlines: parse_inlines(text)}
+        }
+        else if is_rule(line) {
+            i += 1;
+            MdBlock::Rule
+        }
+        else if quote_content(line).is_some() {
+            let mut inner = Vec::new();
+            while i < lines.len() {
+                if let Some(content) = quote_content(lines[i]) {
+                    inner.push(content);
+                }
+                // a line that continues the paragraph the quote ends with
+                else if !is_blank(lines[i]) && !interrupts_paragraph(lines[i]) && inner.last().is_some_and( | line | !is_blank(line)) {
+                    inner.push(lines[i]);
+                }
+                else {
+                    break;
+                }
+                i += 1;
+            }
+            MdBlock::Quote(parse_blocks(&inner).into_iter().map( | (_, block) | block).collect())
+        }
+        else if let Some(marker) = list_marker(line) {
+            let kind = marker.kind;
+            let ordered = matches!(kind, ListKind::Ordered(_));
+            let start = marker.start;
+            let mut items = Vec::new();
+            while let Some(marker) = lines.get(i).and_then( | line | list_marker(line)).filter( | marker | marker.kind == kind) {
+                let mut item = vec![marker.content];
+                i += 1;
+                while i < lines.len() {
+                    let line = lines[i];
+                    if is_blank(line) {
+                        item.push("");
+                    }
+                    else if indent(line) >= marker.content_indent {
+                        item.push(&line[marker.content_indent..]);
+                    }
+                    else if !interrupts_paragraph(line) && list_marker(line).is_none() && item.last().is_some_and( | line | !is_blank(line)) {
+                        item.push(line.trim_start());
+                    }
+                    else {
+                        break;
+                    }
+                    i += 1;
+                }
+                while item.last().is_some_and( | line | is_blank(line)) {
+                    item.pop();
+                }
+                items.push(parse_blocks(&item).into_iter().map( | (_, block) | block).collect());
+            }
+            MdBlock::List {ordered, start, items}
+        }
+        else if line.contains('|') && lines.get(i + 1).is_some_and( | line | is_table_delimiter(line)) {
+            let header = split_row(line).into_iter().map(parse_cell).collect();
+            i += 2;
+            let mut rows = Vec::new();
+            while i < lines.len() && !is_blank(lines[i]) && !interrupts_paragraph(lines[i]) {
+                rows.push(split_row(lines[i]).into_iter().map(parse_cell).collect());
+                i += 1;
+            }
+            MdBlock::Table {header, rows}
+        }
+        else {
+            let mut paragraph = vec![line.trim_start()];
+            let mut level = None;
+            i += 1;
+            while i < lines.len() && !is_blank(lines[i]) {
+                if let Some(setext) = setext_level(lines[i]) {
+                    level = Some(setext);
+                    i += 1;
+                    break;
+                }
+                if interrupts_paragraph(lines[i]) {
+                    break;
+                }
+                paragraph.push(lines[i].trim_start());
+                i += 1;
+            }
+            let inlines = parse_inlines(join_lines(&paragraph).trim_end());
+            match level {
+                Some(level) => MdBlock::Heading {level, inlines},
+                None => MdBlock::Paragraph(inlines),
+            }
+        };
+        blocks.push((start, block));
+    }
+    blocks
+}
+
+enum Piece {
+    Text(String),
+    Inline(MdInline),
+    // the start of a link, until the matching bracket is found
+    Bracket(&'static str),
+    Delim {
+        ch: char,
+        count: usize,
+        can_open: bool,
+        can_close: bool,
+        before: Vec<MdInline>,
+        after: Vec<MdInline>,
+    },
+}
+
+fn push_text(pieces: &mut Vec<Piece>, text: &str) {
+    if let Some(Piece::Text(last)) = pieces.last_mut() {
+        last.push_str(text);
+    }
+    else {
+        pieces.push(Piece::Text(text.to_string()));
+    }
+}
+
+fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation() || (!c.is_alphanumeric() && !c.is_whitespace() && !c.is_ascii())
+}
+
+// parses `(destination "title")` after the closing bracket of a link, returning the destination
+// and the index after the closing parenthesis
+fn parse_link_destination(chars: &[char], mut i: usize) -> Option<(String, usize)> {
+    if chars.get(i) != Some(&'(') {
+        return None
+    }
+    i += 1;
+    while chars.get(i).is_some_and( | c | c.is_whitespace()) {
+        i += 1;
+    }
+    let mut dest = String::new();
+    if chars.get(i) == Some(&'<') {
+        i += 1;
+        while let Some(&c) = chars.get(i) {
+            i += 1;
+            match c {
+                '>' => break,
+                '\n' => return None,
+                c => dest.push(c),
+            }
+        }
+    }
+    else {
+        let mut depth = 0;
+        while let Some(&c) = chars.get(i) {
+            match c {
+                c if c.is_whitespace() => break,
+                '(' => depth += 1,
+                ')' if depth == 0 => break,
+                ')' => depth -= 1,
+                '\\' if chars.get(i + 1).is_some_and( | c | c.is_ascii_punctuation()) => {
+                    i += 1;
+                }
+                _ => ()
+            }
+            dest.push(chars[i]);
+            i += 1;
+        }
+    }
+    while chars.get(i).is_some_and( | c | c.is_whitespace()) {
+        i += 1;
+    }
+    // the title is not shown, so it is only skipped
+    if let Some(&quote) = chars.get(i).filter( | c | matches!(c, '"' | '\'')) {
+        i += 1;
+        while chars.get(i).is_some_and( | c | *c != quote) {
+            i += 1;
+        }
+        i += 1;
+        while chars.get(i).is_some_and( | c | c.is_whitespace()) {
+            i += 1;
+        }
+    }
+    if chars.get(i) != Some(&')') {
+        return None
+    }
+    Some((dest, i + 1))
+}
+
+// matches the emphasis delimiters above `bottom` on the delimiter stack, as in the CommonMark
+// "process emphasis" procedure
+fn process_emphasis(pieces: &mut [Piece], delims: &mut Vec<usize>, bottom: usize) {
+    let mut ci = bottom;
+    while ci < delims.len() {
+        let Piece::Delim {ch, count: closer_count, can_open: closer_opens, can_close, ..} = pieces[delims[ci]] else {
+            unreachable!()
+        };
+        if !can_close {
+            ci += 1;
+            continue;
+        }
+        let opener = (bottom..ci).rev().find( | oi | {
+            let Piece::Delim {ch: opener_ch, count, can_open, can_close: opener_closes, ..} = pieces[delims[*oi]] else {
+                unreachable!()
+            };
+            opener_ch == ch && can_open && !(
+                (opener_closes || closer_opens)
+                    && (count + closer_count) % 3 == 0
+                    && !(count % 3 == 0 && closer_count % 3 == 0)
+            )
+        });
+        let Some(oi) = opener else {
+            ci += 1;
+            continue;
+        };
+        let Piece::Delim {count: opener_count, ..} = pieces[delims[oi]] else {
+            unreachable!()
+        };
+        let n = if opener_count >= 2 && closer_count >= 2 {2} else {1};
+        let (begin, end) = if n == 2 {
+            (MdInline::BeginStrong, MdInline::EndStrong)
+        }
+        else {
+            (MdInline::BeginEmphasis, MdInline::EndEmphasis)
+        };
+        if let Piece::Delim {count, after, ..} = &mut pieces[delims[oi]] {
+            *count -= n;
+            after.insert(0, begin);
+        }
+        if let Piece::Delim {count, before, ..} = &mut pieces[delims[ci]] {
+            *count -= n;
+            before.push(end);
+        }
+        // delimiters in between can not be matched anymore
+        delims.drain(oi + 1..ci);
+        ci = oi + 1;
+        if opener_count == n {
+            delims.remove(oi);
+            ci -= 1;
+        }
+        if closer_count == n {
+            delims.remove(ci);
+        }
+    }
+    delims.truncate(bottom);
+}
+
+/// Parses the inline content of a block: emphasis, code spans, links and line breaks.
+pub fn parse_inlines(text: &str) -> Vec<MdInline> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut pieces = Vec::new();
+    let mut delims = Vec::new();
+    // the pieces that open a link, with the height of the delimiter stack at that point
+    let mut brackets: Vec<(usize, usize)> = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\\' if chars.get(i + 1) == Some(&'\n') => {
+                pieces.push(Piece::Inline(MdInline::LineBreak));
+                i += 2;
+                while chars.get(i) == Some(&' ') {
+                    i += 1;
+                }
+            }
+            '\\' if chars.get(i + 1).is_some_and( | c | c.is_ascii_punctuation()) => {
+                push_text(&mut pieces, &chars[i + 1].to_string());
+                i += 2;
+            }
+            '`' => {
+                let run = chars[i..].iter().take_while( | c | **c == '`').count();
+                let mut j = i + run;
+                let mut end = None;
+                while j < chars.len() {
+                    let close = chars[j..].iter().take_while( | c | **c == '`').count();
+                    if close == run {
+                        end = Some(j);
+                        break;
+                    }
+                    j += close.max(1);
+                }
+                match end {
+                    Some(end) => {
+                        let code: String = chars[i + run..end].iter().map( | c | if *c == '\n' {' '} else {*c}).collect();
+                        let code = if code.len() > 2 && code.starts_with(' ') && code.ends_with(' ') && !code.trim().is_empty() {
+                            code[1..code.len() - 1].to_string()
+                        }
+                        else {
+                            code
+                        };
+                        pieces.push(Piece::Inline(MdInline::Code(code)));
+                        i = end + run;
+                    }
+                    None => {
+                        push_text(&mut pieces, &"`".repeat(run));
+                        i += run;
+                    }
+                }
+            }
+            '*' | '_' => {
+                let count = chars[i..].iter().take_while( | d | **d == c).count();
+                let prev = if i > 0 {chars[i - 1]} else {' '};
+                let next = chars.get(i + count).copied().unwrap_or(' ');
+                let left = !next.is_whitespace() && (!is_punctuation(next) || prev.is_whitespace() || is_punctuation(prev));
+                let right = !prev.is_whitespace() && (!is_punctuation(prev) || next.is_whitespace() || is_punctuation(next));
+                let (can_open, can_close) = if c == '*' {
+                    (left, right)
+                }
+                else {
+                    (left && (!right || is_punctuation(prev)), right && (!left || is_punctuation(next)))
+                };
+                delims.push(pieces.len());
+                pieces.push(Piece::Delim {ch: c, count, can_open, can_close, before: Vec::new(), after: Vec::new()});
+                i += count;
+            }
+            '[' => {
+                brackets.push((pieces.len(), delims.len()));
+                pieces.push(Piece::Bracket("["));
+                i += 1;
+            }
+            '!' if chars.get(i + 1) == Some(&'[') => {
+                brackets.push((pieces.len(), delims.len()));
+                pieces.push(Piece::Bracket("!["));
+                i += 2;
+            }
+            ']' => {
+                let link = brackets.last().and_then( | _ | parse_link_destination(&chars, i + 1));
+                match (brackets.pop(), link) {
+                    (Some((bracket, bottom)), Some((dest, end))) => {
+                        process_emphasis(&mut pieces, &mut delims, bottom);
+                        pieces[bracket] = Piece::Inline(MdInline::BeginLink(dest));
+                        pieces.push(Piece::Inline(MdInline::EndLink));
+                        // links can not hold other links
+                        brackets.clear();
+                        i = end;
+                    }
+                    _ => {
+                        push_text(&mut pieces, "]");
+                        i += 1;
+                    }
+                }
+            }
+            '<' => {
+                let end = chars[i + 1..].iter().position( | c | *c == '>' || *c == '<' || c.is_whitespace());
+                let url: Option<String> = end
+                    .filter( | end | chars[i + 1 + end] == '>')
+                    .map( | end | chars[i + 1..i + 1 + end].iter().collect())
+                    .filter( | url: &String | url.contains("://") || url.starts_with("mailto:"));
+                match url {
+                    Some(url) => {
+                        i += url.chars().count() + 2;
+                        pieces.push(Piece::Inline(MdInline::BeginLink(url.clone())));
+                        pieces.push(Piece::Text(url));
+                        pieces.push(Piece::Inline(MdInline::EndLink));
+                    }
+                    None => {
+                        push_text(&mut pieces, "<");
+                        i += 1;
+                    }
+                }
+            }
+            '\n' => {
+                // two spaces at the end of a line make a hard break
+                let mut hard = false;
+                if let Some(Piece::Text(text)) = pieces.last_mut() {
+                    hard = text.ends_with("  ");
+                    let len = text.trim_end_matches(' ').len();
+                    text.truncate(len);
+                }
+                if hard {
+                    pieces.push(Piece::Inline(MdInline::LineBreak));
+                }
+                else {
+                    push_text(&mut pieces, " ");
+                }
+                i += 1;
+                while chars.get(i) == Some(&' ') {
+                    i += 1;
+                }
+            }
+            c => {
+                push_text(&mut pieces, c.encode_utf8(&mut [0; 4]));
+                i += 1;
+            }
+        }
+    }
+    process_emphasis(&mut pieces, &mut delims, 0);
+
+    let mut inlines = Vec::new();
+    let mut push = | inline: MdInline | {
+        match (inlines.last_mut(), inline) {
+            (Some(MdInline::Text(last)), MdInline::Text(text)) => last.push_str(&text),
+            (_, MdInline::Text(text)) if text.is_empty() => (),
+            (_, inline) => inlines.push(inline),
+        }
+    };
+    for piece in pieces {
+        match piece {
+            Piece::Text(text) => push(MdInline::Text(text)),
+            Piece::Bracket(text) => push(MdInline::Text(text.to_string())),
+            Piece::Inline(inline) => push(inline),
+            Piece::Delim {ch, count, before, after, ..} => {
+                before.into_iter().for_each(&mut push);
+                push(MdInline::Text(ch.to_string().repeat(count)));
+                after.into_iter().for_each(&mut push);
+            }
+        }
+    }
+    inlines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use MdInline::*;
+
+    fn text(text: &str) -> MdInline {
+        Text(text.to_string())
+    }
+
+    fn paragraph(text: &str) -> MdBlock {
+        MdBlock::Paragraph(parse_inlines(text))
+    }
+
+    #[test]
+    fn emphasis() {
+        assert_eq!(parse_inlines("a *b* __c__ ***d***"), vec![
+            text("a "), BeginEmphasis, text("b"), EndEmphasis, text(" "),
+            BeginStrong, text("c"), EndStrong, text(" "),
+            BeginEmphasis, BeginStrong, text("d"), EndStrong, EndEmphasis,
+        ]);
+        assert_eq!(parse_inlines("snake_case_name and * alone"), vec![text("snake_case_name and * alone")]);
+        assert_eq!(parse_inlines("*a **b** c*"), vec![
+            BeginEmphasis, text("a "), BeginStrong, text("b"), EndStrong, text(" c"), EndEmphasis,
+        ]);
+    }
+
+    #[test]
+    fn code_links_and_breaks() {
+        assert_eq!(parse_inlines("`a*b*` [the *site*](http://x.y \"t\") <https://z>\\*  \nnext"), vec![
+            Code("a*b*".into()), text(" "),
+            BeginLink("http://x.y".into()), text("the "), BeginEmphasis, text("site"), EndEmphasis, EndLink, text(" "),
+            BeginLink("https://z".into()), text("https://z"), EndLink, text("*"),
+            LineBreak, text("next"),
+        ]);
+        assert_eq!(parse_inlines("[not a link] `open"), vec![text("[not a link] `open")]);
+    }
+
+    #[test]
+    fn blocks() {
+        let doc = MarkdownDoc::parse("# Title #\n\nSome\ntext\n\nSub\n---\n> quoted\nlazy\n\n***\n```rust\nfn main() {}\n```\n    indented\n");
+        assert_eq!(doc.blocks(), &[
+            MdBlock::Heading {level: 1, inlines: vec![text("Title")]},
+            paragraph("Some text"),
+            MdBlock::Heading {level: 2, inlines: vec![text("Sub")]},
+            MdBlock::Quote(vec![paragraph("quoted lazy")]),
+            MdBlock::Rule,
+            MdBlock::Code {info: "rust".into(), text: "fn main() {}".into()},
+            MdBlock::Code {info: String::new(), text: "indented".into()},
+        ]);
+    }
+
+    #[test]
+    fn lists() {
+        let doc = MarkdownDoc::parse("- a\n- b\n  1. c\n  2. d\n\n- e\n\n3) f\n");
+        assert_eq!(doc.blocks(), &[
+            MdBlock::List {ordered: false, start: 1, items: vec![
+                vec![paragraph("a")],
+                vec![paragraph("b"), MdBlock::List {ordered: true, start: 1, items: vec![
+                    vec![paragraph("c")],
+                    vec![paragraph("d")],
+                ]}],
+                vec![paragraph("e")],
+            ]},
+            MdBlock::List {ordered: true, start: 3, items: vec![vec![paragraph("f")]]},
+        ]);
+    }
+
+    #[test]
+    fn tables() {
+        let doc = MarkdownDoc::parse("| a | *b* |\n|---|:-:|\n| 1 | 2 \\| 3 |\n4|5\n");
+        assert_eq!(doc.blocks(), &[MdBlock::Table {
+            header: vec![vec![text("a")], vec![BeginEmphasis, text("b"), EndEmphasis]],
+            rows: vec![
+                vec![vec![text("1")], vec![text("2 | 3")]],
+                vec![vec![text("4")], vec![text("5")]],
+            ],
+        }]);
+    }
+
+    #[test]
+    fn appending_matches_parsing_at_once() {
+        let source = "# Chat\n\nHere is *some* code:\n\n```rust\nfn main() {\n    println!(\"hi\");\n}\n```\n\n- one\n- two\n\n  more\n\n| k | v |\n|---|---|\n| a | b |\n\nDone.";
+        let mut doc = MarkdownDoc::default();
+        for (i, c) in source.char_indices() {
+            doc.append(&source[i..i + c.len_utf8()]);
+            assert_eq!(doc.blocks(), MarkdownDoc::parse(&source[..i + c.len_utf8()]).blocks());
+        }
+        assert_eq!(doc.source(), source);
+    }
+}
@@ -455,6 +455,17 @@ impl DeJsonState {
                             'r'=>self.strbuf.push('\r'),
                             't'=>self.strbuf.push('\t'),
                             '0'=>self.strbuf.push('\0'),
+                            'u'=>{
+                                let mut code = 0;
+                                for _ in 0..4 {
+                                    self.next(i);
+                                    match self.cur.to_digit(16) {
+                                        Some(digit) => code = code * 16 + digit,
+                                        None => return Err(self.err_parse("string"))
+                                    }
+                                }
+                                self.strbuf.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                            }
                             '\0'=>{
                                 return Err(self.err_parse("string"));
                             },
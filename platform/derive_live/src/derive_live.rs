@@ -170,7 +170,11 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
                     if field.name == "abs_pos" ||
                       field.name == "margin" ||
                       field.name == "width" ||
-                      field.name == "height" {
+                      field.name == "height" ||
+                      field.name == "width_min" ||
+                      field.name == "width_max" ||
+                      field.name == "height_min" ||
+                      field.name == "height_max" {
                           return error_result(&format!("Name collision between walk splat and {}", field.name));
                       }
                 }
@@ -178,6 +182,10 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
                 tb.add("        live_id!(margin)=>self.").ident(&field.name).add(".margin.apply(cx, apply, index, nodes),");
                 tb.add("        live_id!(width)=>self.").ident(&field.name).add(".width.apply(cx, apply, index, nodes),");
                 tb.add("        live_id!(height)=>self.").ident(&field.name).add(".height.apply(cx, apply, index, nodes),");
+                tb.add("        live_id!(width_min)=>self.").ident(&field.name).add(".width_min.apply(cx, apply, index, nodes),");
+                tb.add("        live_id!(width_max)=>self.").ident(&field.name).add(".width_max.apply(cx, apply, index, nodes),");
+                tb.add("        live_id!(height_min)=>self.").ident(&field.name).add(".height_min.apply(cx, apply, index, nodes),");
+                tb.add("        live_id!(height_max)=>self.").ident(&field.name).add(".height_max.apply(cx, apply, index, nodes),");
             }
             else if field.attrs.iter().any( | a | a.name == "layout") {
                 for field in &fields {
@@ -188,7 +196,8 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
                       field.name == "align" ||
                       field.name == "line_spacing" ||
                       field.name == "flow" ||
-                      field.name == "spacing"{
+                      field.name == "spacing" ||
+                      field.name == "grid"{
                           return error_result(&format!("Name collision between layout splat and {}", field.name));
                       }
                 }
@@ -200,6 +209,7 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
                 tb.add("        live_id!(flow)=>self.").ident(&field.name).add(".flow.apply(cx, apply, index, nodes),");
                 tb.add("        live_id!(spacing)=>self.").ident(&field.name).add(".spacing.apply(cx, apply, index, nodes),");
                 tb.add("        live_id!(line_spacing)=>self.").ident(&field.name).add(".line_spacing.apply(cx, apply, index, nodes),");
+                tb.add("        live_id!(grid)=>self.").ident(&field.name).add(".grid.apply(cx, apply, index, nodes),");
             }
         }
         // Unknown value handling
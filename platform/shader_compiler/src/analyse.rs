@@ -157,6 +157,10 @@ impl<'a> DrawShaderAnalyser<'a> {
         
         self.scopes.pop_scope();
         
+        if self.draw_shader_def.is_compute() {
+            return self.analyse_compute_call_tree()
+        }
+        
         let mut all_fns = Vec::new();
         let mut vertex_fns = Vec::new();
         // we should insert our vertex call
@@ -245,6 +249,40 @@ impl<'a> DrawShaderAnalyser<'a> {
         Ok(())
     }
     
+    fn analyse_compute_call_tree(&mut self) -> Result<(), LiveError> {
+        let mut all_fns = Vec::new();
+        let mut compute_fns = Vec::new();
+        self.analyse_call_tree(
+            &mut Vec::new(),
+            self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(compute))).unwrap(),
+            &mut compute_fns,
+            &mut all_fns,
+        ) ?;
+        
+        let mut all_structs = Vec::new();
+        let mut compute_structs = Vec::new();
+        let mut all_live_refs = BTreeMap::new();
+        for compute_fn in &compute_fns {
+            let fn_decl = self.shader_registry.all_fns.get(compute_fn).unwrap();
+            for struct_ptr in fn_decl.struct_refs.borrow().as_ref().unwrap().iter() {
+                let struct_def = self.shader_registry.structs.get(struct_ptr).unwrap();
+                self.analyse_struct_tree(&mut Vec::new(), *struct_ptr, struct_def, &mut compute_structs, &mut all_structs) ?;
+            }
+        }
+        for any_fn in all_fns.iter().rev() {
+            let fn_def = self.shader_registry.all_fns.get(any_fn).unwrap();
+            all_live_refs.extend(fn_def.live_refs.borrow().as_ref().cloned().unwrap());
+            self.analyse_hidden_args(fn_def);
+        }
+        
+        *self.draw_shader_def.all_live_refs.borrow_mut() = all_live_refs;
+        *self.draw_shader_def.all_fns.borrow_mut() = all_fns;
+        *self.draw_shader_def.compute_fns.borrow_mut() = compute_fns;
+        *self.draw_shader_def.all_structs.borrow_mut() = all_structs;
+        *self.draw_shader_def.compute_structs.borrow_mut() = compute_structs;
+        Ok(())
+    }
+    
     fn analyse_hidden_args(&mut self, fn_def: &FnDef) {
         // ok so.. lets build it up
         let mut hidden_args = BTreeSet::new();
@@ -276,6 +314,9 @@ impl<'a> DrawShaderAnalyser<'a> {
                 DrawShaderFieldKind::Varying {..} => {
                     hidden_args.insert(HiddenArgKind::Varyings);
                 }
+                DrawShaderFieldKind::Buffer {..} => {
+                    hidden_args.insert(HiddenArgKind::Buffers);
+                }
             }
         }
         if fn_def.live_refs.borrow().as_ref().unwrap().len() > 0 {
@@ -359,6 +400,22 @@ impl<'a> DrawShaderAnalyser<'a> {
                 }
                 ty
             }
+            DrawShaderFieldKind::Buffer {..} => {
+                let ty = self.ty_checker().ty_check_ty_expr(&decl.ty_expr) ?;
+                match &ty {
+                    Ty::Array {elem_ty, ..} if matches!(**elem_ty, Ty::Float | Ty::Vec2 | Ty::Vec4) => {}
+                    _ => {
+                        return Err(LiveError {
+                            origin: live_error_origin!(),
+                            span: decl.span.into(),
+                            message: String::from(
+                                "buffer must be either a float, vec2 or vec4",
+                            ),
+                        })
+                    }
+                }
+                ty
+            }
         };
         *decl.ty_expr.ty.borrow_mut() = Some(ty);
         Ok(())
@@ -401,6 +458,17 @@ impl<'a> DrawShaderAnalyser<'a> {
                     })
                 }
             }
+        } else if self.draw_shader_def.is_compute() && def.ident == Ident(live_id!(compute)) {
+            let is_id_param = | param: &Param | matches!(param.ty_expr.ty.borrow().as_ref(), Some(Ty::Ivec3));
+            if return_ty != Ty::Void || def.params.len() != 2 || !is_id_param(&def.params[1]) {
+                return Err(LiveError {
+                    origin: live_error_origin!(),
+                    span: def.span.into(),
+                    message: String::from(
+                        "function `compute` must be declared as `fn compute(self, id: ivec3)`",
+                    ),
+                })
+            }
         } else {
             match return_ty {
                 Ty::Array {..} => {
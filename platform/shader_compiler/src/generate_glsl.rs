@@ -42,6 +42,20 @@ pub fn generate_pixel_shader(draw_shader_def: &DrawShaderDef, const_table: &Draw
     string
 }
 
+// expects to be prefixed with a `#version 310 es` or `#version 430` header by the platform
+pub fn generate_compute_shader(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> String {
+    let mut string = String::new();
+    DrawShaderGenerator {
+        draw_shader_def,
+        const_table,
+        shader_registry,
+        string: &mut string,
+        backend_writer: &GlslBackendWriter {shader_registry, const_table}
+    }
+    .generate_compute_shader();
+    string
+}
+
 struct DrawShaderGenerator<'a> {
     draw_shader_def: &'a DrawShaderDef,
     shader_registry: &'a ShaderRegistry,
@@ -318,6 +332,33 @@ impl<'a> DrawShaderGenerator<'a> {
         writeln!(self.string, "}}").unwrap();
     }
     
+    fn generate_compute_shader(&mut self) {
+        self.generate_decls(None, None, 0);
+        // storage buffers are bound in declaration order
+        let mut binding = 0;
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Buffer {..} = field.kind {
+                if let Some(Ty::Array {elem_ty, ..}) = field.ty_expr.ty.borrow().as_ref() {
+                    write!(self.string, "layout(std430, binding = {}) buffer buffer_{} {{ ", binding, field.ident).unwrap();
+                    self.write_ty_lit(elem_ty.maybe_ty_lit().unwrap());
+                    writeln!(self.string, " {}[]; }};", DisplayDsIdent(field.ident)).unwrap();
+                    binding += 1;
+                }
+            }
+        }
+        let [x, y, z] = self.draw_shader_def.workgroup_size.unwrap();
+        writeln!(self.string, "layout(local_size_x = {}, local_size_y = {}, local_size_z = {}) in;", x, y, z).unwrap();
+        
+        self.generate_shader_body(&self.draw_shader_def.compute_fns.borrow(), &self.draw_shader_def.compute_structs.borrow());
+        
+        writeln!(self.string, "void main() {{").unwrap();
+        self.generate_uniform_block_unpack();
+        self.generate_live_unpack();
+        let compute_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(compute))).unwrap();
+        writeln!(self.string, "    {}(ivec3(gl_GlobalInvocationID));", DisplayFnName(compute_def.fn_ptr, compute_def.ident)).unwrap();
+        writeln!(self.string, "}}").unwrap();
+    }
+    
     fn generate_uniform_block_unpack(
         &mut self,
     ) {
@@ -41,7 +41,11 @@ struct DrawShaderGenerator<'a> {
 }
 
 impl<'a> DrawShaderGenerator<'a> {
+    // compute shaders come out as a single `compute_main` kernel instead of the vertex and pixel entry points
     fn generate_shader(&mut self) {
+        let is_compute = self.draw_shader_def.is_compute();
+        // compute shaders have no derivatives to pick a mip level with
+        let sample_fn = if is_compute {"SampleLevel(default_texture_sampler,pos,0)"} else {"Sample(default_texture_sampler,pos)"};
         
         let mut all_constructor_fns = BTreeSet::new();
         
@@ -54,13 +58,13 @@ impl<'a> DrawShaderGenerator<'a> {
             let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
             if fn_def.builtin_deps.borrow().as_ref().unwrap().contains(&Ident(live_id!(sample2d))) {
                 writeln!(self.string, "SamplerState default_texture_sampler{{Filter=MIN_MAX_MIP_LINEAR;AddressU = Wrap;AddressV=Wrap;}};").unwrap();
-                writeln!(self.string, "float4 sample2d(Texture2D tex, float2 pos){{return tex.Sample(default_texture_sampler,pos);}}").unwrap();
+                writeln!(self.string, "float4 sample2d(Texture2D tex, float2 pos){{return tex.{};}}", sample_fn).unwrap();
                 break;
             }
 
             if fn_def.builtin_deps.borrow().as_ref().unwrap().contains(&Ident(live_id!(sample2d_rt))) {
                 writeln!(self.string, "SamplerState default_texture_sampler{{Filter=MIN_MAX_MIP_LINEAR;AddressU = Wrap;AddressV=Wrap;}};").unwrap();
-                writeln!(self.string, "float4 sample2d_rt(Texture2D tex, float2 pos){{return tex.Sample(default_texture_sampler,pos);}}").unwrap();
+                writeln!(self.string, "float4 sample2d_rt(Texture2D tex, float2 pos){{return tex.{};}}", sample_fn).unwrap();
                 break;
             }
         };
@@ -69,9 +73,14 @@ impl<'a> DrawShaderGenerator<'a> {
         let fields_as_uniform_blocks = self.draw_shader_def.fields_as_uniform_blocks();
         self.generate_uniform_structs(&fields_as_uniform_blocks);
         self.generate_texture_defs();
-        self.generate_geometry_struct();
-        self.generate_instance_struct();
-        self.generate_varying_struct();
+        if is_compute {
+            self.generate_buffer_defs();
+        }
+        else {
+            self.generate_geometry_struct();
+            self.generate_instance_struct();
+            self.generate_varying_struct();
+        }
         
         //let vertex_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(vertex))).unwrap();
         //let pixel_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(pixel))).unwrap();
@@ -110,8 +119,38 @@ impl<'a> DrawShaderGenerator<'a> {
             }
             .generate_fn_def()
         }
-        self.generate_vertex_main();
-        self.generate_pixel_main();
+        if is_compute {
+            self.generate_compute_main();
+        }
+        else {
+            self.generate_vertex_main();
+            self.generate_pixel_main();
+        }
+    }
+    
+    fn generate_buffer_defs(&mut self) {
+        let mut index = 0;
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Buffer {..} = field.kind {
+                if let Some(Ty::Array {elem_ty, ..}) = field.ty_expr.ty.borrow().as_ref() {
+                    write!(self.string, "RWStructuredBuffer<").unwrap();
+                    self.write_ty_lit(elem_ty.maybe_ty_lit().unwrap());
+                    writeln!(self.string, "> {}: register(u{});", DisplayDsIdent(field.ident), index).unwrap();
+                    index += 1;
+                }
+            }
+        }
+    }
+    
+    fn generate_compute_main(&mut self) {
+        let [x, y, z] = self.draw_shader_def.workgroup_size.unwrap();
+        writeln!(self.string, "[numthreads({}, {}, {})]", x, y, z).unwrap();
+        writeln!(self.string, "void compute_main(uint3 id: SV_DispatchThreadID) {{").unwrap();
+        let compute_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(compute))).unwrap();
+        write!(self.string, "    {}(int3(id)", DisplayFnName(compute_def.fn_ptr, compute_def.ident)).unwrap();
+        self.backend_writer.write_call_expr_hidden_args(self.string, compute_def.hidden_args.borrow().as_ref().unwrap(), ", ");
+        writeln!(self.string, ");").unwrap();
+        writeln!(self.string, "}}").unwrap();
     }
     
     fn generate_struct_decls(&mut self) {
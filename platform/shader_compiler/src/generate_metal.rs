@@ -46,7 +46,11 @@ struct DrawShaderGenerator<'a> {
 }
 
 impl<'a> DrawShaderGenerator<'a> {
+    // compute shaders come out as a single `compute_main` kernel instead of the vertex and fragment functions
     fn generate_shader(&mut self) {
+        let is_compute = self.draw_shader_def.is_compute();
+        // compute shaders have no derivatives to pick a mip level with
+        let sample_args = if is_compute {"pos,level(0)"} else {"pos"};
         writeln!(self.string, "#include <metal_stdlib>").unwrap();
         writeln!(self.string, "using namespace metal;").unwrap();
 
@@ -60,11 +64,11 @@ impl<'a> DrawShaderGenerator<'a> {
         for fn_iter in self.draw_shader_def.all_fns.borrow().iter() {
             let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
             if fn_def.builtin_deps.borrow().as_ref().unwrap().contains(&Ident(live_id!(sample2d))) {
-                writeln!(self.string, "float4 sample2d(texture2d<float> tex, float2 pos){{return tex.sample(sampler(mag_filter::linear,min_filter::linear),pos);}}").unwrap();
+                writeln!(self.string, "float4 sample2d(texture2d<float> tex, float2 pos){{return tex.sample(sampler(mag_filter::linear,min_filter::linear),{});}}", sample_args).unwrap();
                 break;
             }
             if fn_def.builtin_deps.borrow().as_ref().unwrap().contains(&Ident(live_id!(sample2d_rt))) {
-                writeln!(self.string, "float4 sample2d_rt(texture2d<float> tex, float2 pos){{return tex.sample(sampler(mag_filter::linear,min_filter::linear),pos);}}").unwrap();
+                writeln!(self.string, "float4 sample2d_rt(texture2d<float> tex, float2 pos){{return tex.sample(sampler(mag_filter::linear,min_filter::linear),{});}}", sample_args).unwrap();
                 break;
             }
         };
@@ -73,9 +77,14 @@ impl<'a> DrawShaderGenerator<'a> {
         //let fields_as_uniform_blocks = self.draw_shader_def.fields_as_uniform_blocks();
         self.generate_uniform_structs();
         self.generate_texture_struct();
-        self.generate_geometry_struct();
-        self.generate_instance_struct();
-        self.generate_varying_struct();
+        if is_compute {
+            self.generate_buffer_struct();
+        }
+        else {
+            self.generate_geometry_struct();
+            self.generate_instance_struct();
+            self.generate_varying_struct();
+        }
         
         //let vertex_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(vertex))).unwrap();
         //let pixel_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(pixel))).unwrap();
@@ -114,8 +123,63 @@ impl<'a> DrawShaderGenerator<'a> {
             }
             .generate_fn_def()
         }
-        self.generate_vertex_main();
-        self.generate_pixel_main();
+        if is_compute {
+            self.generate_compute_main();
+        }
+        else {
+            self.generate_vertex_main();
+            self.generate_pixel_main();
+        }
+    }
+    
+    fn generate_buffer_struct(&mut self) {
+        writeln!(self.string, "struct Buffers {{").unwrap();
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Buffer {..} = field.kind {
+                if let Some(Ty::Array {elem_ty, ..}) = field.ty_expr.ty.borrow().as_ref() {
+                    write!(self.string, "    device ").unwrap();
+                    self.write_ty_lit(elem_ty.maybe_ty_lit().unwrap());
+                    writeln!(self.string, " *{};", DisplayDsIdent(field.ident)).unwrap();
+                }
+            }
+        }
+        writeln!(self.string, "}};").unwrap();
+    }
+    
+    // the buffer indices follow the fragment function, storage buffers come after the uniform blocks
+    fn generate_compute_main(&mut self) {
+        writeln!(self.string, "kernel void compute_main(").unwrap();
+        writeln!(self.string, "uint3 id [[thread_position_in_grid]]").unwrap();
+        writeln!(self.string, ", Textures textures").unwrap();
+        writeln!(self.string, ", constant LiveUniforms &live_uniforms [[buffer(2)]]").unwrap();
+        writeln!(self.string, ", constant const float *const_table [[buffer(3)]]").unwrap();
+        let mut buffer_id = 4;
+        for (field, _set) in self.fields_as_uniform_blocks {
+            writeln!(self.string, ", constant Uniforms_{0} &uniforms_{0} [[buffer({1})]]", field, buffer_id).unwrap();
+            buffer_id += 1;
+        }
+        let mut buffers = Vec::new();
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Buffer {..} = field.kind {
+                if let Some(Ty::Array {elem_ty, ..}) = field.ty_expr.ty.borrow().as_ref() {
+                    write!(self.string, ", device ").unwrap();
+                    self.write_ty_lit(elem_ty.maybe_ty_lit().unwrap());
+                    writeln!(self.string, " *{} [[buffer({})]]", DisplayDsIdent(field.ident), buffer_id).unwrap();
+                    buffer_id += 1;
+                    buffers.push(field.ident);
+                }
+            }
+        }
+        writeln!(self.string, ") {{").unwrap();
+        writeln!(self.string, "    Buffers buffers;").unwrap();
+        for ident in buffers {
+            writeln!(self.string, "    buffers.{0} = {0};", DisplayDsIdent(ident)).unwrap();
+        }
+        let compute_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(compute))).unwrap();
+        write!(self.string, "    {}(int3(id)", DisplayFnName(compute_def.fn_ptr, compute_def.ident)).unwrap();
+        self.backend_writer.write_call_expr_hidden_args(self.string, compute_def.hidden_args.borrow().as_ref().unwrap(), ", ");
+        writeln!(self.string, ");").unwrap();
+        writeln!(self.string, "}}").unwrap();
     }
     
     fn generate_struct_defs(&mut self) {
@@ -647,6 +711,9 @@ impl<'a> BackendWriter for MetalBackendWriter<'a> {
                 HiddenArgKind::LiveUniforms => {
                     write!(string, "live_uniforms").unwrap();
                 }
+                HiddenArgKind::Buffers => {
+                    write!(string, "buffers").unwrap();
+                }
             }
             sep = ", ";
         }
@@ -681,6 +748,9 @@ impl<'a> BackendWriter for MetalBackendWriter<'a> {
                 HiddenArgKind::LiveUniforms => {
                     write!(string, "constant LiveUniforms &live_uniforms").unwrap();
                 }
+                HiddenArgKind::Buffers => {
+                    write!(string, "Buffers buffers").unwrap();
+                }
             }
             sep = ", ";
         }
@@ -768,6 +838,9 @@ impl<'a> BackendWriter for MetalBackendWriter<'a> {
             DrawShaderFieldKind::Uniform {block_ident, ..} => {
                 write!(string, "uniforms_{}.", block_ident).unwrap()
             }
+            DrawShaderFieldKind::Buffer {..} => {
+                write!(string, "buffers.").unwrap()
+            }
         }
         write!(string, "{}", &DisplayDsIdent(field_ident)).unwrap();
     }
@@ -0,0 +1,1403 @@
+use {
+    std::{
+        fmt::Write,
+        collections::BTreeSet,
+    },
+    crate::{
+        makepad_live_id::{
+            live_id,
+            LiveId,
+        },
+        makepad_live_compiler::makepad_math::{PrettyPrintedF32, Vec4},
+        generate::*,
+        swizzle::Swizzle,
+        shader_ast::*,
+        shader_registry::ShaderRegistry
+    }
+};
+
+// WGSL has no C style declarations, no ternaries, no swizzle stores and passes inout
+// params as pointers, so unlike the other backends this one doesn't go through the
+// BackendWriter hooks but walks the function bodies itself. The naming and the
+// closure specialisation scheme are shared with generate.rs.
+//
+// Bindings all live in group 0, in this order: the uniform blocks (in
+// fields_as_uniform_blocks order), live_table, const_table, the textures and
+// finally a single sampler. Every table is an array<vec4<f32>> holding the same
+// tightly packed floats the GLSL backend uses. Geometry attributes come first in
+// the vertex locations, followed by the instance attributes. Compute shaders
+// have their storage buffers after the sampler, in declaration order.
+
+pub fn generate_vertex_shader(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> String {
+    let mut string = String::new();
+    DrawShaderGenerator {
+        draw_shader_def,
+        const_table,
+        shader_registry,
+        stage: ShaderStage::Vertex,
+        string: &mut string,
+    }
+    .generate_vertex_shader();
+    string
+}
+
+pub fn generate_pixel_shader(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> String {
+    let mut string = String::new();
+    DrawShaderGenerator {
+        draw_shader_def,
+        const_table,
+        shader_registry,
+        stage: ShaderStage::Pixel,
+        string: &mut string,
+    }
+    .generate_pixel_shader();
+    string
+}
+
+pub fn generate_compute_shader(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> String {
+    let mut string = String::new();
+    DrawShaderGenerator {
+        draw_shader_def,
+        const_table,
+        shader_registry,
+        stage: ShaderStage::Compute,
+        string: &mut string,
+    }
+    .generate_compute_shader();
+    string
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ShaderStage {
+    Vertex,
+    Pixel,
+    Compute
+}
+
+const INVERSE_MAT4: &str = "fn inverse_mat4(m: mat4x4<f32>) -> mat4x4<f32> {
+    let b00 = m[0][0] * m[1][1] - m[0][1] * m[1][0];
+    let b01 = m[0][0] * m[1][2] - m[0][2] * m[1][0];
+    let b02 = m[0][0] * m[1][3] - m[0][3] * m[1][0];
+    let b03 = m[0][1] * m[1][2] - m[0][2] * m[1][1];
+    let b04 = m[0][1] * m[1][3] - m[0][3] * m[1][1];
+    let b05 = m[0][2] * m[1][3] - m[0][3] * m[1][2];
+    let b06 = m[2][0] * m[3][1] - m[2][1] * m[3][0];
+    let b07 = m[2][0] * m[3][2] - m[2][2] * m[3][0];
+    let b08 = m[2][0] * m[3][3] - m[2][3] * m[3][0];
+    let b09 = m[2][1] * m[3][2] - m[2][2] * m[3][1];
+    let b10 = m[2][1] * m[3][3] - m[2][3] * m[3][1];
+    let b11 = m[2][2] * m[3][3] - m[2][3] * m[3][2];
+    let det = b00 * b11 - b01 * b10 + b02 * b09 + b03 * b08 - b04 * b07 + b05 * b06;
+    return mat4x4<f32>(
+        m[1][1] * b11 - m[1][2] * b10 + m[1][3] * b09,
+        m[0][2] * b10 - m[0][1] * b11 - m[0][3] * b09,
+        m[3][1] * b05 - m[3][2] * b04 + m[3][3] * b03,
+        m[2][2] * b04 - m[2][1] * b05 - m[2][3] * b03,
+        m[1][2] * b08 - m[1][0] * b11 - m[1][3] * b07,
+        m[0][0] * b11 - m[0][2] * b08 + m[0][3] * b07,
+        m[3][2] * b02 - m[3][0] * b05 - m[3][3] * b01,
+        m[2][0] * b05 - m[2][2] * b02 + m[2][3] * b01,
+        m[1][0] * b10 - m[1][1] * b08 + m[1][3] * b06,
+        m[0][1] * b08 - m[0][0] * b10 - m[0][3] * b06,
+        m[3][0] * b04 - m[3][1] * b02 + m[3][3] * b00,
+        m[2][1] * b02 - m[2][0] * b04 - m[2][3] * b00,
+        m[1][1] * b07 - m[1][0] * b09 - m[1][2] * b06,
+        m[0][0] * b09 - m[0][1] * b07 + m[0][2] * b06,
+        m[3][1] * b01 - m[3][0] * b03 - m[3][2] * b00,
+        m[2][0] * b03 - m[2][1] * b01 + m[2][2] * b00
+    ) * (1.0 / det);
+}
+";
+
+struct DrawShaderGenerator<'a> {
+    draw_shader_def: &'a DrawShaderDef,
+    shader_registry: &'a ShaderRegistry,
+    const_table: &'a DrawShaderConstTable,
+    stage: ShaderStage,
+    string: &'a mut String,
+}
+
+impl<'a> DrawShaderGenerator<'a> {
+    fn generate_vertex_shader(&mut self) {
+        let packed_geometries_slots = self.compute_packed_slots( | kind | matches!(kind, DrawShaderFieldKind::Geometry {..}));
+        let packed_instances_slots = self.compute_packed_slots( | kind | matches!(kind, DrawShaderFieldKind::Instance {..}));
+        let packed_varyings_slots = self.compute_packed_slots(is_varying);
+
+        self.generate_decls(Some(packed_varyings_slots));
+
+        let mut location = 0;
+        if packed_geometries_slots + packed_instances_slots > 0 {
+            writeln!(self.string, "struct VertexInput {{").unwrap();
+            location = self.generate_packed_var_decls(location, "packed_geometry", packed_geometries_slots);
+            self.generate_packed_var_decls(location, "packed_instance", packed_instances_slots);
+            writeln!(self.string, "}};\n").unwrap();
+        }
+
+        for field in &self.draw_shader_def.fields {
+            match field.kind {
+                DrawShaderFieldKind::Geometry {..} |
+                DrawShaderFieldKind::Instance {..} |
+                DrawShaderFieldKind::Varying {..} => self.generate_private_decl(&DisplayDsIdent(field.ident).to_string(), field.ty_expr.ty.borrow().as_ref().unwrap()),
+                _ => ()
+            }
+        }
+
+        self.generate_shader_body(&self.draw_shader_def.vertex_fns.borrow(), &self.draw_shader_def.vertex_structs.borrow());
+
+        writeln!(self.string, "@vertex").unwrap();
+        if packed_geometries_slots + packed_instances_slots > 0 {
+            writeln!(self.string, "fn vertex_main(input: VertexInput) -> Varyings {{").unwrap();
+        }
+        else {
+            writeln!(self.string, "fn vertex_main() -> Varyings {{").unwrap();
+        }
+        self.generate_uniform_block_unpack();
+        self.generate_live_unpack();
+
+        let mut slot = 0;
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Geometry {..} = field.kind {
+                slot = self.generate_var_unpack(field, "input.packed_geometry", packed_geometries_slots, slot);
+            }
+        }
+        let mut slot = 0;
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Instance {..} = field.kind {
+                slot = self.generate_var_unpack(field, "input.packed_instance", packed_instances_slots, slot);
+            }
+        }
+
+        let vertex_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(vertex))).unwrap();
+        writeln!(self.string, "\n    var varyings: Varyings;").unwrap();
+        writeln!(self.string, "    varyings.position = {}();", DisplayFnName(vertex_def.fn_ptr, vertex_def.ident)).unwrap();
+        let mut slot = 0;
+        for field in &self.draw_shader_def.fields {
+            if is_varying(&field.kind) {
+                slot = self.generate_var_pack(field, "varyings.packed_varying", packed_varyings_slots, slot);
+            }
+        }
+        writeln!(self.string, "    return varyings;").unwrap();
+        writeln!(self.string, "}}").unwrap();
+    }
+
+    fn generate_pixel_shader(&mut self) {
+        let packed_varyings_slots = self.compute_packed_slots(is_varying);
+
+        writeln!(self.string, "diagnostic(off, derivative_uniformity);\n").unwrap();
+        self.generate_decls(Some(packed_varyings_slots));
+
+        for field in &self.draw_shader_def.fields {
+            if is_varying(&field.kind) {
+                self.generate_private_decl(&DisplayDsIdent(field.ident).to_string(), field.ty_expr.ty.borrow().as_ref().unwrap());
+            }
+        }
+
+        self.generate_shader_body(&self.draw_shader_def.pixel_fns.borrow(), &self.draw_shader_def.pixel_structs.borrow());
+
+        writeln!(self.string, "@fragment").unwrap();
+        writeln!(self.string, "fn pixel_main(varyings: Varyings) -> @location(0) vec4<f32> {{").unwrap();
+        self.generate_uniform_block_unpack();
+        self.generate_live_unpack();
+
+        let mut slot = 0;
+        for field in &self.draw_shader_def.fields {
+            if is_varying(&field.kind) {
+                slot = self.generate_var_unpack(field, "varyings.packed_varying", packed_varyings_slots, slot);
+            }
+        }
+
+        let pixel_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(pixel))).unwrap();
+        writeln!(self.string, "\n    return {}();", DisplayFnName(pixel_def.fn_ptr, pixel_def.ident)).unwrap();
+        writeln!(self.string, "}}").unwrap();
+    }
+
+    fn generate_compute_shader(&mut self) {
+        let mut binding = self.generate_decls(None);
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Buffer {..} = field.kind {
+                if let Some(Ty::Array {elem_ty, ..}) = field.ty_expr.ty.borrow().as_ref() {
+                    write!(self.string, "@group(0) @binding({}) var<storage, read_write> {}: array<", binding, DisplayDsIdent(field.ident)).unwrap();
+                    write_ty(self.string, elem_ty);
+                    writeln!(self.string, ">;").unwrap();
+                    binding += 1;
+                }
+            }
+        }
+        writeln!(self.string).unwrap();
+
+        self.generate_shader_body(&self.draw_shader_def.compute_fns.borrow(), &self.draw_shader_def.compute_structs.borrow());
+
+        let [x, y, z] = self.draw_shader_def.workgroup_size.unwrap();
+        writeln!(self.string, "@compute @workgroup_size({}, {}, {})", x, y, z).unwrap();
+        writeln!(self.string, "fn compute_main(@builtin(global_invocation_id) id: vec3<u32>) {{").unwrap();
+        self.generate_uniform_block_unpack();
+        self.generate_live_unpack();
+        let compute_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(compute))).unwrap();
+        writeln!(self.string, "    {}(vec3<i32>(id));", DisplayFnName(compute_def.fn_ptr, compute_def.ident)).unwrap();
+        writeln!(self.string, "}}").unwrap();
+    }
+
+    // returns the next free binding
+    fn generate_decls(&mut self, packed_varyings_slots: Option<usize>) -> usize {
+        let mut binding = 0;
+        for (ident, vec) in self.draw_shader_def.fields_as_uniform_blocks() {
+            let mut slots = 0;
+            for (index, _) in &vec {
+                slots += self.draw_shader_def.fields[*index].ty_expr.ty.borrow().as_ref().unwrap().slots();
+            }
+            self.generate_table_decl(&mut binding, &format!("{}_table", ident), slots);
+            for (index, _) in vec {
+                let field = &self.draw_shader_def.fields[index];
+                self.generate_private_decl(&DisplayDsIdent(field.ident).to_string(), field.ty_expr.ty.borrow().as_ref().unwrap());
+            }
+        }
+
+        let live_slots = self.draw_shader_def.all_live_refs.borrow().values().map( | ty | ty.slots()).sum();
+        self.generate_table_decl(&mut binding, "live_table", live_slots);
+        for (live_ref, ty) in self.draw_shader_def.all_live_refs.borrow().iter() {
+            self.generate_private_decl(&live_ref.to_string(), ty);
+        }
+        self.generate_table_decl(&mut binding, "const_table", self.const_table.table.len());
+
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Texture {..} = field.kind {
+                write!(self.string, "@group(0) @binding({}) var {}: ", binding, DisplayDsIdent(field.ident)).unwrap();
+                write_ty(self.string, field.ty_expr.ty.borrow().as_ref().unwrap());
+                writeln!(self.string, ";").unwrap();
+                binding += 1;
+            }
+        }
+        writeln!(self.string, "@group(0) @binding({}) var default_sampler: sampler;\n", binding).unwrap();
+
+        if let Some(packed_varyings_slots) = packed_varyings_slots {
+            writeln!(self.string, "struct Varyings {{").unwrap();
+            writeln!(self.string, "    @builtin(position) position: vec4<f32>,").unwrap();
+            self.generate_packed_var_decls(0, "packed_varying", packed_varyings_slots);
+            writeln!(self.string, "}};\n").unwrap();
+        }
+        binding + 1
+    }
+
+    fn generate_table_decl(&mut self, binding: &mut usize, name: &str, slots: usize) {
+        if slots > 0 {
+            writeln!(self.string, "@group(0) @binding({}) var<uniform> {}: array<vec4<f32>, {}>;", binding, name, slots.div_ceil(4)).unwrap();
+            *binding += 1;
+        }
+    }
+
+    fn generate_private_decl(&mut self, name: &str, ty: &Ty) {
+        write!(self.string, "var<private> {}: ", name).unwrap();
+        write_ty(self.string, ty);
+        writeln!(self.string, ";").unwrap();
+    }
+
+    fn generate_packed_var_decls(&mut self, mut location: usize, packed_var_name: &str, mut packed_vars_size: usize) -> usize {
+        let mut packed_var_index = 0;
+        while packed_vars_size > 0 {
+            let packed_var_size = packed_vars_size.min(4);
+            writeln!(
+                self.string,
+                "    @location({}) {}_{}: {},",
+                location,
+                packed_var_name,
+                packed_var_index,
+                match packed_var_size {
+                    1 => "f32",
+                    2 => "vec2<f32>",
+                    3 => "vec3<f32>",
+                    _ => "vec4<f32>",
+                }
+            ).unwrap();
+            packed_vars_size -= packed_var_size;
+            packed_var_index += 1;
+            location += 1;
+        }
+        location
+    }
+
+    fn compute_packed_slots(&self, filter: fn(&DrawShaderFieldKind) -> bool) -> usize {
+        self.draw_shader_def.fields.iter()
+            .filter( | field | filter(&field.kind))
+            .map( | field | field.ty_expr.ty.borrow().as_ref().unwrap().slots())
+            .sum()
+    }
+
+    // packed inputs are written one slot at a time, as WGSL can't store through a swizzle
+    fn generate_var_pack(&mut self, field: &DrawShaderFieldDef, packed_var_name: &str, packed_vars_size: usize, slot: usize) -> usize {
+        let ty = field.ty_expr.ty.borrow();
+        let ty = ty.as_ref().unwrap();
+        for index in 0..ty.slots() {
+            write!(self.string, "    ").unwrap();
+            write_packed_slot(self.string, packed_var_name, packed_vars_size, slot + index);
+            write!(self.string, " = ").unwrap();
+            write_field_slot(self.string, &DisplayDsIdent(field.ident).to_string(), ty, index);
+            writeln!(self.string, ";").unwrap();
+        }
+        slot + ty.slots()
+    }
+
+    fn generate_var_unpack(&mut self, field: &DrawShaderFieldDef, packed_var_name: &str, packed_vars_size: usize, slot: usize) -> usize {
+        let ty = field.ty_expr.ty.borrow();
+        let ty = ty.as_ref().unwrap();
+        for index in 0..ty.slots() {
+            write!(self.string, "    ").unwrap();
+            write_field_slot(self.string, &DisplayDsIdent(field.ident).to_string(), ty, index);
+            write!(self.string, " = ").unwrap();
+            write_packed_slot(self.string, packed_var_name, packed_vars_size, slot + index);
+            writeln!(self.string, ";").unwrap();
+        }
+        slot + ty.slots()
+    }
+
+    fn generate_uniform_block_unpack(&mut self) {
+        for (ident, vec) in self.draw_shader_def.fields_as_uniform_blocks() {
+            let table = format!("{}_table", ident);
+            let mut slots = 0;
+            for (index, _) in vec {
+                let field = &self.draw_shader_def.fields[index];
+                let ty = field.ty_expr.ty.borrow();
+                write!(self.string, "    {} = ", DisplayDsIdent(field.ident)).unwrap();
+                write_table_unpack(self.string, ty.as_ref().unwrap(), &table, slots);
+                writeln!(self.string, ";").unwrap();
+                slots += ty.as_ref().unwrap().slots();
+            }
+        }
+    }
+
+    fn generate_live_unpack(&mut self) {
+        let mut slots = 0;
+        for (live_ref, ty) in self.draw_shader_def.all_live_refs.borrow().iter() {
+            write!(self.string, "    {} = ", live_ref).unwrap();
+            write_table_unpack(self.string, ty, "live_table", slots);
+            writeln!(self.string, ";").unwrap();
+            slots += ty.slots();
+        }
+    }
+
+    fn generate_shader_body(&mut self, fn_deps: &[FnPtr], struct_deps: &[StructPtr]) {
+        let mut all_constructor_fns = BTreeSet::new();
+        // LiveId orders by interned name, so a set lookup of a not yet interned id can
+        // match anything; keep the builtins in a plain list
+        let mut all_builtins = Vec::new();
+        for fn_ptr in fn_deps {
+            let fn_def = self.shader_registry.all_fns.get(fn_ptr).unwrap();
+            all_constructor_fns.extend(fn_def.constructor_fn_deps.borrow().as_ref().unwrap().iter().cloned());
+            for builtin in fn_def.builtin_deps.borrow().as_ref().unwrap() {
+                if !all_builtins.contains(builtin) {
+                    all_builtins.push(*builtin);
+                }
+            }
+        }
+
+        for struct_ptr in struct_deps.iter().rev() {
+            let struct_def = self.shader_registry.structs.get(struct_ptr).unwrap();
+            writeln!(self.string, "struct {} {{", struct_ptr).unwrap();
+            for field in &struct_def.fields {
+                write!(self.string, "    {}: ", DisplayStructField(field.ident)).unwrap();
+                write_ty(self.string, field.ty_expr.ty.borrow().as_ref().unwrap());
+                writeln!(self.string, ",").unwrap();
+            }
+            writeln!(self.string, "}};\n").unwrap();
+        }
+
+        self.generate_builtin_helpers(&all_builtins);
+        for (ty_lit, param_tys) in all_constructor_fns {
+            generate_cons_fn(self.string, ty_lit, &param_tys);
+        }
+
+        for fn_ptr in fn_deps.iter().rev() {
+            let const_table_offset = self.const_table.offsets.get(fn_ptr).cloned();
+            let fn_def = self.shader_registry.all_fns.get(fn_ptr).unwrap();
+            if fn_def.has_closure_args() {
+                for call_ptr in fn_deps.iter().rev() {
+                    // any function that depends on us, will have the closures we need
+                    let call_def = self.shader_registry.all_fns.get(call_ptr).unwrap();
+                    if call_def.callees.borrow().as_ref().unwrap().contains(fn_ptr) {
+                        self.generate_fn_def_with_all_closures(fn_def, call_def, const_table_offset);
+                    }
+                }
+                continue
+            }
+            let name = DisplayFnName(fn_def.fn_ptr, fn_def.ident).to_string();
+            let mut generator = self.fn_generator(fn_def, None, const_table_offset);
+            generator.generate_fn_header(&name, &fn_def.params, fn_def.return_ty.borrow().as_ref().unwrap());
+            generator.generate_fn_block(&fn_def.block);
+        }
+    }
+
+    fn generate_fn_def_with_all_closures(&mut self, fn_def: &FnDef, call_def: &FnDef, const_table_offset: Option<usize>) {
+        let closure_sites = call_def.closure_sites.borrow();
+        let closure_sites = closure_sites.as_ref().unwrap();
+        for (closure_def_index, closure_def) in call_def.closure_defs.iter().enumerate() {
+            let closure_def_index = ClosureDefIndex(closure_def_index);
+            for site in closure_sites.iter().filter( | site | site.call_to == fn_def.fn_ptr) {
+                for closure_site_arg in &site.closure_args {
+                    if closure_site_arg.closure_def_index == closure_def_index {
+                        self.generate_closure_def(closure_def, *closure_site_arg, fn_def, call_def, const_table_offset);
+                    }
+                }
+            }
+        }
+
+        for (site_index, closure_site) in closure_sites.iter().enumerate() {
+            if closure_site.call_to != fn_def.fn_ptr {
+                continue
+            }
+            let closure_site_info = ClosureSiteInfo {
+                site_index,
+                closure_site,
+                call_ptr: call_def.fn_ptr,
+            };
+            let name = DisplayFnNameWithClosureArgs(site_index, call_def.fn_ptr, fn_def.ident).to_string();
+            let mut generator = self.fn_generator(fn_def, Some(closure_site_info), const_table_offset);
+            generator.generate_fn_header(&name, &fn_def.params, fn_def.return_ty.borrow().as_ref().unwrap());
+            generator.generate_fn_block(&fn_def.block);
+        }
+    }
+
+    fn generate_closure_def(&mut self, closure_def: &ClosureDef, closure_site_arg: ClosureSiteArg, fn_def: &FnDef, call_def: &FnDef, const_table_offset: Option<usize>) {
+        let fn_param = &fn_def.params[closure_site_arg.param_index];
+        let (decl_params, return_ty) = if let TyExprKind::ClosureDecl {params, return_ty, ..} = &fn_param.ty_expr.kind {
+            (params, return_ty)
+        }
+        else {
+            panic!()
+        };
+        // the closure params carry the types and inout-ness of the decl, but the idents of the def
+        let params: Vec<Param> = decl_params.iter().zip(closure_def.params.iter()).map( | (decl_param, def_param) | Param {
+            span: def_param.span,
+            is_inout: decl_param.is_inout,
+            ident: def_param.ident,
+            shadow: def_param.shadow.clone(),
+            ty_expr: decl_param.ty_expr.clone(),
+        }).collect();
+
+        let name = DisplayClosureName(call_def.fn_ptr, closure_site_arg.closure_def_index).to_string();
+        // the closure body was analysed in the scope of the calling fn
+        let mut generator = self.fn_generator(call_def, None, const_table_offset);
+        generator.closed_over_syms = closure_def.closed_over_syms.borrow().clone().unwrap();
+        generator.generate_fn_header(&name, &params, return_ty.borrow().as_ref().unwrap());
+        match &closure_def.kind {
+            ClosureDefKind::Expr(expr) => {
+                writeln!(generator.string, "{{").unwrap();
+                generator.indent_level = 1;
+                generator.generate_param_copies(&params);
+                write!(generator.string, "    return ").unwrap();
+                generator.generate_expr(expr);
+                writeln!(generator.string, ";\n}}\n").unwrap();
+            }
+            ClosureDefKind::Block(block) => {
+                generator.generate_fn_block(block);
+            }
+        }
+    }
+
+    fn fn_generator<'b>(&'b mut self, fn_def: &'b FnDef, closure_site_info: Option<ClosureSiteInfo<'b >>, const_table_offset: Option<usize>) -> FnGenerator<'b> {
+        FnGenerator {
+            fn_def,
+            closure_site_info,
+            shader_registry: self.shader_registry,
+            const_table_offset,
+            stage: self.stage,
+            closed_over_syms: Vec::new(),
+            pointer_vars: Vec::new(),
+            indent_level: 0,
+            temp_index: 0,
+            string: self.string,
+        }
+    }
+
+    fn generate_builtin_helpers(&mut self, builtins: &[Ident]) {
+        let sample = if self.stage != ShaderStage::Pixel {"textureSampleLevel(tex, default_sampler, pos, 0.0)"} else {"textureSample(tex, default_sampler, pos)"};
+        if builtins.contains(&Ident(live_id!(sample2d))) {
+            writeln!(self.string, "fn sample2d(tex: texture_2d<f32>, pos: vec2<f32>) -> vec4<f32> {{return {};}}", sample).unwrap();
+        }
+        if builtins.contains(&Ident(live_id!(sample2d_rt))) {
+            writeln!(self.string, "fn sample2d_rt(tex: texture_2d<f32>, pos: vec2<f32>) -> vec4<f32> {{return {};}}", sample).unwrap();
+        }
+        if builtins.contains(&Ident(live_id!(sample2dOES))) {
+            writeln!(self.string, "fn sample2dOES(tex: texture_external, pos: vec2<f32>) -> vec4<f32> {{return textureSampleBaseClampToEdge(tex, default_sampler, pos);}}").unwrap();
+        }
+        if builtins.contains(&Ident(live_id!(mod))) {
+            for ty in ["f32", "vec2<f32>", "vec3<f32>", "vec4<f32>"] {
+                writeln!(self.string, "fn mod_{}(x: {}, y: {}) -> {} {{return x - y * floor(x / y);}}", ty.replace("<f32>", ""), ty, ty, ty).unwrap();
+            }
+        }
+        if builtins.contains(&Ident(live_id!(matrixCompMult))) {
+            for size in 2..=4 {
+                write!(self.string, "fn matrixCompMult_mat{0}(a: mat{0}x{0}<f32>, b: mat{0}x{0}<f32>) -> mat{0}x{0}<f32> {{return mat{0}x{0}<f32>(", size).unwrap();
+                for column in 0..size {
+                    write!(self.string, "{}a[{1}] * b[{1}]", if column == 0 {""} else {", "}, column).unwrap();
+                }
+                writeln!(self.string, ");}}").unwrap();
+            }
+        }
+        if builtins.contains(&Ident(live_id!(inverse))) {
+            write!(self.string, "{}", INVERSE_MAT4).unwrap();
+        }
+        writeln!(self.string).unwrap();
+    }
+}
+
+fn is_varying(kind: &DrawShaderFieldKind) -> bool {
+    match kind {
+        DrawShaderFieldKind::Geometry {is_used_in_pixel_shader, ..} |
+        DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} => is_used_in_pixel_shader.get(),
+        DrawShaderFieldKind::Varying {..} => true,
+        _ => false
+    }
+}
+
+fn write_packed_slot(string: &mut String, packed_var_name: &str, packed_vars_size: usize, slot: usize) {
+    let packed_var_index = slot >> 2;
+    write!(string, "{}_{}", packed_var_name, packed_var_index).unwrap();
+    if packed_vars_size - (packed_var_index << 2) > 1 {
+        write!(string, ".{}", Swizzle::from_range(slot & 3, (slot & 3) + 1)).unwrap();
+    }
+}
+
+fn write_field_slot(string: &mut String, name: &str, ty: &Ty, slot: usize) {
+    match ty {
+        Ty::Mat2 => write!(string, "{}[{}][{}]", name, slot / 2, slot % 2),
+        Ty::Mat3 => write!(string, "{}[{}][{}]", name, slot / 3, slot % 3),
+        Ty::Mat4 => write!(string, "{}[{}][{}]", name, slot / 4, slot % 4),
+        _ if ty.slots() > 1 => write!(string, "{}.{}", name, Swizzle::from_range(slot, slot + 1)),
+        _ => write!(string, "{}", name),
+    }.unwrap();
+}
+
+fn write_table_unpack(string: &mut String, ty: &Ty, table: &str, slot: usize) {
+    let item = | index: usize | format!("{}[{}].{}", table, (slot + index) >> 2, Swizzle::from_range((slot + index) & 3, ((slot + index) & 3) + 1));
+    let items = | count: usize, map: &dyn Fn(String) -> String | (0..count).map( | index | map(item(index))).collect::<Vec<_ >>().join(", ");
+    match ty {
+        Ty::Bool => write!(string, "{} > 0.5", item(0)),
+        Ty::Int => write!(string, "i32({})", item(0)),
+        Ty::Float | Ty::Enum(_) => write!(string, "{}", item(0)),
+        Ty::Bvec2 | Ty::Bvec3 | Ty::Bvec4 => {
+            write_ty(string, ty);
+            write!(string, "({})", items(ty.slots(), &| item | format!("{} > 0.5", item)))
+        }
+        Ty::Ivec2 | Ty::Ivec3 | Ty::Ivec4 => {
+            write_ty(string, ty);
+            write!(string, "({})", items(ty.slots(), &| item | format!("i32({})", item)))
+        }
+        Ty::Vec2 | Ty::Vec3 | Ty::Vec4 | Ty::Mat2 | Ty::Mat3 | Ty::Mat4 => {
+            write_ty(string, ty);
+            write!(string, "({})", items(ty.slots(), &| item | item))
+        }
+        _ => panic!("unexpected as initializeable type {:?}", ty),
+    }.unwrap()
+}
+
+fn write_ty(string: &mut String, ty: &Ty) {
+    match ty {
+        Ty::Array {elem_ty, len} => {
+            write!(string, "array<").unwrap();
+            write_ty(string, elem_ty);
+            write!(string, ", {}>", len).unwrap();
+        }
+        Ty::Struct(struct_ptr) => write!(string, "{}", struct_ptr).unwrap(),
+        Ty::Enum(_) => write!(string, "f32").unwrap(),
+        Ty::Texture2D => write!(string, "texture_2d<f32>").unwrap(),
+        Ty::TextureOES => write!(string, "texture_external").unwrap(),
+        _ => write_ty_lit(string, ty.maybe_ty_lit().unwrap()),
+    }
+}
+
+fn write_ty_lit(string: &mut String, ty_lit: TyLit) {
+    write!(
+        string,
+        "{}",
+        match ty_lit {
+            TyLit::Bool => "bool",
+            TyLit::Int => "i32",
+            TyLit::Float => "f32",
+            TyLit::Bvec2 => "vec2<bool>",
+            TyLit::Bvec3 => "vec3<bool>",
+            TyLit::Bvec4 => "vec4<bool>",
+            TyLit::Ivec2 => "vec2<i32>",
+            TyLit::Ivec3 => "vec3<i32>",
+            TyLit::Ivec4 => "vec4<i32>",
+            TyLit::Vec2 => "vec2<f32>",
+            TyLit::Vec3 => "vec3<f32>",
+            TyLit::Vec4 => "vec4<f32>",
+            TyLit::Mat2 => "mat2x2<f32>",
+            TyLit::Mat3 => "mat3x3<f32>",
+            TyLit::Mat4 => "mat4x4<f32>",
+            TyLit::Texture2D => "texture_2d<f32>",
+            TyLit::TextureOES => "texture_external",
+        }
+    ).unwrap();
+}
+
+// the scalar type a value converts to inside a constructor of this type
+fn scalar_ty_lit(ty: &Ty) -> Option<TyLit> {
+    match ty {
+        Ty::Bool | Ty::Bvec2 | Ty::Bvec3 | Ty::Bvec4 => Some(TyLit::Bool),
+        Ty::Int | Ty::Ivec2 | Ty::Ivec3 | Ty::Ivec4 => Some(TyLit::Int),
+        Ty::Float | Ty::Vec2 | Ty::Vec3 | Ty::Vec4 | Ty::Mat2 | Ty::Mat3 | Ty::Mat4 | Ty::Enum(_) => Some(TyLit::Float),
+        _ => None
+    }
+}
+
+fn matrix_size(ty: &Ty) -> Option<usize> {
+    match ty {
+        Ty::Mat2 => Some(2),
+        Ty::Mat3 => Some(3),
+        Ty::Mat4 => Some(4),
+        _ => None
+    }
+}
+
+fn is_float_vector(ty: &Ty) -> bool {
+    matches!(ty, Ty::Vec2 | Ty::Vec3 | Ty::Vec4)
+}
+
+fn cons_fn_name(ty_lit: TyLit, param_tys: &[Ty]) -> String {
+    let mut cons_name = format!("consfn_{}", ty_lit);
+    for param_ty in param_tys {
+        write!(cons_name, "_{}", param_ty).unwrap();
+    }
+    cons_name
+}
+
+// constructors that WGSL can't express directly: scalar to matrix, matrix resizes,
+// mixed matrix columns and anything that drops trailing components
+fn needs_cons_fn(ty_lit: TyLit, param_tys: &[Ty]) -> bool {
+    let ty = ty_lit.to_ty();
+    let slots: usize = param_tys.iter().map( | ty | ty.slots()).sum();
+    if let Some(size) = matrix_size(&ty) {
+        let all_scalars = param_tys.iter().all( | ty | *ty == Ty::Float) && param_tys.len() == size * size;
+        let all_columns = param_tys.iter().all( | ty | ty.slots() == size && is_float_vector(ty)) && param_tys.len() == size;
+        return !(all_scalars || all_columns || (param_tys.len() == 1 && param_tys[0] == ty))
+    }
+    param_tys.len() > 1 && slots > ty.slots() || param_tys.len() == 1 && param_tys[0].slots() > ty.slots()
+}
+
+fn generate_cons_fn(string: &mut String, ty_lit: TyLit, param_tys: &[Ty]) {
+    if !needs_cons_fn(ty_lit, param_tys) {
+        return
+    }
+    let ty = ty_lit.to_ty();
+    let scalar = scalar_ty_lit(&ty).unwrap();
+    write!(string, "fn {}(", cons_fn_name(ty_lit, param_tys)).unwrap();
+    for (index, param_ty) in param_tys.iter().enumerate() {
+        write!(string, "{}{}: ", if index == 0 {""} else {", "}, DisplaConstructorArg(index)).unwrap();
+        write_ty(string, param_ty);
+    }
+    write!(string, ") -> ").unwrap();
+    write_ty_lit(string, ty_lit);
+    write!(string, " {{\n    return ").unwrap();
+    write_ty_lit(string, ty_lit);
+    write!(string, "(").unwrap();
+
+    let mut components = Vec::new();
+    match (matrix_size(&ty), param_tys) {
+        (Some(dst_size), [Ty::Bool | Ty::Int | Ty::Float]) => {
+            for column in 0..dst_size {
+                for row in 0..dst_size {
+                    components.push(if column == row {"x0".to_string()} else {"0.0".to_string()});
+                }
+            }
+        }
+        (Some(dst_size), [src_ty]) if matrix_size(src_ty).is_some() => {
+            let src_size = matrix_size(src_ty).unwrap();
+            for column in 0..dst_size {
+                for row in 0..dst_size {
+                    components.push(if column < src_size && row < src_size {
+                        format!("x0[{}][{}]", column, row)
+                    }
+                    else {
+                        (if column == row {"1.0"} else {"0.0"}).to_string()
+                    });
+                }
+            }
+        }
+        _ => {
+            for (index, param_ty) in param_tys.iter().enumerate() {
+                if let Some(size) = matrix_size(param_ty) {
+                    for slot in 0..size * size {
+                        components.push(format!("x{}[{}][{}]", index, slot / size, slot % size));
+                    }
+                }
+                else if param_ty.slots() == 1 {
+                    components.push(format!("x{}", index));
+                }
+                else {
+                    for slot in 0..param_ty.slots() {
+                        components.push(format!("x{}[{}]", index, slot));
+                    }
+                }
+            }
+            components.truncate(ty.slots());
+        }
+    }
+    for (index, component) in components.iter().enumerate() {
+        write!(string, "{}", if index == 0 {""} else {", "}).unwrap();
+        write_ty_lit(string, scalar);
+        write!(string, "({})", component).unwrap();
+    }
+    writeln!(string, ");\n}}\n").unwrap();
+}
+
+struct FnGenerator<'a> {
+    fn_def: &'a FnDef,
+    closure_site_info: Option<ClosureSiteInfo<'a >>,
+    shader_registry: &'a ShaderRegistry,
+    const_table_offset: Option<usize>,
+    stage: ShaderStage,
+    closed_over_syms: Vec<Sym>,
+    // inout params are passed as pointers and need a deref on every use
+    pointer_vars: Vec<(Ident, ScopeSymShadow)>,
+    indent_level: usize,
+    temp_index: usize,
+    string: &'a mut String,
+}
+
+impl<'a> FnGenerator<'a> {
+    fn generate_fn_header(&mut self, name: &str, params: &[Param], return_ty: &Ty) {
+        write!(self.string, "fn {}(", name).unwrap();
+        let mut sep = "";
+        for param in params {
+            let ty = param.ty_expr.ty.borrow();
+            let ty = ty.as_ref().unwrap();
+            if param.shadow.get().is_none() || !is_value_ty(ty) {
+                continue
+            }
+            let var_name = DisplayVarName(param.ident, param.shadow.get().unwrap());
+            if param.is_inout {
+                write!(self.string, "{}{}: ptr<function, ", sep, var_name).unwrap();
+                write_ty(self.string, ty);
+                write!(self.string, ">").unwrap();
+                self.pointer_vars.push((param.ident, param.shadow.get().unwrap()));
+            }
+            else if is_copyable_ty(ty) {
+                write!(self.string, "{}in_{}: ", sep, var_name).unwrap();
+                write_ty(self.string, ty);
+            }
+            else {
+                write!(self.string, "{}{}: ", sep, var_name).unwrap();
+                write_ty(self.string, ty);
+            }
+            sep = ", ";
+        }
+        if let Some(closure_site_info) = &self.closure_site_info {
+            for sym in &closure_site_info.closure_site.all_closed_over {
+                if is_value_ty(&sym.ty) {
+                    write!(self.string, "{}{}: ", sep, DisplayClosedOverArg(sym.ident, sym.shadow)).unwrap();
+                    write_ty(self.string, &sym.ty);
+                    sep = ", ";
+                }
+            }
+        }
+        for sym in &self.closed_over_syms {
+            if is_value_ty(&sym.ty) {
+                write!(self.string, "{}{}{}: ", sep, if is_copyable_ty(&sym.ty) {"in_"} else {""}, DisplayVarName(sym.ident, sym.shadow)).unwrap();
+                write_ty(self.string, &sym.ty);
+                sep = ", ";
+            }
+        }
+        write!(self.string, ")").unwrap();
+        if *return_ty != Ty::Void {
+            write!(self.string, " -> ").unwrap();
+            write_ty(self.string, return_ty);
+        }
+        write!(self.string, " ").unwrap();
+    }
+
+    // WGSL params are immutable, so value params get copied into a var first
+    fn generate_param_copies(&mut self, params: &[Param]) {
+        let mut copies = Vec::new();
+        for param in params {
+            let ty = param.ty_expr.ty.borrow();
+            if let Some(shadow) = param.shadow.get() {
+                if !param.is_inout && is_copyable_ty(ty.as_ref().unwrap()) {
+                    copies.push(DisplayVarName(param.ident, shadow).to_string());
+                }
+            }
+        }
+        for sym in &self.closed_over_syms {
+            if is_copyable_ty(&sym.ty) {
+                copies.push(DisplayVarName(sym.ident, sym.shadow).to_string());
+            }
+        }
+        for name in copies {
+            self.write_indent();
+            writeln!(self.string, "var {0} = in_{0};", name).unwrap();
+        }
+    }
+
+    fn generate_fn_block(&mut self, block: &Block) {
+        writeln!(self.string, "{{").unwrap();
+        self.indent_level = 1;
+        let params = &self.fn_def.params;
+        if self.closed_over_syms.is_empty() {
+            self.generate_param_copies(params);
+        }
+        else {
+            // closure bodies take their params from the closure def
+            self.generate_param_copies(&[]);
+        }
+        for stmt in &block.stmts {
+            self.generate_stmt(stmt);
+        }
+        writeln!(self.string, "}}\n").unwrap();
+    }
+
+    fn generate_block(&mut self, block: &Block) {
+        writeln!(self.string, "{{").unwrap();
+        self.indent_level += 1;
+        for stmt in &block.stmts {
+            self.generate_stmt(stmt);
+        }
+        self.indent_level -= 1;
+        self.write_indent();
+        write!(self.string, "}}").unwrap();
+    }
+
+    fn generate_stmt(&mut self, stmt: &Stmt) {
+        self.write_indent();
+        match *stmt {
+            Stmt::Break {..} => writeln!(self.string, "break;").unwrap(),
+            Stmt::Continue {..} => writeln!(self.string, "continue;").unwrap(),
+            Stmt::For {
+                ident,
+                ref from_expr,
+                ref to_expr,
+                ref step_expr,
+                ref block,
+                ..
+            } => self.generate_for_stmt(ident, from_expr, to_expr, step_expr, block),
+            Stmt::If {
+                ref expr,
+                ref block_if_true,
+                ref block_if_false,
+                ..
+            } => {
+                write!(self.string, "if (").unwrap();
+                self.generate_expr(expr);
+                write!(self.string, ") ").unwrap();
+                self.generate_block(block_if_true);
+                if let Some(block_if_false) = block_if_false {
+                    write!(self.string, " else ").unwrap();
+                    self.generate_block(block_if_false);
+                }
+                writeln!(self.string).unwrap();
+            }
+            Stmt::Match {
+                ref expr,
+                ref matches,
+                ..
+            } => {
+                // enums are floats, like in the GLSL backend
+                for (index, match_item) in matches.iter().enumerate() {
+                    if index != 0 {
+                        write!(self.string, " else ").unwrap();
+                    }
+                    write!(self.string, "if (abs(").unwrap();
+                    self.generate_expr(expr);
+                    write!(self.string, " - {}.0) < 0.5) ", match_item.enum_value.get().unwrap()).unwrap();
+                    self.generate_block(&match_item.block);
+                }
+                writeln!(self.string).unwrap();
+            }
+            Stmt::Let {
+                ref ty,
+                ident,
+                ref expr,
+                ref shadow,
+                ..
+            } => {
+                write!(self.string, "var {}: ", DisplayVarName(ident, shadow.get().unwrap())).unwrap();
+                write_ty(self.string, ty.borrow().as_ref().unwrap());
+                if let Some(expr) = expr {
+                    write!(self.string, " = ").unwrap();
+                    self.generate_expr(expr);
+                }
+                writeln!(self.string, ";").unwrap();
+            }
+            Stmt::Return {ref expr, ..} => {
+                write!(self.string, "return").unwrap();
+                if let Some(expr) = expr {
+                    write!(self.string, " ").unwrap();
+                    self.generate_expr(expr);
+                }
+                writeln!(self.string, ";").unwrap();
+            }
+            Stmt::Block {ref block, ..} => {
+                self.generate_block(block);
+                writeln!(self.string).unwrap();
+            }
+            Stmt::Expr {ref expr, ..} => self.generate_expr_stmt(expr),
+        }
+    }
+
+    fn generate_for_stmt(&mut self, ident: Ident, from_expr: &Expr, to_expr: &Expr, step_expr: &Option<Expr>, block: &Block) {
+        let const_int = | expr: &Expr | expr.const_val.borrow().as_ref().unwrap().as_ref().unwrap().to_int().unwrap();
+        let from = const_int(from_expr);
+        let to = const_int(to_expr);
+        let step = if let Some(step_expr) = step_expr {
+            const_int(step_expr)
+        }
+        else if from < to {
+            1
+        }
+        else {
+            -1
+        };
+        write!(
+            self.string,
+            "for (var {0}: i32 = {1}; {0} {2} {3}; {0} {4} {5}) ",
+            DisplayLoopVar(ident),
+            if from <= to {from} else {from - 1},
+            if from <= to {"<"} else {">="},
+            to,
+            if step > 0 {"+="} else {"-="},
+            step.abs()
+        ).unwrap();
+        self.generate_block(block);
+        writeln!(self.string).unwrap();
+    }
+
+    fn generate_expr_stmt(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Bin {op, left_expr, right_expr, ..} if is_assign_op(*op) => {
+                self.generate_assign(*op, left_expr, right_expr);
+            }
+            ExprKind::MethodCall {..} | ExprKind::PlainCall {..} if *expr.ty.borrow() == Some(Ty::Void) => {
+                self.generate_expr(expr);
+                writeln!(self.string, ";").unwrap();
+            }
+            _ => {
+                write!(self.string, "_ = ").unwrap();
+                self.generate_expr(expr);
+                writeln!(self.string, ";").unwrap();
+            }
+        }
+    }
+
+    fn generate_assign(&mut self, op: BinOp, left_expr: &Expr, right_expr: &Expr) {
+        // assignments aren't expressions in WGSL, so a chain is split up from the right
+        if let ExprKind::Bin {op: inner_op, left_expr: inner_left_expr, right_expr: inner_right_expr, ..} = &right_expr.kind {
+            if is_assign_op(*inner_op) {
+                self.generate_assign(*inner_op, inner_left_expr, inner_right_expr);
+                self.write_indent();
+                return self.generate_assign(op, left_expr, inner_left_expr);
+            }
+        }
+        if let ExprKind::Field {expr: vec_expr, field_ident, ..} = &left_expr.kind {
+            let swizzle = match vec_expr.ty.borrow().as_ref() {
+                Some(Ty::DrawShader(_)) | Some(Ty::Struct(_)) => None,
+                _ => Swizzle::parse(*field_ident).filter( | swizzle | swizzle.len() > 1)
+            };
+            if let Some(swizzle) = swizzle {
+                // stores through a swizzle become one store per component
+                let temp = format!("swizzle_{}", self.temp_index);
+                self.temp_index += 1;
+                writeln!(self.string, "{{").unwrap();
+                self.indent_level += 1;
+                self.write_indent();
+                write!(self.string, "let {} = ", temp).unwrap();
+                self.generate_expr(right_expr);
+                writeln!(self.string, ";").unwrap();
+                let rhs_is_scalar = right_expr.ty.borrow().as_ref().unwrap().slots() == 1;
+                for (index, component) in swizzle.iter().enumerate() {
+                    self.write_indent();
+                    self.generate_expr(vec_expr);
+                    write!(self.string, ".{} {} {}", Swizzle::from_range(*component, *component + 1), op, temp).unwrap();
+                    if !rhs_is_scalar {
+                        write!(self.string, ".{}", Swizzle::from_range(index, index + 1)).unwrap();
+                    }
+                    writeln!(self.string, ";").unwrap();
+                }
+                self.indent_level -= 1;
+                self.write_indent();
+                writeln!(self.string, "}}").unwrap();
+                return
+            }
+        }
+        self.generate_expr(left_expr);
+        write!(self.string, " {} ", op).unwrap();
+        self.generate_expr(right_expr);
+        writeln!(self.string, ";").unwrap();
+    }
+
+    fn generate_expr(&mut self, in_expr: &Expr) {
+        let write_const_table = | string: &mut String, index: usize | {
+            write!(string, "const_table[{}].{}", index >> 2, Swizzle::from_range(index & 3, (index & 3) + 1)).unwrap();
+        };
+        match (in_expr.const_val.borrow().as_ref(), in_expr.const_index.get()) {
+            (Some(Some(Val::Vec4(_))), Some(index)) if self.const_table_offset.is_some() => {
+                let index = index + self.const_table_offset.unwrap();
+                write!(self.string, "vec4<f32>(").unwrap();
+                for i in 0..4 {
+                    write!(self.string, "{}", if i == 0 {""} else {", "}).unwrap();
+                    write_const_table(self.string, index + i);
+                }
+                write!(self.string, ")").unwrap();
+            }
+            (Some(Some(Val::Float(_))), Some(index)) if self.const_table_offset.is_some() => {
+                write_const_table(self.string, index + self.const_table_offset.unwrap());
+            }
+            (Some(Some(Val::Vec4(val))), _) => write_vec4(self.string, *val),
+            (Some(Some(Val::Float(val))), _) => write!(self.string, "{}f", PrettyPrintedF32(*val)).unwrap(),
+            (Some(Some(Val::Int(val))), _) => write!(self.string, "{}i", val).unwrap(),
+            (Some(Some(Val::Bool(val))), _) => write!(self.string, "{}", val).unwrap(),
+            _ => match in_expr.kind {
+                ExprKind::Cond {
+                    ref expr,
+                    ref expr_if_true,
+                    ref expr_if_false,
+                    ..
+                } => {
+                    write!(self.string, "select(").unwrap();
+                    self.generate_expr(expr_if_false);
+                    write!(self.string, ", ").unwrap();
+                    self.generate_expr(expr_if_true);
+                    write!(self.string, ", ").unwrap();
+                    self.generate_expr(expr);
+                    write!(self.string, ")").unwrap();
+                }
+                ExprKind::Bin {
+                    op,
+                    ref left_expr,
+                    ref right_expr,
+                    ..
+                } => self.generate_bin_expr(op, left_expr, right_expr),
+                ExprKind::Un {op, ref expr, ..} => {
+                    write!(self.string, "{}(", op).unwrap();
+                    self.generate_expr(expr);
+                    write!(self.string, ")").unwrap();
+                }
+                ExprKind::Field {
+                    ref expr,
+                    field_ident,
+                    ..
+                } => match expr.ty.borrow().as_ref() {
+                    Some(Ty::DrawShader(_)) => {
+                        write!(self.string, "{}", DisplayDsIdent(field_ident)).unwrap();
+                    }
+                    Some(Ty::Struct(_)) => {
+                        self.generate_expr(expr);
+                        write!(self.string, ".{}", DisplayStructField(field_ident)).unwrap();
+                    }
+                    _ => {
+                        self.generate_expr(expr);
+                        write!(self.string, ".{}", field_ident).unwrap();
+                    }
+                },
+                ExprKind::Index {
+                    ref expr,
+                    ref index_expr,
+                    ..
+                } => {
+                    self.generate_expr(expr);
+                    write!(self.string, "[").unwrap();
+                    self.generate_expr(index_expr);
+                    write!(self.string, "]").unwrap();
+                }
+                ExprKind::MethodCall {
+                    ident,
+                    ref arg_exprs,
+                    ref closure_site_index,
+                    ..
+                } => self.generate_method_call_expr(ident, arg_exprs, closure_site_index),
+                ExprKind::PlainCall {
+                    fn_ptr,
+                    ref arg_exprs,
+                    ref closure_site_index,
+                    ref param_index,
+                    ..
+                } => {
+                    if param_index.get().is_some() {
+                        self.generate_closure_call_expr(arg_exprs, param_index.get().unwrap());
+                    }
+                    else {
+                        let fn_def = self.shader_registry.all_fns.get(&fn_ptr.unwrap()).unwrap();
+                        self.generate_call_body(fn_def, &fn_def.params, arg_exprs, closure_site_index);
+                    }
+                }
+                ExprKind::BuiltinCall {
+                    ident,
+                    ref arg_exprs,
+                    ..
+                } => self.generate_builtin_call_expr(ident, arg_exprs),
+                ExprKind::ClosureDef(_) => (),
+                ExprKind::ConsCall {
+                    ty_lit,
+                    ref arg_exprs,
+                    ..
+                } => self.generate_cons_call_expr(ty_lit, arg_exprs),
+                ExprKind::StructCons {
+                    struct_ptr,
+                    ref args,
+                    ..
+                } => {
+                    let struct_def = self.shader_registry.structs.get(&struct_ptr).unwrap();
+                    write!(self.string, "{}(", struct_ptr).unwrap();
+                    for (index, field) in struct_def.fields.iter().enumerate() {
+                        if index != 0 {
+                            write!(self.string, ", ").unwrap();
+                        }
+                        let arg = args.iter().find( | (ident, _) | field.ident == *ident).unwrap();
+                        self.generate_expr(&arg.1);
+                    }
+                    write!(self.string, ")").unwrap();
+                }
+                ExprKind::Var {ref kind, ..} => match kind.get().unwrap() {
+                    // only for loop counters are immutable locals
+                    VarKind::Local {ident, ..} => {
+                        write!(self.string, "{}", DisplayLoopVar(ident)).unwrap();
+                    }
+                    VarKind::MutLocal {ident, shadow} => self.write_var_name(ident, shadow),
+                    VarKind::LiveValue(value_ptr) => {
+                        write!(self.string, "{}", value_ptr).unwrap();
+                    }
+                },
+                ExprKind::Lit {lit, ..} => match lit {
+                    Lit::Bool(lit) => write!(self.string, "{}", lit).unwrap(),
+                    Lit::Int(lit) => write!(self.string, "{}i", lit).unwrap(),
+                    Lit::Float(lit) => write!(self.string, "{}f", PrettyPrintedF32(lit)).unwrap(),
+                    Lit::Color(lit) => write_vec4(self.string, Vec4::from_u32(lit)),
+                },
+            },
+        }
+    }
+
+    fn write_var_name(&mut self, ident: Ident, shadow: ScopeSymShadow) {
+        if self.pointer_vars.contains(&(ident, shadow)) {
+            write!(self.string, "(*{})", DisplayVarName(ident, shadow)).unwrap();
+        }
+        else {
+            write!(self.string, "{}", DisplayVarName(ident, shadow)).unwrap();
+        }
+    }
+
+    fn generate_bin_expr(&mut self, op: BinOp, left_expr: &Expr, right_expr: &Expr) {
+        // GLSL compares vectors as a whole, WGSL componentwise
+        let is_vector = left_expr.ty.borrow().as_ref().unwrap().slots() > 1;
+        match op {
+            BinOp::Eq if is_vector => write!(self.string, "all(").unwrap(),
+            BinOp::Ne if is_vector => write!(self.string, "any(").unwrap(),
+            _ => write!(self.string, "(").unwrap(),
+        }
+        self.generate_expr(left_expr);
+        write!(self.string, " {} ", op).unwrap();
+        self.generate_expr(right_expr);
+        write!(self.string, ")").unwrap();
+    }
+
+    fn generate_method_call_expr(&mut self, ident: Ident, arg_exprs: &[Expr], closure_site_index: &std::cell::Cell<Option<usize >>) {
+        match arg_exprs[0].ty.borrow().as_ref().unwrap() {
+            Ty::Struct(struct_ptr) => {
+                let fn_def = self.shader_registry.struct_method_decl_from_ident(
+                    self.shader_registry.structs.get(struct_ptr).unwrap(),
+                    ident
+                ).unwrap();
+                self.generate_call_body(fn_def, &fn_def.params, arg_exprs, closure_site_index);
+            }
+            Ty::DrawShader(shader_ptr) => {
+                let fn_def = self.shader_registry.draw_shader_method_decl_from_ident(
+                    self.shader_registry.draw_shader_defs.get(shader_ptr).unwrap(),
+                    ident
+                ).unwrap();
+                self.generate_call_body(fn_def, &fn_def.params[1..], &arg_exprs[1..], closure_site_index);
+            }
+            _ => panic!(),
+        }
+    }
+
+    fn generate_call_args(&mut self, params: &[Param], arg_exprs: &[Expr]) -> &'static str {
+        let mut sep = "";
+        for (param, arg_expr) in params.iter().zip(arg_exprs) {
+            if !is_value_ty(arg_expr.ty.borrow().as_ref().unwrap()) {
+                continue
+            }
+            write!(self.string, "{}", sep).unwrap();
+            if param.is_inout {
+                write!(self.string, "&").unwrap();
+            }
+            self.generate_expr(arg_expr);
+            sep = ", ";
+        }
+        sep
+    }
+
+    fn generate_call_body(&mut self, fn_def: &FnDef, params: &[Param], arg_exprs: &[Expr], closure_site_index: &std::cell::Cell<Option<usize >>) {
+        if let Some(closure_site_index) = closure_site_index.get() {
+            let call_def = self.fn_def;
+            let closure_sites = call_def.closure_sites.borrow();
+            let closure_site = &closure_sites.as_ref().unwrap()[closure_site_index];
+            write!(self.string, "{}(", DisplayFnNameWithClosureArgs(closure_site_index, call_def.fn_ptr, fn_def.ident)).unwrap();
+            let mut sep = self.generate_call_args(params, arg_exprs);
+            for sym in &closure_site.all_closed_over {
+                if is_value_ty(&sym.ty) {
+                    write!(self.string, "{}", sep).unwrap();
+                    self.write_var_name(sym.ident, sym.shadow);
+                    sep = ", ";
+                }
+            }
+            write!(self.string, ")").unwrap();
+        }
+        else {
+            write!(self.string, "{}(", DisplayFnName(fn_def.fn_ptr, fn_def.ident)).unwrap();
+            self.generate_call_args(params, arg_exprs);
+            write!(self.string, ")").unwrap();
+        }
+    }
+
+    fn generate_closure_call_expr(&mut self, arg_exprs: &[Expr], param_index: usize) {
+        let closure_site_info = self.closure_site_info.clone().unwrap();
+        let closure_def_index = closure_site_info.closure_site.closure_args.iter().find( | arg | arg.param_index == param_index).unwrap().closure_def_index;
+        let call_def = self.shader_registry.all_fns.get(&closure_site_info.call_ptr).unwrap();
+        let closure_def = &call_def.closure_defs[closure_def_index.0];
+        let params = if let TyExprKind::ClosureDecl {params, ..} = &self.fn_def.params[param_index].ty_expr.kind {
+            params
+        }
+        else {
+            panic!()
+        };
+
+        write!(self.string, "{}(", DisplayClosureName(closure_site_info.call_ptr, closure_def_index)).unwrap();
+        let mut sep = self.generate_call_args(params, arg_exprs);
+        for sym in closure_def.closed_over_syms.borrow().as_ref().unwrap() {
+            if is_value_ty(&sym.ty) {
+                write!(self.string, "{}{}", sep, DisplayClosedOverArg(sym.ident, sym.shadow)).unwrap();
+                sep = ", ";
+            }
+        }
+        write!(self.string, ")").unwrap();
+    }
+
+    fn generate_builtin_call_expr(&mut self, ident: Ident, arg_exprs: &[Expr]) {
+        let operator = match ident.0 {
+            live_id!(lessThan) => Some("<"),
+            live_id!(lessThanEqual) => Some("<="),
+            live_id!(greaterThan) => Some(">"),
+            live_id!(greaterThanEqual) => Some(">="),
+            live_id!(equal) => Some("=="),
+            live_id!(notEqual) => Some("!="),
+            _ => None
+        };
+        if let Some(operator) = operator {
+            write!(self.string, "(").unwrap();
+            self.generate_expr(&arg_exprs[0]);
+            write!(self.string, " {} ", operator).unwrap();
+            self.generate_expr(&arg_exprs[1]);
+            write!(self.string, ")").unwrap();
+            return
+        }
+
+        // GLSL lets scalars stand in for vectors in most builtins, WGSL wants them splatted
+        let vector_ty = arg_exprs.iter()
+            .map( | arg_expr | arg_expr.ty.borrow().clone().unwrap())
+            .find(is_float_vector);
+        match ident.0 {
+            live_id!(not) => write!(self.string, "!").unwrap(),
+            live_id!(atan) if arg_exprs.len() == 2 => write!(self.string, "atan2").unwrap(),
+            live_id!(inversesqrt) => write!(self.string, "inverseSqrt").unwrap(),
+            live_id!(dFdx) => write!(self.string, "dpdx").unwrap(),
+            live_id!(dFdy) => write!(self.string, "dpdy").unwrap(),
+            live_id!(inverse) => write!(self.string, "inverse_mat4").unwrap(),
+            live_id!(mod) => write!(self.string, "mod_{}", vector_ty.clone().unwrap_or(Ty::Float).to_string().replace("float", "f32")).unwrap(),
+            live_id!(matrixCompMult) => write!(self.string, "matrixCompMult_{}", arg_exprs[0].ty.borrow().as_ref().unwrap()).unwrap(),
+            _ => write!(self.string, "{}", ident).unwrap(),
+        }
+        write!(self.string, "(").unwrap();
+        for (index, arg_expr) in arg_exprs.iter().enumerate() {
+            if index != 0 {
+                write!(self.string, ", ").unwrap();
+            }
+            let splat = match &vector_ty {
+                Some(vector_ty) if *arg_expr.ty.borrow() == Some(Ty::Float) && ident.0 != live_id!(refract) => Some(vector_ty.clone()),
+                _ => None
+            };
+            if let Some(vector_ty) = &splat {
+                write_ty(self.string, vector_ty);
+                write!(self.string, "(").unwrap();
+            }
+            self.generate_expr(arg_expr);
+            if splat.is_some() {
+                write!(self.string, ")").unwrap();
+            }
+        }
+        write!(self.string, ")").unwrap();
+    }
+
+    fn generate_cons_call_expr(&mut self, ty_lit: TyLit, arg_exprs: &[Expr]) {
+        let param_tys: Vec<Ty> = arg_exprs.iter().map( | arg_expr | arg_expr.ty.borrow().clone().unwrap()).collect();
+        if needs_cons_fn(ty_lit, &param_tys) {
+            write!(self.string, "{}(", cons_fn_name(ty_lit, &param_tys)).unwrap();
+            for (index, arg_expr) in arg_exprs.iter().enumerate() {
+                write!(self.string, "{}", if index == 0 {""} else {", "}).unwrap();
+                self.generate_expr(arg_expr);
+            }
+            write!(self.string, ")").unwrap();
+            return
+        }
+        let ty = ty_lit.to_ty();
+        let scalar = scalar_ty_lit(&ty);
+        write_ty_lit(self.string, ty_lit);
+        write!(self.string, "(").unwrap();
+        for (index, (arg_expr, param_ty)) in arg_exprs.iter().zip(&param_tys).enumerate() {
+            write!(self.string, "{}", if index == 0 {""} else {", "}).unwrap();
+            // components of another scalar type need an explicit conversion,
+            // except for a single vector which converts as a whole
+            let convert = scalar_ty_lit(param_ty) != scalar && !(arg_exprs.len() == 1 && param_ty.slots() == ty.slots());
+            if convert {
+                if param_ty.slots() == 1 {
+                    write_ty_lit(self.string, scalar.unwrap());
+                }
+                else {
+                    write!(self.string, "vec{}<", param_ty.slots()).unwrap();
+                    write_ty_lit(self.string, scalar.unwrap());
+                    write!(self.string, ">").unwrap();
+                }
+                write!(self.string, "(").unwrap();
+            }
+            self.generate_expr(arg_expr);
+            if convert {
+                write!(self.string, ")").unwrap();
+            }
+        }
+        write!(self.string, ")").unwrap();
+    }
+
+    fn write_indent(&mut self) {
+        for _ in 0..self.indent_level {
+            write!(self.string, "    ").unwrap();
+        }
+    }
+}
+
+fn write_vec4(string: &mut String, val: Vec4) {
+    write!(
+        string,
+        "vec4<f32>({}f, {}f, {}f, {}f)",
+        PrettyPrintedF32(val.x),
+        PrettyPrintedF32(val.y),
+        PrettyPrintedF32(val.z),
+        PrettyPrintedF32(val.w),
+    ).unwrap();
+}
+
+fn is_assign_op(op: BinOp) -> bool {
+    matches!(op, BinOp::Assign | BinOp::AddAssign | BinOp::SubAssign | BinOp::MulAssign | BinOp::DivAssign)
+}
+
+// draw shaders and closures are not passed around as values
+fn is_value_ty(ty: &Ty) -> bool {
+    !matches!(ty, Ty::DrawShader(_) | Ty::ClosureDef(_) | Ty::ClosureDecl)
+}
+
+// textures can only live in params, not in function vars
+fn is_copyable_ty(ty: &Ty) -> bool {
+    is_value_ty(ty) && !matches!(ty, Ty::Texture2D | Ty::TextureOES)
+}
+
+struct DisplayLoopVar(Ident);
+impl std::fmt::Display for DisplayLoopVar {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "loop_{}", self.0)
+    }
+}
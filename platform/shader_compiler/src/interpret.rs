@@ -0,0 +1,1207 @@
+// Runs draw shaders on the CPU by walking the analysed AST, for rendering without a GPU.
+// Floats carry their screen space derivatives along (forward mode), so dFdx and dFdy can
+// be answered per pixel instead of by running neighbouring pixels in lockstep like a GPU.
+use {
+    std::{
+        rc::Rc,
+        collections::BTreeMap,
+        ops::{Add, Sub, Mul, Div, Neg},
+    },
+    crate::{
+        makepad_live_id::{LiveId, live_id},
+        makepad_math::{Vec2, Vec4},
+        shader_ast::*,
+        shader_registry::ShaderRegistry,
+        swizzle::Swizzle,
+    }
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Dual {
+    pub v: f32,
+    pub dx: f32,
+    pub dy: f32,
+}
+
+impl Dual {
+    pub const fn new(v: f32, dx: f32, dy: f32) -> Self {
+        Self {v, dx, dy}
+    }
+
+    pub const fn cst(v: f32) -> Self {
+        Self {v, dx: 0.0, dy: 0.0}
+    }
+
+    fn chain(self, v: f32, d: f32) -> Self {
+        Self {v, dx: self.dx * d, dy: self.dy * d}
+    }
+
+    pub fn sqrt(self) -> Self {
+        let v = self.v.max(0.0).sqrt();
+        self.chain(v, if v > 0.0 {0.5 / v} else {0.0})
+    }
+
+    pub fn sin(self) -> Self {self.chain(self.v.sin(), self.v.cos())}
+    pub fn cos(self) -> Self {self.chain(self.v.cos(), -self.v.sin())}
+    pub fn tan(self) -> Self {
+        let c = self.v.cos();
+        self.chain(self.v.tan(), 1.0 / (c * c))
+    }
+    pub fn asin(self) -> Self {self.chain(self.v.asin(), 1.0 / (1.0 - self.v * self.v).max(1e-12).sqrt())}
+    pub fn acos(self) -> Self {self.chain(self.v.acos(), -1.0 / (1.0 - self.v * self.v).max(1e-12).sqrt())}
+    pub fn atan(self) -> Self {self.chain(self.v.atan(), 1.0 / (1.0 + self.v * self.v))}
+
+    pub fn atan2(self, x: Dual) -> Self {
+        let r2 = (x.v * x.v + self.v * self.v).max(1e-12);
+        Self {
+            v: self.v.atan2(x.v),
+            dx: (x.v * self.dx - self.v * x.dx) / r2,
+            dy: (x.v * self.dy - self.v * x.dy) / r2,
+        }
+    }
+
+    pub fn exp(self) -> Self {
+        let v = self.v.exp();
+        self.chain(v, v)
+    }
+
+    pub fn exp2(self) -> Self {
+        let v = self.v.exp2();
+        self.chain(v, v * std::f32::consts::LN_2)
+    }
+
+    pub fn ln(self) -> Self {self.chain(self.v.ln(), 1.0 / self.v)}
+    pub fn log2(self) -> Self {self.chain(self.v.log2(), 1.0 / (self.v * std::f32::consts::LN_2))}
+
+    pub fn pow(self, e: Dual) -> Self {
+        let v = self.v.powf(e.v);
+        let d_base = if self.v != 0.0 {e.v * self.v.powf(e.v - 1.0)} else {0.0};
+        let d_exp = if self.v > 0.0 {v * self.v.ln()} else {0.0};
+        Self {
+            v,
+            dx: d_base * self.dx + d_exp * e.dx,
+            dy: d_base * self.dy + d_exp * e.dy,
+        }
+    }
+
+    pub fn abs(self) -> Self {self.chain(self.v.abs(), if self.v < 0.0 {-1.0} else {1.0})}
+    pub fn sign(self) -> Self {Self::cst(if self.v > 0.0 {1.0} else if self.v < 0.0 {-1.0} else {0.0})}
+    pub fn floor(self) -> Self {Self::cst(self.v.floor())}
+    pub fn ceil(self) -> Self {Self::cst(self.v.ceil())}
+    pub fn fract(self) -> Self {Self {v: self.v - self.v.floor(), ..self}}
+    pub fn min(self, other: Dual) -> Self {if other.v < self.v {other} else {self}}
+    pub fn max(self, other: Dual) -> Self {if other.v > self.v {other} else {self}}
+    pub fn clamp(self, lo: Dual, hi: Dual) -> Self {self.max(lo).min(hi)}
+    pub fn modulo(self, y: Dual) -> Self {self - y * (self / y).floor()}
+    pub fn mix(self, b: Dual, t: Dual) -> Self {self + (b - self) * t}
+    pub fn step(edge: Dual, x: Dual) -> Self {Self::cst(if x.v < edge.v {0.0} else {1.0})}
+
+    pub fn smoothstep(e0: Dual, e1: Dual, x: Dual) -> Self {
+        let t = ((x - e0) / (e1 - e0)).clamp(Self::cst(0.0), Self::cst(1.0));
+        t * t * (Self::cst(3.0) - Self::cst(2.0) * t)
+    }
+}
+
+impl Add for Dual {
+    type Output = Dual;
+    fn add(self, o: Dual) -> Dual {Dual {v: self.v + o.v, dx: self.dx + o.dx, dy: self.dy + o.dy}}
+}
+
+impl Sub for Dual {
+    type Output = Dual;
+    fn sub(self, o: Dual) -> Dual {Dual {v: self.v - o.v, dx: self.dx - o.dx, dy: self.dy - o.dy}}
+}
+
+impl Mul for Dual {
+    type Output = Dual;
+    fn mul(self, o: Dual) -> Dual {
+        Dual {v: self.v * o.v, dx: self.dx * o.v + self.v * o.dx, dy: self.dy * o.v + self.v * o.dy}
+    }
+}
+
+impl Div for Dual {
+    type Output = Dual;
+    fn div(self, o: Dual) -> Dual {
+        let v = self.v / o.v;
+        Dual {v, dx: (self.dx - v * o.dx) / o.v, dy: (self.dy - v * o.dy) / o.v}
+    }
+}
+
+impl Neg for Dual {
+    type Output = Dual;
+    fn neg(self) -> Dual {Dual {v: -self.v, dx: -self.dx, dy: -self.dy}}
+}
+
+/// A closure argument, with the scope it was created in.
+pub struct Closure {
+    fn_ptr: FnPtr,
+    index: ClosureDefIndex,
+    frame: Frame,
+}
+
+#[derive(Clone)]
+pub enum Value {
+    Void,
+    Bool(usize, [bool; 4]),
+    Int(usize, [i32; 4]),
+    Float(usize, [Dual; 4]),
+    // column major, like the uniform buffers
+    Mat(usize, [Dual; 16]),
+    Array(Rc<Vec<Value >>),
+    Struct(Rc<Vec<Value >>),
+    Texture(usize),
+    Closure(Rc<Closure>),
+    DrawShader,
+}
+
+impl Value {
+    pub fn float(v: f32) -> Self {
+        Self::Float(1, [Dual::cst(v); 4])
+    }
+
+    pub fn vec2(v: Vec2) -> Self {
+        Self::floats(&[Dual::cst(v.x), Dual::cst(v.y)])
+    }
+
+    pub fn vec4(v: Vec4) -> Self {
+        Self::floats(&[Dual::cst(v.x), Dual::cst(v.y), Dual::cst(v.z), Dual::cst(v.w)])
+    }
+
+    pub fn floats(comps: &[Dual]) -> Self {
+        let mut data = [Dual::default(); 4];
+        data[..comps.len()].copy_from_slice(comps);
+        Self::Float(comps.len(), data)
+    }
+
+    fn bools(comps: &[bool]) -> Self {
+        let mut data = [false; 4];
+        data[..comps.len()].copy_from_slice(comps);
+        Self::Bool(comps.len(), data)
+    }
+
+    pub fn ints(comps: &[i32]) -> Self {
+        let mut data = [0; 4];
+        data[..comps.len()].copy_from_slice(comps);
+        Self::Int(comps.len(), data)
+    }
+
+    fn from_val(val: &Val, ty: Option<&Ty>) -> Self {
+        match (val, ty) {
+            (Val::Float(v), Some(Ty::Int)) => Self::ints(&[*v as i32]),
+            (Val::Int(v), Some(Ty::Float)) => Self::float(*v as f32),
+            (Val::Bool(v), _) => Self::bools(&[*v]),
+            (Val::Int(v), _) => Self::ints(&[*v]),
+            (Val::Float(v), _) => Self::float(*v),
+            (Val::Vec4(v), _) => Self::vec4(*v),
+        }
+    }
+
+    /// Builds a value of type `ty` from the floats of a packed buffer.
+    pub fn from_slots(ty: &Ty, slots: &[f32]) -> Self {
+        let duals: Vec<Dual> = slots.iter().map( | v | Dual::cst(*v)).collect();
+        Self::from_duals(ty, &duals)
+    }
+
+    /// Builds a value of type `ty` from interpolated floats that carry their derivatives.
+    pub fn from_duals(ty: &Ty, slots: &[Dual]) -> Self {
+        match ty {
+            Ty::Bool | Ty::Bvec2 | Ty::Bvec3 | Ty::Bvec4 => {
+                let bools: Vec<bool> = slots[..ty.slots()].iter().map( | d | d.v > 0.5).collect();
+                Self::bools(&bools)
+            }
+            Ty::Int | Ty::Ivec2 | Ty::Ivec3 | Ty::Ivec4 => {
+                let ints: Vec<i32> = slots[..ty.slots()].iter().map( | d | d.v.round() as i32).collect();
+                Self::ints(&ints)
+            }
+            Ty::Float | Ty::Vec2 | Ty::Vec3 | Ty::Vec4 | Ty::Enum(_) => Self::floats(&slots[..ty.slots()]),
+            Ty::Mat2 | Ty::Mat3 | Ty::Mat4 => {
+                let n = mat_size(ty);
+                let mut data = [Dual::default(); 16];
+                data[..n * n].copy_from_slice(&slots[..n * n]);
+                Self::Mat(n, data)
+            }
+            _ => Self::Void
+        }
+    }
+
+    /// Builds a storage buffer value from the tightly packed floats of its elements.
+    pub fn buffer(elem_ty: &Ty, data: &[f32]) -> Self {
+        let slots = elem_ty.slots();
+        Self::Array(Rc::new(data.chunks_exact(slots).map( | elem | Self::from_slots(elem_ty, elem)).collect()))
+    }
+
+    /// Appends the value as floats, in the layout `from_slots` reads.
+    pub fn write_slots(&self, out: &mut Vec<f32>) {
+        match self {
+            Self::Bool(n, v) => out.extend(v[..*n].iter().map( | b | if *b {1.0} else {0.0})),
+            Self::Int(n, v) => out.extend(v[..*n].iter().map( | i | *i as f32)),
+            Self::Float(n, v) => out.extend(v[..*n].iter().map( | d | d.v)),
+            Self::Mat(n, v) => out.extend(v[..n * n].iter().map( | d | d.v)),
+            Self::Array(items) => for item in items.iter() {
+                item.write_slots(out)
+            }
+            _ => ()
+        }
+    }
+
+    pub fn as_bool(&self) -> bool {
+        match self {
+            Self::Bool(_, v) => v[0],
+            Self::Int(_, v) => v[0] != 0,
+            Self::Float(_, v) => v[0].v != 0.0,
+            _ => false
+        }
+    }
+
+    pub fn as_int(&self) -> i32 {
+        match self {
+            Self::Bool(_, v) => v[0] as i32,
+            Self::Int(_, v) => v[0],
+            Self::Float(_, v) => v[0].v as i32,
+            _ => 0
+        }
+    }
+
+    pub fn as_f32(&self) -> f32 {
+        match self {
+            Self::Bool(_, v) => v[0] as u8 as f32,
+            Self::Int(_, v) => v[0] as f32,
+            Self::Float(_, v) => v[0].v,
+            _ => 0.0
+        }
+    }
+
+    pub fn as_vec2(&self) -> Vec2 {
+        let d = self.duals();
+        Vec2 {x: d[0].v, y: d.get(1).map_or(d[0].v, | d | d.v)}
+    }
+
+    pub fn as_vec4(&self) -> Vec4 {
+        let d = self.duals();
+        let c = | i: usize | d.get(i).map_or(0.0, | d | d.v);
+        Vec4 {x: c(0), y: c(1), z: c(2), w: c(3)}
+    }
+
+    fn duals(&self) -> Vec<Dual> {
+        match self {
+            Self::Bool(n, v) => v[..*n].iter().map( | b | Dual::cst(if *b {1.0} else {0.0})).collect(),
+            Self::Int(n, v) => v[..*n].iter().map( | i | Dual::cst(*i as f32)).collect(),
+            Self::Float(n, v) => v[..*n].to_vec(),
+            Self::Mat(n, v) => v[..n * n].to_vec(),
+            _ => vec![Dual::default()]
+        }
+    }
+
+    fn int_comps(&self) -> Vec<i32> {
+        match self {
+            Self::Int(n, v) => v[..*n].to_vec(),
+            other => other.duals().iter().map( | d | d.v as i32).collect()
+        }
+    }
+
+    fn bool_comps(&self) -> Vec<bool> {
+        match self {
+            Self::Bool(n, v) => v[..*n].to_vec(),
+            other => other.duals().iter().map( | d | d.v != 0.0).collect()
+        }
+    }
+
+    // same shape as self, with new float components
+    fn with_duals(&self, comps: &[Dual]) -> Self {
+        match self {
+            Self::Mat(n, _) => {
+                let mut data = [Dual::default(); 16];
+                data[..n * n].copy_from_slice(&comps[..n * n]);
+                Self::Mat(*n, data)
+            }
+            _ => Self::floats(comps)
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Self::Bool(n, _) | Self::Int(n, _) | Self::Float(n, _) => *n,
+            Self::Mat(n, _) => n * n,
+            _ => 1
+        }
+    }
+
+    fn map(&self, f: impl Fn(Dual) -> Dual) -> Self {
+        let comps: Vec<Dual> = self.duals().into_iter().map(f).collect();
+        self.with_duals(&comps)
+    }
+
+    fn zip(&self, other: &Value, f: impl Fn(Dual, Dual) -> Dual) -> Self {
+        let (a, b) = (self.duals(), other.duals());
+        let len = a.len().max(b.len());
+        let comps: Vec<Dual> = (0..len).map( | i | f(a[if a.len() == 1 {0} else {i}], b[if b.len() == 1 {0} else {i}])).collect();
+        if self.len() >= other.len() {self.with_duals(&comps)} else {other.with_duals(&comps)}
+    }
+
+    fn zip3(&self, b: &Value, c: &Value, f: impl Fn(Dual, Dual, Dual) -> Dual) -> Self {
+        let (da, db, dc) = (self.duals(), b.duals(), c.duals());
+        let len = da.len().max(db.len()).max(dc.len());
+        let pick = | d: &[Dual], i: usize | d[if d.len() == 1 {0} else {i}];
+        let comps: Vec<Dual> = (0..len).map( | i | f(pick(&da, i), pick(&db, i), pick(&dc, i))).collect();
+        let shape = [self, b, c].into_iter().max_by_key( | v | v.len()).unwrap();
+        shape.with_duals(&comps)
+    }
+
+    fn zip_int(&self, other: &Value, f: impl Fn(i32, i32) -> i32) -> Self {
+        let (a, b) = (self.int_comps(), other.int_comps());
+        let len = a.len().max(b.len());
+        let comps: Vec<i32> = (0..len).map( | i | f(a[if a.len() == 1 {0} else {i}], b[if b.len() == 1 {0} else {i}])).collect();
+        Self::ints(&comps)
+    }
+
+    fn compare(&self, other: &Value, f: impl Fn(f32, f32) -> bool) -> Self {
+        let (a, b) = (self.duals(), other.duals());
+        let comps: Vec<bool> = a.iter().zip(b.iter()).map( | (a, b) | f(a.v, b.v)).collect();
+        Self::bools(&comps)
+    }
+
+    fn equals(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Self::Array(a), Self::Array(b)) | (Self::Struct(a), Self::Struct(b)) => {
+                a.iter().zip(b.iter()).all( | (a, b) | a.equals(b))
+            }
+            _ => self.duals().iter().zip(other.duals().iter()).all( | (a, b) | a.v == b.v)
+        }
+    }
+
+    fn component(&self, index: usize) -> Value {
+        match self {
+            Self::Bool(_, v) => Self::bools(&[v[index]]),
+            Self::Int(_, v) => Self::ints(&[v[index]]),
+            Self::Float(_, v) => Self::floats(&[v[index]]),
+            Self::Mat(n, v) => Self::floats(&v[index * n..index * n + n]),
+            Self::Array(items) => items[index].clone(),
+            _ => Self::Void
+        }
+    }
+
+    fn set_component(&mut self, index: usize, value: Value) {
+        match self {
+            Self::Bool(_, v) => v[index] = value.as_bool(),
+            Self::Int(_, v) => v[index] = value.as_int(),
+            Self::Float(_, v) => v[index] = value.duals()[0],
+            Self::Mat(n, v) => {
+                let n = *n;
+                v[index * n..index * n + n].copy_from_slice(&value.duals()[..n]);
+            }
+            Self::Array(items) => if let Some(item) = Rc::make_mut(items).get_mut(index) {
+                *item = value
+            }
+            _ => ()
+        }
+    }
+
+    fn swizzle(&self, indices: &[usize]) -> Value {
+        match self {
+            Self::Bool(_, v) => Self::bools(&indices.iter().map( | i | v[*i]).collect::<Vec<_ >> ()),
+            Self::Int(_, v) => Self::ints(&indices.iter().map( | i | v[*i]).collect::<Vec<_ >> ()),
+            Self::Float(_, v) => Self::floats(&indices.iter().map( | i | v[*i]).collect::<Vec<_ >> ()),
+            _ => Self::Void
+        }
+    }
+
+    fn set_swizzle(&mut self, indices: &[usize], value: &Value) {
+        for (src, dst) in indices.iter().enumerate() {
+            self.set_component(*dst, value.component(src));
+        }
+    }
+}
+
+impl Clone for Closure {
+    fn clone(&self) -> Self {
+        Self {fn_ptr: self.fn_ptr, index: self.index, frame: self.frame.clone()}
+    }
+}
+
+fn mat_size(ty: &Ty) -> usize {
+    match ty {
+        Ty::Mat2 => 2,
+        Ty::Mat3 => 3,
+        _ => 4
+    }
+}
+
+#[derive(Clone, Default)]
+struct Frame {
+    fn_ptr: Option<FnPtr>,
+    locals: Vec<(Ident, Option<ScopeSymShadow>, Value)>,
+    closures: Vec<(usize, Rc<Closure>)>,
+}
+
+impl Frame {
+    fn get(&self, ident: Ident, shadow: ScopeSymShadow) -> Option<&Value> {
+        self.locals.iter().rev().find( | (i, s, _) | *i == ident && *s == Some(shadow))
+            // loop counters are declared without a shadow
+            .or_else( || self.locals.iter().rev().find( | (i, s, _) | *i == ident && s.is_none()))
+            .map( | (_, _, value) | value)
+    }
+
+    fn set(&mut self, ident: Ident, shadow: Option<ScopeSymShadow>, value: Value) {
+        if let Some(local) = self.locals.iter_mut().find( | (i, s, _) | *i == ident && *s == shadow) {
+            local.2 = value;
+        }
+        else {
+            self.locals.push((ident, shadow, value));
+        }
+    }
+}
+
+#[allow(clippy::large_enum_variant)]
+enum Flow {
+    Next,
+    Break,
+    Continue,
+    Return(Value),
+}
+
+/// Supplies the textures a shader samples, by texture slot.
+pub trait ShaderSampler {
+    fn sample_2d(&self, texture: usize, pos: Vec2) -> Vec4;
+}
+
+pub struct ShaderInterpreter<'a> {
+    pub shader_registry: &'a ShaderRegistry,
+    pub draw_shader_def: &'a DrawShaderDef,
+    pub sampler: &'a dyn ShaderSampler,
+    /// The values of `draw_shader_def.fields`, in the same order.
+    pub fields: Vec<Value>,
+    pub live_values: BTreeMap<ValuePtr, Value>,
+    swizzles: Vec<(LiveId, Vec<usize >) >,
+}
+
+impl<'a> ShaderInterpreter<'a> {
+    pub fn new(shader_registry: &'a ShaderRegistry, draw_shader_def: &'a DrawShaderDef, sampler: &'a dyn ShaderSampler) -> Self {
+        let mut ret = Self {
+            shader_registry,
+            draw_shader_def,
+            sampler,
+            fields: Vec::new(),
+            live_values: BTreeMap::new(),
+            swizzles: Vec::new(),
+        };
+        let mut texture_slot = 0;
+        for field in &draw_shader_def.fields {
+            let value = match field.kind {
+                DrawShaderFieldKind::Texture {..} => {
+                    texture_slot += 1;
+                    Value::Texture(texture_slot - 1)
+                }
+                _ => ret.zero(field.ty_expr.ty.borrow().as_ref().unwrap())
+            };
+            ret.fields.push(value);
+        }
+        ret
+    }
+
+    pub fn field_index(&self, ident: Ident) -> Option<usize> {
+        self.draw_shader_def.fields.iter().position( | field | field.ident == ident)
+    }
+
+    /// The fields the vertex function hands to the pixel function, interpolated in between.
+    pub fn varying_fields(&self) -> Vec<usize> {
+        let mut out = Vec::new();
+        for (index, field) in self.draw_shader_def.fields.iter().enumerate() {
+            match &field.kind {
+                DrawShaderFieldKind::Geometry {is_used_in_pixel_shader, ..} |
+                DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => out.push(index),
+                DrawShaderFieldKind::Varying {..} => out.push(index),
+                _ => ()
+            }
+        }
+        out
+    }
+
+    /// Runs a method of the draw shader, usually `vertex` or `pixel`.
+    pub fn call_method(&mut self, ident: Ident) -> Option<Value> {
+        self.call_method_with_args(ident, &[])
+    }
+
+    /// Runs a method that takes arguments after `self`, like `compute` with its invocation id.
+    pub fn call_method_with_args(&mut self, ident: Ident, args: &[Value]) -> Option<Value> {
+        let fn_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, ident) ?;
+        let mut frame = Frame {fn_ptr: Some(fn_def.fn_ptr), ..Default::default()};
+        let values = std::iter::once(&Value::DrawShader).chain(args);
+        for (param, value) in fn_def.params.iter().zip(values) {
+            if let Some(shadow) = param.shadow.get() {
+                frame.set(param.ident, Some(shadow), value.clone());
+            }
+        }
+        Some(match self.exec_block(&mut frame, &fn_def.block) {
+            Flow::Return(value) => value,
+            _ => Value::Void
+        })
+    }
+
+    pub fn zero(&self, ty: &Ty) -> Value {
+        match ty {
+            Ty::Bool => Value::bools(&[false]),
+            Ty::Bvec2 => Value::bools(&[false; 2]),
+            Ty::Bvec3 => Value::bools(&[false; 3]),
+            Ty::Bvec4 => Value::bools(&[false; 4]),
+            Ty::Int => Value::ints(&[0]),
+            Ty::Ivec2 => Value::ints(&[0; 2]),
+            Ty::Ivec3 => Value::ints(&[0; 3]),
+            Ty::Ivec4 => Value::ints(&[0; 4]),
+            Ty::Float | Ty::Enum(_) => Value::float(0.0),
+            Ty::Vec2 => Value::floats(&[Dual::default(); 2]),
+            Ty::Vec3 => Value::floats(&[Dual::default(); 3]),
+            Ty::Vec4 => Value::floats(&[Dual::default(); 4]),
+            Ty::Mat2 | Ty::Mat3 | Ty::Mat4 => Value::Mat(mat_size(ty), [Dual::default(); 16]),
+            Ty::Texture2D | Ty::TextureOES => Value::Texture(0),
+            Ty::Array {elem_ty, len} => Value::Array(Rc::new(vec![self.zero(elem_ty); *len])),
+            Ty::Struct(struct_ptr) => {
+                let struct_def = self.shader_registry.structs.get(struct_ptr).unwrap();
+                Value::Struct(Rc::new(struct_def.fields.iter().map( | field | self.zero(field.ty_expr.ty.borrow().as_ref().unwrap())).collect()))
+            }
+            Ty::DrawShader(_) => Value::DrawShader,
+            Ty::Void | Ty::ClosureDecl | Ty::ClosureDef(_) => Value::Void,
+        }
+    }
+
+    fn exec_block(&mut self, frame: &mut Frame, block: &Block) -> Flow {
+        for stmt in &block.stmts {
+            match self.exec_stmt(frame, stmt) {
+                Flow::Next => (),
+                flow => return flow
+            }
+        }
+        Flow::Next
+    }
+
+    fn exec_stmt(&mut self, frame: &mut Frame, stmt: &Stmt) -> Flow {
+        match stmt {
+            Stmt::Break {..} => Flow::Break,
+            Stmt::Continue {..} => Flow::Continue,
+            Stmt::For {ident, from_expr, to_expr, step_expr, block, ..} => {
+                let from = self.const_int(frame, from_expr);
+                let to = self.const_int(frame, to_expr);
+                let step = match step_expr {
+                    Some(step_expr) => self.const_int(frame, step_expr),
+                    None if from < to => 1,
+                    None => -1
+                };
+                // the same bounds the generated shaders use
+                let mut i = if from <= to {from} else {from - 1};
+                while (from <= to && i < to) || (from > to && i >= to) {
+                    frame.set(*ident, None, Value::ints(&[i]));
+                    match self.exec_block(frame, block) {
+                        Flow::Break => break,
+                        Flow::Return(value) => return Flow::Return(value),
+                        _ => ()
+                    }
+                    i += step;
+                }
+                Flow::Next
+            }
+            Stmt::If {expr, block_if_true, block_if_false, ..} => {
+                if self.eval(frame, expr).as_bool() {
+                    self.exec_block(frame, block_if_true)
+                }
+                else if let Some(block_if_false) = block_if_false {
+                    self.exec_block(frame, block_if_false)
+                }
+                else {
+                    Flow::Next
+                }
+            }
+            Stmt::Match {expr, matches, ..} => {
+                let value = self.eval(frame, expr).as_f32();
+                for match_item in matches {
+                    if (value - match_item.enum_value.get().unwrap() as f32).abs() < 0.5 {
+                        return self.exec_block(frame, &match_item.block)
+                    }
+                }
+                Flow::Next
+            }
+            Stmt::Let {ty, ident, expr, shadow, ..} => {
+                let value = match expr {
+                    Some(expr) => self.eval(frame, expr),
+                    None => self.zero(ty.borrow().as_ref().unwrap())
+                };
+                frame.set(*ident, shadow.get(), value);
+                Flow::Next
+            }
+            Stmt::Return {expr, ..} => Flow::Return(match expr {
+                Some(expr) => self.eval(frame, expr),
+                None => Value::Void
+            }),
+            Stmt::Block {block, ..} => self.exec_block(frame, block),
+            Stmt::Expr {expr, ..} => {
+                self.eval(frame, expr);
+                Flow::Next
+            }
+        }
+    }
+
+    fn const_int(&mut self, frame: &mut Frame, expr: &Expr) -> i32 {
+        let val = expr.const_val.borrow().as_ref().and_then( | val | val.as_ref()).and_then( | val | val.to_int());
+        match val {
+            Some(val) => val,
+            None => self.eval(frame, expr).as_int()
+        }
+    }
+
+    fn eval(&mut self, frame: &mut Frame, expr: &Expr) -> Value {
+        if let Some(Some(val)) = expr.const_val.borrow().as_ref() {
+            return Value::from_val(val, expr.ty.borrow().as_ref())
+        }
+        match &expr.kind {
+            ExprKind::Cond {expr: cond, expr_if_true, expr_if_false, ..} => {
+                if self.eval(frame, cond).as_bool() {
+                    self.eval(frame, expr_if_true)
+                }
+                else {
+                    self.eval(frame, expr_if_false)
+                }
+            }
+            ExprKind::Bin {op, left_expr, right_expr, ..} => self.eval_bin(frame, *op, left_expr, right_expr),
+            ExprKind::Un {op, expr, ..} => {
+                let value = self.eval(frame, expr);
+                match (op, value) {
+                    (UnOp::Not, Value::Bool(n, mut v)) => {
+                        v.iter_mut().for_each( | b | *b = !*b);
+                        Value::Bool(n, v)
+                    }
+                    (UnOp::Neg, Value::Int(n, mut v)) => {
+                        v.iter_mut().for_each( | i | *i = i.wrapping_neg());
+                        Value::Int(n, v)
+                    }
+                    (_, value) => value.map( | d | -d)
+                }
+            }
+            ExprKind::Field {expr: base, field_ident, ..} => {
+                let base_ty = base.ty.borrow().clone();
+                match base_ty {
+                    Some(Ty::DrawShader(_)) => match self.field_index(*field_ident) {
+                        Some(index) => self.fields[index].clone(),
+                        None => Value::Void
+                    }
+                    Some(Ty::Struct(struct_ptr)) => {
+                        let index = self.struct_field_index(struct_ptr, *field_ident);
+                        match self.eval(frame, base) {
+                            Value::Struct(fields) => fields[index].clone(),
+                            _ => Value::Void
+                        }
+                    }
+                    _ => {
+                        let indices = self.swizzle(*field_ident);
+                        self.eval(frame, base).swizzle(&indices)
+                    }
+                }
+            }
+            ExprKind::Index {expr: base, index_expr, ..} => {
+                let index = self.eval(frame, index_expr).as_int().max(0) as usize;
+                match self.eval(frame, base) {
+                    // reading past the end of a buffer gives zero, like robust buffer access does
+                    Value::Array(items) if index >= items.len() => self.zero(expr.ty.borrow().as_ref().unwrap()),
+                    base => base.component(index)
+                }
+            }
+            ExprKind::MethodCall {ident, arg_exprs, ..} => {
+                let self_ty = arg_exprs[0].ty.borrow().clone();
+                let fn_def = match self_ty {
+                    Some(Ty::Struct(struct_ptr)) => self.shader_registry.struct_method_decl_from_ident(
+                        self.shader_registry.structs.get(&struct_ptr).unwrap(),
+                        *ident
+                    ),
+                    Some(Ty::DrawShader(shader_ptr)) => self.shader_registry.draw_shader_method_decl_from_ident(
+                        self.shader_registry.draw_shader_defs.get(&shader_ptr).unwrap_or(self.draw_shader_def),
+                        *ident
+                    ),
+                    _ => None
+                };
+                match fn_def {
+                    Some(fn_def) => self.call(frame, fn_def, arg_exprs),
+                    None => Value::Void
+                }
+            }
+            ExprKind::PlainCall {fn_ptr, arg_exprs, param_index, ..} => {
+                if let Some(param_index) = param_index.get() {
+                    self.call_closure(frame, param_index, arg_exprs)
+                }
+                else {
+                    match fn_ptr.and_then( | fn_ptr | self.shader_registry.all_fns.get(&fn_ptr)) {
+                        Some(fn_def) => self.call(frame, fn_def, arg_exprs),
+                        None => Value::Void
+                    }
+                }
+            }
+            ExprKind::BuiltinCall {ident, arg_exprs, ..} => {
+                let args: Vec<Value> = arg_exprs.iter().map( | arg | self.eval(frame, arg)).collect();
+                self.builtin(*ident, &args)
+            }
+            ExprKind::ClosureDef(_) => Value::Void,
+            ExprKind::ConsCall {ty_lit, arg_exprs, ..} => {
+                let args: Vec<Value> = arg_exprs.iter().map( | arg | self.eval(frame, arg)).collect();
+                Self::cons(*ty_lit, &args)
+            }
+            ExprKind::StructCons {struct_ptr, args, ..} => {
+                let struct_def = self.shader_registry.structs.get(struct_ptr).unwrap();
+                let mut fields = Vec::with_capacity(struct_def.fields.len());
+                for field in &struct_def.fields {
+                    let value = match args.iter().find( | (ident, _) | *ident == field.ident) {
+                        Some((_, arg)) => self.eval(frame, arg),
+                        None => self.zero(field.ty_expr.ty.borrow().as_ref().unwrap())
+                    };
+                    fields.push(value);
+                }
+                Value::Struct(Rc::new(fields))
+            }
+            ExprKind::Var {kind, ..} => {
+                let value = match kind.get() {
+                    Some(VarKind::Local {ident, shadow}) | Some(VarKind::MutLocal {ident, shadow}) => frame.get(ident, shadow).cloned(),
+                    Some(VarKind::LiveValue(value_ptr)) => self.live_values.get(&value_ptr).cloned(),
+                    None => None
+                };
+                match value {
+                    Some(value) => value,
+                    None => self.zero(expr.ty.borrow().as_ref().unwrap_or(&Ty::Void))
+                }
+            }
+            ExprKind::Lit {lit, ..} => Value::from_val(&lit.to_val(), None),
+        }
+    }
+
+    fn eval_bin(&mut self, frame: &mut Frame, op: BinOp, left_expr: &Expr, right_expr: &Expr) -> Value {
+        let arith_op = match op {
+            BinOp::Assign => {
+                let value = self.eval(frame, right_expr);
+                self.assign(frame, left_expr, value.clone());
+                return value
+            }
+            BinOp::Or => return Value::bools(&[self.eval(frame, left_expr).as_bool() || self.eval(frame, right_expr).as_bool()]),
+            BinOp::And => return Value::bools(&[self.eval(frame, left_expr).as_bool() && self.eval(frame, right_expr).as_bool()]),
+            BinOp::AddAssign => BinOp::Add,
+            BinOp::SubAssign => BinOp::Sub,
+            BinOp::MulAssign => BinOp::Mul,
+            BinOp::DivAssign => BinOp::Div,
+            op => op
+        };
+        let left = self.eval(frame, left_expr);
+        let right = self.eval(frame, right_expr);
+        let value = match arith_op {
+            BinOp::Eq => Value::bools(&[left.equals(&right)]),
+            BinOp::Ne => Value::bools(&[!left.equals(&right)]),
+            BinOp::Lt => Value::bools(&[left.as_f32() < right.as_f32()]),
+            BinOp::Le => Value::bools(&[left.as_f32() <= right.as_f32()]),
+            BinOp::Gt => Value::bools(&[left.as_f32() > right.as_f32()]),
+            BinOp::Ge => Value::bools(&[left.as_f32() >= right.as_f32()]),
+            op => Self::arith(op, &left, &right)
+        };
+        if !matches!(op, BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge | BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div) {
+            self.assign(frame, left_expr, value.clone());
+        }
+        value
+    }
+
+    fn arith(op: BinOp, left: &Value, right: &Value) -> Value {
+        match (op, left, right) {
+            (BinOp::Mul, Value::Mat(n, a), Value::Mat(_, b)) => {
+                let n = *n;
+                let mut out = [Dual::default(); 16];
+                for col in 0..n {
+                    for row in 0..n {
+                        let mut sum = Dual::default();
+                        for k in 0..n {
+                            sum = sum + a[k * n + row] * b[col * n + k];
+                        }
+                        out[col * n + row] = sum;
+                    }
+                }
+                Value::Mat(n, out)
+            }
+            (BinOp::Mul, Value::Mat(n, m), Value::Float(len, v)) if *len == *n => {
+                let n = *n;
+                let out: Vec<Dual> = (0..n).map( | row | {
+                    (0..n).fold(Dual::default(), | sum, k | sum + m[k * n + row] * v[k])
+                }).collect();
+                Value::floats(&out)
+            }
+            (BinOp::Mul, Value::Float(len, v), Value::Mat(n, m)) if *len == *n => {
+                let n = *n;
+                let out: Vec<Dual> = (0..n).map( | col | {
+                    (0..n).fold(Dual::default(), | sum, k | sum + v[k] * m[col * n + k])
+                }).collect();
+                Value::floats(&out)
+            }
+            (_, Value::Int(..), Value::Int(..)) => left.zip_int(right, | a, b | match op {
+                BinOp::Add => a.wrapping_add(b),
+                BinOp::Sub => a.wrapping_sub(b),
+                BinOp::Mul => a.wrapping_mul(b),
+                _ => if b == 0 {0} else {a.wrapping_div(b)}
+            }),
+            _ => left.zip(right, | a, b | match op {
+                BinOp::Add => a + b,
+                BinOp::Sub => a - b,
+                BinOp::Mul => a * b,
+                _ => a / b
+            })
+        }
+    }
+
+    fn assign(&mut self, frame: &mut Frame, expr: &Expr, value: Value) {
+        match &expr.kind {
+            ExprKind::Var {kind, ..} => match kind.get() {
+                Some(VarKind::Local {ident, shadow}) | Some(VarKind::MutLocal {ident, shadow}) => {
+                    frame.set(ident, Some(shadow), value);
+                }
+                _ => ()
+            }
+            ExprKind::Field {expr: base, field_ident, ..} => {
+                let base_ty = base.ty.borrow().clone();
+                match base_ty {
+                    Some(Ty::DrawShader(_)) => if let Some(index) = self.field_index(*field_ident) {
+                        self.fields[index] = value;
+                    }
+                    Some(Ty::Struct(struct_ptr)) => {
+                        let index = self.struct_field_index(struct_ptr, *field_ident);
+                        if let Value::Struct(mut fields) = self.eval(frame, base) {
+                            Rc::make_mut(&mut fields)[index] = value;
+                            self.assign(frame, base, Value::Struct(fields));
+                        }
+                    }
+                    _ => {
+                        let indices = self.swizzle(*field_ident);
+                        let mut base_value = self.eval(frame, base);
+                        base_value.set_swizzle(&indices, &value);
+                        self.assign(frame, base, base_value);
+                    }
+                }
+            }
+            ExprKind::Index {expr: base, index_expr, ..} => {
+                let index = self.eval(frame, index_expr).as_int().max(0) as usize;
+                // buffers are written in place instead of copied for every element
+                if let ExprKind::Field {expr: shader, field_ident, ..} = &base.kind {
+                    if let (Some(Ty::DrawShader(_)), Some(field_index)) = (shader.ty.borrow().as_ref(), self.field_index(*field_ident)) {
+                        self.fields[field_index].set_component(index, value);
+                        return
+                    }
+                }
+                let mut base_value = self.eval(frame, base);
+                base_value.set_component(index, value);
+                self.assign(frame, base, base_value);
+            }
+            _ => ()
+        }
+    }
+
+    fn call(&mut self, frame: &mut Frame, fn_def: &FnDef, arg_exprs: &[Expr]) -> Value {
+        let mut callee = Frame {fn_ptr: Some(fn_def.fn_ptr), ..Default::default()};
+        for (param_index, (param, arg_expr)) in fn_def.params.iter().zip(arg_exprs).enumerate() {
+            if let ExprKind::ClosureDef(index) = arg_expr.kind {
+                callee.closures.push((param_index, Rc::new(Closure {
+                    fn_ptr: frame.fn_ptr.unwrap(),
+                    index,
+                    frame: frame.clone()
+                })));
+            }
+            else if let Some(shadow) = param.shadow.get() {
+                let value = self.eval(frame, arg_expr);
+                callee.set(param.ident, Some(shadow), value);
+            }
+        }
+        let ret = match self.exec_block(&mut callee, &fn_def.block) {
+            Flow::Return(value) => value,
+            _ => Value::Void
+        };
+        for (param, arg_expr) in fn_def.params.iter().zip(arg_exprs) {
+            if let (true, Some(shadow)) = (param.is_inout, param.shadow.get()) {
+                if let Some(value) = callee.get(param.ident, shadow).cloned() {
+                    self.assign(frame, arg_expr, value);
+                }
+            }
+        }
+        ret
+    }
+
+    fn call_closure(&mut self, frame: &mut Frame, param_index: usize, arg_exprs: &[Expr]) -> Value {
+        let closure = match frame.closures.iter().find( | (index, _) | *index == param_index) {
+            Some((_, closure)) => closure.clone(),
+            None => return Value::Void
+        };
+        let fn_def = self.shader_registry.all_fns.get(&closure.fn_ptr).unwrap();
+        let closure_def = &fn_def.closure_defs[closure.index.0];
+        let mut callee = closure.frame.clone();
+        for (param, arg_expr) in closure_def.params.iter().zip(arg_exprs) {
+            let value = self.eval(frame, arg_expr);
+            callee.set(param.ident, param.shadow.get(), value);
+        }
+        let ret = match &closure_def.kind {
+            ClosureDefKind::Expr(expr) => self.eval(&mut callee, expr),
+            ClosureDefKind::Block(block) => match self.exec_block(&mut callee, block) {
+                Flow::Return(value) => value,
+                _ => Value::Void
+            }
+        };
+        // write back inout arguments, as declared by the fn taking the closure
+        let decl = frame.fn_ptr.and_then( | fn_ptr | self.shader_registry.all_fns.get(&fn_ptr));
+        if let Some(TyExprKind::ClosureDecl {params, ..}) = decl.map( | decl | &decl.params[param_index].ty_expr.kind) {
+            for ((decl_param, param), arg_expr) in params.iter().zip(&closure_def.params).zip(arg_exprs) {
+                if let (true, Some(shadow)) = (decl_param.is_inout, param.shadow.get()) {
+                    if let Some(value) = callee.get(param.ident, shadow).cloned() {
+                        self.assign(frame, arg_expr, value);
+                    }
+                }
+            }
+        }
+        ret
+    }
+
+    fn struct_field_index(&self, struct_ptr: StructPtr, ident: Ident) -> usize {
+        let struct_def = self.shader_registry.structs.get(&struct_ptr).unwrap();
+        struct_def.fields.iter().position( | field | field.ident == ident).unwrap_or(0)
+    }
+
+    fn swizzle(&mut self, ident: Ident) -> Vec<usize> {
+        if let Some((_, indices)) = self.swizzles.iter().find( | (id, _) | *id == ident.0) {
+            return indices.clone()
+        }
+        let indices: Vec<usize> = Swizzle::parse(ident).map( | swizzle | swizzle.iter().cloned().collect()).unwrap_or_default();
+        self.swizzles.push((ident.0, indices.clone()));
+        indices
+    }
+
+    fn cons(ty_lit: TyLit, args: &[Value]) -> Value {
+        let comps: Vec<Dual> = args.iter().flat_map( | arg | arg.duals()).collect();
+        let scalar_arg = args.len() == 1 && args[0].len() == 1;
+        let take = | n: usize | -> Vec<Dual> {
+            if scalar_arg {vec![comps[0]; n]} else {comps[..n].to_vec()}
+        };
+        match ty_lit {
+            TyLit::Bool => Value::bools(&[comps[0].v != 0.0]),
+            TyLit::Int => Value::ints(&[comps[0].v as i32]),
+            TyLit::Float => Value::floats(&comps[..1]),
+            TyLit::Bvec2 | TyLit::Bvec3 | TyLit::Bvec4 => {
+                let n = ty_lit.to_ty().slots();
+                Value::bools(&take(n).iter().map( | d | d.v != 0.0).collect::<Vec<_ >> ())
+            }
+            TyLit::Ivec2 | TyLit::Ivec3 | TyLit::Ivec4 => {
+                let n = ty_lit.to_ty().slots();
+                Value::ints(&take(n).iter().map( | d | d.v as i32).collect::<Vec<_ >> ())
+            }
+            TyLit::Vec2 | TyLit::Vec3 | TyLit::Vec4 => Value::floats(&take(ty_lit.to_ty().slots())),
+            TyLit::Mat2 | TyLit::Mat3 | TyLit::Mat4 => {
+                let n = mat_size(&ty_lit.to_ty());
+                let mut data = [Dual::default(); 16];
+                match &args[0] {
+                    Value::Mat(m, src) if args.len() == 1 => for col in 0..n {
+                        for row in 0..n {
+                            data[col * n + row] = if col < *m && row < *m {src[col * m + row]}
+                            else {Dual::cst(if col == row {1.0} else {0.0})};
+                        }
+                    }
+                    _ if scalar_arg => for i in 0..n {
+                        data[i * n + i] = comps[0];
+                    }
+                    _ => data[..n * n].copy_from_slice(&comps[..n * n])
+                }
+                Value::Mat(n, data)
+            }
+            TyLit::Texture2D | TyLit::TextureOES => args[0].clone()
+        }
+    }
+
+    fn builtin(&self, ident: Ident, args: &[Value]) -> Value {
+        let is_int = matches!(args.first(), Some(Value::Int(..)));
+        match ident.0 {
+            live_id!(abs) if is_int => Value::ints(&args[0].int_comps().iter().map( | i | i.wrapping_abs()).collect::<Vec<_ >> ()),
+            live_id!(abs) => args[0].map(Dual::abs),
+            live_id!(acos) => args[0].map(Dual::acos),
+            live_id!(asin) => args[0].map(Dual::asin),
+            live_id!(atan) if args.len() == 2 => args[0].zip(&args[1], Dual::atan2),
+            live_id!(atan) => args[0].map(Dual::atan),
+            live_id!(ceil) => args[0].map(Dual::ceil),
+            live_id!(clamp) if is_int => args[0].zip_int(&args[1], i32::max).zip_int(&args[2], i32::min),
+            live_id!(clamp) => args[0].zip3(&args[1], &args[2], Dual::clamp),
+            live_id!(cos) => args[0].map(Dual::cos),
+            live_id!(degrees) => args[0].map( | d | d * Dual::cst(180.0 / std::f32::consts::PI)),
+            live_id!(radians) => args[0].map( | d | d * Dual::cst(std::f32::consts::PI / 180.0)),
+            live_id!(dFdx) => args[0].map( | d | Dual::cst(d.dx)),
+            live_id!(dFdy) => args[0].map( | d | Dual::cst(d.dy)),
+            live_id!(exp) => args[0].map(Dual::exp),
+            live_id!(exp2) => args[0].map(Dual::exp2),
+            live_id!(floor) => args[0].map(Dual::floor),
+            live_id!(fract) => args[0].map(Dual::fract),
+            live_id!(inversesqrt) => args[0].map( | d | Dual::cst(1.0) / d.sqrt()),
+            live_id!(log) => args[0].map(Dual::ln),
+            live_id!(log2) => args[0].map(Dual::log2),
+            live_id!(max) if is_int => args[0].zip_int(&args[1], i32::max),
+            live_id!(max) => args[0].zip(&args[1], Dual::max),
+            live_id!(min) if is_int => args[0].zip_int(&args[1], i32::min),
+            live_id!(min) => args[0].zip(&args[1], Dual::min),
+            live_id!(mix) => args[0].zip3(&args[1], &args[2], Dual::mix),
+            live_id!(mod) => args[0].zip(&args[1], Dual::modulo),
+            live_id!(pow) => args[0].zip(&args[1], Dual::pow),
+            live_id!(sign) => args[0].map(Dual::sign),
+            live_id!(sin) => args[0].map(Dual::sin),
+            live_id!(smoothstep) => args[0].zip3(&args[1], &args[2], Dual::smoothstep),
+            live_id!(sqrt) => args[0].map(Dual::sqrt),
+            live_id!(step) => args[0].zip(&args[1], Dual::step),
+            live_id!(tan) => args[0].map(Dual::tan),
+            live_id!(matrixCompMult) => args[0].zip(&args[1], | a, b | a * b),
+            live_id!(dot) => Value::floats(&[dot(&args[0].duals(), &args[1].duals())]),
+            live_id!(length) => {
+                let v = args[0].duals();
+                Value::floats(&[dot(&v, &v).sqrt()])
+            }
+            live_id!(distance) => {
+                let v = args[0].zip(&args[1], | a, b | a - b).duals();
+                Value::floats(&[dot(&v, &v).sqrt()])
+            }
+            live_id!(normalize) => {
+                let v = args[0].duals();
+                let len = dot(&v, &v).sqrt();
+                args[0].map( | d | d / len)
+            }
+            live_id!(cross) => {
+                let (a, b) = (args[0].duals(), args[1].duals());
+                Value::floats(&[
+                    a[1] * b[2] - a[2] * b[1],
+                    a[2] * b[0] - a[0] * b[2],
+                    a[0] * b[1] - a[1] * b[0],
+                ])
+            }
+            live_id!(faceforward) => {
+                if dot(&args[2].duals(), &args[1].duals()).v < 0.0 {args[0].clone()} else {args[0].map( | d | -d)}
+            }
+            live_id!(reflect) => {
+                let d = dot(&args[1].duals(), &args[0].duals());
+                args[0].zip(&args[1], | i, n | i - Dual::cst(2.0) * d * n)
+            }
+            live_id!(refract) => {
+                let eta = args[2].duals()[0];
+                let d = dot(&args[1].duals(), &args[0].duals());
+                let k = Dual::cst(1.0) - eta * eta * (Dual::cst(1.0) - d * d);
+                if k.v < 0.0 {
+                    args[0].map( | _ | Dual::default())
+                }
+                else {
+                    args[0].zip(&args[1], | i, n | eta * i - (eta * d + k.sqrt()) * n)
+                }
+            }
+            live_id!(transpose) => match &args[0] {
+                Value::Mat(n, m) => {
+                    let mut out = [Dual::default(); 16];
+                    for col in 0..*n {
+                        for row in 0..*n {
+                            out[row * n + col] = m[col * n + row];
+                        }
+                    }
+                    Value::Mat(*n, out)
+                }
+                other => other.clone()
+            }
+            live_id!(inverse) => match &args[0] {
+                Value::Mat(n, m) => Value::Mat(*n, invert(*n, m)),
+                other => other.clone()
+            }
+            live_id!(equal) => args[0].compare(&args[1], | a, b | a == b),
+            live_id!(notEqual) => args[0].compare(&args[1], | a, b | a != b),
+            live_id!(lessThan) => args[0].compare(&args[1], | a, b | a < b),
+            live_id!(lessThanEqual) => args[0].compare(&args[1], | a, b | a <= b),
+            live_id!(greaterThan) => args[0].compare(&args[1], | a, b | a > b),
+            live_id!(greaterThanEqual) => args[0].compare(&args[1], | a, b | a >= b),
+            live_id!(all) => Value::bools(&[args[0].bool_comps().iter().all( | b | *b)]),
+            live_id!(any) => Value::bools(&[args[0].bool_comps().iter().any( | b | *b)]),
+            live_id!(not) => Value::bools(&args[0].bool_comps().iter().map( | b | !*b).collect::<Vec<_ >> ()),
+            // render targets are kept top down, so these sample the same way
+            live_id!(sample2d) | live_id!(sample2d_rt) | live_id!(sample2dOES) => match &args[0] {
+                Value::Texture(slot) => Value::vec4(self.sampler.sample_2d(*slot, args[1].as_vec2())),
+                _ => Value::vec4(Vec4::default())
+            }
+            _ => Value::Void
+        }
+    }
+}
+
+fn dot(a: &[Dual], b: &[Dual]) -> Dual {
+    a.iter().zip(b.iter()).fold(Dual::default(), | sum, (a, b) | sum + *a * *b)
+}
+
+// Gauss-Jordan on the values, derivatives of inverted matrices are not tracked
+fn invert(n: usize, m: &[Dual; 16]) -> [Dual; 16] {
+    let mut a = [[0f32; 8]; 4];
+    for row in 0..n {
+        for col in 0..n {
+            a[row][col] = m[col * n + row].v;
+        }
+        a[row][n + row] = 1.0;
+    }
+    for col in 0..n {
+        let pivot = (col..n).max_by(| x, y | a[*x][col].abs().total_cmp(&a[*y][col].abs())).unwrap();
+        a.swap(col, pivot);
+        let p = a[col][col];
+        if p == 0.0 {
+            return [Dual::default(); 16]
+        }
+        for v in &mut a[col][..2 * n] {
+            *v /= p;
+        }
+        for row in 0..n {
+            if row != col {
+                let f = a[row][col];
+                let pivot_row = a[col];
+                for (v, p) in a[row][..2 * n].iter_mut().zip(pivot_row) {
+                    *v -= f * p;
+                }
+            }
+        }
+    }
+    let mut out = [Dual::default(); 16];
+    for row in 0..n {
+        for col in 0..n {
+            out[col * n + row] = Dual::cst(a[row][n + col]);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duals_carry_screen_space_derivatives() {
+        // a varying that grows 0.5 per pixel in x and 2 per pixel in y
+        let p = Dual::new(3.0, 0.5, 2.0);
+        let q = p * p + Dual::cst(1.0);
+        assert_eq!((q.v, q.dx, q.dy), (10.0, 3.0, 12.0));
+
+        let len = Value::floats(&[p, Dual::cst(4.0)]);
+        let len = match len.map(Dual::abs) {
+            Value::Float(2, v) => dot(&v[..2], &v[..2]).sqrt(),
+            _ => unreachable!()
+        };
+        assert_eq!(len.v, 5.0);
+        assert!((len.dx - 0.3).abs() < 1e-6);
+
+        let s = Dual::smoothstep(Dual::cst(0.0), Dual::cst(1.0), Dual::new(0.5, 1.0, 0.0));
+        assert_eq!((s.v, s.dx), (0.5, 1.5));
+    }
+
+    #[test]
+    fn matrices_are_column_major() {
+        let translate = Value::Mat(4, {
+            let mut m = [Dual::default(); 16];
+            for i in 0..4 {
+                m[i * 4 + i] = Dual::cst(1.0);
+            }
+            m[12] = Dual::cst(10.0);
+            m
+        });
+        let p = Value::floats(&[Dual::cst(1.0), Dual::cst(2.0), Dual::cst(3.0), Dual::cst(1.0)]);
+        let moved = ShaderInterpreter::arith(BinOp::Mul, &translate, &p).as_vec4();
+        assert_eq!((moved.x, moved.y, moved.z, moved.w), (11.0, 2.0, 3.0, 1.0));
+
+        let back = match translate {
+            Value::Mat(n, m) => ShaderInterpreter::arith(BinOp::Mul, &Value::Mat(n, invert(n, &m)), &Value::vec4(moved)),
+            _ => unreachable!()
+        };
+        assert_eq!(back.as_vec4().x, 1.0);
+    }
+}
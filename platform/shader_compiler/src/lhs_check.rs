@@ -150,7 +150,7 @@ impl<'a> LhsChecker<'a> {
                         Err(LiveError {
                             origin:live_error_origin!(),
                             span:span.into(),
-                            message: String::from("Can only assign to varying values for shader self"),
+                            message: String::from("Can only assign to varying values or buffer elements for shader self"),
                         })
                     }
                 }
@@ -167,6 +167,15 @@ impl<'a> LhsChecker<'a> {
         expr: &Expr,
         _index_expr: &Expr,
     ) -> Result<(), LiveError> {
+        // the elements of compute shader buffers are writable, the buffer itself is not
+        if let ExprKind::Field {expr: ref base, field_ident, ..} = expr.kind {
+            if let Some(Ty::DrawShader(shader_ptr)) = base.ty.borrow().as_ref() {
+                let field_decl = self.shader_registry.draw_shader_defs.get(shader_ptr).unwrap().find_field(field_ident).unwrap();
+                if let DrawShaderFieldKind::Buffer {..} = field_decl.kind {
+                    return Ok(())
+                }
+            }
+        }
         self.lhs_check_expr(expr)
     }
     
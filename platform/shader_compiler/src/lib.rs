@@ -3,6 +3,7 @@
 pub mod shader_parser;
 pub mod shader_ast;
 pub mod shader_registry;
+pub mod interpret;
 //pub mod env;
 pub mod analyse;
 pub mod builtin;
@@ -14,12 +15,12 @@ pub mod lhs_check;
 pub mod swizzle;
 pub mod util;
 pub mod generate;
+pub mod generate_wgsl;
 
 #[cfg(any(target_os = "android", target_os = "linux", target_arch = "wasm32"))]
 pub mod generate_glsl;
-#[cfg(any(target_os = "macos", target_os="ios", target_os="tvos"))]
+// built on every target, like generate_wgsl, so their output can be tested anywhere
 pub mod generate_metal;
-#[cfg(any(target_os = "windows"))]
 pub mod generate_hlsl;
 
 pub use makepad_live_compiler;
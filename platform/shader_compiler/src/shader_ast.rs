@@ -90,6 +90,10 @@ pub struct DrawShaderDef {
     pub all_structs: RefCell<Vec<StructPtr >>,
     pub vertex_structs: RefCell<Vec<StructPtr >>,
     pub pixel_structs: RefCell<Vec<StructPtr >>,
+    // compute shaders have a `compute` method instead of `vertex` and `pixel`
+    pub workgroup_size: Option<[u32; 3]>,
+    pub compute_fns: RefCell<Vec<FnPtr >>,
+    pub compute_structs: RefCell<Vec<StructPtr >>,
     // ok these 2 things dont belong here
     //pub const_table: DrawShaderConstTable,
     //pub var_inputs: RefCell<DrawShaderVarInputs>
@@ -135,6 +139,10 @@ pub enum DrawShaderFieldKind {
     },
     Varying {
         var_def_ptr: VarDefPtr,
+    },
+    // a read/write storage buffer of a compute shader, typed as a runtime sized array
+    Buffer {
+        var_def_ptr: Option<VarDefPtr>,
     }
 }
 
@@ -181,6 +189,7 @@ pub enum HiddenArgKind {
     Textures,
     Uniform(Ident),
     LiveUniforms,
+    Buffers,
 }
 
 #[derive(Clone, Debug)]
@@ -774,6 +783,10 @@ impl FnDef {
 
 impl DrawShaderDef {
     
+    pub fn is_compute(&self) -> bool {
+        self.workgroup_size.is_some()
+    }
+    
     pub fn find_field(&self, ident: Ident) -> Option<&DrawShaderFieldDef> {
         self.fields.iter().find( | decl | {
             decl.ident == ident
@@ -1206,8 +1219,8 @@ impl TyLit {
             live_id!(bvec2) => Some(TyLit::Bvec2),
             live_id!(bvec3) => Some(TyLit::Bvec3),
             live_id!(bvec4) => Some(TyLit::Bvec4),
-            live_id!(ivec2) => Some(TyLit::Ivec4),
-            live_id!(ivec3) => Some(TyLit::Ivec4),
+            live_id!(ivec2) => Some(TyLit::Ivec2),
+            live_id!(ivec3) => Some(TyLit::Ivec3),
             live_id!(ivec4) => Some(TyLit::Ivec4),
             live_id!(texture2D) => Some(TyLit::Texture2D),
             _ => None
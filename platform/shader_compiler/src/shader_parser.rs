@@ -1021,7 +1021,7 @@ impl<'a> ShaderParser<'a> {
                         })
                     }
                 }
-                LiveToken::Close(Delim::Bracket) => {
+                LiveToken::Open(Delim::Bracket) => {
                     self.skip_token();
                     let expr = Box::new(acc);
                     let index_expr = Box::new(self.expect_expr() ?);
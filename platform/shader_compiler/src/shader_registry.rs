@@ -2,6 +2,7 @@ use {
     std::{
         collections::{BTreeMap, HashSet, HashMap},
         cell::{Cell, RefCell},
+        rc::Rc,
     },
     crate::{
         //makepad_error_log::*,
@@ -491,21 +492,39 @@ impl ShaderRegistry {
     }
     
     // lets compile the thing
-    pub fn analyse_draw_shader<F>(&mut self, live_registry: &LiveRegistry, draw_shader_ptr: DrawShaderPtr, mut ext_self: F) -> Result<(),
+    pub fn analyse_draw_shader<F>(&mut self, live_registry: &LiveRegistry, draw_shader_ptr: DrawShaderPtr, ext_self: F) -> Result<(),
+        LiveError>
+    where F: FnMut(&LiveRegistry, &ShaderRegistry, TokenSpan, DrawShaderQuery, LiveType, &mut DrawShaderDef)
+    {
+        self.analyse_shader_def(live_registry, draw_shader_ptr, false, ext_self)
+    }
+    
+    // compute shaders share the draw shader body syntax, but declare storage buffers and
+    // a workgroup size instead of geometry, instances and varyings
+    pub fn analyse_compute_shader(&mut self, live_registry: &LiveRegistry, compute_shader_ptr: DrawShaderPtr) -> Result<(), LiveError> {
+        self.analyse_shader_def(live_registry, compute_shader_ptr, true, | _, _, _, _, _, _ | {})
+    }
+    
+    fn analyse_shader_def<F>(&mut self, live_registry: &LiveRegistry, draw_shader_ptr: DrawShaderPtr, is_compute: bool, mut ext_self: F) -> Result<(),
         LiveError>
     where F: FnMut(&LiveRegistry, &ShaderRegistry, TokenSpan, DrawShaderQuery, LiveType, &mut DrawShaderDef)
     {
         let mut draw_shader_def = DrawShaderDef::default();
         
-        // lets insert the 2D drawshader uniforms
-        draw_shader_def.add_uniform(id_lut!(camera_projection), id_lut!(pass), Ty::Mat4, TokenSpan::default());
-        draw_shader_def.add_uniform(id_lut!(camera_view), id_lut!(pass), Ty::Mat4, TokenSpan::default());
-        draw_shader_def.add_uniform(id_lut!(camera_inv), id_lut!(pass), Ty::Mat4, TokenSpan::default());
-        draw_shader_def.add_uniform(id_lut!(dpi_factor), id_lut!(pass), Ty::Float, TokenSpan::default());
-        draw_shader_def.add_uniform(id_lut!(dpi_dilate), id_lut!(pass), Ty::Float, TokenSpan::default());
-        draw_shader_def.add_uniform(id_lut!(time), id_lut!(pass), Ty::Float, TokenSpan::default());
-        draw_shader_def.add_uniform(id_lut!(view_transform), id_lut!(view), Ty::Mat4, TokenSpan::default());
-        draw_shader_def.add_uniform(id_lut!(draw_zbias), id_lut!(draw), Ty::Float, TokenSpan::default());
+        if is_compute {
+            draw_shader_def.workgroup_size = Some([64, 1, 1]);
+        }
+        else {
+            // lets insert the 2D drawshader uniforms
+            draw_shader_def.add_uniform(id_lut!(camera_projection), id_lut!(pass), Ty::Mat4, TokenSpan::default());
+            draw_shader_def.add_uniform(id_lut!(camera_view), id_lut!(pass), Ty::Mat4, TokenSpan::default());
+            draw_shader_def.add_uniform(id_lut!(camera_inv), id_lut!(pass), Ty::Mat4, TokenSpan::default());
+            draw_shader_def.add_uniform(id_lut!(dpi_factor), id_lut!(pass), Ty::Float, TokenSpan::default());
+            draw_shader_def.add_uniform(id_lut!(dpi_dilate), id_lut!(pass), Ty::Float, TokenSpan::default());
+            draw_shader_def.add_uniform(id_lut!(time), id_lut!(pass), Ty::Float, TokenSpan::default());
+            draw_shader_def.add_uniform(id_lut!(view_transform), id_lut!(view), Ty::Mat4, TokenSpan::default());
+            draw_shader_def.add_uniform(id_lut!(draw_zbias), id_lut!(draw), Ty::Float, TokenSpan::default());
+        }
         
         let (doc, class_node) = live_registry.ptr_to_doc_node(draw_shader_ptr.0);
 
@@ -572,6 +591,30 @@ impl ShaderRegistry {
                             };
                             let ty_expr = ty.to_ty_expr();
                             match before {
+                                Some(live_id!(geometry)) | Some(live_id!(instance)) | Some(live_id!(varying)) if is_compute => {
+                                    return Err(LiveError {
+                                        origin: live_error_origin!(),
+                                        span: first_def.into(),
+                                        message: format!("Compute shaders can't have {:?} fields", before)
+                                    })
+                                }
+                                Some(live_id!(buffer)) if !is_compute => {
+                                    return Err(LiveError {
+                                        origin: live_error_origin!(),
+                                        span: first_def.into(),
+                                        message: "Only compute shaders can have buffer fields".to_string()
+                                    })
+                                }
+                                Some(live_id!(buffer)) => {
+                                    draw_shader_def.fields.push(DrawShaderFieldDef {
+                                        kind: DrawShaderFieldKind::Buffer {
+                                            var_def_ptr: Some(VarDefPtr(prop_ptr)),
+                                        },
+                                        span: first_def.into(),
+                                        ident: Ident(prop.id),
+                                        ty_expr: Ty::Array {elem_ty: Rc::new(ty), len: 0}.to_ty_expr()
+                                    });
+                                }
                                 Some(live_id!(geometry)) => {
                                     draw_shader_def.fields.push(DrawShaderFieldDef {
                                         kind: DrawShaderFieldKind::Geometry {
@@ -642,6 +685,21 @@ impl ShaderRegistry {
                                         ty_expr
                                     });
                                 }
+                                None if is_compute && prop.id == live_id!(workgroup_size) => {
+                                    let size = match prop.value {
+                                        LiveValue::Int64(x) => [x, 1, 1],
+                                        LiveValue::Vec3(v) => [v.x as i64, v.y as i64, v.z as i64],
+                                        _ => [0, 0, 0]
+                                    };
+                                    if size.iter().any( | v | *v < 1 || *v > 1024) {
+                                        return Err(LiveError {
+                                            origin: live_error_origin!(),
+                                            span: first_def.into(),
+                                            message: "workgroup_size must be an int or a vec3 with components in 1..=1024".to_string()
+                                        })
+                                    }
+                                    draw_shader_def.workgroup_size = Some(size.map( | v | v as u32));
+                                },
                                 None => {
                                     if let LiveValue::Bool(val) = prop.value {
                                         match prop.id {
@@ -768,7 +826,16 @@ impl ShaderRegistry {
                 
                 self.draw_shader_defs.insert(draw_shader_ptr, draw_shader_def);
                 
-                if !method_set.contains(&live_id!(vertex)) {
+                if is_compute {
+                    if !method_set.contains(&live_id!(compute)) {
+                        return Err(LiveError {
+                            origin: live_error_origin!(),
+                            span: class_node.origin.token_id().unwrap().into(),
+                            message: "analyse_compute_shader missing compute method".to_string()
+                        })
+                    }
+                }
+                else if !method_set.contains(&live_id!(vertex)) {
                     return Err(LiveError {
                         origin: live_error_origin!(),
                         span: class_node.origin.token_id().unwrap().into(),
@@ -776,7 +843,7 @@ impl ShaderRegistry {
                     })
                 }
                 
-                if !method_set.contains(&live_id!(pixel)) {
+                if !is_compute && !method_set.contains(&live_id!(pixel)) {
                     return Err(LiveError {
                         origin: live_error_origin!(),
                         span: class_node.origin.token_id().unwrap().into(),
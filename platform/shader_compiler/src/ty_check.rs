@@ -784,6 +784,7 @@ impl<'a> TyChecker<'a> {
             Ty::Mat2 => Ty::Vec2,
             Ty::Mat3 => Ty::Vec3,
             Ty::Mat4 => Ty::Vec4,
+            Ty::Array {ref elem_ty, ..} => (**elem_ty).clone(),
             _ => {
                 return Err(LiveError {
                     origin: live_error_origin!(),
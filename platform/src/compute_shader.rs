@@ -0,0 +1,426 @@
+use {
+    std::{
+        rc::Rc,
+        ops::{Index, IndexMut},
+        collections::{
+            HashMap,
+            HashSet,
+            BTreeSet,
+        },
+    },
+    crate::{
+        makepad_live_compiler::{
+            LivePtr,
+            LiveTypeInfo,
+            LiveModuleId,
+            LiveNode,
+            LiveNodeSliceApi
+        },
+        makepad_live_tokenizer::{LiveErrorOrigin, live_error_origin},
+        makepad_shader_compiler::*,
+        makepad_live_id::*,
+        id_pool::*,
+        cx::Cx,
+        texture::Texture,
+        live_traits::*,
+        draw_vars::{DrawVars, DRAW_CALL_USER_UNIFORMS, DRAW_CALL_TEXTURE_SLOTS},
+        draw_shader::{CxDrawShaderMapping, DRAW_SHADER_INPUT_PACKING},
+        os::{CxOsComputeShader, CxOsComputeBuffer},
+    }
+};
+
+pub const COMPUTE_BUFFER_SLOTS: usize = 4;
+
+/// A GPU storage buffer of floats that compute shaders read and write, and that can be
+/// drawn as instance data or copied into a texture.
+#[derive(Clone)]
+pub struct ComputeBuffer(Rc<PoolId>);
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Copy)]
+pub struct ComputeBufferId(pub (crate) usize, u64);
+
+impl ComputeBuffer {
+    /// A zeroed buffer of `len` floats.
+    pub fn new(cx: &mut Cx, len: usize) -> Self {
+        Self::new_with_data(cx, vec![0.0; len])
+    }
+
+    pub fn new_with_data(cx: &mut Cx, data: Vec<f32>) -> Self {
+        cx.compute_buffers.alloc(data)
+    }
+
+    pub fn compute_buffer_id(&self) -> ComputeBufferId {ComputeBufferId(self.0.id, self.0.generation)}
+
+    pub fn len(&self, cx: &Cx) -> usize {
+        cx.compute_buffers[self.compute_buffer_id()].data.len()
+    }
+
+    /// Replaces the contents from the CPU, they are uploaded before the buffer is next used.
+    pub fn set_data(&self, cx: &mut Cx, data: Vec<f32>) {
+        let cx_buffer = &mut cx.compute_buffers[self.compute_buffer_id()];
+        cx_buffer.data = data;
+        cx_buffer.updated = true;
+    }
+}
+
+#[derive(Default)]
+pub struct CxComputeBuffer {
+    /// The CPU side contents, on GPU backends these go stale once a compute shader writes the buffer
+    pub data: Vec<f32>,
+    pub (crate) updated: bool,
+    pub os: CxOsComputeBuffer,
+}
+
+#[derive(Default)]
+pub struct CxComputeBufferPool(pub (crate) IdPool<CxComputeBuffer>);
+
+impl CxComputeBufferPool {
+    pub fn alloc(&mut self, data: Vec<f32>) -> ComputeBuffer {
+        // reusing a freed buffer keeps its os buffer around to upload into
+        let new_id = self.0.alloc();
+        let cx_buffer = &mut self.0.pool[new_id.id].item;
+        cx_buffer.data = data;
+        cx_buffer.updated = true;
+        ComputeBuffer(Rc::new(new_id))
+    }
+}
+
+impl Index<ComputeBufferId> for CxComputeBufferPool {
+    type Output = CxComputeBuffer;
+    fn index(&self, index: ComputeBufferId) -> &Self::Output {
+        let d = &self.0.pool[index.0];
+        if d.generation != index.1 {
+            error!("ComputeBuffer id generation wrong {} {} {}", index.0, d.generation, index.1)
+        }
+        &d.item
+    }
+}
+
+impl IndexMut<ComputeBufferId> for CxComputeBufferPool {
+    fn index_mut(&mut self, index: ComputeBufferId) -> &mut Self::Output {
+        let d = &mut self.0.pool[index.0];
+        if d.generation != index.1 {
+            error!("ComputeBuffer id generation wrong {} {} {}", index.0, d.generation, index.1)
+        }
+        &mut d.item
+    }
+}
+
+#[derive(Clone)]
+pub struct ComputeShaderBufferInput {
+    pub id: LiveId,
+    pub elem_ty: ShaderTy,
+}
+
+pub struct CxComputeShader {
+    pub class_prop: LiveId,
+    pub compute_shader_ptr: DrawShaderPtr,
+    pub workgroup_size: [u32; 3],
+    pub mapping: CxDrawShaderMapping,
+    /// The storage buffers in declaration order, which is also their binding order
+    pub buffers: Vec<ComputeShaderBufferInput>,
+    pub os: CxOsComputeShader,
+}
+
+pub struct CxComputeDispatch {
+    pub compute_shader_id: usize,
+    pub groups: [u32; 3],
+    pub user_uniforms: [f32; DRAW_CALL_USER_UNIFORMS],
+    pub texture_slots: [Option<Texture>; DRAW_CALL_TEXTURE_SLOTS],
+    pub buffer_slots: [Option<ComputeBuffer>; COMPUTE_BUFFER_SLOTS],
+}
+
+/// Compute work runs in queue order, before the passes of the frame that queued it.
+pub enum CxComputeOp {
+    Dispatch(CxComputeDispatch),
+    CopyToTexture {buffer: ComputeBuffer, texture: Texture},
+}
+
+#[derive(Default)]
+pub struct CxComputeShaders {
+    pub shaders: Vec<CxComputeShader>,
+    pub generation: u64,
+    pub ptr_to_id: HashMap<DrawShaderPtr, usize>,
+    pub compile_set: BTreeSet<DrawShaderPtr>,
+    pub error_set: HashSet<DrawShaderPtr>,
+    pub ops: Vec<CxComputeOp>,
+    pub (crate) warned_unsupported: bool,
+}
+
+impl CxComputeShaders {
+    pub fn reset_for_live_reload(&mut self) {
+        self.ptr_to_id.clear();
+        self.error_set.clear();
+    }
+}
+
+impl Index<usize> for CxComputeShaders {
+    type Output = CxComputeShader;
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.shaders[index]
+    }
+}
+
+impl IndexMut<usize> for CxComputeShaders {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.shaders[index]
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct ComputeShaderRef {
+    pub compute_shader_generation: u64,
+    pub compute_shader_id: usize,
+    pub compute_shader_ptr: DrawShaderPtr
+}
+
+/// The compute counterpart of `DrawVars`, deref a live struct to it to declare a compute shader.
+#[derive(Default)]
+pub struct ComputeVars {
+    pub compute_shader: Option<ComputeShaderRef>,
+    pub user_uniforms: [f32; DRAW_CALL_USER_UNIFORMS],
+    pub texture_slots: [Option<Texture>; DRAW_CALL_TEXTURE_SLOTS],
+    pub buffer_slots: [Option<ComputeBuffer>; COMPUTE_BUFFER_SLOTS],
+}
+
+impl LiveHookDeref for ComputeVars{}
+
+impl LiveNew for ComputeVars {
+    fn new(_cx: &mut Cx) -> Self {
+        Self::default()
+    }
+
+    fn live_type_info(_cx: &mut Cx) -> LiveTypeInfo {
+        LiveTypeInfo {
+            module_id: LiveModuleId::from_str(module_path!()).unwrap(),
+            live_type: std::any::TypeId::of::<Self>(),
+            live_ignore: true,
+            fields: Vec::new(),
+            type_name: id_lut!(ComputeVars)
+        }
+    }
+}
+
+impl LiveApply for ComputeVars {
+    fn apply(&mut self, _cx: &mut Cx, _applyl: &mut Apply, _index: usize, _nodes: &[LiveNode]) -> usize {
+        panic!()
+    }
+}
+
+impl LiveHook for ComputeVars {}
+
+impl ComputeVars {
+    pub fn set_texture(&mut self, slot: usize, texture: &Texture) {
+        self.texture_slots[slot] = Some(texture.clone());
+    }
+
+    pub fn empty_texture(&mut self, slot: usize) {
+        self.texture_slots[slot] = None;
+    }
+
+    /// Binds a buffer to the storage buffer field declared at position `slot`.
+    pub fn set_buffer(&mut self, slot: usize, buffer: &ComputeBuffer) {
+        self.buffer_slots[slot] = Some(buffer.clone());
+    }
+
+    pub fn empty_buffer(&mut self, slot: usize) {
+        self.buffer_slots[slot] = None;
+    }
+
+    pub fn can_dispatch(&self) -> bool {
+        self.compute_shader.is_some()
+    }
+
+    pub fn workgroup_size(&self, cx: &Cx) -> Option<[u32; 3]> {
+        self.compute_shader.map( | cs | cx.compute_shaders[cs.compute_shader_id].workgroup_size)
+    }
+
+    /// Queues `groups` workgroups, the compute method runs once per workgroup size in every one.
+    pub fn dispatch(&self, cx: &mut Cx, groups: [u32; 3]) {
+        if let Some(compute_shader) = self.compute_shader {
+            if compute_shader.compute_shader_generation != cx.compute_shaders.generation {
+                return
+            }
+            cx.compute_shaders.ops.push(CxComputeOp::Dispatch(CxComputeDispatch {
+                compute_shader_id: compute_shader.compute_shader_id,
+                groups,
+                user_uniforms: self.user_uniforms,
+                texture_slots: self.texture_slots.clone(),
+                buffer_slots: self.buffer_slots.clone(),
+            }));
+        }
+    }
+
+    /// Queues enough workgroups to cover `invocations`, the compute method has to skip the
+    /// ids past the end itself.
+    pub fn dispatch_invocations(&self, cx: &mut Cx, invocations: [u32; 3]) {
+        if let Some([x, y, z]) = self.workgroup_size(cx) {
+            let groups = [invocations[0].div_ceil(x), invocations[1].div_ceil(y), invocations[2].div_ceil(z)];
+            self.dispatch(cx, groups);
+        }
+    }
+
+    pub fn set_uniform(&mut self, cx: &Cx, uniform: &[LiveId], value: &[f32]) {
+        if let Some(compute_shader) = self.compute_shader {
+            let sh = &cx.compute_shaders[compute_shader.compute_shader_id];
+            for input in &sh.mapping.user_uniforms.inputs {
+                let offset = input.offset;
+                let slots = input.slots;
+                if input.id == uniform[0] {
+                    let len = value.len().min(slots);
+                    self.user_uniforms[offset..offset + len].copy_from_slice(&value[..len]);
+                }
+            }
+        }
+    }
+
+    pub fn init_shader(&mut self, cx: &mut Cx, apply: &mut Apply, compute_shader_ptr: DrawShaderPtr) {
+        self.compute_shader = None;
+
+        if cx.compute_shaders.error_set.contains(&compute_shader_ptr) {
+            return
+        }
+
+        if let Some(compute_shader_id) = cx.compute_shaders.ptr_to_id.get(&compute_shader_ptr) {
+            self.compute_shader = Some(ComputeShaderRef {
+                compute_shader_generation: cx.compute_shaders.generation,
+                compute_shader_ptr,
+                compute_shader_id: *compute_shader_id
+            });
+            return
+        }
+
+        let live_registry_cp = cx.live_registry.clone();
+        let live_registry = live_registry_cp.borrow();
+
+        match cx.shader_registry.analyse_compute_shader(&live_registry, compute_shader_ptr) {
+            Err(e) => {
+                cx.compute_shaders.error_set.insert(compute_shader_ptr);
+                let err = live_registry.live_error_to_live_file_error(e);
+                if std::env::args().find(|v| v == "--message-format=json").is_some(){
+                    crate::log::log_with_level(
+                        &err.file,
+                        err.span.start.line,
+                        err.span.start.column,
+                        err.span.end.line,
+                        err.span.end.column,
+                        err.message,
+                        crate::log::LogLevel::Error
+                    );
+                }
+                else{
+                    log!("Error {}", err);
+                }
+            }
+            Ok(()) => {
+                let compute_shader_id = cx.compute_shaders.shaders.len();
+                let const_table = cx.shader_registry.compute_const_table(compute_shader_ptr);
+                let compute_shader_def = cx.shader_registry.draw_shader_defs.get(&compute_shader_ptr).unwrap();
+
+                let mut buffers = Vec::new();
+                for field in &compute_shader_def.fields {
+                    if let DrawShaderFieldKind::Buffer {..} = field.kind {
+                        if let Some(ShaderTy::Array {elem_ty, ..}) = field.ty_expr.ty.borrow().as_ref() {
+                            buffers.push(ComputeShaderBufferInput {
+                                id: field.ident.0,
+                                elem_ty: (**elem_ty).clone()
+                            });
+                        }
+                    }
+                }
+                if buffers.len() > COMPUTE_BUFFER_SLOTS {
+                    cx.compute_shaders.error_set.insert(compute_shader_ptr);
+                    log!("Error compute shaders can have at most {} buffers", COMPUTE_BUFFER_SLOTS);
+                    return
+                }
+                let workgroup_size = compute_shader_def.workgroup_size.unwrap();
+                let mut mapping = CxDrawShaderMapping::from_draw_shader_def(
+                    compute_shader_def,
+                    const_table,
+                    DRAW_SHADER_INPUT_PACKING
+                );
+                mapping.update_live_and_user_uniforms(cx, apply);
+
+                let class_node = live_registry.ptr_to_node(compute_shader_ptr.0);
+                cx.compute_shaders.shaders.push(CxComputeShader {
+                    class_prop: class_node.id,
+                    compute_shader_ptr,
+                    workgroup_size,
+                    mapping,
+                    buffers,
+                    os: CxOsComputeShader::default(),
+                });
+                cx.compute_shaders.ptr_to_id.insert(compute_shader_ptr, compute_shader_id);
+                cx.compute_shaders.compile_set.insert(compute_shader_ptr);
+                self.compute_shader = Some(ComputeShaderRef {
+                    compute_shader_generation: cx.compute_shaders.generation,
+                    compute_shader_id,
+                    compute_shader_ptr
+                });
+            }
+        }
+    }
+
+    pub fn before_apply_init_shader(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, _nodes: &[LiveNode]) {
+        let compute_shader_ptr = if let Some(file_id) = apply.from.file_id() {
+            let generation = cx.live_registry.borrow().file_id_to_file(file_id).generation;
+            DrawShaderPtr(LivePtr::from_index(file_id, index, generation))
+        }
+        else {
+            return
+        };
+        self.init_shader(cx, apply, compute_shader_ptr)
+    }
+
+    pub fn apply_value(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) -> usize {
+        if nodes[index].origin.node_has_prefix() {
+            return nodes.skip_node(index)
+        }
+
+        if let Some(compute_shader) = self.compute_shader {
+            let id = nodes[index].id;
+            if compute_shader.compute_shader_generation != cx.compute_shaders.generation {
+                return nodes.skip_node(index);
+            }
+            let sh = &cx.compute_shaders[compute_shader.compute_shader_id];
+            for input in &sh.mapping.user_uniforms.inputs {
+                let offset = input.offset;
+                let slots = input.slots;
+                if input.id == id {
+                    return DrawVars::apply_slots(cx, slots, &mut self.user_uniforms, offset, apply, index, nodes);
+                }
+            }
+        }
+        else { // our shader simply didnt compile
+            return nodes.skip_node(index);
+        }
+
+        let unknown_shader_props = !matches!(nodes[index].id, live_id!(workgroup_size) | live_id!(debug));
+
+        if unknown_shader_props && nodes[index].value.is_value_type() {
+            cx.apply_error_no_matching_field(live_error_origin!(), index, nodes);
+        }
+        nodes.skip_node(index)
+    }
+}
+
+impl Cx {
+    /// Queues a copy of a buffer into a `TextureFormat::VecRGBAf32` texture of the same size,
+    /// four floats per pixel.
+    pub fn copy_compute_buffer_to_texture(&mut self, buffer: &ComputeBuffer, texture: &Texture) {
+        self.compute_shaders.ops.push(CxComputeOp::CopyToTexture {
+            buffer: buffer.clone(),
+            texture: texture.clone()
+        });
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub (crate) fn skip_unsupported_compute_ops(&mut self, backend: &str) {
+        if !self.compute_shaders.ops.is_empty() && !self.compute_shaders.warned_unsupported {
+            self.compute_shaders.warned_unsupported = true;
+            log!("Compute shaders are not supported on {} yet", backend);
+        }
+        self.compute_shaders.ops.clear();
+        self.compute_shaders.compile_set.clear();
+    }
+}
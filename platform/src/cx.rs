@@ -18,6 +18,8 @@ use {
         },
         makepad_shader_compiler::ShaderRegistry,
         draw_shader::CxDrawShaders,
+        compute_shader::{CxComputeShaders, CxComputeBufferPool},
+        readback::CxReadbacks,
         draw_matrix::CxDrawMatrixPool,
         os::{CxOs},
         debug::Debug,
@@ -28,11 +30,13 @@ use {
             CxDragDrop,
             Event,
             Trigger,
+            AccessActionEvent,
             CxKeyboard,
             NextFrame,
         },
         action::ActionsBuf,
         cx_api::CxOsOp,
+        file_dialogs::FileDialogResultEvent,
         area::Area,
         gpu_info::GpuInfo,
         window::CxWindowPool,
@@ -70,6 +74,9 @@ pub struct Cx {
     
     pub draw_shaders: CxDrawShaders,
     
+    pub compute_shaders: CxComputeShaders,
+    pub compute_buffers: CxComputeBufferPool,
+    
     pub (crate) new_draw_event: DrawEvent,
     
     pub redraw_id: u64,
@@ -90,6 +97,12 @@ pub struct Cx {
     
     pub (crate) new_actions: ActionsBuf,
     
+    pub (crate) file_dialog_results: Vec<FileDialogResultEvent>,
+    
+    pub (crate) access_actions: Vec<AccessActionEvent>,
+    
+    pub (crate) readbacks: CxReadbacks,
+    
     pub (crate) dependencies: HashMap<String, CxDependency>,
     
     pub (crate) triggers: HashMap<Area, Vec<Trigger >>,
@@ -227,9 +240,14 @@ impl Cx {
             geometries_refs: Default::default(),
             
             draw_shaders: Default::default(),
+            compute_shaders: Default::default(),
+            compute_buffers: Default::default(),
             
             new_draw_event: Default::default(),
             new_actions: Default::default(),
+            file_dialog_results: Default::default(),
+            access_actions: Default::default(),
+            readbacks: Default::default(),
             
             start_time: Instant::now(),
             
@@ -1,4 +1,4 @@
-use crate::file_dialogs::FileDialog;
+use crate::file_dialogs::{FileDialog, FileDialogKind, FileDialogResultEvent};
 
 use {
     crate::{
@@ -6,7 +6,7 @@ use {
         cursor::MouseCursor,
         cx::{Cx, CxRef, OsType, XrCapabilities},
         draw_list::DrawListId,
-        event::{DragItem, HttpRequest, NextFrame, Timer, Trigger, VideoSource},
+        event::{AccessAction, AccessActionEvent, DragItem, HttpRequest, NextFrame, Timer, Trigger, VideoSource},
         gpu_info::GpuInfo,
         macos_menu::MacosMenu,
         makepad_futures::executor::Spawner,
@@ -18,6 +18,7 @@ use {
     },
     std::{
         any::{Any, TypeId},
+        path::PathBuf,
         rc::Rc,
     },
 };
@@ -425,6 +426,12 @@ impl Cx {
         }
     }
 
+    /// Asks the widget drawn at `area` to perform an accessibility action. It arrives as
+    /// an `Event::AccessAction` once the current event has been handled.
+    pub fn send_access_action(&mut self, area: Area, action: AccessAction) {
+        self.access_actions.push(AccessActionEvent {area, action});
+    }
+
     pub fn set_global<T: 'static + Any + Sized>(&mut self, value: T) {
         if !self.globals.iter().any(|v| v.0 == TypeId::of::<T>()) {
             self.globals.push((TypeId::of::<T>(), Box::new(value)));
@@ -544,6 +551,21 @@ impl Cx {
         self.platform_ops.push(CxOsOp::SelectFolderDialog(FileDialog::new()));
 
     }
+    
+    pub fn open_file_dialog(&mut self, kind: FileDialogKind, dialog: FileDialog) {
+        self.platform_ops.push(match kind {
+            FileDialogKind::SaveFile => CxOsOp::SaveFileDialog(dialog),
+            FileDialogKind::SelectFile => CxOsOp::SelectFileDialog(dialog),
+            FileDialogKind::SaveFolder => CxOsOp::SaveFolderDialog(dialog),
+            FileDialogKind::SelectFolder => CxOsOp::SelectFolderDialog(dialog),
+        });
+    }
+    
+    /// Reports how a file dialog was closed, for dialogs that are drawn by the application. It
+    /// arrives as an `Event::FileDialogResult` once the current event has been handled.
+    pub fn file_dialog_closed(&mut self, kind: FileDialogKind, path: Option<PathBuf>) {
+        self.file_dialog_results.push(FileDialogResultEvent {kind, path});
+    }
 }
 
 #[macro_export]
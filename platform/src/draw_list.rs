@@ -22,6 +22,7 @@ use {
             DRAW_CALL_TEXTURE_SLOTS
         },
         texture::Texture,
+        compute_shader::ComputeBuffer,
         geometry::{GeometryId}
     }
 };
@@ -178,6 +179,7 @@ pub struct CxDrawCall {
     pub geometry_id: Option<GeometryId>,
     pub user_uniforms: [f32; DRAW_CALL_USER_UNIFORMS], // user uniforms
     pub texture_slots: [Option<Texture>; DRAW_CALL_TEXTURE_SLOTS],
+    pub instance_buffer: Option<ComputeBuffer>,
     pub instance_dirty: bool,
     pub uniforms_dirty: bool,
 }
@@ -192,6 +194,7 @@ impl CxDrawCall {
             draw_uniforms: DrawUniforms::default(),
             user_uniforms: draw_vars.user_uniforms,
             texture_slots: draw_vars.texture_slots.clone(),
+            instance_buffer: draw_vars.instance_buffer.clone(),
             instance_dirty: true,
             uniforms_dirty: true,
         }
@@ -308,6 +311,10 @@ impl CxDrawList {
                 let draw_item = &mut self.draw_items[i];
                 if let Some(draw_call) = &draw_item.draw_call() {
                     if draw_call.draw_shader == draw_vars.draw_shader.unwrap() {
+                        let buffer_id = | buffer: &Option<ComputeBuffer> | buffer.as_ref().map( | buffer | buffer.compute_buffer_id());
+                        if buffer_id(&draw_call.instance_buffer) != buffer_id(&draw_vars.instance_buffer) {
+                            continue
+                        }
                         // lets compare uniforms and textures..
                         if !sh.mapping.flags.draw_call_nocompare {
                             if draw_call.geometry_id != draw_vars.geometry_id {
@@ -89,6 +89,10 @@ impl Cx {
         self.draw_shaders.fingerprints.clear();
         self.draw_shaders.error_set.clear();
         self.draw_shaders.error_fingerprints.clear();
+        self.compute_shaders.generation += 1;
+        self.compute_shaders.shaders.clear();
+        self.compute_shaders.ptr_to_id.clear();
+        self.compute_shaders.error_set.clear();
     }
 }
 
@@ -18,6 +18,7 @@ use {
         makepad_math::*,
         cx::Cx,
         texture::{Texture},
+        compute_shader::ComputeBuffer,
         geometry::GeometryId,
         area::Area,
         geometry::{GeometryFields},
@@ -94,6 +95,7 @@ pub struct DrawVars {
     pub (crate) geometry_id: Option<GeometryId>,
     pub user_uniforms: [f32; DRAW_CALL_USER_UNIFORMS],
     pub texture_slots: [Option<Texture>; DRAW_CALL_TEXTURE_SLOTS],
+    pub instance_buffer: Option<ComputeBuffer>,
     pub var_instances: [f32; DRAW_CALL_VAR_INSTANCES]
 }
 
@@ -132,6 +134,17 @@ impl DrawVars {
     pub fn empty_texture(&mut self, slot: usize) {
         self.texture_slots[slot] = None;
     }
+    
+    /// Draws the instances from a compute buffer instead of the ones written from the CPU,
+    /// one instance per `instances.total_slots` floats. At least one instance still has to be
+    /// drawn to create the draw call.
+    pub fn set_instance_buffer(&mut self, buffer: &ComputeBuffer) {
+        self.instance_buffer = Some(buffer.clone());
+    }
+    
+    pub fn empty_instance_buffer(&mut self) {
+        self.instance_buffer = None;
+    }
 
     pub fn redraw(&self, cx: &mut Cx) {
         self.area.redraw(cx);
@@ -22,6 +22,8 @@ use {
         audio::AudioDevicesEvent,
         midi::MidiPortsEvent,
         video::VideoInputsEvent,
+        file_dialogs::{FileDialogRequestEvent, FileDialogResultEvent},
+        readback::ReadbackEvent,
         draw_list::DrawListId,
     },
 };
@@ -175,6 +177,10 @@ pub enum Event {
     TextureHandleReady(TextureHandleReadyEvent),
 
     BackPressed,
+    FileDialogRequest(FileDialogRequestEvent),
+    FileDialogResult(FileDialogResultEvent),
+    AccessAction(AccessActionEvent),
+    Readback(ReadbackEvent),
     #[cfg(target_arch = "wasm32")]
     ToWasmMsg(ToWasmMsgEvent),
 }
@@ -246,6 +252,10 @@ impl Event{
             48=>"MouseLeave",
             49=>"Actions",
             50=>"BackPressed",
+            52=>"FileDialogRequest",
+            53=>"FileDialogResult",
+            54=>"AccessAction",
+            55=>"Readback",
 
             #[cfg(target_arch = "wasm32")]
             51=>"ToWasmMsg",
@@ -315,6 +325,10 @@ impl Event{
             Self::MouseLeave(_)=>48,
             Self::Actions(_)=>49,
             Self::BackPressed=>50,
+            Self::FileDialogRequest(_)=>52,
+            Self::FileDialogResult(_)=>53,
+            Self::AccessAction(_)=>54,
+            Self::Readback(_)=>55,
 
             #[cfg(target_arch = "wasm32")]
             Self::ToWasmMsg(_)=>51,
@@ -329,6 +343,7 @@ pub enum Hit{
     KeyDown(KeyEvent),
     KeyUp(KeyEvent),
     Trigger(TriggerHitEvent),
+    AccessAction(AccessAction),
     TextInput(TextInputEvent),
     TextCopy(TextClipboardEvent),
     TextCut(TextClipboardEvent),
@@ -451,6 +466,23 @@ pub struct Trigger{
 #[derive(Clone, Debug, PartialEq)]
 pub struct TriggerHitEvent(pub Vec<Trigger>);
 
+/// An action an assistive technology asks a widget to perform.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AccessAction {
+    Click,
+    Focus,
+    Increment,
+    Decrement,
+    SetValue(String),
+    ScrollIntoView,
+}
+
+#[derive(Clone, Debug)]
+pub struct AccessActionEvent {
+    pub area: Area,
+    pub action: AccessAction,
+}
+
 #[derive(Clone, Debug)]
 pub struct WebSocketErrorEvent {
     pub socket_id: LiveId,
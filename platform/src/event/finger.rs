@@ -666,6 +666,11 @@ impl Event {
                     return Hit::TextCut(tc.clone());
                 }
             },
+            Event::AccessAction(e) => {
+                if e.area == area {
+                    return Hit::AccessAction(e.action.clone())
+                }
+            },
             Event::Scroll(e) => {
                 let digit_id = live_id!(mouse).into();
                 
@@ -1,17 +1,68 @@
 // mildly stripped down version of native_dialog_rs dialog interface.
-use std::path::{PathBuf};
+use std::{
+    cell::Cell,
+    path::{Path, PathBuf},
+};
 
 
 /// Represents a set of file extensions and their description.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Filter {
     pub description: String,
     pub extensions: Vec<String>,
 }
 
+impl Filter {
+    /// Returns whether the path has one of the extensions of this filter. Extensions can be given
+    /// as `rs`, `.rs` or `*.rs`, and are compared case insensitively.
+    pub fn matches(&self, path: &Path) -> bool {
+        let Some(extension) = path.extension().and_then(|extension| extension.to_str()) else {
+            return false
+        };
+        self.extensions.iter().any(|filter| {
+            filter.trim_start_matches('*').trim_start_matches('.').eq_ignore_ascii_case(extension)
+        })
+    }
+}
+
+/// The kinds of file dialog an application can ask for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FileDialogKind {
+    SaveFile,
+    SelectFile,
+    SaveFolder,
+    SelectFolder,
+}
+
+impl FileDialogKind {
+    pub fn is_save(&self) -> bool {
+        matches!(self, Self::SaveFile | Self::SaveFolder)
+    }
+
+    pub fn is_folder(&self) -> bool {
+        matches!(self, Self::SaveFolder | Self::SelectFolder)
+    }
+}
+
+/// Sent on platforms without a native file dialog, so that a window of the application can draw
+/// one instead. The first window to set `handled` shows it.
+#[derive(Debug)]
+pub struct FileDialogRequestEvent {
+    pub kind: FileDialogKind,
+    pub dialog: FileDialog,
+    pub handled: Cell<bool>,
+}
+
+/// Sent when a file dialog closes, with the chosen path, or `None` if the dialog was cancelled.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileDialogResultEvent {
+    pub kind: FileDialogKind,
+    pub path: Option<PathBuf>,
+}
+
 /// Builds and shows file dialogs.
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct FileDialog {
     pub filename: Option<String>,
     pub location: Option<PathBuf>,
@@ -81,8 +132,10 @@ impl FileDialog {
         self
     }
 
-
-
+    /// Returns whether a file passes the filters of this dialog. Without filters every file does.
+    pub fn accepts(&self, path: &Path) -> bool {
+        self.filters.is_empty() || self.filters.iter().any(|filter| filter.matches(path))
+    }
 }
 
 
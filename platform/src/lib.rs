@@ -27,6 +27,8 @@ mod draw_matrix;
 mod draw_shader; 
 mod draw_list;
 mod draw_vars;
+mod compute_shader;
+mod readback;
 
 mod id_pool;
 pub mod event;
@@ -42,6 +44,7 @@ mod geometry;
 mod debug;
 mod component_map;
 mod performance_stats;
+mod profile;
 pub mod studio;
 
 pub mod web_socket;
@@ -163,6 +166,8 @@ pub use {
             Hit,
             DragHit,
             Trigger,
+            AccessAction,
+            AccessActionEvent,
             //MidiInputListEvent,
             Timer,
             NextFrame,
@@ -210,6 +215,12 @@ pub use {
             ActionTrait
         },
         cursor::MouseCursor,
+        file_dialogs::{
+            FileDialog,
+            FileDialogKind,
+            FileDialogRequestEvent,
+            FileDialogResultEvent,
+        },
         macos_menu::MacosMenu,
         draw_matrix::DrawMatrix,
         window::WindowHandle,
@@ -256,6 +267,17 @@ pub use {
             shader_enum,
             DrawVars
         },
+        compute_shader::{
+            ComputeVars,
+            ComputeBuffer,
+            ComputeBufferId,
+        },
+        profile::ProfileScope,
+        readback::{
+            ReadbackEvent,
+            ReadbackImage,
+            ReadbackPixels,
+        },
         geometry::{
             GeometryFingerprint,
             GeometryField,
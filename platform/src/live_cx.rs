@@ -253,6 +253,7 @@ impl Cx {
                 error!("check_live_file_watcher: Error expanding live file {}", err);
             }
             self.draw_shaders.reset_for_live_reload();
+            self.compute_shaders.reset_for_live_reload();
             true
         }
         else{
@@ -124,3 +124,12 @@ macro_rules!profile_end_log {
         )
     }
 }
+
+/// Times the rest of the enclosing block as a named span in the Studio profiler, the name is
+/// formatted like `format!` and only while the Studio profiler is open.
+#[macro_export]
+macro_rules!profile_scope {
+    ( $ cx: expr, $ ( $ t: tt) *) => {
+        let _profile_scope = $ crate::ProfileScope::begin( $ cx, || format!( $ ( $ t) *));
+    }
+}
@@ -72,6 +72,7 @@ impl Cx {
         let mut passes_todo = Vec::new();
         self.compute_pass_repaint_order(&mut passes_todo);
         self.repaint_id += 1;
+        self.skip_unsupported_readbacks("Metal");
         for pass_id in &passes_todo {
             match self.passes[*pass_id].parent.clone() {
                 CxPassParent::Window(_window_id) => {
@@ -194,6 +195,7 @@ impl Cx {
                 if self.need_redrawing() {
                     self.call_draw_event();
                     self.mtl_compile_shaders(&metal_cx);
+                    self.mtl_run_compute(&metal_cx);
                 }
                 // ok here we send out to all our childprocesses
                 self.handle_repaint(metal_cx);
@@ -49,6 +49,7 @@ impl Cx {
         let mut passes_todo = Vec::new();
         self.compute_pass_repaint_order(&mut passes_todo);
         self.repaint_id += 1;
+        self.skip_unsupported_readbacks("Metal");
         for &pass_id in &passes_todo {
             self.passes[pass_id].set_time(time as f32);
             match self.passes[pass_id].parent.clone() {
@@ -230,6 +231,7 @@ impl Cx {
                     if self.need_redrawing() {
                         self.call_draw_event();
                         self.mtl_compile_shaders(metal_cx);
+                        self.mtl_run_compute(metal_cx);
                     }
 
                     let [presentable_image] = &swapchain.presentable_images;
@@ -23,6 +23,7 @@ use {
         },
         draw_list::DrawListId,
         cx::Cx,
+        compute_shader::{CxComputeOp, CxComputeBuffer},
         pass::{PassClearColor, PassClearDepth, PassId},
         studio::{AppToStudio, GPUSample},
         texture::{
@@ -109,8 +110,16 @@ impl Cx {
                     draw_call.uniforms_dirty = false;
                 }
                 
-                // lets verify our instance_offset is not disaligned
-                let instances = (draw_item.instances.as_ref().unwrap().len() / sh.mapping.instances.total_slots) as u64;
+                // instances drawn from a compute buffer replace the ones written from the CPU
+                let (instance_buffer, instances) = if let Some(buffer) = &draw_call.instance_buffer {
+                    let cxbuffer = &mut self.compute_buffers[buffer.compute_buffer_id()];
+                    cxbuffer.update_metal_buffer(metal_cx);
+                    (&cxbuffer.os.buffer, (cxbuffer.data.len() / sh.mapping.instances.total_slots) as u64)
+                }
+                else {
+                    // lets verify our instance_offset is not disaligned
+                    (draw_item.os.instance_buffer.get(), (draw_item.instances.as_ref().unwrap().len() / sh.mapping.instances.total_slots) as u64)
+                };
                 
                 if instances == 0 {
                     continue;
@@ -143,7 +152,7 @@ impl Cx {
                 }
                 else {crate::error!("Drawing error: vertex_buffer None")}
                 
-                if let Some(inner) = draw_item.os.instance_buffer.get().cpu_read().inner.as_ref() {
+                if let Some(inner) = instance_buffer.cpu_read().inner.as_ref() {
                     unsafe {msg_send![
                         encoder,
                         setVertexBuffer: inner.buffer.as_id()
@@ -247,7 +256,7 @@ impl Cx {
                 }
                 else {crate::error!("Drawing error: index_buffer None")}
                 
-                gpu_read_guards.push(draw_item.os.instance_buffer.get().gpu_read());
+                gpu_read_guards.push(instance_buffer.gpu_read());
                 gpu_read_guards.push(geometry.os.vertex_buffer.get().gpu_read());
                 gpu_read_guards.push(geometry.os.index_buffer.get().gpu_read());
             }
@@ -539,6 +548,147 @@ impl Cx {
             }
         }
         self.draw_shaders.compile_set.clear();
+        
+        for compute_shader_ptr in &self.compute_shaders.compile_set {
+            if let Some(compute_shader_id) = self.compute_shaders.ptr_to_id.get(compute_shader_ptr) {
+                let cx_shader = &mut self.compute_shaders.shaders[*compute_shader_id];
+                let compute_shader_def = self.shader_registry.draw_shader_defs.get(compute_shader_ptr);
+                let gen = generate_metal::generate_shader(
+                    compute_shader_def.as_ref().unwrap(),
+                    &cx_shader.mapping.const_table,
+                    &self.shader_registry
+                );
+                if cx_shader.mapping.flags.debug {
+                    crate::log!("{}", gen.mtlsl);
+                }
+                cx_shader.os = CxOsComputeShader::new(metal_cx, gen);
+            }
+        }
+        self.compute_shaders.compile_set.clear();
+    }
+    
+    // runs the compute work queued since the last repaint in a command buffer of its own, which
+    // the command queue runs before the passes that are committed after it
+    pub (crate) fn mtl_run_compute(&mut self, metal_cx: &MetalCx) {
+        let ops = std::mem::take(&mut self.compute_shaders.ops);
+        if ops.is_empty() {
+            return
+        }
+        let pool: ObjcId = unsafe {msg_send![class!(NSAutoreleasePool), new]};
+        let command_buffer: ObjcId = unsafe {msg_send![metal_cx.command_queue, commandBuffer]};
+        let mut gpu_read_guards = Vec::new();
+        for op in ops {
+            match op {
+                CxComputeOp::Dispatch(dispatch) => {
+                    let sh = &self.compute_shaders.shaders[dispatch.compute_shader_id];
+                    let pipeline_state = if let Some(pipeline_state) = &sh.os.pipeline_state {pipeline_state} else {continue};
+                    // the kernel reads its buffers through plain pointers, so it can't run with one of them unset
+                    if (0..sh.buffers.len()).any( | i | dispatch.buffer_slots[i].is_none()) {
+                        crate::error!("Compute dispatch skipped, not all of its buffers are set");
+                        continue
+                    }
+                    let encoder: ObjcId = unsafe {msg_send![command_buffer, computeCommandEncoder]};
+                    unsafe {
+                        let () = msg_send![encoder, setComputePipelineState: pipeline_state.as_id()];
+                        let () = msg_send![encoder, setBytes: sh.mapping.live_uniforms_buf.as_ptr() as *const std::ffi::c_void length: (sh.mapping.live_uniforms_buf.len() * 4) as u64 atIndex: 2u64];
+                        let ct = &sh.mapping.const_table.table;
+                        if ct.len()>0 {
+                            let () = msg_send![encoder, setBytes: ct.as_ptr() as *const std::ffi::c_void length: (ct.len() * 4) as u64 atIndex: 3u64];
+                        }
+                        if let Some(id) = sh.os.user_uniform_buffer_id {
+                            let () = msg_send![encoder, setBytes: dispatch.user_uniforms.as_ptr() as *const std::ffi::c_void length: (dispatch.user_uniforms.len() * 4) as u64 atIndex: id];
+                        }
+                    }
+                    for i in 0..sh.mapping.textures.len() {
+                        let texture_id = if let Some(texture) = &dispatch.texture_slots[i] {
+                            texture.texture_id()
+                        }else {
+                            continue
+                        };
+                        let cxtexture = &mut self.textures[texture_id];
+                        if cxtexture.format.is_vec(){
+                            cxtexture.update_vec_texture(metal_cx);
+                        }
+                        if let Some(texture) = cxtexture.os.texture.as_ref() {
+                            let () = unsafe {msg_send![
+                                encoder,
+                                setTexture: texture.as_id()
+                                atIndex: i as u64
+                            ]};
+                        }
+                    }
+                    // the buffers follow the uniform blocks in declaration order
+                    for i in 0..sh.buffers.len() {
+                        let buffer = dispatch.buffer_slots[i].as_ref().unwrap();
+                        let cxbuffer = &mut self.compute_buffers[buffer.compute_buffer_id()];
+                        cxbuffer.update_metal_buffer(metal_cx);
+                        if let Some(inner) = cxbuffer.os.buffer.cpu_read().inner.as_ref() {
+                            let () = unsafe {msg_send![
+                                encoder,
+                                setBuffer: inner.buffer.as_id()
+                                offset: 0
+                                atIndex: sh.os.buffers_buffer_id + i as u64
+                            ]};
+                        }
+                        gpu_read_guards.push(cxbuffer.os.buffer.gpu_read());
+                    }
+                    let [x, y, z] = dispatch.groups;
+                    let [wx, wy, wz] = sh.workgroup_size;
+                    unsafe {
+                        let () = msg_send![
+                            encoder,
+                            dispatchThreadgroups: MTLSize {width: x as u64, height: y as u64, depth: z as u64}
+                            threadsPerThreadgroup: MTLSize {width: wx as u64, height: wy as u64, depth: wz as u64}
+                        ];
+                        let () = msg_send![encoder, endEncoding];
+                    }
+                }
+                CxComputeOp::CopyToTexture {buffer, texture} => {
+                    let cxbuffer = &mut self.compute_buffers[buffer.compute_buffer_id()];
+                    cxbuffer.update_metal_buffer(metal_cx);
+                    let cxtexture = &mut self.textures[texture.texture_id()];
+                    let (width, height) = match &cxtexture.format {
+                        TextureFormat::VecRGBAf32 {width, height, ..} if width * height * 4 == cxbuffer.data.len() => (*width, *height),
+                        _ => {
+                            crate::log!("copy_compute_buffer_to_texture needs a VecRGBAf32 texture with 4 floats per pixel of the buffer");
+                            continue
+                        }
+                    };
+                    cxtexture.update_vec_texture(metal_cx);
+                    let (inner, texture) = match (cxbuffer.os.buffer.cpu_read().inner.as_ref(), cxtexture.os.texture.as_ref()) {
+                        (Some(inner), Some(texture)) => (inner, texture),
+                        _ => continue
+                    };
+                    let encoder: ObjcId = unsafe {msg_send![command_buffer, blitCommandEncoder]};
+                    unsafe {
+                        let () = msg_send![
+                            encoder,
+                            copyFromBuffer: inner.buffer.as_id()
+                            sourceOffset: 0u64
+                            sourceBytesPerRow: (width * 16) as u64
+                            sourceBytesPerImage: (width * height * 16) as u64
+                            sourceSize: MTLSize {width: width as u64, height: height as u64, depth: 1}
+                            toTexture: texture.as_id()
+                            destinationSlice: 0u64
+                            destinationLevel: 0u64
+                            destinationOrigin: MTLOrigin {x: 0, y: 0, z: 0}
+                        ];
+                        let () = msg_send![encoder, endEncoding];
+                    }
+                    gpu_read_guards.push(cxbuffer.os.buffer.gpu_read());
+                }
+            }
+        }
+        // the buffers can only be written from the CPU again once the GPU is done with them
+        let gpu_read_guards = Mutex::new(Some(gpu_read_guards));
+        let () = unsafe {msg_send![
+            command_buffer,
+            addCompletedHandler: &objc_block!(move | _command_buffer: ObjcId | {
+                drop(gpu_read_guards.lock().unwrap().take().unwrap());
+            })
+        ]};
+        let () = unsafe {msg_send![command_buffer, commit]};
+        let () = unsafe {msg_send![pool, release]};
     }
     
     #[cfg(target_os="macos")]
@@ -815,6 +965,99 @@ struct MetalBufferInner {
 pub struct CxOsTexture {
     texture: Option<RcObjcId>
 }
+
+#[derive(Default)]
+pub struct CxOsComputeShader {
+    _library: Option<RcObjcId>,
+    pipeline_state: Option<RcObjcId>,
+    user_uniform_buffer_id: Option<u64>,
+    buffers_buffer_id: u64,
+}
+
+impl CxOsComputeShader {
+    // a kernel that doesn't compile is logged and skipped, so one broken compute shader leaves the
+    // rest of the app running
+    fn new(
+        metal_cx: &MetalCx,
+        shader: MetalGeneratedShader,
+    ) -> Self {
+        // the storage buffers come after the uniform blocks, which start at buffer 4
+        let mut user_uniform_buffer_id = None;
+        let mut buffer_id = 4;
+        for (field, _) in &shader.fields_as_uniform_blocks {
+            if field.0 == live_id!(user) {
+                user_uniform_buffer_id = Some(buffer_id);
+            }
+            buffer_id += 1;
+        }
+        
+        let options = RcObjcId::from_owned(unsafe {msg_send![class!(MTLCompileOptions), new]});
+        unsafe {
+            let _: () = msg_send![options.as_id(), setFastMathEnabled: YES];
+        };
+        let mut error: ObjcId = nil;
+        let library: ObjcId = unsafe {msg_send![
+            metal_cx.device,
+            newLibraryWithSource: str_to_nsstring(&shader.mtlsl)
+            options: options
+            error: &mut error
+        ]};
+        let library = if let Some(library) = NonNull::new(library) {
+            RcObjcId::from_owned(library)
+        }
+        else {
+            let description: ObjcId = unsafe {msg_send![error, localizedDescription]};
+            let mut out = format!("{}\n", nsstring_to_string(description));
+            for (index, line) in shader.mtlsl.split("\n").enumerate() {
+                out.push_str(&format!("{}: {}\n", index + 1, line));
+            }
+            crate::error!("{}", out);
+            return Self::default()
+        };
+        
+        let compute_function = RcObjcId::from_owned(NonNull::new(unsafe {
+            msg_send![library.as_id(), newFunctionWithName: str_to_nsstring("compute_main")]
+        }).unwrap());
+        
+        let mut error: ObjcId = nil;
+        let pipeline_state: ObjcId = unsafe {msg_send![
+            metal_cx.device,
+            newComputePipelineStateWithFunction: compute_function.as_id()
+            error: &mut error
+        ]};
+        let pipeline_state = if let Some(pipeline_state) = NonNull::new(pipeline_state) {
+            RcObjcId::from_owned(pipeline_state)
+        }
+        else {
+            let description: ObjcId = unsafe {msg_send![error, localizedDescription]};
+            crate::error!("{}", nsstring_to_string(description));
+            return Self::default()
+        };
+        
+        Self {
+            _library: Some(library),
+            pipeline_state: Some(pipeline_state),
+            user_uniform_buffer_id,
+            buffers_buffer_id: buffer_id,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CxOsComputeBuffer {
+    buffer: MetalRwLock<MetalBuffer>,
+}
+
+impl CxComputeBuffer {
+    // uploads the CPU data when it changed, after the GPU is done with the buffer
+    fn update_metal_buffer(&mut self, metal_cx: &MetalCx) {
+        if self.os.buffer.cpu_read().inner.is_some() && !self.updated {
+            return
+        }
+        self.updated = false;
+        self.os.buffer.cpu_write().update(metal_cx, &self.data);
+    }
+}
 fn texture_pixel_to_mtl_pixel(pix:&TexturePixel)-> MTLPixelFormat {
      match pix{
          TexturePixel::BGRAu8 => MTLPixelFormat::BGRA8Unorm,
@@ -16,6 +16,7 @@ use {
             NextFrameEvent,
         },
         studio::{AppToStudio,EventSample},
+        file_dialogs::{FileDialog, FileDialogKind, FileDialogRequestEvent},
     }
 };
 
@@ -112,6 +113,7 @@ impl Cx {
     pub (crate) fn inner_call_event_handler(&mut self, event: &Event) {
         self.event_id += 1;
         if Cx::has_studio_web_socket(){
+            self.handle_studio_messages();
             let start = Instant::now().duration_since(self.start_time);
             let mut event_handler = self.event_handler.take().unwrap();
             event_handler(self, event);
@@ -173,11 +175,59 @@ impl Cx {
         }
     }
     
+    // for platforms without native file dialogs, the application draws them
+    #[allow(dead_code)]
+    pub (crate) fn request_file_dialog(&mut self, kind: FileDialogKind, dialog: FileDialog) {
+        let event = Event::FileDialogRequest(FileDialogRequestEvent {
+            kind,
+            dialog,
+            handled: Default::default(),
+        });
+        self.call_event_handler(&event);
+        // without a window to show the dialog in, it counts as cancelled
+        if let Event::FileDialogRequest(request) = event {
+            if !request.handled.get() {
+                self.file_dialog_closed(kind, None);
+                self.handle_file_dialog_results();
+            }
+        }
+    }
+    
+    pub fn handle_file_dialog_results(&mut self) {
+        for result in std::mem::take(&mut self.file_dialog_results) {
+            self.inner_call_event_handler(&Event::FileDialogResult(result));
+            self.inner_key_focus_change();
+            self.handle_triggers();
+            self.handle_actions();
+        }
+    }
+    
+    pub fn handle_access_actions(&mut self) {
+        for action in std::mem::take(&mut self.access_actions) {
+            self.inner_call_event_handler(&Event::AccessAction(action));
+            self.inner_key_focus_change();
+            self.handle_triggers();
+            self.handle_actions();
+        }
+    }
+    
+    pub fn handle_readback_results(&mut self) {
+        for result in std::mem::take(&mut self.readbacks.results) {
+            self.inner_call_event_handler(&Event::Readback(result));
+            self.inner_key_focus_change();
+            self.handle_triggers();
+            self.handle_actions();
+        }
+    }
+    
     pub (crate) fn call_event_handler(&mut self, event: &Event) {
         self.inner_call_event_handler(event);
         self.inner_key_focus_change();
         self.handle_triggers();
         self.handle_actions();
+        self.handle_file_dialog_results();
+        self.handle_access_actions();
+        self.handle_readback_results();
     }
 
     // helpers
@@ -0,0 +1,720 @@
+// A Cx backend without a GPU or a display: draw lists are executed on the CPU by interpreting
+// the analysed shader AST, and every pass renders into an RGBA image. Meant for golden image
+// tests of widgets, so it favours being exact and simple over being fast.
+use {
+    std::collections::HashMap,
+    crate::{
+        cx::Cx,
+        cx_api::CxOsOp,
+        compute_shader::{CxComputeDispatch, CxComputeOp},
+        draw_list::DrawListId,
+        event::Event,
+        makepad_live_id::{LiveId, live_id},
+        makepad_math::{DVec2, Mat4, Vec2, Vec4},
+        makepad_shader_compiler::{
+            interpret::{Dual, ShaderInterpreter, ShaderSampler, Value},
+            shader_ast::{DrawShaderFieldKind, Ident, Ty, ValuePtr},
+        },
+        pass::{PassClearColor, PassClearDepth, PassId},
+        readback::{ReadbackEvent, ReadbackImage, ReadbackPixels},
+        texture::{TextureFormat, TextureId},
+    }
+};
+
+/// An 8 bit RGBA image, rows top down.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HeadlessImage {
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<u8>,
+}
+
+impl HeadlessImage {
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        let i = (y * self.width + x) * 4;
+        [self.data[i], self.data[i + 1], self.data[i + 2], self.data[i + 3]]
+    }
+
+    /// Counts the pixels where a channel differs by more than `tolerance`, a size mismatch
+    /// counts every pixel.
+    pub fn diff(&self, other: &HeadlessImage, tolerance: u8) -> usize {
+        if self.width != other.width || self.height != other.height {
+            return self.width.max(other.width) * self.height.max(other.height)
+        }
+        self.data.chunks(4).zip(other.data.chunks(4)).filter( | (a, b) | {
+            a.iter().zip(b.iter()).any( | (a, b) | a.abs_diff(*b) > tolerance)
+        }).count()
+    }
+
+    /// Encodes the image as a PAM (netpbm P7) file, which needs no image codec to write or read.
+    pub fn to_pam(&self) -> Vec<u8> {
+        let mut out = format!(
+            "P7\nWIDTH {}\nHEIGHT {}\nDEPTH 4\nMAXVAL 255\nTUPLTYPE RGB_ALPHA\nENDHDR\n",
+            self.width,
+            self.height
+        ).into_bytes();
+        out.extend_from_slice(&self.data);
+        out
+    }
+
+    pub fn from_pam(bytes: &[u8]) -> Option<HeadlessImage> {
+        const END: &[u8] = b"ENDHDR\n";
+        let header_len = bytes.windows(END.len()).position( | w | w == END) ? + END.len();
+        let header = std::str::from_utf8(&bytes[..header_len]).ok() ?;
+        let mut lines = header.lines();
+        if lines.next() ? != "P7" {
+            return None
+        }
+        let (mut width, mut height) = (None, None);
+        for line in lines {
+            let mut parts = line.split_whitespace();
+            match (parts.next(), parts.next()) {
+                (Some("WIDTH"), Some(v)) => width = v.parse().ok(),
+                (Some("HEIGHT"), Some(v)) => height = v.parse().ok(),
+                (Some("DEPTH"), Some(v)) if v != "4" => return None,
+                (Some("MAXVAL"), Some(v)) if v != "255" => return None,
+                _ => ()
+            }
+        }
+        let (width, height): (usize, usize) = (width ?, height ?);
+        let data = bytes.get(header_len..header_len + width * height * 4) ?.to_vec();
+        Some(HeadlessImage {width, height, data})
+    }
+}
+
+#[derive(Default)]
+struct RenderTarget {
+    width: usize,
+    height: usize,
+    color: Vec<Vec4>,
+    // render targets with 8 bit channels saturate when blending
+    clamp: bool,
+}
+
+impl RenderTarget {
+    fn resize(&mut self, width: usize, height: usize) -> bool {
+        if self.width == width && self.height == height {
+            return false
+        }
+        self.width = width;
+        self.height = height;
+        self.color = vec![Vec4::default(); width * height];
+        true
+    }
+
+    fn to_image(&self) -> HeadlessImage {
+        let mut data = Vec::with_capacity(self.color.len() * 4);
+        for c in &self.color {
+            for v in [c.x, c.y, c.z, c.w] {
+                data.push((v.clamp(0.0, 1.0) * 255.0 + 0.5) as u8);
+            }
+        }
+        HeadlessImage {width: self.width, height: self.height, data}
+    }
+
+    fn to_readback(&self) -> ReadbackImage {
+        let pixels = if self.clamp {
+            ReadbackPixels::BGRAu8(self.color.iter().map( | c | {
+                let u = | v: f32 | (v.clamp(0.0, 1.0) * 255.0 + 0.5) as u32;
+                (u(c.w) << 24) | (u(c.x) << 16) | (u(c.y) << 8) | u(c.z)
+            }).collect())
+        }
+        else {
+            ReadbackPixels::RGBAf32(self.color.iter().flat_map( | c | [c.x, c.y, c.z, c.w]).collect())
+        };
+        ReadbackImage {width: self.width, height: self.height, pixels}
+    }
+}
+
+#[derive(Default)]
+struct DepthTarget {
+    width: usize,
+    height: usize,
+    depth: Vec<f32>,
+}
+
+/// Drives a `Cx` frame by frame and renders its passes on the CPU.
+pub struct HeadlessRenderer {
+    pub window_size: DVec2,
+    pub dpi_factor: f64,
+    window_targets: Vec<(PassId, RenderTarget)>,
+    texture_targets: HashMap<TextureId, RenderTarget>,
+    depth_targets: HashMap<TextureId, DepthTarget>,
+}
+
+impl HeadlessRenderer {
+    pub fn new(window_size: DVec2, dpi_factor: f64) -> Self {
+        Self {
+            window_size,
+            dpi_factor,
+            window_targets: Vec::new(),
+            texture_targets: HashMap::new(),
+            depth_targets: HashMap::new(),
+        }
+    }
+
+    /// Sends the startup event and asks for a first full draw.
+    pub fn startup(&mut self, cx: &mut Cx) {
+        cx.call_event_handler(&Event::Startup);
+        cx.redraw_all();
+    }
+
+    /// Runs one frame: next frame and draw events, then repaints every dirty pass and sends
+    /// the readbacks that were asked for.
+    pub fn frame(&mut self, cx: &mut Cx, time: f64) {
+        self.handle_platform_ops(cx);
+        if !cx.new_next_frames.is_empty() {
+            cx.call_next_frame_event(time);
+        }
+        if cx.need_redrawing() {
+            cx.call_draw_event();
+            // the shaders are interpreted from the AST, there is nothing to compile
+            cx.draw_shaders.compile_set.clear();
+            cx.compute_shaders.compile_set.clear();
+        }
+        self.run_compute_ops(cx);
+        // windows opened while drawing still need a size before they can paint
+        self.handle_platform_ops(cx);
+
+        let mut passes_todo = Vec::new();
+        cx.compute_pass_repaint_order(&mut passes_todo);
+        cx.repaint_id += 1;
+        for pass_id in passes_todo {
+            cx.passes[pass_id].set_time(time as f32);
+            self.render_pass(cx, pass_id);
+        }
+        for (request_id, texture) in cx.take_texture_readbacks() {
+            let image = self.texture_targets.get(&texture.texture_id()).map(RenderTarget::to_readback);
+            cx.readbacks.results.push(ReadbackEvent {request_id, image});
+        }
+        cx.handle_readback_results();
+    }
+
+    /// The last image rendered by a pass, from its first color texture or its window.
+    pub fn pass_image(&self, cx: &Cx, pass_id: PassId) -> Option<HeadlessImage> {
+        match cx.passes[pass_id].color_textures.first() {
+            Some(color_texture) => self.texture_targets.get(&color_texture.texture.texture_id()).map(RenderTarget::to_image),
+            None => self.window_targets.iter().find( | (id, _) | *id == pass_id).map( | (_, target) | target.to_image())
+        }
+    }
+
+    fn handle_platform_ops(&mut self, cx: &mut Cx) {
+        while let Some(op) = cx.platform_ops.pop() {
+            if let CxOsOp::CreateWindow(window_id) = op {
+                let window = &mut cx.windows[window_id];
+                let inner_size = window.create_inner_size.unwrap_or(self.window_size);
+                window.window_geom.inner_size = inner_size;
+                window.window_geom.outer_size = inner_size;
+                window.window_geom.dpi_factor = window.dpi_override.unwrap_or(self.dpi_factor);
+                window.is_created = true;
+            }
+        }
+    }
+
+    pub fn render_pass(&mut self, cx: &mut Cx, pass_id: PassId) {
+        let dpi_factor = cx.passes[pass_id].dpi_factor.unwrap_or(self.dpi_factor);
+        let pass_rect = cx.get_pass_rect(pass_id, dpi_factor);
+        cx.passes[pass_id].paint_dirty = false;
+        let (pass_rect, draw_list_id) = match (pass_rect, cx.passes[pass_id].main_draw_list_id) {
+            (Some(pass_rect), Some(draw_list_id)) if pass_rect.size.x >= 0.5 && pass_rect.size.y >= 0.5 => (pass_rect, draw_list_id),
+            _ => return
+        };
+        cx.passes[pass_id].set_matrix(pass_rect.pos, pass_rect.size);
+        cx.passes[pass_id].set_dpi_factor(dpi_factor);
+        let width = (pass_rect.size.x * dpi_factor) as usize;
+        let height = (pass_rect.size.y * dpi_factor) as usize;
+
+        // take the target out so the draw calls can still sample the other textures
+        let pass = &cx.passes[pass_id];
+        let mut target_texture = None;
+        let mut target = match pass.color_textures.first() {
+            Some(color_texture) => {
+                let texture_id = color_texture.texture.texture_id();
+                target_texture = Some(texture_id);
+                let mut target = self.texture_targets.remove(&texture_id).unwrap_or_default();
+                let resized = target.resize(width, height);
+                target.clamp = matches!(cx.textures[texture_id].format, TextureFormat::RenderBGRAu8 {..});
+                match color_texture.clear_color {
+                    PassClearColor::InitWith(clear_color) if resized => target.color.fill(clear_color),
+                    PassClearColor::ClearWith(clear_color) => target.color.fill(clear_color),
+                    _ => ()
+                }
+                target
+            }
+            None => {
+                let mut target = match self.window_targets.iter().position( | (id, _) | *id == pass_id) {
+                    Some(index) => self.window_targets.remove(index).1,
+                    None => RenderTarget::default()
+                };
+                target.resize(width, height);
+                target.clamp = true;
+                target.color.fill(pass.clear_color);
+                target
+            }
+        };
+
+        // window passes always have a depth buffer, texture passes only when they ask for one
+        let mut depth_texture = None;
+        let mut depth = match &pass.depth_texture {
+            Some(texture) => {
+                let texture_id = texture.texture_id();
+                depth_texture = Some(texture_id);
+                let mut depth = self.depth_targets.remove(&texture_id).unwrap_or_default();
+                let resized = depth.width != width || depth.height != height;
+                if resized {
+                    depth = DepthTarget {width, height, depth: vec![1.0; width * height]};
+                }
+                match pass.clear_depth {
+                    PassClearDepth::InitWith(clear_depth) if resized => depth.depth.fill(clear_depth),
+                    PassClearDepth::ClearWith(clear_depth) => depth.depth.fill(clear_depth),
+                    _ => ()
+                }
+                Some(depth)
+            }
+            None if target_texture.is_none() => Some(DepthTarget {width, height, depth: vec![1.0; width * height]}),
+            None => None
+        };
+
+        let mut draw_items = Vec::new();
+        let mut zbias = 0.0;
+        let zbias_step = cx.passes[pass_id].zbias_step;
+        Self::collect_draw_items(cx, draw_list_id, &mut zbias, zbias_step, &mut draw_items);
+        for (draw_list_id, draw_item_id) in draw_items {
+            self.render_draw_call(cx, pass_id, draw_list_id, draw_item_id, &mut target, depth.as_mut());
+        }
+
+        match target_texture {
+            Some(texture_id) => {self.texture_targets.insert(texture_id, target);}
+            None => {
+                for request_id in cx.readbacks.take_pass_requests(pass_id) {
+                    cx.readbacks.results.push(ReadbackEvent {request_id, image: Some(target.to_readback())});
+                }
+                self.window_targets.push((pass_id, target))
+            }
+        }
+        if let (Some(texture_id), Some(depth)) = (depth_texture, depth) {
+            self.depth_targets.insert(texture_id, depth);
+        }
+    }
+
+    fn run_compute_ops(&mut self, cx: &mut Cx) {
+        for op in std::mem::take(&mut cx.compute_shaders.ops) {
+            match op {
+                CxComputeOp::Dispatch(dispatch) => self.run_dispatch(cx, &dispatch),
+                CxComputeOp::CopyToTexture {buffer, texture} => {
+                    let data = &cx.compute_buffers[buffer.compute_buffer_id()].data;
+                    let cxtexture = &mut cx.textures[texture.texture_id()];
+                    match &mut cxtexture.format {
+                        TextureFormat::VecRGBAf32 {width, height, data: pixels} if *width * *height * 4 == data.len() => {
+                            pixels.clear();
+                            pixels.extend_from_slice(data);
+                            cxtexture.set_updated(true);
+                        }
+                        _ => crate::log!("copy_compute_buffer_to_texture needs a VecRGBAf32 texture with 4 floats per pixel of the buffer")
+                    }
+                }
+            }
+        }
+    }
+
+    // runs every invocation of the workgroups one after the other, the buffers are only
+    // written back once the whole dispatch finished
+    fn run_dispatch(&self, cx: &mut Cx, dispatch: &CxComputeDispatch) {
+        let sh = &cx.compute_shaders.shaders[dispatch.compute_shader_id];
+        let compute_shader_def = match cx.shader_registry.draw_shader_defs.get(&sh.compute_shader_ptr) {
+            Some(compute_shader_def) => compute_shader_def,
+            None => return
+        };
+        let sampler = DrawCallSampler {
+            cx,
+            renderer: self,
+            textures: dispatch.texture_slots.iter().map( | slot | slot.as_ref().map( | texture | texture.texture_id())).collect(),
+        };
+        let mut interp = ShaderInterpreter::new(&cx.shader_registry, compute_shader_def, &sampler);
+
+        let mut buffer_fields = Vec::new();
+        for (index, field) in compute_shader_def.fields.iter().enumerate() {
+            let ty = field.ty_expr.ty.borrow().clone().unwrap();
+            match &field.kind {
+                DrawShaderFieldKind::Uniform {block_ident, ..} if block_ident.0 == live_id!(user) => {
+                    if let Some(input) = sh.mapping.user_uniforms.inputs.iter().find( | input | input.id == field.ident.0) {
+                        interp.fields[index] = Value::from_slots(&ty, &dispatch.user_uniforms[input.offset..]);
+                    }
+                }
+                DrawShaderFieldKind::Buffer {..} => {
+                    let slot = buffer_fields.len();
+                    if let (Some(buffer), Ty::Array {elem_ty, ..}) = (&dispatch.buffer_slots[slot], &ty) {
+                        let buffer_id = buffer.compute_buffer_id();
+                        interp.fields[index] = Value::buffer(elem_ty, &cx.compute_buffers[buffer_id].data);
+                        buffer_fields.push((index, Some(buffer_id)));
+                    }
+                    else {
+                        buffer_fields.push((index, None));
+                    }
+                }
+                _ => ()
+            }
+        }
+        for input in &sh.mapping.live_uniforms.inputs {
+            if let Some(live_ptr) = input.live_ptr {
+                let value = Value::from_slots(&input.ty, &sh.mapping.live_uniforms_buf[input.offset..]);
+                interp.live_values.insert(ValuePtr(live_ptr), value);
+            }
+        }
+
+        let [wx, wy, wz] = sh.workgroup_size;
+        let [gx, gy, gz] = dispatch.groups;
+        for z in 0..gz * wz {
+            for y in 0..gy * wy {
+                for x in 0..gx * wx {
+                    let id = Value::ints(&[x as i32, y as i32, z as i32]);
+                    interp.call_method_with_args(Ident(live_id!(compute)), &[id]);
+                }
+            }
+        }
+
+        let mut results = Vec::new();
+        for (index, buffer_id) in buffer_fields {
+            if let Some(buffer_id) = buffer_id {
+                let mut data = Vec::new();
+                interp.fields[index].write_slots(&mut data);
+                results.push((buffer_id, data));
+            }
+        }
+        drop(interp);
+        for (buffer_id, data) in results {
+            let cxbuffer = &mut cx.compute_buffers[buffer_id];
+            cxbuffer.data = data;
+            cxbuffer.updated = true;
+        }
+    }
+
+    // walks the draw tree in paint order, updating the uniforms the gpu backends update
+    fn collect_draw_items(cx: &mut Cx, draw_list_id: DrawListId, zbias: &mut f32, zbias_step: f32, out: &mut Vec<(DrawListId, usize)>) {
+        cx.draw_lists[draw_list_id].uniform_view_transform(&Mat4::identity());
+        for draw_item_id in 0..cx.draw_lists[draw_list_id].draw_items.len() {
+            let draw_item = &mut cx.draw_lists[draw_list_id].draw_items[draw_item_id];
+            if let Some(sub_list_id) = draw_item.kind.sub_list() {
+                Self::collect_draw_items(cx, sub_list_id, zbias, zbias_step, out);
+            }
+            else if let Some(draw_call) = draw_item.kind.draw_call_mut() {
+                draw_call.draw_uniforms.set_zbias(*zbias);
+                *zbias += zbias_step;
+                out.push((draw_list_id, draw_item_id));
+            }
+        }
+    }
+
+    fn render_draw_call(&self, cx: &Cx, pass_id: PassId, draw_list_id: DrawListId, draw_item_id: usize, target: &mut RenderTarget, mut depth: Option<&mut DepthTarget>) {
+        let draw_list = &cx.draw_lists[draw_list_id];
+        let draw_item = &draw_list.draw_items[draw_item_id];
+        let draw_call = draw_item.kind.draw_call().unwrap();
+        let sh = &cx.draw_shaders.shaders[draw_call.draw_shader.draw_shader_id];
+        let mapping = &sh.mapping;
+        let draw_shader_def = match cx.shader_registry.draw_shader_defs.get(&draw_call.draw_shader.draw_shader_ptr) {
+            Some(draw_shader_def) => draw_shader_def,
+            None => return
+        };
+        let (geometry, instances) = match (draw_call.geometry_id, &draw_item.instances) {
+            (Some(geometry_id), Some(instances)) if mapping.instances.total_slots > 0 => (&cx.geometries[geometry_id], instances),
+            _ => return
+        };
+        // instances drawn from a compute buffer replace the ones written from the CPU
+        let instances = match &draw_call.instance_buffer {
+            Some(buffer) => &cx.compute_buffers[buffer.compute_buffer_id()].data,
+            None => instances
+        };
+
+        let sampler = DrawCallSampler {
+            cx,
+            renderer: self,
+            textures: draw_call.texture_slots.iter().map( | slot | slot.as_ref().map( | texture | texture.texture_id())).collect(),
+        };
+        let mut interp = ShaderInterpreter::new(&cx.shader_registry, draw_shader_def, &sampler);
+
+        // uniforms and live values stay the same for the whole draw call
+        let pass_uniforms = cx.passes[pass_id].pass_uniforms.as_slice();
+        let mut geometry_fields = Vec::new();
+        let mut instance_fields = Vec::new();
+        for (index, field) in draw_shader_def.fields.iter().enumerate() {
+            let ty = field.ty_expr.ty.borrow().clone().unwrap();
+            let find = | inputs: &crate::draw_shader::DrawShaderInputs | inputs.inputs.iter().find( | input | input.id == field.ident.0).map( | input | input.offset);
+            match &field.kind {
+                DrawShaderFieldKind::Geometry {..} => if let Some(offset) = find(&mapping.geometries) {
+                    geometry_fields.push((index, offset, ty));
+                }
+                DrawShaderFieldKind::Instance {..} => if let Some(offset) = find(&mapping.instances) {
+                    instance_fields.push((index, offset, ty));
+                }
+                DrawShaderFieldKind::Uniform {block_ident, ..} => {
+                    let (inputs, buf): (_, &[f32]) = match block_ident.0 {
+                        live_id!(draw) => (&mapping.draw_uniforms, draw_call.draw_uniforms.as_slice()),
+                        live_id!(view) => (&mapping.view_uniforms, draw_list.draw_list_uniforms.as_slice()),
+                        live_id!(pass) => (&mapping.pass_uniforms, pass_uniforms),
+                        live_id!(user) => (&mapping.user_uniforms, &draw_call.user_uniforms),
+                        _ => continue
+                    };
+                    if let Some(offset) = find(inputs) {
+                        interp.fields[index] = Value::from_slots(&ty, &buf[offset..]);
+                    }
+                }
+                _ => ()
+            }
+        }
+        for input in &mapping.live_uniforms.inputs {
+            if let Some(live_ptr) = input.live_ptr {
+                let value = Value::from_slots(&input.ty, &mapping.live_uniforms_buf[input.offset..]);
+                interp.live_values.insert(ValuePtr(live_ptr), value);
+            }
+        }
+        let varyings = interp.varying_fields();
+        let varying_tys: Vec<_> = varyings.iter().map( | index | draw_shader_def.fields[*index].ty_expr.ty.borrow().clone().unwrap()).collect();
+        let varying_zeros: Vec<Value> = varying_tys.iter().map( | ty | interp.zero(ty)).collect();
+
+        let geometry_slots = mapping.geometries.total_slots.max(1);
+        let vertex_count = geometry.vertices.len() / geometry_slots;
+        let instance_slots = mapping.instances.total_slots;
+        for instance in instances.chunks_exact(instance_slots) {
+            let mut vertices = Vec::with_capacity(vertex_count);
+            for vertex in geometry.vertices.chunks_exact(geometry_slots).take(vertex_count) {
+                for (index, offset, ty) in &geometry_fields {
+                    interp.fields[*index] = Value::from_slots(ty, &vertex[*offset..]);
+                }
+                for (index, offset, ty) in &instance_fields {
+                    interp.fields[*index] = Value::from_slots(ty, &instance[*offset..]);
+                }
+                for (index, zero) in varyings.iter().zip(&varying_zeros) {
+                    if matches!(draw_shader_def.fields[*index].kind, DrawShaderFieldKind::Varying {..}) {
+                        interp.fields[*index] = zero.clone();
+                    }
+                }
+                let position = interp.call_method(Ident(live_id!(vertex))).map_or(Vec4::default(), | v | v.as_vec4());
+                let mut out = Vec::new();
+                for index in &varyings {
+                    interp.fields[*index].write_slots(&mut out);
+                }
+                vertices.push(ShadedVertex {position, varyings: out});
+            }
+            for tri in geometry.indices.chunks_exact(3) {
+                let tri = [tri[0] as usize, tri[1] as usize, tri[2] as usize];
+                if tri.iter().any( | i | *i >= vertices.len()) {
+                    continue;
+                }
+                Self::rasterize(&mut interp, &varyings, &varying_tys, [&vertices[tri[0]], &vertices[tri[1]], &vertices[tri[2]]], target, depth.as_deref_mut());
+            }
+        }
+    }
+
+    fn rasterize(
+        interp: &mut ShaderInterpreter,
+        varyings: &[usize],
+        varying_tys: &[crate::makepad_shader_compiler::ShaderTy],
+        tri: [&ShadedVertex; 3],
+        target: &mut RenderTarget,
+        mut depth: Option<&mut DepthTarget>
+    ) {
+        // no clipping, triangles reaching behind the camera are dropped
+        if tri.iter().any( | v | v.position.w <= 0.0) {
+            return
+        }
+        let (w, h) = (target.width as f32, target.height as f32);
+        let screen: Vec<(f32, f32, f32, f32)> = tri.iter().map( | v | {
+            let p = v.position;
+            let inv_w = 1.0 / p.w;
+            ((p.x * inv_w * 0.5 + 0.5) * w, (0.5 - p.y * inv_w * 0.5) * h, p.z * inv_w, inv_w)
+        }).collect();
+        let area = edge(screen[0], screen[1], screen[2].0, screen[2].1);
+        if area == 0.0 || !area.is_finite() {
+            return
+        }
+        let min_x = screen.iter().map( | s | s.0).fold(f32::MAX, f32::min).floor().max(0.0) as usize;
+        let max_x = screen.iter().map( | s | s.0).fold(f32::MIN, f32::max).ceil().min(w) as usize;
+        let min_y = screen.iter().map( | s | s.1).fold(f32::MAX, f32::min).floor().max(0.0) as usize;
+        let max_y = screen.iter().map( | s | s.1).fold(f32::MIN, f32::max).ceil().min(h) as usize;
+
+        // perspective correct interpolation of every varying float at a screen position
+        let slot_count = tri[0].varyings.len();
+        let interpolate = | x: f32, y: f32, out: &mut Vec<f32> | {
+            let b = [
+                edge(screen[1], screen[2], x, y) / area * screen[0].3,
+                edge(screen[2], screen[0], x, y) / area * screen[1].3,
+                edge(screen[0], screen[1], x, y) / area * screen[2].3,
+            ];
+            let sum = b[0] + b[1] + b[2];
+            out.clear();
+            for slot in 0..slot_count {
+                out.push((0..3).map( | i | tri[i].varyings[slot] * b[i]).sum::<f32>() / sum);
+            }
+        };
+        let (mut here, mut right, mut below) = (Vec::new(), Vec::new(), Vec::new());
+        let mut duals = Vec::with_capacity(slot_count);
+        for py in min_y..max_y {
+            for px in min_x..max_x {
+                let (x, y) = (px as f32 + 0.5, py as f32 + 0.5);
+                let b = [
+                    edge(screen[1], screen[2], x, y) / area,
+                    edge(screen[2], screen[0], x, y) / area,
+                    edge(screen[0], screen[1], x, y) / area,
+                ];
+                if b.iter().any( | b | *b < 0.0) {
+                    continue;
+                }
+                let z = b[0] * screen[0].2 + b[1] * screen[1].2 + b[2] * screen[2].2;
+                if !(-1.0..=1.0).contains(&z) {
+                    continue;
+                }
+                let pixel_index = py * target.width + px;
+                let z = z * 0.5 + 0.5;
+                if let Some(depth) = &depth {
+                    if z > depth.depth[pixel_index] {
+                        continue;
+                    }
+                }
+
+                interpolate(x, y, &mut here);
+                interpolate(x + 1.0, y, &mut right);
+                interpolate(x, y + 1.0, &mut below);
+                duals.clear();
+                duals.extend((0..slot_count).map( | i | Dual::new(here[i], right[i] - here[i], below[i] - here[i])));
+                let mut slot = 0;
+                for (index, ty) in varyings.iter().zip(varying_tys) {
+                    interp.fields[*index] = Value::from_duals(ty, &duals[slot..]);
+                    slot += ty.slots();
+                }
+                let color = match interp.call_method(Ident(live_id!(pixel))) {
+                    Some(color) => color.as_vec4(),
+                    None => continue
+                };
+                if let Some(depth) = &mut depth {
+                    depth.depth[pixel_index] = z;
+                }
+                // premultiplied alpha, like the gpu backends
+                let dst = target.color[pixel_index];
+                let inv_a = 1.0 - color.w;
+                let mut out = Vec4 {
+                    x: color.x + dst.x * inv_a,
+                    y: color.y + dst.y * inv_a,
+                    z: color.z + dst.z * inv_a,
+                    w: color.w + dst.w * inv_a,
+                };
+                if target.clamp {
+                    out = Vec4 {x: out.x.clamp(0.0, 1.0), y: out.y.clamp(0.0, 1.0), z: out.z.clamp(0.0, 1.0), w: out.w.clamp(0.0, 1.0)};
+                }
+                target.color[pixel_index] = out;
+            }
+        }
+    }
+}
+
+struct ShadedVertex {
+    position: Vec4,
+    varyings: Vec<f32>,
+}
+
+fn edge(a: (f32, f32, f32, f32), b: (f32, f32, f32, f32), x: f32, y: f32) -> f32 {
+    (b.0 - a.0) * (y - a.1) - (b.1 - a.1) * (x - a.0)
+}
+
+struct DrawCallSampler<'a> {
+    cx: &'a Cx,
+    renderer: &'a HeadlessRenderer,
+    textures: Vec<Option<TextureId >>,
+}
+
+impl DrawCallSampler<'_> {
+    fn size(&self, texture_id: TextureId) -> (usize, usize) {
+        match &self.cx.textures[texture_id].format {
+            TextureFormat::VecBGRAu8_32 {width, height, ..} |
+            TextureFormat::VecMipBGRAu8_32 {width, height, ..} |
+            TextureFormat::VecRGBAf32 {width, height, ..} |
+            TextureFormat::VecRu8 {width, height, ..} |
+            TextureFormat::VecRGu8 {width, height, ..} |
+            TextureFormat::VecRf32 {width, height, ..} => (*width, *height),
+            TextureFormat::DepthD32 {..} => self.renderer.depth_targets.get(&texture_id).map_or((0, 0), | d | (d.width, d.height)),
+            _ => self.renderer.texture_targets.get(&texture_id).map_or((0, 0), | t | (t.width, t.height)),
+        }
+    }
+
+    fn texel(&self, texture_id: TextureId, x: usize, y: usize) -> Vec4 {
+        let unorm = | v: u8 | v as f32 / 255.0;
+        match &self.cx.textures[texture_id].format {
+            TextureFormat::VecBGRAu8_32 {width, data, ..} |
+            TextureFormat::VecMipBGRAu8_32 {width, data, ..} => {
+                let p = data[y * width + x];
+                Vec4 {
+                    x: unorm((p >> 16) as u8),
+                    y: unorm((p >> 8) as u8),
+                    z: unorm(p as u8),
+                    w: unorm((p >> 24) as u8)
+                }
+            }
+            TextureFormat::VecRGBAf32 {width, data, ..} => {
+                let i = (y * width + x) * 4;
+                Vec4 {x: data[i], y: data[i + 1], z: data[i + 2], w: data[i + 3]}
+            }
+            TextureFormat::VecRu8 {width, data, unpack_row_length, ..} => {
+                let r = data[y * unpack_row_length.unwrap_or(*width) + x];
+                Vec4 {x: unorm(r), y: 0.0, z: 0.0, w: 1.0}
+            }
+            TextureFormat::VecRGu8 {width, data, unpack_row_length, ..} => {
+                let i = (y * unpack_row_length.unwrap_or(*width) + x) * 2;
+                Vec4 {x: unorm(data[i]), y: unorm(data[i + 1]), z: 0.0, w: 1.0}
+            }
+            TextureFormat::VecRf32 {width, data, ..} => Vec4 {x: data[y * width + x], y: 0.0, z: 0.0, w: 1.0},
+            TextureFormat::DepthD32 {..} => {
+                let d = &self.renderer.depth_targets[&texture_id];
+                Vec4 {x: d.depth[y * d.width + x], y: 0.0, z: 0.0, w: 1.0}
+            }
+            _ => {
+                let t = &self.renderer.texture_targets[&texture_id];
+                t.color[y * t.width + x]
+            }
+        }
+    }
+}
+
+impl ShaderSampler for DrawCallSampler<'_> {
+    // bilinear filtering with clamp to edge, like the samplers of the gpu backends
+    fn sample_2d(&self, texture: usize, pos: Vec2) -> Vec4 {
+        let texture_id = match self.textures.get(texture) {
+            Some(Some(texture_id)) => *texture_id,
+            _ => return Vec4::default()
+        };
+        let (width, height) = self.size(texture_id);
+        if width == 0 || height == 0 {
+            return Vec4::default()
+        }
+        let fx = (pos.x * width as f32 - 0.5).clamp(0.0, (width - 1) as f32);
+        let fy = (pos.y * height as f32 - 0.5).clamp(0.0, (height - 1) as f32);
+        let (x0, y0) = (fx as usize, fy as usize);
+        let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+        let (tx, ty) = (fx - x0 as f32, fy - y0 as f32);
+        let lerp = | a: Vec4, b: Vec4, t: f32 | a + (b - a) * t;
+        let top = lerp(self.texel(texture_id, x0, y0), self.texel(texture_id, x1, y0), tx);
+        let bottom = lerp(self.texel(texture_id, x0, y1), self.texel(texture_id, x1, y1), tx);
+        lerp(top, bottom, ty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pam_roundtrip_and_diff() {
+        let image = HeadlessImage {
+            width: 2,
+            height: 1,
+            data: vec![255, 0, 0, 255, 0, 0, 255, 128],
+        };
+        let decoded = HeadlessImage::from_pam(&image.to_pam()).unwrap();
+        assert_eq!(decoded, image);
+        assert_eq!(decoded.pixel(1, 0), [0, 0, 255, 128]);
+
+        let mut other = image.clone();
+        other.data[6] = 250;
+        assert_eq!(image.diff(&other, 2), 1);
+        assert_eq!(image.diff(&other, 5), 0);
+        assert_eq!(image.diff(&HeadlessImage::default(), 0), 2);
+    }
+}
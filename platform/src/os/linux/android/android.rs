@@ -17,6 +17,7 @@ use {
     },
     self::super::super::{
         gl_sys,
+        opengl::{GlProfiler, GlReadbacks},
         //libc_sys,
     },
     crate::{
@@ -405,13 +406,14 @@ impl Cx {
             }
             self.handle_platform_ops();
 
-            if self.any_passes_dirty() || self.need_redrawing() || self.new_next_frames.len() != 0 {
+            if self.any_passes_dirty() || self.need_redrawing() || self.new_next_frames.len() != 0 || self.opengl_has_pending_readbacks() {
                 if self.new_next_frames.len() != 0 {
                     self.call_next_frame_event(self.os.timers.time_now());
                 }
                 if self.need_redrawing() {
                     self.call_draw_event();
                     self.opengl_compile_shaders();
+                    self.opengl_run_compute();
                 }
 
                 if self.os.first_after_resize {
@@ -420,6 +422,7 @@ impl Cx {
                 }
 
                 self.handle_repaint();
+                self.handle_readback_results();
             }
             else {
                 std::thread::sleep(Duration::from_millis(8));
@@ -647,6 +650,7 @@ impl Cx {
             &mut zbias,
             zbias_step,
         );
+        self.opengl_read_back_pass(pass_id, 0, 0, self.os.display_size.x as usize, self.os.display_size.y as usize);
 
         //to_java.swap_buffers();
         //unsafe {
@@ -659,6 +663,7 @@ impl Cx {
         let mut passes_todo = Vec::new();
         self.compute_pass_repaint_order(&mut passes_todo);
         self.repaint_id += 1;
+        self.opengl_profile_begin_frame();
         for pass_id in &passes_todo {
             self.passes[*pass_id].set_time(self.os.timers.time_now() as f32);
             match self.passes[*pass_id].parent.clone() {
@@ -681,8 +686,9 @@ impl Cx {
                 }
             }
         }
-
-
+        self.opengl_poll_readbacks();
+        self.opengl_read_back_textures();
+        self.opengl_profile_end_frame();
     }
 
     fn handle_platform_ops(&mut self) -> EventFlow {
@@ -809,6 +815,8 @@ impl Default for CxOs {
             fullscreen: false,
             timers: Default::default(),
             video_surfaces: HashMap::new(),
+            gl_profiler: Default::default(),
+            gl_readbacks: Default::default(),
         }
     }
 }
@@ -836,6 +844,8 @@ pub struct CxOs {
     pub (crate) display: Option<CxAndroidDisplay>,
     pub (crate) media: CxAndroidMedia,
     pub (crate) video_surfaces: HashMap<LiveId, jobject>,
+    pub (crate) gl_profiler: GlProfiler,
+    pub (crate) gl_readbacks: GlReadbacks,
 }
 
 impl CxAndroidDisplay {
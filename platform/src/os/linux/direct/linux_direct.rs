@@ -9,7 +9,9 @@ use {
     self::super::super::{
         gl_sys,
         select_timer::SelectTimers,
-        linux_media::CxLinuxMedia
+        linux_media::CxLinuxMedia,
+        http::make_http_request,
+        opengl::{GlProfiler, GlReadbacks},
     },
     crate::{
         cx_api::{CxOsOp, CxOsApi},
@@ -20,6 +22,7 @@ use {
             TimerEvent,
             Event,
             WindowGeom,
+            NetworkResponseChannel,
         },
         window::CxWindowPool,
         pass::CxPassParent,
@@ -27,6 +30,7 @@ use {
         gpu_info::GpuPerformance,
         os::cx_native::EventFlow,
         pass::{PassClearColor, PassClearDepth, PassId},
+        file_dialogs::FileDialogKind,
     }
 };
 
@@ -134,11 +138,13 @@ impl Cx {
                     self.call_draw_event();
                     direct_app.egl.make_current();
                     self.opengl_compile_shaders();
+                    self.opengl_run_compute();
                 }
                 // ok here we send out to all our childprocesses
                 //profile_end("paint event handling", p);
                 //let p = profile_start();
                 self.handle_repaint(direct_app);
+                self.handle_readback_results();
                 //profile_end("paint openGL", p);
             }
             DirectEvent::MouseDown(e) => {
@@ -177,6 +183,7 @@ impl Cx {
             DirectEvent::Timer(e) => {
                 if e.timer_id == 0 {
                     if Signal::check_and_clear_ui_signal() {
+                        self.handle_networking_events();
                         self.handle_media_signals();
                         self.call_event_handler(&Event::Signal);
                     }
@@ -186,13 +193,23 @@ impl Cx {
                 }
             }
         }
-        if self.any_passes_dirty() || self.need_redrawing() || self.new_next_frames.len() != 0 {
+        if self.any_passes_dirty() || self.need_redrawing() || self.new_next_frames.len() != 0 || self.opengl_has_pending_readbacks() {
             EventFlow::Poll
         } else {
             EventFlow::Wait
         }
     }
     
+    pub(crate) fn handle_networking_events(&mut self) {
+        let mut out = Vec::new();
+        while let Ok(event) = self.os.network_response.receiver.try_recv() {
+            out.push(event);
+        }
+        if out.len()>0 {
+            self.call_event_handler(&Event::NetworkResponses(out))
+        }
+    }
+    
     pub fn draw_pass_to_fullscreen(
         &mut self,
         pass_id: PassId,
@@ -243,6 +260,7 @@ impl Cx {
             &mut zbias,
             zbias_step,
         );
+        self.opengl_read_back_pass(pass_id, 0, 0, direct_app.drm.width as usize, direct_app.drm.height as usize);
         
         unsafe {
             direct_app.drm.swap_buffers_and_wait(&direct_app.egl);
@@ -254,6 +272,7 @@ impl Cx {
         let mut passes_todo = Vec::new();
         self.compute_pass_repaint_order(&mut passes_todo);
         self.repaint_id += 1;
+        self.opengl_profile_begin_frame();
         for pass_id in &passes_todo {
             self.passes[*pass_id].set_time(direct_app.timers.time_now() as f32);
             match self.passes[*pass_id].parent.clone() {
@@ -268,6 +287,9 @@ impl Cx {
                 }
             }
         }
+        self.opengl_poll_readbacks();
+        self.opengl_read_back_textures();
+        self.opengl_profile_end_frame();
     }
     
     fn handle_platform_ops(&mut self, direct_app: &mut DirectApp) -> EventFlow {
@@ -297,6 +319,21 @@ impl Cx {
                 CxOsOp::StopTimer(timer_id) => {
                     direct_app.timers.stop_timer(timer_id);
                 },
+                CxOsOp::HttpRequest {request_id, request} => {
+                    make_http_request(request_id, request, self.os.network_response.sender.clone());
+                },
+                CxOsOp::SaveFileDialog(dialog) => {
+                    self.request_file_dialog(FileDialogKind::SaveFile, dialog);
+                }
+                CxOsOp::SelectFileDialog(dialog) => {
+                    self.request_file_dialog(FileDialogKind::SelectFile, dialog);
+                }
+                CxOsOp::SaveFolderDialog(dialog) => {
+                    self.request_file_dialog(FileDialogKind::SaveFolder, dialog);
+                }
+                CxOsOp::SelectFolderDialog(dialog) => {
+                    self.request_file_dialog(FileDialogKind::SelectFolder, dialog);
+                }
                 _ => ()
             }
         }
@@ -319,5 +356,8 @@ impl CxOsApi for Cx {
 #[derive(Default)]
 pub struct CxOs {
     pub (crate) media: CxLinuxMedia,
+    pub (crate) network_response: NetworkResponseChannel,
+    pub (crate) gl_profiler: GlProfiler,
+    pub (crate) gl_readbacks: GlReadbacks,
 }
 
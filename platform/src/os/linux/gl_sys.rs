@@ -17,6 +17,10 @@ pub mod types {
     pub type GLchar = raw::c_char;
     pub type GLubyte = raw::c_uchar;
     pub type GLsizeiptr = isize; 
+    pub type GLint64 = i64;
+    pub type GLuint64 = u64;
+    pub type GLintptr = isize;
+    pub type GLsync = *const raw::c_void;
 }
 pub use types::*; 
 
@@ -74,6 +78,26 @@ pub const TEXTURE_EXTERNAL_OES: types::GLenum = 0x8D65;
 pub const EXTENSIONS: types::GLenum = 0x1F03;
 pub const VENDOR: types::GLenum = 0x1F00;
 pub const RENDERER: types::GLenum = 0x1F01;
+pub const VERSION: types::GLenum = 0x1F02;
+pub const COMPUTE_SHADER: types::GLenum = 0x91B9;
+pub const SHADER_STORAGE_BUFFER: types::GLenum = 0x90D2;
+pub const PIXEL_UNPACK_BUFFER: types::GLenum = 0x88EC;
+pub const DYNAMIC_COPY: types::GLenum = 0x88EA;
+pub const RGBA32F: types::GLenum = 0x8814;
+pub const PACK_ALIGNMENT: types::GLenum = 0x0D05;
+pub const TIMESTAMP: types::GLenum = 0x8E28;
+pub const QUERY_RESULT: types::GLenum = 0x8866;
+pub const QUERY_RESULT_AVAILABLE: types::GLenum = 0x8867;
+pub const VERTEX_ATTRIB_ARRAY_BARRIER_BIT: types::GLbitfield = 0x00000001;
+pub const TEXTURE_FETCH_BARRIER_BIT: types::GLbitfield = 0x00000008;
+pub const PIXEL_BUFFER_BARRIER_BIT: types::GLbitfield = 0x00000080;
+pub const SHADER_STORAGE_BARRIER_BIT: types::GLbitfield = 0x00002000;
+pub const PIXEL_PACK_BUFFER: types::GLenum = 0x88EB;
+pub const STREAM_READ: types::GLenum = 0x88E1;
+pub const MAP_READ_BIT: types::GLbitfield = 0x0001;
+pub const SYNC_GPU_COMMANDS_COMPLETE: types::GLenum = 0x9117;
+pub const SYNC_FLUSH_COMMANDS_BIT: types::GLbitfield = 0x00000001;
+pub const TIMEOUT_EXPIRED: types::GLenum = 0x911B;
 
 #[inline] pub unsafe fn GenVertexArrays(n: types::GLsizei, arrays: *mut types::GLuint) -> () {mem::transmute::<_, extern "system" fn(types::GLsizei, *mut types::GLuint) -> ()>(storage::GenVertexArrays.f)(n, arrays)}
 #[inline] pub unsafe fn BindVertexArray(array: types::GLuint) -> () {mem::transmute::<_, extern "system" fn(types::GLuint) -> ()>(storage::BindVertexArray.f)(array)}
@@ -97,6 +121,21 @@ pub const RENDERER: types::GLenum = 0x1F01;
 #[inline] pub unsafe fn GenFramebuffers(n: types::GLsizei, framebuffers: *mut types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLsizei, *mut types::GLuint) -> ()>(storage::GenFramebuffers.f)(n, framebuffers) }
 #[inline] pub unsafe fn GenRenderbuffers(n: types::GLsizei, renderbuffers: *mut types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLsizei, *mut types::GLuint) -> ()>(storage::GenRenderbuffers.f)(n, renderbuffers) }
 #[inline] pub unsafe fn BindRenderbuffer(target: types::GLenum, renderbuffer: types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLenum, types::GLuint) -> ()>(storage::BindRenderbuffer.f)(target, renderbuffer) }
+#[inline] pub unsafe fn DispatchCompute(num_groups_x: types::GLuint, num_groups_y: types::GLuint, num_groups_z: types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLuint, types::GLuint, types::GLuint) -> ()>(storage::DispatchCompute.f)(num_groups_x, num_groups_y, num_groups_z) }
+#[inline] pub unsafe fn BindBufferBase(target: types::GLenum, index: types::GLuint, buffer: types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLenum, types::GLuint, types::GLuint) -> ()>(storage::BindBufferBase.f)(target, index, buffer) }
+#[inline] pub unsafe fn MemoryBarrier(barriers: types::GLbitfield) -> () { mem::transmute::<_, extern "system" fn(types::GLbitfield) -> ()>(storage::MemoryBarrier.f)(barriers) }
+#[inline] pub unsafe fn ReadPixels(x: types::GLint, y: types::GLint, width: types::GLsizei, height: types::GLsizei, format: types::GLenum, type_: types::GLenum, pixels: *mut raw::c_void) -> () { mem::transmute::<_, extern "system" fn(types::GLint, types::GLint, types::GLsizei, types::GLsizei, types::GLenum, types::GLenum, *mut raw::c_void) -> ()>(storage::ReadPixels.f)(x, y, width, height, format, type_, pixels) }
+#[inline] pub unsafe fn GenQueries(n: types::GLsizei, ids: *mut types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLsizei, *mut types::GLuint) -> ()>(storage::GenQueries.f)(n, ids) }
+#[inline] pub unsafe fn DeleteQueries(n: types::GLsizei, ids: *const types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLsizei, *const types::GLuint) -> ()>(storage::DeleteQueries.f)(n, ids) }
+#[inline] pub unsafe fn QueryCounter(id: types::GLuint, target: types::GLenum) -> () { mem::transmute::<_, extern "system" fn(types::GLuint, types::GLenum) -> ()>(storage::QueryCounter.f)(id, target) }
+#[inline] pub unsafe fn GetQueryObjectuiv(id: types::GLuint, pname: types::GLenum, params: *mut types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLuint, types::GLenum, *mut types::GLuint) -> ()>(storage::GetQueryObjectuiv.f)(id, pname, params) }
+#[inline] pub unsafe fn GetQueryObjectui64v(id: types::GLuint, pname: types::GLenum, params: *mut types::GLuint64) -> () { mem::transmute::<_, extern "system" fn(types::GLuint, types::GLenum, *mut types::GLuint64) -> ()>(storage::GetQueryObjectui64v.f)(id, pname, params) }
+#[inline] pub unsafe fn GetInteger64v(pname: types::GLenum, data: *mut types::GLint64) -> () { mem::transmute::<_, extern "system" fn(types::GLenum, *mut types::GLint64) -> ()>(storage::GetInteger64v.f)(pname, data) }
+#[inline] pub unsafe fn FenceSync(condition: types::GLenum, flags: types::GLbitfield) -> types::GLsync { mem::transmute::<_, extern "system" fn(types::GLenum, types::GLbitfield) -> types::GLsync>(storage::FenceSync.f)(condition, flags) }
+#[inline] pub unsafe fn ClientWaitSync(sync: types::GLsync, flags: types::GLbitfield, timeout: types::GLuint64) -> types::GLenum { mem::transmute::<_, extern "system" fn(types::GLsync, types::GLbitfield, types::GLuint64) -> types::GLenum>(storage::ClientWaitSync.f)(sync, flags, timeout) }
+#[inline] pub unsafe fn DeleteSync(sync: types::GLsync) -> () { mem::transmute::<_, extern "system" fn(types::GLsync) -> ()>(storage::DeleteSync.f)(sync) }
+#[inline] pub unsafe fn MapBufferRange(target: types::GLenum, offset: types::GLintptr, length: types::GLsizeiptr, access: types::GLbitfield) -> *mut raw::c_void { mem::transmute::<_, extern "system" fn(types::GLenum, types::GLintptr, types::GLsizeiptr, types::GLbitfield) -> *mut raw::c_void>(storage::MapBufferRange.f)(target, offset, length, access) }
+#[inline] pub unsafe fn UnmapBuffer(target: types::GLenum) -> types::GLboolean { mem::transmute::<_, extern "system" fn(types::GLenum) -> types::GLboolean>(storage::UnmapBuffer.f)(target) }
 #[inline] pub unsafe fn RenderbufferStorage(target: types::GLenum, internalformat: types::GLenum, width: types::GLsizei, height: types::GLsizei) -> () { mem::transmute::<_, extern "system" fn(types::GLenum, types::GLenum, types::GLsizei, types::GLsizei) -> ()>(storage::RenderbufferStorage.f)(target, internalformat, width, height) }
 #[inline] pub unsafe fn Disable(cap: types::GLenum) -> () { mem::transmute::<_, extern "system" fn(types::GLenum) -> ()>(storage::Disable.f)(cap) }
 #[inline] pub unsafe fn FramebufferRenderbuffer(target: types::GLenum, attachment: types::GLenum, renderbuffertarget: types::GLenum, renderbuffer: types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLenum, types::GLenum, types::GLenum, types::GLuint) -> ()>(storage::FramebufferRenderbuffer.f)(target, attachment, renderbuffertarget, renderbuffer) }
@@ -195,6 +234,21 @@ mod storage {
     pub static mut GenerateMipmap: FnPtr = FnPtr::default();
     pub static mut PixelStorei: FnPtr = FnPtr::default();
     pub static mut GetString: FnPtr = FnPtr::default();
+    pub static mut DispatchCompute: FnPtr = FnPtr::default();
+    pub static mut BindBufferBase: FnPtr = FnPtr::default();
+    pub static mut MemoryBarrier: FnPtr = FnPtr::default();
+    pub static mut ReadPixels: FnPtr = FnPtr::default();
+    pub static mut GenQueries: FnPtr = FnPtr::default();
+    pub static mut DeleteQueries: FnPtr = FnPtr::default();
+    pub static mut QueryCounter: FnPtr = FnPtr::default();
+    pub static mut GetQueryObjectuiv: FnPtr = FnPtr::default();
+    pub static mut GetQueryObjectui64v: FnPtr = FnPtr::default();
+    pub static mut GetInteger64v: FnPtr = FnPtr::default();
+    pub static mut FenceSync: FnPtr = FnPtr::default();
+    pub static mut ClientWaitSync: FnPtr = FnPtr::default();
+    pub static mut DeleteSync: FnPtr = FnPtr::default();
+    pub static mut MapBufferRange: FnPtr = FnPtr::default();
+    pub static mut UnmapBuffer: FnPtr = FnPtr::default();
 }
 
 pub unsafe fn load_with<F>(mut loadfn: F) where F: FnMut(&'static str) -> *const raw::c_void {
@@ -257,6 +311,21 @@ pub unsafe fn load_with<F>(mut loadfn: F) where F: FnMut(&'static str) -> *const
     storage::GenerateMipmap = FnPtr::new(metaloadfn(&mut loadfn, "glGenerateMipmap", &[]));
     storage::PixelStorei = FnPtr::new(metaloadfn(&mut loadfn, "glPixelStorei", &[]));
     storage::GetString = FnPtr::new(metaloadfn(&mut loadfn, "glGetString", &[]));
+    storage::DispatchCompute = FnPtr::new(metaloadfn(&mut loadfn, "glDispatchCompute", &[]));
+    storage::BindBufferBase = FnPtr::new(metaloadfn(&mut loadfn, "glBindBufferBase", &["glBindBufferBaseEXT"]));
+    storage::MemoryBarrier = FnPtr::new(metaloadfn(&mut loadfn, "glMemoryBarrier", &["glMemoryBarrierEXT"]));
+    storage::ReadPixels = FnPtr::new(metaloadfn(&mut loadfn, "glReadPixels", &[]));
+    storage::GenQueries = FnPtr::new(metaloadfn(&mut loadfn, "glGenQueries", &["glGenQueriesEXT"]));
+    storage::DeleteQueries = FnPtr::new(metaloadfn(&mut loadfn, "glDeleteQueries", &["glDeleteQueriesEXT"]));
+    storage::QueryCounter = FnPtr::new(metaloadfn(&mut loadfn, "glQueryCounter", &["glQueryCounterEXT"]));
+    storage::GetQueryObjectuiv = FnPtr::new(metaloadfn(&mut loadfn, "glGetQueryObjectuiv", &["glGetQueryObjectuivEXT"]));
+    storage::GetQueryObjectui64v = FnPtr::new(metaloadfn(&mut loadfn, "glGetQueryObjectui64v", &["glGetQueryObjectui64vEXT"]));
+    storage::GetInteger64v = FnPtr::new(metaloadfn(&mut loadfn, "glGetInteger64v", &["glGetInteger64vEXT"]));
+    storage::FenceSync = FnPtr::new(metaloadfn(&mut loadfn, "glFenceSync", &["glFenceSyncAPPLE"]));
+    storage::ClientWaitSync = FnPtr::new(metaloadfn(&mut loadfn, "glClientWaitSync", &["glClientWaitSyncAPPLE"]));
+    storage::DeleteSync = FnPtr::new(metaloadfn(&mut loadfn, "glDeleteSync", &["glDeleteSyncAPPLE"]));
+    storage::MapBufferRange = FnPtr::new(metaloadfn(&mut loadfn, "glMapBufferRange", &["glMapBufferRangeEXT"]));
+    storage::UnmapBuffer = FnPtr::new(metaloadfn(&mut loadfn, "glUnmapBuffer", &["glUnmapBufferOES"]));
 }
 
 #[inline(never)]
@@ -0,0 +1,558 @@
+use {
+    std::{
+        io::{self, BufRead, BufReader, Read, Write},
+        net::TcpStream,
+        os::unix::io::AsRawFd,
+        sync::{mpsc::Sender, OnceLock},
+        time::Duration,
+    },
+    self::super::openssl_sys::*,
+    crate::{
+        makepad_live_id::*,
+        thread::SignalToUI,
+        event::{
+            NetworkResponseItem,
+            NetworkResponse,
+            HttpRequest,
+            HttpResponse
+        },
+    }
+};
+
+const MAX_REDIRECTS: usize = 10;
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+const SOCKET_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub fn make_http_request(request_id: LiveId, request: HttpRequest, networking_sender: Sender<NetworkResponseItem>) {
+    std::thread::spawn(move || {
+        let response = match fetch(request_id, &request, &networking_sender) {
+            Ok(response) => NetworkResponse::HttpResponse(response),
+            Err(error) => NetworkResponse::HttpRequestError(error)
+        };
+        let _ = networking_sender.send(NetworkResponseItem {
+            request_id,
+            response
+        });
+        SignalToUI::set_ui_signal();
+    });
+}
+
+fn fetch(request_id: LiveId, request: &HttpRequest, networking_sender: &Sender<NetworkResponseItem>) -> Result<HttpResponse, String> {
+    let mut url = HttpUrl::parse(&request.url) ?;
+    let mut method = request.method.to_string().to_string();
+    let mut body = request.body.as_deref();
+    let mut cross_origin = false;
+
+    for _ in 0..=MAX_REDIRECTS {
+        let mut stream = HttpStream::connect(&url, request.ignore_ssl_cert) ?;
+
+        let mut head = format!("{} {} HTTP/1.1\r\nHost: {}\r\n", method, url.path, url.host_header());
+        for (name, values) in &request.headers {
+            let lower = name.to_ascii_lowercase();
+            if lower == "host" || lower == "content-length" || lower == "connection" {
+                continue;
+            }
+            // dont leak credentials to another origin on redirect
+            if cross_origin && (lower == "authorization" || lower == "cookie") {
+                continue;
+            }
+            head.push_str(&format!("{}: {}\r\n", name, values.join(",")));
+        }
+        if !request.headers.keys().any( | k | k.eq_ignore_ascii_case("user-agent")) {
+            head.push_str("User-Agent: makepad\r\n");
+        }
+        if let Some(body) = body {
+            head.push_str(&format!("Content-Length: {}\r\n", body.len()));
+        }
+        head.push_str("Connection: close\r\n\r\n");
+
+        stream.write_all(head.as_bytes()).map_err( | e | format!("Error writing request: {}", e)) ?;
+        if let Some(body) = body {
+            stream.write_all(body).map_err( | e | format!("Error writing request body: {}", e)) ?;
+        }
+        stream.flush().map_err( | e | format!("Error writing request: {}", e)) ?;
+
+        let mut reader = BufReader::with_capacity(READ_BUFFER_SIZE, stream);
+        let (status_code, headers) = read_response_head(&mut reader) ?;
+
+        if let (301 | 302 | 303 | 307 | 308, Some(location)) = (status_code, find_header(&headers, "location")) {
+            let next_url = url.join(location) ?;
+            cross_origin = cross_origin || next_url.origin() != url.origin();
+            url = next_url;
+            if status_code == 303 || (status_code <= 302 && method == "POST") {
+                method = "GET".to_string();
+                body = None;
+            }
+            continue;
+        }
+
+        let has_body = method != "HEAD" && status_code >= 200 && status_code != 204 && status_code != 304;
+        let body = if has_body {
+            let mut progress = | loaded: u64, total: u64 | {
+                let _ = networking_sender.send(NetworkResponseItem {
+                    request_id,
+                    response: NetworkResponse::HttpProgress {loaded, total}
+                });
+                SignalToUI::set_ui_signal();
+            };
+            let chunked = find_header(&headers, "transfer-encoding")
+                .map( | v | v.to_ascii_lowercase().contains("chunked"))
+                .unwrap_or(false);
+            if chunked {
+                Some(read_chunked_body(&mut reader, &mut progress) ?)
+            }
+            else {
+                let content_length = find_header(&headers, "content-length").and_then( | v | v.trim().parse::<u64>().ok());
+                Some(read_plain_body(&mut reader, content_length, &mut progress) ?)
+            }
+        }
+        else {
+            None
+        };
+
+        let mut response = HttpResponse::new(request.metadata_id, status_code, "".to_string(), body);
+        for (name, value) in headers {
+            response.set_header(name, value);
+        }
+        return Ok(response)
+    }
+    Err(format!("Too many redirects fetching {}", request.url))
+}
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers.iter().find( | (k, _) | k.eq_ignore_ascii_case(name)).map( | (_, v) | v.as_str())
+}
+
+fn read_line<R: BufRead>(reader: &mut R) -> Result<String, String> {
+    let mut line = String::new();
+    let len = reader.read_line(&mut line).map_err( | e | format!("Error reading response: {}", e)) ?;
+    if len == 0 {
+        return Err("Connection closed before response was complete".to_string())
+    }
+    Ok(line.trim_end_matches(&['\r', '\n'][..]).to_string())
+}
+
+fn read_response_head<R: BufRead>(reader: &mut R) -> Result<(u16, Vec<(String, String)>), String> {
+    loop {
+        let status_line = read_line(reader) ?;
+        let mut parts = status_line.splitn(3, ' ');
+        let version = parts.next().unwrap_or("");
+        if !version.starts_with("HTTP/") {
+            return Err(format!("Invalid status line: {}", status_line))
+        }
+        let status_code = parts.next()
+            .and_then( | v | v.parse::<u16>().ok())
+            .ok_or_else( | | format!("Invalid status line: {}", status_line)) ?;
+
+        let mut headers = Vec::new();
+        loop {
+            let line = read_line(reader) ?;
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                headers.push((name.trim().to_string(), value.trim().to_string()));
+            }
+        }
+        // skip interim responses such as 100 Continue
+        if (100..200).contains(&status_code) && status_code != 101 {
+            continue;
+        }
+        return Ok((status_code, headers))
+    }
+}
+
+fn read_plain_body<R: Read>(reader: &mut R, content_length: Option<u64>, progress: &mut dyn FnMut(u64, u64)) -> Result<Vec<u8>, String> {
+    let total = content_length.unwrap_or(0);
+    let mut body = Vec::with_capacity(total.min(1 << 24) as usize);
+    let mut buf = vec![0u8; READ_BUFFER_SIZE];
+    loop {
+        if let Some(content_length) = content_length {
+            if body.len() as u64 >= content_length {
+                break;
+            }
+        }
+        let len = match reader.read(&mut buf) {
+            Ok(len) => len,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(format!("Error reading response body: {}", e))
+        };
+        if len == 0 {
+            if let Some(content_length) = content_length {
+                return Err(format!("Connection closed after {} of {} bytes", body.len(), content_length))
+            }
+            break;
+        }
+        body.extend_from_slice(&buf[0..len]);
+        progress(body.len() as u64, total);
+    }
+    if let Some(content_length) = content_length {
+        body.truncate(content_length as usize);
+    }
+    Ok(body)
+}
+
+fn read_chunked_body<R: BufRead>(reader: &mut R, progress: &mut dyn FnMut(u64, u64)) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+    loop {
+        let line = read_line(reader) ?;
+        let size_str = line.split(';').next().unwrap_or("").trim();
+        let size = usize::from_str_radix(size_str, 16)
+            .map_err( | _ | format!("Invalid chunk size: {}", line)) ?;
+        if size == 0 {
+            // consume the trailer section
+            while !read_line(reader) ?.is_empty() {}
+            return Ok(body)
+        }
+        let start = body.len();
+        body.resize(start + size, 0);
+        reader.read_exact(&mut body[start..]).map_err( | e | format!("Error reading chunk: {}", e)) ?;
+        if !read_line(reader) ?.is_empty() {
+            return Err("Chunk not terminated by CRLF".to_string())
+        }
+        progress(body.len() as u64, 0);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct HttpUrl {
+    secure: bool,
+    host: String,
+    port: u16,
+    path: String,
+}
+
+impl HttpUrl {
+    fn parse(url: &str) -> Result<Self, String> {
+        let (secure, rest) = if let Some(rest) = url.strip_prefix("http://") {
+            (false, rest)
+        }
+        else if let Some(rest) = url.strip_prefix("https://") {
+            (true, rest)
+        }
+        else {
+            return Err(format!("Unsupported url scheme: {}", url))
+        };
+        let (authority, path) = match rest.find(['/', '?', '#']) {
+            Some(index) => rest.split_at(index),
+            None => (rest, "/")
+        };
+        // strip userinfo and fragment, neither is sent on the wire
+        let authority = authority.rsplit('@').next().unwrap();
+        let path = path.split('#').next().unwrap();
+        let path = if path.starts_with('?') {format!("/{}", path)} else if path.is_empty() {"/".to_string()} else {path.to_string()};
+
+        let default_port = if secure {443} else {80};
+        let (host, port) = if let Some(v6) = authority.strip_prefix('[') {
+            let end = v6.find(']').ok_or_else( | | format!("Invalid host in url: {}", url)) ?;
+            let port = v6[end + 1..].strip_prefix(':');
+            (v6[..end].to_string(), port)
+        }
+        else {
+            match authority.split_once(':') {
+                Some((host, port)) => (host.to_string(), Some(port)),
+                None => (authority.to_string(), None)
+            }
+        };
+        let port = match port {
+            Some(port) => port.parse::<u16>().map_err( | _ | format!("Invalid port in url: {}", url)) ?,
+            None => default_port
+        };
+        if host.is_empty() {
+            return Err(format!("Missing host in url: {}", url))
+        }
+        Ok(Self {secure, host, port, path})
+    }
+
+    fn join(&self, location: &str) -> Result<Self, String> {
+        if location.starts_with("http://") || location.starts_with("https://") {
+            return Self::parse(location)
+        }
+        let mut url = self.clone();
+        if let Some(rest) = location.strip_prefix("//") {
+            return Self::parse(&format!("{}://{}", if self.secure {"https"} else {"http"}, rest))
+        }
+        if location.starts_with('/') {
+            url.path = location.to_string();
+        }
+        else {
+            let base = self.path.split('?').next().unwrap();
+            let dir = &base[..base.rfind('/').map( | i | i + 1).unwrap_or(0)];
+            url.path = format!("{}{}", if dir.is_empty() {"/"} else {dir}, location);
+        }
+        Ok(url)
+    }
+
+    fn origin(&self) -> (bool, &str, u16) {
+        (self.secure, &self.host, self.port)
+    }
+
+    fn host_header(&self) -> String {
+        let host = if self.host.contains(':') {format!("[{}]", self.host)} else {self.host.clone()};
+        if self.port == if self.secure {443} else {80} {
+            host
+        }
+        else {
+            format!("{}:{}", host, self.port)
+        }
+    }
+}
+
+enum HttpStream {
+    Plain(TcpStream),
+    Tls(TlsStream),
+}
+
+impl HttpStream {
+    fn connect(url: &HttpUrl, ignore_ssl_cert: bool) -> Result<Self, String> {
+        let tcp = TcpStream::connect((url.host.as_str(), url.port))
+            .map_err( | e | format!("Error connecting to {}:{}: {}", url.host, url.port, e)) ?;
+        let _ = tcp.set_read_timeout(Some(SOCKET_TIMEOUT));
+        let _ = tcp.set_write_timeout(Some(SOCKET_TIMEOUT));
+        if url.secure {
+            Ok(HttpStream::Tls(TlsStream::connect(tcp, &url.host, ignore_ssl_cert) ?))
+        }
+        else {
+            Ok(HttpStream::Plain(tcp))
+        }
+    }
+}
+
+impl Read for HttpStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            HttpStream::Plain(tcp) => tcp.read(buf),
+            HttpStream::Tls(tls) => tls.read(buf),
+        }
+    }
+}
+
+impl Write for HttpStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            HttpStream::Plain(tcp) => tcp.write(buf),
+            HttpStream::Tls(tls) => tls.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            HttpStream::Plain(tcp) => tcp.flush(),
+            HttpStream::Tls(_) => Ok(()),
+        }
+    }
+}
+
+fn lib_ssl() -> Option<&'static LibSsl> {
+    static LIB_SSL: OnceLock<Option<LibSsl>> = OnceLock::new();
+    LIB_SSL.get_or_init(LibSsl::try_load).as_ref()
+}
+
+struct TlsStream {
+    lib: &'static LibSsl,
+    ctx: *mut SSL_CTX,
+    ssl: *mut SSL,
+    // keeps the socket open for as long as openssl refers to its fd
+    _tcp: TcpStream,
+}
+
+// the SSL objects are only ever touched from the thread owning the stream
+unsafe impl Send for TlsStream {}
+
+impl TlsStream {
+    fn connect(tcp: TcpStream, host: &str, ignore_ssl_cert: bool) -> Result<Self, String> {
+        let lib = lib_ssl().ok_or_else( | | "https is unavailable: libssl could not be loaded".to_string()) ?;
+        let host_c = std::ffi::CString::new(host).map_err( | _ | "Invalid host name".to_string()) ?;
+        unsafe {
+            let ctx = (lib.SSL_CTX_new)((lib.TLS_client_method)());
+            if ctx.is_null() {
+                return Err("SSL_CTX_new failed".to_string())
+            }
+            let ssl = (lib.SSL_new)(ctx);
+            // construct early so Drop cleans up on any failure below
+            let stream = TlsStream {lib, ctx, ssl, _tcp: tcp};
+            if ssl.is_null() {
+                return Err("SSL_new failed".to_string())
+            }
+            if ignore_ssl_cert {
+                (lib.SSL_CTX_set_verify)(ctx, SSL_VERIFY_NONE, std::ptr::null());
+            }
+            else {
+                (lib.SSL_CTX_set_default_verify_paths)(ctx);
+                (lib.SSL_CTX_set_verify)(ctx, SSL_VERIFY_PEER, std::ptr::null());
+                (lib.SSL_set1_host)(ssl, host_c.as_ptr());
+            }
+            (lib.SSL_ctrl)(ssl, SSL_CTRL_SET_TLSEXT_HOSTNAME, TLSEXT_NAMETYPE_host_name, host_c.as_ptr() as *mut _);
+            (lib.SSL_set_fd)(ssl, stream._tcp.as_raw_fd());
+            let ret = (lib.SSL_connect)(ssl);
+            if ret != 1 {
+                return Err(format!("TLS handshake with {} failed (ssl error {})", host, (lib.SSL_get_error)(ssl, ret)))
+            }
+            Ok(stream)
+        }
+    }
+}
+
+impl Read for TlsStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(i32::MAX as usize) as i32;
+        let ret = unsafe {(self.lib.SSL_read)(self.ssl, buf.as_mut_ptr() as *mut _, len)};
+        if ret > 0 {
+            return Ok(ret as usize)
+        }
+        match unsafe {(self.lib.SSL_get_error)(self.ssl, ret)} {
+            SSL_ERROR_ZERO_RETURN => Ok(0),
+            // servers commonly close without close_notify once Content-Length is satisfied
+            _ if ret == 0 => Ok(0),
+            err => Err(io::Error::other(format!("SSL_read failed (ssl error {})", err)))
+        }
+    }
+}
+
+impl Write for TlsStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len().min(i32::MAX as usize) as i32;
+        let ret = unsafe {(self.lib.SSL_write)(self.ssl, buf.as_ptr() as *const _, len)};
+        if ret > 0 {
+            return Ok(ret as usize)
+        }
+        let err = unsafe {(self.lib.SSL_get_error)(self.ssl, ret)};
+        Err(io::Error::other(format!("SSL_write failed (ssl error {})", err)))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for TlsStream {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.ssl.is_null() {
+                (self.lib.SSL_shutdown)(self.ssl);
+                (self.lib.SSL_free)(self.ssl);
+            }
+            (self.lib.SSL_CTX_free)(self.ctx);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::event::HttpMethod,
+        std::{
+            net::TcpListener,
+            sync::mpsc::channel,
+        },
+    };
+
+    // serves each canned response to one connection, returning the raw requests it saw
+    fn serve(responses: Vec<&'static str>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for response in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut request = String::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    request.push_str(&line);
+                    if line == "\r\n" {
+                        break;
+                    }
+                }
+                if let Some(len) = request.lines()
+                    .find_map( | l | l.strip_prefix("Content-Length: "))
+                    .and_then( | l | l.trim().parse::<usize>().ok()) {
+                    let mut body = vec![0u8; len];
+                    reader.read_exact(&mut body).unwrap();
+                    request.push_str(std::str::from_utf8(&body).unwrap());
+                }
+                reader.into_inner().write_all(response.as_bytes()).unwrap();
+                requests.push(request);
+            }
+            requests
+        });
+        (base, handle)
+    }
+
+    fn run(request: HttpRequest) -> (Vec<NetworkResponse>, Result<HttpResponse, String>) {
+        let (sender, receiver) = channel();
+        make_http_request(live_id!(test), request, sender);
+        let mut progress = Vec::new();
+        loop {
+            let item = receiver.recv().unwrap();
+            assert_eq!(item.request_id, live_id!(test));
+            match item.response {
+                NetworkResponse::HttpResponse(response) => return (progress, Ok(response)),
+                NetworkResponse::HttpRequestError(err) => return (progress, Err(err)),
+                other => progress.push(other)
+            }
+        }
+    }
+
+    #[test]
+    fn parse_url() {
+        let url = HttpUrl::parse("https://example.com:8443/a/b?c=d#frag").unwrap();
+        assert_eq!(url, HttpUrl {secure: true, host: "example.com".into(), port: 8443, path: "/a/b?c=d".into()});
+        let url = HttpUrl::parse("http://[::1]?x").unwrap();
+        assert_eq!(url, HttpUrl {secure: false, host: "::1".into(), port: 80, path: "/?x".into()});
+        assert_eq!(url.join("y/z").unwrap().path, "/y/z");
+        assert_eq!(HttpUrl::parse("http://h/a/b").unwrap().join("c").unwrap().path, "/a/c");
+        assert!(HttpUrl::parse("ftp://h/").is_err());
+    }
+
+    #[test]
+    fn content_length_body() {
+        let (base, server) = serve(vec![
+            "HTTP/1.1 200 OK\r\nContent-Length: 5\r\nDate: Mon, 01 Jan 2024 10:00:00 GMT\r\n\r\nhello"
+        ]);
+        let mut request = HttpRequest::new(format!("{}/post", base), HttpMethod::POST);
+        request.set_header("X-Test".into(), "1".into());
+        request.set_body_string("ping");
+        let (progress, response) = run(request);
+        let response = response.unwrap();
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.get_string_body().unwrap(), "hello");
+        assert_eq!(response.headers["Date"], vec!["Mon, 01 Jan 2024 10:00:00 GMT".to_string()]);
+        assert!(matches!(progress.last(), Some(NetworkResponse::HttpProgress {loaded: 5, total: 5})));
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("POST /post HTTP/1.1\r\n"));
+        assert!(requests[0].contains("X-Test: 1\r\n"));
+        assert!(requests[0].ends_with("\r\n\r\nping"));
+    }
+
+    #[test]
+    fn chunked_body_after_redirect() {
+        let (base, server) = serve(vec![
+            "HTTP/1.1 302 Found\r\nLocation: /final\r\nContent-Length: 0\r\n\r\n",
+            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4;ext=1\r\nWiki\r\n6\r\npedia \r\nE\r\nin \r\n\r\nchunks.\r\n0\r\nX-Trailer: 1\r\n\r\n",
+        ]);
+        let (progress, response) = run(HttpRequest::new(format!("{}/start", base), HttpMethod::POST));
+        let response = response.unwrap();
+        assert_eq!(response.get_string_body().unwrap(), "Wikipedia in \r\n\r\nchunks.");
+        assert_eq!(progress.len(), 3);
+        let requests = server.join().unwrap();
+        assert!(requests[1].starts_with("GET /final HTTP/1.1\r\n"));
+    }
+
+    #[test]
+    fn read_until_close_and_errors() {
+        let (base, server) = serve(vec!["HTTP/1.0 404 Not Found\r\n\r\nmissing"]);
+        let (_, response) = run(HttpRequest::new(base, HttpMethod::GET));
+        let response = response.unwrap();
+        assert_eq!(response.status_code, 404);
+        assert_eq!(response.get_string_body().unwrap(), "missing");
+        server.join().unwrap();
+
+        let (base, server) = serve(vec!["HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nshort"]);
+        let (_, response) = run(HttpRequest::new(base, HttpMethod::GET));
+        assert!(response.is_err());
+        server.join().unwrap();
+    }
+}
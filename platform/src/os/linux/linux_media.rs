@@ -5,6 +5,7 @@ use {
         alsa_audio::AlsaAudioAccess,
         pulse_audio::PulseAudioAccess,
         alsa_midi::*,
+        v4l2_camera::V4l2CameraAccess,
     },
     crate::{
         cx::Cx,
@@ -35,6 +36,12 @@ impl Cx {
                 descs,
             }));
         }
+        if self.os.media.v4l2_camera_change.check_and_clear() {
+            let descs = self.os.media.v4l2_camera().lock().unwrap().get_updated_descs();
+            self.call_event_handler(&Event::VideoInputs(VideoInputsEvent {
+                descs
+            }));
+        }
     }
 }
 
@@ -45,6 +52,8 @@ pub struct CxLinuxMedia {
     pub (crate) audio_change: SignalToUI,
     pub (crate) alsa_midi: Option<Arc<Mutex<AlsaMidiAccess >> >,
     pub (crate) alsa_midi_change: SignalToUI,
+    pub (crate) v4l2_camera: Option<Arc<Mutex<V4l2CameraAccess >> >,
+    pub (crate) v4l2_camera_change: SignalToUI,
 }
 
 impl CxLinuxMedia {
@@ -68,6 +77,13 @@ impl CxLinuxMedia {
         }
         self.alsa_midi.as_ref().unwrap().clone()
     }
+    
+    pub fn v4l2_camera(&mut self) -> Arc<Mutex<V4l2CameraAccess >> {
+        if self.v4l2_camera.is_none() {
+            self.v4l2_camera = Some(V4l2CameraAccess::new(self.v4l2_camera_change.clone()));
+        }
+        self.v4l2_camera.as_ref().unwrap().clone()
+    }
 
 
 }
@@ -111,10 +127,12 @@ impl CxMediaApi for Cx {
         *self.os.media.alsa_audio().lock().unwrap().audio_input_cb[index].lock().unwrap() = Some(f);
     }    
     
-    fn video_input_box(&mut self, _index: usize, _f: VideoInputFn){
+    fn video_input_box(&mut self, index: usize, f: VideoInputFn){
+        *self.os.media.v4l2_camera().lock().unwrap().video_input_cb[index].lock().unwrap() = Some(f);
     }
     
-    fn use_video_input(&mut self, _inputs: &[(VideoInputId, VideoFormatId)]) {
+    fn use_video_input(&mut self, inputs: &[(VideoInputId, VideoFormatId)]) {
+        self.os.media.v4l2_camera().lock().unwrap().use_video_input(inputs);
     }
 }
 
@@ -29,9 +29,17 @@ pub mod select_timer;
 pub mod pulse_audio; 
 #[cfg(not(target_os="android"))]
 pub mod pulse_sys;
+#[cfg(not(target_os="android"))]
+pub mod v4l2_sys;
+#[cfg(not(target_os="android"))]
+pub mod v4l2_camera;
 
 #[cfg(not(target_os="android"))]
 mod web_socket;
+#[cfg(not(target_os="android"))]
+mod openssl_sys;
+#[cfg(not(target_os="android"))]
+pub mod http;
 
 #[cfg(target_os="android")]
 pub mod android;
@@ -11,11 +11,14 @@ use {
         makepad_live_id::*,
         makepad_shader_compiler::generate_glsl,
         cx::{Cx, OsType, OsType::Android},
-        texture::{Texture, TextureFormat, TexturePixel, CxTexture},
+        texture::{Texture, TextureFormat, TexturePixel, TextureCategory, CxTexture},
         makepad_math::{Mat4, DVec2, Vec4},
         pass::{PassClearColor, PassClearDepth, PassId},
         draw_list::DrawListId,
         draw_shader::{CxDrawShaderMapping, DrawShaderTextureInput},
+        compute_shader::{CxComputeOp, CxComputeBuffer},
+        readback::{ReadbackEvent, ReadbackImage, ReadbackPixels},
+        studio::{AppToStudio, FrameSample, GPUDrawCallSample},
         event::{Event, TextureHandleReadyEvent}
     },
 };
@@ -80,7 +83,15 @@ impl Cx {
                 draw_call.draw_uniforms.set_zbias(*zbias);
                 *zbias += zbias_step;
                 
-                let instances = (draw_item.instances.as_ref().unwrap().len() / sh.mapping.instances.total_slots) as u64;
+                // instances drawn from a compute buffer replace the ones written from the CPU
+                let (inst_vb, instances) = if let Some(buffer) = &draw_call.instance_buffer {
+                    let cxbuffer = &mut self.compute_buffers[buffer.compute_buffer_id()];
+                    cxbuffer.update_gl_buffer();
+                    (cxbuffer.os.gl_buffer, (cxbuffer.data.len() / sh.mapping.instances.total_slots) as u64)
+                }
+                else {
+                    (draw_item.os.inst_vb.gl_buffer, (draw_item.instances.as_ref().unwrap().len() / sh.mapping.instances.total_slots) as u64)
+                };
                 
                 if instances == 0 {
                     continue;
@@ -119,7 +130,7 @@ impl Cx {
                 }
                 
                 let vao = draw_item.os.vao.as_mut().unwrap();
-                if vao.inst_vb != draw_item.os.inst_vb.gl_buffer
+                if vao.inst_vb != inst_vb
                     || vao.geom_vb != geometry.os.vb.gl_buffer
                     || vao.geom_ib != geometry.os.ib.gl_buffer
                     || vao.shader_id != Some(draw_call.draw_shader.draw_shader_id) {
@@ -135,7 +146,7 @@ impl Cx {
                     });    
                     
                     vao.shader_id = Some(draw_call.draw_shader.draw_shader_id);
-                    vao.inst_vb = draw_item.os.inst_vb.gl_buffer;
+                    vao.inst_vb = inst_vb;
                     vao.geom_vb = geometry.os.vb.gl_buffer;
                     vao.geom_ib = geometry.os.ib.gl_buffer;
                     unsafe {
@@ -166,7 +177,6 @@ impl Cx {
                     gl_sys::UseProgram(shgl.program);
                     
                     gl_sys::BindVertexArray(draw_item.os.vao.as_ref().unwrap().vao.unwrap());
-                    let instances = (draw_item.instances.as_ref().unwrap().len() / sh.mapping.instances.total_slots) as u64;
                     
                     let pass_uniforms = self.passes[pass_id].pass_uniforms.as_slice();
                     let draw_list_uniforms = draw_list.draw_list_uniforms.as_slice();
@@ -232,6 +242,7 @@ impl Cx {
                         gl_sys::Uniform1i(shgl.textures[i].loc, i as i32);
                     }
                     
+                    let timed = self.os.gl_profiler.begin_draw_call( || sh.type_name.to_string(), instances);
                     gl_sys::DrawElementsInstanced(
                         gl_sys::TRIANGLES,
                         indices as i32,
@@ -239,6 +250,7 @@ impl Cx {
                         ptr::null(),
                         instances as i32
                     );
+                    self.os.gl_profiler.end_draw_call(timed);
                     
                     gl_sys::BindVertexArray(0);
                 }
@@ -488,6 +500,208 @@ impl Cx {
             }
         }
         self.draw_shaders.compile_set.clear();
+        
+        for compute_shader_ptr in &self.compute_shaders.compile_set {
+            if let Some(compute_shader_id) = self.compute_shaders.ptr_to_id.get(compute_shader_ptr) {
+                let cx_shader = &mut self.compute_shaders.shaders[*compute_shader_id];
+                let compute_shader_def = self.shader_registry.draw_shader_defs.get(compute_shader_ptr);
+                
+                let compute = generate_glsl::generate_compute_shader(
+                    compute_shader_def.as_ref().unwrap(),
+                    &cx_shader.mapping.const_table,
+                    &self.shader_registry
+                );
+                if cx_shader.mapping.flags.debug {
+                    crate::log!("{}", compute);
+                }
+                cx_shader.os = CxOsComputeShader::new(&compute, &self.os_type);
+            }
+        }
+        self.compute_shaders.compile_set.clear();
+    }
+    
+    // runs the compute work queued since the last repaint, so the passes see its results
+    pub fn opengl_run_compute(&mut self) {
+        let ops = std::mem::take(&mut self.compute_shaders.ops);
+        if ops.is_empty() {
+            return
+        }
+        if !opengl_supports_compute() {
+            if !self.compute_shaders.warned_unsupported {
+                self.compute_shaders.warned_unsupported = true;
+                crate::log!("Compute shaders need OpenGL 4.3 or OpenGL ES 3.1, this context is {}", get_gl_string(gl_sys::VERSION));
+            }
+            return
+        }
+        for op in ops {
+            match op {
+                CxComputeOp::Dispatch(dispatch) => {
+                    let sh = &mut self.compute_shaders.shaders[dispatch.compute_shader_id];
+                    if sh.os.gl_program.is_none() && !sh.os.failed {
+                        match GlComputeProgram::new(&sh.os.compute, &sh.mapping) {
+                            Ok(program) => sh.os.gl_program = Some(program),
+                            Err(error) => {
+                                crate::log!("ERROR::SHADER::COMPUTE::COMPILATION_FAILED\n{}", error);
+                                sh.os.failed = true;
+                            }
+                        }
+                    }
+                    let glp = if let Some(glp) = &sh.os.gl_program {glp} else {continue};
+                    unsafe {
+                        gl_sys::UseProgram(glp.program);
+                        GlShader::set_uniform_array(&glp.user_uniforms, &dispatch.user_uniforms);
+                        GlShader::set_uniform_array(&glp.live_uniforms, &sh.mapping.live_uniforms_buf);
+                        let ct = &sh.mapping.const_table.table;
+                        if !ct.is_empty() {
+                            GlShader::set_uniform_array(&glp.const_table_uniform, ct);
+                        }
+                        for i in 0..sh.mapping.textures.len() {
+                            let texture_id = if let Some(texture) = &dispatch.texture_slots[i] {
+                                texture.texture_id()
+                            }else {
+                                continue;
+                            };
+                            let cxtexture = &mut self.textures[texture_id];
+                            if cxtexture.format.is_vec(){
+                                cxtexture.update_vec_texture();
+                            }
+                            gl_sys::ActiveTexture(gl_sys::TEXTURE0 + i as u32);
+                            gl_sys::BindTexture(gl_sys::TEXTURE_2D, cxtexture.os.gl_texture.unwrap_or(0));
+                            gl_sys::Uniform1i(glp.textures[i].loc, i as i32);
+                        }
+                        for i in 0..sh.buffers.len() {
+                            if let Some(buffer) = &dispatch.buffer_slots[i] {
+                                let cxbuffer = &mut self.compute_buffers[buffer.compute_buffer_id()];
+                                cxbuffer.update_gl_buffer();
+                                gl_sys::BindBufferBase(gl_sys::SHADER_STORAGE_BUFFER, i as u32, cxbuffer.os.gl_buffer.unwrap());
+                            }
+                        }
+                        let [x, y, z] = dispatch.groups;
+                        gl_sys::DispatchCompute(x, y, z);
+                        // the results can be read by a later dispatch, drawn as instances or copied to a texture
+                        gl_sys::MemoryBarrier(
+                            gl_sys::SHADER_STORAGE_BARRIER_BIT |
+                            gl_sys::VERTEX_ATTRIB_ARRAY_BARRIER_BIT |
+                            gl_sys::PIXEL_BUFFER_BARRIER_BIT |
+                            gl_sys::TEXTURE_FETCH_BARRIER_BIT
+                        );
+                        gl_sys::UseProgram(0);
+                    }
+                }
+                CxComputeOp::CopyToTexture {buffer, texture} => {
+                    let cxbuffer = &mut self.compute_buffers[buffer.compute_buffer_id()];
+                    cxbuffer.update_gl_buffer();
+                    let cxtexture = &mut self.textures[texture.texture_id()];
+                    let (width, height) = match &cxtexture.format {
+                        TextureFormat::VecRGBAf32 {width, height, ..} if width * height * 4 == cxbuffer.data.len() => (*width, *height),
+                        _ => {
+                            crate::log!("copy_compute_buffer_to_texture needs a VecRGBAf32 texture with 4 floats per pixel of the buffer");
+                            continue
+                        }
+                    };
+                    cxtexture.update_vec_texture();
+                    unsafe {
+                        gl_sys::BindBuffer(gl_sys::PIXEL_UNPACK_BUFFER, cxbuffer.os.gl_buffer.unwrap());
+                        gl_sys::BindTexture(gl_sys::TEXTURE_2D, cxtexture.os.gl_texture.unwrap());
+                        gl_sys::TexImage2D(
+                            gl_sys::TEXTURE_2D,
+                            0,
+                            gl_sys::RGBA32F as i32,
+                            width as i32,
+                            height as i32,
+                            0,
+                            gl_sys::RGBA,
+                            gl_sys::FLOAT,
+                            ptr::null()
+                        );
+                        gl_sys::BindTexture(gl_sys::TEXTURE_2D, 0);
+                        gl_sys::BindBuffer(gl_sys::PIXEL_UNPACK_BUFFER, 0);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Starts the readbacks of the window pass that was just drawn, before its buffers are
+    /// swapped. The framebuffer is read from row `y` up, where the pass viewport starts.
+    pub fn opengl_read_back_pass(&mut self, pass_id: PassId, gl_framebuffer: u32, y: usize, width: usize, height: usize) {
+        let request_ids = self.readbacks.take_pass_requests(pass_id);
+        if request_ids.is_empty() {
+            return
+        }
+        let readback = unsafe {
+            gl_sys::BindFramebuffer(gl_sys::FRAMEBUFFER, gl_framebuffer);
+            let readback = GlReadback::start(request_ids, y, width, height, false);
+            gl_sys::BindFramebuffer(gl_sys::FRAMEBUFFER, 0);
+            readback
+        };
+        self.os.gl_readbacks.pending.push(readback);
+    }
+
+    // render textures are read through a framebuffer, GLES has no glGetTexImage
+    pub fn opengl_read_back_textures(&mut self) {
+        let readbacks = self.take_texture_readbacks();
+        if readbacks.is_empty() {
+            return
+        }
+        let gl_framebuffer = unsafe {
+            let mut gl_framebuffer = std::mem::MaybeUninit::uninit();
+            gl_sys::GenFramebuffers(1, gl_framebuffer.as_mut_ptr());
+            gl_framebuffer.assume_init()
+        };
+        for (request_id, texture) in readbacks {
+            let cxtexture = &self.textures[texture.texture_id()];
+            match (cxtexture.os.gl_texture, &cxtexture.alloc) {
+                (Some(gl_texture), Some(alloc)) if matches!(alloc.category, TextureCategory::Render {..}) => unsafe {
+                    gl_sys::BindFramebuffer(gl_sys::FRAMEBUFFER, gl_framebuffer);
+                    gl_sys::FramebufferTexture2D(gl_sys::FRAMEBUFFER, gl_sys::COLOR_ATTACHMENT0, gl_sys::TEXTURE_2D, gl_texture, 0);
+                    let is_float = alloc.pixel != TexturePixel::BGRAu8;
+                    let readback = GlReadback::start(vec![request_id], 0, alloc.width, alloc.height, is_float);
+                    self.os.gl_readbacks.pending.push(readback);
+                }
+                _ => self.readbacks.results.push(ReadbackEvent {request_id, image: None})
+            }
+        }
+        unsafe {
+            gl_sys::BindFramebuffer(gl_sys::FRAMEBUFFER, 0);
+            gl_sys::DeleteFramebuffers(1, &gl_framebuffer);
+        }
+    }
+
+    /// Answers the readbacks whose copies the GPU has finished, without waiting for the others.
+    pub fn opengl_poll_readbacks(&mut self) {
+        // fences signal in submission order, so the readbacks are collected oldest first
+        while let Some(readback) = self.os.gl_readbacks.pending.first() {
+            if !unsafe {readback.is_done()} {
+                break
+            }
+            let readback = self.os.gl_readbacks.pending.remove(0);
+            let request_ids = readback.request_ids.clone();
+            let image = unsafe {readback.finish()};
+            for request_id in request_ids {
+                self.readbacks.results.push(ReadbackEvent {request_id, image: image.clone()});
+            }
+        }
+    }
+
+    /// Whether readbacks are still waiting on the GPU, the event loop keeps painting until they are in.
+    pub fn opengl_has_pending_readbacks(&self) -> bool {
+        !self.os.gl_readbacks.pending.is_empty()
+    }
+
+    /// Starts counting and timing the draw calls of a repaint, when the Studio profiler is open.
+    pub fn opengl_profile_begin_frame(&mut self) {
+        if !Cx::has_studio_profiler() {
+            return
+        }
+        let start = std::time::Instant::now().duration_since(self.start_time).as_secs_f64();
+        self.os.gl_profiler.begin_frame(self.repaint_id, start);
+    }
+
+    /// Sends the frame sample, and the draw call timings of earlier frames that the GPU finished.
+    pub fn opengl_profile_end_frame(&mut self) {
+        let end = std::time::Instant::now().duration_since(self.start_time).as_secs_f64();
+        self.os.gl_profiler.end_frame(end);
     }
 
     pub fn maybe_warn_hardware_support(&self) {
@@ -812,6 +1026,204 @@ impl CxOsDrawShader {
 }
 
 
+struct GlProfiledDrawCall {
+    shader: String,
+    instances: u64,
+    queries: Option<[u32; 2]>,
+}
+
+struct GlProfiledFrame {
+    frame_id: u64,
+    start: f64,
+    // the GPU clock in nanoseconds when the frame began, to map query results to frame time
+    gpu_origin: i64,
+    draw_calls: Vec<GlProfiledDrawCall>,
+}
+
+/// Counts the draw calls of a repaint and times each of them with a pair of GL timestamp
+/// queries. The query results are collected a few frames later, without stalling the GPU.
+#[derive(Default)]
+pub struct GlProfiler {
+    frame: Option<GlProfiledFrame>,
+    pending: Vec<GlProfiledFrame>,
+    free_queries: Vec<u32>,
+    supports_timers: Option<bool>,
+}
+
+impl GlProfiler {
+    fn begin_frame(&mut self, frame_id: u64, start: f64) {
+        let supports_timers = *self.supports_timers.get_or_insert_with(opengl_supports_timer_queries);
+        let mut gpu_origin = 0;
+        if supports_timers {
+            unsafe {gl_sys::GetInteger64v(gl_sys::TIMESTAMP, &mut gpu_origin)};
+        }
+        self.frame = Some(GlProfiledFrame {frame_id, start, gpu_origin, draw_calls: Vec::new()});
+    }
+
+    fn begin_draw_call(&mut self, shader: impl FnOnce() -> String, instances: u64) -> Option<usize> {
+        let frame = self.frame.as_mut()?;
+        let queries = if self.supports_timers == Some(true) {
+            let mut queries = [0u32; 2];
+            for query in &mut queries {
+                *query = self.free_queries.pop().unwrap_or_else( || unsafe {
+                    let mut query = 0;
+                    gl_sys::GenQueries(1, &mut query);
+                    query
+                });
+            }
+            unsafe {gl_sys::QueryCounter(queries[0], gl_sys::TIMESTAMP)};
+            Some(queries)
+        }
+        else {
+            None
+        };
+        frame.draw_calls.push(GlProfiledDrawCall {shader: shader(), instances, queries});
+        Some(frame.draw_calls.len() - 1)
+    }
+
+    fn end_draw_call(&mut self, timed: Option<usize>) {
+        let queries = timed.and_then( | index | self.frame.as_ref()?.draw_calls[index].queries);
+        if let Some(queries) = queries {
+            unsafe {gl_sys::QueryCounter(queries[1], gl_sys::TIMESTAMP)};
+        }
+    }
+
+    fn end_frame(&mut self, end: f64) {
+        if let Some(frame) = self.frame.take() {
+            Cx::send_studio_message(AppToStudio::FrameSample(FrameSample {
+                frame_id: frame.frame_id,
+                start: frame.start,
+                end,
+                draw_calls: frame.draw_calls.len() as u32,
+                instances: frame.draw_calls.iter().map( | dc | dc.instances).sum(),
+            }));
+            if frame.draw_calls.iter().any( | dc | dc.queries.is_some()) {
+                self.pending.push(frame);
+            }
+        }
+        // queries complete in submission order, so the frames are collected oldest first
+        while let Some(frame) = self.pending.first() {
+            let last_query = frame.draw_calls.iter().rev().find_map( | dc | dc.queries).unwrap()[1];
+            let mut available = 0;
+            unsafe {gl_sys::GetQueryObjectuiv(last_query, gl_sys::QUERY_RESULT_AVAILABLE, &mut available)};
+            if available == 0 {
+                break
+            }
+            let frame = self.pending.remove(0);
+            for draw_call in frame.draw_calls {
+                let Some(queries) = draw_call.queries else {continue};
+                let mut timestamps = [0u64; 2];
+                for (query, timestamp) in queries.iter().zip(timestamps.iter_mut()) {
+                    unsafe {gl_sys::GetQueryObjectui64v(*query, gl_sys::QUERY_RESULT, timestamp)};
+                }
+                let to_frame_time = | t: u64 | frame.start + (t as i64 - frame.gpu_origin) as f64 / 1e9;
+                Cx::send_studio_message(AppToStudio::GPUDrawCallSample(GPUDrawCallSample {
+                    frame_id: frame.frame_id,
+                    shader: draw_call.shader,
+                    instances: draw_call.instances,
+                    start: to_frame_time(timestamps[0]),
+                    end: to_frame_time(timestamps[1]),
+                }));
+                self.free_queries.extend_from_slice(&queries);
+            }
+        }
+    }
+}
+
+// timestamp queries are core in GL 3.3, GLES only has them through an extension
+fn opengl_supports_timer_queries() -> bool {
+    let version = get_gl_string(gl_sys::VERSION);
+    match version.strip_prefix("OpenGL ES ") {
+        Some(_) => get_gl_string(gl_sys::EXTENSIONS).split(' ').any( | ext | ext == "GL_EXT_disjoint_timer_query"),
+        None => {
+            let mut parts = version.split(|c: char| !c.is_ascii_digit()).map( | v | v.parse::<u32>().unwrap_or(0));
+            (parts.next().unwrap_or(0), parts.next().unwrap_or(0)) >= (3, 3)
+        }
+    }
+}
+
+// compute needs GL 4.3 or GLES 3.1, the loader happily returns entrypoints for older contexts
+fn opengl_supports_compute() -> bool {
+    let version = get_gl_string(gl_sys::VERSION);
+    let (is_es, number) = match version.strip_prefix("OpenGL ES ") {
+        Some(number) => (true, number),
+        None => (false, version.as_str())
+    };
+    let mut parts = number.split(|c: char| !c.is_ascii_digit()).map( | v | v.parse::<u32>().unwrap_or(0));
+    let major_minor = (parts.next().unwrap_or(0), parts.next().unwrap_or(0));
+    if is_es {major_minor >= (3, 1)} else {major_minor >= (4, 3)}
+}
+
+struct GlReadback {
+    request_ids: Vec<LiveId>,
+    width: usize,
+    height: usize,
+    is_float: bool,
+    gl_buffer: u32,
+    sync: gl_sys::GLsync,
+}
+
+/// Readbacks copied into pixel pack buffers, which are mapped on a later frame once their
+/// fence has signaled, so reading pixels does not stall on the GPU.
+#[derive(Default)]
+pub struct GlReadbacks {
+    pending: Vec<GlReadback>,
+}
+
+impl GlReadback {
+    fn byte_size(width: usize, height: usize, is_float: bool) -> usize {
+        width * height * 4 * if is_float {4} else {1}
+    }
+
+    // copies the bound framebuffer into a new pixel pack buffer and fences the copy
+    unsafe fn start(request_ids: Vec<LiveId>, y: usize, width: usize, height: usize, is_float: bool) -> Self {
+        let mut gl_buffer = 0;
+        gl_sys::GenBuffers(1, &mut gl_buffer);
+        gl_sys::BindBuffer(gl_sys::PIXEL_PACK_BUFFER, gl_buffer);
+        gl_sys::BufferData(gl_sys::PIXEL_PACK_BUFFER, Self::byte_size(width, height, is_float) as _, ptr::null(), gl_sys::STREAM_READ);
+        gl_sys::PixelStorei(gl_sys::PACK_ALIGNMENT, 4);
+        let data_type = if is_float {gl_sys::FLOAT} else {gl_sys::UNSIGNED_BYTE};
+        gl_sys::ReadPixels(0, y as i32, width as i32, height as i32, gl_sys::RGBA, data_type, ptr::null_mut());
+        gl_sys::BindBuffer(gl_sys::PIXEL_PACK_BUFFER, 0);
+        let sync = gl_sys::FenceSync(gl_sys::SYNC_GPU_COMMANDS_COMPLETE, 0);
+        Self {request_ids, width, height, is_float, gl_buffer, sync}
+    }
+
+    unsafe fn is_done(&self) -> bool {
+        gl_sys::ClientWaitSync(self.sync, gl_sys::SYNC_FLUSH_COMMANDS_BIT, 0) != gl_sys::TIMEOUT_EXPIRED
+    }
+
+    // maps the finished copy, GL rows go bottom up so they are flipped to top down
+    unsafe fn finish(self) -> Option<ReadbackImage> {
+        let (width, height) = (self.width, self.height);
+        let byte_size = Self::byte_size(width, height, self.is_float);
+        gl_sys::BindBuffer(gl_sys::PIXEL_PACK_BUFFER, self.gl_buffer);
+        let mapped = gl_sys::MapBufferRange(gl_sys::PIXEL_PACK_BUFFER, 0, byte_size as _, gl_sys::MAP_READ_BIT);
+        let pixels = if mapped.is_null() {
+            None
+        }
+        else if self.is_float {
+            let data = std::slice::from_raw_parts(mapped as *const f32, width * height * 4);
+            let rows: Vec<&[f32]> = data.chunks(width * 4).rev().collect();
+            Some(ReadbackPixels::RGBAf32(rows.concat()))
+        }
+        else {
+            let data = std::slice::from_raw_parts(mapped as *const u8, byte_size);
+            let pixels = data.chunks(width * 4).rev().flat_map( | row | row.chunks(4)).map( | c | {
+                ((c[3] as u32) << 24) | ((c[0] as u32) << 16) | ((c[1] as u32) << 8) | c[2] as u32
+            }).collect();
+            Some(ReadbackPixels::BGRAu8(pixels))
+        };
+        if !mapped.is_null() {
+            gl_sys::UnmapBuffer(gl_sys::PIXEL_PACK_BUFFER);
+        }
+        gl_sys::BindBuffer(gl_sys::PIXEL_PACK_BUFFER, 0);
+        gl_sys::DeleteBuffers(1, &self.gl_buffer);
+        gl_sys::DeleteSync(self.sync);
+        pixels.map( | pixels | ReadbackImage {width, height, pixels})
+    }
+}
+
 fn get_gl_string(key: gl_sys::types::GLenum) -> String {
     unsafe {
         let string_ptr = gl_sys::GetString(key) as *const c_char;
@@ -891,6 +1303,101 @@ impl CxOsDrawCall {
     }    
 }
 
+#[derive(Clone, Default)]
+pub struct CxOsComputeShader {
+    pub compute: String,
+    pub gl_program: Option<GlComputeProgram>,
+    pub failed: bool,
+}
+
+impl CxOsComputeShader {
+    pub fn new(compute: &str, os_type: &OsType) -> Self {
+        let version = match os_type {
+            Android(_) => "#version 310 es",
+            _ => "#version 430"
+        };
+        let compute = format!("
+            {}
+            precision highp float;
+            precision highp int;
+            vec4 sample2d(sampler2D sampler, vec2 pos){{return textureLod(sampler, vec2(pos.x, pos.y), 0.0);}}
+            vec4 sample2d_rt(sampler2D sampler, vec2 pos){{return textureLod(sampler, vec2(pos.x, 1.0-pos.y), 0.0);}}
+            {}\0", version, compute);
+        Self {
+            compute,
+            gl_program: None,
+            failed: false,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct GlComputeProgram {
+    pub program: u32,
+    pub textures: Vec<OpenglUniform>,
+    pub user_uniforms: OpenglUniform,
+    pub live_uniforms: OpenglUniform,
+    pub const_table_uniform: OpenglUniform,
+}
+
+impl GlComputeProgram {
+    pub fn new(compute: &str, mapping: &CxDrawShaderMapping) -> Result<Self, String> {
+        unsafe {
+            let cs = gl_sys::CreateShader(gl_sys::COMPUTE_SHADER);
+            gl_sys::ShaderSource(cs, 1, [compute.as_ptr() as *const _].as_ptr(), ptr::null());
+            gl_sys::CompileShader(cs);
+            if let Some(error) = GlShader::opengl_has_shader_error(true, cs as usize, compute) {
+                gl_sys::DeleteShader(cs);
+                return Err(error)
+            }
+            let program = gl_sys::CreateProgram();
+            gl_sys::AttachShader(program, cs);
+            gl_sys::LinkProgram(program);
+            gl_sys::DeleteShader(cs);
+            if let Some(error) = GlShader::opengl_has_shader_error(false, program as usize, "") {
+                return Err(error)
+            }
+            Ok(Self {
+                program,
+                textures: GlShader::opengl_get_texture_slots(program, &mapping.textures),
+                user_uniforms: GlShader::opengl_get_uniform(program, "user_table"),
+                live_uniforms: GlShader::opengl_get_uniform(program, "live_table"),
+                const_table_uniform: GlShader::opengl_get_uniform(program, "const_table"),
+            })
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct CxOsComputeBuffer {
+    pub gl_buffer: Option<u32>,
+}
+
+impl CxComputeBuffer {
+    // uploads the CPU data when it changed, the buffer is otherwise only written on the GPU
+    pub fn update_gl_buffer(&mut self) {
+        if self.os.gl_buffer.is_some() && !self.updated {
+            return
+        }
+        self.updated = false;
+        unsafe {
+            if self.os.gl_buffer.is_none() {
+                let mut gl_buffer = std::mem::MaybeUninit::uninit();
+                gl_sys::GenBuffers(1, gl_buffer.as_mut_ptr());
+                self.os.gl_buffer = Some(gl_buffer.assume_init());
+            }
+            gl_sys::BindBuffer(gl_sys::SHADER_STORAGE_BUFFER, self.os.gl_buffer.unwrap());
+            gl_sys::BufferData(
+                gl_sys::SHADER_STORAGE_BUFFER,
+                (self.data.len() * mem::size_of::<f32>()) as gl_sys::types::GLsizeiptr,
+                self.data.as_ptr() as *const _,
+                gl_sys::DYNAMIC_COPY
+            );
+            gl_sys::BindBuffer(gl_sys::SHADER_STORAGE_BUFFER, 0);
+        }
+    }
+}
+
 #[derive(Clone, Default)]
 pub struct CxOsTexture {
     pub gl_texture: Option<u32>,
@@ -0,0 +1,123 @@
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
+
+// Runtime-loaded libssl, so https works on any distro that ships OpenSSL
+// without making the dev headers/symlinks a build dependency.
+
+use std::os::raw::{c_char, c_int, c_long, c_void};
+
+pub enum SSL_METHOD {}
+pub enum SSL_CTX {}
+pub enum SSL {}
+
+pub const SSL_VERIFY_NONE: c_int = 0;
+pub const SSL_VERIFY_PEER: c_int = 1;
+pub const SSL_CTRL_SET_TLSEXT_HOSTNAME: c_int = 55;
+pub const TLSEXT_NAMETYPE_host_name: c_long = 0;
+pub const SSL_ERROR_ZERO_RETURN: c_int = 6;
+
+type PFNOPENSSLINITSSL = unsafe extern "C" fn(opts: u64, settings: *const c_void) -> c_int;
+type PFNTLSCLIENTMETHOD = unsafe extern "C" fn() -> *const SSL_METHOD;
+type PFNSSLCTXNEW = unsafe extern "C" fn(method: *const SSL_METHOD) -> *mut SSL_CTX;
+type PFNSSLCTXFREE = unsafe extern "C" fn(ctx: *mut SSL_CTX);
+type PFNSSLCTXSETDEFAULTVERIFYPATHS = unsafe extern "C" fn(ctx: *mut SSL_CTX) -> c_int;
+type PFNSSLCTXSETVERIFY = unsafe extern "C" fn(ctx: *mut SSL_CTX, mode: c_int, callback: *const c_void);
+type PFNSSLNEW = unsafe extern "C" fn(ctx: *mut SSL_CTX) -> *mut SSL;
+type PFNSSLFREE = unsafe extern "C" fn(ssl: *mut SSL);
+type PFNSSLSETFD = unsafe extern "C" fn(ssl: *mut SSL, fd: c_int) -> c_int;
+type PFNSSLSET1HOST = unsafe extern "C" fn(ssl: *mut SSL, hostname: *const c_char) -> c_int;
+type PFNSSLCTRL = unsafe extern "C" fn(ssl: *mut SSL, cmd: c_int, larg: c_long, parg: *mut c_void) -> c_long;
+type PFNSSLCONNECT = unsafe extern "C" fn(ssl: *mut SSL) -> c_int;
+type PFNSSLREAD = unsafe extern "C" fn(ssl: *mut SSL, buf: *mut c_void, num: c_int) -> c_int;
+type PFNSSLWRITE = unsafe extern "C" fn(ssl: *mut SSL, buf: *const c_void, num: c_int) -> c_int;
+type PFNSSLSHUTDOWN = unsafe extern "C" fn(ssl: *mut SSL) -> c_int;
+type PFNSSLGETERROR = unsafe extern "C" fn(ssl: *const SSL, ret: c_int) -> c_int;
+
+struct Module(::std::ptr::NonNull<c_void>);
+
+unsafe impl Send for Module {}
+unsafe impl Sync for Module {}
+
+impl Module {
+    fn load(path: &str) -> Result<Self, ()> {
+        use self::super::libc_sys::{dlopen, RTLD_LAZY, RTLD_LOCAL};
+        let path = std::ffi::CString::new(path).unwrap();
+        let module = unsafe {dlopen(path.as_ptr(), RTLD_LAZY | RTLD_LOCAL)};
+        std::ptr::NonNull::new(module).map(Module).ok_or(())
+    }
+
+    fn get_symbol<F: Sized>(&self, name: &str) -> Result<F, ()> {
+        use self::super::libc_sys::dlsym;
+        let name = std::ffi::CString::new(name).unwrap();
+        let symbol = unsafe {dlsym(self.0.as_ptr(), name.as_ptr())};
+        if symbol.is_null() {
+            return Err(());
+        }
+        Ok(unsafe {std::mem::transmute_copy::<_, F>(&symbol)})
+    }
+}
+
+impl Drop for Module {
+    fn drop(&mut self) {
+        unsafe {self::super::libc_sys::dlclose(self.0.as_ptr())};
+    }
+}
+
+pub struct LibSsl {
+    pub OPENSSL_init_ssl: PFNOPENSSLINITSSL,
+    pub TLS_client_method: PFNTLSCLIENTMETHOD,
+    pub SSL_CTX_new: PFNSSLCTXNEW,
+    pub SSL_CTX_free: PFNSSLCTXFREE,
+    pub SSL_CTX_set_default_verify_paths: PFNSSLCTXSETDEFAULTVERIFYPATHS,
+    pub SSL_CTX_set_verify: PFNSSLCTXSETVERIFY,
+    pub SSL_new: PFNSSLNEW,
+    pub SSL_free: PFNSSLFREE,
+    pub SSL_set_fd: PFNSSLSETFD,
+    pub SSL_set1_host: PFNSSLSET1HOST,
+    pub SSL_ctrl: PFNSSLCTRL,
+    pub SSL_connect: PFNSSLCONNECT,
+    pub SSL_read: PFNSSLREAD,
+    pub SSL_write: PFNSSLWRITE,
+    pub SSL_shutdown: PFNSSLSHUTDOWN,
+    pub SSL_get_error: PFNSSLGETERROR,
+
+    _keep_module_alive: Module,
+}
+
+impl LibSsl {
+    pub fn try_load() -> Option<LibSsl> {
+        let module = Module::load("libssl.so.3")
+            .or_else( | _ | Module::load("libssl.so.1.1"))
+            .or_else( | _ | Module::load("libssl.so"))
+            .ok() ?;
+
+        macro_rules! get_fn {
+            ($name:literal) => {
+                module.get_symbol($name).ok() ?
+            }
+        }
+
+        let lib = LibSsl {
+            OPENSSL_init_ssl: get_fn!("OPENSSL_init_ssl"),
+            TLS_client_method: get_fn!("TLS_client_method"),
+            SSL_CTX_new: get_fn!("SSL_CTX_new"),
+            SSL_CTX_free: get_fn!("SSL_CTX_free"),
+            SSL_CTX_set_default_verify_paths: get_fn!("SSL_CTX_set_default_verify_paths"),
+            SSL_CTX_set_verify: get_fn!("SSL_CTX_set_verify"),
+            SSL_new: get_fn!("SSL_new"),
+            SSL_free: get_fn!("SSL_free"),
+            SSL_set_fd: get_fn!("SSL_set_fd"),
+            SSL_set1_host: get_fn!("SSL_set1_host"),
+            SSL_ctrl: get_fn!("SSL_ctrl"),
+            SSL_connect: get_fn!("SSL_connect"),
+            SSL_read: get_fn!("SSL_read"),
+            SSL_write: get_fn!("SSL_write"),
+            SSL_shutdown: get_fn!("SSL_shutdown"),
+            SSL_get_error: get_fn!("SSL_get_error"),
+            _keep_module_alive: module,
+        };
+        unsafe {(lib.OPENSSL_init_ssl)(0, std::ptr::null())};
+        Some(lib)
+    }
+}
@@ -0,0 +1,551 @@
+use {
+    std::{
+        ffi::CString,
+        fs,
+        io,
+        os::raw::{c_int, c_ulong, c_void},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex,
+        },
+        thread::{self, JoinHandle},
+    },
+    self::super::{
+        libc_sys,
+        v4l2_sys::*,
+    },
+    crate::{
+        makepad_live_id::*,
+        thread::SignalToUI,
+        video::*,
+    },
+};
+
+// The number of buffers the driver fills while we process the last one.
+const BUFFER_COUNT: u32 = 4;
+
+/// A combination of pixel format, frame size and frame interval that a device supports.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct V4l2FrameFormat {
+    pub fourcc: u32,
+    pub width: u32,
+    pub height: u32,
+    pub interval: Option<v4l2_fract>,
+}
+
+impl V4l2FrameFormat {
+    pub fn pixel_format(&self) -> VideoPixelFormat {
+        match self.fourcc {
+            V4L2_PIX_FMT_YUYV => VideoPixelFormat::YUY2,
+            V4L2_PIX_FMT_MJPEG | V4L2_PIX_FMT_JPEG => VideoPixelFormat::MJPEG,
+            fourcc => VideoPixelFormat::Unsupported(fourcc),
+        }
+    }
+
+    /// Returns the format as it is presented to the application, or `None` if its pixel format
+    /// cannot be streamed.
+    pub fn to_video_format(&self) -> Option<VideoFormat> {
+        let pixel_format = self.pixel_format();
+        if let VideoPixelFormat::Unsupported(_) = pixel_format {
+            return None
+        }
+        let frame_rate = self.interval
+            .filter( | interval | interval.numerator != 0)
+            .map( | interval | interval.denominator as f64 / interval.numerator as f64);
+        Some(VideoFormat {
+            format_id: LiveId::from_str(&format!("{} {} {} {:?}", self.fourcc, self.width, self.height, frame_rate)).into(),
+            width: self.width as usize,
+            height: self.height as usize,
+            frame_rate,
+            pixel_format,
+        })
+    }
+}
+
+/// Checks the format the driver settled on against the one we asked for. Drivers are free to
+/// adjust the frame size, but a different pixel format means the device cannot deliver what was
+/// asked for.
+pub fn negotiated_format(requested: VideoFormat, fourcc: u32, pix: &v4l2_pix_format) -> Option<(VideoFormat, usize)> {
+    if pix.pixelformat != fourcc || pix.width == 0 || pix.height == 0 {
+        return None
+    }
+    let format = VideoFormat {
+        width: pix.width as usize,
+        height: pix.height as usize,
+        ..requested
+    };
+    let bytes_per_line = match format.pixel_format {
+        VideoPixelFormat::YUY2 => (pix.bytesperline as usize).max(format.width * 2),
+        _ => pix.bytesperline as usize,
+    };
+    Some((format, bytes_per_line))
+}
+
+/// Copies the rows of a YUY2 frame into `output` without the padding at the end of each row,
+/// packing each pair of pixels into a `u32`.
+pub fn pack_yuy2(data: &[u8], width: usize, height: usize, bytes_per_line: usize, output: &mut Vec<u32>) {
+    output.clear();
+    output.reserve(width / 2 * height);
+    for row in data.chunks(bytes_per_line).take(height) {
+        output.extend(row[..(width * 2).min(row.len())].chunks_exact(4).map( | pixels | {
+            u32::from_le_bytes([pixels[0], pixels[1], pixels[2], pixels[3]])
+        }));
+    }
+}
+
+struct V4l2Device {
+    path: String,
+    desc: VideoInputDesc,
+    frame_formats: Vec<V4l2FrameFormat>,
+}
+
+struct V4l2Fd(c_int);
+
+impl Drop for V4l2Fd {
+    fn drop(&mut self) {
+        unsafe {libc_sys::close(self.0);}
+    }
+}
+
+impl V4l2Fd {
+    fn open(path: &str) -> io::Result<Self> {
+        let path = CString::new(path).unwrap();
+        let fd = unsafe {libc_sys::open(path.as_ptr(), libc_sys::O_RDWR | O_NONBLOCK)};
+        if fd < 0 {
+            return Err(io::Error::last_os_error())
+        }
+        Ok(Self(fd))
+    }
+
+    fn ioctl<T>(&self, request: c_ulong, arg: &mut T) -> io::Result<()> {
+        loop {
+            if unsafe {ioctl(self.0, request, arg as *mut T)} != -1 {
+                return Ok(())
+            }
+            let err = io::Error::last_os_error();
+            if err.raw_os_error() != Some(EINTR) {
+                return Err(err)
+            }
+        }
+    }
+
+    fn query_name(&self) -> Option<String> {
+        let mut cap: v4l2_capability = unsafe {std::mem::zeroed()};
+        self.ioctl(VIDIOC_QUERYCAP, &mut cap).ok()?;
+        let caps = if cap.capabilities & V4L2_CAP_DEVICE_CAPS != 0 {cap.device_caps} else {cap.capabilities};
+        if caps & V4L2_CAP_VIDEO_CAPTURE == 0 || caps & V4L2_CAP_STREAMING == 0 {
+            return None
+        }
+        let len = cap.card.iter().position( | c | *c == 0).unwrap_or(cap.card.len());
+        Some(String::from_utf8_lossy(&cap.card[..len]).to_string())
+    }
+
+    fn frame_formats(&self) -> Vec<V4l2FrameFormat> {
+        let mut frame_formats = Vec::new();
+        for fmt_index in 0.. {
+            let mut fmtdesc: v4l2_fmtdesc = unsafe {std::mem::zeroed()};
+            fmtdesc.index = fmt_index;
+            fmtdesc.type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+            if self.ioctl(VIDIOC_ENUM_FMT, &mut fmtdesc).is_err() {
+                break;
+            }
+            let fourcc = fmtdesc.pixelformat;
+            for size_index in 0.. {
+                let mut frmsize: v4l2_frmsizeenum = unsafe {std::mem::zeroed()};
+                frmsize.index = size_index;
+                frmsize.pixel_format = fourcc;
+                if self.ioctl(VIDIOC_ENUM_FRAMESIZES, &mut frmsize).is_err() {
+                    break;
+                }
+                // of a range of sizes, only the largest is offered
+                let (width, height, is_range) = unsafe {match frmsize.type_ {
+                    V4L2_FRMSIZE_TYPE_DISCRETE => (frmsize.size.discrete.width, frmsize.size.discrete.height, false),
+                    _ => (frmsize.size.stepwise.max_width, frmsize.size.stepwise.max_height, true)
+                }};
+                let intervals = self.frame_intervals(fourcc, width, height);
+                if intervals.is_empty() {
+                    frame_formats.push(V4l2FrameFormat {fourcc, width, height, interval: None});
+                }
+                for interval in intervals {
+                    frame_formats.push(V4l2FrameFormat {fourcc, width, height, interval: Some(interval)});
+                }
+                if is_range {
+                    break;
+                }
+            }
+        }
+        frame_formats
+    }
+
+    fn frame_intervals(&self, fourcc: u32, width: u32, height: u32) -> Vec<v4l2_fract> {
+        let mut intervals = Vec::new();
+        for index in 0.. {
+            let mut frmival: v4l2_frmivalenum = unsafe {std::mem::zeroed()};
+            frmival.index = index;
+            frmival.pixel_format = fourcc;
+            frmival.width = width;
+            frmival.height = height;
+            if self.ioctl(VIDIOC_ENUM_FRAMEINTERVALS, &mut frmival).is_err() {
+                break;
+            }
+            unsafe {
+                if frmival.type_ == V4L2_FRMIVAL_TYPE_DISCRETE {
+                    intervals.push(frmival.interval.discrete);
+                }
+                else {
+                    // of a range of intervals, only the shortest is offered
+                    intervals.push(frmival.interval.stepwise.min);
+                    break;
+                }
+            }
+        }
+        intervals
+    }
+}
+
+struct V4l2MappedBuffer {
+    ptr: *mut c_void,
+    len: usize,
+}
+
+/// A device that is streaming frames into buffers that are mapped into our memory.
+struct V4l2Stream {
+    fd: V4l2Fd,
+    buffers: Vec<V4l2MappedBuffer>,
+    format: VideoFormat,
+    bytes_per_line: usize,
+}
+
+unsafe impl Send for V4l2Stream {}
+
+impl V4l2Stream {
+    fn start(path: &str, frame_format: V4l2FrameFormat, format: VideoFormat) -> io::Result<Self> {
+        let fd = V4l2Fd::open(path)?;
+
+        let mut fmt: v4l2_format = unsafe {std::mem::zeroed()};
+        fmt.type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+        fmt.fmt.pix = v4l2_pix_format {
+            width: frame_format.width,
+            height: frame_format.height,
+            pixelformat: frame_format.fourcc,
+            field: V4L2_FIELD_ANY,
+            ..Default::default()
+        };
+        fd.ioctl(VIDIOC_S_FMT, &mut fmt)?;
+        let Some((format, bytes_per_line)) = negotiated_format(format, frame_format.fourcc, unsafe {&fmt.fmt.pix}) else {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "driver changed the pixel format"))
+        };
+
+        if let Some(interval) = frame_format.interval {
+            let mut parm: v4l2_streamparm = unsafe {std::mem::zeroed()};
+            parm.type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+            let mut capture: v4l2_captureparm = unsafe {std::mem::zeroed()};
+            capture.timeperframe = interval;
+            parm.parm.capture = capture;
+            // not every driver lets the frame rate be set, which is fine
+            let _ = fd.ioctl(VIDIOC_S_PARM, &mut parm);
+        }
+
+        let mut req: v4l2_requestbuffers = unsafe {std::mem::zeroed()};
+        req.count = BUFFER_COUNT;
+        req.type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+        req.memory = V4L2_MEMORY_MMAP;
+        fd.ioctl(VIDIOC_REQBUFS, &mut req)?;
+
+        let mut stream = Self {fd, buffers: Vec::new(), format, bytes_per_line};
+        for index in 0..req.count {
+            let mut buf = stream.new_buffer(index);
+            stream.fd.ioctl(VIDIOC_QUERYBUF, &mut buf)?;
+            let ptr = unsafe {mmap(
+                std::ptr::null_mut(),
+                buf.length as usize,
+                PROT_READ | PROT_WRITE,
+                MAP_SHARED,
+                stream.fd.0,
+                buf.m.offset as _
+            )};
+            if ptr == MAP_FAILED {
+                return Err(io::Error::last_os_error())
+            }
+            stream.buffers.push(V4l2MappedBuffer {ptr, len: buf.length as usize});
+            stream.fd.ioctl(VIDIOC_QBUF, &mut buf)?;
+        }
+        let mut buf_type = V4L2_BUF_TYPE_VIDEO_CAPTURE as c_int;
+        stream.fd.ioctl(VIDIOC_STREAMON, &mut buf_type)?;
+        Ok(stream)
+    }
+
+    fn new_buffer(&self, index: u32) -> v4l2_buffer {
+        let mut buf: v4l2_buffer = unsafe {std::mem::zeroed()};
+        buf.index = index;
+        buf.type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+        buf.memory = V4L2_MEMORY_MMAP;
+        buf
+    }
+
+    /// Waits up to the given number of milliseconds for a frame, and passes it to `f` before
+    /// handing its buffer back to the driver.
+    fn next_frame(&self, timeout_ms: u64, f: &mut dyn FnMut(&[u8])) -> io::Result<()> {
+        unsafe {
+            let mut fds: libc_sys::fd_set = std::mem::zeroed();
+            libc_sys::FD_ZERO(&mut fds);
+            libc_sys::FD_SET(self.fd.0, &mut fds);
+            let mut timeout = libc_sys::timeval {
+                tv_sec: (timeout_ms / 1000) as _,
+                tv_usec: ((timeout_ms % 1000) * 1000) as _,
+            };
+            if libc_sys::select(self.fd.0 + 1, &mut fds, std::ptr::null_mut(), std::ptr::null_mut(), &mut timeout) <= 0 {
+                return Ok(())
+            }
+        }
+        let mut buf = self.new_buffer(0);
+        if let Err(err) = self.fd.ioctl(VIDIOC_DQBUF, &mut buf) {
+            if err.raw_os_error() == Some(EAGAIN) {
+                return Ok(())
+            }
+            return Err(err)
+        }
+        if let Some(buffer) = self.buffers.get(buf.index as usize) {
+            let len = (buf.bytesused as usize).min(buffer.len);
+            f(unsafe {std::slice::from_raw_parts(buffer.ptr as *const u8, len)});
+        }
+        self.fd.ioctl(VIDIOC_QBUF, &mut buf)
+    }
+}
+
+impl Drop for V4l2Stream {
+    fn drop(&mut self) {
+        let mut buf_type = V4L2_BUF_TYPE_VIDEO_CAPTURE as c_int;
+        let _ = self.fd.ioctl(VIDIOC_STREAMOFF, &mut buf_type);
+        for buffer in &self.buffers {
+            unsafe {munmap(buffer.ptr, buffer.len);}
+        }
+    }
+}
+
+struct V4l2Capture {
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+impl V4l2Capture {
+    fn start(input_fn: Arc<Mutex<Option<VideoInputFn >> >, stream: V4l2Stream) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = thread::spawn({
+            let stop = stop.clone();
+            move || {
+                let format = stream.format;
+                let mut packed = Vec::new();
+                while !stop.load(Ordering::Relaxed) {
+                    let result = stream.next_frame(100, &mut | data | {
+                        let mut input_fn = input_fn.lock().unwrap();
+                        let Some(cb) = &mut *input_fn else {
+                            return
+                        };
+                        match format.pixel_format {
+                            VideoPixelFormat::YUY2 => {
+                                pack_yuy2(data, format.width, format.height, stream.bytes_per_line, &mut packed);
+                                cb(VideoBufferRef {
+                                    format,
+                                    data: VideoBufferRefData::U32(&packed)
+                                })
+                            }
+                            _ => cb(VideoBufferRef {
+                                format,
+                                data: VideoBufferRefData::U8(data)
+                            })
+                        }
+                    });
+                    if let Err(err) = result {
+                        crate::error!("V4L2 capture stopped: {}", err);
+                        break;
+                    }
+                }
+            }
+        });
+        Self {stop, thread}
+    }
+
+    fn stop(self) {
+        self.stop.store(true, Ordering::Relaxed);
+        let _ = self.thread.join();
+    }
+}
+
+pub struct V4l2CameraAccess {
+    pub video_input_cb: [Arc<Mutex<Option<VideoInputFn> > >; MAX_VIDEO_DEVICE_INDEX],
+    devices: Vec<V4l2Device>,
+    captures: Vec<V4l2Capture>,
+}
+
+impl V4l2CameraAccess {
+    pub fn new(change_signal: SignalToUI) -> Arc<Mutex<Self >> {
+        change_signal.set();
+        Arc::new(Mutex::new(Self {
+            video_input_cb: Default::default(),
+            devices: Default::default(),
+            captures: Default::default(),
+        }))
+    }
+
+    pub fn use_video_input(&mut self, inputs: &[(VideoInputId, VideoFormatId)]) {
+        while let Some(capture) = self.captures.pop() {
+            capture.stop();
+        }
+        for (index, (input_id, format_id)) in inputs.iter().enumerate() {
+            let Some(device) = self.devices.iter().find( | v | v.desc.input_id == *input_id) else {
+                continue
+            };
+            let Some((frame_format, format)) = device.frame_formats.iter()
+                .filter_map( | frame_format | Some((*frame_format, frame_format.to_video_format()?)))
+                .find( | (_, format) | format.format_id == *format_id) else {
+                continue
+            };
+            match V4l2Stream::start(&device.path, frame_format, format) {
+                Ok(stream) => self.captures.push(V4l2Capture::start(self.video_input_cb[index].clone(), stream)),
+                Err(err) => crate::error!("Cannot start V4L2 capture on {}: {}", device.path, err)
+            }
+        }
+    }
+
+    pub fn get_updated_descs(&mut self) -> Vec<VideoInputDesc> {
+        let mut paths: Vec<String> = fs::read_dir("/dev").map( | entries | {
+            entries.filter_map( | entry | {
+                let name = entry.ok()?.file_name().into_string().ok()?;
+                name.starts_with("video").then( || format!("/dev/{}", name))
+            }).collect()
+        }).unwrap_or_default();
+        paths.sort();
+
+        self.devices.clear();
+        for path in paths {
+            let Ok(fd) = V4l2Fd::open(&path) else {
+                continue
+            };
+            let Some(name) = fd.query_name() else {
+                continue
+            };
+            let frame_formats = fd.frame_formats();
+            let formats: Vec<VideoFormat> = frame_formats.iter().filter_map( | v | v.to_video_format()).collect();
+            if formats.is_empty() {
+                continue
+            }
+            self.devices.push(V4l2Device {
+                desc: VideoInputDesc {
+                    input_id: LiveId::from_str(&path).into(),
+                    name,
+                    formats
+                },
+                path,
+                frame_formats,
+            });
+        }
+        self.devices.iter().map( | device | device.desc.clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn yuyv(width: u32, height: u32, fps: u32) -> V4l2FrameFormat {
+        V4l2FrameFormat {
+            fourcc: V4L2_PIX_FMT_YUYV,
+            width,
+            height,
+            interval: Some(v4l2_fract {numerator: 1, denominator: fps})
+        }
+    }
+
+    #[test]
+    fn frame_formats_map_to_video_formats() {
+        let format = yuyv(640, 480, 30).to_video_format().unwrap();
+        assert_eq!((format.width, format.height, format.frame_rate), (640, 480, Some(30.0)));
+        assert_eq!(format.pixel_format, VideoPixelFormat::YUY2);
+
+        let mjpeg = V4l2FrameFormat {fourcc: V4L2_PIX_FMT_MJPEG, interval: None, ..yuyv(1920, 1080, 0)};
+        let format = mjpeg.to_video_format().unwrap();
+        assert_eq!(format.pixel_format, VideoPixelFormat::MJPEG);
+        assert_eq!(format.frame_rate, None);
+
+        let zero_interval = V4l2FrameFormat {interval: Some(v4l2_fract {numerator: 0, denominator: 1}), ..yuyv(8, 8, 0)};
+        assert_eq!(zero_interval.to_video_format().unwrap().frame_rate, None);
+
+        assert!(V4l2FrameFormat {fourcc: V4L2_PIX_FMT_GREY, ..yuyv(8, 8, 30)}.to_video_format().is_none());
+
+        // every combination needs its own id to be found again
+        let a = yuyv(640, 480, 30).to_video_format().unwrap().format_id;
+        let b = yuyv(640, 480, 15).to_video_format().unwrap().format_id;
+        assert_ne!(a, b);
+        assert_eq!(a, yuyv(640, 480, 30).to_video_format().unwrap().format_id);
+    }
+
+    #[test]
+    fn negotiation_accepts_adjusted_sizes_only() {
+        let requested = yuyv(640, 480, 30).to_video_format().unwrap();
+        let pix = v4l2_pix_format {
+            width: 320,
+            height: 240,
+            pixelformat: V4L2_PIX_FMT_YUYV,
+            bytesperline: 0,
+            ..Default::default()
+        };
+        let (format, bytes_per_line) = negotiated_format(requested, V4L2_PIX_FMT_YUYV, &pix).unwrap();
+        assert_eq!((format.width, format.height), (320, 240));
+        assert_eq!(format.format_id, requested.format_id);
+        assert_eq!(bytes_per_line, 640);
+
+        let padded = v4l2_pix_format {bytesperline: 1024, ..pix};
+        assert_eq!(negotiated_format(requested, V4L2_PIX_FMT_YUYV, &padded).unwrap().1, 1024);
+
+        let other = v4l2_pix_format {pixelformat: V4L2_PIX_FMT_MJPEG, ..pix};
+        assert!(negotiated_format(requested, V4L2_PIX_FMT_YUYV, &other).is_none());
+    }
+
+    #[test]
+    fn yuy2_rows_are_packed() {
+        // two rows of two pixels, each padded to 6 bytes
+        let data = [1, 2, 3, 4, 0xff, 0xff, 5, 6, 7, 8, 0xff, 0xff];
+        let mut output = Vec::new();
+        pack_yuy2(&data, 2, 2, 6, &mut output);
+        assert_eq!(output, vec![0x04030201, 0x08070605]);
+    }
+
+    #[test]
+    fn sys_struct_sizes() {
+        assert_eq!(std::mem::size_of::<v4l2_capability>(), 104);
+        assert_eq!(std::mem::size_of::<v4l2_fmtdesc>(), 64);
+        assert_eq!(std::mem::size_of::<v4l2_frmsizeenum>(), 44);
+        assert_eq!(std::mem::size_of::<v4l2_frmivalenum>(), 52);
+        assert_eq!(std::mem::size_of::<v4l2_requestbuffers>(), 20);
+        assert_eq!(std::mem::size_of::<v4l2_streamparm>(), 204);
+        #[cfg(target_pointer_width = "64")]
+        {
+            assert_eq!(std::mem::size_of::<v4l2_format>(), 208);
+            assert_eq!(std::mem::size_of::<v4l2_buffer>(), 88);
+        }
+    }
+
+    // Needs a capture device, such as the one the vivid driver provides after `modprobe vivid`.
+    #[test]
+    #[ignore]
+    fn capture_from_device() {
+        let access = V4l2CameraAccess::new(SignalToUI::new());
+        let mut access = access.lock().unwrap();
+        let descs = access.get_updated_descs();
+        let desc = descs.first().expect("no V4L2 capture device");
+        let format = desc.formats.iter()
+            .find( | format | format.pixel_format == VideoPixelFormat::YUY2)
+            .expect("no YUY2 format");
+        let (sender, receiver) = std::sync::mpsc::channel();
+        *access.video_input_cb[0].lock().unwrap() = Some(Box::new(move | buffer: VideoBufferRef | {
+            let _ = sender.send(buffer.to_buffer());
+        }));
+        access.use_video_input(&[(desc.input_id, format.format_id)]);
+        let mut buffer = receiver.recv_timeout(std::time::Duration::from_secs(5)).unwrap();
+        let format = buffer.format;
+        let pixels = buffer.as_vec_u32().unwrap();
+        assert_eq!(pixels.len(), format.width / 2 * format.height);
+        access.use_video_input(&[]);
+    }
+}
@@ -0,0 +1,287 @@
+#![allow(non_camel_case_types)]
+#![allow(dead_code)]
+
+// The subset of linux/videodev2.h needed to capture frames from a V4L2 device.
+
+use std::{
+    mem,
+    os::raw::{
+        c_int,
+        c_long,
+        c_ulong,
+        c_void,
+    },
+};
+
+pub const VIDEO_MAX_PLANES: usize = 8;
+
+pub const V4L2_BUF_TYPE_VIDEO_CAPTURE: u32 = 1;
+pub const V4L2_MEMORY_MMAP: u32 = 1;
+pub const V4L2_FIELD_ANY: u32 = 0;
+pub const V4L2_FIELD_NONE: u32 = 1;
+
+pub const V4L2_CAP_VIDEO_CAPTURE: u32 = 0x00000001;
+pub const V4L2_CAP_STREAMING: u32 = 0x04000000;
+pub const V4L2_CAP_DEVICE_CAPS: u32 = 0x80000000;
+pub const V4L2_CAP_TIMEPERFRAME: u32 = 0x1000;
+
+pub const V4L2_FRMSIZE_TYPE_DISCRETE: u32 = 1;
+pub const V4L2_FRMSIZE_TYPE_CONTINUOUS: u32 = 2;
+pub const V4L2_FRMSIZE_TYPE_STEPWISE: u32 = 3;
+
+pub const V4L2_FRMIVAL_TYPE_DISCRETE: u32 = 1;
+pub const V4L2_FRMIVAL_TYPE_CONTINUOUS: u32 = 2;
+pub const V4L2_FRMIVAL_TYPE_STEPWISE: u32 = 3;
+
+pub const fn v4l2_fourcc(a: u8, b: u8, c: u8, d: u8) -> u32 {
+    (a as u32) | ((b as u32) << 8) | ((c as u32) << 16) | ((d as u32) << 24)
+}
+
+pub const V4L2_PIX_FMT_YUYV: u32 = v4l2_fourcc(b'Y', b'U', b'Y', b'V');
+pub const V4L2_PIX_FMT_MJPEG: u32 = v4l2_fourcc(b'M', b'J', b'P', b'G');
+pub const V4L2_PIX_FMT_JPEG: u32 = v4l2_fourcc(b'J', b'P', b'E', b'G');
+pub const V4L2_PIX_FMT_NV12: u32 = v4l2_fourcc(b'N', b'V', b'1', b'2');
+pub const V4L2_PIX_FMT_RGB24: u32 = v4l2_fourcc(b'R', b'G', b'B', b'3');
+pub const V4L2_PIX_FMT_GREY: u32 = v4l2_fourcc(b'G', b'R', b'E', b'Y');
+
+pub const PROT_READ: c_int = 1;
+pub const PROT_WRITE: c_int = 2;
+pub const MAP_SHARED: c_int = 1;
+pub const MAP_FAILED: *mut c_void = !0 as *mut c_void;
+pub const O_NONBLOCK: c_int = 0o4000;
+pub const EINTR: c_int = 4;
+pub const EAGAIN: c_int = 11;
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct v4l2_capability {
+    pub driver: [u8; 16],
+    pub card: [u8; 32],
+    pub bus_info: [u8; 32],
+    pub version: u32,
+    pub capabilities: u32,
+    pub device_caps: u32,
+    pub reserved: [u32; 3],
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct v4l2_fmtdesc {
+    pub index: u32,
+    pub type_: u32,
+    pub flags: u32,
+    pub description: [u8; 32],
+    pub pixelformat: u32,
+    pub mbus_code: u32,
+    pub reserved: [u32; 3],
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+pub struct v4l2_fract {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct v4l2_frmsize_discrete {
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct v4l2_frmsize_stepwise {
+    pub min_width: u32,
+    pub max_width: u32,
+    pub step_width: u32,
+    pub min_height: u32,
+    pub max_height: u32,
+    pub step_height: u32,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub union v4l2_frmsize_union {
+    pub discrete: v4l2_frmsize_discrete,
+    pub stepwise: v4l2_frmsize_stepwise,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct v4l2_frmsizeenum {
+    pub index: u32,
+    pub pixel_format: u32,
+    pub type_: u32,
+    pub size: v4l2_frmsize_union,
+    pub reserved: [u32; 2],
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct v4l2_frmival_stepwise {
+    pub min: v4l2_fract,
+    pub max: v4l2_fract,
+    pub step: v4l2_fract,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub union v4l2_frmival_union {
+    pub discrete: v4l2_fract,
+    pub stepwise: v4l2_frmival_stepwise,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct v4l2_frmivalenum {
+    pub index: u32,
+    pub pixel_format: u32,
+    pub width: u32,
+    pub height: u32,
+    pub type_: u32,
+    pub interval: v4l2_frmival_union,
+    pub reserved: [u32; 2],
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub struct v4l2_pix_format {
+    pub width: u32,
+    pub height: u32,
+    pub pixelformat: u32,
+    pub field: u32,
+    pub bytesperline: u32,
+    pub sizeimage: u32,
+    pub colorspace: u32,
+    pub priv_: u32,
+    pub flags: u32,
+    pub ycbcr_enc: u32,
+    pub quantization: u32,
+    pub xfer_func: u32,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub union v4l2_format_union {
+    pub pix: v4l2_pix_format,
+    pub raw_data: [u8; 200],
+    // some of the formats in the union hold pointers, which determines its alignment
+    _align: [*mut c_void; 0],
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct v4l2_format {
+    pub type_: u32,
+    pub fmt: v4l2_format_union,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct v4l2_captureparm {
+    pub capability: u32,
+    pub capturemode: u32,
+    pub timeperframe: v4l2_fract,
+    pub extendedmode: u32,
+    pub readbuffers: u32,
+    pub reserved: [u32; 4],
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub union v4l2_streamparm_union {
+    pub capture: v4l2_captureparm,
+    pub raw_data: [u8; 200],
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct v4l2_streamparm {
+    pub type_: u32,
+    pub parm: v4l2_streamparm_union,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct v4l2_requestbuffers {
+    pub count: u32,
+    pub type_: u32,
+    pub memory: u32,
+    pub capabilities: u32,
+    pub flags: u8,
+    pub reserved: [u8; 3],
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct v4l2_timecode {
+    pub type_: u32,
+    pub flags: u32,
+    pub frames: u8,
+    pub seconds: u8,
+    pub minutes: u8,
+    pub hours: u8,
+    pub userbits: [u8; 4],
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct timeval {
+    pub tv_sec: c_long,
+    pub tv_usec: c_long,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub union v4l2_buffer_m {
+    pub offset: u32,
+    pub userptr: c_ulong,
+    pub planes: *mut c_void,
+    pub fd: i32,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct v4l2_buffer {
+    pub index: u32,
+    pub type_: u32,
+    pub bytesused: u32,
+    pub flags: u32,
+    pub field: u32,
+    pub timestamp: timeval,
+    pub timecode: v4l2_timecode,
+    pub sequence: u32,
+    pub memory: u32,
+    pub m: v4l2_buffer_m,
+    pub length: u32,
+    pub reserved2: u32,
+    pub request_fd: i32,
+}
+
+const IOC_WRITE: c_ulong = 1;
+const IOC_READ: c_ulong = 2;
+
+const fn ioc(dir: c_ulong, nr: c_ulong, size: usize) -> c_ulong {
+    (dir << 30) | ((size as c_ulong) << 16) | ((b'V' as c_ulong) << 8) | nr
+}
+
+pub const VIDIOC_QUERYCAP: c_ulong = ioc(IOC_READ, 0, mem::size_of::<v4l2_capability>());
+pub const VIDIOC_ENUM_FMT: c_ulong = ioc(IOC_READ | IOC_WRITE, 2, mem::size_of::<v4l2_fmtdesc>());
+pub const VIDIOC_S_FMT: c_ulong = ioc(IOC_READ | IOC_WRITE, 5, mem::size_of::<v4l2_format>());
+pub const VIDIOC_REQBUFS: c_ulong = ioc(IOC_READ | IOC_WRITE, 8, mem::size_of::<v4l2_requestbuffers>());
+pub const VIDIOC_QUERYBUF: c_ulong = ioc(IOC_READ | IOC_WRITE, 9, mem::size_of::<v4l2_buffer>());
+pub const VIDIOC_QBUF: c_ulong = ioc(IOC_READ | IOC_WRITE, 15, mem::size_of::<v4l2_buffer>());
+pub const VIDIOC_DQBUF: c_ulong = ioc(IOC_READ | IOC_WRITE, 17, mem::size_of::<v4l2_buffer>());
+pub const VIDIOC_STREAMON: c_ulong = ioc(IOC_WRITE, 18, mem::size_of::<c_int>());
+pub const VIDIOC_STREAMOFF: c_ulong = ioc(IOC_WRITE, 19, mem::size_of::<c_int>());
+pub const VIDIOC_S_PARM: c_ulong = ioc(IOC_READ | IOC_WRITE, 22, mem::size_of::<v4l2_streamparm>());
+pub const VIDIOC_ENUM_FRAMESIZES: c_ulong = ioc(IOC_READ | IOC_WRITE, 74, mem::size_of::<v4l2_frmsizeenum>());
+pub const VIDIOC_ENUM_FRAMEINTERVALS: c_ulong = ioc(IOC_READ | IOC_WRITE, 75, mem::size_of::<v4l2_frmivalenum>());
+
+extern "C" {
+    pub fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
+    pub fn mmap(addr: *mut c_void, len: usize, prot: c_int, flags: c_int, fd: c_int, offset: c_long) -> *mut c_void;
+    pub fn munmap(addr: *mut c_void, len: usize) -> c_int;
+}
@@ -9,19 +9,22 @@ use {
         egl_sys,
         x11::xlib_event::*,
         x11::xlib_app::*,
-        linux_media::CxLinuxMedia
+        linux_media::CxLinuxMedia,
+        http::make_http_request,
+        opengl::{GlProfiler, GlReadbacks},
     },
     crate::{
         cx_api::{CxOsOp, CxOsApi}, 
         makepad_math::dvec2,
         makepad_live_id::*,
         thread::SignalToUI,
-        event::Event,
+        event::{Event, NetworkResponseChannel},
         pass::CxPassParent,
         cx::{Cx, OsType,LinuxWindowParams}, 
         os::cx_stdin::{PollTimers},
         gpu_info::GpuPerformance,
         os::cx_native::EventFlow,
+        file_dialogs::FileDialogKind,
     }
 };
 
@@ -132,10 +135,12 @@ impl Cx {
                     self.call_draw_event();
                     self.os.opengl_cx.as_ref().unwrap().make_current();
                     self.opengl_compile_shaders();
+                    self.opengl_run_compute();
                 }
                 // ok here we send out to all our childprocesses
                 
                 self.handle_repaint(opengl_windows);
+                self.handle_readback_results();
             }
             XlibEvent::MouseDown(e) => {
                 self.fingers.process_tap_count(
@@ -195,6 +200,7 @@ impl Cx {
                 //println!("TIMER! {:?}", std::time::Instant::now());
                 if e.timer_id == 0{
                     if SignalToUI::check_and_clear_ui_signal(){
+                        self.handle_networking_events();
                         self.handle_media_signals();
                         self.call_event_handler(&Event::Signal);
                     }
@@ -205,7 +211,7 @@ impl Cx {
             }
         }
         
-        if self.any_passes_dirty() || self.need_redrawing() || self.new_next_frames.len() != 0 || paint_dirty {
+        if self.any_passes_dirty() || self.need_redrawing() || self.new_next_frames.len() != 0 || self.opengl_has_pending_readbacks() || paint_dirty {
             EventFlow::Poll
         } else {
             EventFlow::Wait
@@ -214,6 +220,13 @@ impl Cx {
     }
 
     pub(crate) fn handle_networking_events(&mut self) {
+        let mut out = Vec::new();
+        while let Ok(event) = self.os.network_response.receiver.try_recv() {
+            out.push(event);
+        }
+        if out.len()>0 {
+            self.call_event_handler(&Event::NetworkResponses(out))
+        }
     }
     
     pub (crate) fn handle_repaint(&mut self, opengl_windows: &mut Vec<OpenglWindow>) {
@@ -221,6 +234,7 @@ impl Cx {
         let mut passes_todo = Vec::new();
         self.compute_pass_repaint_order(&mut passes_todo);
         self.repaint_id += 1;
+        self.opengl_profile_begin_frame();
         for pass_id in &passes_todo {
             self.passes[*pass_id].set_time(get_xlib_app_global().time_now() as f32);
             match self.passes[*pass_id].parent.clone() {
@@ -240,6 +254,9 @@ impl Cx {
                 }
             }
         }
+        self.opengl_poll_readbacks();
+        self.opengl_read_back_textures();
+        self.opengl_profile_end_frame();
     }
     
     fn handle_platform_ops(&mut self, opengl_windows: &mut Vec<OpenglWindow>, xlib_app: &mut XlibApp) -> EventFlow {
@@ -323,8 +340,8 @@ impl Cx {
                 },
                 CxOsOp::UpdateMacosMenu(_menu) => {
                 },
-                CxOsOp::HttpRequest{request_id:_, request:_} => {
-                    todo!()
+                CxOsOp::HttpRequest{request_id, request} => {
+                    make_http_request(request_id, request, self.os.network_response.sender.clone());
                 },
                 CxOsOp::PrepareVideoPlayback(_, _, _, _, _) => todo!(),
                 CxOsOp::BeginVideoPlayback(_) => todo!(),
@@ -335,10 +352,18 @@ impl Cx {
                 CxOsOp::CleanupVideoPlaybackResources(_) => todo!(),
                 CxOsOp::UpdateVideoSurfaceTexture(_) => todo!(),
 
-                CxOsOp::SaveFileDialog(_) => todo!(),
-                CxOsOp::SelectFileDialog(_) => todo!(),
-                CxOsOp::SaveFolderDialog(_) => todo!(),
-                CxOsOp::SelectFolderDialog(_) => todo!(),
+                CxOsOp::SaveFileDialog(dialog) => {
+                    self.request_file_dialog(FileDialogKind::SaveFile, dialog);
+                }
+                CxOsOp::SelectFileDialog(dialog) => {
+                    self.request_file_dialog(FileDialogKind::SelectFile, dialog);
+                }
+                CxOsOp::SaveFolderDialog(dialog) => {
+                    self.request_file_dialog(FileDialogKind::SaveFolder, dialog);
+                }
+                CxOsOp::SelectFolderDialog(dialog) => {
+                    self.request_file_dialog(FileDialogKind::SelectFolder, dialog);
+                }
             }
         }
         ret
@@ -361,8 +386,11 @@ impl CxOsApi for Cx {
 pub struct CxOs {
     pub(crate) media: CxLinuxMedia,
     pub (crate) stdin_timers: PollTimers,
+    pub (crate) network_response: NetworkResponseChannel,
 
     // HACK(eddyb) generalize this to EGL, properly.
     pub(super) opengl_cx: Option<OpenglCx>,
+    pub(crate) gl_profiler: GlProfiler,
+    pub(crate) gl_readbacks: GlReadbacks,
 }
 
@@ -19,6 +19,7 @@ use {
         cx_api::CxOsOp,
         cx::Cx,
         gl_sys,
+        os::linux::http::make_http_request,
     } 
 };
 
@@ -33,6 +34,7 @@ impl Cx {
         let mut passes_todo = Vec::new();
         self.compute_pass_repaint_order(&mut passes_todo);
         self.repaint_id += 1;
+        self.opengl_profile_begin_frame();
         for &pass_id in &passes_todo {
             match self.passes[pass_id].parent.clone() {
                 CxPassParent::Window(_) => {
@@ -55,6 +57,13 @@ impl Cx {
                             height: (pass_rect.size.y * dpi_factor) as u32,
                         };
 
+                        // the pass is drawn at the top of the swapchain image, which can be larger
+                        if let (Some(gl_framebuffer), Some(alloc)) = (self.passes[pass_id].os.gl_framebuffer, &self.textures[current_image.image.texture_id()].alloc) {
+                            let (width, height) = (presentable_draw.width as usize, presentable_draw.height as usize);
+                            let y = alloc.height.saturating_sub(height);
+                            self.opengl_read_back_pass(pass_id, gl_framebuffer, y, width.min(alloc.width), height.min(alloc.height));
+                        }
+
                         // inform host that frame is ready
                         let _ = io::stdout().write_all(StdinToHost::DrawCompleteAndFlip(presentable_draw).to_json().as_bytes());
                     }
@@ -68,6 +77,9 @@ impl Cx {
                 }
             }
         }
+        self.opengl_poll_readbacks();
+        self.opengl_read_back_textures();
+        self.opengl_profile_end_frame();
     }
     
     pub fn stdin_event_loop(&mut self) {
@@ -222,9 +234,11 @@ impl Cx {
                     if self.need_redrawing() {
                         self.call_draw_event();
                         self.opengl_compile_shaders();
+                        self.opengl_run_compute();
                     }
 
                     self.stdin_handle_repaint(swapchain.as_ref(), &mut present_index);
+                    self.handle_readback_results();
                 }
             }
         }
@@ -263,6 +277,9 @@ impl Cx {
                 CxOsOp::StopTimer(timer_id) => {
                     self.os.stdin_timers.timers.remove(&timer_id);
                 },
+                CxOsOp::HttpRequest {request_id, request} => {
+                    make_http_request(request_id, request, self.os.network_response.sender.clone());
+                },
                 _ => ()
                 /*
                 CxOsOp::CloseWindow(_window_id) => {},
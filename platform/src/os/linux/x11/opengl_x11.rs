@@ -80,6 +80,7 @@ impl Cx {
             &mut zbias,
             zbias_step,
         );
+        self.opengl_read_back_pass(pass_id, 0, 0, pix_width.floor() as usize, pix_height.floor() as usize);
 
         unsafe {
             let opengl_cx = self.os.opengl_cx.as_ref().unwrap();
@@ -7,6 +7,8 @@ pub mod cx_shared;
 
 pub mod cx_stdin;
 
+pub mod headless;
+
 #[cfg(any(target_os = "macos", target_os="ios", target_os="tvos"))]
 pub mod apple;
 
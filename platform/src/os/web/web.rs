@@ -368,6 +368,7 @@ impl Cx {
          
         self.compute_pass_repaint_order(&mut passes_todo);
         self.repaint_id += 1;
+        self.skip_unsupported_readbacks("WebGL");
         for pass_id in &passes_todo {
             self.passes[*pass_id].set_time(time as f32);
             match self.passes[*pass_id].parent.clone() {
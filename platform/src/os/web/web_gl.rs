@@ -369,6 +369,7 @@ impl Cx {
             }
         }
         self.draw_shaders.compile_set.clear();
+        self.skip_unsupported_compute_ops("WebGL");
     }
 }
 
@@ -441,6 +442,15 @@ pub struct CxOsDrawShader {
 pub struct CxOsTexture {
 }
 
+// WebGL has no compute shaders
+#[derive(Clone, Default)]
+pub struct CxOsComputeShader {
+}
+
+#[derive(Clone, Default)]
+pub struct CxOsComputeBuffer {
+}
+
 #[derive(Clone, Default)]
 pub struct CxOsGeometry {
     pub vb_id: Option<usize>,
@@ -10,6 +10,7 @@ use crate::{
     event::WindowGeom,
     cx::Cx,
     draw_shader::CxDrawShaderMapping,
+    compute_shader::{CxComputeOp, CxComputeBuffer},
     pass::{PassClearColor, PassClearDepth, PassId},
     window::WindowId,
     texture::{ 
@@ -75,6 +76,15 @@ use crate::{
                     D3D11_MAP_WRITE_DISCARD,
                     D3D11_QUERY_DESC,
                     D3D11_QUERY_EVENT,
+                    D3D11_BIND_UNORDERED_ACCESS,
+                    D3D11_RESOURCE_MISC_BUFFER_STRUCTURED,
+                    D3D11_UNORDERED_ACCESS_VIEW_DESC,
+                    D3D11_UNORDERED_ACCESS_VIEW_DESC_0,
+                    D3D11_UAV_DIMENSION_BUFFER,
+                    D3D11_BUFFER_UAV,
+                    D3D11_USAGE_STAGING,
+                    D3D11_CPU_ACCESS_READ,
+                    D3D11_MAP_READ,
                     ID3D11Device,
                     ID3D11DeviceContext,
                     ID3D11RenderTargetView,
@@ -86,6 +96,8 @@ use crate::{
                     ID3D11DepthStencilState,
                     ID3D11PixelShader,
                     ID3D11VertexShader,
+                    ID3D11ComputeShader,
+                    ID3D11UnorderedAccessView,
                     ID3D11InputLayout,
                     ID3D11Buffer,
                     D3D11CreateDevice,
@@ -124,6 +136,7 @@ use crate::{
                         DXGI_FORMAT_R32_FLOAT,
                         DXGI_FORMAT_R32G32_FLOAT,
                         DXGI_FORMAT_R32G32B32_FLOAT,
+                        DXGI_FORMAT_UNKNOWN,
                     },
                 },
             },
@@ -177,11 +190,10 @@ impl Cx {
                 
                 if draw_call.instance_dirty {
                     draw_call.instance_dirty = false;
-                    if draw_item.instances.as_ref().unwrap().len() == 0 {
-                        continue;
+                    // update the instance buffer data, there is none when the instances come from a compute buffer
+                    if draw_item.instances.as_ref().unwrap().len() != 0 {
+                        draw_item.os.inst_vbuf.update_with_f32_vertex_data(d3d11_cx, draw_item.instances.as_ref().unwrap());
                     }
-                    // update the instance buffer data
-                    draw_item.os.inst_vbuf.update_with_f32_vertex_data(d3d11_cx, draw_item.instances.as_ref().unwrap());
                 }
                 
                 // update the zbias uniform if we have it.
@@ -197,7 +209,15 @@ impl Cx {
                     }
                 }
                 
-                let instances = (draw_item.instances.as_ref().unwrap().len() / sh.mapping.instances.total_slots) as u64;
+                // instances drawn from a compute buffer replace the ones written from the CPU
+                let (inst_vbuf, instances) = if let Some(buffer) = &draw_call.instance_buffer {
+                    let cxbuffer = &mut self.compute_buffers[buffer.compute_buffer_id()];
+                    let inst_vbuf = cxbuffer.update_d3d11_instance_buffer(d3d11_cx);
+                    (inst_vbuf, (cxbuffer.data.len() / sh.mapping.instances.total_slots) as u64)
+                }
+                else {
+                    (draw_item.os.inst_vbuf.buffer.clone(), (draw_item.instances.as_ref().unwrap().len() / sh.mapping.instances.total_slots) as u64)
+                };
                 
                 if instances == 0 {
                     continue;
@@ -229,7 +249,7 @@ impl Cx {
                     let inst_slots = sh.mapping.instances.total_slots;
                     let strides = [(geom_slots * 4) as u32, (inst_slots * 4) as u32];
                     let offsets = [0u32, 0u32];
-                    let buffers = [geometry.os.geom_vbuf.buffer.clone(), draw_item.os.inst_vbuf.buffer.clone()];
+                    let buffers = [geometry.os.geom_vbuf.buffer.clone(), inst_vbuf];
                     d3d11_cx.context.IASetVertexBuffers(0, 2, Some(buffers.as_ptr()), Some(strides.as_ptr()), Some(offsets.as_ptr()));
                     
                     fn buffer_slot(d3d11_cx: &D3d11Cx, index: u32, buffer: &Option<ID3D11Buffer>) {
@@ -497,6 +517,116 @@ impl Cx {
             }
         }
         self.draw_shaders.compile_set.clear();
+        
+        for compute_shader_ptr in &self.compute_shaders.compile_set {
+            if let Some(compute_shader_id) = self.compute_shaders.ptr_to_id.get(compute_shader_ptr) {
+                let cx_shader = &mut self.compute_shaders.shaders[*compute_shader_id];
+                let compute_shader_def = self.shader_registry.draw_shader_defs.get(compute_shader_ptr);
+                let hlsl = generate_hlsl::generate_shader(
+                    compute_shader_def.as_ref().unwrap(),
+                    &cx_shader.mapping.const_table,
+                    &self.shader_registry
+                );
+                if cx_shader.mapping.flags.debug {
+                    crate::log!("{}", hlsl);
+                }
+                cx_shader.os = CxOsComputeShader::new(d3d11_cx, hlsl, &cx_shader.mapping);
+            }
+        }
+        self.compute_shaders.compile_set.clear();
+    }
+    
+    // runs the compute work queued since the last repaint, so the passes see its results
+    pub (crate) fn d3d11_run_compute(&mut self, d3d11_cx: &D3d11Cx) {
+        let ops = std::mem::take(&mut self.compute_shaders.ops);
+        for op in ops {
+            match op {
+                CxComputeOp::Dispatch(dispatch) => {
+                    let sh = &mut self.compute_shaders.shaders[dispatch.compute_shader_id];
+                    let compute_shader = if let Some(compute_shader) = &sh.os.compute_shader {compute_shader} else {continue};
+                    sh.os.user_uniforms.update_with_f32_constant_data(d3d11_cx, &dispatch.user_uniforms);
+                    unsafe {
+                        d3d11_cx.context.CSSetShader(compute_shader, None);
+                        d3d11_cx.context.CSSetConstantBuffers(0, Some(&[sh.os.live_uniforms.buffer.clone()]));
+                        d3d11_cx.context.CSSetConstantBuffers(1, Some(&[sh.os.const_table_uniforms.buffer.clone()]));
+                        d3d11_cx.context.CSSetConstantBuffers(5, Some(&[sh.os.user_uniforms.buffer.clone()]));
+                    }
+                    for i in 0..sh.mapping.textures.len() {
+                        let texture_id = if let Some(texture) = &dispatch.texture_slots[i] {
+                            texture.texture_id()
+                        } else {
+                            continue;
+                        };
+                        let cxtexture = &mut self.textures[texture_id];
+                        if cxtexture.format.is_vec(){
+                            cxtexture.update_vec_texture(d3d11_cx);
+                        }
+                        unsafe {
+                            d3d11_cx.context.CSSetShaderResources(i as u32, Some(&[cxtexture.os.shader_resource_view.clone()]));
+                        }
+                    }
+                    // the buffers are bound in declaration order, as the u registers the shader declares
+                    let mut uavs = Vec::new();
+                    for (i, input) in sh.buffers.iter().enumerate() {
+                        let uav = if let Some(buffer) = &dispatch.buffer_slots[i] {
+                            let cxbuffer = &mut self.compute_buffers[buffer.compute_buffer_id()];
+                            cxbuffer.update_d3d11_buffer(d3d11_cx, input.elem_ty.slots() * 4);
+                            cxbuffer.os.inst_vbuf_dirty = true;
+                            cxbuffer.os.uav.clone()
+                        }
+                        else {
+                            None
+                        };
+                        uavs.push(uav);
+                    }
+                    let [x, y, z] = dispatch.groups;
+                    unsafe {
+                        d3d11_cx.context.CSSetUnorderedAccessViews(0, uavs.len() as u32, Some(uavs.as_ptr()), None);
+                        d3d11_cx.context.Dispatch(x, y, z);
+                        // unbound again, a buffer can't be read as instances or copied while it is bound for writing
+                        let unbound: Vec<Option<ID3D11UnorderedAccessView>> = vec![None; uavs.len()];
+                        d3d11_cx.context.CSSetUnorderedAccessViews(0, unbound.len() as u32, Some(unbound.as_ptr()), None);
+                    }
+                }
+                CxComputeOp::CopyToTexture {buffer, texture} => {
+                    let cxbuffer = &mut self.compute_buffers[buffer.compute_buffer_id()];
+                    cxbuffer.update_d3d11_buffer(d3d11_cx, cxbuffer.os.stride.max(4));
+                    let cxtexture = &mut self.textures[texture.texture_id()];
+                    let (width, height) = match &cxtexture.format {
+                        TextureFormat::VecRGBAf32 {width, height, ..} if width * height * 16 == cxbuffer.os.byte_width => (*width, *height),
+                        _ => {
+                            crate::log!("copy_compute_buffer_to_texture needs a VecRGBAf32 texture with 4 floats per pixel of the buffer");
+                            continue
+                        }
+                    };
+                    cxtexture.update_vec_texture(d3d11_cx);
+                    let (src, dst) = match (&cxbuffer.os.buffer, &cxtexture.os.texture) {
+                        (Some(src), Some(dst)) => (src, dst),
+                        _ => continue
+                    };
+                    // a buffer can't be copied into a texture on the GPU, so it goes through a staging
+                    // buffer, mapping that waits for the compute work that wrote it
+                    let staging_desc = D3D11_BUFFER_DESC {
+                        Usage: D3D11_USAGE_STAGING,
+                        ByteWidth: cxbuffer.os.byte_width as u32,
+                        BindFlags: 0,
+                        CPUAccessFlags: D3D11_CPU_ACCESS_READ.0 as u32,
+                        MiscFlags: 0,
+                        StructureByteStride: 0
+                    };
+                    let mut staging = None;
+                    unsafe {
+                        d3d11_cx.device.CreateBuffer(&staging_desc, None, Some(&mut staging)).unwrap();
+                        let staging = staging.unwrap();
+                        d3d11_cx.context.CopyResource(&staging, src);
+                        let mut mapped = D3D11_MAPPED_SUBRESOURCE::default();
+                        d3d11_cx.context.Map(&staging, 0, D3D11_MAP_READ, 0, Some(&mut mapped)).unwrap();
+                        d3d11_cx.context.UpdateSubresource(dst, 0, None, mapped.pData, (width * 16) as u32, (width * height * 16) as u32);
+                        d3d11_cx.context.Unmap(&staging, 0);
+                    }
+                }
+            }
+        }
     }
 
     pub fn share_texture_for_presentable_image(
@@ -788,6 +918,132 @@ impl D3d11Buffer {
 }
 
 
+#[derive(Clone, Default)]
+pub struct CxOsComputeShader {
+    pub hlsl: String,
+    pub compute_shader: Option<ID3D11ComputeShader>,
+    pub const_table_uniforms: D3d11Buffer,
+    pub live_uniforms: D3d11Buffer,
+    pub user_uniforms: D3d11Buffer,
+}
+
+impl CxOsComputeShader {
+    fn new(d3d11_cx: &D3d11Cx, hlsl: String, mapping: &CxDrawShaderMapping) -> Self {
+        let compute_shader = match compile_shader("cs_5_0\0", "compute_main\0", &hlsl) {
+            Err(msg) => {
+                println!("Cannot compile computeshader\n{}\n{}", msg, split_source(&hlsl));
+                None
+            },
+            Ok(blob) => {
+                let mut cs = None;
+                unsafe {d3d11_cx.device.CreateComputeShader(
+                    std::slice::from_raw_parts(blob.GetBufferPointer() as *const u8, blob.GetBufferSize() as usize),
+                    None,
+                    Some(&mut cs)
+                ).unwrap()};
+                cs
+            }
+        };
+        
+        let mut live_uniforms = D3d11Buffer::default();
+        live_uniforms.update_with_f32_constant_data(d3d11_cx, mapping.live_uniforms_buf.as_ref());
+        
+        let mut const_table_uniforms = D3d11Buffer::default();
+        const_table_uniforms.update_with_f32_constant_data(d3d11_cx, mapping.const_table.table.as_ref());
+        
+        Self {
+            hlsl,
+            compute_shader,
+            const_table_uniforms,
+            live_uniforms,
+            user_uniforms: D3d11Buffer::default(),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct CxOsComputeBuffer {
+    buffer: Option<ID3D11Buffer>,
+    uav: Option<ID3D11UnorderedAccessView>,
+    stride: usize,
+    byte_width: usize,
+    // structured buffers can't be bound as vertex buffers, so instances are drawn from a copy
+    inst_vbuf: Option<ID3D11Buffer>,
+    inst_vbuf_dirty: bool,
+}
+
+impl CxComputeBuffer {
+    // uploads the CPU data when it changed. The structured buffer is made for the element size of
+    // the shader that binds it, and made again from the CPU data for a shader with another one
+    fn update_d3d11_buffer(&mut self, d3d11_cx: &D3d11Cx, stride: usize) {
+        if self.os.buffer.is_some() && self.os.stride == stride && !self.updated {
+            return
+        }
+        self.updated = false;
+        self.os.buffer = None;
+        self.os.uav = None;
+        self.os.inst_vbuf = None;
+        self.os.stride = stride;
+        let elements = self.data.len() * 4 / stride;
+        self.os.byte_width = elements * stride;
+        if elements == 0 {
+            return
+        }
+        let buffer_desc = D3D11_BUFFER_DESC {
+            Usage: D3D11_USAGE_DEFAULT,
+            ByteWidth: self.os.byte_width as u32,
+            BindFlags: D3D11_BIND_UNORDERED_ACCESS.0 as u32,
+            CPUAccessFlags: 0,
+            MiscFlags: D3D11_RESOURCE_MISC_BUFFER_STRUCTURED.0 as u32,
+            StructureByteStride: stride as u32
+        };
+        let sub_data = D3D11_SUBRESOURCE_DATA {
+            pSysMem: self.data.as_ptr() as *const _,
+            SysMemPitch: 0,
+            SysMemSlicePitch: 0
+        };
+        let uav_desc = D3D11_UNORDERED_ACCESS_VIEW_DESC {
+            Format: DXGI_FORMAT_UNKNOWN,
+            ViewDimension: D3D11_UAV_DIMENSION_BUFFER,
+            Anonymous: D3D11_UNORDERED_ACCESS_VIEW_DESC_0 {
+                Buffer: D3D11_BUFFER_UAV {
+                    FirstElement: 0,
+                    NumElements: elements as u32,
+                    Flags: 0
+                }
+            }
+        };
+        unsafe {
+            d3d11_cx.device.CreateBuffer(&buffer_desc, Some(&sub_data), Some(&mut self.os.buffer)).unwrap();
+            d3d11_cx.device.CreateUnorderedAccessView(self.os.buffer.as_ref().unwrap(), Some(&uav_desc), Some(&mut self.os.uav)).unwrap();
+        }
+        self.os.inst_vbuf_dirty = true;
+    }
+    
+    // copies the buffer into a vertex buffer when a compute shader or the CPU changed it
+    fn update_d3d11_instance_buffer(&mut self, d3d11_cx: &D3d11Cx) -> Option<ID3D11Buffer> {
+        self.update_d3d11_buffer(d3d11_cx, self.os.stride.max(4));
+        let buffer = self.os.buffer.as_ref()?;
+        if self.os.inst_vbuf.is_none() {
+            let buffer_desc = D3D11_BUFFER_DESC {
+                Usage: D3D11_USAGE_DEFAULT,
+                ByteWidth: self.os.byte_width as u32,
+                BindFlags: D3D11_BIND_VERTEX_BUFFER.0 as u32,
+                CPUAccessFlags: 0,
+                MiscFlags: 0,
+                StructureByteStride: 0
+            };
+            unsafe {d3d11_cx.device.CreateBuffer(&buffer_desc, None, Some(&mut self.os.inst_vbuf)).unwrap()};
+            self.os.inst_vbuf_dirty = true;
+        }
+        if self.os.inst_vbuf_dirty {
+            self.os.inst_vbuf_dirty = false;
+            unsafe {d3d11_cx.context.CopyResource(self.os.inst_vbuf.as_ref().unwrap(), buffer)};
+        }
+        self.os.inst_vbuf.clone()
+    }
+}
+
 #[derive(Default)]
 pub struct CxOsTexture {
     texture: Option<ID3D11Texture2D >,
@@ -1099,6 +1355,46 @@ pub struct CxOsGeometry {
     pub geom_ibuf: D3d11Buffer,
 }
 
+fn compile_shader(target: &str, entry: &str, shader: &str) -> Result<ID3DBlob, String> {
+    unsafe {
+        let shader_bytes = shader.as_bytes();
+        let mut blob = None;
+        let mut errors = None;
+        if D3DCompile(
+            shader_bytes.as_ptr() as *const _,
+            shader_bytes.len(),
+            PCSTR("makepad_shader\0".as_ptr()), // sourcename
+            None, // defines
+            None, // include
+            PCSTR(entry.as_ptr()), // entry point
+            PCSTR(target.as_ptr()), // target
+            0, // flags1
+            0, // flags2
+            &mut blob,
+            Some(&mut errors)
+        ).is_ok() {
+            return Ok(blob.unwrap());
+        };
+        let error = errors.unwrap();
+        let pointer = error.GetBufferPointer();
+        let size = error.GetBufferSize();
+        let slice = std::slice::from_raw_parts(pointer as *const u8, size as usize);
+        return Err(String::from_utf8_lossy(slice).into_owned());
+    }
+}
+
+fn split_source(src: &str) -> String {
+    let mut r = String::new();
+    let split = src.split("\n");
+    for (line, chunk) in split.enumerate() {
+        r.push_str(&(line + 1).to_string());
+        r.push_str(":");
+        r.push_str(chunk);
+        r.push_str("\n");
+    }
+    return r
+}
+
 #[derive(Clone)]
 pub struct CxOsDrawShader {
     pub hlsl: String,
@@ -1115,45 +1411,6 @@ impl CxOsDrawShader {
     
     fn new(d3d11_cx: &D3d11Cx, hlsl: String, mapping: &CxDrawShaderMapping) -> Option<Self> {
         
-        fn compile_shader(target: &str, entry: &str, shader: &str) -> Result<ID3DBlob, String> {
-            unsafe {
-                let shader_bytes = shader.as_bytes();
-                let mut blob = None;
-                let mut errors = None;
-                if D3DCompile(
-                    shader_bytes.as_ptr() as *const _,
-                    shader_bytes.len(),
-                    PCSTR("makepad_shader\0".as_ptr()), // sourcename
-                    None, // defines
-                    None, // include
-                    PCSTR(entry.as_ptr()), // entry point
-                    PCSTR(target.as_ptr()), // target
-                    0, // flags1
-                    0, // flags2
-                    &mut blob,
-                    Some(&mut errors)
-                ).is_ok() {
-                    return Ok(blob.unwrap());
-                };
-                let error = errors.unwrap();
-                let pointer = error.GetBufferPointer();
-                let size = error.GetBufferSize();
-                let slice = std::slice::from_raw_parts(pointer as *const u8, size as usize);
-                return Err(String::from_utf8_lossy(slice).into_owned());
-            }
-        }
-        fn split_source(src: &str) -> String {
-            let mut r = String::new();
-            let split = src.split("\n");
-            for (line, chunk) in split.enumerate() {
-                r.push_str(&(line + 1).to_string());
-                r.push_str(":");
-                r.push_str(chunk);
-                r.push_str("\n");
-            }
-            return r
-        }
-        
         fn slots_to_dxgi_format(slots: usize) -> DXGI_FORMAT {
             
             match slots {
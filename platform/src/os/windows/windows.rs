@@ -160,6 +160,7 @@ impl Cx {
                 if self.need_redrawing() {
                     self.call_draw_event();
                     self.hlsl_compile_shaders(&d3d11_cx);
+                    self.d3d11_run_compute(&d3d11_cx);
                 }
                 // ok here we send out to all our childprocesses
                 
@@ -259,6 +260,7 @@ impl Cx {
         let mut passes_todo = Vec::new();
         self.compute_pass_repaint_order(&mut passes_todo);
         self.repaint_id += 1;
+        self.skip_unsupported_readbacks("DirectX 11");
         for pass_id in &passes_todo {
             self.passes[*pass_id].set_time(get_win32_app_global().time_now() as f32);
             match self.passes[*pass_id].parent.clone() {
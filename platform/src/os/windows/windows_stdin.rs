@@ -36,6 +36,7 @@ impl Cx {
         let mut passes_todo = Vec::new();
         self.compute_pass_repaint_order(&mut passes_todo);
         self.repaint_id += 1;
+        self.skip_unsupported_readbacks("DirectX 11");
         for &pass_id in &passes_todo {
             match self.passes[pass_id].parent.clone() {
                 CxPassParent::Window(_) => {
@@ -216,6 +217,7 @@ impl Cx {
                     if self.need_redrawing() {
                         self.call_draw_event();
                         self.hlsl_compile_shaders(d3d11_cx);
+                        self.d3d11_run_compute(d3d11_cx);
                     }
 
                     // repaint
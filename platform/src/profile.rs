@@ -0,0 +1,59 @@
+use {
+    std::{
+        cell::Cell,
+        time::Instant,
+    },
+    crate::{
+        cx::Cx,
+        studio::{AppToStudio, SpanSample},
+    }
+};
+
+thread_local! {
+    static SPAN_DEPTH: Cell<u32> = const {Cell::new(0)};
+}
+
+struct OpenSpan {
+    name: String,
+    depth: u32,
+    start_time: Instant,
+    start: f64,
+}
+
+/// Times the scope it lives in as a named span for the Studio profiler, see `profile_scope!`.
+/// Unless the Studio profiler is open it does nothing, and the name is never built.
+pub struct ProfileScope(Option<OpenSpan>);
+
+impl ProfileScope {
+    pub fn begin(cx: &Cx, name: impl FnOnce() -> String) -> Self {
+        if !Cx::has_studio_profiler() {
+            return Self(None)
+        }
+        let depth = SPAN_DEPTH.with( | d | {
+            let depth = d.get();
+            d.set(depth + 1);
+            depth
+        });
+        Self(Some(OpenSpan {
+            name: name(),
+            depth,
+            start_time: cx.start_time,
+            start: Instant::now().duration_since(cx.start_time).as_secs_f64(),
+        }))
+    }
+}
+
+impl Drop for ProfileScope {
+    fn drop(&mut self) {
+        if let Some(span) = self.0.take() {
+            let end = Instant::now().duration_since(span.start_time).as_secs_f64();
+            SPAN_DEPTH.with( | d | d.set(span.depth));
+            Cx::send_studio_message(AppToStudio::SpanSample(SpanSample {
+                name: span.name,
+                depth: span.depth,
+                start: span.start,
+                end,
+            }));
+        }
+    }
+}
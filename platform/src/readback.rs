@@ -0,0 +1,154 @@
+use {
+    crate::{
+        cx::Cx,
+        makepad_live_id::LiveId,
+        pass::{Pass, PassId, CxPassParent},
+        texture::{Texture, TextureFormat},
+    }
+};
+
+/// Pixels read back from a texture or a pass, row by row from the top.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReadbackPixels {
+    /// Packed as 0xAARRGGBB, like `TextureFormat::VecBGRAu8_32`
+    BGRAu8(Vec<u32>),
+    /// Four floats per pixel in RGBA order, from float textures
+    RGBAf32(Vec<f32>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReadbackImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: ReadbackPixels,
+}
+
+impl ReadbackImage {
+    /// The pixels packed as 0xAARRGGBB, float channels are clamped and rounded to 8 bits.
+    pub fn to_bgra_u32(&self) -> Vec<u32> {
+        match &self.pixels {
+            ReadbackPixels::BGRAu8(data) => data.clone(),
+            ReadbackPixels::RGBAf32(data) => data.chunks(4).map( | c | {
+                let u = | v: f32 | (v.clamp(0.0, 1.0) * 255.0 + 0.5) as u32;
+                (u(c[3]) << 24) | (u(c[0]) << 16) | (u(c[1]) << 8) | u(c[2])
+            }).collect()
+        }
+    }
+}
+
+/// Sent in answer to `Texture::read_back` or `Pass::read_back`. The image is `None` when there
+/// was nothing to read, or when the backend cannot read back yet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReadbackEvent {
+    pub request_id: LiveId,
+    pub image: Option<ReadbackImage>,
+}
+
+pub(crate) enum ReadbackSource {
+    Texture(Texture),
+    Pass(PassId),
+}
+
+#[derive(Default)]
+pub struct CxReadbacks {
+    pub(crate) requests: Vec<(LiveId, ReadbackSource)>,
+    pub(crate) results: Vec<ReadbackEvent>,
+    #[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "windows", target_arch = "wasm32"))]
+    pub(crate) warned_unsupported: bool,
+}
+
+impl CxReadbacks {
+    /// Takes the requests for a window pass, to be read right after it is drawn.
+    pub(crate) fn take_pass_requests(&mut self, pass_id: PassId) -> Vec<LiveId> {
+        let mut taken = Vec::new();
+        self.requests.retain( | (request_id, source) | {
+            if matches!(source, ReadbackSource::Pass(id) if *id == pass_id) {
+                taken.push(*request_id);
+                return false
+            }
+            true
+        });
+        taken
+    }
+}
+
+impl Texture {
+    /// Asks for the contents of the texture, which arrive in an `Event::Readback` carrying
+    /// `request_id`. Render textures are read once the passes drawing into them have painted,
+    /// vec textures are answered from their CPU side data.
+    pub fn read_back(&self, cx: &mut Cx, request_id: LiveId) {
+        let texture_id = self.texture_id();
+        let image = match &cx.textures[texture_id].format {
+            TextureFormat::VecBGRAu8_32 {width, height, data} |
+            TextureFormat::VecMipBGRAu8_32 {width, height, data, ..} => Some(ReadbackImage {
+                width: *width,
+                height: *height,
+                pixels: ReadbackPixels::BGRAu8(data.clone())
+            }),
+            TextureFormat::VecRGBAf32 {width, height, data} => Some(ReadbackImage {
+                width: *width,
+                height: *height,
+                pixels: ReadbackPixels::RGBAf32(data.clone())
+            }),
+            format if format.is_render() => {
+                cx.readbacks.requests.push((request_id, ReadbackSource::Texture(self.clone())));
+                for pass_id in cx.passes.id_iter() {
+                    if cx.passes[pass_id].color_textures.iter().any( | ct | ct.texture.texture_id() == texture_id) {
+                        cx.repaint_pass(pass_id);
+                    }
+                }
+                return
+            }
+            _ => None
+        };
+        cx.readbacks.results.push(ReadbackEvent {request_id, image});
+    }
+}
+
+impl Pass {
+    /// Asks for the image the pass renders, its first color texture or its window, and
+    /// repaints it. The pixels arrive in an `Event::Readback` carrying `request_id`.
+    pub fn read_back(&self, cx: &mut Cx, request_id: LiveId) {
+        cx.readbacks.requests.push((request_id, ReadbackSource::Pass(self.pass_id())));
+        cx.repaint_pass(self.pass_id());
+    }
+}
+
+impl Cx {
+    /// Takes the requests that read a texture, after every pass of the frame has painted.
+    /// Requests for window passes stay queued until their window draws.
+    pub(crate) fn take_texture_readbacks(&mut self) -> Vec<(LiveId, Texture)> {
+        let mut taken = Vec::new();
+        let mut unanswered = Vec::new();
+        for (request_id, source) in std::mem::take(&mut self.readbacks.requests) {
+            match source {
+                ReadbackSource::Texture(texture) => taken.push((request_id, texture)),
+                ReadbackSource::Pass(pass_id) => match self.passes[pass_id].color_textures.first() {
+                    Some(color_texture) => taken.push((request_id, color_texture.texture.clone())),
+                    None if matches!(self.passes[pass_id].parent, CxPassParent::Window(_)) => {
+                        self.readbacks.requests.push((request_id, ReadbackSource::Pass(pass_id)))
+                    }
+                    None => unanswered.push(request_id)
+                }
+            }
+        }
+        for request_id in unanswered {
+            self.readbacks.results.push(ReadbackEvent {request_id, image: None});
+        }
+        taken
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "windows", target_arch = "wasm32"))]
+    pub(crate) fn skip_unsupported_readbacks(&mut self, backend: &str) {
+        if self.readbacks.requests.is_empty() {
+            return
+        }
+        if !self.readbacks.warned_unsupported {
+            self.readbacks.warned_unsupported = true;
+            log!("Texture readback is not supported on {} yet", backend);
+        }
+        for (request_id, _) in std::mem::take(&mut self.readbacks.requests) {
+            self.readbacks.results.push(ReadbackEvent {request_id, image: None});
+        }
+    }
+}
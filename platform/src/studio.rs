@@ -3,19 +3,48 @@ use crate::log::LogLevel;
 
 // communication enums for studio
 
-#[derive(SerBin, DeBin, Debug)]
+#[derive(SerBin, DeBin, Debug, Clone)]
 pub struct EventSample{
     pub event_u32: u32,
     pub start: f64,
     pub end: f64,
 }
 
-#[derive(SerBin, DeBin, Debug)]
+#[derive(SerBin, DeBin, Debug, Clone)]
 pub struct GPUSample{
     pub start: f64,
     pub end: f64,
 }
 
+/// A named span timed with `profile_scope!`, spans opened inside it have a larger depth.
+#[derive(SerBin, DeBin, Debug, Clone)]
+pub struct SpanSample{
+    pub name: String,
+    pub depth: u32,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// The GPU time of one draw call of a frame.
+#[derive(SerBin, DeBin, Debug, Clone)]
+pub struct GPUDrawCallSample{
+    pub frame_id: u64,
+    pub shader: String,
+    pub instances: u64,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// One repaint, with the number of draw calls and instances it submitted.
+#[derive(SerBin, DeBin, Debug, Clone)]
+pub struct FrameSample{
+    pub frame_id: u64,
+    pub start: f64,
+    pub end: f64,
+    pub draw_calls: u32,
+    pub instances: u64,
+}
+
 #[derive(SerBin, DeBin, Debug)]
 pub struct StudioLogItem{
     pub file_name:String,
@@ -31,7 +60,10 @@ pub struct StudioLogItem{
 pub enum AppToStudio{
     LogItem(StudioLogItem),
     EventSample(EventSample),
-    GPUSample(GPUSample)
+    GPUSample(GPUSample),
+    SpanSample(SpanSample),
+    GPUDrawCallSample(GPUDrawCallSample),
+    FrameSample(FrameSample),
 }
 
 #[derive(SerBin, DeBin)]
@@ -42,7 +74,10 @@ pub enum StudioToApp{
     LiveChange{
         file_name: String,
         content: String
-    }
+    },
+    /// The Studio profiler opened, spans and draw call timings are sent until it closes.
+    EnableProfiler,
+    DisableProfiler,
 }
 
 #[derive(SerBin, DeBin)]
@@ -11,7 +11,7 @@ use {
 #[derive(Clone)]
 pub struct Texture(Rc<PoolId>);
 
-#[derive(Clone, Debug, PartialEq, Copy)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Copy)]
 pub struct TextureId(pub (crate) usize, u64);
 
 impl Texture {
@@ -74,6 +74,17 @@ impl VideoPixelFormat{
                     }
                 }
             }
+            Self::YUY2=>{
+                rgba.resize(width * height,0u32);
+                for y in 0..height{
+                    for x in (0..width).step_by(2){
+                        let d = input[y*(width>>1) + (x>>1)];
+                        let (y1, u, y2, v) = yuy2_components(d);
+                        rgba[y*width + x] = rgb_to_bgra(bt601_to_rgb(y1, u, v));
+                        rgba[y*width + x + 1] = rgb_to_bgra(bt601_to_rgb(y2, u, v));
+                    }
+                }
+            }
             _=>{
                 crate::error!("convert to bgra not supported");
             }
@@ -122,6 +133,21 @@ impl VideoPixelFormat{
                     }
                 }
             }
+            Self::YUY2=>{
+                rgb.clear();
+                rgb.reserve(out_width * out_height * 3);
+                for y in top..top+out_height{
+                    for x in (left..left+out_width).step_by(2){
+                        let (y1, u, y2, v) = yuy2_components(input[y*(in_width>>1) + (x>>1)]);
+                        for luma in [y1, y2]{
+                            let (r,g,b) = bt601_to_rgb(luma, u, v);
+                            rgb.push(r);
+                            rgb.push(g);
+                            rgb.push(b);
+                        }
+                    }
+                }
+            }
             _=>{
                 crate::error!("convert to bgra not supported");
             }
@@ -129,6 +155,28 @@ impl VideoPixelFormat{
     }
 }
 
+// YUY2 packs two pixels into each u32, as the bytes Y0 U Y1 V.
+fn yuy2_components(d: u32)->(u8, u8, u8, u8){
+    (d as u8, (d>>8) as u8, (d>>16) as u8, (d>>24) as u8)
+}
+
+// Converts the limited range BT.601 YUV that cameras deliver to RGB.
+fn bt601_to_rgb(y: u8, u: u8, v: u8)->(u8, u8, u8){
+    let c = y as i32 - 16;
+    let d = u as i32 - 128;
+    let e = v as i32 - 128;
+    let clip = | a: i32 | a.clamp(0, 255) as u8;
+    (
+        clip((298 * c + 409 * e + 128) >> 8),
+        clip((298 * c - 100 * d - 208 * e + 128) >> 8),
+        clip((298 * c + 516 * d + 128) >> 8),
+    )
+}
+
+fn rgb_to_bgra((r, g, b): (u8, u8, u8))->u32{
+    (255 << 24) | ((r as u32) << 16) | ((g as u32) << 8) | b as u32
+}
+
 pub enum VideoBufferRefData<'a>{
     U8(&'a [u8]),
     U32(&'a [u32])
@@ -335,3 +383,34 @@ impl std::fmt::Debug for VideoInputsEvent {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn yuy2(y1: u8, u: u8, y2: u8, v: u8)->u32{
+        u32::from_le_bytes([y1, u, y2, v])
+    }
+
+    #[test]
+    fn yuy2_to_bgra() {
+        // white and black share their chroma, then pure red twice
+        let input = [yuy2(235, 128, 16, 128), yuy2(81, 90, 81, 240)];
+        let mut bgra = Vec::new();
+        VideoPixelFormat::YUY2.buffer_to_bgra_32(&input, 4, 1, &mut bgra);
+        assert_eq!(bgra, vec![0xffffffff, 0xff000000, 0xffff0000, 0xffff0000]);
+    }
+
+    #[test]
+    fn yuy2_to_rgb_crops() {
+        let input = [
+            yuy2(235, 128, 235, 128), yuy2(16, 128, 16, 128),
+            yuy2(81, 90, 81, 240), yuy2(235, 128, 235, 128),
+        ];
+        let mut rgb = Vec::new();
+        VideoPixelFormat::YUY2.buffer_to_rgb_8(&input, &mut rgb, 4, 2, 2, 1, 2, 1);
+        assert_eq!(rgb, vec![255, 255, 255, 255, 255, 255]);
+        VideoPixelFormat::YUY2.buffer_to_rgb_8(&input, &mut rgb, 4, 2, 0, 1, 2, 1);
+        assert_eq!(rgb, vec![255, 0, 0, 255, 0, 0]);
+    }
+}
@@ -2,7 +2,7 @@ use crate::{
     os::OsWebSocket,
     cx_api::*,
     Cx,
-    studio::{AppToStudio,AppToStudioVec},
+    studio::{AppToStudio,AppToStudioVec,StudioToApp,StudioToAppVec},
     event::{HttpMethod,HttpRequest},
     makepad_micro_serde::*
 };
@@ -50,6 +50,7 @@ pub enum WebSocketMessage{
 pub (crate) static WEB_SOCKET_THREAD_SENDER: Mutex<Option<Sender<WebSocketThreadMsg>>> = Mutex::new(None);
 pub (crate) static WEB_SOCKET_ID: AtomicU64 = AtomicU64::new(0);
 pub (crate) static HAS_STUDIO_WEB_SOCKET: AtomicBool = AtomicBool::new(false);
+pub (crate) static STUDIO_PROFILER_ENABLED: AtomicBool = AtomicBool::new(false);
 
 impl Drop for WebSocket{
     fn drop(&mut self){
@@ -66,6 +67,26 @@ impl Cx{
        HAS_STUDIO_WEB_SOCKET.load(Ordering::SeqCst)
     }
     
+    /// Whether the Studio profiler is open, profile spans and GPU timings are only collected then.
+    pub(crate) fn has_studio_profiler()->bool{
+        Cx::has_studio_web_socket() && STUDIO_PROFILER_ENABLED.load(Ordering::Relaxed)
+    }
+    
+    pub(crate) fn handle_studio_messages(&mut self){
+        let Some(web_socket) = &mut self.studio_web_socket else {return};
+        while let Ok(message) = web_socket.try_recv(){
+            let WebSocketMessage::Binary(data) = message else {continue};
+            let Ok(messages) = StudioToAppVec::deserialize_bin(&data) else {continue};
+            for message in messages.0{
+                match message{
+                    StudioToApp::EnableProfiler => STUDIO_PROFILER_ENABLED.store(true, Ordering::Relaxed),
+                    StudioToApp::DisableProfiler => STUDIO_PROFILER_ENABLED.store(false, Ordering::Relaxed),
+                    StudioToApp::LiveChange{..} => ()
+                }
+            }
+        }
+    }
+    
     fn run_websocket_thread(&mut self){
         // lets create a thread
         let (rx_sender, rx_receiver) = channel();
@@ -2,6 +2,7 @@ use {
     crate::{
         makepad_live_id::*,
         makepad_micro_serde::{SerBin, DeBin, DeBinErr},
+        makepad_collab::text::{Change, Edit, Position},
     },
 };
 
@@ -36,10 +37,27 @@ pub enum FileRequest {
     /// If the client is the first participant for the file, this also causes the file to be opened
     /// on the server.
     OpenFile(String, u64),
-    /// Requests the collab server to apply the given delta to the given revision of the file with
-    /// the given id.
+    /// Requests the collab server to save the file with the given path and id. If the file has
+    /// participants, the server writes its own revision of the file instead of the given contents.
     SaveFile(String, String, u64),
-
+    /// Requests the collab server to apply the given delta to the given revision of the file with
+    /// the given path.
+    ApplyDelta(String, usize, Vec<Change>),
+    /// Requests the collab server to share the given selections, which are based on the given
+    /// revision of the file with the given path, with the other participants.
+    UpdateSelections(String, usize, Vec<SharedSelection>),
+    /// Requests the collab server to remove the client as a participant from the file with the
+    /// given path. If the client is the last participant for the file, this also causes the file
+    /// to be closed on the server.
+    CloseFile(String),
+    /// Requests the collab server to store the given serialized edit history for the file with the
+    /// given path in the journal directory of the workspace.
+    SaveJournal(String, Vec<u8>),
+    /// Requests the collab server to mark the start of an editing session. If the previous session
+    /// did not end cleanly, the journals it left behind are returned so they can be recovered.
+    BeginSession,
+    /// Requests the collab server to mark the end of the current editing session.
+    EndSession,
 }
 
 /// A type for representing either a response or a notification from the collab server.
@@ -58,10 +76,43 @@ pub enum FileResponse {
     LoadFileTree(Result<FileTreeData, FileError>),
     /// The result of requesting the collab server to add the client as a participant to the file
     /// with the given id.
-    OpenFile(Result<(String, String, u64), FileError>),
-    /// The result of requesting the collab server to apply a delta to a revision of the file with
-    /// the given id.
+    OpenFile(Result<OpenedFile, FileError>),
+    /// The result of requesting the collab server to save the file with the given id. Contains the
+    /// path, the old contents and the new contents of the file.
     SaveFile(Result<(String,String,String, u64), FileError>),
+    /// The result of requesting the collab server to apply a delta to a revision of the file with
+    /// the given path. Contains the new revision of the file.
+    ApplyDelta(Result<(String, usize), FileError>),
+    /// The result of requesting the collab server to share selections for the file with the given
+    /// path.
+    UpdateSelections(Result<String, FileError>),
+    /// The result of requesting the collab server to remove the client as a participant from the
+    /// file with the given path.
+    CloseFile(Result<String, FileError>),
+    /// The result of requesting the collab server to store the journal for the file with the given
+    /// path.
+    SaveJournal(Result<String, FileError>),
+    /// The result of requesting the collab server to begin a session. If the previous session
+    /// ended abnormally, contains the path and contents of every journal, together with the
+    /// contents of the file it belongs to on disk, if that still exists. Contains nothing otherwise.
+    BeginSession(Result<Vec<(String, Vec<u8>, Option<String>)>, FileError>),
+    /// Acknowledges that the collab server ended the current session.
+    EndSession,
+}
+
+/// A type for representing a file that the client was added to as a participant.
+#[derive(Clone, Debug, SerBin, DeBin)]
+pub struct OpenedFile {
+    /// The path of this file.
+    pub path: String,
+    /// The contents of this file at the given revision.
+    pub text: String,
+    /// The journal for this file, if one exists.
+    pub journal: Option<Vec<u8>>,
+    /// The revision of this file on the collab server.
+    pub revision: usize,
+    /// The id that was passed along with the request.
+    pub id: u64,
 }
 
 /// A type for representing data about a file tree.
@@ -97,9 +148,30 @@ pub struct DirectoryEntry {
 #[derive(Clone, Debug, SerBin, DeBin)]
 pub enum FileNotification {
     FileChangedOnDisk,
-    // Notifies the client that another client applied the given delta to the file with the given
-    // id. This is only sent for files for which the client is a participant.
-   // DeltaWasApplied(TextFileId),
+    /// Notifies the client that another client applied the given delta to the file with the given
+    /// path, as the next revision of that file. This is only sent for files for which the client
+    /// is a participant.
+    DeltaWasApplied(String, Vec<Change>),
+    /// Notifies the client that the participant with the given id changed its selections in the
+    /// file with the given path. The selections are based on the latest revision of that file.
+    SelectionsChanged(String, u64, Vec<SharedSelection>),
+}
+
+/// A type for representing a selection that a participant shares with the other participants of a
+/// file. Only the positions are shared, everything else about the cursor stays with the client.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, SerBin, DeBin)]
+pub struct SharedSelection {
+    pub anchor: Position,
+    pub cursor: Position,
+}
+
+impl SharedSelection {
+    pub fn apply_edit(self, edit: &Edit) -> Self {
+        Self {
+            anchor: self.anchor.apply_edit(edit),
+            cursor: self.cursor.apply_edit(edit),
+        }
+    }
 }
 
 /// A type for representing errors from the collab server.
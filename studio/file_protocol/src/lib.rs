@@ -3,3 +3,4 @@ pub mod file_protocol;
 pub use file_protocol::*;
 pub use makepad_live_id;
 pub use makepad_micro_serde;
+pub use makepad_collab;
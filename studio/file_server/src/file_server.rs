@@ -8,14 +8,21 @@ use {
             FileNotification,
             FileRequest,
             FileResponse,
+            OpenedFile,
+            SharedSelection,
+            makepad_collab::{
+                collab,
+                text::{Change, Drift, Edit, Position, Text},
+            },
         },
     },
     std::{
         cmp::Ordering,
+        collections::{HashMap, VecDeque},
         fmt,
         fs,
         path::{Path, PathBuf},
-        sync::{Arc, RwLock},
+        sync::{Arc, Mutex, RwLock},
     },
 };
 
@@ -24,6 +31,8 @@ pub struct FileServer {
     next_connection_id: usize,
     // State that is shared between every connection
     shared: Arc<RwLock<Shared >>,
+    // Notifications that still have to be sent, shared between every connection
+    outbox: Arc<Outbox>,
 }
 
 impl FileServer {
@@ -33,7 +42,10 @@ impl FileServer {
             next_connection_id: 0,
             shared: Arc::new(RwLock::new(Shared {
                 root_path: root_path.into(),
+                notification_senders: HashMap::new(),
+                collab_files: HashMap::new(),
             })),
+            outbox: Arc::new(Outbox::default()),
         }
     }
     
@@ -44,10 +56,11 @@ impl FileServer {
     pub fn connect(&mut self, notification_sender: Box<dyn NotificationSender>) -> FileServerConnection {
         let connection_id = ConnectionId(self.next_connection_id);
         self.next_connection_id += 1;
+        self.shared.write().unwrap().notification_senders.insert(connection_id, Mutex::new(notification_sender));
         FileServerConnection {
-            _connection_id:connection_id,
+            connection_id,
             shared: self.shared.clone(),
-            _notification_sender: notification_sender
+            outbox: self.outbox.clone(),
         }
     }
 }
@@ -55,11 +68,12 @@ impl FileServer {
 /// A connection to a collab server.
 pub struct FileServerConnection {
     // The id for this connection.
-    _connection_id: ConnectionId,
-    // State is shared between every connection.
+    connection_id: ConnectionId,
+    // State is shared between every connection. This includes the notification sender for this
+    // connection, so that other connections can notify it as well.
     shared: Arc<RwLock<Shared >>,
-    // Used to send notifications for this connection.
-    _notification_sender: Box<dyn NotificationSender>,
+    // Notifications that still have to be sent to the other connections.
+    outbox: Arc<Outbox>,
 }
 
 impl FileServerConnection {
@@ -74,6 +88,15 @@ impl FileServerConnection {
             FileRequest::LoadFileTree {with_data} => FileResponse::LoadFileTree(self.load_file_tree(with_data)),
             FileRequest::OpenFile(path,id) => FileResponse::OpenFile(self.open_file(path, id)),
             FileRequest::SaveFile(path, delta, id) => FileResponse::SaveFile(self.save_file(path, delta, id)),
+            FileRequest::ApplyDelta(path, revision, delta) => FileResponse::ApplyDelta(self.apply_delta(path, revision, delta)),
+            FileRequest::UpdateSelections(path, revision, selections) => FileResponse::UpdateSelections(self.update_selections(path, revision, selections)),
+            FileRequest::CloseFile(path) => FileResponse::CloseFile(self.close_file(path)),
+            FileRequest::SaveJournal(path, journal) => FileResponse::SaveJournal(self.save_journal(path, journal)),
+            FileRequest::BeginSession => FileResponse::BeginSession(self.begin_session()),
+            FileRequest::EndSession => {
+                self.end_session();
+                FileResponse::EndSession
+            }
         }
     }
     
@@ -164,26 +187,48 @@ impl FileServerConnection {
         path
     }
     
+    // Journals live in a hidden directory at the root of the workspace, so they are skipped when
+    // loading the file tree.
+    fn journal_dir(&self) -> PathBuf {
+        let mut path = self.shared.read().unwrap().root_path.clone();
+        path.push(".makepad");
+        path.push("journal");
+        path
+    }
+    
+    fn journal_path(&self, child_path:&str) -> PathBuf {
+        self.journal_dir().join(format!("{}.journal", escape_journal_name(child_path)))
+    }
+    
     // Handles an `OpenFile` request.
-    fn open_file(&self, child_path: String, id:u64) -> Result<(String, String, u64), FileError> {
+    fn open_file(&self, child_path: String, id:u64) -> Result<OpenedFile, FileError> {
         let path = self.make_full_path(&child_path);
+        let journal = fs::read(self.journal_path(&child_path)).ok();
         
-        let bytes = fs::read(&path).map_err(
-            | error | FileError::Unknown(error.to_string())
-        ) ?;
-        // Converts the file contents to a `Text`. This is necessarily a lossy conversion
-        // because `Text` assumes everything is UTF-8 encoded, and this isn't always the
-        // case for files on disk (is this a problem?)
-        /*let text: Text = Text::from_lines(String::from_utf8_lossy(&bytes)
-            .lines()
-            .map( | line | line.chars().collect::<Vec<_ >> ())
-            .collect::<Vec<_ >>());*/
-        
-        let text = String::from_utf8_lossy(&bytes);
-        Ok((child_path, text.to_string(), id))
+        let mut shared = self.shared.write().unwrap();
+        if !shared.collab_files.contains_key(&child_path) {
+            let bytes = fs::read(&path).map_err(
+                | error | FileError::Unknown(error.to_string())
+            ) ?;
+            // Converts the file contents to a `Text`. This is necessarily a lossy conversion
+            // because `Text` assumes everything is UTF-8 encoded, and this isn't always the
+            // case for files on disk (is this a problem?)
+            let text = Text::from(String::from_utf8_lossy(&bytes).as_ref());
+            shared.collab_files.insert(child_path.clone(), CollabFile::new(text));
+        }
+        let file = shared.collab_files.get_mut(&child_path).unwrap();
+        let revision = file.revision();
+        file.participants.insert(self.connection_id, revision);
+        Ok(OpenedFile {
+            text: file.text.to_string(),
+            path: child_path,
+            journal,
+            revision,
+            id,
+        })
     }
     
-    // Handles an `ApplyDelta` request.
+    // Handles a `SaveFile` request.
     fn save_file(
         &self,
         child_path: String,
@@ -192,6 +237,12 @@ impl FileServerConnection {
     ) -> Result<(String, String, String, u64), FileError> {
         let path = self.make_full_path(&child_path);
         
+        // If the file has participants, our own revision is the authoritative one.
+        let new_content = match self.shared.read().unwrap().collab_files.get(&child_path) {
+            Some(file) => file.text.to_string(),
+            None => new_content,
+        };
+        
         let old_content = String::from_utf8_lossy(&fs::read(&path).map_err(
             | error | FileError::Unknown(error.to_string())
         ) ?).to_string();
@@ -202,6 +253,221 @@ impl FileServerConnection {
         
         Ok((child_path, old_content, new_content, id))
     }
+    
+    // Handles an `ApplyDelta` request.
+    fn apply_delta(
+        &self,
+        child_path: String,
+        revision: usize,
+        delta: Vec<Change>
+    ) -> Result<(String, usize), FileError> {
+        let result = self.apply_delta_locked(child_path, revision, delta);
+        self.outbox.flush(&self.shared);
+        result
+    }
+    
+    fn apply_delta_locked(
+        &self,
+        child_path: String,
+        revision: usize,
+        delta: Vec<Change>
+    ) -> Result<(String, usize), FileError> {
+        let mut shared = self.shared.write().unwrap();
+        let file = shared.collab_files.get_mut(&child_path).ok_or_else(
+            || FileError::Unknown(format!("{} is not open", child_path))
+        ) ?;
+        if !file.participants.contains_key(&self.connection_id) {
+            return Err(FileError::Unknown(format!("not a participant of {}", child_path)));
+        }
+        
+        // Check the delta against the text it was made to, then transform it against every delta
+        // that was applied since, so that it can be applied to the latest revision.
+        let invalid_delta = || FileError::Unknown(format!("invalid delta for {}", child_path));
+        let mut text = file.text_at(revision) ?;
+        for change in &delta {
+            if !is_valid_change(&text, change) {
+                return Err(invalid_delta());
+            }
+            text.apply_change(change.clone());
+        }
+        let (delta, _) = collab::transform(delta, file.deltas_since(revision) ?).map_err( | _ | invalid_delta()) ?;
+        let mut text = file.text.clone();
+        for change in &delta {
+            if !is_valid_change(&text, change) {
+                return Err(invalid_delta());
+            }
+            text.apply_change(change.clone());
+        }
+        file.text = text;
+        file.deltas.push(delta.clone());
+        file.confirm_revision(self.connection_id, revision);
+        
+        for connection_id in file.participants.keys() {
+            if *connection_id != self.connection_id {
+                self.outbox.push(*connection_id, FileNotification::DeltaWasApplied(child_path.clone(), delta.clone()));
+            }
+        }
+        Ok((child_path, file.revision()))
+    }
+    
+    // Handles an `UpdateSelections` request.
+    fn update_selections(
+        &self,
+        child_path: String,
+        revision: usize,
+        selections: Vec<SharedSelection>
+    ) -> Result<String, FileError> {
+        let result = self.update_selections_locked(child_path, revision, selections);
+        self.outbox.flush(&self.shared);
+        result
+    }
+    
+    fn update_selections_locked(
+        &self,
+        child_path: String,
+        revision: usize,
+        selections: Vec<SharedSelection>
+    ) -> Result<String, FileError> {
+        let mut shared = self.shared.write().unwrap();
+        let file = shared.collab_files.get_mut(&child_path).ok_or_else(
+            || FileError::Unknown(format!("{} is not open", child_path))
+        ) ?;
+        if !file.participants.contains_key(&self.connection_id) {
+            return Err(FileError::Unknown(format!("not a participant of {}", child_path)));
+        }
+        
+        // Bring the selections up to date with the latest revision.
+        let mut selections = selections;
+        for change in file.deltas_since(revision) ? {
+            let edit = Edit {change, drift: Drift::Before};
+            for selection in &mut selections {
+                *selection = selection.apply_edit(&edit);
+            }
+        }
+        if !selections.iter().all( | selection | {
+            is_valid_position(&file.text, selection.cursor)
+                && is_valid_position(&file.text, selection.anchor)
+        }) {
+            return Err(FileError::Unknown(format!("invalid selections for {}", child_path)));
+        }
+        file.confirm_revision(self.connection_id, revision);
+        
+        for connection_id in file.participants.keys() {
+            if *connection_id != self.connection_id {
+                self.outbox.push(*connection_id, FileNotification::SelectionsChanged(
+                    child_path.clone(),
+                    self.connection_id.0 as u64,
+                    selections.clone()
+                ));
+            }
+        }
+        Ok(child_path)
+    }
+    
+    // Handles a `CloseFile` request.
+    fn close_file(&self, child_path: String) -> Result<String, FileError> {
+        let removed = self.shared.write().unwrap().remove_participant(self.connection_id, &child_path, &self.outbox);
+        self.outbox.flush(&self.shared);
+        if !removed {
+            return Err(FileError::Unknown(format!("not a participant of {}", child_path)));
+        }
+        Ok(child_path)
+    }
+    
+    // Handles a `SaveJournal` request.
+    fn save_journal(&self, child_path: String, journal: Vec<u8>) -> Result<String, FileError> {
+        fs::create_dir_all(self.journal_dir()).map_err(
+            | error | FileError::Unknown(error.to_string())
+        ) ?;
+        fs::write(self.journal_path(&child_path), journal).map_err(
+            | error | FileError::Unknown(error.to_string())
+        ) ?;
+        Ok(child_path)
+    }
+    
+    // Handles a `BeginSession` request. A session lock is kept in the journal directory for as
+    // long as the session lasts, so if it still exists here the previous session crashed.
+    fn begin_session(&self) -> Result<Vec<(String, Vec<u8>, Option<String>)>, FileError> {
+        let dir = self.journal_dir();
+        let lock_path = dir.join("session.lock");
+        let crashed = lock_path.exists();
+        fs::create_dir_all(&dir).map_err(
+            | error | FileError::Unknown(error.to_string())
+        ) ?;
+        fs::write(&lock_path, b"").map_err(
+            | error | FileError::Unknown(error.to_string())
+        ) ?;
+        
+        let mut journals = Vec::new();
+        if crashed {
+            for entry in fs::read_dir(&dir).map_err( | error | FileError::Unknown(error.to_string())) ? {
+                let entry = entry.map_err( | error | FileError::Unknown(error.to_string())) ?;
+                let file_name = entry.file_name();
+                let Some(name) = file_name.to_str().and_then( | name | name.strip_suffix(".journal")) else {
+                    continue
+                };
+                if let Ok(journal) = fs::read(entry.path()) {
+                    // The client needs the file as it is on disk to tell whether the journal still
+                    // applies to it.
+                    let child_path = unescape_journal_name(name);
+                    let text = fs::read(self.make_full_path(&child_path)).ok().map( | bytes | {
+                        String::from_utf8_lossy(&bytes).into_owned()
+                    });
+                    journals.push((child_path, journal, text));
+                }
+            }
+        }
+        Ok(journals)
+    }
+    
+    // Handles an `EndSession` request.
+    fn end_session(&self) {
+        let _ = fs::remove_file(self.journal_dir().join("session.lock"));
+    }
+}
+
+impl Drop for FileServerConnection {
+    fn drop(&mut self) {
+        let mut shared = self.shared.write().unwrap();
+        shared.notification_senders.remove(&self.connection_id);
+        let paths: Vec<String> = shared.collab_files.keys().cloned().collect();
+        for path in paths {
+            shared.remove_participant(self.connection_id, &path, &self.outbox);
+        }
+        drop(shared);
+        self.outbox.flush(&self.shared);
+    }
+}
+
+fn is_valid_position(text: &Text, position: Position) -> bool {
+    text.as_lines().get(position.line_index).is_some_and( | line | {
+        line.is_char_boundary(position.byte_index)
+    })
+}
+
+// Deltas come from the client, so they are checked before they are applied to make sure they
+// cannot take down the server.
+fn is_valid_change(text: &Text, change: &Change) -> bool {
+    match *change {
+        Change::Insert(position, ref inserted_text) => {
+            !inserted_text.as_lines().is_empty() && is_valid_position(text, position)
+        }
+        Change::Delete(start, length) => {
+            is_valid_position(text, start) && start.checked_add(length).is_some_and( | end | {
+                is_valid_position(text, end)
+            })
+        }
+    }
+}
+
+// Journals are stored in a single flat directory, so the path separators in the path of the file
+// they belong to are escaped.
+fn escape_journal_name(child_path: &str) -> String {
+    child_path.replace('%', "%25").replace('/', "%2F").replace('\\', "%5C")
+}
+
+fn unescape_journal_name(name: &str) -> String {
+    name.replace("%5C", "\\").replace("%2F", "/").replace("%25", "%")
 }
 
 /// A trait for sending notifications over a connection.
@@ -239,9 +505,362 @@ impl fmt::Debug for dyn NotificationSender {
 #[derive(Debug)]
 struct Shared {
     root_path: PathBuf,
+    // The notification sender for each connection. Notification senders are only `Send`, so they
+    // are wrapped in a mutex to keep `Shared` shareable between connection threads.
+    notification_senders: HashMap<ConnectionId, Mutex<Box<dyn NotificationSender>>>,
+    // Every file that has at least one participant, by path.
+    collab_files: HashMap<String, CollabFile>,
+}
+
+impl Shared {
+    // Removes the given connection as a participant from the file with the given path, and closes
+    // the file if it has no participants left. Returns `false` if the connection was not a
+    // participant.
+    fn remove_participant(&mut self, connection_id: ConnectionId, child_path: &str, outbox: &Outbox) -> bool {
+        let Some(file) = self.collab_files.get_mut(child_path) else {
+            return false
+        };
+        if file.participants.remove(&connection_id).is_none() {
+            return false
+        }
+        if file.participants.is_empty() {
+            self.collab_files.remove(child_path);
+            return true
+        }
+        file.prune_deltas();
+        // Clear the selections of the removed participant for everyone else.
+        for participant_id in file.participants.keys() {
+            outbox.push(*participant_id, FileNotification::SelectionsChanged(
+                child_path.to_string(),
+                connection_id.0 as u64,
+                Vec::new()
+            ));
+        }
+        true
+    }
+}
+
+// Notifications are queued while the shared state is locked, so that every connection receives
+// them in the order in which the changes were made. They are only sent after the lock is released
+// though, so that a slow connection cannot stall the requests of every other connection.
+#[derive(Default)]
+struct Outbox {
+    queue: Mutex<VecDeque<(ConnectionId, FileNotification)>>,
+    // Held by whichever thread is sending, so that notifications are sent one at a time, in order.
+    sending: Mutex<()>,
+}
+
+impl Outbox {
+    fn push(&self, connection_id: ConnectionId, notification: FileNotification) {
+        self.queue.lock().unwrap().push_back((connection_id, notification));
+    }
+    
+    // Sends every queued notification. If another thread is already sending, that thread sends
+    // ours as well.
+    fn flush(&self, shared: &RwLock<Shared>) {
+        loop {
+            let Ok(_sending) = self.sending.try_lock() else {
+                return
+            };
+            loop {
+                let Some((connection_id, notification)) = self.queue.lock().unwrap().pop_front() else {
+                    break
+                };
+                // Senders are cloned out of the shared state so it is not locked while sending.
+                let sender = shared.read().unwrap().notification_senders.get(&connection_id).map( | sender | {
+                    sender.lock().unwrap().clone()
+                });
+                if let Some(sender) = sender {
+                    sender.send_notification(notification);
+                }
+            }
+            drop(_sending);
+            // A notification may have been queued after we stopped looking, but before we released
+            // the lock, in which case its thread gave up on sending it.
+            if self.queue.lock().unwrap().is_empty() {
+                return
+            }
+        }
+    }
+}
+
+// A file for which at least one connection is a participant.
+#[derive(Debug)]
+struct CollabFile {
+    // The text of the latest revision of this file.
+    text: Text,
+    // The text of `base_revision`, to check incoming deltas against the text they were made to.
+    base_text: Text,
+    // The revision to which the first delta in `deltas` applies.
+    base_revision: usize,
+    // The deltas from `base_revision` up to the latest revision.
+    deltas: Vec<Vec<Change>>,
+    // The latest revision that each participant has confirmed to have seen.
+    participants: HashMap<ConnectionId, usize>,
+}
+
+impl CollabFile {
+    fn new(text: Text) -> Self {
+        Self {
+            base_text: text.clone(),
+            text,
+            base_revision: 0,
+            deltas: Vec::new(),
+            participants: HashMap::new(),
+        }
+    }
+    
+    fn revision(&self) -> usize {
+        self.base_revision + self.deltas.len()
+    }
+    
+    // Returns every change that was applied since the given revision.
+    fn deltas_since(&self, revision: usize) -> Result<Vec<Change>, FileError> {
+        if revision < self.base_revision || revision > self.revision() {
+            return Err(FileError::Unknown(format!("unknown revision {}", revision)));
+        }
+        Ok(self.deltas[revision - self.base_revision..].iter().flatten().cloned().collect())
+    }
+    
+    // Returns the text of the given revision.
+    fn text_at(&self, revision: usize) -> Result<Text, FileError> {
+        if revision < self.base_revision || revision > self.revision() {
+            return Err(FileError::Unknown(format!("unknown revision {}", revision)));
+        }
+        let mut text = self.base_text.clone();
+        for change in self.deltas[..revision - self.base_revision].iter().flatten() {
+            text.apply_change(change.clone());
+        }
+        Ok(text)
+    }
+    
+    fn confirm_revision(&mut self, connection_id: ConnectionId, revision: usize) {
+        if let Some(confirmed_revision) = self.participants.get_mut(&connection_id) {
+            *confirmed_revision = revision.max(*confirmed_revision);
+        }
+        self.prune_deltas();
+    }
+    
+    // Removes the deltas that every participant has seen.
+    fn prune_deltas(&mut self) {
+        let Some(&revision) = self.participants.values().min() else {
+            return
+        };
+        if revision > self.base_revision {
+            for change in self.deltas.drain(..revision - self.base_revision).flatten() {
+                self.base_text.apply_change(change);
+            }
+            self.base_revision = revision;
+        }
+    }
 }
 
 /// An identifier for a connection.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 struct ConnectionId(usize);
 
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::makepad_file_protocol::{
+            FileClientMessage,
+            makepad_collab::{collab::CollabState, text::Length},
+        },
+    };
+    
+    // A client that edits `test.txt` over its own connection to a shared server. Responses and
+    // notifications end up in the same queue, in the order in which a real client would receive
+    // them.
+    struct Client {
+        connection: FileServerConnection,
+        messages: Arc<Mutex<VecDeque<FileClientMessage >>>,
+        text: Text,
+        state: CollabState,
+    }
+    
+    impl Client {
+        fn connect(server: &mut FileServer) -> Self {
+            let messages = Arc::new(Mutex::new(VecDeque::new()));
+            let connection = server.connect(Box::new({
+                let messages = messages.clone();
+                move | notification | messages.lock().unwrap().push_back(FileClientMessage::Notification(notification))
+            }));
+            let FileResponse::OpenFile(Ok(file)) = connection.handle_request(FileRequest::OpenFile("test.txt".to_string(), 0)) else {
+                panic!("failed to open test.txt")
+            };
+            Self {
+                connection,
+                messages,
+                text: Text::from(file.text.as_str()),
+                state: CollabState::new(file.revision),
+            }
+        }
+        
+        fn send(&self, request: FileRequest) {
+            let response = self.connection.handle_request(request);
+            self.messages.lock().unwrap().push_back(FileClientMessage::Response(response));
+        }
+        
+        fn edit(&mut self, change: Change) {
+            self.text.apply_change(change.clone());
+            if let Some(delta) = self.state.apply_local(vec![change]) {
+                self.send(FileRequest::ApplyDelta("test.txt".to_string(), self.state.revision(), delta));
+            }
+        }
+        
+        // Handles every queued message.
+        fn sync(&mut self) {
+            loop {
+                let Some(message) = self.messages.lock().unwrap().pop_front() else {
+                    return
+                };
+                match message {
+                    FileClientMessage::Response(FileResponse::ApplyDelta(response)) => {
+                        let (_, revision) = response.unwrap();
+                        if let Some(delta) = self.state.acknowledge(revision) {
+                            self.send(FileRequest::ApplyDelta("test.txt".to_string(), revision, delta));
+                        }
+                    }
+                    FileClientMessage::Notification(FileNotification::DeltaWasApplied(_, delta)) => {
+                        for change in self.state.apply_remote(delta).unwrap() {
+                            self.text.apply_change(change);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    
+    // Removes the root directory of a test server when the test ends.
+    struct TempDir(PathBuf);
+    
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+    
+    fn server(name: &str, text: &str) -> (FileServer, TempDir) {
+        let root_path = std::env::temp_dir().join(format!("makepad-file-server-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&root_path).unwrap();
+        fs::write(root_path.join("test.txt"), text).unwrap();
+        (FileServer::new(root_path.clone()), TempDir(root_path))
+    }
+    
+    fn insert(byte_index: usize, text: &str) -> Change {
+        Change::Insert(Position {line_index: 0, byte_index}, Text::from(text))
+    }
+    
+    fn delete(byte_index: usize, byte_count: usize) -> Change {
+        Change::Delete(Position {line_index: 0, byte_index}, Length {line_count: 0, byte_count})
+    }
+    
+    fn server_text(server: &FileServer) -> String {
+        server.shared.read().unwrap().collab_files["test.txt"].text.to_string()
+    }
+    
+    #[test]
+    fn concurrent_inserts_at_same_position() {
+        let (mut server, _dir) = server("inserts", "abc");
+        let mut a = Client::connect(&mut server);
+        let mut b = Client::connect(&mut server);
+        // Both edits are based on revision 0, so the server has to transform the second one.
+        a.edit(insert(1, "X"));
+        b.edit(insert(1, "Y"));
+        a.sync();
+        b.sync();
+        
+        assert_eq!(server_text(&server), "aXYbc");
+        assert_eq!(a.text.to_string(), "aXYbc");
+        assert_eq!(b.text.to_string(), "aXYbc");
+        assert!(a.state.is_synchronized() && b.state.is_synchronized());
+        assert_eq!(a.state.revision(), 2);
+        assert_eq!(b.state.revision(), 2);
+    }
+    
+    #[test]
+    fn concurrent_delete_and_insert() {
+        let (mut server, _dir) = server("delete_insert", "abcdef");
+        let mut a = Client::connect(&mut server);
+        let mut b = Client::connect(&mut server);
+        // `a` deletes "bcde", while `b` inserts into the middle of that range.
+        a.edit(delete(1, 4));
+        b.edit(insert(3, "XY"));
+        // `b` edits again before it has seen the delete, so this change is buffered and sent
+        // when the first one is acknowledged.
+        b.edit(insert(0, ">"));
+        a.sync();
+        b.sync();
+        a.sync();
+        
+        assert_eq!(server_text(&server), ">aXYf");
+        assert_eq!(a.text.to_string(), ">aXYf");
+        assert_eq!(b.text.to_string(), ">aXYf");
+        assert!(a.state.is_synchronized() && b.state.is_synchronized());
+    }
+    
+    #[test]
+    fn stale_revision() {
+        let (mut server, _dir) = server("stale", "abc");
+        let mut a = Client::connect(&mut server);
+        let mut b = Client::connect(&mut server);
+        a.edit(insert(0, "X"));
+        a.sync();
+        b.sync();
+        b.edit(insert(0, "Y"));
+        b.sync();
+        a.sync();
+        // Sharing selections confirms that a participant has seen a revision, so once both have
+        // done so, the deltas up to revision 2 are gone.
+        a.send(FileRequest::UpdateSelections("test.txt".to_string(), 2, Vec::new()));
+        b.send(FileRequest::UpdateSelections("test.txt".to_string(), 2, Vec::new()));
+        a.sync();
+        b.sync();
+        assert_eq!(
+            server.shared.read().unwrap().collab_files["test.txt"].base_revision,
+            2
+        );
+        
+        let response = a.connection.handle_request(FileRequest::ApplyDelta("test.txt".to_string(), 0, vec![insert(0, "Z")]));
+        assert!(matches!(response, FileResponse::ApplyDelta(Err(_))));
+        let response = a.connection.handle_request(FileRequest::ApplyDelta("test.txt".to_string(), 3, vec![insert(0, "Z")]));
+        assert!(matches!(response, FileResponse::ApplyDelta(Err(_))));
+        // A rejected delta is neither applied nor sent to the other participants.
+        assert_eq!(server_text(&server), "YXabc");
+        assert!(b.messages.lock().unwrap().is_empty());
+    }
+    
+    #[test]
+    fn malformed_deltas() {
+        let (mut server, _dir) = server("malformed", "abc\ndef");
+        let mut a = Client::connect(&mut server);
+        let mut b = Client::connect(&mut server);
+        a.edit(insert(0, "X"));
+        a.sync();
+        let far = Position {line_index: 0, byte_index: usize::MAX - 1};
+        let deltas = [
+            // Would overflow when transformed against the insert of `a`.
+            vec![Change::Delete(far, Length {line_count: 0, byte_count: 1})],
+            vec![Change::Insert(far, Text::from("Z"))],
+            vec![Change::Delete(Position::zero(), Length {line_count: usize::MAX, byte_count: 0})],
+            // Fits the latest text, but not the text of revision 0 that it claims to be based on.
+            vec![delete(3, 1)],
+            // The second change runs past the text left by the first.
+            vec![delete(0, 3), delete(1, 1)],
+        ];
+        for delta in deltas {
+            let response = b.connection.handle_request(FileRequest::ApplyDelta("test.txt".to_string(), 0, delta));
+            assert!(matches!(response, FileResponse::ApplyDelta(Err(_))));
+        }
+        // The server still works for everyone.
+        b.sync();
+        b.edit(insert(4, "Y"));
+        b.sync();
+        a.sync();
+        assert_eq!(server_text(&server), "XabcY\ndef");
+        assert_eq!(a.text.to_string(), "XabcY\ndef");
+    }
+}
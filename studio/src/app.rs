@@ -13,7 +13,11 @@ use crate::{
             BuildManager,
             BuildManagerAction
         },
-    }
+    },
+    lsp::lsp_manager::{
+        LspManager,
+        LspAction
+    },
 }; 
 use std::fs::File;
 use std::io::Write;
@@ -31,6 +35,7 @@ live_design!{
 pub struct App {
     #[live] ui: WidgetRef,
     #[rust] data: AppData,
+    #[rust] editor_files_to_open: Vec<(LiveId, String)>,
 }
 
 impl LiveRegister for App{
@@ -57,16 +62,38 @@ impl App {
             let dock = self.ui.dock(id!(dock));            
             let tab_id = dock.unique_tab_id(file_id.0.0);
             self.data.file_system.request_open_file(tab_id, file_id);
-            dock.create_and_select_tab(cx, live_id!(edit_tabs), tab_id, live_id!(CodeEditor), "".to_string(), TabClosable::Yes);
+            dock.create_and_select_tab(cx, live_id!(edit_tabs), tab_id, live_id!(StudioEditor), "".to_string(), TabClosable::Yes);
             self.data.file_system.ensure_unique_tab_names(cx, &dock)
         }
     }
+    
+    // restores the dock layout of the last session, the files of its editor tabs are opened
+    // once the file tree has loaded
+    fn load_state(&mut self, cx: &mut Cx) {
+        let Ok(saved) = std::fs::read_to_string("makepad_state.ron") else {
+            return
+        };
+        let state = match PersistentState::deserialize_ron(&saved) {
+            Ok(state) => state,
+            Err(err) => {
+                log!("Cannot read makepad_state.ron: {:?}", err);
+                return
+            }
+        };
+        let dock = self.ui.dock(id!(dock));
+        if let Err(err) = dock.from_store_items(cx, &state.dock_items) {
+            log!("Cannot restore the dock layout: {:?}", err);
+            return
+        }
+        self.editor_files_to_open = state.editor_files.into_iter().map( | file | (file.tab_id.0, file.path)).collect();
+    }
 }
 
 #[derive(Default)]
 pub struct AppData{
     pub build_manager: BuildManager,
     pub file_system: FileSystem,
+    pub lsp_manager: LspManager,
 }
 
 // all global app commands coming in from keybindings, and UI components
@@ -99,6 +126,8 @@ impl MatchEvent for App{
         self.data.build_manager.init(cx, &root_path);
         self.data.build_manager.discover_external_ip(cx);
         self.data.build_manager.start_http_server();
+        self.data.lsp_manager.init(cx, &root_path);
+        self.load_state(cx);
     }
     
     fn handle_action(&mut self, cx:&mut Cx, action:&Action){
@@ -171,10 +200,33 @@ impl MatchEvent for App{
             }
             BuildManagerAction::None=>()
         }
+        
+        match action.cast(){
+            LspAction::Hover{tab_id, position, text} => {
+                if let Some(mut editor) = dock.item(tab_id).as_studio_editor().borrow_mut() {
+                    editor.editor.show_hover(cx, position, text);
+                }
+            }
+            LspAction::Completions{tab_id, items} => {
+                if let Some(mut editor) = dock.item(tab_id).as_studio_editor().borrow_mut() {
+                    if let Some(session) = self.data.file_system.get_session_mut(tab_id) {
+                        editor.editor.show_completions(cx, session, items);
+                    }
+                }
+            }
+            LspAction::None=>()
+        }
                 
         match action.cast(){
             FileSystemAction::TreeLoaded => {
                 file_tree.redraw(cx);
+                for (tab_id, path) in std::mem::take(&mut self.editor_files_to_open) {
+                    match self.data.file_system.path_to_file_node_id(&path) {
+                        Some(file_id) => self.data.file_system.request_open_file(tab_id, file_id),
+                        None => dock.close_tab(cx, tab_id)
+                    }
+                }
+                self.data.file_system.ensure_unique_tab_names(cx, &dock);
                 //self.open_code_file_by_path(cx, "examples/slides/src/app.rs");
             }
             FileSystemAction::RecompileNeeded => {
@@ -185,6 +237,25 @@ impl MatchEvent for App{
                 self.data.build_manager.clear_log(cx, &dock, &mut self.data.file_system);
                 log_list.redraw(cx);
             }
+            FileSystemAction::RecoveryAvailable => {
+                let paths = self.data.file_system.recoverable_paths();
+                let conflicts = self.data.file_system.conflicting_paths();
+                let mut message = "Makepad Studio did not shut down properly.".to_string();
+                if !paths.is_empty() {
+                    message.push_str(&format!(" Recover unsaved changes to {}?", paths.join(", ")));
+                }
+                if !conflicts.is_empty() {
+                    message.push_str(&format!(
+                        " Unsaved changes to {} cannot be recovered because the files changed on disk.",
+                        conflicts.join(", ")
+                    ));
+                }
+                let recovery_bar = self.ui.view(id!(recovery_bar));
+                recovery_bar.label(id!(message)).set_text(&message);
+                recovery_bar.view(id!(recover_view)).set_visible(!paths.is_empty());
+                recovery_bar.set_visible(true);
+                self.ui.redraw(cx);
+            }
             FileSystemAction::None=>()
         }
                 
@@ -209,6 +280,15 @@ impl MatchEvent for App{
                     // lets write the file
                     self.data.file_system.request_save_file(action.path.get(0))
                 }
+                CodeEditorAction::HoverRequested(position) => {
+                    self.data.lsp_manager.request_hover(&mut self.data.file_system, action.path.get(0), position)
+                }
+                CodeEditorAction::CompletionRequested(position) => {
+                    self.data.lsp_manager.request_completion(&mut self.data.file_system, action.path.get(0), position)
+                }
+                CodeEditorAction::DefinitionRequested(position) => {
+                    self.data.lsp_manager.request_definition(&mut self.data.file_system, action.path.get(0), position)
+                }
                 CodeEditorAction::None=>{}
             }
             
@@ -291,6 +371,18 @@ impl MatchEvent for App{
         for action in actions{
             self.handle_action(cx, action);
         }
+        if self.ui.button(id!(recovery_bar.recover)).clicked(&actions) {
+            for path in self.data.file_system.recover_unsaved() {
+                self.open_code_file_by_path(cx, &path);
+            }
+            self.ui.view(id!(recovery_bar)).set_visible(false);
+            self.ui.redraw(cx);
+        }
+        if self.ui.button(id!(recovery_bar.discard)).clicked(&actions) {
+            self.data.file_system.discard_unsaved();
+            self.ui.view(id!(recovery_bar)).set_visible(false);
+            self.ui.redraw(cx);
+        }
         if let Some(file_id) = file_tree.should_file_start_drag(&actions) {
             let path = self.data.file_system.file_node_path(file_id);
             file_tree.file_start_drag(cx, file_id, DragItem::FilePath {
@@ -313,6 +405,8 @@ impl MatchEvent for App{
     
     fn handle_shutdown(&mut self, _cx:&mut Cx){
         self.data.build_manager.clear_active_builds();
+        self.data.file_system.end_session();
+        self.data.lsp_manager.shutdown();
     }
 }
 
@@ -324,6 +418,7 @@ impl AppMain for App {
         
         self.data.file_system.handle_event(cx, event, &self.ui);
         self.data.build_manager.handle_event(cx, event, &mut self.data.file_system); 
+        self.data.lsp_manager.handle_event(cx, event, &mut self.data.file_system);
 
         // process events on all run_views
         let dock = self.ui.dock(id!(dock));
@@ -333,6 +428,11 @@ impl AppMain for App {
                     run_view.pump_event_loop(cx, event, *id, &mut self.data.build_manager);
                 }
             }
+            // the apps only time their spans while the profiler tab is showing
+            if let Event::Draw(_) = event {
+                let profiling = dock.visible_items().any(|(id, _)| id == live_id!(profiler));
+                self.data.build_manager.set_profiling(profiling);
+            }
         }
         
         if let Some(mut dock_items) = dock.needs_save(){
@@ -344,8 +444,19 @@ impl AppMain for App {
                 }
                 true 
             }); 
+            let editor_files = dock_items.iter().filter_map( | di | match di {
+                DockItemStore::Tab {id, ..} => {
+                    let file_id = self.data.file_system.tab_id_to_file_node_id.get(&id.0)?;
+                    Some(EditorFileStore {
+                        tab_id: id.clone(),
+                        path: self.data.file_system.file_node_path(*file_id)
+                    })
+                }
+                _ => None
+            }).collect();
             let state = PersistentState{
-                dock_items
+                dock_items,
+                editor_files
             };
             // alright lets save it to disk
             let saved = state.serialize_ron();
@@ -357,5 +468,12 @@ impl AppMain for App {
 
 #[derive(Clone, Debug, SerRon, DeRon)]
 struct PersistentState{
-    dock_items: Vec<DockItemStore>
+    dock_items: Vec<DockItemStore>,
+    editor_files: Vec<EditorFileStore>
+}
+
+#[derive(Clone, Debug, SerRon, DeRon)]
+struct EditorFileStore{
+    tab_id: LiveIdStore,
+    path: String
 }
@@ -82,7 +82,29 @@ live_design!{
                 
             line = Line,
         }
-        body = {dock = <Dock> {
+        body = {
+            flow: Down
+            recovery_bar = <RectView> {
+                visible: false
+                width: Fill,
+                height: Fit
+                flow: Right
+                align: {y: 0.5}
+                padding: {left: 10, top: 5, right: 10, bottom: 5}
+                spacing: 10
+                draw_bg: {color: #x5a3a1c}
+                message = <Label> {
+                    width: Fill
+                    text: ""
+                }
+                recover_view = <View> {
+                    width: Fit,
+                    height: Fit
+                    recover = <Button> {text: "Recover"}
+                }
+                discard = <Button> {text: "Discard"}
+            }
+            dock = <Dock> {
             height: Fill,
             width: Fill
                 
@@ -9,7 +9,7 @@ use {
             HostToStdin,
             StdinToHost,
         },
-        makepad_platform::studio::{AppToStudioVec,AppToStudio,EventSample, GPUSample},
+        makepad_platform::studio::{AppToStudioVec,AppToStudio,StudioToApp,StudioToAppVec,EventSample, GPUSample, SpanSample, GPUDrawCallSample, FrameSample},
         makepad_platform::log::LogLevel,
         build_manager::{
             build_protocol::*,
@@ -28,7 +28,7 @@ use {
         path::Path,
         fs::File,
     },
-    std::sync::mpsc,
+    std::sync::{mpsc, Arc, Mutex},
     std::thread,
     std::time,
     std::net::{UdpSocket, SocketAddr},
@@ -71,10 +71,41 @@ impl ActiveBuilds {
     }
 }
 
-#[derive(Default)]
+/// The samples of one running build, also what a saved profiler capture holds.
+#[derive(Default, Clone, Debug, SerBin, DeBin)]
 pub struct ProfileSampleStore{
     pub event: Vec<EventSample>,
     pub gpu: Vec<GPUSample>,
+    pub spans: Vec<SpanSample>,
+    pub draw_calls: Vec<GPUDrawCallSample>,
+    pub frames: Vec<FrameSample>,
+}
+
+impl ProfileSampleStore{
+    /// Sorts the spans appended after the first `sorted` ones into place. Spans arrive as
+    /// they close, inner ones before their parents, and are kept ordered by start with
+    /// parents first.
+    pub fn sort_spans_from(&mut self, sorted: usize){
+        let Some(first_start) = self.spans[sorted..].iter().map(|v| v.start).min_by(f64::total_cmp) else{
+            return
+        };
+        let from = self.spans[..sorted].partition_point(|v| v.start < first_start);
+        self.spans[from..].sort_by(|a, b| a.start.total_cmp(&b.start).then(a.depth.cmp(&b.depth)));
+    }
+}
+
+/// The web sockets of the running apps, and whether the profiler is open for them.
+#[derive(Default)]
+struct AppSockets{
+    senders: HashMap<LiveId, mpsc::Sender<Vec<u8>>>,
+    profiling: bool,
+}
+
+impl AppSockets{
+    fn profiler_message(&self)->Vec<u8>{
+        let msg = if self.profiling{StudioToApp::EnableProfiler} else {StudioToApp::DisableProfiler};
+        StudioToAppVec(vec![msg]).serialize_bin()
+    }
 }
 
 #[derive(Default)]
@@ -90,6 +121,7 @@ pub struct BuildManager {
     pub active: ActiveBuilds,
     pub studio_http: String,
     pub recv_studio_msg: ToUIReceiver<(LiveId,AppToStudioVec)>,
+    app_sockets: Arc<Mutex<AppSockets>>,
     pub recv_external_ip: ToUIReceiver<SocketAddr>,
     pub send_file_change: FromUISender<LiveFileChange>
 }
@@ -212,6 +244,19 @@ impl BuildManager {
         let _ = self.send_file_change.send(live_file_change);
     }
     
+    /// Tells the running apps to collect profile spans only while the profiler is visible.
+    pub fn set_profiling(&mut self, profiling: bool) {
+        let mut app_sockets = self.app_sockets.lock().unwrap();
+        if app_sockets.profiling == profiling {
+            return
+        }
+        app_sockets.profiling = profiling;
+        let data = app_sockets.profiler_message();
+        for sender in app_sockets.senders.values() {
+            let _ = sender.send(data.clone());
+        }
+    }
+    
     pub fn handle_event(&mut self, cx: &mut Cx, event: &Event, file_system: &mut FileSystem) {
 
         if let Event::Signal = event {
@@ -223,6 +268,7 @@ impl BuildManager {
                 self.studio_http = format!("http://{}/$studio_web_socket", addr);
             }
             
+            let mut spans_sorted = HashMap::new();
             while let Ok((build_id, msgs)) = self.recv_studio_msg.try_recv() {
                 for msg in msgs.0{
                     match msg{
@@ -280,9 +326,30 @@ impl BuildManager {
                             values.gpu.push(sample);
                             cx.action(AppAction::RedrawProfiler)
                         }
+                        AppToStudio::SpanSample(sample)=>{
+                            let values = self.profile.entry(build_id).or_default();
+                            spans_sorted.entry(build_id).or_insert(values.spans.len());
+                            values.spans.push(sample);
+                            cx.action(AppAction::RedrawProfiler)
+                        }
+                        AppToStudio::GPUDrawCallSample(sample)=>{
+                            let values = self.profile.entry(build_id).or_default();
+                            values.draw_calls.push(sample);
+                            cx.action(AppAction::RedrawProfiler)
+                        }
+                        AppToStudio::FrameSample(sample)=>{
+                            let values = self.profile.entry(build_id).or_default();
+                            values.frames.push(sample);
+                            cx.action(AppAction::RedrawProfiler)
+                        }
                     }
                 }
             }
+            for (build_id, sorted) in spans_sorted {
+                if let Some(values) = self.profile.get_mut(&build_id) {
+                    values.sort_spans_from(sorted);
+                }
+            }
             
             while let Ok(wrap) = self.clients[0].msg_receiver.try_recv(){
                 match wrap.message {
@@ -380,6 +447,7 @@ impl BuildManager {
             }
         });
         let studio_sender = self.recv_studio_msg.sender();
+        let app_sockets = self.app_sockets.clone();
         std::thread::spawn(move || {
             // TODO fix this proper:
             let makepad_path = "./".to_string();
@@ -402,15 +470,22 @@ impl BuildManager {
             while let Ok(message) = rx_request.recv() {
                 // only store last change, fix later
                 match message {
-                    HttpServerRequest::ConnectWebSocket {web_socket_id, response_sender: _,headers} => {
+                    HttpServerRequest::ConnectWebSocket {web_socket_id, response_sender, headers} => {
                         if let Some(id) = headers.path.rsplit("/").next(){
                             if let Ok(id) = id.parse::<u64>(){
                                 socket_id_to_build_id.insert(web_socket_id, LiveId(id));
+                                let mut app_sockets = app_sockets.lock().unwrap();
+                                if app_sockets.profiling {
+                                    let _ = response_sender.send(app_sockets.profiler_message());
+                                }
+                                app_sockets.senders.insert(LiveId(id), response_sender);
                             }
                         }
                     },
                     HttpServerRequest::DisconnectWebSocket {web_socket_id} => {
-                        socket_id_to_build_id.remove(&web_socket_id);
+                        if let Some(id) = socket_id_to_build_id.remove(&web_socket_id){
+                            app_sockets.lock().unwrap().senders.remove(&id);
+                        }
                     },
                     HttpServerRequest::BinaryMessage {web_socket_id, response_sender: _, data} => {
                         if let Some(id) = socket_id_to_build_id.get(&web_socket_id){
@@ -1,8 +1,20 @@
 use {
     std::collections::{HashMap, hash_map},
     std::path::Path,
+    std::time::{Duration, Instant},
     crate::{
-        makepad_code_editor::{Document, decoration::{Decoration, DecorationSet}, Session},
+        makepad_code_editor::{
+            Document,
+            History,
+            Session,
+            Tokenizer,
+            collab::CollabState,
+            decoration::{Decoration, DecorationSet},
+            journal::{self, Journal},
+            selection::{Cursor, Selection},
+            text::{Change, Position, Text},
+        },
+        makepad_micro_serde::*,
         makepad_platform::makepad_live_compiler::LiveFileChange,
         makepad_widgets::*,
         makepad_widgets::file_tree::*,
@@ -13,11 +25,17 @@ use {
             FileResponse,
             FileClientMessage,
             FileNodeData,
+            FileNotification,
             FileTreeData,
+            OpenedFile,
+            SharedSelection,
         },
     },
 };
 
+// How often the journals of open documents are written out, in seconds.
+const JOURNAL_INTERVAL: f64 = 2.0;
+
 #[derive(Default)]
 pub struct FileSystem {
     pub file_client: FileClient,
@@ -26,7 +44,27 @@ pub struct FileSystem {
     pub path_to_file_node_id: HashMap<String, FileNodeId>,
     pub tab_id_to_file_node_id: HashMap<LiveId, FileNodeId>,
     pub tab_id_to_session: HashMap<LiveId, Session>,
-    pub open_documents: HashMap<FileNodeId, OpenDoc>
+    pub open_documents: HashMap<FileNodeId, OpenDoc>,
+    // The hash of the contents of each open file, as last loaded from or saved to disk.
+    pub saved_hashes: HashMap<FileNodeId, u64>,
+    // The saved hash and text hash of each open file when its journal was last written.
+    pub journaled_hashes: HashMap<FileNodeId, (u64, u64)>,
+    // Journals with unsaved edits, left behind by a session that did not end cleanly.
+    pub recoverable_journals: Vec<(String, Journal)>,
+    // The paths of files with unsaved edits left behind by a session that did not end cleanly,
+    // which changed on disk since, so their journals no longer apply.
+    pub conflicting_journals: Vec<String>,
+    // Journals the user chose to recover, to be applied once their files are opened.
+    pub recovered_journals: HashMap<String, Journal>,
+    pub journal_timer: Timer,
+    // The collaboration state of each open file, which tracks the local changes that the file
+    // server has not yet acknowledged.
+    pub collab_states: HashMap<FileNodeId, CollabState>,
+    // The selections of each open file, as last shared with the other participants.
+    pub sent_selections: HashMap<FileNodeId, Vec<SharedSelection>>,
+    // The changes made to each open document, local and remote, in the order they were applied,
+    // for the language server to catch up with.
+    pub document_changes: HashMap<FileNodeId, Vec<Change>>,
 }
 
 pub enum OpenDoc {
@@ -59,6 +97,7 @@ pub enum FileSystemAction {
     TreeLoaded,
     RecompileNeeded,
     LiveReloadNeeded(LiveFileChange),
+    RecoveryAvailable,
     None
 }
 
@@ -66,12 +105,58 @@ impl FileSystem {
     pub fn init(&mut self, cx: &mut Cx, path:&Path) {
         self.file_client.init(cx, path);
         self.reload_file_tree();
+        self.file_client.send_request(FileRequest::BeginSession);
+        self.journal_timer = cx.start_interval(JOURNAL_INTERVAL);
+    }
+    
+    /// Writes out all journals and waits for the file server to end the session, so the next
+    /// session does not mistake this one for a crash.
+    pub fn end_session(&mut self) {
+        self.flush_journals();
+        self.file_client.send_request(FileRequest::EndSession);
+        let deadline = Instant::now() + Duration::from_secs(1);
+        let message_receiver = &self.file_client.inner.as_ref().unwrap().message_receiver;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match message_receiver.recv_timeout(timeout) {
+                Ok(FileClientMessage::Response(FileResponse::EndSession)) => break,
+                Ok(_) => (),
+                Err(_) => break,
+            }
+        }
     }
     
     pub fn reload_file_tree(&mut self) {
         self.file_client.send_request(FileRequest::LoadFileTree {with_data: false});
     }
     
+    /// The paths of the files with unsaved edits that can be recovered from a crashed session.
+    pub fn recoverable_paths(&self) -> Vec<String> {
+        self.recoverable_journals.iter().map( | (path, _) | path.clone()).collect()
+    }
+    
+    /// The paths of the files with unsaved edits from a crashed session that changed on disk
+    /// since, so the edits cannot be recovered.
+    pub fn conflicting_paths(&self) -> Vec<String> {
+        self.conflicting_journals.clone()
+    }
+    
+    /// Accepts all recoverable journals, and returns the paths of the files that need to be opened
+    /// to restore them.
+    pub fn recover_unsaved(&mut self) -> Vec<String> {
+        let mut paths = Vec::new();
+        for (path, journal) in self.recoverable_journals.drain(..) {
+            paths.push(path.clone());
+            self.recovered_journals.insert(path, journal);
+        }
+        paths
+    }
+    
+    pub fn discard_unsaved(&mut self) {
+        self.recoverable_journals.clear();
+        self.conflicting_journals.clear();
+    }
+    
     pub fn remove_tab(&mut self, tab_id: LiveId) {
         self.tab_id_to_file_node_id.remove(&tab_id);
         self.tab_id_to_session.remove(&tab_id);
@@ -104,6 +189,9 @@ impl FileSystem {
     }
     
     pub fn handle_event(&mut self, cx: &mut Cx, event: &Event, ui: &WidgetRef) {
+        if self.journal_timer.is_event(event).is_some() {
+            self.flush_journals();
+        }
         
         if let Event::Signal = event{
             while let Ok(message) = self.file_client.inner.as_mut().unwrap().message_receiver.try_recv() {
@@ -116,7 +204,7 @@ impl FileSystem {
                         }
                         FileResponse::OpenFile(result) => {
                             match result {
-                                Ok((_unix_path, data, id)) => {
+                                Ok(OpenedFile {path: unix_path, text: data, journal: journal_data, revision, id}) => {
                                     let file_id = FileNodeId(LiveId(id));
                                     let dock = ui.dock(id!(dock));
                                     for (tab_id, file_id) in &self.tab_id_to_file_node_id {
@@ -126,7 +214,41 @@ impl FileSystem {
                                     }
                                     if let Some(OpenDoc::Decorations(dec)) = self.open_documents.get(&file_id) {
                                         let dec = dec.clone();
-                                        self.open_documents.insert(file_id, OpenDoc::Document(Document::new(data.into(), dec)));
+                                        let text: Text = data.into();
+                                        let saved_hash = journal::content_hash(&text);
+                                        self.saved_hashes.insert(file_id, saved_hash);
+                                        // a recovered journal brings back unsaved edits, otherwise we only restore the undo history
+                                        let mut recovered = self.recovered_journals.remove(&unix_path);
+                                        // the file may have changed on disk since the user chose to recover it
+                                        if recovered.as_ref().is_some_and( | journal | !journal.is_based_on(&text)) {
+                                            log!("Not recovering unsaved changes to {}, the file changed on disk", unix_path);
+                                            recovered = None;
+                                        }
+                                        let is_recovered = recovered.is_some();
+                                        let history = match recovered.or_else( || journal_data.and_then( | data | Journal::deserialize_bin(&data).ok())) {
+                                            Some(journal) => journal.into_history(&text, is_recovered),
+                                            None => History::from(text.clone())
+                                        };
+                                        // nothing needs to be journaled until the document is edited
+                                        self.journaled_hashes.insert(file_id, (saved_hash, journal::content_hash(history.as_text())));
+                                        // a recovered journal the user confirmed replaces the text on the file server as a single change
+                                        let recovered_changes = if is_recovered && history.as_text() != &text {
+                                            vec![
+                                                Change::Delete(Position::zero(), text.length()),
+                                                Change::Insert(Position::zero(), history.as_text().clone()),
+                                            ]
+                                        } else {
+                                            Vec::new()
+                                        };
+                                        let tokenizer = Tokenizer::for_path(&unix_path, history.as_text().as_lines().len());
+                                        let doc = Document::with_history(history, dec, tokenizer);
+                                        doc.start_recording_changes();
+                                        self.open_documents.insert(file_id, OpenDoc::Document(doc));
+                                        let mut collab_state = CollabState::new(revision);
+                                        if let Some(changes) = collab_state.apply_local(recovered_changes) {
+                                            self.file_client.send_request(FileRequest::ApplyDelta(unix_path, revision, changes));
+                                        }
+                                        self.collab_states.insert(file_id, collab_state);
                                     }else {panic!()}
                                     ui.redraw(cx);
                                 }
@@ -139,7 +261,10 @@ impl FileSystem {
                             }
                         }
                         FileResponse::SaveFile(result) => match result {
-                            Ok((path, old, new, _id)) => {
+                            Ok((path, old, new, id)) => {
+                                let file_id = FileNodeId(LiveId(id));
+                                self.saved_hashes.insert(file_id, journal::content_hash(&Text::from(&new)));
+                                self.write_journal(file_id);
                                 // alright file has been saved
                                 // now we need to check if a live_design!{} changed or something outside it
                                 if old != new {
@@ -175,19 +300,162 @@ impl FileSystem {
                             // to see if we need a recompile
                             
                         }
+                        FileResponse::ApplyDelta(result) => match result {
+                            Ok((path, revision)) => if let Some(file_id) = self.path_to_file_node_id(&path) {
+                                if let Some(state) = self.collab_states.get_mut(&file_id) {
+                                    match state.acknowledge(revision) {
+                                        Some(changes) => {
+                                            let revision = state.revision();
+                                            self.file_client.send_request(FileRequest::ApplyDelta(path, revision, changes));
+                                        }
+                                        // everything we changed is on the file server now, so it can be written to disk
+                                        None => self.save_file(file_id)
+                                    }
+                                }
+                            }
+                            Err(FileError::Unknown(err) | FileError::CannotOpen(err)) => {
+                                log!("Cannot apply delta {}", err);
+                            }
+                        }
+                        FileResponse::UpdateSelections(result) => {
+                            if let Err(FileError::Unknown(err) | FileError::CannotOpen(err)) = result {
+                                log!("Cannot update selections {}", err);
+                            }
+                        }
+                        FileResponse::CloseFile(result) => {
+                            if let Err(FileError::Unknown(err) | FileError::CannotOpen(err)) = result {
+                                log!("Cannot close file {}", err);
+                            }
+                        }
+                        FileResponse::SaveJournal(result) => {
+                            if let Err(FileError::Unknown(err) | FileError::CannotOpen(err)) = result {
+                                log!("Cannot save journal {}", err);
+                            }
+                        }
+                        FileResponse::BeginSession(result) => match result {
+                            Ok(journals) => {
+                                self.recoverable_journals.clear();
+                                self.conflicting_journals.clear();
+                                for (path, journal, text) in journals {
+                                    let Ok(journal) = Journal::deserialize_bin(&journal) else {
+                                        continue
+                                    };
+                                    if !journal.is_dirty() {
+                                        continue
+                                    }
+                                    // only offer to recover edits to the file as it was when they were made
+                                    if text.is_some_and( | text | journal.is_based_on(&Text::from(text))) {
+                                        self.recoverable_journals.push((path, journal));
+                                    }
+                                    else {
+                                        self.conflicting_journals.push(path);
+                                    }
+                                }
+                                if !self.recoverable_journals.is_empty() || !self.conflicting_journals.is_empty() {
+                                    cx.action(FileSystemAction::RecoveryAvailable)
+                                }
+                            }
+                            Err(FileError::Unknown(err) | FileError::CannotOpen(err)) => {
+                                log!("Cannot begin session {}", err);
+                            }
+                        }
+                        FileResponse::EndSession => {}
                     },
-                    FileClientMessage::Notification(_notification) => {
-                        //self.editors.handle_collab_notification(cx, &mut state.editor_state, notification)
+                    FileClientMessage::Notification(notification) => {
+                        self.handle_notification(notification);
+                        self.redraw_all_views(cx, &ui.dock(id!(dock)));
                     }
                 }
             }
         }
     }
 
+    fn handle_notification(&mut self, notification: FileNotification) {
+        match notification {
+            FileNotification::FileChangedOnDisk => {}
+            FileNotification::DeltaWasApplied(path, changes) => {
+                let Some(file_id) = self.path_to_file_node_id(&path) else {
+                    return
+                };
+                // local changes that were not recorded yet have to be taken into account when
+                // transforming the remote changes
+                self.sync_document(file_id);
+                let (Some(OpenDoc::Document(doc)), Some(state)) = (self.open_documents.get(&file_id), self.collab_states.get_mut(&file_id)) else {
+                    return
+                };
+                let changes = match state.apply_remote(changes) {
+                    Ok(changes) => changes,
+                    Err(err) => {
+                        log!("Cannot apply remote delta to {}: {}", path, err);
+                        return
+                    }
+                };
+                self.document_changes.entry(file_id).or_default().extend(changes.iter().cloned());
+                doc.apply_remote_changes(changes);
+                self.handle_sessions();
+            }
+            FileNotification::SelectionsChanged(path, participant_id, selections) => {
+                let Some(file_id) = self.path_to_file_node_id(&path) else {
+                    return
+                };
+                let (Some(OpenDoc::Document(doc)), Some(state)) = (self.open_documents.get(&file_id), self.collab_states.get(&file_id)) else {
+                    return
+                };
+                let selections: Vec<Selection> = selections.into_iter().map( | selection | Selection {
+                    cursor: Cursor::from(state.transform_position(selection.cursor)),
+                    anchor: state.transform_position(selection.anchor),
+                }).collect();
+                doc.set_remote_selections(participant_id as usize, &selections);
+            }
+        }
+    }
+
     pub fn handle_sessions(&mut self) {
         for session in self.tab_id_to_session.values_mut() {
             session.handle_changes();
         }
+        let file_ids: Vec<FileNodeId> = self.collab_states.keys().cloned().collect();
+        for file_id in file_ids {
+            self.sync_document(file_id);
+        }
+    }
+    
+    // Sends the local changes to an open document to the file server, as well as the selections
+    // of its sessions once the file server has caught up with those changes.
+    fn sync_document(&mut self, file_id: FileNodeId) {
+        let Some(OpenDoc::Document(doc)) = self.open_documents.get(&file_id) else {
+            return
+        };
+        let path = self.file_node_path(file_id);
+        let Some(state) = self.collab_states.get_mut(&file_id) else {
+            return
+        };
+        let changes = doc.take_recorded_changes();
+        if !changes.is_empty() {
+            self.document_changes.entry(file_id).or_default().extend(changes.iter().cloned());
+        }
+        if let Some(changes) = state.apply_local(changes) {
+            self.file_client.send_request(FileRequest::ApplyDelta(path.clone(), state.revision(), changes));
+        }
+        if !state.is_synchronized() {
+            return
+        }
+        let selections: Vec<SharedSelection> = self.tab_id_to_session.iter()
+            .filter( | (tab_id, _) | self.tab_id_to_file_node_id.get(tab_id) == Some(&file_id))
+            .flat_map( | (_, session) | session.selections().iter().map( | selection | SharedSelection {
+                anchor: selection.anchor,
+                cursor: selection.cursor.position,
+            }).collect::<Vec<_>>())
+            .collect();
+        if self.sent_selections.get(&file_id) != Some(&selections) {
+            self.file_client.send_request(FileRequest::UpdateSelections(path, state.revision(), selections.clone()));
+            self.sent_selections.insert(file_id, selections);
+        }
+    }
+    
+    /// Returns the changes made to the open documents since the last call.
+    pub fn take_document_changes(&mut self) -> HashMap<FileNodeId, Vec<Change>> {
+        std::mem::take(&mut self.document_changes)
     }
     
     pub fn request_open_file(&mut self, tab_id: LiveId, file_id: FileNodeId) {
@@ -214,15 +482,47 @@ impl FileSystem {
     
     
     pub fn request_save_file(&mut self, tab_id: LiveId) {
-        // ok lets see if we have a document
-        // ifnot, we create a new one
         if let Some(file_id) = self.tab_id_to_file_node_id.get(&tab_id) {
-            if let Some(OpenDoc::Document(doc)) = self.open_documents.get(&file_id) {
-                let text = doc.as_text().to_string();
-                let path = self.file_node_path(*file_id);
-                self.file_client.send_request(FileRequest::SaveFile(path.clone(), text, file_id.0.0));
-            }
+            self.save_file(*file_id);
+        };
+    }
+    
+    fn save_file(&mut self, file_id: FileNodeId) {
+        // the file server writes its own revision of the file, so we wait until it has all of our
+        // changes, after which the file is saved anyway
+        if self.collab_states.get(&file_id).is_some_and( | state | !state.is_synchronized()) {
+            return
+        }
+        if let Some(OpenDoc::Document(doc)) = self.open_documents.get(&file_id) {
+            let text = doc.as_text().to_string();
+            let path = self.file_node_path(file_id);
+            self.file_client.send_request(FileRequest::SaveFile(path.clone(), text, file_id.0.0));
+        }
+    }
+    
+    /// Writes the journal of every open document that changed since its journal was last written.
+    pub fn flush_journals(&mut self) {
+        let file_ids: Vec<FileNodeId> = self.saved_hashes.keys().cloned().collect();
+        for file_id in file_ids {
+            self.write_journal(file_id);
+        }
+    }
+    
+    fn write_journal(&mut self, file_id: FileNodeId) {
+        let Some(OpenDoc::Document(doc)) = self.open_documents.get(&file_id) else {
+            return
+        };
+        let Some(saved_hash) = self.saved_hashes.get(&file_id).cloned() else {
+            return
         };
+        let hashes = (saved_hash, journal::content_hash(&doc.as_text()));
+        if self.journaled_hashes.get(&file_id) == Some(&hashes) {
+            return
+        }
+        self.journaled_hashes.insert(file_id, hashes);
+        let journal = Journal::new(saved_hash, doc.history().clone());
+        let path = self.file_node_path(file_id);
+        self.file_client.send_request(FileRequest::SaveJournal(path, journal.serialize_bin()));
     }
     
     pub fn clear_decorations(&mut self, file_node_id: &FileNodeId) {
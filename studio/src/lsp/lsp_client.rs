@@ -0,0 +1,186 @@
+use {
+    crate::{
+        makepad_micro_serde::*,
+        makepad_platform::*,
+        lsp::lsp_protocol::*,
+    },
+    std::{
+        io::{self, BufRead, BufReader, Write},
+        path::Path,
+        process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+        sync::mpsc::{self, Receiver, Sender},
+        thread,
+        time::{Duration, Instant},
+    },
+};
+
+/// A connection to a language server that runs as a child process, and speaks JSON-RPC over its
+/// stdin and stdout.
+pub struct LspClient {
+    child: Child,
+    message_sender: Sender<String>,
+    pub message_receiver: ToUIReceiver<LspMessage>,
+    next_request_id: u64,
+}
+
+impl LspClient {
+    pub fn spawn(command: &str, args: &[String], cwd: &Path) -> io::Result<Self> {
+        let mut child = Command::new(command)
+            .args(args)
+            .current_dir(cwd)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let (message_sender, message_receiver) = mpsc::channel();
+        spawn_message_writer(message_receiver, child.stdin.take().unwrap());
+        let incoming = ToUIReceiver::default();
+        spawn_message_reader(child.stdout.take().unwrap(), incoming.sender());
+        Ok(Self {
+            child,
+            message_sender,
+            message_receiver: incoming,
+            next_request_id: 1,
+        })
+    }
+
+    /// Sends a request, and returns the id that its response will carry.
+    pub fn send_request(&mut self, method: &str, params: &str) -> u64 {
+        let id = self.next_request_id;
+        self.next_request_id += 1;
+        let _ = self.message_sender.send(request_json(id, method, params));
+        id
+    }
+
+    pub fn send_notification(&self, method: &str, params: &str) {
+        let _ = self.message_sender.send(notification_json(method, params));
+    }
+
+    pub fn send_response(&self, id: &JsonValue, result: &str) {
+        let _ = self.message_sender.send(response_json(id, result));
+    }
+
+    /// Asks the server to exit, and kills it if it has not done so within a second.
+    pub fn shutdown(&mut self) {
+        self.send_request("shutdown", "null");
+        self.send_notification("exit", "null");
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn spawn_message_writer(message_receiver: Receiver<String>, mut stdin: ChildStdin) {
+    thread::spawn(move || {
+        while let Ok(message) = message_receiver.recv() {
+            let header = format!("Content-Length: {}\r\n\r\n", message.len());
+            if stdin.write_all(header.as_bytes()).is_err()
+                || stdin.write_all(message.as_bytes()).is_err()
+                || stdin.flush().is_err()
+            {
+                break;
+            }
+        }
+    });
+}
+
+fn spawn_message_reader(stdout: ChildStdout, message_sender: ToUISender<LspMessage>) {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(stdout);
+        while let Some(message) = read_message(&mut reader) {
+            if let Some(message) = LspMessage::from_json(&message) {
+                if message_sender.send(message).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+}
+
+// Reads the headers and content of a single message, or returns `None` once the server has
+// closed its end of the pipe.
+fn read_message(reader: &mut impl BufRead) -> Option<String> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).ok()? == 0 {
+            return None;
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let mut content = vec![0; content_length?];
+    reader.read_exact(&mut content).ok()?;
+    String::from_utf8(content).ok()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    // A fake server that reads one message, and answers with a diagnostics notification and a
+    // response to request 1.
+    const FAKE_SERVER: &str = r#"
+        while read -r line; do
+            line=$(printf '%s' "$line" | tr -d '\r')
+            case "$line" in
+                Content-Length:*) length=${line#Content-Length: } ;;
+                "") break ;;
+            esac
+        done
+        dd bs=1 count="$length" of=/dev/null 2>/dev/null
+        send() { printf 'Content-Length: %d\r\n\r\n%s' "${#1}" "$1"; }
+        send '{"jsonrpc":"2.0","method":"textDocument/publishDiagnostics","params":{"uri":"file:///a.rs","diagnostics":[{"range":{"start":{"line":0,"character":1},"end":{"line":0,"character":2}},"severity":2,"message":"unused"}]}}'
+        send '{"jsonrpc":"2.0","id":1,"result":{"contents":{"kind":"markdown","value":"fn main()"}}}'
+    "#;
+
+    fn recv(client: &LspClient) -> LspMessage {
+        client
+            .message_receiver
+            .receiver
+            .recv_timeout(Duration::from_secs(5))
+            .unwrap()
+    }
+
+    #[test]
+    fn talks_to_a_scripted_server() {
+        let mut client = LspClient::spawn(
+            "sh",
+            &["-c".to_string(), FAKE_SERVER.to_string()],
+            Path::new("."),
+        )
+        .unwrap();
+        let id = client.send_request("textDocument/hover", "{}");
+        match recv(&client) {
+            LspMessage::Notification { method, params } => {
+                assert_eq!(method, "textDocument/publishDiagnostics");
+                let params = parse_publish_diagnostics(&params).unwrap();
+                assert_eq!(params.uri, "file:///a.rs");
+                assert_eq!(params.diagnostics.len(), 1);
+                assert_eq!(params.diagnostics[0].message, "unused");
+            }
+            message => panic!("unexpected message {:?}", message),
+        }
+        match recv(&client) {
+            LspMessage::Response { id: response_id, result } => {
+                assert_eq!(response_id, id);
+                assert_eq!(parse_hover(&result.unwrap()).as_deref(), Some("fn main()"));
+            }
+            message => panic!("unexpected message {:?}", message),
+        }
+        client.shutdown();
+    }
+}
@@ -0,0 +1,366 @@
+use {
+    crate::{
+        makepad_micro_serde::*,
+        makepad_widgets::*,
+        makepad_widgets::file_tree::FileNodeId,
+        makepad_code_editor::{
+            code_editor::CompletionItem,
+            decoration::{Decoration, DecorationSet},
+            text::{Change, Position, Text},
+        },
+        file_system::file_system::{FileSystem, OpenDoc},
+        lsp::{lsp_client::LspClient, lsp_protocol::*},
+        app::AppAction,
+        log_list::JumpTo,
+    },
+    std::{
+        collections::HashMap,
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+/// Keeps a language server in sync with the open documents, and turns its answers into
+/// diagnostics, hover and completion popups, and jumps to definitions.
+#[derive(Default)]
+pub struct LspManager {
+    client: Option<LspClient>,
+    root_path: PathBuf,
+    initialize_id: Option<u64>,
+    is_initialized: bool,
+    encoding: PositionEncoding,
+    documents: HashMap<FileNodeId, LspDocument>,
+    pending_requests: HashMap<u64, PendingRequest>,
+}
+
+// What the server knows about an open document. The text mirrors the document as of the last
+// change that was sent, which is what the positions of the next change are relative to.
+struct LspDocument {
+    uri: String,
+    version: u64,
+    text: Text,
+}
+
+struct PendingRequest {
+    kind: RequestKind,
+    tab_id: LiveId,
+    position: Position,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum RequestKind {
+    Hover,
+    Definition,
+    Completion,
+}
+
+impl RequestKind {
+    fn method(self) -> &'static str {
+        match self {
+            Self::Hover => "textDocument/hover",
+            Self::Definition => "textDocument/definition",
+            Self::Completion => "textDocument/completion",
+        }
+    }
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum LspAction {
+    Hover {
+        tab_id: LiveId,
+        position: Position,
+        text: String,
+    },
+    Completions {
+        tab_id: LiveId,
+        items: Vec<CompletionItem>,
+    },
+    None,
+}
+
+impl LspManager {
+    /// Starts the language server given with `--lsp=<command>`, or rust-analyzer if the root
+    /// is a cargo project. `--lsp=` without a command turns the language server off.
+    pub fn init(&mut self, _cx: &mut Cx, path: &Path) {
+        self.root_path = path.to_path_buf();
+        let command_line = std::env::args()
+            .find_map(|arg| arg.strip_prefix("--lsp=").map(String::from))
+            .or_else(|| {
+                path.join("Cargo.toml")
+                    .exists()
+                    .then(|| "rust-analyzer".to_string())
+            });
+        let Some(command_line) = command_line else {
+            return
+        };
+        let mut parts = command_line.split_whitespace().map(String::from);
+        let Some(command) = parts.next() else {
+            return
+        };
+        let args: Vec<String> = parts.collect();
+        match LspClient::spawn(&command, &args, path) {
+            Ok(mut client) => {
+                let root_uri = path_to_uri(path);
+                self.initialize_id = Some(client.send_request(
+                    "initialize",
+                    &initialize_params(std::process::id(), &root_uri),
+                ));
+                self.client = Some(client);
+            }
+            Err(err) => log!("Cannot start language server {}: {}", command, err),
+        }
+    }
+
+    pub fn shutdown(&mut self) {
+        if let Some(mut client) = self.client.take() {
+            client.shutdown();
+        }
+    }
+
+    pub fn handle_event(&mut self, cx: &mut Cx, event: &Event, file_system: &mut FileSystem) {
+        if self.client.is_none() {
+            return
+        }
+        if let Event::Signal = event {
+            while let Some(Ok(message)) = self.client.as_ref().map(|client| client.message_receiver.try_recv()) {
+                self.handle_message(cx, message, file_system);
+            }
+        }
+        self.sync(file_system);
+    }
+
+    pub fn request_hover(&mut self, file_system: &mut FileSystem, tab_id: LiveId, position: Position) {
+        self.send_position_request(file_system, RequestKind::Hover, tab_id, position);
+    }
+
+    pub fn request_definition(&mut self, file_system: &mut FileSystem, tab_id: LiveId, position: Position) {
+        self.send_position_request(file_system, RequestKind::Definition, tab_id, position);
+    }
+
+    pub fn request_completion(&mut self, file_system: &mut FileSystem, tab_id: LiveId, position: Position) {
+        self.send_position_request(file_system, RequestKind::Completion, tab_id, position);
+    }
+
+    fn send_position_request(
+        &mut self,
+        file_system: &mut FileSystem,
+        kind: RequestKind,
+        tab_id: LiveId,
+        position: Position,
+    ) {
+        // the server has to know about the latest changes for the position to make sense
+        self.sync(file_system);
+        let Some(file_id) = file_system.tab_id_to_file_node_id.get(&tab_id) else {
+            return
+        };
+        let (Some(client), Some(document)) = (self.client.as_mut(), self.documents.get(file_id)) else {
+            return
+        };
+        // only the answer to the latest request of each kind is still of interest
+        let stale_ids: Vec<u64> = self.pending_requests.iter()
+            .filter( | (_, request) | request.kind == kind)
+            .map( | (id, _) | *id)
+            .collect();
+        for id in stale_ids {
+            self.pending_requests.remove(&id);
+            client.send_notification("$/cancelRequest", &format!(r#"{{"id":{}}}"#, id));
+        }
+        let params = TextDocumentPositionParams {
+            textDocument: TextDocumentIdentifier {uri: document.uri.clone()},
+            position: self.encoding.to_lsp(document.text.as_lines(), position),
+        };
+        let id = client.send_request(kind.method(), &params.serialize_json());
+        self.pending_requests.insert(id, PendingRequest {kind, tab_id, position});
+    }
+
+    fn handle_message(&mut self, cx: &mut Cx, message: LspMessage, file_system: &mut FileSystem) {
+        match message {
+            LspMessage::Response {id, result} if Some(id) == self.initialize_id => match result {
+                Ok(result) => {
+                    self.encoding = PositionEncoding::from_initialize_result(&result);
+                    self.client.as_ref().unwrap().send_notification("initialized", "{}");
+                    self.is_initialized = true;
+                }
+                Err(err) => {
+                    log!("Cannot initialize language server: {}", err);
+                    self.shutdown();
+                }
+            }
+            LspMessage::Response {id, result} => {
+                let Some(request) = self.pending_requests.remove(&id) else {
+                    return
+                };
+                let result = match result {
+                    Ok(result) => result,
+                    Err(err) => {
+                        log!("Language server request {} failed: {}", request.kind.method(), err);
+                        return
+                    }
+                };
+                match request.kind {
+                    RequestKind::Hover => if let Some(text) = parse_hover(&result) {
+                        cx.action(LspAction::Hover {
+                            tab_id: request.tab_id,
+                            position: request.position,
+                            text
+                        });
+                    }
+                    RequestKind::Completion => {
+                        cx.action(LspAction::Completions {
+                            tab_id: request.tab_id,
+                            items: parse_completions(&result),
+                        });
+                    }
+                    RequestKind::Definition => if let Some((uri, position)) = parse_definition(&result) {
+                        if let Some(jump_to) = self.jump_to(file_system, &uri, position) {
+                            cx.action(AppAction::JumpTo(jump_to));
+                        }
+                    }
+                }
+            }
+            LspMessage::Notification {method, params} => {
+                if method == "textDocument/publishDiagnostics" {
+                    if let Some(params) = parse_publish_diagnostics(&params) {
+                        if let Some(file_id) = self.set_diagnostics(file_system, params) {
+                            cx.action(AppAction::RedrawFile(file_id));
+                        }
+                    }
+                }
+            }
+            // we announce no capabilities that need an answer, so an empty one will do
+            LspMessage::Request {id, ..} => {
+                if let Some(client) = &self.client {
+                    client.send_response(&id, "null");
+                }
+            }
+        }
+    }
+
+    fn jump_to(&self, file_system: &FileSystem, uri: &str, position: LspPosition) -> Option<JumpTo> {
+        let path = uri_to_path(uri)?;
+        let file_name = path.strip_prefix(&self.root_path).ok()?.to_string_lossy().replace('\\', "/");
+        let file_id = file_system.path_to_file_node_id(&file_name)?;
+        let start = match self.documents.get(&file_id) {
+            Some(document) => self.encoding.from_lsp(document.text.as_lines(), position),
+            None => {
+                let text = Text::from(fs::read_to_string(&path).ok()?);
+                self.encoding.from_lsp(text.as_lines(), position)
+            }
+        };
+        Some(JumpTo {file_name, start})
+    }
+
+    fn set_diagnostics(&self, file_system: &FileSystem, params: PublishDiagnosticsParams) -> Option<FileNodeId> {
+        let (file_id, document) = self.documents.iter().find( | (_, document) | document.uri == params.uri)?;
+        // diagnostics for an older version would end up in the wrong place
+        if params.version.is_some_and( | version | version != document.version) {
+            return None
+        }
+        let Some(OpenDoc::Document(doc)) = file_system.open_documents.get(file_id) else {
+            return None
+        };
+        let lines = document.text.as_lines();
+        let mut decorations = DecorationSet::new();
+        for diagnostic in params.diagnostics {
+            let start = self.encoding.from_lsp(lines, diagnostic.range.start);
+            let mut end = self.encoding.from_lsp(lines, diagnostic.range.end);
+            if start == end {
+                // an empty range would not be visible, so it covers the next character instead
+                end.byte_index = lines[end.line_index][end.byte_index..]
+                    .chars()
+                    .next()
+                    .map_or(end.byte_index, | char | end.byte_index + char.len_utf8());
+            }
+            decorations.add_decoration(Decoration::new(0, start, end, diagnostic.ty));
+        }
+        doc.set_diagnostics(decorations);
+        Some(*file_id)
+    }
+
+    // Opens the documents that were opened since the last time, and sends the changes that were
+    // made to the others since then.
+    fn sync(&mut self, file_system: &mut FileSystem) {
+        let mut changes = file_system.take_document_changes();
+        if !self.is_initialized {
+            return
+        }
+        let Some(client) = &self.client else {
+            return
+        };
+        self.documents.retain( | file_id, document | {
+            let is_open = matches!(file_system.open_documents.get(file_id), Some(OpenDoc::Document(_)));
+            if !is_open {
+                let params = DidCloseTextDocumentParams {
+                    textDocument: TextDocumentIdentifier {uri: document.uri.clone()}
+                };
+                client.send_notification("textDocument/didClose", &params.serialize_json());
+            }
+            is_open
+        });
+        for (file_id, open_doc) in &file_system.open_documents {
+            let OpenDoc::Document(doc) = open_doc else {
+                continue
+            };
+            match self.documents.get_mut(file_id) {
+                Some(document) => {
+                    let Some(changes) = changes.remove(file_id).filter( | changes | !changes.is_empty()) else {
+                        continue
+                    };
+                    let content_changes = changes.into_iter().map( | change | {
+                        let event = change_event(self.encoding, &document.text, &change);
+                        document.text.apply_change(change);
+                        event
+                    }).collect();
+                    document.version += 1;
+                    let params = DidChangeTextDocumentParams {
+                        textDocument: VersionedTextDocumentIdentifier {
+                            uri: document.uri.clone(),
+                            version: document.version,
+                        },
+                        contentChanges: content_changes,
+                    };
+                    client.send_notification("textDocument/didChange", &params.serialize_json());
+                }
+                None => {
+                    let path = file_system.file_node_path(*file_id);
+                    let text = doc.as_text().clone();
+                    let document = LspDocument {
+                        uri: path_to_uri(&self.root_path.join(&path)),
+                        version: 0,
+                        text,
+                    };
+                    let params = DidOpenTextDocumentParams {
+                        textDocument: TextDocumentItem {
+                            uri: document.uri.clone(),
+                            languageId: language_id(&path).to_string(),
+                            version: document.version,
+                            text: document.text.to_string(),
+                        }
+                    };
+                    client.send_notification("textDocument/didOpen", &params.serialize_json());
+                    self.documents.insert(*file_id, document);
+                }
+            }
+        }
+    }
+}
+
+fn change_event(encoding: PositionEncoding, text: &Text, change: &Change) -> TextDocumentContentChangeEvent {
+    let lines = text.as_lines();
+    match change {
+        Change::Insert(position, inserted) => {
+            let position = encoding.to_lsp(lines, *position);
+            TextDocumentContentChangeEvent {
+                range: Some(LspRange {start: position, end: position}),
+                text: inserted.to_string(),
+            }
+        }
+        Change::Delete(position, length) => TextDocumentContentChangeEvent {
+            range: Some(LspRange {
+                start: encoding.to_lsp(lines, *position),
+                end: encoding.to_lsp(lines, *position + *length),
+            }),
+            text: String::new(),
+        }
+    }
+}
@@ -0,0 +1,442 @@
+#![allow(non_snake_case)]
+use {
+    crate::{
+        makepad_micro_serde::*,
+        makepad_code_editor::{
+            code_editor::CompletionItem,
+            decoration::DecorationType,
+            text::Position,
+        },
+    },
+    std::path::{Path, PathBuf},
+};
+
+// The capabilities we announce to the server. Snippets are not supported, so completions come
+// back as plain text that can be inserted as is.
+const CLIENT_CAPABILITIES: &str = r#"{
+    "general": {"positionEncodings": ["utf-8", "utf-16"]},
+    "textDocument": {
+        "synchronization": {"dynamicRegistration": false},
+        "hover": {"contentFormat": ["plaintext", "markdown"]},
+        "completion": {"completionItem": {"snippetSupport": false}},
+        "definition": {"linkSupport": true},
+        "publishDiagnostics": {"versionSupport": true}
+    }
+}"#;
+
+// lsp json structs, named after the ones in the specification
+#[derive(Clone, Copy, Debug, Default, PartialEq, SerJson)]
+pub struct LspPosition {
+    pub line: usize,
+    pub character: usize,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, SerJson)]
+pub struct LspRange {
+    pub start: LspPosition,
+    pub end: LspPosition,
+}
+
+#[derive(Clone, Debug, SerJson)]
+pub struct TextDocumentIdentifier {
+    pub uri: String,
+}
+
+#[derive(Clone, Debug, SerJson)]
+pub struct VersionedTextDocumentIdentifier {
+    pub uri: String,
+    pub version: u64,
+}
+
+#[derive(Clone, Debug, SerJson)]
+pub struct TextDocumentItem {
+    pub uri: String,
+    pub languageId: String,
+    pub version: u64,
+    pub text: String,
+}
+
+#[derive(Clone, Debug, SerJson)]
+pub struct TextDocumentContentChangeEvent {
+    pub range: Option<LspRange>,
+    pub text: String,
+}
+
+#[derive(Clone, Debug, SerJson)]
+pub struct DidOpenTextDocumentParams {
+    pub textDocument: TextDocumentItem,
+}
+
+#[derive(Clone, Debug, SerJson)]
+pub struct DidChangeTextDocumentParams {
+    pub textDocument: VersionedTextDocumentIdentifier,
+    pub contentChanges: Vec<TextDocumentContentChangeEvent>,
+}
+
+#[derive(Clone, Debug, SerJson)]
+pub struct DidCloseTextDocumentParams {
+    pub textDocument: TextDocumentIdentifier,
+}
+
+#[derive(Clone, Debug, SerJson)]
+pub struct TextDocumentPositionParams {
+    pub textDocument: TextDocumentIdentifier,
+    pub position: LspPosition,
+}
+
+pub fn initialize_params(process_id: u32, root_uri: &str) -> String {
+    format!(
+        r#"{{"processId":{},"rootUri":{},"capabilities":{}}}"#,
+        process_id,
+        root_uri.to_string().serialize_json(),
+        CLIENT_CAPABILITIES
+    )
+}
+
+pub fn request_json(id: u64, method: &str, params: &str) -> String {
+    format!(
+        r#"{{"jsonrpc":"2.0","id":{},"method":{},"params":{}}}"#,
+        id,
+        method.to_string().serialize_json(),
+        params
+    )
+}
+
+pub fn notification_json(method: &str, params: &str) -> String {
+    format!(
+        r#"{{"jsonrpc":"2.0","method":{},"params":{}}}"#,
+        method.to_string().serialize_json(),
+        params
+    )
+}
+
+pub fn response_json(id: &JsonValue, result: &str) -> String {
+    let id = match id {
+        JsonValue::String(id) => id.serialize_json(),
+        JsonValue::U64(id) => id.to_string(),
+        JsonValue::I64(id) => id.to_string(),
+        _ => "null".to_string(),
+    };
+    format!(r#"{{"jsonrpc":"2.0","id":{},"result":{}}}"#, id, result)
+}
+
+/// A message sent by the language server.
+#[derive(Clone, Debug)]
+pub enum LspMessage {
+    /// The response to one of our requests. The error holds the message of a failed request.
+    Response {
+        id: u64,
+        result: Result<JsonValue, String>,
+    },
+    Notification {
+        method: String,
+        params: JsonValue,
+    },
+    /// A request from the server, which has to be answered with a response carrying the same id.
+    Request {
+        id: JsonValue,
+        method: String,
+        params: JsonValue,
+    },
+}
+
+impl LspMessage {
+    pub fn from_json(json: &str) -> Option<Self> {
+        let message = JsonValue::deserialize_json(json).ok()?;
+        let method = get(&message, "method").and_then(as_str).map(String::from);
+        let params = get(&message, "params").cloned().unwrap_or(JsonValue::Null);
+        match (get(&message, "id"), method) {
+            (Some(id), Some(method)) => Some(Self::Request {
+                id: id.clone(),
+                method,
+                params,
+            }),
+            (None, Some(method)) => Some(Self::Notification { method, params }),
+            (Some(id), None) => Some(Self::Response {
+                id: as_u64(id)?,
+                result: match get(&message, "error") {
+                    Some(error) => Err(get(error, "message")
+                        .and_then(as_str)
+                        .unwrap_or("unknown error")
+                        .to_string()),
+                    None => Ok(get(&message, "result").cloned().unwrap_or(JsonValue::Null)),
+                },
+            }),
+            (None, None) => None,
+        }
+    }
+}
+
+/// How the server counts the characters in a line. UTF-16 is the default, so it is what we get
+/// when the server does not support UTF-8.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum PositionEncoding {
+    Utf8,
+    #[default]
+    Utf16,
+}
+
+impl PositionEncoding {
+    pub fn from_initialize_result(result: &JsonValue) -> Self {
+        match get(result, "capabilities")
+            .and_then(|capabilities| get(capabilities, "positionEncoding"))
+            .and_then(as_str)
+        {
+            Some("utf-8") => Self::Utf8,
+            _ => Self::Utf16,
+        }
+    }
+
+    pub fn to_lsp(self, lines: &[String], position: Position) -> LspPosition {
+        let line = lines.get(position.line_index).map_or("", |line| line.as_str());
+        let byte_index = position.byte_index.min(line.len());
+        LspPosition {
+            line: position.line_index,
+            character: match self {
+                Self::Utf8 => byte_index,
+                Self::Utf16 => line
+                    .char_indices()
+                    .take_while(|(index, _)| *index < byte_index)
+                    .map(|(_, char)| char.len_utf16())
+                    .sum(),
+            },
+        }
+    }
+
+    /// Converts a position from the server, clamping it to the text.
+    pub fn from_lsp(self, lines: &[String], position: LspPosition) -> Position {
+        if position.line >= lines.len() {
+            let line_index = lines.len().saturating_sub(1);
+            return Position {
+                line_index,
+                byte_index: lines.get(line_index).map_or(0, |line| line.len()),
+            };
+        }
+        let line = &lines[position.line];
+        let mut character = 0;
+        let byte_index = line
+            .char_indices()
+            .find(|(_, char)| {
+                if character >= position.character {
+                    return true;
+                }
+                character += match self {
+                    Self::Utf8 => char.len_utf8(),
+                    Self::Utf16 => char.len_utf16(),
+                };
+                false
+            })
+            .map_or(line.len(), |(index, _)| index);
+        Position {
+            line_index: position.line,
+            byte_index,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LspDiagnostic {
+    pub range: LspRange,
+    pub ty: DecorationType,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublishDiagnosticsParams {
+    pub uri: String,
+    pub version: Option<u64>,
+    pub diagnostics: Vec<LspDiagnostic>,
+}
+
+/// Parses the params of a `textDocument/publishDiagnostics` notification. Hints and information
+/// are left out, as there are no decorations for them.
+pub fn parse_publish_diagnostics(params: &JsonValue) -> Option<PublishDiagnosticsParams> {
+    Some(PublishDiagnosticsParams {
+        uri: get(params, "uri").and_then(as_str)?.to_string(),
+        version: get(params, "version").and_then(as_u64),
+        diagnostics: get(params, "diagnostics")
+            .and_then(as_array)?
+            .iter()
+            .filter_map(|diagnostic| {
+                let ty = match get(diagnostic, "severity").and_then(as_u64) {
+                    None | Some(1) => DecorationType::Error,
+                    Some(2) => DecorationType::Warning,
+                    _ => return None,
+                };
+                Some(LspDiagnostic {
+                    range: parse_range(get(diagnostic, "range")?)?,
+                    ty,
+                    message: get(diagnostic, "message")
+                        .and_then(as_str)
+                        .unwrap_or("")
+                        .to_string(),
+                })
+            })
+            .collect(),
+    })
+}
+
+/// Parses the result of a `textDocument/hover` request into plain text.
+pub fn parse_hover(result: &JsonValue) -> Option<String> {
+    fn marked_string(value: &JsonValue) -> Option<String> {
+        match value {
+            JsonValue::String(string) => Some(string.clone()),
+            JsonValue::Array(values) => Some(
+                values
+                    .iter()
+                    .filter_map(marked_string)
+                    .collect::<Vec<_>>()
+                    .join("\n\n"),
+            ),
+            _ => get(value, "value").and_then(as_str).map(String::from),
+        }
+    }
+
+    let contents = marked_string(get(result, "contents")?)?;
+    // code fences only get in the way of reading the text
+    let text = contents
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("```"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Parses the result of a `textDocument/definition` request into the uri and start of the first
+/// definition.
+pub fn parse_definition(result: &JsonValue) -> Option<(String, LspPosition)> {
+    let location = match result {
+        JsonValue::Array(locations) => locations.first()?,
+        location => location,
+    };
+    if let Some(uri) = get(location, "targetUri").and_then(as_str) {
+        let range = get(location, "targetSelectionRange")
+            .or_else(|| get(location, "targetRange"))
+            .and_then(parse_range)?;
+        return Some((uri.to_string(), range.start));
+    }
+    let uri = get(location, "uri").and_then(as_str)?;
+    let range = get(location, "range").and_then(parse_range)?;
+    Some((uri.to_string(), range.start))
+}
+
+/// Parses the result of a `textDocument/completion` request.
+pub fn parse_completions(result: &JsonValue) -> Vec<CompletionItem> {
+    let items = match result {
+        JsonValue::Array(items) => items,
+        result => match get(result, "items").and_then(as_array) {
+            Some(items) => items,
+            None => return Vec::new(),
+        },
+    };
+    items
+        .iter()
+        .filter_map(|item| {
+            let label = get(item, "label").and_then(as_str)?.to_string();
+            let insert_text = get(item, "textEdit")
+                .and_then(|text_edit| get(text_edit, "newText"))
+                .or_else(|| get(item, "insertText"))
+                .and_then(as_str)
+                .unwrap_or(&label)
+                .to_string();
+            Some(CompletionItem {
+                detail: get(item, "detail").and_then(as_str).map(String::from),
+                label,
+                insert_text,
+            })
+        })
+        .collect()
+}
+
+pub fn language_id(path: &str) -> &'static str {
+    match path.rsplit_once('.').map(|(_, extension)| extension) {
+        Some("rs") => "rust",
+        Some("toml") => "toml",
+        Some("json") => "json",
+        Some("md") => "markdown",
+        _ => "plaintext",
+    }
+}
+
+pub fn path_to_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from("file://");
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                uri.push(byte as char)
+            }
+            // drive letters are left as is
+            b':' if uri.len() == "file:///c".len() => uri.push(':'),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut iter = path.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    let path = String::from_utf8(bytes).ok()?;
+    // on windows the path comes after a slash, as in file:///c:/dir
+    match path.as_bytes() {
+        [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => Some(PathBuf::from(&path[1..])),
+        _ => Some(PathBuf::from(path)),
+    }
+}
+
+fn parse_range(value: &JsonValue) -> Option<LspRange> {
+    Some(LspRange {
+        start: parse_position(get(value, "start")?)?,
+        end: parse_position(get(value, "end")?)?,
+    })
+}
+
+fn parse_position(value: &JsonValue) -> Option<LspPosition> {
+    Some(LspPosition {
+        line: as_u64(get(value, "line")?)? as usize,
+        character: as_u64(get(value, "character")?)? as usize,
+    })
+}
+
+fn get<'a>(value: &'a JsonValue, key: &str) -> Option<&'a JsonValue> {
+    match value {
+        JsonValue::Object(object) => object.get(key),
+        _ => None,
+    }
+}
+
+fn as_str(value: &JsonValue) -> Option<&str> {
+    match value {
+        JsonValue::String(string) => Some(string),
+        _ => None,
+    }
+}
+
+fn as_u64(value: &JsonValue) -> Option<u64> {
+    match *value {
+        JsonValue::U64(value) => Some(value),
+        JsonValue::I64(value) => u64::try_from(value).ok(),
+        _ => None,
+    }
+}
+
+fn as_array(value: &JsonValue) -> Option<&[JsonValue]> {
+    match value {
+        JsonValue::Array(array) => Some(array),
+        _ => None,
+    }
+}
@@ -2,11 +2,14 @@
 use {
     crate::{
         app::AppData,
+        build_manager::build_manager::ProfileSampleStore,
+        makepad_micro_serde::*,
         makepad_widgets::*,
+        makepad_platform::studio::SpanSample,
     },
     std::{
         fmt::Write,
-        env,
+        path::Path,
     },
 };
 
@@ -14,7 +17,7 @@ live_design!{
     import makepad_draw::shader::std::*;
     import makepad_widgets::base::*;
     import makepad_widgets::theme_desktop_dark::*;
-    
+
     ProfilerEventChart = {{ProfilerEventChart}}{
         height: Fill,
         width: Fill
@@ -41,7 +44,7 @@ live_design!{
                 return self.color
             }
         }
-        draw_time:{ 
+        draw_time:{
             color: #f,
             text_style: <THEME_FONT_LABEL>{}
         }
@@ -50,18 +53,36 @@ live_design!{
             text_style: <THEME_FONT_LABEL>{}
         }
     }
-    
+
     Profiler = {{Profiler}}{
         height: Fill,
         width: Fill
-        <ProfilerEventChart>{
+        flow: Down
+        <View>{
+            height: Fit,
+            width: Fill
+            flow: Right
+            spacing: 5
+            padding: 5
+            view_mode = <Button> {text: "Flame graph"}
+            save = <Button> {text: "Save capture"}
+            load = <Button> {text: "Compare with capture"}
+        }
+        chart = <ProfilerEventChart>{
         }
     }
 }
 
+const LANE_HEIGHT: f64 = 25.0;
+const LANE_FRAMES: f64 = 20.0;
+const LANE_EVENTS: f64 = LANE_FRAMES + LANE_HEIGHT;
+const LANE_GPU: f64 = LANE_EVENTS + LANE_HEIGHT;
+const LANE_DRAW_CALLS: f64 = LANE_GPU + LANE_HEIGHT;
+const LANE_SPANS: f64 = LANE_DRAW_CALLS + LANE_HEIGHT;
+
 #[derive(Clone)]
 struct TimeRange{
-    start:f64, 
+    start:f64,
     end: f64
 }
 
@@ -70,6 +91,90 @@ impl TimeRange{
     fn shifted(&self, shift:f64)->Self{Self{start:self.start+shift, end:self.end+shift}}
 }
 
+/// The samples of a list sorted by start time that overlap the range.
+fn visible<'a, T>(samples: &'a [T], range: &TimeRange, time: impl Fn(&T)->(f64, f64) + Copy)->impl Iterator<Item = &'a T>{
+    let (start, end) = (range.start, range.end);
+    let first = samples.iter().position(|v| time(v).1 > start).unwrap_or(samples.len());
+    samples[first..].iter().take_while(move |v| time(v).0 <= end)
+}
+
+fn write_duration(out: &mut String, time: f64){
+    if time > 0.001{
+        write!(out, "{:.2} ms", time * 1000.0).unwrap();
+    }
+    else{
+        write!(out, "{:.0} us", time * 1000000.0).unwrap();
+    }
+}
+
+fn name_color(name: &str)->Vec4{
+    Vec4::from_u32(LiveId(0).bytes_append(name.as_bytes()).0 as u32 | 0xff000000)
+}
+
+struct FlameNode{
+    name: String,
+    time: f64,
+    children: Vec<usize>,
+}
+
+/// The spans of a time range merged by their stack of names, node 0 is the root.
+struct FlameGraph{
+    nodes: Vec<FlameNode>,
+}
+
+impl FlameGraph{
+    fn from_spans(spans: &[SpanSample], range: &TimeRange)->Self{
+        let mut nodes = vec![FlameNode{name: String::new(), time: 0.0, children: Vec::new()}];
+        let mut stack = vec![0];
+        // the children of a span that overlaps the range can still end before it
+        for span in visible(spans, range, |v| (v.start, v.end)).filter(|v| v.end > range.start){
+            // a parent that started before the range is missing, hang its children higher up
+            stack.truncate((span.depth as usize + 1).min(stack.len()));
+            let parent = *stack.last().unwrap();
+            let node = match nodes[parent].children.iter().find(|c| nodes[**c].name == span.name){
+                Some(node) => *node,
+                None => {
+                    let node = nodes.len();
+                    nodes.push(FlameNode{name: span.name.clone(), time: 0.0, children: Vec::new()});
+                    nodes[parent].children.push(node);
+                    node
+                }
+            };
+            nodes[node].time += span.end.min(range.end) - span.start.max(range.start);
+            stack.push(node);
+        }
+        nodes[0].time = nodes[0].children.iter().map(|c| nodes[*c].time).sum();
+        Self{nodes}
+    }
+
+    fn child(&self, node: usize, name: &str)->Option<usize>{
+        self.nodes[node].children.iter().find(|c| self.nodes[**c].name == name).copied()
+    }
+}
+
+/// A flame graph with its times averaged over the frames it covers.
+struct FrameAverage{
+    graph: FlameGraph,
+    frames: f64,
+}
+
+impl FrameAverage{
+    fn new(store: &ProfileSampleStore, range: &TimeRange)->Self{
+        let frames = visible(&store.frames, range, |v| (v.start, v.end)).count().max(1) as f64;
+        Self{graph: FlameGraph::from_spans(&store.spans, range), frames}
+    }
+
+    fn time(&self, node: usize)->f64{
+        self.graph.nodes[node].time / self.frames
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ChartViewMode{
+    Timeline,
+    Flame,
+}
+
 #[derive(Live, LiveHook, Widget)]
 struct ProfilerEventChart{
     #[walk] walk:Walk,
@@ -78,102 +183,187 @@ struct ProfilerEventChart{
     #[live] draw_item: DrawColor,
     #[live] draw_label: DrawText,
     #[live] draw_time: DrawText,
-    #[rust(TimeRange{start:0.0, end: 1.0})] time_range: TimeRange, 
+    #[rust(TimeRange{start:0.0, end: 1.0})] time_range: TimeRange,
     #[rust] time_drag: Option<TimeRange>,
+    #[rust(ChartViewMode::Timeline)] view_mode: ChartViewMode,
+    #[rust] selected_frame: Option<u64>,
+    #[rust] compare: Option<ProfileSampleStore>,
     #[rust] tmp_label: String,
 }
 
 impl ProfilerEventChart{
-    fn draw_block(&mut self, cx: &mut Cx2d, rect:&Rect, sample_start:f64, sample_end: f64, label:&str){
+    fn draw_bar(&mut self, cx: &mut Cx2d, rect: Rect, label: &str){
+        self.draw_item.draw_abs(cx, rect);
+        // if the bar is wide enough lets draw a clipped piece of text
+        if rect.size.x > 10.0{
+            cx.begin_turtle(Walk::abs_rect(rect), Layout::default());
+            self.draw_label.draw_abs(cx, rect.pos+dvec2(2.0,4.0), label);
+            cx.end_turtle();
+        }
+    }
+
+    fn draw_block(&mut self, cx: &mut Cx2d, rect:&Rect, ypos: f64, sample_start:f64, sample_end: f64, label:&str){
         let scale = rect.size.x / self.time_range.len();
         let xpos = rect.pos.x + (sample_start - self.time_range.start) * scale;
         let xsize = ((sample_end - sample_start) * scale).max(2.0);
-        
-        let pos = dvec2(xpos, rect.pos.y+20.0);
-        let size = dvec2(xsize, 20.0);
-        let rect = Rect{pos,size};
-                
-        self.draw_item.draw_abs(cx, rect);
-        self.tmp_label.clear();
-        if sample_end - sample_start > 0.001{
-            write!(&mut self.tmp_label, "{} {:.2} ms", label, (sample_end-sample_start)*1000.0).unwrap();                        
+
+        let mut tmp_label = std::mem::take(&mut self.tmp_label);
+        tmp_label.clear();
+        write!(&mut tmp_label, "{} ", label).unwrap();
+        write_duration(&mut tmp_label, sample_end - sample_start);
+        self.draw_bar(cx, Rect{pos: dvec2(xpos, rect.pos.y + ypos), size: dvec2(xsize, 20.0)}, &tmp_label);
+        self.tmp_label = tmp_label;
+    }
+
+    fn draw_timeline(&mut self, cx: &mut Cx2d, rect: &Rect, pss: &ProfileSampleStore){
+        let scale = rect.size.x / self.time_range.len();
+        let mut label = String::new();
+
+        let mut step_size = 0.008;
+        while self.time_range.len() / step_size > rect.size.x / 80.0{
+            step_size *= 2.0;
         }
-        else{
-            write!(&mut self.tmp_label, "{} {:.0} ns", label, (sample_end-sample_start)*1000000.0).unwrap();
+
+        while self.time_range.len() / step_size < rect.size.x / 80.0{
+            step_size /= 2.0;
         }
-        
-        // if xsize > 10.0 lets draw a clipped piece of text 
-        if xsize > 10.0{
-            cx.begin_turtle(Walk::abs_rect(rect), Layout::default());
-            self.draw_label.draw_abs(cx, pos+dvec2(2.0,4.0), &self.tmp_label);
-            cx.end_turtle();
+
+        let mut iter = (self.time_range.start / step_size).floor() * step_size - self.time_range.start;
+        while iter < self.time_range.len(){
+            let xpos =  iter * scale;
+            let pos = dvec2(xpos,0.0)+rect.pos;
+            self.draw_line.draw_abs(cx, Rect{pos, size:dvec2(3.0, rect.size.y)});
+            label.clear();
+            write!(&mut label, "{:.3}s", (iter+self.time_range.start)).unwrap();
+            self.draw_time.draw_abs(cx, pos+dvec2(2.0,2.0), &label);
+            iter += step_size;
+        }
+
+        let range = self.time_range.clone();
+        for frame in visible(&pss.frames, &range, |v| (v.start, v.end)){
+            let selected = self.selected_frame == Some(frame.frame_id);
+            self.draw_item.color = Vec4::from_u32(if selected {0xffcf4fff} else {0x4f8fcfff});
+            label.clear();
+            write!(&mut label, "frame {} {} calls {} instances", frame.frame_id, frame.draw_calls, frame.instances).unwrap();
+            self.draw_block(cx, rect, LANE_FRAMES, frame.start, frame.end, &label);
+        }
+
+        for sample in visible(&pss.event, &range, |v| (v.start, v.end)){
+            let color = LiveId(0).bytes_append(&sample.event_u32.to_be_bytes()).0 as u32 | 0xff000000;
+            self.draw_item.color = Vec4::from_u32(color);
+            self.draw_block(cx, rect, LANE_EVENTS, sample.start, sample.end, Event::name_from_u32(sample.event_u32));
+        }
+
+        self.draw_item.color = Vec4::from_u32(0x7f7f7fff);
+        for sample in visible(&pss.gpu, &range, |v| (v.start, v.end)){
+            self.draw_block(cx, rect, LANE_GPU, sample.start, sample.end, "GPU");
+        }
+
+        for sample in visible(&pss.draw_calls, &range, |v| (v.start, v.end)){
+            self.draw_item.color = name_color(&sample.shader);
+            label.clear();
+            write!(&mut label, "{} x{}", sample.shader, sample.instances).unwrap();
+            self.draw_block(cx, rect, LANE_DRAW_CALLS, sample.start, sample.end, &label);
+        }
+
+        for span in visible(&pss.spans, &range, |v| (v.start, v.end)){
+            self.draw_item.color = name_color(&span.name);
+            self.draw_block(cx, rect, LANE_SPANS + span.depth as f64 * LANE_HEIGHT, span.start, span.end, &span.name);
+        }
+    }
+
+    /// The selected frame, or otherwise the visible part of the timeline.
+    fn flame_range(&self, pss: &ProfileSampleStore)->TimeRange{
+        self.selected_frame
+            .and_then(|id| pss.frames.iter().find(|v| v.frame_id == id))
+            .map(|v| TimeRange{start: v.start, end: v.end})
+            .unwrap_or_else(|| self.time_range.clone())
+    }
+
+    fn draw_flame(&mut self, cx: &mut Cx2d, rect: &Rect, pss: &ProfileSampleStore){
+        let range = self.flame_range(pss);
+        let current = FrameAverage::new(pss, &range);
+        let compare = self.compare.as_ref().map(|store| FrameAverage::new(store, &TimeRange{start: 0.0, end: f64::MAX}));
+
+        let mut label = String::new();
+        match self.selected_frame{
+            Some(frame_id) => write!(&mut label, "Spans of frame {}", frame_id).unwrap(),
+            None => write!(&mut label, "Spans of the visible range, per frame over {} frames", current.frames).unwrap()
+        }
+        if compare.is_some(){
+            label.push_str(", compared with the loaded capture");
+        }
+        self.draw_time.draw_abs(cx, rect.pos + dvec2(2.0, 2.0), &label);
+
+        let compare = compare.as_ref().map(|compare| (compare, 0));
+        let bar = Rect{pos: rect.pos + dvec2(0.0, LANE_FRAMES), size: rect.size};
+        self.draw_flame_children(cx, &current, 0, compare, bar);
+    }
+
+    fn draw_flame_children(&mut self, cx: &mut Cx2d, current: &FrameAverage, node: usize, compare: Option<(&FrameAverage, usize)>, rect: Rect){
+        let total = current.graph.nodes[node].time;
+        if total <= 0.0{
+            return
+        }
+        let mut xpos = rect.pos.x;
+        for &child in &current.graph.nodes[node].children{
+            let width = current.graph.nodes[child].time / total * rect.size.x;
+            let name = &current.graph.nodes[child].name;
+            let compare_child = compare.and_then(|(graph, node)| graph.graph.child(node, name).map(|c| (graph, c)));
+
+            let mut label = String::new();
+            write!(&mut label, "{} ", name).unwrap();
+            write_duration(&mut label, current.time(child));
+            if let Some((graph, c)) = compare_child{
+                label.push_str(" was ");
+                write_duration(&mut label, graph.time(c));
+            }
+            self.draw_item.color = name_color(name);
+            let bar = Rect{pos: dvec2(xpos, rect.pos.y), size: dvec2(width.max(2.0), 20.0)};
+            self.draw_bar(cx, bar, &label);
+
+            self.draw_flame_children(cx, current, child, compare_child, Rect{
+                pos: dvec2(xpos, rect.pos.y + LANE_HEIGHT),
+                size: dvec2(width, rect.size.y)
+            });
+            xpos += width;
         }
     }
+
+    fn select_frame(&mut self, cx: &mut Cx, pss: &ProfileSampleStore, time: f64){
+        let Some(frame) = pss.frames.iter().find(|v| v.start <= time && v.end >= time) else{
+            self.selected_frame = None;
+            self.draw_bg.redraw(cx);
+            return
+        };
+        self.selected_frame = Some(frame.frame_id);
+        // zoom in on the frame with some room on either side
+        let margin = (frame.end - frame.start) * 0.25;
+        self.time_range = TimeRange{start: frame.start - margin, end: frame.end + margin};
+        self.draw_bg.redraw(cx);
+    }
 }
 
 impl Widget for ProfilerEventChart {
     fn draw_walk(&mut self, cx: &mut Cx2d, scope:&mut Scope, walk:Walk)->DrawStep{
         self.draw_bg.begin(cx, walk, Layout::default());
         let bm = &scope.data.get::<AppData>().build_manager;
-        let mut label = String::new();
-        
-        let rect = cx.turtle().rect(); 
+
+        let rect = cx.turtle().rect();
         if let Some(pss) = bm.profile.values().next(){
-            let scale = rect.size.x / self.time_range.len();
-                            
-            let mut step_size = 0.008;
-            while self.time_range.len() / step_size > rect.size.x / 80.0{
-                step_size *= 2.0;
-            }
-                            
-            while self.time_range.len() / step_size < rect.size.x / 80.0{
-                step_size /= 2.0;
-            }
-                        
-            let mut iter = (self.time_range.start / step_size).floor() * step_size - self.time_range.start;
-            while iter < self.time_range.len(){
-                let xpos =  iter * scale;
-                let pos = dvec2(xpos,0.0)+rect.pos;
-                self.draw_line.draw_abs(cx, Rect{pos, size:dvec2(3.0, rect.size.y)});
-                label.clear();
-                write!(&mut label, "{:.3}s", (iter+self.time_range.start)).unwrap();       
-                self.draw_time.draw_abs(cx, pos+dvec2(2.0,2.0), &label);
-                iter += step_size; 
-            }
-            
-            if let Some(first) = pss.event.iter().position(|v| v.end > self.time_range.start){
-                // lets draw the time lines and time text
-                for i in first..pss.event.len(){
-                    let sample = &pss.event[i];
-                    if sample.start > self.time_range.end{
-                        break;
-                    }
-                    let color = LiveId(0).bytes_append(&sample.event_u32.to_be_bytes()).0 as u32 | 0xff000000;
-                    self.draw_item.color = Vec4::from_u32(color);
-                    self.draw_block(cx, &rect, sample.start, sample.end, Event::name_from_u32(sample.event_u32));
-                }
-            }
-            
-            self.draw_item.color = Vec4::from_u32(0x7f7f7fff);
-            if let Some(first) = pss.gpu.iter().position(|v| v.end > self.time_range.start){
-                // lets draw the time lines and time text
-                for i in first..pss.gpu.len(){
-                    let sample = &pss.gpu[i];
-                    if sample.start > self.time_range.end{
-                        break;
-                    }
-                    self.draw_block(cx, &Rect{
-                        pos:rect.pos + dvec2(0.0,25.0),
-                        size:rect.size
-                    }, sample.start, sample.end, "GPU");
-                }
+            match self.view_mode{
+                ChartViewMode::Timeline => self.draw_timeline(cx, &rect, pss),
+                ChartViewMode::Flame => self.draw_flame(cx, &rect, pss),
             }
         }
         self.draw_bg.end(cx);
         DrawStep::done()
     }
-        
-    fn handle_event(&mut self, cx: &mut Cx, event: &Event, _scope: &mut Scope){
+
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope){
+        if self.view_mode == ChartViewMode::Flame{
+            return
+        }
         match event.hits(cx, self.draw_bg.area()) {
             Hit::FingerDown(_fe) => {
                 // ok so we get multiple finger downs
@@ -199,10 +389,25 @@ impl Widget for ProfilerEventChart {
                    self.time_range = TimeRange{
                        start: (self.time_range.start - time) * zoom + time,
                        end: (self.time_range.end - time) * zoom + time,
-                   }
+                   };
+                   self.draw_bg.redraw(cx);
                }
             }
-            Hit::FingerUp(_) => {
+            Hit::FingerUp(fe) => {
+                self.time_drag = None;
+                // tapping the frame lane selects a frame, tapping it again clears the selection
+                let ypos = fe.abs.y - fe.rect.pos.y;
+                if fe.was_tap() && (LANE_FRAMES..LANE_FRAMES + 20.0).contains(&ypos){
+                    let time = self.time_range.len() / fe.rect.size.x * (fe.abs.x - fe.rect.pos.x) + self.time_range.start;
+                    let bm = &scope.data.get::<AppData>().build_manager;
+                    if let Some(pss) = bm.profile.values().next(){
+                        let was_selected = self.selected_frame;
+                        self.select_frame(cx, pss, time);
+                        if was_selected.is_some() && was_selected == self.selected_frame{
+                            self.selected_frame = None;
+                        }
+                    }
+                }
             }
             _ => ()
         }
@@ -212,6 +417,46 @@ impl Widget for ProfilerEventChart {
 #[derive(Live, LiveHook, Widget)]
 struct Profiler{
     #[deref] view:View,
+    #[rust] file_dialog: Option<FileDialogKind>,
+}
+
+impl Profiler{
+    fn capture_dialog(title: &str)->FileDialog{
+        FileDialog::new()
+            .set_title(title.to_string())
+            .add_filter("Profiler capture".to_string(), vec!["mpcap".to_string()])
+    }
+
+    fn save_capture(&mut self, scope: &mut Scope, path: &Path){
+        let bm = &scope.data.get::<AppData>().build_manager;
+        let Some(pss) = bm.profile.values().next() else{
+            log!("There are no profile samples to save");
+            return
+        };
+        if let Err(err) = std::fs::write(path, pss.serialize_bin()){
+            log!("Cannot save profiler capture {}: {}", path.display(), err);
+        }
+    }
+
+    fn load_capture(&mut self, cx: &mut Cx, path: &Path){
+        let capture = match std::fs::read(path){
+            Ok(data) => match ProfileSampleStore::deserialize_bin(&data){
+                Ok(capture) => capture,
+                Err(err) => {
+                    log!("Cannot read profiler capture {}: {:?}", path.display(), err);
+                    return
+                }
+            },
+            Err(err) => {
+                log!("Cannot open profiler capture {}: {}", path.display(), err);
+                return
+            }
+        };
+        if let Some(mut chart) = self.widget(id!(chart)).borrow_mut::<ProfilerEventChart>(){
+            chart.compare = Some(capture);
+            chart.redraw(cx);
+        }
+    }
 }
 
 impl Widget for Profiler {
@@ -219,8 +464,135 @@ impl Widget for Profiler {
         self.view.draw_walk_all(cx, scope, walk);
         DrawStep::done()
     }
-    
+
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope){
         self.view.handle_event(cx, event, scope);
+        if let Event::Actions(actions) = event{
+            if self.button(id!(view_mode)).clicked(actions){
+                if let Some(mut chart) = self.widget(id!(chart)).borrow_mut::<ProfilerEventChart>(){
+                    chart.view_mode = match chart.view_mode{
+                        ChartViewMode::Timeline => ChartViewMode::Flame,
+                        ChartViewMode::Flame => ChartViewMode::Timeline,
+                    };
+                    self.button(id!(view_mode)).set_text(match chart.view_mode{
+                        ChartViewMode::Timeline => "Flame graph",
+                        ChartViewMode::Flame => "Timeline",
+                    });
+                    chart.redraw(cx);
+                }
+                self.redraw(cx);
+            }
+            if self.button(id!(save)).clicked(actions){
+                self.file_dialog = Some(FileDialogKind::SaveFile);
+                cx.open_file_dialog(FileDialogKind::SaveFile, Self::capture_dialog("Save Profiler Capture")
+                    .set_filename("profile.mpcap".to_string()));
+            }
+            if self.button(id!(load)).clicked(actions){
+                self.file_dialog = Some(FileDialogKind::SelectFile);
+                cx.open_file_dialog(FileDialogKind::SelectFile, Self::capture_dialog("Compare With Profiler Capture"));
+            }
+        }
+        if let Event::FileDialogResult(result) = event{
+            if self.file_dialog == Some(result.kind){
+                self.file_dialog = None;
+                match (result.kind, &result.path){
+                    (FileDialogKind::SaveFile, Some(path)) => self.save_capture(scope, path),
+                    (FileDialogKind::SelectFile, Some(path)) => self.load_capture(cx, path),
+                    _ => ()
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use makepad_platform::studio::{EventSample, GPUSample, GPUDrawCallSample, FrameSample};
+
+    fn span(name: &str, depth: u32, start: f64, end: f64)->SpanSample{
+        SpanSample{name: name.to_string(), depth, start, end}
+    }
+
+    // the child names and times of a node, in the order they were first seen
+    fn children(graph: &FlameGraph, node: usize)->Vec<(String, f64)>{
+        graph.nodes[node].children.iter().map(|c| (graph.nodes[*c].name.clone(), graph.nodes[*c].time)).collect()
+    }
+
+    #[test]
+    fn flame_graph_merges_nested_spans(){
+        let spans = vec![
+            span("draw", 0, 0.0, 10.0),
+            span("list", 1, 1.0, 4.0),
+            span("item", 2, 1.0, 2.0),
+            span("item", 2, 2.0, 4.0),
+            span("button", 1, 5.0, 9.0),
+            span("draw", 0, 12.0, 14.0),
+            span("list", 1, 12.0, 13.0),
+        ];
+        let graph = FlameGraph::from_spans(&spans, &TimeRange{start: 0.0, end: 20.0});
+        assert_eq!(graph.nodes[0].time, 12.0);
+        assert_eq!(children(&graph, 0), vec![("draw".to_string(), 12.0)]);
+        let draw = graph.child(0, "draw").unwrap();
+        assert_eq!(children(&graph, draw), vec![("list".to_string(), 4.0), ("button".to_string(), 4.0)]);
+        let list = graph.child(draw, "list").unwrap();
+        assert_eq!(children(&graph, list), vec![("item".to_string(), 3.0)]);
+        assert_eq!(graph.child(draw, "item"), None);
+    }
+
+    #[test]
+    fn flame_graph_clips_spans_to_range(){
+        let spans = vec![
+            span("draw", 0, 0.0, 10.0),
+            span("early", 1, 1.0, 3.0),
+            span("late", 1, 4.0, 9.0),
+            span("next", 0, 11.0, 20.0),
+            span("after", 0, 21.0, 22.0),
+        ];
+        let graph = FlameGraph::from_spans(&spans, &TimeRange{start: 5.0, end: 15.0});
+        assert_eq!(children(&graph, 0), vec![("draw".to_string(), 5.0), ("next".to_string(), 4.0)]);
+        let draw = graph.child(0, "draw").unwrap();
+        assert_eq!(children(&graph, draw), vec![("late".to_string(), 4.0)]);
+        assert_eq!(graph.nodes[0].time, 9.0);
+    }
+
+    #[test]
+    fn spans_sorted_as_they_arrive(){
+        // spans close inner first, and a later batch can hold the parent of an earlier one
+        let mut store = ProfileSampleStore{
+            spans: vec![span("item", 1, 1.0, 2.0), span("list", 0, 0.0, 3.0)],
+            ..Default::default()
+        };
+        store.sort_spans_from(0);
+        store.spans.extend([
+            span("item", 1, 4.0, 5.0),
+            span("label", 2, 5.0, 5.5),
+            span("item", 1, 5.0, 6.0),
+            span("list", 0, 4.0, 7.0),
+        ]);
+        store.sort_spans_from(2);
+        let order: Vec<(&str, u32, f64)> = store.spans.iter().map(|v| (v.name.as_str(), v.depth, v.start)).collect();
+        assert_eq!(order, vec![
+            ("list", 0, 0.0),
+            ("item", 1, 1.0),
+            ("list", 0, 4.0),
+            ("item", 1, 4.0),
+            ("item", 1, 5.0),
+            ("label", 2, 5.0),
+        ]);
+    }
+
+    #[test]
+    fn capture_round_trips(){
+        let store = ProfileSampleStore{
+            event: vec![EventSample{event_u32: 3, start: 0.5, end: 0.75}],
+            gpu: vec![GPUSample{start: 1.0, end: 1.5}],
+            spans: vec![span("draw", 0, 0.0, 10.0), span("list", 1, 1.0, 4.0)],
+            draw_calls: vec![GPUDrawCallSample{frame_id: 7, shader: "DrawQuad".to_string(), instances: 12, start: 2.0, end: 2.25}],
+            frames: vec![FrameSample{frame_id: 7, start: 0.0, end: 16.0, draw_calls: 1, instances: 12}],
+        };
+        let loaded = ProfileSampleStore::deserialize_bin(&store.serialize_bin()).unwrap();
+        assert_eq!(format!("{:?}", loaded), format!("{:?}", store));
+        assert!(ProfileSampleStore::deserialize_bin(&store.serialize_bin()[..10]).is_err());
     }
 }
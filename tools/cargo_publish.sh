@@ -61,6 +61,8 @@ cargo publish $DRYRUN -p makepad-math
 #cargo publish $DRYRUN -p makepad-zune-jpeg
 #echo "---- PUBLISHING makepad-zune-png----" 
 #cargo publish $DRYRUN -p makepad-zune-png
+#echo "---- PUBLISHING makepad-image-formats----" 
+#cargo publish $DRYRUN -p makepad-image-formats
 
 #echo "---- PUBLISHING makepad-studio----" 
 #cargo publish $DRYRUN -p makepad-studio
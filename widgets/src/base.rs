@@ -11,6 +11,7 @@ live_design!{
     import crate::drop_down::DropDownBase;
     import crate::file_tree::FileTreeBase;
     import crate::file_tree::FileTreeNodeBase;
+    import crate::file_dialog::FileDialogViewBase;
     import crate::fold_button::FoldButtonBase;
     import crate::fold_header::FoldHeaderBase;
     import crate::image::ImageBase;
@@ -21,12 +22,15 @@ live_design!{
     import crate::link_label::LinkLabelBase;
     import crate::portal_list::PortalListBase;
     import crate::flat_list::FlatListBase;
+    import crate::data_table::DataTableBase;
+    import crate::data_table::DataTableColumnBase;
     import crate::scroll_bars::ScrollBarsBase;
     import crate::view::ViewBase;
     import crate::nav_control::NavControlBase;
     import crate::popup_menu::PopupMenuItemBase;
     import crate::popup_menu::PopupMenuBase;
     import crate::radio_button::RadioButtonBase;
+    import crate::radio_button::RadioButtonGroupBase;
     import crate::scroll_bar::ScrollBarBase;
     import crate::scroll_bars::ScrollBarsBase;
     import crate::slide_panel::SlidePanelBase;
@@ -45,6 +49,7 @@ live_design!{
     import crate::keyboard_view::KeyboardViewBase;
     import crate::window_menu::WindowMenuBase;
     import crate::html::HtmlBase;
+    import crate::markdown::MarkdownBase;
     
     import makepad_draw::shader::std::*;
     import makepad_draw::shader::draw_color::DrawColor;
@@ -667,6 +672,7 @@ live_design!{
     // todo fix this by allowing reexporting imports
     // for now this works too
     HtmlBase = <HtmlBase>{}
+    MarkdownBase = <MarkdownBase>{}
     KeyboardViewBase = <KeyboardViewBase>{}
     PageFlipBase = <PageFlipBase>{}
     ViewBase = <ViewBase>{}
@@ -679,6 +685,7 @@ live_design!{
     DropDownBase = <DropDownBase> {}
     FileTreeBase = <FileTreeBase> {}
     FileTreeNodeBase = <FileTreeNodeBase> {}
+    FileDialogViewBase = <FileDialogViewBase> {}
     FoldButtonBase = <FoldButtonBase> {}
     FoldHeaderBase = <FoldHeaderBase> {}
     ImageBase = <ImageBase> {}
@@ -688,10 +695,13 @@ live_design!{
     LinkLabelBase = <LinkLabelBase> {}
     PortalListBase = <PortalListBase> {}
     FlatListBase = <FlatListBase>{}
+    DataTableBase = <DataTableBase>{}
+    DataTableColumnBase = <DataTableColumnBase>{}
     NavControlBase = <NavControlBase> {}
     PopupMenuBase = <PopupMenuBase> {}
     PopupMenuItemBase = <PopupMenuItemBase> {}
     RadioButtonBase = <RadioButtonBase> {}
+    RadioButtonGroupBase = <RadioButtonGroupBase> {}
     ScrollBarBase = <ScrollBarBase> {}
     ScrollBarsBase = <ScrollBarsBase> {}
     SlidePanelBase = <SlidePanelBase> {}   
@@ -35,6 +35,8 @@ pub struct Button {
 
     #[live(true)]
     grab_key_focus: bool,
+    #[live]
+    tab_index: i64,
 
     #[live]
     pub text: RcStringMut,
@@ -75,6 +77,15 @@ impl Widget for Button {
                     self.animator_play(cx, id!(hover.off));
                 }
             }
+            Hit::KeyDown(KeyEvent {key_code: KeyCode::Space | KeyCode::ReturnKey, ..}) |
+            Hit::AccessAction(AccessAction::Click) => {
+                cx.widget_action(uid, &scope.path, ButtonAction::Pressed);
+                cx.widget_action(uid, &scope.path, ButtonAction::Clicked);
+                cx.widget_action(uid, &scope.path, ButtonAction::Released);
+            }
+            Hit::AccessAction(AccessAction::Focus) => {
+                cx.set_key_focus(self.draw_bg.area());
+            }
             _ => (),
         }
     }
@@ -85,6 +96,17 @@ impl Widget for Button {
             .draw_walk(cx, self.label_walk, Align::default(), self.text.as_ref());
         self.draw_icon.draw_walk(cx, self.icon_walk);
         self.draw_bg.end(cx);
+        if self.grab_key_focus {
+            if let Some(order) = NavOrder::from_tab_index(self.tab_index) {
+                cx.push_nav_stop(NavStop::new(self.draw_bg.area(), NavRole::Button).with_order(order));
+            }
+        }
+        cx.add_access_node(
+            AccessNode::new(self.widget_uid().0, AccessRole::Button, self.draw_bg.area())
+                .with_name(self.text.as_ref())
+                .with_states(AccessStates {focusable: self.grab_key_focus, ..Default::default()})
+                .with_actions(AccessActions {click: true, focus: true, ..Default::default()})
+        );
         DrawStep::done()
     }
 
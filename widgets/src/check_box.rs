@@ -47,6 +47,7 @@ pub struct CheckBox {
     #[live] draw_icon: DrawIcon,
     
     #[live] text: RcStringMut,
+    #[live] tab_index: i64,
     
     #[live] bind: String,
 }
@@ -71,6 +72,20 @@ impl CheckBox {
         self.draw_text.draw_walk(cx, self.label_walk, self.label_align, self.text.as_ref());
         self.draw_icon.draw_walk(cx, self.icon_walk);
         self.draw_check.end(cx);
+        let role = match self.draw_check.check_type {
+            CheckType::Radio => AccessRole::RadioButton,
+            _ => AccessRole::CheckBox
+        };
+        let checked = self.animator_in_state(cx, id!(selected.on));
+        if let Some(order) = NavOrder::from_tab_index(self.tab_index) {
+            cx.push_nav_stop(NavStop::new(self.draw_check.area(), NavRole::CheckBox).with_order(order));
+        }
+        cx.add_access_node(
+            AccessNode::new(self.widget_uid().0, role, self.draw_check.area())
+                .with_name(self.text.as_ref())
+                .with_states(AccessStates {checked: Some(checked), ..Default::default()})
+                .with_actions(AccessActions {click: true, ..Default::default()})
+        );
     }
 }
 
@@ -106,7 +121,9 @@ impl Widget for CheckBox {
             Hit::FingerHoverOut(_) => {
                 self.animator_play(cx, id!(hover.off));
             },
-            Hit::FingerDown(_fe) => {
+            Hit::FingerDown(_) |
+            Hit::KeyDown(KeyEvent {key_code: KeyCode::Space, ..}) |
+            Hit::AccessAction(AccessAction::Click) => {
                 if self.animator_in_state(cx, id!(selected.on)) {
                     self.animator_play(cx, id!(selected.off));
                     cx.widget_action(uid, &scope.path, CheckBoxAction::Change(false));
@@ -0,0 +1,909 @@
+use {
+    std::ops::Range,
+    crate::{
+        widget::*,
+        makepad_derive_widget::*,
+        makepad_draw::*,
+        scroll_bar::{ScrollBar, ScrollAxis, ScrollBarAction}
+    }
+};
+
+live_design!{
+    DrawDataTableRow = {{DrawDataTableRow}} {}
+    DrawDataTableHeader = {{DrawDataTableHeader}} {}
+    DrawDataTableSort = {{DrawDataTableSort}} {}
+    DrawDataTableText = {{DrawDataTableText}} {}
+    DataTableColumnBase = {{DataTableColumn}} {}
+    DataTableBase = {{DataTable}} {}
+}
+
+/// Where a `DataTable` gets its rows from. Only the cells on screen are asked for, so the
+/// row count can be as large as the data is.
+pub trait DataTableSource {
+    fn row_count(&self) -> usize;
+
+    /// Writes the text of a cell into `out`, which is empty when this is called.
+    fn cell_text(&self, row: usize, column: LiveId, out: &mut String);
+
+    /// Called with the rows about to be drawn, before any of their cells are asked for.
+    /// Sources that page their data in can fetch here.
+    fn visible_rows(&mut self, _rows: Range<usize>) {}
+}
+
+#[derive(Live, LiveHook, LiveRegister)]
+#[repr(C)]
+pub struct DrawDataTableRow {
+    #[deref] draw_super: DrawQuad,
+    #[live] is_odd: f32,
+    #[live] selected: f32,
+    #[live] hover: f32,
+    #[live] focus: f32,
+}
+
+#[derive(Live, LiveHook, LiveRegister)]
+#[repr(C)]
+pub struct DrawDataTableHeader {
+    #[deref] draw_super: DrawQuad,
+    #[live] hover: f32,
+    #[live] pressed: f32,
+}
+
+#[derive(Live, LiveHook, LiveRegister)]
+#[repr(C)]
+pub struct DrawDataTableSort {
+    #[deref] draw_super: DrawQuad,
+    #[live] descending: f32,
+}
+
+#[derive(Live, LiveHook, LiveRegister)]
+#[repr(C)]
+pub struct DrawDataTableText {
+    #[deref] draw_super: DrawText,
+    #[live] selected: f32,
+}
+
+/// A column of a `DataTable`, declared as a named child of the table. The name is the
+/// column id handed to the `DataTableSource`.
+#[derive(Live, LiveHook, LiveRegister)]
+pub struct DataTableColumn {
+    #[rust] pub id: LiveId,
+    #[live] pub label: String,
+    #[live(120.0)] pub width: f64,
+    #[live(30.0)] pub min_width: f64,
+    #[live(true)] pub resizable: bool,
+    #[live(true)] pub sortable: bool,
+    #[live] pub align: Align,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Live, LiveHook)]
+#[live_ignore]
+pub enum DataTableSelectionMode {
+    None,
+    Single,
+    #[pick] Multi,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum DataTableAction {
+    /// The sort order was changed from a header. The table doesn't reorder anything itself,
+    /// the source is expected to sort and the table to be redrawn.
+    Sort {column: LiveId, direction: SortDirection},
+    SelectionChanged,
+    /// A row was double clicked or had return pressed on it.
+    RowActivated(usize),
+    ColumnResized {column: LiveId, width: f64},
+    ColumnMoved {column: LiveId, index: usize},
+    None
+}
+
+/// The selected rows as sorted, non-touching ranges, so selecting all of a large table
+/// stays small.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DataTableSelection {
+    ranges: Vec<Range<usize>>,
+    anchor: Option<usize>,
+    cursor: Option<usize>,
+}
+
+impl DataTableSelection {
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.ranges.iter().map( | range | range.len()).sum()
+    }
+
+    pub fn ranges(&self) -> &[Range<usize>] {
+        &self.ranges
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = usize> + '_ {
+        self.ranges.iter().flat_map( | range | range.clone())
+    }
+
+    /// The row keyboard navigation moves from.
+    pub fn cursor(&self) -> Option<usize> {
+        self.cursor
+    }
+
+    pub fn contains(&self, row: usize) -> bool {
+        let index = self.ranges.partition_point( | range | range.end <= row);
+        self.ranges.get(index).is_some_and( | range | range.start <= row)
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn select(&mut self, row: usize) {
+        self.ranges.clear();
+        self.ranges.push(row..row + 1);
+        self.anchor = Some(row);
+        self.cursor = Some(row);
+    }
+
+    pub fn toggle(&mut self, row: usize) {
+        if self.contains(row) {
+            self.remove(row);
+        }
+        else {
+            self.insert(row..row + 1);
+        }
+        self.anchor = Some(row);
+        self.cursor = Some(row);
+    }
+
+    /// Selects the rows from the anchor to `row`, replacing the rest of the selection.
+    pub fn extend_to(&mut self, row: usize) {
+        let anchor = self.anchor.unwrap_or(row);
+        self.ranges.clear();
+        self.ranges.push(anchor.min(row)..anchor.max(row) + 1);
+        self.anchor = Some(anchor);
+        self.cursor = Some(row);
+    }
+
+    pub fn select_all(&mut self, row_count: usize) {
+        self.ranges.clear();
+        if row_count > 0 {
+            self.ranges.push(0..row_count);
+        }
+    }
+
+    /// Drops the rows past the end of the data, after it got shorter.
+    pub fn truncate(&mut self, row_count: usize) {
+        self.ranges.retain_mut( | range | {
+            range.end = range.end.min(row_count);
+            range.start < range.end
+        });
+        if self.anchor.is_some_and( | anchor | anchor >= row_count) {
+            self.anchor = None;
+        }
+        if self.cursor.is_some_and( | cursor | cursor >= row_count) {
+            self.cursor = None;
+        }
+    }
+
+    fn insert(&mut self, new: Range<usize>) {
+        self.ranges.push(new);
+        self.ranges.sort_by_key( | range | range.start);
+        let mut merged: Vec<Range<usize>> = Vec::with_capacity(self.ranges.len());
+        for range in self.ranges.drain(..) {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range)
+            }
+        }
+        self.ranges = merged;
+    }
+
+    fn remove(&mut self, row: usize) {
+        let index = self.ranges.partition_point( | range | range.end <= row);
+        let range = self.ranges[index].clone();
+        let mut split = Vec::new();
+        if range.start < row {
+            split.push(range.start..row);
+        }
+        if row + 1 < range.end {
+            split.push(row + 1..range.end);
+        }
+        self.ranges.splice(index..index + 1, split);
+    }
+}
+
+/// The columns that overlap `from..to`, given their left edges followed by the total width.
+fn overlapping_columns(offsets: &[f64], from: f64, to: f64) -> Range<usize> {
+    let count = offsets.len() - 1;
+    let start = offsets[1..].partition_point( | right | *right <= from);
+    let end = offsets[..count].partition_point( | left | *left < to);
+    start..end.max(start)
+}
+
+enum HeaderDrag {
+    Press {column: usize, start: f64},
+    Reorder {column: usize, target: usize},
+    Resize {column: usize, width: f64, start: f64},
+}
+
+#[derive(Live, Widget)]
+pub struct DataTable {
+    #[redraw] #[rust] area: Area,
+    #[walk] walk: Walk,
+    #[layout] layout: Layout,
+
+    #[live] draw_bg: DrawColor,
+    #[live] draw_row: DrawDataTableRow,
+    #[live] draw_header: DrawDataTableHeader,
+    #[live] draw_sort: DrawDataTableSort,
+    #[live] draw_text: DrawDataTableText,
+    #[live] draw_header_text: DrawText,
+    #[live] draw_drop: DrawColor,
+
+    #[live(24.0)] row_height: f64,
+    #[live(26.0)] header_height: f64,
+    #[live] cell_padding: Padding,
+    #[live(8.0)] sort_indicator_size: f64,
+    #[live(6.0)] resize_handle_width: f64,
+    #[live(4.0)] min_drag_distance: f64,
+    /// How many of the leftmost columns stay in place when scrolling sideways.
+    #[live(1usize)] sticky_columns: usize,
+    #[live] selection_mode: DataTableSelectionMode,
+    #[live] tab_index: i64,
+    #[live] scroll_bar_x: ScrollBar,
+    #[live] scroll_bar_y: ScrollBar,
+
+    #[rust] columns: Vec<DataTableColumn>,
+    /// The display order, as indices into `columns`.
+    #[rust] order: Vec<usize>,
+    #[rust] sort: Option<(LiveId, SortDirection)>,
+    #[rust] selection: DataTableSelection,
+    #[rust] scroll_pos: DVec2,
+    #[rust] row_count: usize,
+    #[rust] hover_row: Option<usize>,
+    #[rust] hover_header: Option<usize>,
+    #[rust] header_drag: Option<HeaderDrag>,
+    #[rust] draw_state: DrawStateWrap<()>,
+    #[rust] access_group: Option<AccessGroupIndex>,
+    // the geometry of the last draw, for hit testing
+    #[rust] rect: Rect,
+    #[rust] offsets: Vec<f64>,
+    #[rust] sticky: usize,
+    #[rust] cell_buf: String,
+}
+
+impl LiveHook for DataTable {
+    // the named children of the table are its columns
+    fn apply_value_instance(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) -> usize {
+        let id = nodes[index].id;
+        match apply.from {
+            ApplyFrom::NewFromDoc {..} | ApplyFrom::UpdateFromDoc {..} => {
+                if nodes[index].origin.has_prop_type(LivePropType::Instance) {
+                    if let Some(column) = self.columns.iter_mut().find( | column | column.id == id) {
+                        return column.apply(cx, apply, index, nodes);
+                    }
+                    let mut column = DataTableColumn::new(cx);
+                    let next = column.apply(cx, apply, index, nodes);
+                    column.id = id;
+                    self.order.push(self.columns.len());
+                    self.columns.push(column);
+                    return next;
+                }
+                else {
+                    cx.apply_error_no_matching_field(live_error_origin!(), index, nodes);
+                }
+            }
+            _ => ()
+        }
+        nodes.skip_node(index)
+    }
+}
+
+impl DataTable {
+    fn column(&self, display: usize) -> &DataTableColumn {
+        &self.columns[self.order[display]]
+    }
+
+    fn compute_offsets(&mut self) {
+        self.offsets.clear();
+        let mut x = 0.0;
+        for display in 0..self.order.len() {
+            self.offsets.push(x);
+            x += self.column(display).width;
+        }
+        self.offsets.push(x);
+        self.sticky = self.sticky_columns.min(self.order.len());
+    }
+
+    fn sticky_width(&self) -> f64 {
+        self.offsets.get(self.sticky).copied().unwrap_or(0.0)
+    }
+
+    fn body_rect(&self) -> Rect {
+        Rect {
+            pos: dvec2(self.rect.pos.x, self.rect.pos.y + self.header_height),
+            size: dvec2(self.rect.size.x, (self.rect.size.y - self.header_height).max(0.0))
+        }
+    }
+
+    fn total_size(&self) -> DVec2 {
+        dvec2(self.offsets.last().copied().unwrap_or(0.0), self.row_count as f64 * self.row_height)
+    }
+
+    fn clamp_scroll(&mut self) {
+        let body = self.body_rect();
+        let total = self.total_size();
+        self.scroll_pos.x = self.scroll_pos.x.min(total.x - body.size.x).max(0.0);
+        self.scroll_pos.y = self.scroll_pos.y.min(total.y - body.size.y).max(0.0);
+    }
+
+    /// Maps a window x position to the x position within the columns laid end to end.
+    fn content_x(&self, abs_x: f64) -> f64 {
+        let x = abs_x - self.rect.pos.x;
+        if x < self.sticky_width() {x} else {x + self.scroll_pos.x}
+    }
+
+    fn column_at(&self, abs_x: f64) -> Option<usize> {
+        if self.order.is_empty() {
+            return None
+        }
+        let x = self.content_x(abs_x);
+        let display = self.offsets[1..].partition_point( | right | *right <= x);
+        (display < self.order.len()).then_some(display)
+    }
+
+    fn resize_edge_at(&self, abs_x: f64) -> Option<usize> {
+        let display = self.column_at(abs_x)?;
+        let x = self.content_x(abs_x);
+        let half = self.resize_handle_width * 0.5;
+        let edge = if self.offsets[display + 1] - x < half {
+            display
+        }
+        else if display > 0 && x - self.offsets[display] < half {
+            display - 1
+        }
+        else {
+            return None
+        };
+        self.column(edge).resizable.then_some(edge)
+    }
+
+    fn is_in_header(&self, abs: DVec2) -> bool {
+        abs.y < self.rect.pos.y + self.header_height
+    }
+
+    fn row_at(&self, abs_y: f64) -> Option<usize> {
+        let y = abs_y - self.rect.pos.y - self.header_height;
+        if y < 0.0 {
+            return None
+        }
+        let row = ((y + self.scroll_pos.y) / self.row_height) as usize;
+        (row < self.row_count).then_some(row)
+    }
+
+    /// The display index a dragged header would be inserted at.
+    fn drop_target_at(&self, abs_x: f64) -> usize {
+        match self.column_at(abs_x) {
+            Some(display) => {
+                let x = self.content_x(abs_x);
+                if x > (self.offsets[display] + self.offsets[display + 1]) * 0.5 {display + 1} else {display}
+            }
+            None => self.order.len()
+        }
+    }
+
+    fn visible_rows(&self) -> Range<usize> {
+        let body = self.body_rect();
+        let start = (self.scroll_pos.y / self.row_height) as usize;
+        let end = ((self.scroll_pos.y + body.size.y) / self.row_height).ceil() as usize;
+        start.min(self.row_count)..end.min(self.row_count)
+    }
+
+    fn page_rows(&self) -> usize {
+        ((self.body_rect().size.y / self.row_height) as usize).max(1)
+    }
+
+    fn set_scroll(&mut self, cx: &mut Cx, scroll: DVec2) {
+        self.scroll_pos = scroll;
+        self.clamp_scroll();
+        self.scroll_bar_x.set_scroll_pos_no_action(cx, self.scroll_pos.x);
+        self.scroll_bar_y.set_scroll_pos_no_action(cx, self.scroll_pos.y);
+        self.area.redraw(cx);
+    }
+
+    pub fn scroll_to_row(&mut self, cx: &mut Cx, row: usize) {
+        let top = row as f64 * self.row_height;
+        let visible = self.body_rect().size.y;
+        let mut scroll = self.scroll_pos;
+        if top < scroll.y {
+            scroll.y = top;
+        }
+        else if top + self.row_height > scroll.y + visible {
+            scroll.y = top + self.row_height - visible;
+        }
+        self.set_scroll(cx, scroll);
+    }
+
+    pub fn selection(&self) -> &DataTableSelection {
+        &self.selection
+    }
+
+    /// Sets the sort indicator, for data that comes in sorted already.
+    pub fn set_sort(&mut self, cx: &mut Cx, sort: Option<(LiveId, SortDirection)>) {
+        self.sort = sort;
+        self.area.redraw(cx);
+    }
+
+    fn select_row(&mut self, cx: &mut Cx, row: usize, modifiers: KeyModifiers, scope: &mut Scope) {
+        let uid = self.widget_uid();
+        match self.selection_mode {
+            DataTableSelectionMode::None => return,
+            DataTableSelectionMode::Single => self.selection.select(row),
+            DataTableSelectionMode::Multi => if modifiers.shift {
+                self.selection.extend_to(row)
+            }
+            else if modifiers.control || modifiers.logo {
+                self.selection.toggle(row)
+            }
+            else {
+                self.selection.select(row)
+            }
+        }
+        self.scroll_to_row(cx, row);
+        cx.widget_action(uid, &scope.path, DataTableAction::SelectionChanged);
+    }
+
+    fn move_cursor(&mut self, cx: &mut Cx, delta: isize, modifiers: KeyModifiers, scope: &mut Scope) {
+        if self.row_count == 0 {
+            return
+        }
+        let cursor = self.selection.cursor().unwrap_or(0);
+        let row = cursor.saturating_add_signed(delta).min(self.row_count - 1);
+        // moving with control held would otherwise toggle every row passed
+        let modifiers = KeyModifiers {control: false, logo: false, ..modifiers};
+        self.select_row(cx, row, modifiers, scope);
+    }
+
+    fn click_header(&mut self, cx: &mut Cx, display: usize, scope: &mut Scope) {
+        let uid = self.widget_uid();
+        let column = self.column(display);
+        if !column.sortable {
+            return
+        }
+        let column = column.id;
+        let direction = match self.sort {
+            Some((id, SortDirection::Ascending)) if id == column => SortDirection::Descending,
+            _ => SortDirection::Ascending
+        };
+        self.sort = Some((column, direction));
+        // the rows are about to be reordered, so the selected indices no longer mean anything
+        self.selection.clear();
+        self.area.redraw(cx);
+        cx.widget_action(uid, &scope.path, DataTableAction::Sort {column, direction});
+    }
+
+    fn move_column(&mut self, cx: &mut Cx, from: usize, target: usize, scope: &mut Scope) {
+        let uid = self.widget_uid();
+        let to = if target > from {target - 1} else {target};
+        if to == from {
+            return
+        }
+        let column = self.order.remove(from);
+        self.order.insert(to, column);
+        self.area.redraw(cx);
+        cx.widget_action(uid, &scope.path, DataTableAction::ColumnMoved {column: self.columns[column].id, index: to});
+    }
+
+    fn draw_header_cell(&mut self, cx: &mut Cx2d, display: usize, x: f64) {
+        let width = self.offsets[display + 1] - self.offsets[display];
+        let rect = Rect {pos: dvec2(x, self.rect.pos.y), size: dvec2(width, self.header_height)};
+        let pressed = matches!(self.header_drag, Some(HeaderDrag::Press {column, ..}) | Some(HeaderDrag::Reorder {column, ..}) if column == display);
+        self.draw_header.hover = if self.hover_header == Some(display) {1.0} else {0.0};
+        self.draw_header.pressed = if pressed {1.0} else {0.0};
+        self.draw_header.draw_abs(cx, rect);
+
+        let column = &self.columns[self.order[display]];
+        let sort = self.sort.filter( | (id, _) | *id == column.id).map( | (_, direction) | direction);
+        let mut padding = self.cell_padding;
+        if sort.is_some() {
+            padding.right += self.sort_indicator_size + padding.left;
+        }
+        self.draw_header_text.draw_walk(cx, Walk {
+            abs_pos: Some(rect.pos),
+            width: Size::Fixed(width),
+            height: Size::Fixed(self.header_height),
+            ..Walk::default()
+        }.with_add_padding(padding), column.align, &column.label);
+
+        if let Some(direction) = sort {
+            let size = self.sort_indicator_size;
+            self.draw_sort.descending = if direction == SortDirection::Descending {1.0} else {0.0};
+            self.draw_sort.draw_abs(cx, Rect {
+                pos: dvec2(rect.pos.x + width - self.cell_padding.right - size, rect.pos.y + (self.header_height - size) * 0.5),
+                size: dvec2(size, size)
+            });
+        }
+    }
+
+    fn draw_rows(&mut self, cx: &mut Cx2d, source: &dyn DataTableSource, columns: Range<usize>, clip: Rect, scroll_x: f64) {
+        let body = self.body_rect();
+        let focus = if cx.has_key_focus(self.area) {1.0} else {0.0};
+        for row in self.visible_rows() {
+            let y = body.pos.y + row as f64 * self.row_height - self.scroll_pos.y;
+            let selected = self.selection.contains(row);
+            self.draw_row.is_odd = (row & 1) as f32;
+            self.draw_row.selected = if selected {1.0} else {0.0};
+            self.draw_row.hover = if self.hover_row == Some(row) {1.0} else {0.0};
+            self.draw_row.focus = focus;
+            self.draw_row.draw_abs(cx, Rect {pos: dvec2(clip.pos.x, y), size: dvec2(clip.size.x, self.row_height)});
+
+            self.draw_text.selected = self.draw_row.selected;
+            for display in columns.clone() {
+                let column = &self.columns[self.order[display]];
+                let x = self.rect.pos.x + self.offsets[display] - scroll_x;
+                let width = self.offsets[display + 1] - self.offsets[display];
+                self.cell_buf.clear();
+                source.cell_text(row, column.id, &mut self.cell_buf);
+                self.draw_text.draw_walk(cx, Walk {
+                    abs_pos: Some(dvec2(x, y)),
+                    width: Size::Fixed(width),
+                    height: Size::Fixed(self.row_height),
+                    ..Walk::default()
+                }.with_add_padding(self.cell_padding), column.align, &self.cell_buf);
+            }
+        }
+    }
+
+    /// Draws the visible part of `source`. Call this while the table is the current draw
+    /// step, the same way items are drawn into a `PortalList`.
+    pub fn draw_source(&mut self, cx: &mut Cx2d, source: &mut dyn DataTableSource) {
+        self.rect = cx.turtle().padded_rect();
+        self.row_count = source.row_count();
+        self.selection.truncate(self.row_count);
+        self.compute_offsets();
+        self.clamp_scroll();
+
+        self.draw_bg.draw_abs(cx, self.rect);
+        let body = self.body_rect();
+        let sticky_width = self.sticky_width().min(self.rect.size.x);
+        let sticky_clip = Rect {pos: body.pos, size: dvec2(sticky_width, body.size.y)};
+        let scroll_clip = Rect {
+            pos: dvec2(body.pos.x + sticky_width, body.pos.y),
+            size: dvec2(body.size.x - sticky_width, body.size.y)
+        };
+        let scrolled = {
+            let range = overlapping_columns(&self.offsets, sticky_width + self.scroll_pos.x, self.scroll_pos.x + self.rect.size.x);
+            range.start.max(self.sticky)..range.end.max(self.sticky)
+        };
+
+        let rows = self.visible_rows();
+        source.visible_rows(rows.clone());
+
+        // each part of the table is drawn in its own clipped turtle, so cells that scroll
+        // under the header or the sticky columns are cut off there
+        cx.begin_turtle(Walk::abs_rect(scroll_clip), Layout::default());
+        self.draw_rows(cx, source, scrolled.clone(), scroll_clip, self.scroll_pos.x);
+        cx.end_turtle();
+
+        cx.begin_turtle(Walk::abs_rect(sticky_clip), Layout::default());
+        self.draw_rows(cx, source, 0..self.sticky, sticky_clip, 0.0);
+        cx.end_turtle();
+
+        cx.begin_turtle(Walk::abs_rect(Rect {
+            pos: dvec2(self.rect.pos.x + sticky_width, self.rect.pos.y),
+            size: dvec2(self.rect.size.x - sticky_width, self.header_height)
+        }), Layout::default());
+        for display in scrolled {
+            let x = self.rect.pos.x + self.offsets[display] - self.scroll_pos.x;
+            self.draw_header_cell(cx, display, x);
+        }
+        cx.end_turtle();
+
+        cx.begin_turtle(Walk::abs_rect(Rect {
+            pos: self.rect.pos,
+            size: dvec2(sticky_width, self.header_height)
+        }), Layout::default());
+        for display in 0..self.sticky {
+            let x = self.rect.pos.x + self.offsets[display];
+            self.draw_header_cell(cx, display, x);
+        }
+        cx.end_turtle();
+
+        if let Some(HeaderDrag::Reorder {target, ..}) = self.header_drag {
+            let x = if target < self.sticky || target == 0 {
+                self.rect.pos.x + self.offsets[target]
+            }
+            else {
+                (self.rect.pos.x + self.offsets[target] - self.scroll_pos.x).max(self.rect.pos.x + sticky_width)
+            };
+            // DrawColor shares its shader with draw_bg, which would put this under the rows
+            self.draw_drop.new_draw_call(cx);
+            self.draw_drop.draw_abs(cx, Rect {
+                pos: dvec2(x - 1.0, self.rect.pos.y),
+                size: dvec2(2.0, self.rect.size.y)
+            });
+        }
+
+        self.draw_access(cx, source, rows);
+    }
+
+    fn draw_access(&mut self, cx: &mut Cx2d, source: &dyn DataTableSource, rows: Range<usize>) {
+        let uid = self.widget_uid().0;
+        for display in 0..self.order.len() {
+            let column = self.column(display);
+            cx.add_access_node(
+                AccessNode::new(LiveId(uid).id_append(column.id).0, AccessRole::ColumnHeader, Area::Empty)
+                    .with_name(column.label.as_str())
+            );
+        }
+        // rows are named after their first cell, the way screen readers announce them
+        if let Some(first) = self.order.first().map( | index | self.columns[*index].id) {
+            for row in rows {
+                self.cell_buf.clear();
+                source.cell_text(row, first, &mut self.cell_buf);
+                cx.add_access_node(
+                    AccessNode::new(LiveId::from_num(uid, row as u64).0, AccessRole::TableRow, Area::Empty)
+                        .with_name(self.cell_buf.as_str())
+                        .with_states(AccessStates {selected: self.selection.contains(row), ..Default::default()})
+                );
+            }
+        }
+    }
+}
+
+impl Widget for DataTable {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        let uid = self.widget_uid();
+
+        let mut scroll = None;
+        let mut scroll_action = | _cx: &mut Cx, action | {
+            if let ScrollBarAction::Scroll {scroll_pos, ..} = action {
+                scroll = Some(scroll_pos)
+            }
+        };
+        self.scroll_bar_x.handle_event_with(cx, event, &mut scroll_action);
+        self.scroll_bar_x.handle_scroll_event(cx, event, self.area, &mut scroll_action);
+        if let Some(x) = scroll.take() {
+            self.scroll_pos.x = x;
+            self.area.redraw(cx);
+        }
+        let mut scroll_action = | _cx: &mut Cx, action | {
+            if let ScrollBarAction::Scroll {scroll_pos, ..} = action {
+                scroll = Some(scroll_pos)
+            }
+        };
+        self.scroll_bar_y.handle_event_with(cx, event, &mut scroll_action);
+        self.scroll_bar_y.handle_scroll_event(cx, event, self.area, &mut scroll_action);
+        if let Some(y) = scroll {
+            self.scroll_pos.y = y;
+            self.area.redraw(cx);
+        }
+        if self.scroll_bar_x.is_area_captured(cx) || self.scroll_bar_y.is_area_captured(cx) {
+            return
+        }
+
+        match event.hits(cx, self.area) {
+            Hit::FingerHoverIn(e) | Hit::FingerHoverOver(e) => {
+                let (hover_row, hover_header) = if self.is_in_header(e.abs) {
+                    if self.resize_edge_at(e.abs.x).is_some() {
+                        cx.set_cursor(MouseCursor::ColResize);
+                    }
+                    else {
+                        cx.set_cursor(MouseCursor::Default);
+                    }
+                    (None, self.column_at(e.abs.x))
+                }
+                else {
+                    cx.set_cursor(MouseCursor::Default);
+                    (self.row_at(e.abs.y), None)
+                };
+                if (hover_row, hover_header) != (self.hover_row, self.hover_header) {
+                    self.hover_row = hover_row;
+                    self.hover_header = hover_header;
+                    self.area.redraw(cx);
+                }
+            }
+            Hit::FingerHoverOut(_) if self.hover_row.is_some() || self.hover_header.is_some() => {
+                self.hover_row = None;
+                self.hover_header = None;
+                self.area.redraw(cx);
+            }
+            Hit::FingerDown(e) => {
+                cx.set_key_focus(self.area);
+                if self.is_in_header(e.abs) {
+                    if let Some(column) = self.resize_edge_at(e.abs.x) {
+                        self.header_drag = Some(HeaderDrag::Resize {column, width: self.column(column).width, start: e.abs.x});
+                    }
+                    else if let Some(column) = self.column_at(e.abs.x) {
+                        self.header_drag = Some(HeaderDrag::Press {column, start: e.abs.x});
+                        self.area.redraw(cx);
+                    }
+                }
+                else if let Some(row) = self.row_at(e.abs.y) {
+                    if e.tap_count == 2 {
+                        cx.widget_action(uid, &scope.path, DataTableAction::RowActivated(row));
+                    }
+                    else {
+                        self.select_row(cx, row, e.modifiers, scope);
+                    }
+                }
+            }
+            Hit::FingerMove(e) => match self.header_drag {
+                Some(HeaderDrag::Resize {column, width, start}) => {
+                    let min_width = self.column(column).min_width;
+                    self.columns[self.order[column]].width = (width + e.abs.x - start).max(min_width);
+                    cx.set_cursor(MouseCursor::ColResize);
+                    self.area.redraw(cx);
+                }
+                Some(HeaderDrag::Press {column, start}) if (e.abs.x - start).abs() > self.min_drag_distance => {
+                    self.header_drag = Some(HeaderDrag::Reorder {column, target: self.drop_target_at(e.abs.x)});
+                    self.area.redraw(cx);
+                }
+                Some(HeaderDrag::Reorder {column, target}) => {
+                    let new_target = self.drop_target_at(e.abs.x);
+                    if new_target != target {
+                        self.header_drag = Some(HeaderDrag::Reorder {column, target: new_target});
+                        self.area.redraw(cx);
+                    }
+                }
+                Some(HeaderDrag::Press {..}) | None => ()
+            }
+            Hit::FingerUp(e) => match self.header_drag.take() {
+                Some(HeaderDrag::Resize {column, ..}) => {
+                    let column = self.column(column);
+                    cx.widget_action(uid, &scope.path, DataTableAction::ColumnResized {column: column.id, width: column.width});
+                }
+                Some(HeaderDrag::Press {column, ..}) => {
+                    if e.is_over {
+                        self.click_header(cx, column, scope);
+                    }
+                    self.area.redraw(cx);
+                }
+                Some(HeaderDrag::Reorder {column, target}) => {
+                    self.move_column(cx, column, target, scope);
+                    self.area.redraw(cx);
+                }
+                None => ()
+            }
+            Hit::KeyDown(ke) => match ke.key_code {
+                KeyCode::ArrowUp => self.move_cursor(cx, -1, ke.modifiers, scope),
+                KeyCode::ArrowDown => self.move_cursor(cx, 1, ke.modifiers, scope),
+                KeyCode::PageUp => self.move_cursor(cx, -(self.page_rows() as isize), ke.modifiers, scope),
+                KeyCode::PageDown => self.move_cursor(cx, self.page_rows() as isize, ke.modifiers, scope),
+                KeyCode::Home => self.move_cursor(cx, isize::MIN, ke.modifiers, scope),
+                KeyCode::End => self.move_cursor(cx, isize::MAX, ke.modifiers, scope),
+                KeyCode::ArrowLeft => self.set_scroll(cx, self.scroll_pos - dvec2(self.row_height * 2.0, 0.0)),
+                KeyCode::ArrowRight => self.set_scroll(cx, self.scroll_pos + dvec2(self.row_height * 2.0, 0.0)),
+                KeyCode::KeyA if (ke.modifiers.control || ke.modifiers.logo)
+                    && self.selection_mode == DataTableSelectionMode::Multi => {
+                    self.selection.select_all(self.row_count);
+                    self.area.redraw(cx);
+                    cx.widget_action(uid, &scope.path, DataTableAction::SelectionChanged);
+                }
+                KeyCode::ReturnKey => if let Some(row) = self.selection.cursor() {
+                    cx.widget_action(uid, &scope.path, DataTableAction::RowActivated(row));
+                }
+                _ => ()
+            }
+            Hit::KeyFocus(_) | Hit::KeyFocusLost(_) => {
+                self.area.redraw(cx);
+            }
+            _ => ()
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
+        if self.draw_state.begin(cx, ()) {
+            cx.begin_turtle(walk, self.layout);
+            self.access_group = Some(cx.begin_access_group(
+                AccessNode::new(self.widget_uid().0, AccessRole::Table, Area::Empty)
+            ));
+            return DrawStep::make_step()
+        }
+        if self.draw_state.get().is_some() {
+            self.draw_state.end();
+            let body = self.body_rect();
+            let total = self.total_size();
+            // the bars are placed relative to the turtle they are drawn in
+            cx.begin_turtle(Walk::abs_rect(body), Layout::default());
+            self.scroll_pos.x = self.scroll_bar_x.draw_scroll_bar(cx, ScrollAxis::Horizontal, body, total);
+            self.scroll_pos.y = self.scroll_bar_y.draw_scroll_bar(cx, ScrollAxis::Vertical, body, total);
+            cx.end_turtle();
+            cx.end_turtle_with_area(&mut self.area);
+            if let Some(access_group) = self.access_group.take() {
+                cx.end_access_group(access_group, self.area);
+            }
+            if let Some(order) = NavOrder::from_tab_index(self.tab_index) {
+                cx.push_nav_stop(NavStop::new(self.area, NavRole::Table).with_order(order));
+            }
+        }
+        DrawStep::done()
+    }
+}
+
+impl DataTableRef {
+    pub fn sorted(&self, actions: &Actions) -> Option<(LiveId, SortDirection)> {
+        if let DataTableAction::Sort {column, direction} = actions.find_widget_action(self.widget_uid()).cast() {
+            return Some((column, direction))
+        }
+        None
+    }
+
+    pub fn selection_changed(&self, actions: &Actions) -> bool {
+        matches!(actions.find_widget_action(self.widget_uid()).cast(), DataTableAction::SelectionChanged)
+    }
+
+    pub fn row_activated(&self, actions: &Actions) -> Option<usize> {
+        if let DataTableAction::RowActivated(row) = actions.find_widget_action(self.widget_uid()).cast() {
+            return Some(row)
+        }
+        None
+    }
+
+    pub fn selection(&self) -> DataTableSelection {
+        let Some(inner) = self.borrow() else {return DataTableSelection::default()};
+        inner.selection.clone()
+    }
+
+    pub fn set_sort(&self, cx: &mut Cx, sort: Option<(LiveId, SortDirection)>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_sort(cx, sort);
+        }
+    }
+
+    pub fn scroll_to_row(&self, cx: &mut Cx, row: usize) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.scroll_to_row(cx, row);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(selection: &DataTableSelection) -> Vec<(usize, usize)> {
+        selection.ranges().iter().map( | range | (range.start, range.end)).collect()
+    }
+
+    #[test]
+    fn selection_keeps_merged_ranges() {
+        let mut selection = DataTableSelection::default();
+        selection.select(5);
+        selection.extend_to(2);
+        assert_eq!(spans(&selection), [(2, 6)]);
+        assert_eq!(selection.cursor(), Some(2));
+
+        selection.toggle(8);
+        selection.toggle(7);
+        selection.toggle(6);
+        assert_eq!(spans(&selection), [(2, 9)]);
+
+        selection.toggle(4);
+        assert_eq!(spans(&selection), [(2, 4), (5, 9)]);
+        assert!(!selection.contains(4) && selection.contains(5) && !selection.contains(9));
+        assert_eq!(selection.len(), 6);
+
+        // the anchor moved to the last toggled row
+        selection.extend_to(1);
+        assert_eq!(spans(&selection), [(1, 5)]);
+
+        selection.select_all(1_000_000);
+        selection.truncate(10);
+        assert_eq!(spans(&selection), [(0, 10)]);
+    }
+
+    #[test]
+    fn overlapping_columns_finds_the_visible_span() {
+        let offsets = [0.0, 100.0, 150.0, 300.0, 320.0];
+        assert_eq!(overlapping_columns(&offsets, 0.0, 100.0), 0..1);
+        assert_eq!(overlapping_columns(&offsets, 120.0, 310.0), 1..4);
+        assert_eq!(overlapping_columns(&offsets, 150.0, 1000.0), 2..4);
+        assert_eq!(overlapping_columns(&offsets, 400.0, 500.0), 4..4);
+    }
+}
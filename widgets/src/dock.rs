@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::str::Chars;
 use crate::{
     makepad_micro_serde::*,
@@ -234,16 +234,33 @@ pub struct LiveIdStore(pub LiveId);
 impl SerRon for LiveIdStore {
     fn ser_ron(&self, _d: usize, s: &mut SerRonState) {
         self.0.as_string(|v|{
-            if let Some(v) = v{
-                s.out.push_str(v);
-            }
-            else{
-                s.out.push_str(&self.0.0.to_string());
+            // only identifiers read back as names, anything else is stored as its number
+            match v.filter(|v| is_ron_ident(v)){
+                Some(v)=>s.out.push_str(v),
+                None=>s.out.push_str(&self.0.0.to_string())
             }
         });
     }
 }
 
+fn is_ron_ident(v: &str) -> bool {
+    let mut chars = v.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_') &&
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl SerBin for LiveIdStore {
+    fn ser_bin(&self, s: &mut Vec<u8>) {
+        self.0.0.ser_bin(s);
+    }
+}
+
+impl DeBin for LiveIdStore {
+    fn de_bin(o: &mut usize, d: &[u8]) -> Result<LiveIdStore, DeBinErr> {
+        Ok(LiveIdStore(LiveId(DeBin::de_bin(o, d)?)))
+    }
+}
+
 impl DeRon for LiveIdStore {
     fn de_ron(s: &mut DeRonState, i: &mut Chars) -> Result<LiveIdStore, DeRonErr> {
         let liveid = match s.tok{
@@ -262,7 +279,7 @@ impl DeRon for LiveIdStore {
 }
 
 
-#[derive(Clone, Debug, SerRon, DeRon)]
+#[derive(Clone, Debug, SerRon, DeRon, SerBin, DeBin)]
 pub enum DockItemStore{
     Splitter {
         id: LiveIdStore,
@@ -285,6 +302,19 @@ pub enum DockItemStore{
     }
 }
 
+/// Why `Dock::from_store_items` could not restore a layout.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DockStoreError {
+    /// Two stored items have the same id.
+    DuplicateItem(LiveId),
+    /// A splitter or the root refers to an item that was not stored.
+    MissingItem(LiveId),
+    /// An item appears in more than one place, or inside itself.
+    ItemUsedTwice(LiveId),
+    /// The dock is gone, or in use by whoever tried to restore the layout.
+    DockUnavailable,
+}
+
 impl LiveHook for Dock {
     fn apply_value_instance(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) -> usize {
         let id = nodes[index].id;
@@ -462,8 +492,12 @@ impl Dock {
     }
     
     pub fn to_store_items(&self)->Vec<DockItemStore>{
+        Self::store_items(&self.dock_items)
+    }
+    
+    fn store_items(dock_items: &HashMap<LiveId, DockItem>)->Vec<DockItemStore>{
         let mut out = Vec::new();
-        for (id, dock_item) in &self.dock_items{
+        for (id, dock_item) in dock_items{
             match dock_item{
                 DockItem::Splitter {
                     axis,
@@ -508,8 +542,92 @@ impl Dock {
         out
     }
     
-    pub fn from_store_item(&mut self, _store:&[DockItemStore]){
-        
+    /// Replaces the layout with one saved by `to_store_items`, and creates the items of its tabs.
+    /// Tabs with a template the dock does not have are left out, as are tabs that were not stored.
+    /// If the layout does not form a tree from `root`, the current layout is kept.
+    pub fn from_store_items(&mut self, cx: &mut Cx, store: &[DockItemStore]) -> Result<(), DockStoreError> {
+        let dock_items = Self::validate_store_items(store, |kind| self.templates.contains_key(&kind))?;
+        self.items.retain(|id, (kind, _)| {
+            matches!(dock_items.get(id), Some(DockItem::Tab {kind: new_kind, ..}) if new_kind == kind)
+        });
+        self.dock_items = dock_items;
+        let tabs: Vec<(LiveId, LiveId)> = self.dock_items.iter().filter_map(|(id, item)| match item {
+            DockItem::Tab {kind, ..} => Some((*id, *kind)),
+            _ => None
+        }).collect();
+        for (item_id, kind) in tabs {
+            self.item_or_create(cx, item_id, kind);
+        }
+        for tab_bar in self.tab_bars.values_mut() {
+            tab_bar.contents_draw_list.redraw(cx);
+        }
+        self.drop_state = None;
+        self.needs_save = false;
+        self.redraw(cx);
+        Ok(())
+    }
+    
+    fn validate_store_items(store: &[DockItemStore], has_template: impl Fn(LiveId) -> bool) -> Result<HashMap<LiveId, DockItem>, DockStoreError> {
+        let mut dock_items = HashMap::new();
+        for item in store {
+            let (id, dock_item) = match item {
+                DockItemStore::Splitter {id, axis, align, a, b} => (id.0, DockItem::Splitter {
+                    axis: *axis,
+                    align: *align,
+                    a: a.0,
+                    b: b.0
+                }),
+                DockItemStore::Tabs {id, tabs, selected, closable} => (id.0, DockItem::Tabs {
+                    tabs: tabs.iter().map(|v| v.0).collect(),
+                    selected: *selected,
+                    closable: *closable
+                }),
+                DockItemStore::Tab {id, name, closable, kind} => {
+                    if !has_template(kind.0) {
+                        log!("Dock template not found {}, leaving out tab {}", kind.0, id.0);
+                        continue
+                    }
+                    (id.0, DockItem::Tab {
+                        name: name.clone(),
+                        closable: *closable,
+                        kind: kind.0
+                    })
+                }
+            };
+            if dock_items.insert(id, dock_item).is_some() {
+                return Err(DockStoreError::DuplicateItem(id))
+            }
+        }
+        // drop the tabs that were left out above, or were never stored
+        let tab_ids: Vec<LiveId> = dock_items.iter()
+            .filter(|(_, item)| matches!(item, DockItem::Tab {..}))
+            .map(|(id, _)| *id)
+            .collect();
+        for item in dock_items.values_mut() {
+            if let DockItem::Tabs {tabs, selected, ..} = item {
+                tabs.retain(|tab| tab_ids.contains(tab));
+                *selected = (*selected).min(tabs.len().saturating_sub(1));
+            }
+        }
+        // every item has to be reachable from the root exactly once
+        let mut used = HashSet::new();
+        let mut stack = vec![live_id!(root)];
+        while let Some(id) = stack.pop() {
+            if !used.insert(id) {
+                return Err(DockStoreError::ItemUsedTwice(id))
+            }
+            match dock_items.get(&id) {
+                Some(DockItem::Splitter {a, b, ..}) => {
+                    stack.push(*b);
+                    stack.push(*a);
+                }
+                Some(DockItem::Tabs {tabs, ..}) => stack.extend(tabs.iter().copied()),
+                Some(DockItem::Tab {..}) => (),
+                None => return Err(DockStoreError::MissingItem(id))
+            }
+        }
+        dock_items.retain(|id, _| used.contains(id));
+        Ok(dock_items)
     }
     
     pub fn item(&mut self, entry_id: LiveId) -> Option<WidgetRef> {
@@ -1188,6 +1306,13 @@ impl DockRef {
         LiveId(0)
     }
         
+    pub fn from_store_items(&self, cx: &mut Cx, store: &[DockItemStore]) -> Result<(), DockStoreError> {
+        if let Some(mut dock) = self.borrow_mut() {
+            return dock.from_store_items(cx, store)
+        }
+        Err(DockStoreError::DockUnavailable)
+    }
+    
     pub fn needs_save(&self)->Option<Vec<DockItemStore>>{
         if let Some(mut dock) = self.borrow_mut() {
             if dock.needs_save{
@@ -1202,3 +1327,155 @@ impl DockRef {
         cx.start_dragging(vec![item]);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // root splits into a file tree on the left, and two editors and a terminal on the right
+    fn layout() -> HashMap<LiveId, DockItem> {
+        let mut dock_items = HashMap::new();
+        dock_items.insert(live_id!(root), DockItem::Splitter {
+            axis: SplitterAxis::Horizontal,
+            align: SplitterAlign::FromA(250.0),
+            a: live_id!(left),
+            b: live_id!(right)
+        });
+        dock_items.insert(live_id!(left), DockItem::Tabs {
+            tabs: vec![live_id!(files)],
+            selected: 0,
+            closable: false
+        });
+        dock_items.insert(live_id!(right), DockItem::Tabs {
+            tabs: vec![live_id!(edit1), live_id!(edit2), live_id!(shell)],
+            selected: 2,
+            closable: true
+        });
+        for (id, kind) in [
+            (live_id!(files), live_id!(FileTree)),
+            (live_id!(edit1), live_id!(Editor)),
+            (live_id!(edit2), live_id!(Editor)),
+            (live_id!(shell), live_id!(Terminal)),
+        ] {
+            dock_items.insert(id, DockItem::Tab {name: id.to_string(), closable: true, kind});
+        }
+        dock_items
+    }
+    
+    fn any_template(_kind: LiveId) -> bool {
+        true
+    }
+    
+    // DockItem has no PartialEq, so layouts are compared by their debug output
+    fn sorted(dock_items: &HashMap<LiveId, DockItem>) -> Vec<String> {
+        let mut items: Vec<String> = dock_items.iter().map(|(id, item)| format!("{} {:?}", id, item)).collect();
+        items.sort();
+        items
+    }
+    
+    fn tabs(dock_items: &HashMap<LiveId, DockItem>, id: LiveId) -> (Vec<LiveId>, usize) {
+        match &dock_items[&id] {
+            DockItem::Tabs {tabs, selected, ..} => (tabs.clone(), *selected),
+            item => panic!("{} is not a tab bar but {:?}", id, item)
+        }
+    }
+    
+    #[test]
+    fn round_trips_through_store_items() {
+        let store = Dock::store_items(&layout());
+        assert_eq!(store.len(), 7);
+        let restored = Dock::validate_store_items(&store, any_template).unwrap();
+        assert_eq!(sorted(&restored), sorted(&layout()));
+        
+        // and through the binary format the studio saves it in
+        let store = Vec::<DockItemStore>::deserialize_bin(&store.serialize_bin()).unwrap();
+        let restored = Dock::validate_store_items(&store, any_template).unwrap();
+        assert_eq!(sorted(&restored), sorted(&layout()));
+    }
+    
+    #[test]
+    fn rejects_duplicate_ids() {
+        let mut store = Dock::store_items(&layout());
+        store.push(DockItemStore::Tab {
+            id: LiveIdStore(live_id!(edit1)),
+            name: "edit1".to_string(),
+            closable: true,
+            kind: LiveIdStore(live_id!(Editor))
+        });
+        assert_eq!(
+            Dock::validate_store_items(&store, any_template).unwrap_err(),
+            DockStoreError::DuplicateItem(live_id!(edit1))
+        );
+    }
+    
+    #[test]
+    fn rejects_missing_children() {
+        let mut dock_items = layout();
+        dock_items.remove(&live_id!(right));
+        assert_eq!(
+            Dock::validate_store_items(&Dock::store_items(&dock_items), any_template).unwrap_err(),
+            DockStoreError::MissingItem(live_id!(right))
+        );
+        
+        let mut dock_items = layout();
+        dock_items.remove(&live_id!(root));
+        assert_eq!(
+            Dock::validate_store_items(&Dock::store_items(&dock_items), any_template).unwrap_err(),
+            DockStoreError::MissingItem(live_id!(root))
+        );
+        
+        // a tab that was not stored is left out of its tab bar instead
+        let mut dock_items = layout();
+        dock_items.remove(&live_id!(edit2));
+        let restored = Dock::validate_store_items(&Dock::store_items(&dock_items), any_template).unwrap();
+        assert_eq!(tabs(&restored, live_id!(right)), (vec![live_id!(edit1), live_id!(shell)], 1));
+    }
+    
+    #[test]
+    fn rejects_cycles_and_shared_items() {
+        let mut dock_items = layout();
+        dock_items.insert(live_id!(right), DockItem::Splitter {
+            axis: SplitterAxis::Vertical,
+            align: SplitterAlign::Weighted(0.5),
+            a: live_id!(left),
+            b: live_id!(root)
+        });
+        assert_eq!(
+            Dock::validate_store_items(&Dock::store_items(&dock_items), any_template).unwrap_err(),
+            DockStoreError::ItemUsedTwice(live_id!(left))
+        );
+        
+        let mut dock_items = layout();
+        dock_items.insert(live_id!(left), DockItem::Tabs {
+            tabs: vec![live_id!(files), live_id!(edit1)],
+            selected: 0,
+            closable: false
+        });
+        assert_eq!(
+            Dock::validate_store_items(&Dock::store_items(&dock_items), any_template).unwrap_err(),
+            DockStoreError::ItemUsedTwice(live_id!(edit1))
+        );
+        
+        let mut dock_items = layout();
+        dock_items.insert(live_id!(root), DockItem::Splitter {
+            axis: SplitterAxis::Horizontal,
+            align: SplitterAlign::FromA(250.0),
+            a: live_id!(left),
+            b: live_id!(root)
+        });
+        assert_eq!(
+            Dock::validate_store_items(&Dock::store_items(&dock_items), any_template).unwrap_err(),
+            DockStoreError::ItemUsedTwice(live_id!(root))
+        );
+    }
+    
+    #[test]
+    fn leaves_out_tabs_with_unknown_templates() {
+        let store = Dock::store_items(&layout());
+        let restored = Dock::validate_store_items(&store, |kind| kind != live_id!(Terminal)).unwrap();
+        assert!(!restored.contains_key(&live_id!(shell)));
+        // the selection moves to a tab that is still there
+        assert_eq!(tabs(&restored, live_id!(right)), (vec![live_id!(edit1), live_id!(edit2)], 1));
+        assert_eq!(restored.len(), 6);
+    }
+}
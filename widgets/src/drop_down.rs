@@ -36,6 +36,7 @@ pub struct DropDown {
     #[rust] is_open: bool,
     
     #[live] selected_item: usize,
+    #[live] tab_index: i64,
     
     #[layout] layout: Layout,
 }
@@ -117,7 +118,15 @@ impl DropDown {
         }
         self.draw_bg.end(cx);
         
-        cx.add_nav_stop(self.draw_bg.area(), NavRole::DropDown, Margin::default());
+        if let Some(order) = NavOrder::from_tab_index(self.tab_index) {
+            cx.push_nav_stop(NavStop::new(self.draw_bg.area(), NavRole::DropDown).with_order(order));
+        }
+        cx.add_access_node(
+            AccessNode::new(self.widget_uid().0, AccessRole::DropDown, self.draw_bg.area())
+                .with_value(AccessValue::Text(self.labels.get(self.selected_item).cloned().unwrap_or_default()))
+                .with_states(AccessStates {focusable: true, ..Default::default()})
+                .with_actions(AccessActions {click: true, focus: true, set_value: true, ..Default::default()})
+        );
         
         if self.is_open && self.popup_menu.is_some() {
             //cx.set_sweep_lock(self.draw_bg.area());
@@ -244,6 +253,21 @@ impl Widget for DropDown {
                 self.set_open(cx);
                 self.animator_play(cx, id!(hover.pressed));
             },
+            Hit::AccessAction(AccessAction::Click) => {
+                cx.set_key_focus(self.draw_bg.area());
+                self.set_open(cx);
+            }
+            Hit::AccessAction(AccessAction::Focus) => {
+                cx.set_key_focus(self.draw_bg.area());
+            }
+            Hit::AccessAction(AccessAction::SetValue(label)) => {
+                if let Some(index) = self.labels.iter().position( | l | *l == label) {
+                    self.selected_item = index;
+                    cx.widget_action(uid, &scope.path, DropDownAction::Select(self.selected_item, self.values.get(self.selected_item).cloned().unwrap_or(LiveValue::None)));
+                    self.set_closed(cx);
+                    self.draw_bg.redraw(cx);
+                }
+            }
             Hit::FingerHoverIn(_) => {
                 cx.set_cursor(MouseCursor::Hand);
                 self.animator_play(cx, id!(hover.on));
@@ -0,0 +1,480 @@
+use {
+    std::{
+        collections::HashMap,
+        fs,
+        path::{Path, PathBuf},
+    },
+    crate::{
+        makepad_derive_widget::*,
+        makepad_draw::*,
+        widget::*,
+        view::*,
+        button::*,
+        label::*,
+        text_input::*,
+        file_tree::*,
+    }
+};
+
+live_design!{
+    FileDialogViewBase = {{FileDialogView}} {}
+}
+
+/// A file dialog that Makepad draws itself, for platforms without a native one. The window shows
+/// it on top of its content when it receives an `Event::FileDialogRequest`, and reports the
+/// chosen path with an `Event::FileDialogResult` once it closes.
+#[derive(Live, LiveHook, Widget)]
+pub struct FileDialogView {
+    #[deref] view: View,
+    #[rust] state: FileDialogState,
+    #[rust] focus_name: bool,
+}
+
+impl FileDialogView {
+    pub fn is_open(&self) -> bool {
+        self.state.is_open()
+    }
+
+    pub fn open(&mut self, cx: &mut Cx, kind: FileDialogKind, dialog: FileDialog) {
+        let title = dialog.title.clone().unwrap_or_else( || match kind {
+            FileDialogKind::SaveFile => "Save File",
+            FileDialogKind::SelectFile => "Open File",
+            FileDialogKind::SaveFolder => "Save Folder",
+            FileDialogKind::SelectFolder => "Select Folder",
+        }.to_string());
+        self.label(id!(title)).set_text(&title);
+        self.button(id!(accept)).set_text(if kind.is_save() {"Save"} else {"Open"});
+        let filters: Vec<&str> = dialog.filters.iter().map( | filter | filter.description.as_str()).collect();
+        self.label(id!(filters)).set_text(&filters.join(", "));
+        self.view(id!(name_bar)).set_visible(kind.is_save());
+        self.text_input(id!(name)).set_text(dialog.filename.as_deref().unwrap_or(""));
+        self.focus_name = kind.is_save();
+        self.state.open(kind, dialog, std::env::current_dir().ok());
+        self.location_changed(cx);
+    }
+
+    fn location_changed(&mut self, cx: &mut Cx) {
+        self.label(id!(location)).set_text(&self.state.location().to_string_lossy());
+        if let Some(mut file_tree) = self.file_tree(id!(file_tree)).borrow_mut() {
+            file_tree.forget();
+        }
+        self.redraw(cx);
+    }
+
+    fn draw_entry(&mut self, cx: &mut Cx2d, id: FileNodeId, file_tree: &mut FileTree) {
+        let Some(entry) = self.state.entries.get(&id) else {
+            return
+        };
+        if !entry.is_folder {
+            file_tree.file(cx, id, &entry.name);
+            return
+        }
+        if file_tree.begin_folder(cx, id, &entry.name).is_ok() {
+            for child_id in self.state.folder_children(id) {
+                self.draw_entry(cx, child_id, file_tree);
+            }
+            file_tree.end_folder();
+        }
+    }
+}
+
+impl Widget for FileDialogView {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        if !self.is_open() {
+            return
+        }
+        self.view.handle_event(cx, event, scope);
+        if let Event::Actions(actions) = event {
+            let file_tree = self.file_tree(id!(file_tree));
+            match actions.find_widget_action_cast(file_tree.widget_uid()) {
+                FileTreeAction::FileClicked(id) | FileTreeAction::FolderClicked(id) => {
+                    if let Some(name) = self.state.select(id) {
+                        self.text_input(id!(name)).set_text_and_redraw(cx, &name);
+                    }
+                }
+                _ => ()
+            }
+            if self.button(id!(up)).clicked(actions) && self.state.up() {
+                self.location_changed(cx);
+            }
+            if self.button(id!(cancel)).clicked(actions) {
+                self.state.close(cx, None);
+            }
+            else if self.button(id!(accept)).clicked(actions) || self.text_input(id!(name)).returned(actions).is_some() {
+                let name = self.text_input(id!(name)).text();
+                self.state.accept(cx, name.trim());
+            }
+        }
+        if let Event::KeyDown(KeyEvent {key_code: KeyCode::Escape, ..}) = event {
+            self.state.close(cx, None);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        if !self.is_open() {
+            return DrawStep::done()
+        }
+        let root_id = self.state.root_id();
+        while let Some(item) = self.view.draw_walk(cx, scope, walk).step() {
+            if let Some(mut file_tree) = item.as_file_tree().borrow_mut() {
+                file_tree.set_folder_is_open(cx, root_id, true, Animate::No);
+                self.draw_entry(cx, root_id, &mut file_tree);
+            }
+        }
+        // the name input can only take the key focus once it has been drawn
+        if self.focus_name {
+            self.focus_name = false;
+            if let Some(name_input) = self.text_input(id!(name)).borrow() {
+                name_input.set_key_focus(cx);
+            }
+        }
+        DrawStep::done()
+    }
+}
+
+/// The state of a file dialog: the request it answers, the folder it shows, and the entries of
+/// that folder and of the subfolders that were opened in it. Folders are read through
+/// `read_folder`, the first time they are opened.
+pub struct FileDialogState {
+    request: Option<(FileDialogKind, FileDialog)>,
+    location: PathBuf,
+    entries: HashMap<FileNodeId, DialogEntry>,
+    selected: Option<FileNodeId>,
+    read_folder: ReadFolder,
+}
+
+/// Lists a folder as the names of its entries and whether they are folders, or returns `None` if
+/// there is no such folder.
+pub type ReadFolder = Box<dyn Fn(&Path) -> Option<Vec<(String, bool)>>>;
+
+struct DialogEntry {
+    name: String,
+    path: PathBuf,
+    is_folder: bool,
+    // the entries of a folder are read the first time it is opened
+    children: Option<Vec<FileNodeId>>,
+}
+
+impl Default for FileDialogState {
+    fn default() -> Self {
+        Self::new(Box::new(read_folder))
+    }
+}
+
+impl FileDialogState {
+    /// Creates the state of a dialog that lists folders with `read_folder`.
+    pub fn new(read_folder: ReadFolder) -> Self {
+        Self {
+            request: None,
+            location: PathBuf::new(),
+            entries: HashMap::new(),
+            selected: None,
+            read_folder,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.request.is_some()
+    }
+
+    pub fn location(&self) -> &Path {
+        &self.location
+    }
+
+    /// Opens the dialog at its own location if that is a folder, or at `current_dir` otherwise.
+    pub fn open(&mut self, kind: FileDialogKind, dialog: FileDialog, current_dir: Option<PathBuf>) {
+        let location = dialog.location.clone()
+            .filter( | location | (self.read_folder)(location).is_some())
+            .or(current_dir)
+            .unwrap_or_else( || PathBuf::from("/"));
+        self.request = Some((kind, dialog));
+        self.show_location(location);
+    }
+
+    fn show_location(&mut self, location: PathBuf) {
+        self.location = location;
+        self.entries.clear();
+        self.selected = None;
+    }
+
+    /// Shows the parent of the current folder. Returns `false` if there is none.
+    pub fn up(&mut self) -> bool {
+        let Some(parent) = self.location.parent().map(Path::to_path_buf) else {
+            return false
+        };
+        self.show_location(parent);
+        true
+    }
+
+    /// Returns the id of the entry for the folder the dialog shows.
+    pub fn root_id(&mut self) -> FileNodeId {
+        let root_id = entry_id(&self.location);
+        if !self.entries.contains_key(&root_id) {
+            self.entries.insert(root_id, DialogEntry {
+                name: self.location.to_string_lossy().to_string(),
+                path: self.location.clone(),
+                is_folder: true,
+                children: None,
+            });
+        }
+        root_id
+    }
+
+    /// Returns the entries of the given folder, reading it if this is the first time it is shown.
+    pub fn folder_children(&mut self, id: FileNodeId) -> Vec<FileNodeId> {
+        let Some((kind, dialog)) = &self.request else {
+            return Vec::new()
+        };
+        let Some(entry) = self.entries.get(&id) else {
+            return Vec::new()
+        };
+        if let Some(children) = &entry.children {
+            return children.clone()
+        }
+        let folder = entry.path.clone();
+        let entries = (self.read_folder)(&folder).unwrap_or_default();
+        let mut children = Vec::new();
+        for (name, is_folder) in filter_entries(entries, *kind, dialog) {
+            let path = folder.join(&name);
+            let child_id = entry_id(&path);
+            self.entries.insert(child_id, DialogEntry {name, path, is_folder, children: None});
+            children.push(child_id);
+        }
+        self.entries.get_mut(&id).unwrap().children = Some(children.clone());
+        children
+    }
+
+    /// Selects the given entry. Returns its name if it should go into the name field, which is
+    /// when a file is selected in a dialog that saves a file.
+    pub fn select(&mut self, id: FileNodeId) -> Option<String> {
+        self.selected = Some(id);
+        let is_save_file = matches!(self.request, Some((FileDialogKind::SaveFile, _)));
+        self.entries.get(&id).filter( | entry | !entry.is_folder && is_save_file).map( | entry | entry.name.clone())
+    }
+
+    /// Closes the dialog with the path chosen by the selection and the typed name, if they make a
+    /// choice. Returns whether the dialog closed.
+    pub fn accept(&mut self, cx: &mut Cx, name: &str) -> bool {
+        let Some((kind, dialog)) = &self.request else {
+            return false
+        };
+        let selected = self.selected
+            .and_then( | id | self.entries.get(&id))
+            .map( | entry | (entry.path.as_path(), entry.is_folder));
+        let Some(path) = chosen_path(*kind, dialog, &self.location, selected, name) else {
+            return false
+        };
+        self.close(cx, Some(path));
+        true
+    }
+
+    /// Closes the dialog, which sends an `Event::FileDialogResult` with the given path.
+    pub fn close(&mut self, cx: &mut Cx, path: Option<PathBuf>) {
+        if let Some((kind, _)) = self.request.take() {
+            self.entries.clear();
+            self.selected = None;
+            cx.file_dialog_closed(kind, path);
+        }
+    }
+}
+
+fn entry_id(path: &Path) -> FileNodeId {
+    LiveId::from_str(&path.to_string_lossy()).into()
+}
+
+fn read_folder(folder: &Path) -> Option<Vec<(String, bool)>> {
+    let read_dir = fs::read_dir(folder).ok()?;
+    Some(read_dir.filter_map( | entry | {
+        let entry = entry.ok()?;
+        let name = entry.file_name().to_str()?.to_string();
+        Some((name, entry.path().is_dir()))
+    }).collect())
+}
+
+/// Picks what a dialog shows of the entries of a folder: its subfolders, and unless it selects
+/// folders the files that pass its filters. Hidden entries are left out, and folders come before
+/// files.
+pub fn filter_entries(entries: Vec<(String, bool)>, kind: FileDialogKind, dialog: &FileDialog) -> Vec<(String, bool)> {
+    let mut entries: Vec<(String, bool)> = entries.into_iter().filter( | (name, is_folder) | {
+        !name.starts_with('.') && (*is_folder || !kind.is_folder() && dialog.accepts(Path::new(name)))
+    }).collect();
+    entries.sort_by( | (a, a_is_folder), (b, b_is_folder) | {
+        b_is_folder.cmp(a_is_folder).then_with( || a.to_lowercase().cmp(&b.to_lowercase()))
+    });
+    entries
+}
+
+/// Works out the path a dialog returns, from the folder it shows, the selected entry and whether
+/// that is a folder, and the typed name. Returns `None` if that does not make a choice yet.
+pub fn chosen_path(
+    kind: FileDialogKind,
+    dialog: &FileDialog,
+    location: &Path,
+    selected: Option<(&Path, bool)>,
+    name: &str
+) -> Option<PathBuf> {
+    // typed names are relative to the selected folder, or the folder of the selected file
+    let folder = match selected {
+        Some((path, true)) => path,
+        Some((path, false)) => path.parent().unwrap_or(location),
+        None => location,
+    };
+    let selected_file = selected.filter( | (_, is_folder) | !is_folder).map( | (path, _) | path);
+    match kind {
+        FileDialogKind::SelectFile => selected_file.map(Path::to_path_buf),
+        FileDialogKind::SelectFolder => Some(folder.to_path_buf()),
+        FileDialogKind::SaveFolder if name.is_empty() => Some(folder.to_path_buf()),
+        FileDialogKind::SaveFolder => Some(folder.join(name)),
+        FileDialogKind::SaveFile if name.is_empty() => selected_file.map(Path::to_path_buf),
+        FileDialogKind::SaveFile => {
+            let mut path = folder.join(name);
+            // a name without one of the extensions gets the first one
+            if !dialog.accepts(&path) {
+                let extension = &dialog.filters[0].extensions[0];
+                let extension = extension.trim_start_matches('*').trim_start_matches('.');
+                path.as_mut_os_string().push(format!(".{}", extension));
+            }
+            Some(path)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::{cell::RefCell, rc::Rc},
+    };
+
+    // /home/user with a few files and folders, kept in memory
+    fn state() -> FileDialogState {
+        let folders: HashMap<PathBuf, Vec<(String, bool)>> = [
+            ("/", vec![("home", true)]),
+            ("/home", vec![("user", true)]),
+            ("/home/user", vec![
+                ("notes.TXT", false),
+                ("src", true),
+                (".config", true),
+                ("main.rs", false),
+                ("Assets", true),
+                (".hidden.rs", false),
+                ("photo.png", false),
+            ]),
+            ("/home/user/src", vec![("lib.rs", false), ("build", true)]),
+            ("/home/user/Assets", vec![]),
+        ].into_iter().map( | (path, entries) | {
+            (PathBuf::from(path), entries.into_iter().map( | (name, is_folder) | (name.to_string(), is_folder)).collect())
+        }).collect();
+        FileDialogState::new(Box::new(move | path | folders.get(path).cloned()))
+    }
+
+    fn rust_dialog() -> FileDialog {
+        FileDialog::new()
+            .set_location(PathBuf::from("/home/user"))
+            .add_filter("Rust".to_string(), vec!["*.rs".to_string()])
+            .add_filter("Text".to_string(), vec!["txt".to_string()])
+    }
+
+    fn names(state: &mut FileDialogState, id: FileNodeId) -> Vec<String> {
+        state.folder_children(id).iter().map( | id | state.entries[id].name.clone()).collect()
+    }
+
+    fn child(state: &mut FileDialogState, id: FileNodeId, name: &str) -> FileNodeId {
+        state.folder_children(id).into_iter().find( | id | state.entries[id].name == name).unwrap()
+    }
+
+    // a Cx that records the results of the dialogs that close
+    fn cx() -> (Cx, Rc<RefCell<Vec<FileDialogResultEvent >>>) {
+        let results = Rc::new(RefCell::new(Vec::new()));
+        let cx = Cx::new(Box::new({
+            let results = results.clone();
+            move | _, event | if let Event::FileDialogResult(result) = event {
+                results.borrow_mut().push(result.clone());
+            }
+        }));
+        (cx, results)
+    }
+
+    #[test]
+    fn filters_entries() {
+        let mut state = state();
+        state.open(FileDialogKind::SelectFile, rust_dialog(), None);
+        let root_id = state.root_id();
+        // folders first, hidden entries and files that no filter accepts left out
+        assert_eq!(names(&mut state, root_id), ["Assets", "src", "main.rs", "notes.TXT"]);
+
+        state.open(FileDialogKind::SelectFile, FileDialog::new().set_location(PathBuf::from("/home/user")), None);
+        let root_id = state.root_id();
+        assert_eq!(names(&mut state, root_id), ["Assets", "src", "main.rs", "notes.TXT", "photo.png"]);
+
+        state.open(FileDialogKind::SelectFolder, rust_dialog(), None);
+        let root_id = state.root_id();
+        assert_eq!(names(&mut state, root_id), ["Assets", "src"]);
+        let src_id = child(&mut state, root_id, "src");
+        assert_eq!(names(&mut state, src_id), ["build"]);
+    }
+
+    #[test]
+    fn handles_locations() {
+        let mut state = state();
+        state.open(FileDialogKind::SelectFile, rust_dialog(), Some(PathBuf::from("/home")));
+        assert_eq!(state.location(), Path::new("/home/user"));
+
+        // a location that is not a folder falls back to the current folder
+        let dialog = rust_dialog().set_location(PathBuf::from("/home/user/main.rs"));
+        state.open(FileDialogKind::SelectFile, dialog, Some(PathBuf::from("/home")));
+        assert_eq!(state.location(), Path::new("/home"));
+        state.open(FileDialogKind::SelectFile, FileDialog::new(), None);
+        assert_eq!(state.location(), Path::new("/"));
+
+        state.open(FileDialogKind::SelectFile, rust_dialog(), None);
+        let root_id = state.root_id();
+        let src_id = child(&mut state, root_id, "src");
+        state.select(src_id);
+        // going up forgets the selection and the entries of the old location
+        assert!(state.up());
+        assert_eq!(state.location(), Path::new("/home"));
+        assert!(state.selected.is_none());
+        let root_id = state.root_id();
+        assert_eq!(names(&mut state, root_id), ["user"]);
+        assert!(state.up());
+        assert!(!state.up());
+        assert_eq!(state.location(), Path::new("/"));
+    }
+
+    #[test]
+    fn sends_chosen_path() {
+        let (mut cx, results) = cx();
+        let mut state = state();
+
+        state.open(FileDialogKind::SelectFile, rust_dialog(), None);
+        let root_id = state.root_id();
+        let src_id = child(&mut state, root_id, "src");
+        // a folder is no choice for a dialog that selects a file
+        assert_eq!(state.select(src_id), None);
+        assert!(!state.accept(&mut cx, ""));
+        let lib_id = child(&mut state, src_id, "lib.rs");
+        state.select(lib_id);
+        assert!(state.accept(&mut cx, ""));
+        assert!(!state.is_open());
+
+        state.open(FileDialogKind::SaveFile, rust_dialog(), None);
+        let root_id = state.root_id();
+        let main_id = child(&mut state, root_id, "main.rs");
+        assert_eq!(state.select(main_id).as_deref(), Some("main.rs"));
+        // a typed name without one of the extensions gets the first one
+        assert!(state.accept(&mut cx, "other"));
+
+        state.open(FileDialogKind::SelectFolder, rust_dialog(), None);
+        state.close(&mut cx, None);
+        // closing a closed dialog sends nothing
+        state.close(&mut cx, None);
+
+        cx.handle_file_dialog_results();
+        assert_eq!(*results.borrow(), [
+            FileDialogResultEvent {kind: FileDialogKind::SelectFile, path: Some(PathBuf::from("/home/user/src/lib.rs"))},
+            FileDialogResultEvent {kind: FileDialogKind::SaveFile, path: Some(PathBuf::from("/home/user/other.rs"))},
+            FileDialogResultEvent {kind: FileDialogKind::SelectFolder, path: None},
+        ]);
+    }
+}
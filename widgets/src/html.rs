@@ -4,9 +4,13 @@ use {
         makepad_derive_widget::*,
         makepad_draw::*,
         widget::*,
-        text_flow::TextFlow,
+        image_cache::*,
+        text_flow::{TextFlow, FlowBlockType},
+    },
+    std::{
+        collections::HashMap,
+        rc::Rc,
     },
-    std::rc::Rc,
 };
 
 live_design!{
@@ -20,80 +24,653 @@ live_design!{
 pub struct Html{
     #[deref] text_flow: TextFlow,
     #[live] html: Rc<String>,
-    #[rust] doc: HtmlDoc
+    #[live] link_color: Vec4,
+    #[rust] doc: HtmlDoc,
+    #[rust] ops: Vec<HtmlFlowOp>,
+    #[rust] images: HashMap<String, HtmlImage>,
+}
+
+/// What the `Html` widget does with the `TextFlow`, in order, to draw a document.
+#[derive(Clone, Debug, PartialEq)]
+pub enum HtmlFlowOp{
+    Text(String),
+    PushBold,
+    PopBold,
+    PushItalic,
+    PopItalic,
+    PushFixed,
+    PopFixed,
+    PushScale(f64),
+    PopSize,
+    /// A color from a `style` attribute, as `0xRRGGBBAA`.
+    PushColor(u32),
+    PopColor,
+    BeginLink(String),
+    EndLink,
+    /// Ends the line, leaving an empty one if there was nothing on it.
+    Break,
+    NewLine,
+    /// Ends the line and leaves the gap between paragraphs.
+    Gap,
+    BeginQuote,
+    EndQuote,
+    BeginCode,
+    EndCode,
+    BeginListItem(String),
+    EndListItem,
+    BeginTable(usize),
+    EndTable,
+    BeginTableRow,
+    EndTableRow,
+    BeginTableCell,
+    EndTableCell,
+    Rule,
+    Image{src: String, width: Option<f64>, height: Option<f64>},
+    Widget{id: u64, template: LiveId, text: String},
+}
+
+#[derive(Default)]
+struct HtmlImage{
+    texture: Option<Texture>,
+}
+
+impl ImageCacheImpl for HtmlImage{
+    fn get_texture(&self) -> &Option<Texture>{
+        &self.texture
+    }
+
+    fn set_texture(&mut self, texture: Option<Texture>){
+        self.texture = texture;
+    }
 }
 
 // alright lets parse the HTML
 impl LiveHook for Html{
-    fn after_apply_from(&mut self, _cx: &mut Cx, _apply:&mut Apply) {
+    fn after_apply_from(&mut self, cx: &mut Cx, _apply:&mut Apply) {
         let mut errors = Some(Vec::new());
         self.doc = parse_html(&*self.html, &mut errors);
         if errors.as_ref().unwrap().len()>0{
             log!("HTML parser returned errors {:?}", errors)
         }
+        let text_flow = &self.text_flow;
+        self.ops = html_to_flow(&self.doc, &|template| text_flow.has_template(template));
+        for op in &self.ops{
+            if let HtmlFlowOp::Image{src, ..} = op{
+                if !self.images.contains_key(src){
+                    let mut image = HtmlImage::default();
+                    image.lazy_create_image_cache(cx);
+                    let _ = image.load_image_dep_by_path(cx, src);
+                    self.images.insert(src.clone(), image);
+                }
+            }
+        }
     }
 }
- 
+
 impl Widget for Html {
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
         self.text_flow.handle_event(cx, event, scope);
     }
-    
+
     fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk:Walk)->DrawStep{
         let tf = &mut self.text_flow;
         tf.begin(cx, walk);
-        let mut auto_id = 0;
-        // alright lets iterate the html doc and draw it
-        let mut node = self.doc.walk();
-        while !node.empty(){
-            
-            match node.open_tag_lc(){
-                some_id!(a)=>{
-                    node = node.jump_to_close();
-                }
-                some_id!(h1)=>{
-                    tf.push_scale(1.5)
-                },
-                some_id!(b)=>tf.push_bold(),
-                some_id!(i)=>tf.push_italic(),
-                Some(_)=>{ // custom widget
-                    let id = if let Some(id) = node.find_attr_lc(live_id!(id)){
-                        LiveId::from_str(id).0
-                    }
-                    else{
-                        auto_id += 1;
-                        auto_id
+        for op in &self.ops{
+            match op{
+                HtmlFlowOp::Text(text)=>tf.draw_text(cx, text),
+                HtmlFlowOp::PushBold=>tf.push_bold(),
+                HtmlFlowOp::PopBold=>tf.pop_bold(),
+                HtmlFlowOp::PushItalic=>tf.push_italic(),
+                HtmlFlowOp::PopItalic=>tf.pop_italic(),
+                HtmlFlowOp::PushFixed=>tf.push_fixed(),
+                HtmlFlowOp::PopFixed=>tf.pop_fixed(),
+                HtmlFlowOp::PushScale(scale)=>tf.push_scale(*scale),
+                HtmlFlowOp::PopSize=>tf.pop_size(),
+                HtmlFlowOp::PushColor(color)=>tf.push_color(Vec4::from_u32(*color)),
+                HtmlFlowOp::PopColor | HtmlFlowOp::EndLink=>tf.pop_color(),
+                HtmlFlowOp::BeginLink(_)=>tf.push_color(self.link_color),
+                HtmlFlowOp::Break=>tf.draw_break(cx),
+                HtmlFlowOp::NewLine=>tf.new_line(cx),
+                HtmlFlowOp::Gap=>tf.draw_gap(cx),
+                HtmlFlowOp::BeginQuote=>tf.begin_block(cx, FlowBlockType::Quote),
+                HtmlFlowOp::BeginCode=>tf.begin_block(cx, FlowBlockType::Code),
+                HtmlFlowOp::EndQuote | HtmlFlowOp::EndCode=>tf.end_block(cx),
+                HtmlFlowOp::BeginListItem(marker)=>tf.begin_list_item(cx, marker),
+                HtmlFlowOp::EndListItem=>tf.end_list_item(cx),
+                HtmlFlowOp::BeginTable(columns)=>tf.begin_table(cx, *columns),
+                HtmlFlowOp::EndTable=>tf.end_table(cx),
+                HtmlFlowOp::BeginTableRow=>tf.begin_table_row(cx),
+                HtmlFlowOp::EndTableRow=>tf.end_table_row(cx),
+                HtmlFlowOp::BeginTableCell=>tf.begin_table_cell(cx),
+                HtmlFlowOp::EndTableCell=>tf.end_table_cell(cx),
+                HtmlFlowOp::Rule=>tf.draw_rule(cx),
+                HtmlFlowOp::Image{src, width, height}=>{
+                    let Some(texture) = self.images.get(src).and_then(|image| image.texture.as_ref()) else{
+                        continue
                     };
-                    let template = node.open_tag_nc().unwrap();
-                    if let Some(item) = tf.item(cx, id, template){
-                        item.set_text(node.find_text().unwrap_or(""));
+                    // a missing size follows from the other one and the aspect ratio of the image
+                    let (tex_width, tex_height) = texture.get_format(cx).vec_width_height().unwrap_or((1, 1));
+                    let (tex_width, tex_height) = (tex_width.max(1) as f64, tex_height.max(1) as f64);
+                    let (width, height) = match (width, height){
+                        (Some(width), Some(height))=>(*width, *height),
+                        (Some(width), None)=>(*width, width * tex_height / tex_width),
+                        (None, Some(height))=>(height * tex_width / tex_height, *height),
+                        (None, None)=>(tex_width, tex_height),
+                    };
+                    tf.draw_image(cx, texture, width, height);
+                }
+                HtmlFlowOp::Widget{id, template, text}=>{
+                    if let Some(item) = tf.item(cx, *id, *template){
+                        item.set_text(text);
                         item.draw_all(cx, scope);
                     }
-                    node = node.jump_to_close();
                 }
-                _=>()
-            } 
-            match node.close_tag_lc(){
-                some_id!(h1)=>tf.pop_size(),
-                some_id!(b)=>tf.pop_bold(),
-                some_id!(i)=>tf.pop_italic(),
-                _=>()
             }
-            if let Some(text) = node.text(){
-                tf.draw_text(cx, text);
-            }
-            node = node.walk();
         }
         tf.end(cx);
         DrawStep::done()
-    }  
-     
+    }
+
     fn text(&self)->String{
         self.html.as_ref().to_string()
-    } 
-    
+    }
+
     fn set_text(&mut self, v:&str){
         self.html = Rc::new(v.to_string())
     }
-} 
- 
\ No newline at end of file
+}
+
+// an element that is still open, with the ops that close it
+struct OpenElement{
+    tag: LiveId,
+    close_ops: Vec<HtmlFlowOp>,
+    is_list: bool,
+    is_pre: bool,
+}
+
+struct ListState{
+    ordered: bool,
+    next: i64,
+}
+
+#[derive(Default)]
+struct FlowBuilder{
+    ops: Vec<HtmlFlowOp>,
+    open: Vec<OpenElement>,
+    lists: Vec<ListState>,
+    pre_depth: usize,
+    auto_id: u64,
+}
+
+impl FlowBuilder{
+    fn push(&mut self, op: HtmlFlowOp){
+        match op{
+            // line breaks collapse, and are not needed at the start or end of a block
+            HtmlFlowOp::NewLine | HtmlFlowOp::Gap=>{
+                match self.ops.last(){
+                    None | Some(HtmlFlowOp::Gap) | Some(HtmlFlowOp::BeginQuote) | Some(HtmlFlowOp::BeginCode) |
+                    Some(HtmlFlowOp::BeginListItem(_)) | Some(HtmlFlowOp::BeginTableCell)=>return,
+                    Some(HtmlFlowOp::NewLine) if op == HtmlFlowOp::NewLine=>return,
+                    Some(HtmlFlowOp::NewLine)=>{self.ops.pop();}
+                    _=>()
+                }
+            }
+            HtmlFlowOp::EndQuote | HtmlFlowOp::EndCode | HtmlFlowOp::EndListItem | HtmlFlowOp::EndTableCell=>{
+                while let Some(HtmlFlowOp::NewLine) | Some(HtmlFlowOp::Gap) = self.ops.last(){
+                    self.ops.pop();
+                }
+            }
+            _=>()
+        }
+        self.ops.push(op);
+    }
+
+    fn at_line_start(&self)->bool{
+        for op in self.ops.iter().rev(){
+            match op{
+                HtmlFlowOp::PushBold | HtmlFlowOp::PopBold | HtmlFlowOp::PushItalic | HtmlFlowOp::PopItalic |
+                HtmlFlowOp::PushFixed | HtmlFlowOp::PopFixed | HtmlFlowOp::PushScale(_) | HtmlFlowOp::PopSize |
+                HtmlFlowOp::PushColor(_) | HtmlFlowOp::PopColor | HtmlFlowOp::BeginLink(_) | HtmlFlowOp::EndLink=>(),
+                HtmlFlowOp::Text(_) | HtmlFlowOp::Image{..} | HtmlFlowOp::Widget{..}=>return false,
+                _=>return true
+            }
+        }
+        true
+    }
+
+    fn text(&mut self, text: &str){
+        if self.pre_depth > 0{
+            // a newline right after the opening tag is not part of the content
+            let text = if self.at_line_start(){text.strip_prefix('\n').unwrap_or(text)}else{text};
+            for (i, line) in text.split('\n').enumerate(){
+                if i > 0{
+                    self.push(HtmlFlowOp::Break);
+                }
+                if !line.is_empty(){
+                    self.push(HtmlFlowOp::Text(line.to_string()));
+                }
+            }
+            return
+        }
+        let mut text: String = text.chars().map(|c| if c.is_whitespace(){' '}else{c}).collect();
+        if self.at_line_start(){
+            text = text.trim_start().to_string();
+        }
+        if !text.is_empty(){
+            self.push(HtmlFlowOp::Text(text));
+        }
+    }
+
+    fn open(&mut self, tag: LiveId, close_ops: Vec<HtmlFlowOp>){
+        self.open.push(OpenElement{tag, close_ops, is_list: false, is_pre: false});
+    }
+
+    fn close(&mut self, tag: LiveId){
+        // closing an element closes the ones inside it that were left open
+        if !self.open.iter().any(|open| open.tag == tag){
+            return
+        }
+        while let Some(open) = self.open.pop(){
+            for op in open.close_ops{
+                self.push(op);
+            }
+            if open.is_list{
+                self.lists.pop();
+            }
+            if open.is_pre{
+                self.pre_depth -= 1;
+            }
+            if open.tag == tag{
+                break
+            }
+        }
+    }
+
+    // closes an element of one of the given tags that was left open inside the nearest of the
+    // boundary tags, like a list item when the next one starts
+    fn close_implied(&mut self, tags: &[LiveId], boundary: &[LiveId]){
+        for open in self.open.iter().rev(){
+            if boundary.contains(&open.tag){
+                return
+            }
+            if tags.contains(&open.tag){
+                let tag = open.tag;
+                self.close(tag);
+                return
+            }
+        }
+    }
+}
+
+fn find_attr(nodes: &[HtmlNode], decoded: &str, index: usize, attr: LiveId)->Option<String>{
+    for node in &nodes[index + 1..]{
+        match node{
+            HtmlNode::Attribute{lc, start, end, ..}=>if *lc == attr{
+                return Some(decoded[*start..*end].to_string())
+            }
+            _=>return None
+        }
+    }
+    None
+}
+
+// the index of the node that closes the element opened at the given index
+fn find_close(nodes: &[HtmlNode], index: usize)->usize{
+    let mut depth = 0;
+    for (i, node) in nodes.iter().enumerate().skip(index + 1){
+        match node{
+            HtmlNode::OpenTag{lc, ..} if !is_void_tag(*lc)=>depth += 1,
+            HtmlNode::CloseTag{lc, ..} if !is_void_tag(*lc)=>{
+                if depth == 0{
+                    return i
+                }
+                depth -= 1;
+            }
+            _=>()
+        }
+    }
+    nodes.len()
+}
+
+fn is_void_tag(tag: LiveId)->bool{
+    tag == live_id!(br) || tag == live_id!(hr) || tag == live_id!(img)
+}
+
+// the most cells in a row of the table opened at the given index, leaving out nested tables
+fn table_columns(nodes: &[HtmlNode], index: usize)->usize{
+    let mut depth = 0;
+    let mut columns = 0;
+    let mut cells = 0;
+    for node in &nodes[index + 1..find_close(nodes, index)]{
+        match node{
+            HtmlNode::OpenTag{lc, ..} if *lc == live_id!(table)=>depth += 1,
+            HtmlNode::CloseTag{lc, ..} if *lc == live_id!(table)=>depth -= 1,
+            HtmlNode::OpenTag{lc, ..} if depth == 0 && *lc == live_id!(tr)=>cells = 0,
+            HtmlNode::OpenTag{lc, ..} if depth == 0 && (*lc == live_id!(td) || *lc == live_id!(th))=>{
+                cells += 1;
+                columns = columns.max(cells);
+            }
+            _=>()
+        }
+    }
+    columns.max(1)
+}
+
+fn parse_length(value: &str)->Option<f64>{
+    value.trim().trim_end_matches("px").trim().parse().ok()
+}
+
+// the color of a `style` attribute, as `0xRRGGBBAA`
+fn parse_style_color(style: &str)->Option<u32>{
+    let value = style.split(';').find_map(|decl|{
+        let (name, value) = decl.split_once(':')?;
+        (name.trim().eq_ignore_ascii_case("color")).then(|| value.trim().to_lowercase())
+    })?;
+    if let Some(hex) = value.strip_prefix('#'){
+        let digits = u32::from_str_radix(hex, 16).ok()?;
+        return match hex.len(){
+            3=>{
+                let (r, g, b) = ((digits >> 8) & 0xf, (digits >> 4) & 0xf, digits & 0xf);
+                Some((r * 0x11) << 24 | (g * 0x11) << 16 | (b * 0x11) << 8 | 0xff)
+            }
+            6=>Some(digits << 8 | 0xff),
+            8=>Some(digits),
+            _=>None
+        }
+    }
+    match value.as_str(){
+        "black"=>Some(0x000000ff),
+        "white"=>Some(0xffffffff),
+        "gray" | "grey"=>Some(0x808080ff),
+        "red"=>Some(0xff0000ff),
+        "green"=>Some(0x008000ff),
+        "blue"=>Some(0x0000ffff),
+        "yellow"=>Some(0xffff00ff),
+        "orange"=>Some(0xffa500ff),
+        "purple"=>Some(0x800080ff),
+        _=>None
+    }
+}
+
+fn list_marker(list: &ListState, depth: usize)->String{
+    if list.ordered{
+        format!("{}.", list.next)
+    }
+    else{
+        match depth % 3{
+            1=>"•",
+            2=>"◦",
+            _=>"▪",
+        }.to_string()
+    }
+}
+
+/// Turns a parsed document into the ops that draw it. Tags that are not part of the supported
+/// subset become widgets if `has_template` knows their name, and otherwise just show their
+/// content.
+pub fn html_to_flow(doc: &HtmlDoc, has_template: &dyn Fn(LiveId)->bool)->Vec<HtmlFlowOp>{
+    let nodes = &doc.nodes;
+    let mut b = FlowBuilder::default();
+    let mut index = 0;
+    while index < nodes.len(){
+        match &nodes[index]{
+            HtmlNode::Text{start, end}=>b.text(&doc.decoded[*start..*end]),
+            HtmlNode::Attribute{..}=>(),
+            HtmlNode::CloseTag{lc, ..}=>b.close(*lc),
+            HtmlNode::OpenTag{lc, nc}=>{
+                let tag = *lc;
+                let mut close_ops = Vec::new();
+                // any element can set the color of its text
+                let color = find_attr(nodes, &doc.decoded, index, live_id!(style)).and_then(|style| parse_style_color(&style));
+                let push_color = |b: &mut FlowBuilder, close_ops: &mut Vec<HtmlFlowOp>|{
+                    if let Some(color) = color{
+                        b.push(HtmlFlowOp::PushColor(color));
+                        close_ops.insert(0, HtmlFlowOp::PopColor);
+                    }
+                };
+                match tag{
+                    live_id!(br)=>b.push(HtmlFlowOp::Break),
+                    live_id!(hr)=>{
+                        b.push(HtmlFlowOp::Gap);
+                        b.push(HtmlFlowOp::Rule);
+                        b.push(HtmlFlowOp::Gap);
+                    }
+                    live_id!(img)=>{
+                        if let Some(src) = find_attr(nodes, &doc.decoded, index, live_id!(src)){
+                            b.push(HtmlFlowOp::Image{
+                                src,
+                                width: find_attr(nodes, &doc.decoded, index, live_id!(width)).and_then(|v| parse_length(&v)),
+                                height: find_attr(nodes, &doc.decoded, index, live_id!(height)).and_then(|v| parse_length(&v)),
+                            });
+                        }
+                    }
+                    live_id!(p)=>{
+                        b.close_implied(&[live_id!(p)], &[live_id!(blockquote), live_id!(li), live_id!(td), live_id!(th)]);
+                        b.push(HtmlFlowOp::Gap);
+                        push_color(&mut b, &mut close_ops);
+                        close_ops.push(HtmlFlowOp::Gap);
+                        b.open(tag, close_ops);
+                    }
+                    live_id!(div)=>{
+                        b.push(HtmlFlowOp::NewLine);
+                        push_color(&mut b, &mut close_ops);
+                        close_ops.push(HtmlFlowOp::NewLine);
+                        b.open(tag, close_ops);
+                    }
+                    live_id!(h1) | live_id!(h2) | live_id!(h3) | live_id!(h4) | live_id!(h5) | live_id!(h6)=>{
+                        let scale = match tag{
+                            live_id!(h1)=>2.0,
+                            live_id!(h2)=>1.5,
+                            live_id!(h3)=>1.17,
+                            live_id!(h4)=>1.0,
+                            live_id!(h5)=>0.83,
+                            _=>0.67,
+                        };
+                        b.push(HtmlFlowOp::Gap);
+                        b.push(HtmlFlowOp::PushScale(scale));
+                        b.push(HtmlFlowOp::PushBold);
+                        push_color(&mut b, &mut close_ops);
+                        close_ops.extend([HtmlFlowOp::PopBold, HtmlFlowOp::PopSize, HtmlFlowOp::Gap]);
+                        b.open(tag, close_ops);
+                    }
+                    live_id!(b) | live_id!(strong)=>{
+                        b.push(HtmlFlowOp::PushBold);
+                        push_color(&mut b, &mut close_ops);
+                        close_ops.push(HtmlFlowOp::PopBold);
+                        b.open(tag, close_ops);
+                    }
+                    live_id!(i) | live_id!(em)=>{
+                        b.push(HtmlFlowOp::PushItalic);
+                        push_color(&mut b, &mut close_ops);
+                        close_ops.push(HtmlFlowOp::PopItalic);
+                        b.open(tag, close_ops);
+                    }
+                    live_id!(code)=>{
+                        b.push(HtmlFlowOp::PushFixed);
+                        push_color(&mut b, &mut close_ops);
+                        close_ops.push(HtmlFlowOp::PopFixed);
+                        b.open(tag, close_ops);
+                    }
+                    live_id!(a)=>{
+                        let href = find_attr(nodes, &doc.decoded, index, live_id!(href)).unwrap_or_default();
+                        b.push(HtmlFlowOp::BeginLink(href));
+                        b.open(tag, vec![HtmlFlowOp::EndLink]);
+                    }
+                    live_id!(pre)=>{
+                        b.push(HtmlFlowOp::Gap);
+                        b.push(HtmlFlowOp::BeginCode);
+                        b.push(HtmlFlowOp::PushFixed);
+                        push_color(&mut b, &mut close_ops);
+                        close_ops.extend([HtmlFlowOp::PopFixed, HtmlFlowOp::EndCode, HtmlFlowOp::Gap]);
+                        b.open(tag, close_ops);
+                        b.open.last_mut().unwrap().is_pre = true;
+                        b.pre_depth += 1;
+                    }
+                    live_id!(blockquote)=>{
+                        b.push(HtmlFlowOp::Gap);
+                        b.push(HtmlFlowOp::BeginQuote);
+                        push_color(&mut b, &mut close_ops);
+                        close_ops.extend([HtmlFlowOp::EndQuote, HtmlFlowOp::Gap]);
+                        b.open(tag, close_ops);
+                    }
+                    live_id!(ul) | live_id!(ol)=>{
+                        // nested lists follow the line of the item they are in
+                        let sep = if b.lists.is_empty(){HtmlFlowOp::Gap}else{HtmlFlowOp::NewLine};
+                        b.push(sep.clone());
+                        push_color(&mut b, &mut close_ops);
+                        close_ops.push(sep);
+                        b.open(tag, close_ops);
+                        b.open.last_mut().unwrap().is_list = true;
+                        let next = find_attr(nodes, &doc.decoded, index, live_id!(start)).and_then(|v| v.trim().parse().ok()).unwrap_or(1);
+                        b.lists.push(ListState{ordered: tag == live_id!(ol), next});
+                    }
+                    live_id!(li)=>{
+                        b.close_implied(&[live_id!(li)], &[live_id!(ul), live_id!(ol)]);
+                        let depth = b.lists.len();
+                        let marker = match b.lists.last_mut(){
+                            Some(list)=>{
+                                let marker = list_marker(list, depth);
+                                list.next += 1;
+                                marker
+                            }
+                            None=>list_marker(&ListState{ordered: false, next: 1}, 1),
+                        };
+                        b.push(HtmlFlowOp::BeginListItem(marker));
+                        push_color(&mut b, &mut close_ops);
+                        close_ops.push(HtmlFlowOp::EndListItem);
+                        b.open(tag, close_ops);
+                    }
+                    live_id!(table)=>{
+                        b.push(HtmlFlowOp::Gap);
+                        b.push(HtmlFlowOp::BeginTable(table_columns(nodes, index)));
+                        push_color(&mut b, &mut close_ops);
+                        close_ops.extend([HtmlFlowOp::EndTable, HtmlFlowOp::Gap]);
+                        b.open(tag, close_ops);
+                    }
+                    live_id!(tr)=>{
+                        b.close_implied(&[live_id!(tr)], &[live_id!(table)]);
+                        b.push(HtmlFlowOp::BeginTableRow);
+                        b.open(tag, vec![HtmlFlowOp::EndTableRow]);
+                    }
+                    live_id!(td) | live_id!(th)=>{
+                        b.close_implied(&[live_id!(td), live_id!(th)], &[live_id!(tr), live_id!(table)]);
+                        b.push(HtmlFlowOp::BeginTableCell);
+                        if tag == live_id!(th){
+                            b.push(HtmlFlowOp::PushBold);
+                            close_ops.push(HtmlFlowOp::PopBold);
+                        }
+                        push_color(&mut b, &mut close_ops);
+                        close_ops.push(HtmlFlowOp::EndTableCell);
+                        b.open(tag, close_ops);
+                    }
+                    _ if has_template(*nc)=>{ // custom widget
+                        let id = if let Some(id) = find_attr(nodes, &doc.decoded, index, live_id!(id)){
+                            LiveId::from_str(&id).0
+                        }
+                        else{
+                            b.auto_id += 1;
+                            b.auto_id
+                        };
+                        let close = find_close(nodes, index);
+                        let text = nodes[index + 1..close].iter().find_map(|node| match node{
+                            HtmlNode::Text{start, end}=>Some(doc.decoded[*start..*end].to_string()),
+                            _=>None
+                        }).unwrap_or_default();
+                        b.push(HtmlFlowOp::Widget{id, template: *nc, text});
+                        index = close;
+                    }
+                    _=>{
+                        // unknown tags like span just show their content
+                        push_color(&mut b, &mut close_ops);
+                        b.open(tag, close_ops);
+                    }
+                }
+            }
+        }
+        index += 1;
+    }
+    while let Some(open) = b.open.last(){
+        let tag = open.tag;
+        b.close(tag);
+    }
+    while let Some(HtmlFlowOp::NewLine) | Some(HtmlFlowOp::Gap) = b.ops.last(){
+        b.ops.pop();
+    }
+    b.ops
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use HtmlFlowOp::*;
+
+    fn flow(html: &str)->Vec<HtmlFlowOp>{
+        let doc = parse_html(html, &mut None);
+        html_to_flow(&doc, &|template| template == live_id!(Button))
+    }
+
+    fn text(text: &str)->HtmlFlowOp{
+        Text(text.to_string())
+    }
+
+    #[test]
+    fn paragraphs_and_headings(){
+        assert_eq!(flow("<h2>Title</h2>\n<p>One <b>bold</b></p>\n<p>Two</p>"), vec![
+            PushScale(1.5), PushBold, text("Title"), PopBold, PopSize,
+            Gap, text("One "), PushBold, text("bold"), PopBold,
+            Gap, text("Two"),
+        ]);
+    }
+
+    #[test]
+    fn nested_lists(){
+        assert_eq!(flow("<ul>\n<li>a\n<ol start=\"3\"><li>b</li><li>c</ol></li>\n<li>d</ul>"), vec![
+            BeginListItem("•".into()), text("a "), NewLine,
+            BeginListItem("3.".into()), text("b"), EndListItem,
+            BeginListItem("4.".into()), text("c"), EndListItem,
+            EndListItem,
+            BeginListItem("•".into()), text("d"), EndListItem,
+        ]);
+    }
+
+    #[test]
+    fn code_blocks_keep_whitespace(){
+        assert_eq!(flow("<p>Use <code>x&lt;y</code></p><pre>\nfn  main() {\n    x\n}\n</pre>"), vec![
+            text("Use "), PushFixed, text("x<y"), PopFixed,
+            Gap, BeginCode, PushFixed,
+            text("fn  main() {"), Break, text("    x"), Break, text("}"), Break,
+            PopFixed, EndCode,
+        ]);
+    }
+
+    #[test]
+    fn quotes_breaks_and_rules(){
+        assert_eq!(flow("<blockquote><p>a<br>b</p></blockquote><hr/>c"), vec![
+            BeginQuote, text("a"), Break, text("b"), EndQuote,
+            Gap, Rule, Gap, text("c"),
+        ]);
+    }
+
+    #[test]
+    fn tables(){
+        assert_eq!(flow("<table><tr><th>k</th><th>v</th></tr><tr><td>a<td>b</tr></table>"), vec![
+            BeginTable(2),
+            BeginTableRow, BeginTableCell, PushBold, text("k"), PopBold, EndTableCell,
+            BeginTableCell, PushBold, text("v"), PopBold, EndTableCell, EndTableRow,
+            BeginTableRow, BeginTableCell, text("a"), EndTableCell,
+            BeginTableCell, text("b"), EndTableCell, EndTableRow,
+            EndTable,
+        ]);
+    }
+
+    #[test]
+    fn images_colors_links_and_widgets(){
+        assert_eq!(flow("<span style=\"font-weight:bold; color: #f80\">hot</span> <a href=\"x\">link</a><img src=\"crate://self/a.png\" width=\"20px\"><Button id=\"ok\">OK</Button><blink>?</blink>"), vec![
+            PushColor(0xff8800ff), text("hot"), PopColor, text(" "),
+            BeginLink("x".into()), text("link"), EndLink,
+            Image{src: "crate://self/a.png".into(), width: Some(20.0), height: None},
+            HtmlFlowOp::Widget{id: LiveId::from_str("ok").0, template: live_id!(Button), text: "OK".into()},
+            text("?"),
+        ]);
+    }
+}
@@ -4,7 +4,6 @@ use crate::{
     makepad_draw::*,
     widget::*
 };
-use std::fmt;
 
 live_design!{
     ImageBase = {{Image}} {}
@@ -111,6 +110,15 @@ impl ImageRef {
         }
     }
     
+    /// Loads an image into this `ImageRef`, detecting the format of the encoded `data`.
+    pub fn load_image_from_data(&self, cx: &mut Cx, data: &[u8]) -> Result<(), ImageError> {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.load_image_from_data(cx, data)
+        } else {
+            Ok(()) // preserving existing behavior of silent failures.
+        }
+    }
+    
     /// Loads a JPEG into this `ImageRef` by decoding the given encoded JPEG `data`.
     pub fn load_jpg_from_data(&self, cx: &mut Cx, data: &[u8]) -> Result<(), ImageError> {
         if let Some(mut inner) = self.borrow_mut() {
@@ -144,25 +152,4 @@ impl ImageRef {
 
 
 /// The possible errors that can occur when loading or creating an image texture.
-#[derive(Debug)]
-pub enum ImageError {
-    /// The image data buffer was empty.
-    EmptyData,
-    /// The image's pixel data was not aligned to 3-byte or 4-byte pixels.
-    /// The unsupported alignment value (in bytes) is included.
-    InvalidPixelAlignment(usize),
-    /// The image data could not be decoded as a JPEG.
-    JpgDecode(JpgDecodeErrors),
-    /// The image file at the given resource path could not be found.
-    PathNotFound(String),
-    /// The image data could not be decoded as a PNG.
-    PngDecode(PngDecodeErrors),
-    /// The image data was in an unsupported format.
-    /// Currently, only JPEG and PNG are supported.
-    UnsupportedFormat,
-}
-impl fmt::Display for ImageError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{self:?}")
-    }
-}
\ No newline at end of file
+pub use makepad_image_formats::ImageError;
@@ -1,10 +1,12 @@
 use crate::{makepad_draw::*, ImageError};
 use std::collections::HashMap;
-use makepad_zune_jpeg::JpegDecoder;
-use makepad_zune_png::PngDecoder;
 
-pub use makepad_zune_png::error::PngDecodeErrors;
-pub use makepad_zune_jpeg::errors::DecodeErrors as JpgDecodeErrors;
+pub use makepad_image_formats::{
+    ImageBuffer,
+    ImageFormat,
+    PngDecodeErrors,
+    JpgDecodeErrors,
+};
 
 #[derive(Live, LiveHook)]
 #[live_ignore]
@@ -16,56 +18,13 @@ pub enum ImageFit{
     Biggest
 }
 
-
-#[derive(Default, Clone)] 
-pub struct ImageBuffer {
-    pub width: usize,
-    pub height: usize,
-    pub data: Vec<u32>,
+/// Uploads a decoded `ImageBuffer` to the GPU.
+pub trait ImageBufferTextureExt {
+    fn into_new_texture(self, cx:&mut Cx)->Texture;
 }
 
-impl ImageBuffer {
-    pub fn new(in_data: &[u8], width: usize, height: usize) -> Result<ImageBuffer, ImageError> {
-        let mut out = Vec::new();
-        let pixels = width * height;
-        out.resize(pixels, 0u32);
-        // input pixel packing
-        match in_data.len() / pixels {
-            4 => for i in 0..pixels {
-                let r = in_data[i*4];
-                let g = in_data[i*4+1];
-                let b = in_data[i*4+2];
-                let a = in_data[i*4+3];
-                out[i] = ((a as u32)<<24) | ((r as u32)<<16) | ((g as u32)<<8) | ((b as u32)<<0);
-            }
-            3 => for i in 0..pixels {
-                let r = in_data[i*3];
-                let g = in_data[i*3+1];
-                let b = in_data[i*3+2];
-                out[i] = 0xff000000 | ((r as u32)<<16) | ((g as u32)<<8) | ((b as u32)<<0);
-            }
-            2 => for i in 0..pixels {
-                let r = in_data[i*2];
-                let a = in_data[i*2+1];
-                out[i] = ((a as u32)<<24) | ((r as u32)<<16) | ((r as u32)<<8) | ((r as u32)<<0);
-            }
-            1 => for i in 0..pixels {
-                let r = in_data[i];
-                out[i] = ((0xff as u32)<<24) | ((r as u32)<<16) | ((r as u32)<<8) | ((r as u32)<<0);
-            }   
-            unsupported => {
-                error!("ImageBuffer::new Image buffer pixel alignment of {unsupported} is unsupported.");
-                return Err(ImageError::InvalidPixelAlignment(unsupported));
-            }     
-        }
-        Ok(ImageBuffer {
-            width,
-            height,
-            data: out
-        })
-    }
-    
-    pub fn into_new_texture(self, cx:&mut Cx)->Texture{
+impl ImageBufferTextureExt for ImageBuffer {
+    fn into_new_texture(self, cx:&mut Cx)->Texture{
         let texture = Texture::new_with_format(cx, TextureFormat::VecBGRAu8_32 {
             width: self.width,
             height: self.height,
@@ -73,43 +32,19 @@ impl ImageBuffer {
         });
         texture
     }
-    
-    pub fn from_png(
-        data: &[u8]
-    ) -> Result<Self, ImageError> {
-        let mut decoder = PngDecoder::new(data);
-        match decoder.decode() {
-            Ok(image) => {
-                if let Some(data) = image.u8() {
-                    let (width,height) = decoder.get_dimensions().unwrap();
-                    ImageBuffer::new(&data, width as usize, height as usize)
-                }
-                else{
-                    error!("Error decoding PNG: image data empty");
-                    Err(ImageError::EmptyData)
-                }
-            }
-            Err(err) => {
-                error!("Error decoding PNG: {:?}", err);
-                Err(ImageError::PngDecode(err))
-            }
-        }
-    }
+}
 
-    pub fn from_jpg(
-        data: &[u8]
-    ) -> Result<Self, ImageError> {
-        let mut decoder = JpegDecoder::new(&*data);
-        // decode the file
-        match decoder.decode() {
-            Ok(data) => {
-                let info = decoder.info().unwrap();
-                ImageBuffer::new(&data, info.width as usize, info.height as usize)
-            },
-            Err(err) => {
-                error!("Error decoding JPG: {:?}", err);
-                Err(ImageError::JpgDecode(err))
-            }
+/// Converts the pixels of a texture or pass readback, to encode or save them.
+pub trait ReadbackImageBufferExt {
+    fn to_image_buffer(&self)->ImageBuffer;
+}
+
+impl ReadbackImageBufferExt for ReadbackImage {
+    fn to_image_buffer(&self)->ImageBuffer{
+        ImageBuffer {
+            width: self.width,
+            height: self.height,
+            data: self.to_bgra_u32()
         }
     }
 }
@@ -136,6 +71,20 @@ pub trait ImageCacheImpl {
         }
     }
 
+    /// Decodes `data` in any supported format, detected from its contents.
+    fn load_image_from_data(&mut self, cx: &mut Cx, data: &[u8]) -> Result<(), ImageError> {
+        match makepad_image_formats::decode(data){
+            Ok(data)=>{
+                self.set_texture(Some(data.into_new_texture(cx)));
+                Ok(())
+            }
+            Err(err)=>{
+                error!("load_image_from_data: Cannot load image from data: {}", err);
+                Err(err)
+            }
+        }
+    }
+
     fn load_png_from_data(&mut self, cx: &mut Cx, data: &[u8]) -> Result<(), ImageError> {
         match ImageBuffer::from_png(&*data){
             Ok(data)=>{
@@ -148,7 +97,7 @@ pub trait ImageCacheImpl {
             }
         }
     }
-    
+
     fn load_jpg_from_data(&mut self, cx: &mut Cx, data: &[u8]) -> Result<(), ImageError> {
         match ImageBuffer::from_jpg(&*data){
             Ok(data)=>{
@@ -156,7 +105,7 @@ pub trait ImageCacheImpl {
                 Ok(())
             }
             Err(err)=>{
-                error!("load_jpg_from_data: Cannot load png image from data: {}", err);
+                error!("load_jpg_from_data: Cannot load jpeg image from data: {}", err);
                 Err(err)
             }
         }
@@ -174,35 +123,23 @@ pub trait ImageCacheImpl {
         else{
             match cx.get_dependency(image_path) {
                 Ok(data) => {
-                    if image_path.ends_with(".jpg") {
-                        match ImageBuffer::from_jpg(&*data){
-                            Ok(data)=>{
-                                let texture = data.into_new_texture(cx);
-                                cx.get_global::<ImageCache>().map.insert(image_path.to_string(), texture.clone());
-                                self.set_texture(Some(texture));
-                                Ok(())
-                            }
-                            Err(err)=>{
-                                error!("load_image_dep_by_path: Cannot load jpeg image from path: {} {}", image_path, err);
-                                Err(err)
-                            }
+                    // trust the file contents over the extension
+                    let format = ImageFormat::sniff(&data).or_else(|| ImageFormat::from_path(image_path));
+                    let Some(format) = format else {
+                        error!("load_image_dep_by_path: Image format not supported {}", image_path);
+                        return Err(ImageError::UnsupportedFormat)
+                    };
+                    match makepad_image_formats::decode_as(&data, format){
+                        Ok(data)=>{
+                            let texture = data.into_new_texture(cx);
+                            cx.get_global::<ImageCache>().map.insert(image_path.to_string(), texture.clone());
+                            self.set_texture(Some(texture));
+                            Ok(())
                         }
-                    } else if image_path.ends_with(".png") {
-                        match ImageBuffer::from_png(&*data){
-                            Ok(data)=>{
-                                let texture = data.into_new_texture(cx);
-                                cx.get_global::<ImageCache>().map.insert(image_path.to_string(), texture.clone());
-                                self.set_texture(Some(texture));
-                                Ok(())
-                            }
-                            Err(err)=>{
-                                error!("load_image_dep_by_path: Cannot load png image from path: {} {}", image_path, err);
-                                Err(err)
-                            }
+                        Err(err)=>{
+                            error!("load_image_dep_by_path: Cannot load {} image from path: {} {}", format.extension(), image_path, err);
+                            Err(err)
                         }
-                    } else {
-                        error!("load_image_dep_by_path: Image format not supported {}", image_path);
-                        Err(ImageError::UnsupportedFormat)
                     }
                 }
                 Err(err) => {
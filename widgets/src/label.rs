@@ -24,6 +24,10 @@ impl Widget for Label {
 
     fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk:Walk)->DrawStep{
         self.draw_text.draw_walk(cx, walk.with_add_padding(self.padding), self.align, self.text.as_ref());
+        cx.add_access_node(
+            AccessNode::new(self.widget_uid().0, AccessRole::Label, self.draw_text.area())
+                .with_name(self.text.as_ref())
+        );
         DrawStep::done()
     }
     
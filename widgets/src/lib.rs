@@ -1,6 +1,8 @@
 pub use makepad_draw::makepad_platform;
 pub use makepad_draw;
 pub use makepad_html;
+pub use makepad_markdown;
+pub use makepad_image_formats;
 pub use makepad_derive_widget;
 pub use makepad_draw::*;
 pub use makepad_derive_widget::*;
@@ -35,6 +37,7 @@ pub mod window;
 pub mod scroll_shadow;
 pub mod window_menu;
 pub mod html;
+pub mod markdown;
 pub mod text_flow;
 // Only available on Android at the moment
 // #[cfg(target_os="android")]
@@ -44,8 +47,10 @@ pub mod slide_panel;
 pub mod page_flip;
 pub mod keyboard_view;
 pub mod flat_list;
+pub mod data_table;
 
 pub mod file_tree;
+pub mod file_dialog;
 pub mod slides_view;
 pub mod color_picker;
 
@@ -71,6 +76,7 @@ pub use crate::{
     button::*,
     view::*,
     image::*,
+    image_cache::{ImageBufferTextureExt, ReadbackImageBufferExt},
     label::*,
     slider::*,
     check_box::*,
@@ -81,6 +87,7 @@ pub use crate::{
     link_label::*,
     portal_list::*,
     flat_list::*,
+    data_table::*,
     page_flip::*,
     slide_panel::*,
     fold_button::*,
@@ -152,12 +159,14 @@ pub fn live_design(cx: &mut Cx) {
     crate::designer::live_design(cx);
     crate::portal_list::live_design(cx);
     crate::flat_list::live_design(cx);
+    crate::data_table::live_design(cx);
     crate::slide_panel::live_design(cx);
     crate::tab::live_design(cx);
     crate::tab_bar::live_design(cx);
     crate::dock::live_design(cx);
     crate::color_picker::live_design(cx);
     crate::file_tree::live_design(cx);
+    crate::file_dialog::live_design(cx);
     crate::slides_view::live_design(cx);
     crate::tab_close_button::live_design(cx);
     crate::keyboard_view::live_design(cx);
@@ -165,5 +174,6 @@ pub fn live_design(cx: &mut Cx) {
     crate::stack_navigation::live_design(cx);
     crate::expandable_panel::live_design(cx);
     crate::html::live_design(cx);
+    crate::markdown::live_design(cx);
     crate::text_flow::live_design(cx);
 }
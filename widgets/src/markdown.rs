@@ -0,0 +1,320 @@
+use {
+    crate::{
+        makepad_markdown::*,
+        makepad_derive_widget::*,
+        makepad_draw::*,
+        widget::*,
+        text_flow::{TextFlow, FlowBlockType},
+    },
+    std::{
+        collections::HashMap,
+        rc::Rc,
+    },
+};
+
+live_design!{
+    MarkdownBase = {{Markdown}} {}
+}
+
+/// The kinds of tokens that code blocks are highlighted with.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CodeTokenKind {
+    Plain,
+    BranchKeyword,
+    LoopKeyword,
+    OtherKeyword,
+    Comment,
+    Constant,
+    Number,
+    String,
+    Function,
+    Typename,
+    Punctuator,
+}
+
+/// Splits each line of a code block into tokens, as their length in bytes and their kind. The
+/// language is the info string of the fence, and `None` means it is not known.
+pub type CodeHighlightFn = fn(language: &str, code: &str) -> Option<Vec<Vec<(usize, CodeTokenKind)>>>;
+
+/// The highlighter that `Markdown` widgets use for code blocks, as a global. The code editor
+/// registers one that uses its tokenizers; without it code is not highlighted.
+pub struct CodeHighlighter(pub CodeHighlightFn);
+
+#[derive(Live, LiveHook, LiveRegister)]
+pub struct CodeColors {
+    #[live] plain: Vec4,
+    #[live] branch_keyword: Vec4,
+    #[live] loop_keyword: Vec4,
+    #[live] other_keyword: Vec4,
+    #[live] comment: Vec4,
+    #[live] constant: Vec4,
+    #[live] number: Vec4,
+    #[live] string: Vec4,
+    #[live] function: Vec4,
+    #[live] typename: Vec4,
+    #[live] punctuator: Vec4,
+}
+
+impl CodeColors {
+    fn color(&self, kind: CodeTokenKind) -> Vec4 {
+        match kind {
+            CodeTokenKind::Plain => self.plain,
+            CodeTokenKind::BranchKeyword => self.branch_keyword,
+            CodeTokenKind::LoopKeyword => self.loop_keyword,
+            CodeTokenKind::OtherKeyword => self.other_keyword,
+            CodeTokenKind::Comment => self.comment,
+            CodeTokenKind::Constant => self.constant,
+            CodeTokenKind::Number => self.number,
+            CodeTokenKind::String => self.string,
+            CodeTokenKind::Function => self.function,
+            CodeTokenKind::Typename => self.typename,
+            CodeTokenKind::Punctuator => self.punctuator,
+        }
+    }
+}
+
+type CodeTokens = Vec<Vec<(usize, CodeTokenKind)>>;
+
+/// Shows CommonMark text. Text that streams in can be added with `append`, which only parses
+/// the end of the document again.
+#[derive(Live, Widget)]
+pub struct Markdown {
+    #[deref] text_flow: TextFlow,
+    #[live] body: Rc<String>,
+    #[live] link_color: Vec4,
+    #[live] code_colors: CodeColors,
+    #[rust] doc: MarkdownDoc,
+    // the tokens of the code blocks, by their language and text
+    #[rust] code_tokens: HashMap<LiveId, Option<CodeTokens>>,
+}
+
+impl LiveHook for Markdown {
+    fn after_apply_from(&mut self, _cx: &mut Cx, _apply: &mut Apply) {
+        self.doc = MarkdownDoc::parse(&self.body);
+    }
+}
+
+impl Widget for Markdown {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.text_flow.handle_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.update_code_tokens(cx);
+        self.text_flow.begin(cx, walk);
+        let mut drawer = MarkdownDrawer {
+            tf: &mut self.text_flow,
+            link_color: self.link_color,
+            code_colors: &self.code_colors,
+            code_tokens: &self.code_tokens,
+        };
+        drawer.draw_blocks(cx, self.doc.blocks(), 0);
+        self.text_flow.end(cx);
+        DrawStep::done()
+    }
+
+    fn text(&self) -> String {
+        self.doc.source().to_string()
+    }
+
+    fn set_text(&mut self, v: &str) {
+        self.body = Rc::new(v.to_string());
+        self.doc = MarkdownDoc::parse(v);
+    }
+}
+
+fn code_key(info: &str, text: &str) -> LiveId {
+    LiveId::from_bytes(LiveId::from_str(info).0, text.as_bytes(), 0, text.len())
+}
+
+fn collect_code_blocks<'a>(blocks: &'a [MdBlock], out: &mut Vec<(&'a str, &'a str)>) {
+    for block in blocks {
+        match block {
+            MdBlock::Code {info, text} => out.push((info, text)),
+            MdBlock::Quote(blocks) => collect_code_blocks(blocks, out),
+            MdBlock::List {items, ..} => for item in items {
+                collect_code_blocks(item, out);
+            }
+            _ => ()
+        }
+    }
+}
+
+impl Markdown {
+    /// Adds text to the end of the document.
+    pub fn append(&mut self, cx: &mut Cx, text: &str) {
+        self.doc.append(text);
+        self.redraw(cx);
+    }
+
+    // tokenizes the code blocks that changed since the last draw, and forgets the ones that are gone
+    fn update_code_tokens(&mut self, cx: &mut Cx) {
+        let highlighter = if cx.has_global::<CodeHighlighter>() {
+            Some(cx.get_global::<CodeHighlighter>().0)
+        }
+        else {
+            None
+        };
+        let mut code_blocks = Vec::new();
+        collect_code_blocks(self.doc.blocks(), &mut code_blocks);
+        let mut code_tokens = HashMap::new();
+        for (info, text) in code_blocks {
+            let key = code_key(info, text);
+            let tokens = self.code_tokens.remove(&key).unwrap_or_else( || {
+                highlighter.and_then( | highlighter | highlighter(info, text))
+            });
+            code_tokens.insert(key, tokens);
+        }
+        self.code_tokens = code_tokens;
+    }
+}
+
+impl MarkdownRef {
+    pub fn append(&self, cx: &mut Cx, text: &str) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.append(cx, text);
+        }
+    }
+}
+
+struct MarkdownDrawer<'a> {
+    tf: &'a mut TextFlow,
+    link_color: Vec4,
+    code_colors: &'a CodeColors,
+    code_tokens: &'a HashMap<LiveId, Option<CodeTokens>>,
+}
+
+impl<'a> MarkdownDrawer<'a> {
+    fn draw_blocks(&mut self, cx: &mut Cx2d, blocks: &[MdBlock], list_depth: usize) {
+        for (i, block) in blocks.iter().enumerate() {
+            if i > 0 {
+                self.tf.draw_gap(cx);
+            }
+            self.draw_block(cx, block, list_depth);
+        }
+    }
+
+    fn draw_block(&mut self, cx: &mut Cx2d, block: &MdBlock, list_depth: usize) {
+        let tf = &mut *self.tf;
+        match block {
+            MdBlock::Heading {level, inlines} => {
+                let scale = match level {
+                    1 => 2.0,
+                    2 => 1.5,
+                    3 => 1.17,
+                    4 => 1.0,
+                    5 => 0.83,
+                    _ => 0.67,
+                };
+                tf.push_scale(scale);
+                tf.push_bold();
+                self.draw_inlines(cx, inlines);
+                self.tf.pop_bold();
+                self.tf.pop_size();
+                self.tf.new_line(cx);
+            }
+            MdBlock::Paragraph(inlines) => {
+                self.draw_inlines(cx, inlines);
+                self.tf.new_line(cx);
+            }
+            MdBlock::Code {info, text} => {
+                tf.begin_block(cx, FlowBlockType::Code);
+                tf.push_fixed();
+                let tokens = self.code_tokens.get(&code_key(info, text)).and_then( | tokens | tokens.as_ref());
+                for (index, line) in text.split('\n').enumerate() {
+                    if index > 0 {
+                        tf.draw_break(cx);
+                    }
+                    match tokens.and_then( | tokens | tokens.get(index)) {
+                        Some(line_tokens) => {
+                            let mut start = 0;
+                            for (len, kind) in line_tokens {
+                                let Some(token) = line.get(start..start + len) else {
+                                    break
+                                };
+                                tf.push_color(self.code_colors.color(*kind));
+                                tf.draw_text(cx, token);
+                                tf.pop_color();
+                                start += len;
+                            }
+                        }
+                        None => {
+                            tf.push_color(self.code_colors.plain);
+                            tf.draw_text(cx, line);
+                            tf.pop_color();
+                        }
+                    }
+                }
+                tf.pop_fixed();
+                tf.end_block(cx);
+            }
+            MdBlock::Quote(blocks) => {
+                tf.begin_block(cx, FlowBlockType::Quote);
+                self.draw_blocks(cx, blocks, list_depth);
+                self.tf.end_block(cx);
+            }
+            MdBlock::List {ordered, start, items} => {
+                for (index, item) in items.iter().enumerate() {
+                    let marker = if *ordered {
+                        format!("{}.", start + index as u64)
+                    }
+                    else {
+                        match list_depth % 3 {
+                            0 => "•",
+                            1 => "◦",
+                            _ => "▪",
+                        }.to_string()
+                    };
+                    self.tf.begin_list_item(cx, &marker);
+                    self.draw_blocks(cx, item, list_depth + 1);
+                    self.tf.end_list_item(cx);
+                }
+            }
+            MdBlock::Table {header, rows} => {
+                let columns = rows.iter().map( | row | row.len()).chain(Some(header.len())).max().unwrap_or(1);
+                tf.begin_table(cx, columns);
+                tf.begin_table_row(cx);
+                for cell in header {
+                    self.tf.begin_table_cell(cx);
+                    self.tf.push_bold();
+                    self.draw_inlines(cx, cell);
+                    self.tf.pop_bold();
+                    self.tf.end_table_cell(cx);
+                }
+                self.tf.end_table_row(cx);
+                for row in rows {
+                    self.tf.begin_table_row(cx);
+                    for cell in row {
+                        self.tf.begin_table_cell(cx);
+                        self.draw_inlines(cx, cell);
+                        self.tf.end_table_cell(cx);
+                    }
+                    self.tf.end_table_row(cx);
+                }
+                self.tf.end_table(cx);
+            }
+            MdBlock::Rule => tf.draw_rule(cx),
+        }
+    }
+
+    fn draw_inlines(&mut self, cx: &mut Cx2d, inlines: &[MdInline]) {
+        let tf = &mut *self.tf;
+        for inline in inlines {
+            match inline {
+                MdInline::Text(text) => tf.draw_text(cx, text),
+                MdInline::Code(code) => {
+                    tf.push_fixed();
+                    tf.draw_text(cx, code);
+                    tf.pop_fixed();
+                }
+                MdInline::BeginEmphasis => tf.push_italic(),
+                MdInline::EndEmphasis => tf.pop_italic(),
+                MdInline::BeginStrong => tf.push_bold(),
+                MdInline::EndStrong => tf.pop_bold(),
+                MdInline::BeginLink(_) => tf.push_color(self.link_color),
+                MdInline::EndLink => tf.pop_color(),
+                MdInline::LineBreak => tf.draw_break(cx),
+            }
+        }
+    }
+}
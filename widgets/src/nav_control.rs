@@ -12,7 +12,8 @@ pub struct NavControl {
     #[live] draw_list: DrawList2d,
     #[live] draw_focus: DrawQuad,
     #[live] draw_text: DrawText,
-    #[rust] _recent_focus: Area,
+    #[live(2.0)] focus_margin: f64,
+    #[rust] focus_visible: bool,
 }
 
 impl NavControl {
@@ -30,52 +31,65 @@ impl NavControl {
         }
     }
     
+    fn move_focus(&mut self, cx: &mut Cx, entry: &NavEntry) {
+        let mut stack = entry.scroll_stack.clone();
+        stack.push(entry.stop.area);
+        Self::send_trigger_to_scroll_stack(cx, stack);
+        cx.set_key_focus(entry.stop.area);
+        self.focus_visible = true;
+        self.draw_list.redraw(cx);
+    }
+    
     pub fn handle_event(&mut self, cx: &mut Cx, event: &Event, root: DrawListId) {
         match event {
-            Event::KeyDown(ke) => match ke.key_code {
-                KeyCode::Tab => {
-                    if ke.modifiers.shift {
-                        let mut prev_area = Area::Empty;
-                        if let Some((prev_area, scroll_stack)) = Cx2d::iterate_nav_stops(cx, root, | cx, stop | {
-                            if cx.has_key_focus(stop.area) {
-                                return Some(prev_area);
-                            }
-                            prev_area = stop.area;
-                            None
-                        }) {
-                            if !prev_area.is_empty() {
-                                Self::send_trigger_to_scroll_stack(cx, scroll_stack);
-                                cx.set_key_focus(prev_area);
-                            }
-                        }
-                    }
-                    else {
-                        let mut next_stop = false;
-                        if let Some((next_area, scroll_stack)) = Cx2d::iterate_nav_stops(cx, root, | cx, stop | {
-                            if next_stop {
-                                return Some(stop.area)
-                            }
-                            if cx.has_key_focus(stop.area) {
-                                next_stop = true;
-                            }
-                            None
-                        }) {
-                            Self::send_trigger_to_scroll_stack(cx, scroll_stack);
-                            cx.set_key_focus(next_area);
-                        }
-                    }
+            Event::KeyDown(ke) => {
+                let backward = match ke.key_code {
+                    KeyCode::Tab => ke.modifiers.shift,
+                    KeyCode::ArrowUp | KeyCode::ArrowLeft => true,
+                    KeyCode::ArrowDown | KeyCode::ArrowRight => false,
+                    _ => return
+                };
+                let stops = Cx2d::nav_stops(cx, root);
+                let focus = stops.focused(cx);
+                let next = if let KeyCode::Tab = ke.key_code {
+                    stops.next_tab_stop(focus, backward)
                 }
-                _ => ()
-            },
+                else {
+                    stops.next_arrow_stop(focus, backward)
+                };
+                if let Some(next) = next {
+                    self.move_focus(cx, &stops.entries[next]);
+                }
+            }
+            Event::KeyFocus(_) => {
+                self.draw_list.redraw(cx);
+            }
+            // the focus ring is only shown while the keyboard is used
+            Event::MouseDown(_) | Event::TouchUpdate(_) if self.focus_visible => {
+                self.focus_visible = false;
+                self.draw_list.redraw(cx);
+            }
             _ => ()
         }
     }
     
-    pub fn draw(&mut self, cx: &mut Cx2d) {
-        if !self.draw_list.begin(cx, Walk::default()).is_redrawing() {
-            return
+    pub fn draw(&mut self, cx: &mut Cx2d, root: DrawListId) {
+        self.draw_list.begin_overlay_last(cx);
+        if self.focus_visible {
+            let stops = Cx2d::nav_stops(cx, root);
+            if let Some(focus) = stops.focused(cx) {
+                let stop = &stops.entries[focus].stop;
+                let rect = stop.area.clipped_rect(cx);
+                let margin = self.focus_margin;
+                self.draw_focus.draw_abs(cx, Rect {
+                    pos: rect.pos - dvec2(stop.margin.left + margin, stop.margin.top + margin),
+                    size: rect.size + dvec2(
+                        stop.margin.left + stop.margin.right + 2.0 * margin,
+                        stop.margin.top + stop.margin.bottom + 2.0 * margin
+                    )
+                });
+            }
         }
-        
         self.draw_list.end(cx);
     }
 }
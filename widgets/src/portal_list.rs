@@ -77,7 +77,8 @@ pub struct PortalList {
     #[rust] templates: ComponentMap<LiveId, LivePtr>,
     #[rust] items: ComponentMap<(usize, LiveId), WidgetRef>,
     //#[rust(DragState::None)] drag_state: DragState,
-    #[rust(ScrollState::Stopped)] scroll_state: ScrollState
+    #[rust(ScrollState::Stopped)] scroll_state: ScrollState,
+    #[rust] access_group: Option<AccessGroupIndex>,
 }
 
 struct AlignItem {
@@ -136,6 +137,11 @@ impl PortalList {
     fn begin(&mut self, cx: &mut Cx2d, walk: Walk) {
         cx.begin_turtle(walk, self.layout);
         self.draw_align_list.clear();
+        self.access_group = Some(cx.begin_access_group(
+            AccessNode::new(self.widget_uid().0, AccessRole::List, Area::Empty)
+        ));
+        // the focusable items of a list are reached with the arrow keys
+        cx.begin_nav_scope(NavScopeKind::Arrows);
     }
     
     fn end(&mut self, cx: &mut Cx2d) {
@@ -293,6 +299,10 @@ impl PortalList {
             self.items.retain_visible();
         }
         cx.end_turtle_with_area(&mut self.area);
+        cx.end_nav_scope();
+        if let Some(access_group) = self.access_group.take() {
+            cx.end_access_group(access_group, self.area);
+        }
     }
     
     pub fn next_visible_item(&mut self, cx: &mut Cx2d) -> Option<usize> {
@@ -311,7 +321,8 @@ impl PortalList {
                         abs_pos: Some(dvec2(viewport.pos.x, viewport.pos.y + self.first_scroll)),
                         margin: Default::default(),
                         width: Size::Fill,
-                        height: Size::Fit
+                        height: Size::Fit,
+                        ..Walk::default()
                     }, Layout::flow_down());
                     return Some(self.first_id)
                 }
@@ -340,7 +351,8 @@ impl PortalList {
                                 abs_pos: Some(dvec2(viewport.pos.x, viewport.pos.y)),
                                 margin: Default::default(),
                                 width: Size::Fill,
-                                height: Size::Fit
+                                height: Size::Fit,
+                                ..Walk::default()
                             }, Layout::flow_down());
                             return Some(self.first_id - 1);
                         }
@@ -367,7 +379,8 @@ impl PortalList {
                         abs_pos: Some(dvec2(viewport.pos.x, viewport.pos.y + pos + rect.size.index(vi))),
                         margin: Default::default(),
                         width: Size::Fill,
-                        height: Size::Fit
+                        height: Size::Fit,
+                        ..Walk::default()
                     }, Layout::flow_down());
                     return Some(index + 1)
                 }
@@ -399,7 +412,8 @@ impl PortalList {
                                     abs_pos: Some(dvec2(viewport.pos.x, viewport.pos.y + total_height)),
                                     margin: Default::default(),
                                     width: Size::Fill,
-                                    height: Size::Fit
+                                    height: Size::Fit,
+                                    ..Walk::default()
                                 }, Layout::flow_down());
                                 return Some(last_index + 1);
                             }
@@ -424,7 +438,8 @@ impl PortalList {
                         abs_pos: Some(dvec2(viewport.pos.x, viewport.pos.y)),
                         margin: Default::default(),
                         width: Size::Fill,
-                        height: Size::Fit
+                        height: Size::Fit,
+                        ..Walk::default()
                     }, Layout::flow_down());
                     
                     return Some(index - 1);
@@ -9,7 +9,7 @@ live_design!{
 
     DrawRadioButton = {{DrawRadioButton}} {}
     RadioButtonBase = {{RadioButton}} {}
-    RadioButtonGroupBase = {{RadioButtonGroup }} {}
+    RadioButtonGroupBase = {{RadioButtonGroup }} {nav_scope: Arrows}
 }
 
 #[derive(Live, LiveHook, LiveRegister)]
@@ -53,6 +53,7 @@ pub struct RadioButton {
     #[live] label_walk: Walk,
     #[live] label_align: Align,
     #[live] label: String,
+    #[live] tab_index: i64,
     
     #[live] bind: String,
 }
@@ -64,31 +65,34 @@ pub enum RadioButtonAction {
 }
 
 
-impl RadioButtonGroup {
-    pub fn draw_walk(&mut self, _cx: &mut Cx2d, _walk: Walk) {}
-}
-
 impl RadioButton {
     pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk) {
         self.draw_radio.begin(cx, walk, self.layout);
         self.draw_icon.draw_walk(cx, self.icon_walk);
         self.draw_text.draw_walk(cx, self.label_walk, self.label_align, &self.label);
         self.draw_radio.end(cx);
+        let checked = self.animator_in_state(cx, id!(selected.on));
+        if let Some(order) = NavOrder::from_tab_index(self.tab_index) {
+            cx.push_nav_stop(NavStop::new(self.draw_radio.area(), NavRole::RadioButton).with_order(order).with_selected(checked));
+        }
+        cx.add_access_node(
+            AccessNode::new(self.widget_uid().0, AccessRole::RadioButton, self.draw_radio.area())
+                .with_name(self.label.as_str())
+                .with_states(AccessStates {checked: Some(checked), ..Default::default()})
+                .with_actions(AccessActions {click: true, ..Default::default()})
+        );
     }
 
 }
 
 impl Widget for RadioButtonGroup {
     
-    fn handle_event(&mut self, cx: &mut Cx, event: &Event, _scope: &mut Scope) {
-        //let uid = self.widget_uid();
-        self.animator_handle_event(cx, event);
-              
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.frame.handle_event(cx, event, scope);
     }
     
-    fn draw_walk(&mut self, cx: &mut Cx2d, _scope:&mut Scope, walk: Walk) -> DrawStep {
-        self.draw_walk(cx, walk);
-        DrawStep::done()
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope:&mut Scope, walk: Walk) -> DrawStep {
+        self.frame.draw_walk(cx, scope, walk)
     }
 }
 
@@ -107,7 +111,11 @@ impl Widget for RadioButton {
                 cx.set_cursor(MouseCursor::Arrow);
                 self.animator_play(cx, id!(hover.off));
             },
-            Hit::FingerDown(_fe) => {
+            // moving the focus into a radio button with the arrow keys also selects it
+            Hit::FingerDown(_) |
+            Hit::KeyFocus(_) |
+            Hit::KeyDown(KeyEvent {key_code: KeyCode::Space, ..}) |
+            Hit::AccessAction(AccessAction::Click) => {
                 if self.animator_in_state(cx, id!(selected.off)) {
                     self.animator_play(cx, id!(selected.on));
                     cx.widget_action(uid, &scope.path, RadioButtonAction::Clicked);
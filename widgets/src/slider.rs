@@ -105,6 +105,10 @@ impl Slider {
         self.text_input.redraw(cx);
     }
     
+    fn access_step(&self) -> f64 {
+        if self.step != 0.0 {self.step} else {(self.max - self.min) / 100.0}
+    }
+    
     pub fn draw_walk_slider(&mut self, cx: &mut Cx2d, walk: Walk) {
         self.draw_slider.slide_pos = self.value as f32;
         self.draw_slider.begin(cx, walk, self.layout);
@@ -113,10 +117,29 @@ impl Slider {
             //, (self.value*100.0) as usize);
             let walk = self.text_input.walk(cx);
             self.text_input.draw_walk_text_input(cx, walk);
-            self.draw_text.draw_walk(cx, dw.resolve(cx), self.label_align, &self.text);
+            let walk = dw.resolve(cx);
+            self.draw_text.draw_walk(cx, walk, self.label_align, &self.text);
         }
         
         self.draw_slider.end(cx);
+        cx.add_access_node(
+            AccessNode::new(self.widget_uid().0, AccessRole::Slider, self.draw_slider.area())
+                .with_name(self.text.as_str())
+                .with_value(AccessValue::Number {
+                    value: self.to_external(),
+                    min: self.min,
+                    max: self.max,
+                    step: self.access_step()
+                })
+                .with_states(AccessStates {focusable: true, ..Default::default()})
+                .with_actions(AccessActions {
+                    focus: true,
+                    increment: true,
+                    decrement: true,
+                    set_value: true,
+                    ..Default::default()
+                })
+        );
     }
 }
 
@@ -191,6 +214,24 @@ impl Widget for Slider {
                     cx.widget_action(uid, &scope.path, SliderAction::Slide(self.to_external()));
                 }
             }
+            Hit::AccessAction(action) => {
+                let value = match action {
+                    AccessAction::Increment => Some(self.to_external() + self.access_step()),
+                    AccessAction::Decrement => Some(self.to_external() - self.access_step()),
+                    AccessAction::SetValue(value) => value.parse::<f64>().ok(),
+                    AccessAction::Focus => {
+                        self.text_input.set_key_focus(cx);
+                        None
+                    }
+                    _ => None
+                };
+                if let Some(value) = value {
+                    self.set_internal(value.max(self.min).min(self.max));
+                    self.draw_slider.redraw(cx);
+                    self.update_text_input_and_redraw(cx);
+                    cx.widget_action(uid, &scope.path, SliderAction::Slide(self.to_external()));
+                }
+            }
             _ => ()
         }
     }
@@ -7,13 +7,39 @@ use {
 }; 
    
 live_design!{
+    DrawFlowBlock = {{DrawFlowBlock}} {}
+    
     TextFlowBase = {{TextFlow}} {
         // ok so we can use one drawtext
         // change to italic, change bold (SDF), strikethrough
         font_size: 8,
-        flow: RightWrap
+        flow: RightWrap,
+        paragraph_gap: 6.0,
+        list_item_indent: 16.0,
+        rule_height: 1.0,
+        quote_layout: {flow: RightWrap, padding: {left: 10.0, top: 4.0, right: 4.0, bottom: 4.0}}
+        code_layout: {flow: RightWrap, padding: 6.0}
+        table_cell_layout: {flow: RightWrap, padding: 4.0}
     }
 }
+
+/// The background of the blocks a `TextFlow` lays out, and of horizontal rules.
+#[derive(Live, LiveHook, LiveRegister)]
+#[repr(C)]
+pub struct DrawFlowBlock {
+    #[deref] draw_super: DrawQuad,
+    #[live] pub block_type: FlowBlockType,
+}
+
+#[derive(Live, LiveHook, LiveRegister)]
+#[live_ignore]
+#[repr(u32)]
+pub enum FlowBlockType {
+    #[pick] Quote = shader_enum(1),
+    Code = shader_enum(2),
+    Rule = shader_enum(3),
+    TableCell = shader_enum(4),
+}
    
 // this widget has a retained and an immediate mode api
 #[derive(Live, Widget)]
@@ -22,10 +48,23 @@ pub struct TextFlow {
     #[live] draw_italic: DrawText,
     #[live] draw_bold: DrawText,
     #[live] draw_bold_italic: DrawText,
+    #[live] draw_fixed: DrawText,
+    #[live] draw_block: DrawFlowBlock,
+    #[live] draw_image: DrawQuad,
     #[live] font_size: f64,
+    #[live] paragraph_gap: f64,
+    #[live] list_item_indent: f64,
+    #[live] rule_height: f64,
+    #[live] quote_layout: Layout,
+    #[live] code_layout: Layout,
+    #[live] table_cell_layout: Layout,
     #[walk] walk: Walk,
     #[rust] bold_counter: usize,
     #[rust] italic_counter: usize,
+    #[rust] fixed_counter: usize,
+    #[rust] color_stack: Vec<Vec4>,
+    #[rust] block_area_stack: Vec<Area>,
+    #[rust] table_stack: Vec<TableState>,
     #[rust] font_size_stack: FontSizeStack,
     #[layout] layout: Layout,
     #[redraw] #[rust] area:Area,
@@ -99,6 +138,12 @@ impl FontSizeStack{
     }
 }
 
+// the width of the rows of a table, and of each of its cells
+struct TableState {
+    width: f64,
+    cell_width: f64,
+}
+
 #[derive(Clone)]
 enum DrawState {
     Begin,
@@ -144,6 +189,16 @@ impl TextFlow{
         // well know if we have a known width to wrap
         // if we dont we just dont wrap
         cx.begin_turtle(walk, self.layout);
+        // block backgrounds have to be drawn before any of the text on top of them
+        self.draw_block.new_draw_call(cx);
+        self.draw_normal.new_draw_call(cx);
+        self.bold_counter = 0;
+        self.italic_counter = 0;
+        self.fixed_counter = 0;
+        self.font_size_stack = FontSizeStack::default();
+        self.color_stack.clear();
+        self.block_area_stack.clear();
+        self.table_stack.clear();
         self.draw_state.set(DrawState::Drawing);
     }
     
@@ -153,6 +208,170 @@ impl TextFlow{
         self.items.retain_visible();
     }
     
+    /// Moves to the start of the next line, unless the flow is at the start of a line already.
+    pub fn new_line(&mut self, cx: &mut Cx2d){
+        cx.turtle_new_line();
+    }
+    
+    /// Ends the current line, leaving an empty line if it has nothing on it.
+    pub fn draw_break(&mut self, cx: &mut Cx2d){
+        if self.at_line_start(cx){
+            let line_height = self.line_height();
+            self.move_down(cx, line_height);
+        }
+        else{
+            cx.turtle_new_line();
+        }
+    }
+    
+    /// Moves to the start of the next line and leaves the paragraph gap.
+    pub fn draw_gap(&mut self, cx: &mut Cx2d){
+        cx.turtle_new_line();
+        let gap = self.paragraph_gap;
+        self.move_down(cx, gap);
+    }
+    
+    fn at_line_start(&self, cx: &Cx2d)->bool{
+        cx.turtle().rel_pos_padded().x <= 0.0
+    }
+    
+    fn move_down(&mut self, cx: &mut Cx2d, dy: f64){
+        let turtle = cx.turtle_mut();
+        let pos = turtle.pos() + dvec2(0.0, dy);
+        let used = turtle.used();
+        let origin = turtle.origin();
+        turtle.set_pos(pos);
+        turtle.set_used(used.x, used.y.max(pos.y - origin.y));
+    }
+    
+    // what is left of the width of the current line, or NaN if the flow does not wrap
+    fn width_left(&self, cx: &Cx2d)->f64{
+        let turtle = cx.turtle();
+        turtle.padded_rect().size.x - turtle.rel_pos_padded().x
+    }
+    
+    fn line_height(&self)->f64{
+        let dt = &self.draw_normal;
+        self.font_size_stack.value(self.font_size) * dt.text_style.height_factor * dt.font_scale
+    }
+    
+    /// Begins a block with a background, such as a quote or a code block, that fills the rest of
+    /// the width on a line of its own.
+    pub fn begin_block(&mut self, cx: &mut Cx2d, block_type: FlowBlockType){
+        cx.turtle_new_line();
+        let width = self.width_left(cx);
+        self.begin_block_walk(cx, block_type, width);
+    }
+    
+    fn begin_block_walk(&mut self, cx: &mut Cx2d, block_type: FlowBlockType, width: f64){
+        let layout = match block_type{
+            FlowBlockType::Quote | FlowBlockType::Rule => self.quote_layout,
+            FlowBlockType::Code => self.code_layout,
+            FlowBlockType::TableCell => self.table_cell_layout,
+        };
+        let width = if width.is_nan(){Size::Fit}else{Size::Fixed(width)};
+        self.draw_block.block_type = block_type;
+        self.draw_block.begin(cx, Walk::size(width, Size::Fit), layout);
+        self.block_area_stack.push(self.draw_block.draw_vars.area);
+    }
+    
+    pub fn end_block(&mut self, cx: &mut Cx2d){
+        // nested blocks replace the area, so we restore the one of the block we end
+        if let Some(area) = self.block_area_stack.pop(){
+            self.draw_block.draw_vars.area = area;
+            self.draw_block.end(cx);
+            cx.turtle_new_line();
+        }
+    }
+    
+    /// Begins a list item, with the marker in the indent in front of it.
+    pub fn begin_list_item(&mut self, cx: &mut Cx2d, marker: &str){
+        cx.turtle_new_line();
+        let width = self.width_left(cx);
+        let indent = self.list_item_indent;
+        let (width, body_width) = if width.is_nan(){
+            (Size::Fit, Size::Fit)
+        }
+        else{
+            (Size::Fixed(width), Size::Fixed((width - indent).max(0.0)))
+        };
+        cx.begin_turtle(Walk::size(width, Size::Fit), Layout::flow_right());
+        cx.begin_turtle(Walk::size(Size::Fixed(indent), Size::Fit), Layout::flow_right());
+        self.draw_text(cx, marker);
+        cx.end_turtle();
+        cx.begin_turtle(Walk::size(body_width, Size::Fit), Layout{flow: Flow::RightWrap, ..Layout::default()});
+    }
+    
+    pub fn end_list_item(&mut self, cx: &mut Cx2d){
+        cx.end_turtle();
+        cx.end_turtle();
+        cx.turtle_new_line();
+    }
+    
+    /// Begins a table that splits the rest of the width evenly over its columns.
+    pub fn begin_table(&mut self, cx: &mut Cx2d, columns: usize){
+        cx.turtle_new_line();
+        let width = self.width_left(cx);
+        self.table_stack.push(TableState{
+            width,
+            cell_width: width / columns.max(1) as f64,
+        });
+    }
+    
+    pub fn end_table(&mut self, cx: &mut Cx2d){
+        self.table_stack.pop();
+        cx.turtle_new_line();
+    }
+    
+    pub fn begin_table_row(&mut self, cx: &mut Cx2d){
+        cx.turtle_new_line();
+        let width = self.table_stack.last().map(|table| table.width).unwrap_or(f64::NAN);
+        let width = if width.is_nan(){Size::Fit}else{Size::Fixed(width)};
+        cx.begin_turtle(Walk::size(width, Size::Fit), Layout::flow_right());
+    }
+    
+    pub fn end_table_row(&mut self, cx: &mut Cx2d){
+        cx.end_turtle();
+        cx.turtle_new_line();
+    }
+    
+    pub fn begin_table_cell(&mut self, cx: &mut Cx2d){
+        let width = self.table_stack.last().map(|table| table.cell_width).unwrap_or(f64::NAN);
+        self.begin_block_walk(cx, FlowBlockType::TableCell, width);
+    }
+    
+    pub fn end_table_cell(&mut self, cx: &mut Cx2d){
+        if let Some(area) = self.block_area_stack.pop(){
+            self.draw_block.draw_vars.area = area;
+            self.draw_block.end(cx);
+        }
+    }
+    
+    /// Draws a horizontal rule on a line of its own.
+    pub fn draw_rule(&mut self, cx: &mut Cx2d){
+        cx.turtle_new_line();
+        let width = self.width_left(cx);
+        if width.is_nan(){
+            return
+        }
+        self.draw_block.block_type = FlowBlockType::Rule;
+        self.draw_block.draw_walk(cx, Walk::size(Size::Fixed(width), Size::Fixed(self.rule_height)));
+        cx.turtle_new_line();
+    }
+    
+    /// Draws an image inline, scaled down to fit the width of the flow if needed.
+    pub fn draw_image(&mut self, cx: &mut Cx2d, texture: &Texture, width: f64, height: f64){
+        let max_width = cx.turtle().padded_rect().size.x;
+        let (width, height) = if max_width < width{
+            (max_width, height * max_width / width)
+        }
+        else{
+            (width, height)
+        };
+        self.draw_image.draw_vars.set_texture(0, texture);
+        self.draw_image.draw_walk(cx, Walk::size(Size::Fixed(width), Size::Fixed(height)));
+    }
+    
     pub fn push_bold(&mut self){
         self.bold_counter += 1;
     }
@@ -173,6 +392,24 @@ impl TextFlow{
         }
     }
     
+    pub fn push_fixed(&mut self){
+        self.fixed_counter += 1;
+    }
+    
+    pub fn pop_fixed(&mut self){
+        if self.fixed_counter>0{
+            self.fixed_counter -= 1;
+        }
+    }
+    
+    pub fn push_color(&mut self, color: Vec4){
+        self.color_stack.push(color);
+    }
+    
+    pub fn pop_color(&mut self){
+        self.color_stack.pop();
+    }
+    
     pub fn push_size(&mut self, size: f64){
         self.font_size_stack.push(size);
     }
@@ -188,6 +425,10 @@ impl TextFlow{
     }
     
     
+    pub fn has_template(&self, template: LiveId) -> bool {
+        self.templates.contains_key(&template)
+    }
+    
     pub fn item(&mut self, cx: &mut Cx, entry_id: u64, template: LiveId) -> Option<WidgetRef> {
         if let Some(ptr) = self.templates.get(&template) {
             let entry = self.items.get_or_insert(cx, (entry_id, template), | cx | {
@@ -200,7 +441,10 @@ impl TextFlow{
     
     pub fn draw_text(&mut self, cx:&mut Cx2d, text:&str){
         if let Some(DrawState::Drawing) = self.draw_state.get(){
-            let dt = if self.bold_counter > 0{
+            let dt = if self.fixed_counter > 0{
+                &mut self.draw_fixed
+            }
+            else if self.bold_counter > 0{
                 if self.italic_counter > 0{
                     &mut self.draw_bold_italic
                 }
@@ -219,7 +463,15 @@ impl TextFlow{
             let fs = self.font_size_stack.value(self.font_size);
             dt.text_style.font_size = fs;
             // the turtle is at pos X so we walk it.
-            dt.draw_walk_word(cx, text);
+            if let Some(color) = self.color_stack.last(){
+                let old_color = dt.color;
+                dt.color = *color;
+                dt.draw_walk_word(cx, text);
+                dt.color = old_color;
+            }
+            else{
+                dt.draw_walk_word(cx, text);
+            }
         }
     }
 }
@@ -1,7 +1,7 @@
 use {
     crate::{
         makepad_derive_widget::*,
-        makepad_draw::*,
+        makepad_draw::{*, bidi::{BidiText, BaseDirection}},
         widget::*,
     }
 };
@@ -60,6 +60,7 @@ pub struct TextInput {
     #[live] secret: bool,
     #[live] on_focus_select_all: bool,
     #[live] pub read_only: bool,
+    #[live] tab_index: i64,
     
     //#[live] label_walk: Walk,
     
@@ -121,6 +122,17 @@ impl Widget for TextInput {
                 }
                 if self.change(cx, &input){self.push_change_action(uid, scope, cx)}
             }
+            Hit::AccessAction(AccessAction::Focus) => {
+                self.set_key_focus(cx);
+            }
+            Hit::AccessAction(AccessAction::SetValue(value)) => {
+                let mut input = String::new();
+                self.filter_input(&value, Some(&mut input));
+                self.undo_id += 1;
+                self.create_external_undo();
+                self.select_all();
+                if self.change(cx, &input){self.push_change_action(uid, scope, cx)}
+            }
             Hit::TextCopy(ce) => {
                 self.undo_id += 1;
                 *ce.response.borrow_mut() = Some(self.selected_text());
@@ -168,9 +180,7 @@ impl Widget for TextInput {
                 KeyCode::ArrowLeft => if !ke.modifiers.logo {
                                         
                     self.undo_id += 1;
-                    if self.cursor_head>0 {
-                        self.cursor_head -= 1;
-                    }
+                    self.cursor_head = self.bidi_text().cursor_left(self.cursor_head);
                     if !ke.modifiers.shift {
                         self.cursor_tail = self.cursor_head;
                     }
@@ -178,9 +188,7 @@ impl Widget for TextInput {
                 },
                 KeyCode::ArrowRight => if !ke.modifiers.logo {
                     self.undo_id += 1;
-                    if self.cursor_head < self.text.chars().count() {
-                        self.cursor_head += 1;
-                    }
+                    self.cursor_head = self.bidi_text().cursor_right(self.cursor_head);
                     if !ke.modifiers.shift {
                         self.cursor_tail = self.cursor_head;
                     }
@@ -189,8 +197,9 @@ impl Widget for TextInput {
                 KeyCode::ArrowDown => if !ke.modifiers.logo {
                     self.undo_id += 1;
                     // we need to figure out what is below our current cursor
-                    if let Some(pos) = self.draw_text.get_cursor_pos(cx, 0.0, self.cursor_head) {
-                        if let Some(pos) = self.draw_text.closest_offset(cx, dvec2(pos.x, pos.y + self.draw_text.get_line_spacing() * 1.5)) {
+                    let bidi = self.bidi_text();
+                    if let Some(pos) = self.cursor_pos(cx, &bidi, 0.0, self.cursor_head) {
+                        if let Some(pos) = self.closest_cursor(cx, &bidi, dvec2(pos.x, pos.y + self.draw_text.get_line_spacing() * 1.5)) {
                             self.cursor_head = pos;
                             if !ke.modifiers.shift {
                                 self.cursor_tail = self.cursor_head;
@@ -202,8 +211,9 @@ impl Widget for TextInput {
                 KeyCode::ArrowUp => if !ke.modifiers.logo {
                     self.undo_id += 1;
                     // we need to figure out what is below our current cursor
-                    if let Some(pos) = self.draw_text.get_cursor_pos(cx, 0.0, self.cursor_head) {
-                        if let Some(pos) = self.draw_text.closest_offset(cx, dvec2(pos.x, pos.y - self.draw_text.get_line_spacing() * 0.5)) {
+                    let bidi = self.bidi_text();
+                    if let Some(pos) = self.cursor_pos(cx, &bidi, 0.0, self.cursor_head) {
+                        if let Some(pos) = self.closest_cursor(cx, &bidi, dvec2(pos.x, pos.y - self.draw_text.get_line_spacing() * 0.5)) {
                             self.cursor_head = pos;
                             if !ke.modifiers.shift {
                                 self.cursor_tail = self.cursor_head;
@@ -261,9 +271,8 @@ impl Widget for TextInput {
                 self.set_key_focus(cx);
                 // ok so we need to calculate where we put the cursor down.
                 //elf.
-                if let Some(pos) = self.draw_text.closest_offset(cx, fe.abs) {
+                if let Some(pos) = self.closest_cursor(cx, &self.bidi_text(), fe.abs) {
                     //log!("{} {}", pos, fe.abs);
-                    let pos = pos.min(self.text.chars().count());
                     if fe.tap_count == 1 {
                         if pos != self.cursor_head {
                             self.cursor_head = pos;
@@ -286,8 +295,7 @@ impl Widget for TextInput {
             },
             Hit::FingerUp(fe) => {
                 self.double_tap_start = None;
-                if let Some(pos) = self.draw_text.closest_offset(cx, fe.abs) {
-                    let pos = pos.min(self.text.chars().count());
+                if let Some(pos) = self.closest_cursor(cx, &self.bidi_text(), fe.abs) {
                     if !fe.modifiers.shift && fe.tap_count == 1 && fe.was_tap() {
                         self.cursor_head = pos;
                         self.cursor_tail = self.cursor_head;
@@ -305,8 +313,8 @@ impl Widget for TextInput {
                 }
             }
             Hit::FingerMove(fe) => {
-                if let Some(pos) = self.draw_text.closest_offset(cx, fe.abs) {
-                    let pos = pos.min(self.text.chars().count());
+                let bidi = self.bidi_text();
+                if let Some(pos) = self.closest_cursor(cx, &bidi, fe.abs) {
                     if fe.tap_count == 2 {
                         let (head, tail) = self.double_tap_start.unwrap();
                         // ok so. now we do a word select and merge the selection
@@ -320,9 +328,7 @@ impl Widget for TextInput {
                         self.draw_bg.redraw(cx);
                     }
                     else if fe.tap_count == 1 {
-                        if let Some(pos_start) = self.draw_text.closest_offset(cx, fe.abs_start) {
-                            let pos_start = pos_start.min(self.text.chars().count());
-                                                        
+                        if let Some(pos_start) = self.closest_cursor(cx, &bidi, fe.abs_start) {
                             self.cursor_head = pos_start;
                             self.cursor_tail = self.cursor_head;
                         }
@@ -339,6 +345,18 @@ impl Widget for TextInput {
     
     fn draw_walk(&mut self, cx: &mut Cx2d, _scope:&mut Scope, walk: Walk) -> DrawStep {
         self.draw_walk_text_input(cx, walk);
+        cx.add_access_node(
+            AccessNode::new(self.widget_uid().0, AccessRole::TextInput, self.draw_bg.area())
+                .with_name(self.empty_message.as_str())
+                .with_value(AccessValue::Text(self.text.clone()))
+                .with_states(AccessStates {
+                    editable: !self.read_only,
+                    password: self.secret,
+                    focusable: true,
+                    ..Default::default()
+                })
+                .with_actions(AccessActions {focus: true, set_value: !self.read_only, ..Default::default()})
+        );
         DrawStep::done()
     }
     
@@ -364,6 +382,25 @@ pub enum TextInputAction {
 
 impl TextInput {
     
+    // the text as it is drawn, to move the cursor and select in visual order
+    fn bidi_text(&self) -> BidiText {
+        if self.secret {
+            BidiText::new(&"*".repeat(self.text.chars().count()), BaseDirection::Auto)
+        }
+        else {
+            BidiText::new(&self.text, BaseDirection::Auto)
+        }
+    }
+    
+    fn cursor_pos(&self, cx: &Cx, bidi: &BidiText, pos: f32, cursor: usize) -> Option<DVec2> {
+        self.draw_text.get_cursor_pos(cx, pos, bidi.visual_cursor(cursor.min(bidi.char_count())))
+    }
+    
+    fn closest_cursor(&self, cx: &Cx, bidi: &BidiText, pos: DVec2) -> Option<usize> {
+        let offset = self.draw_text.closest_offset(cx, pos)?;
+        Some(bidi.logical_cursor(offset.min(bidi.char_count())))
+    }
+    
     pub fn sorted_cursor(&self) -> (usize, usize) {
         if self.cursor_head < self.cursor_tail {
             (self.cursor_head, self.cursor_tail)
@@ -586,7 +623,8 @@ impl TextInput {
         // move the IME
         let line_spacing = self.draw_text.get_line_spacing();
         let top_drop = self.draw_text.get_font_size() * 0.2;
-        let head = self.draw_text.get_cursor_pos(cx, 0.0, self.cursor_head)
+        let bidi = self.bidi_text();
+        let head = self.cursor_pos(cx, &bidi, 0.0, self.cursor_head)
             .unwrap_or(dvec2(turtle.pos.x, 0.0));
         
         if !self.read_only && self.cursor_head == self.cursor_tail {
@@ -603,16 +641,19 @@ impl TextInput {
             let bottom_drop = self.draw_text.get_font_size() * 0.1;
             
             let (start, end) = self.sorted_cursor();
-            let rects = self.draw_text.get_selection_rects(cx, start, end, dvec2(0.0, -top_drop), dvec2(0.0, bottom_drop));
-            for rect in rects {
-                self.draw_select.draw_abs(cx, rect);
+            // a selection in mixed direction text can be in several places on screen
+            for range in bidi.visual_ranges(start..end.min(bidi.char_count())) {
+                let rects = self.draw_text.get_selection_rects(cx, range.start, range.end, dvec2(0.0, -top_drop), dvec2(0.0, bottom_drop));
+                for rect in rects {
+                    self.draw_select.draw_abs(cx, rect);
+                }
             }
         }
         self.draw_bg.end(cx);
         
         if  cx.has_key_focus(self.draw_bg.area()) {
             // ok so. if we have the IME we should inject a tracking point
-            let ime_x = self.draw_text.get_cursor_pos(cx, 0.5, self.cursor_head)
+            let ime_x = self.cursor_pos(cx, &bidi, 0.5, self.cursor_head)
                 .unwrap_or(dvec2(turtle.pos.x, 0.0)).x;
             
             if self.numeric_only {
@@ -624,7 +665,9 @@ impl TextInput {
             }
         }
         
-        cx.add_nav_stop(self.draw_bg.area(), NavRole::TextInput, Margin::default())
+        if let Some(order) = NavOrder::from_tab_index(self.tab_index) {
+            cx.push_nav_stop(NavStop::new(self.draw_bg.area(), NavRole::TextInput).with_order(order));
+        }
     }
 }
 
@@ -636,4 +679,10 @@ impl TextInputRef {
         None
     }
     
+    pub fn returned(&self, actions: &Actions) -> Option<String> {
+        if let TextInputAction::Return(val) = actions.find_widget_action_cast(self.widget_uid()) {
+            return Some(val);
+        }
+        None
+    }
 }
@@ -3,6 +3,7 @@ use crate::makepad_platform::*;
 live_design! {
     import makepad_draw::shader::std::*;
     import crate::base::*;
+    import crate::text_flow::DrawFlowBlock;
 
     THEME_FONT_LABEL = {
         font_size: 9.4,
@@ -51,6 +52,9 @@ live_design! {
         font: {
             path: dep("crate://self/resources/LiberationMono-Regular.ttf")
         }
+        fallback_fonts: [
+            {path: dep("crate://self/resources/IBMPlexSans-Text.ttf")}
+        ]
         brightness: 1.1
         font_size: 9.0
         line_spacing: 2.0
@@ -162,11 +166,71 @@ live_design! {
     const THEME_SPLITTER_MAX_VERTICAL = (THEME_SPLITTER_HORIZONTAL + THEME_SPLITTER_SIZE),
     const THEME_SPLITTER_SIZE = 5.0
  
+    TextFlowBlock = <DrawFlowBlock>{
+        fn pixel(self) -> vec4 {
+            let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+            match self.block_type {
+                FlowBlockType::Quote => {
+                    sdf.box(0., 0., self.rect_size.x, self.rect_size.y, 2.);
+                    sdf.fill(THEME_COLOR_BG_HEADER);
+                    sdf.box(0., 0., 3., self.rect_size.y, 1.);
+                    sdf.fill(THEME_COLOR_TEXT_META);
+                }
+                FlowBlockType::Code => {
+                    sdf.box(0., 0., self.rect_size.x, self.rect_size.y, 2.);
+                    sdf.fill(THEME_COLOR_BG_EDITOR);
+                }
+                FlowBlockType::Rule => {
+                    sdf.rect(0., 0., self.rect_size.x, self.rect_size.y);
+                    sdf.fill(THEME_COLOR_TEXT_META);
+                }
+                FlowBlockType::TableCell => {
+                    sdf.rect(0., 0., self.rect_size.x, self.rect_size.y);
+                    sdf.stroke(THEME_COLOR_TEXT_META, 0.5);
+                }
+            }
+            return sdf.result
+        }
+    }
+
     Html = <HtmlBase>{
         draw_normal: {text_style:<THEME_FONT_LABEL>{}}
         draw_italic: {text_style:<THEME_FONT_ITALIC>{}}
         draw_bold: {text_style:<THEME_FONT_BOLD>{}}
         draw_bold_italic: {text_style:<THEME_FONT_BOLD_ITALIC>{}}
+        draw_fixed: {text_style:<THEME_FONT_CODE>{}}
+        link_color: #x9CDCFE
+        draw_block: <TextFlowBlock>{}
+        draw_image: {
+            texture image: texture2d
+            fn pixel(self) -> vec4 {
+                let color = sample2d(self.image, self.pos).xyzw;
+                return Pal::premul(color)
+            }
+        }
+    }
+
+    Markdown = <MarkdownBase>{
+        draw_normal: {text_style:<THEME_FONT_LABEL>{}}
+        draw_italic: {text_style:<THEME_FONT_ITALIC>{}}
+        draw_bold: {text_style:<THEME_FONT_BOLD>{}}
+        draw_bold_italic: {text_style:<THEME_FONT_BOLD_ITALIC>{}}
+        draw_fixed: {text_style:<THEME_FONT_CODE>{}}
+        link_color: #x9CDCFE
+        draw_block: <TextFlowBlock>{}
+        code_colors: {
+            plain: #D4D4D4,
+            branch_keyword: #C485BE,
+            loop_keyword: #FF8C00,
+            other_keyword: #5B9BD3,
+            comment: #638D54,
+            constant: #CC917B,
+            number: #B6CEAA,
+            string: #CC917B,
+            function: #fffcc9,
+            typename: #56C9B1,
+            punctuator: #D4D4D4,
+        }
     }
 
     ScrollBar = <ScrollBarBase> {
@@ -689,7 +753,10 @@ live_design! {
     NavControl = <NavControlBase> {
         draw_focus: {
             fn pixel(self) -> vec4 {
-                return #000f
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                sdf.box(1., 1., self.rect_size.x - 2.0, self.rect_size.y - 2.0, 3.0);
+                sdf.stroke(THEME_COLOR_TEXT_SELECTED, 1.5);
+                return sdf.result
             }
         }
         draw_text: {
@@ -705,95 +772,6 @@ live_design! {
         width: 0
     }
 
-    Window = <WindowBase> {
-        pass: {clear_color: (THEME_COLOR_CLEAR)}
-        flow: Down
-        nav_control: <NavControl> {}
-        caption_bar = <SolidView> {
-            visible: false,
-
-            flow: Right
-
-            draw_bg: {color: (THEME_COLOR_BG_APP)}
-            height: 27
-            caption_label = <View> {
-                width: Fill,
-                height: Fill
-                align: {x: 0.5, y: 0.5},
-                label = <Label> {text: "Makepad", margin: {left: 100}}
-            }
-            windows_buttons = <View> {
-                visible: false,
-                width: Fit,
-                height: Fit
-                min = <DesktopButton> {draw_bg: {button_type: WindowsMin}}
-                max = <DesktopButton> {draw_bg: {button_type: WindowsMax}}
-                close = <DesktopButton> {draw_bg: {button_type: WindowsClose}}
-            }
-            web_fullscreen = <View> {
-                visible: false,
-                width: Fit,
-                height: Fit
-                fullscreen = <DesktopButton> {draw_bg: {button_type: Fullscreen}}
-            }
-            web_xr = <View> {
-                visible: false,
-                width: Fit,
-                height: Fit
-                xr_on = <DesktopButton> {draw_bg: {button_type: XRMode}}
-            }
-        }
-        
-        window_menu = <WindowMenu>{
-            main = Main{items:[app]}
-            app = Sub{name:"Makepad",items:[quit]}
-            quit = Item{
-                name:"Quit",
-                shift: false,
-                key: KeyQ,
-                enabled: true
-            }
-        }
-        body = <KeyboardView>{
-            keyboard_min_shift: 30,
-            width: Fill,
-            height: Fill
-        }
-
-        cursor: Default
-        mouse_cursor_size: vec2(20, 20),
-        draw_cursor: {
-            instance border_width: 1.5
-            instance color: #000
-            instance border_color: #fff
-
-            fn get_color(self) -> vec4 {
-                return self.color
-            }
-
-            fn get_border_color(self) -> vec4 {
-                return self.border_color
-            }
-
-            fn pixel(self) -> vec4 {
-                let sdf = Sdf2d::viewport(self.pos * self.rect_size)
-                sdf.move_to(1.0, 1.0);
-                sdf.line_to(self.rect_size.x - 1.0, self.rect_size.y * 0.5)
-                sdf.line_to(self.rect_size.x * 0.5, self.rect_size.y - 1.0)
-                sdf.close_path();
-                sdf.fill_keep(self.get_color())
-                if self.border_width > 0.0 {
-                    sdf.stroke(self.get_border_color(), self.border_width)
-                }
-                return sdf.result
-            }
-        }
-        window: {
-            inner_size: vec2(1024, 768)
-        }
-    }
-
-
     // Dock
 
 
@@ -1924,6 +1902,11 @@ live_design! {
             }
         }
     }
+
+    RadioButtonGroup = <RadioButtonGroupBase> {
+        width: Fit,
+        height: Fit
+    }
     
     
 
@@ -1943,6 +1926,81 @@ live_design! {
         flow: Down
     }
 
+    DataTableColumn = <DataTableColumnBase> {}
+
+    DataTable = <DataTableBase> {
+        width: Fill
+        height: Fill
+        row_height: (THEME_DATA_ITEM_HEIGHT)
+        header_height: (THEME_DATA_ITEM_HEIGHT + 3.0)
+        cell_padding: {left: 6.0, right: 6.0}
+        scroll_bar_x: <ScrollBar> {}
+        scroll_bar_y: <ScrollBar> {}
+
+        draw_bg: {color: (THEME_COLOR_BG_EDITOR)}
+
+        draw_row: {
+            fn pixel(self) -> vec4 {
+                return mix(
+                    mix(
+                        mix(THEME_COLOR_BG_EDITOR, THEME_COLOR_BG_ODD, self.is_odd),
+                        THEME_COLOR_CONTROL_HOVER,
+                        self.hover * 0.25
+                    ),
+                    mix(THEME_COLOR_BG_UNFOCUSSED, THEME_COLOR_BG_SELECTED, self.focus),
+                    self.selected
+                );
+            }
+        }
+
+        draw_header: {
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                sdf.rect(0., 0., self.rect_size.x, self.rect_size.y);
+                sdf.fill(mix(
+                    mix(THEME_COLOR_BG_HEADER, THEME_COLOR_CONTROL_HOVER, self.hover),
+                    THEME_COLOR_CONTROL_PRESSED,
+                    self.pressed
+                ));
+                // the separator on the right doubles as the resize handle
+                sdf.rect(self.rect_size.x - 1., 3., 1., self.rect_size.y - 6.);
+                sdf.fill(THEME_COLOR_UP_10);
+                return sdf.result;
+            }
+        }
+
+        draw_sort: {
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                let w = self.rect_size.x;
+                let h = self.rect_size.y;
+                let top = mix(0.2 * h, 0.8 * h, self.descending);
+                let bottom = mix(0.8 * h, 0.2 * h, self.descending);
+                sdf.move_to(0., bottom);
+                sdf.line_to(0.5 * w, top);
+                sdf.line_to(w, bottom);
+                sdf.close_path();
+                return sdf.fill(THEME_COLOR_TEXT_DEFAULT);
+            }
+        }
+
+        draw_text: {
+            wrap: Ellipsis
+            text_style: <THEME_FONT_DATA> {}
+            fn get_color(self) -> vec4 {
+                return mix(THEME_COLOR_TEXT_DEFAULT, THEME_COLOR_TEXT_SELECTED, self.selected);
+            }
+        }
+
+        draw_header_text: {
+            wrap: Ellipsis
+            text_style: <THEME_FONT_BOLD> {}
+            color: (THEME_COLOR_TEXT_DEFAULT)
+        }
+
+        draw_drop: {color: (THEME_COLOR_DRAG_QUAD)}
+    }
+
     CachedScrollXY = <CachedView> {
         scroll_bars: <ScrollBars> {show_scroll_x: true, show_scroll_y: true}
     }
@@ -2110,6 +2168,141 @@ live_design! {
         }
     }
 
+    FileDialogView = <FileDialogViewBase> {
+        width: Fill,
+        height: Fill,
+        align: {x: 0.5, y: 0.5},
+        show_bg: true,
+        draw_bg: {color: #0008}
+        dialog = <RoundedView> {
+            width: 600,
+            height: 440,
+            flow: Down,
+            spacing: 8,
+            padding: 12,
+            draw_bg: {color: (THEME_COLOR_BG_APP), radius: 4.0}
+            title = <Label> {
+                draw_text: {text_style: <THEME_FONT_BOLD> {}}
+            }
+            location_bar = <View> {
+                width: Fill,
+                height: Fit,
+                spacing: 8,
+                align: {y: 0.5}
+                up = <Button> {text: "Up"}
+                location = <Label> {width: Fill}
+            }
+            file_tree = <FileTree> {}
+            name_bar = <View> {
+                width: Fill,
+                height: Fit,
+                spacing: 8,
+                align: {y: 0.5}
+                <Label> {text: "Name"}
+                name = <TextInput> {width: Fill, empty_message: "File name"}
+            }
+            button_bar = <View> {
+                width: Fill,
+                height: Fit,
+                spacing: 8,
+                align: {y: 0.5}
+                filters = <Label> {width: Fill}
+                cancel = <Button> {text: "Cancel"}
+                accept = <Button> {text: "Open"}
+            }
+        }
+    }
+
+    Window = <WindowBase> {
+        pass: {clear_color: (THEME_COLOR_CLEAR)}
+        flow: Down
+        nav_control: <NavControl> {}
+        file_dialog: <FileDialogView> {}
+        caption_bar = <SolidView> {
+            visible: false,
+
+            flow: Right
+
+            draw_bg: {color: (THEME_COLOR_BG_APP)}
+            height: 27
+            caption_label = <View> {
+                width: Fill,
+                height: Fill
+                align: {x: 0.5, y: 0.5},
+                label = <Label> {text: "Makepad", margin: {left: 100}}
+            }
+            windows_buttons = <View> {
+                visible: false,
+                width: Fit,
+                height: Fit
+                min = <DesktopButton> {draw_bg: {button_type: WindowsMin}}
+                max = <DesktopButton> {draw_bg: {button_type: WindowsMax}}
+                close = <DesktopButton> {draw_bg: {button_type: WindowsClose}}
+            }
+            web_fullscreen = <View> {
+                visible: false,
+                width: Fit,
+                height: Fit
+                fullscreen = <DesktopButton> {draw_bg: {button_type: Fullscreen}}
+            }
+            web_xr = <View> {
+                visible: false,
+                width: Fit,
+                height: Fit
+                xr_on = <DesktopButton> {draw_bg: {button_type: XRMode}}
+            }
+        }
+        
+        window_menu = <WindowMenu>{
+            main = Main{items:[app]}
+            app = Sub{name:"Makepad",items:[quit]}
+            quit = Item{
+                name:"Quit",
+                shift: false,
+                key: KeyQ,
+                enabled: true
+            }
+        }
+        body = <KeyboardView>{
+            keyboard_min_shift: 30,
+            width: Fill,
+            height: Fill
+        }
+
+        cursor: Default
+        mouse_cursor_size: vec2(20, 20),
+        draw_cursor: {
+            instance border_width: 1.5
+            instance color: #000
+            instance border_color: #fff
+
+            fn get_color(self) -> vec4 {
+                return self.color
+            }
+
+            fn get_border_color(self) -> vec4 {
+                return self.border_color
+            }
+
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size)
+                sdf.move_to(1.0, 1.0);
+                sdf.line_to(self.rect_size.x - 1.0, self.rect_size.y * 0.5)
+                sdf.line_to(self.rect_size.x * 0.5, self.rect_size.y - 1.0)
+                sdf.close_path();
+                sdf.fill_keep(self.get_color())
+                if self.border_width > 0.0 {
+                    sdf.stroke(self.get_border_color(), self.border_width)
+                }
+                return sdf.result
+            }
+        }
+        window: {
+            inner_size: vec2(1024, 768)
+        }
+    }
+
+
     Slider = <SliderBase> {
         min: 0.0,
         max: 1.0,
@@ -127,6 +127,15 @@ pub struct View {
     #[walk]
     walk: Walk,
 
+    /// The spans and alignment of this view when its parent has `flow: Grid`.
+    #[live]
+    grid_cell: GridCell,
+
+    /// Keeps Tab inside this view (`Focus`, `Modal`) or moves between its children with the
+    /// arrow keys (`Arrows`).
+    #[live]
+    nav_scope: NavScopeKind,
+
     //#[live] use_cache: bool,
     #[live]
     dpi_factor: Option<f64>,
@@ -671,6 +680,7 @@ impl Widget for View {
             }
 
             self.defer_walks.clear();
+            cx.set_grid_cell(self.grid_cell);
 
             match self.optimize {
                 ViewOptimize::Texture => {
@@ -749,6 +759,10 @@ impl Widget for View {
                 _ => (),
             }
 
+            if self.nav_scope != NavScopeKind::None {
+                cx.begin_nav_scope(self.nav_scope);
+            }
+
             // ok so.. we have to keep calling draw till we return LiveId(0)
             let scroll = if let Some(scroll_bars) = &mut self.scroll_bars_obj {
                 scroll_bars.begin_nav_area(cx);
@@ -774,13 +788,25 @@ impl Widget for View {
                 if let Some(child) = self.children.get_mut(&id) {
                     if child.is_visible() {
                         let walk = child.walk(cx);
+                        // a grid needs the cell of a child to know if it has to defer it
+                        if !resume {
+                            if let Some(view) = child.borrow::<View>() {
+                                cx.set_grid_cell(view.grid_cell);
+                            }
+                        }
                         if resume {
-                            scope.with_id(id, |scope| child.draw_walk(cx, scope, walk))?;
+                            scope.with_id(id, |scope| {
+                                profile_scope!(cx, "{}", id);
+                                child.draw_walk(cx, scope, walk)
+                            })?;
                         } else if let Some(fw) = cx.defer_walk(walk) {
                             self.defer_walks.push((id, fw));
                         } else {
                             self.draw_state.set(DrawState::Drawing(step, true));
-                            scope.with_id(id, |scope| child.draw_walk(cx, scope, walk))?;
+                            scope.with_id(id, |scope| {
+                                profile_scope!(cx, "{}", id);
+                                child.draw_walk(cx, scope, walk)
+                            })?;
                         }
                     }
                 }
@@ -795,7 +821,10 @@ impl Widget for View {
                 let (id, dw) = &mut self.defer_walks[step];
                 if let Some(child) = self.children.get_mut(&id) {
                     let walk = dw.resolve(cx);
-                    scope.with_id(*id, |scope| child.draw_walk(cx, scope, walk))?;
+                    scope.with_id(*id, |scope| {
+                        profile_scope!(cx, "{}", id);
+                        child.draw_walk(cx, scope, walk)
+                    })?;
                 }
                 self.draw_state.set(DrawState::DeferWalk(step + 1));
             } else {
@@ -818,6 +847,10 @@ impl Widget for View {
                     scroll_bars.end_nav_area(cx);
                 };
 
+                if self.nav_scope != NavScopeKind::None {
+                    cx.end_nav_scope();
+                }
+
                 if self.optimize.needs_draw_list() {
                     let rect = self.area.rect(cx);
                     self.view_size = Some(rect.size);
@@ -932,7 +965,7 @@ impl View {
             } else {
                 Size::Fixed(view_size.y)
             },
-            margin: walk.margin,
+            ..walk
         }
     }
 
@@ -3,7 +3,9 @@ use crate::{
     debug_view::DebugView,
     performance_view::PerformanceView,
     makepad_draw::*,
+    makepad_draw::atspi::AtspiNode,
     nav_control::NavControl,
+    file_dialog::FileDialogView,
     button::*,
     view::*,
     widget::*,
@@ -25,6 +27,8 @@ pub struct Window {
     #[live] debug_view: DebugView,
     #[live] performance_view: PerformanceView,
     #[live] nav_control: NavControl,
+    #[live] file_dialog: FileDialogView,
+    #[live] file_dialog_draw_list: DrawList2d,
     #[live] window: WindowHandle,
     #[live] stdin_size: DrawColor,
     #[live] overlay: Overlay,
@@ -146,6 +150,18 @@ impl Window {
             self.cursor_draw_list.end(cx);
         }
         
+        if self.file_dialog.is_open() {
+            cx.begin_nav_scope(NavScopeKind::Modal);
+            self.file_dialog_draw_list.begin_overlay_reuse(cx);
+            cx.begin_pass_sized_turtle(Layout::flow_down());
+            let _ = self.file_dialog.draw(cx, &mut Scope::empty());
+            cx.end_pass_sized_turtle();
+            self.file_dialog_draw_list.end(cx);
+            cx.end_nav_scope();
+        }
+        
+        self.nav_control.draw(cx, self.main_draw_list.draw_list_id());
+        
         self.overlay.end(cx);
         // lets get te pass size
         fn encode_size(x: f64)->Vec4{
@@ -174,6 +190,21 @@ impl Window {
         self.main_draw_list.end(cx);
         cx.end_pass(&self.pass);
     }
+    
+    /// The accessibility tree of everything drawn in this window, as of its last draw.
+    pub fn access_tree(&self, cx: &mut Cx) -> AccessTree {
+        let window = &cx.windows[self.window.window_id()];
+        let root = AccessNode::new(self.widget_uid().0, AccessRole::Window, Area::Empty)
+            .with_name(window.create_title.as_str());
+        let rect = Rect {pos: DVec2::default(), size: window.window_geom.inner_size};
+        Cx2d::access_tree(cx, self.main_draw_list.draw_list_id(), root, rect)
+    }
+    
+    /// The accessibility tree mapped onto AT-SPI nodes. These are not served on the bus yet.
+    pub fn atspi_nodes(&self, cx: &mut Cx) -> Vec<AtspiNode> {
+        let geom = cx.windows[self.window.window_id()].window_geom.clone();
+        self.access_tree(cx).to_atspi(geom.position, geom.dpi_factor)
+    }
 }
 
 impl Widget for Window {
@@ -255,9 +286,26 @@ impl Widget for Window {
             cx.widget_action(uid, &scope.path, WindowAction::EventForOtherWindow);
             return
         }
-        else {
-            self.view.handle_event(cx, event, scope);
+        
+        if let Event::FileDialogRequest(request) = event {
+            if !request.handled.get() && !self.file_dialog.is_open() {
+                request.handled.set(true);
+                self.file_dialog.open(cx, request.kind, request.dialog.clone());
+                self.main_draw_list.redraw(cx);
+            }
         }
+        if self.file_dialog.is_open() {
+            self.file_dialog.handle_event(cx, event, &mut Scope::empty());
+            if !self.file_dialog.is_open() {
+                self.main_draw_list.redraw(cx);
+            }
+            // the dialog is modal, so the input it gets is not for the content below
+            if let Event::MouseDown(_) | Event::MouseMove(_) | Event::MouseUp(_) | Event::Scroll(_) |
+                Event::TouchUpdate(_) | Event::KeyDown(_) | Event::KeyUp(_) | Event::TextInput(_) = event {
+                return
+            }
+        }
+        self.view.handle_event(cx, event, scope);
         
         if let Event::Actions(actions) = event{
             if self.button(id!(min)).clicked(&actions) {
@@ -313,3 +361,15 @@ impl Widget for Window {
         DrawStep::done()
     }
 }
+
+impl WindowRef {
+    pub fn access_tree(&self, cx: &mut Cx) -> AccessTree {
+        let Some(inner) = self.borrow() else {return AccessTree::default()};
+        inner.access_tree(cx)
+    }
+    
+    pub fn atspi_nodes(&self, cx: &mut Cx) -> Vec<AtspiNode> {
+        let Some(inner) = self.borrow() else {return Vec::new()};
+        inner.atspi_nodes(cx)
+    }
+}